@@ -0,0 +1,151 @@
+/* Tydi C interface, enabled by the `ffi` feature. */
+
+#ifndef TYDI_H
+#define TYDI_H
+
+/* Generated with cbindgen from src/ffi.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// A collection of Streamlets.
+typedef struct TydiLibrary TydiLibrary;
+
+// A collection of Streamlets.
+typedef struct TydiProject TydiProject;
+
+// Streamlet interface definition.
+typedef struct TydiStreamlet TydiStreamlet;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Returns the message of the last error on this thread, or null if no
+// error occurred. The message is owned by this library, and valid until
+// the next error on this thread.
+const char *tydi_last_error(void);
+
+// Free a string returned by this interface. Does nothing if the string is
+// null.
+//
+// # Safety
+//
+// The string must be returned by this interface, and not be freed before.
+void tydi_string_free(char *string);
+
+// Construct an empty project. Returns null if the name is invalid.
+//
+// # Safety
+//
+// The name must be null or a null-terminated string.
+struct TydiProject *tydi_project_new(const char *name);
+
+// Free a project. Does nothing if the project is null.
+//
+// # Safety
+//
+// The project must be returned by [tydi_project_new], and not be freed
+// before.
+void tydi_project_free(struct TydiProject *project);
+
+// Parse a library from the contents of a Streamlet Definition File, and
+// add it to a project. The library may refer to the named types of the
+// libraries of the project it imports.
+//
+// Returns 0 on success, or -1 if the contents are invalid or the project
+// already contains a library with the name.
+//
+// # Safety
+//
+// The project must be a valid handle, the name and contents must be null
+// or null-terminated strings.
+int tydi_project_add_library(struct TydiProject *project, const char *name, const char *sdf);
+
+// Returns the number of libraries of a project, or 0 if it is null.
+//
+// # Safety
+//
+// The project must be null or a valid handle.
+uintptr_t tydi_project_library_count(const struct TydiProject *project);
+
+// Returns the library of a project at an index, in the order they were
+// added, or null if the index is out of range.
+//
+// # Safety
+//
+// The project must be null or a valid handle.
+const struct TydiLibrary *tydi_project_library(const struct TydiProject *project, uintptr_t index);
+
+// Returns the name of a library, or null if it is null.
+//
+// # Safety
+//
+// The library must be null or a valid handle.
+char *tydi_library_name(const struct TydiLibrary *library);
+
+// Returns a library in Streamlet Definition File syntax, or null if it is
+// null.
+//
+// # Safety
+//
+// The library must be null or a valid handle.
+char *tydi_library_to_sdf(const struct TydiLibrary *library);
+
+// Returns the number of streamlets of a library, or 0 if it is null.
+//
+// # Safety
+//
+// The library must be null or a valid handle.
+uintptr_t tydi_library_streamlet_count(const struct TydiLibrary *library);
+
+// Returns the streamlet of a library at an index, in order of declaration,
+// or null if the index is out of range.
+//
+// # Safety
+//
+// The library must be null or a valid handle.
+const struct TydiStreamlet *tydi_library_streamlet(const struct TydiLibrary *library,
+                                                   uintptr_t index);
+
+// Returns the name of a streamlet, or null if it is null.
+//
+// # Safety
+//
+// The streamlet must be null or a valid handle.
+char *tydi_streamlet_name(const struct TydiStreamlet *streamlet);
+
+// Returns the total number of bits of all interfaces of a streamlet, or 0
+// if it is null.
+//
+// # Safety
+//
+// The streamlet must be null or a valid handle.
+uint64_t tydi_streamlet_bit_count(const struct TydiStreamlet *streamlet);
+
+// Returns a human-readable report of the interfaces of a streamlet and the
+// physical streams they are synthesized into, or null if it is null.
+//
+// # Safety
+//
+// The streamlet must be null or a valid handle.
+char *tydi_streamlet_info(const struct TydiStreamlet *streamlet);
+
+// Generate VHDL sources for a project in a directory, with the default
+// configuration of the VHDL back-end.
+//
+// Returns 0 on success, or -1 on failure.
+//
+// # Safety
+//
+// The project must be a valid handle, and the path must be null or a
+// null-terminated string.
+int tydi_generate_vhdl(const struct TydiProject *project, const char *path);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* TYDI_H */
@@ -5,16 +5,24 @@
 use std::convert::TryInto;
 use std::path::{Path, PathBuf};
 
-use log::{debug, info, LevelFilter};
+use similar::TextDiff;
 use structopt::StructOpt;
+use tracing::{debug, info, info_span, Level};
+use tracing_subscriber::fmt::format::FmtSpan;
 
-use tydi::design::{Library, Project};
-use tydi::generator::vhdl::{VHDLBackEnd, VHDLConfig};
-use tydi::generator::GenerateProject;
-use tydi::UniquelyNamedBuilder;
-use tydi::{Logger, Result};
-
-static LOGGER: Logger = Logger;
+use tydi::design::implementation::composer::{throughput, GenericComponent};
+use tydi::design::implementation::Implementation;
+use tydi::design::{GenerateSettings, LibraryManifest, LintConfig, Manifest, Project};
+use tydi::generator::c::CBackEnd;
+use tydi::generator::common::convert::Packify;
+use tydi::generator::dpi::DpiBackEnd;
+use tydi::generator::filter::Filter;
+use tydi::generator::header::HeaderTemplate;
+use tydi::generator::vhdl::{Declare, VHDLBackEnd, VHDLConfig};
+use tydi::generator::{GenerateProject, GeneratedFile, OverwritePolicy};
+use tydi::parser::format::format;
+use tydi::Result;
+use tydi::{Error, Identify};
 
 /// Back-end options.
 #[derive(Debug, StructOpt)]
@@ -24,36 +32,280 @@ enum TargetOpt {
     VHDL(VHDLConfig),
     /// Generate Chisel sources.
     Chisel,
+    /// Generate C headers of the element layouts of physical streams.
+    C,
+    /// Generate SystemVerilog DPI-C transactors of physical streams, with C
+    /// stubs of their software model.
+    Dpi,
 }
 
 #[derive(Debug, StructOpt)]
 struct GenerateOpts {
     /// Name of the project to generate.
-    name: String,
+    /// If not supplied, the name in the project manifest is used.
+    name: Option<String>,
 
     #[structopt(
         short,
         help = "Streamlet Definition Files to generate output from.\n\
-                If not supplied, all .sdf files in the current directory are used."
+                If not supplied, the libraries in the project manifest are used, or all\n\
+                .sdf files in the current directory if there is no manifest."
     )]
     inputs: Option<Vec<PathBuf>>,
 
     #[structopt(
         short,
         help = "Output directory for generated files.\n\
-                If not supplied, the target name is used."
+                If not supplied, the output directory in the project manifest is used, or\n\
+                the current directory if there is none."
     )]
     output: Option<PathBuf>,
 
+    #[structopt(
+        short,
+        long,
+        help = "Project manifest with default generation settings.\n\
+                If not supplied, tydi.toml in the current directory is used, if it exists.\n\
+                Options on the command line override the settings in the manifest."
+    )]
+    manifest: Option<PathBuf>,
+
+    #[structopt(
+        short = "t",
+        long = "target",
+        number_of_values = 1,
+        help = "Additional targets to generate with their default configuration, e.g. \"vhdl\".\n\
+                May be supplied multiple times. All targets are generated from the same\n\
+                project, which is only parsed once."
+    )]
+    targets: Vec<String>,
+
+    #[structopt(
+        long,
+        number_of_values = 1,
+        help = "Only generate output for a library or streamlet, e.g. \"my_library\" or\n\
+                \"my_library.my_streamlet\". May be supplied multiple times."
+    )]
+    only: Vec<String>,
+
+    #[structopt(
+        long,
+        number_of_values = 1,
+        help = "Do not generate output for a library or streamlet, e.g. \"my_library\" or\n\
+                \"my_library.my_streamlet\". May be supplied multiple times."
+    )]
+    exclude: Vec<String>,
+
+    #[structopt(
+        long,
+        help = "Print a unified diff of the generated files against the files on disk,\n\
+                without writing them."
+    )]
+    dry_run: bool,
+
+    #[structopt(
+        long,
+        default_value = "error",
+        help = "What to do with existing files that were modified since they were generated:\n\
+                error, skip or force."
+    )]
+    overwrite: OverwritePolicy,
+
+    #[structopt(
+        long,
+        help = "File with a template of a header to prepend to every generated file, e.g. a\n\
+                copyright banner. The template may refer to {project}, {library},\n\
+                {streamlet}, {fingerprint}, {date} and {version}."
+    )]
+    header: Option<PathBuf>,
+
     #[structopt(subcommand)]
-    target: TargetOpt,
+    target: Option<TargetOpt>,
+}
+
+impl TargetOpt {
+    /// Returns the target with the given name and its default configuration.
+    fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "vhdl" => Ok(TargetOpt::VHDL(VHDLConfig::new(None, None))),
+            "chisel" => Ok(TargetOpt::Chisel),
+            "c" => Ok(TargetOpt::C),
+            "dpi" => Ok(TargetOpt::Dpi),
+            _ => Err(Error::CLIError(format!("unknown target: {}", name))),
+        }
+    }
+
+    /// Returns the name of the target.
+    fn name(&self) -> &'static str {
+        match self {
+            TargetOpt::VHDL(_) => "vhdl",
+            TargetOpt::Chisel => "chisel",
+            TargetOpt::C => "c",
+            TargetOpt::Dpi => "dpi",
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct InfoOpts {
+    #[structopt(
+        short,
+        help = "Streamlet Definition Files to report on.\n\
+                If not supplied, all .sdf files in the current directory are used."
+    )]
+    inputs: Option<Vec<PathBuf>>,
+
+    #[structopt(
+        long,
+        help = "Report interface statistics per library and streamlet instead of footprints."
+    )]
+    stats: bool,
+
+    #[structopt(
+        long,
+        help = "Report the interfaces, physical streams and signal widths of a streamlet\n\
+                instead of footprints, e.g. \"my_streamlet\" or \"my_library.my_streamlet\"."
+    )]
+    streamlet: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+struct CheckOpts {
+    #[structopt(
+        short,
+        help = "Streamlet Definition Files to check.\n\
+                If not supplied, all .sdf files in the current directory are used."
+    )]
+    inputs: Option<Vec<PathBuf>>,
+
+    #[structopt(
+        long,
+        help = "File with the interface fingerprints of a previous version of the project.\n\
+                The check fails if the interfaces of a streamlet of the baseline have changed."
+    )]
+    baseline: Option<PathBuf>,
+
+    #[structopt(
+        long,
+        requires = "baseline",
+        help = "Write the interface fingerprints of the project to the baseline file."
+    )]
+    update_baseline: bool,
+}
+
+#[derive(Debug, StructOpt)]
+struct DiffOpts {
+    #[structopt(
+        long,
+        required = true,
+        help = "Streamlet Definition Files of the old version of the project."
+    )]
+    old: Vec<PathBuf>,
+
+    #[structopt(
+        long,
+        required = true,
+        help = "Streamlet Definition Files of the new version of the project."
+    )]
+    new: Vec<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct ThroughputOpts {
+    #[structopt(
+        short,
+        help = "Streamlet Definition Files to analyze.\n\
+                If not supplied, all .sdf files in the current directory are used."
+    )]
+    inputs: Option<Vec<PathBuf>>,
+}
+
+#[derive(Debug, StructOpt)]
+struct NewOpts {
+    /// Name of the project to create.
+    name: String,
+
+    #[structopt(
+        short,
+        help = "Directory to create the project in.\n\
+                If not supplied, a directory with the name of the project is created."
+    )]
+    path: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct ValidateOpts {
+    #[structopt(
+        short,
+        help = "Streamlet Definition Files to validate.\n\
+                If not supplied, all .sdf files in the current directory are used."
+    )]
+    inputs: Option<Vec<PathBuf>>,
+
+    #[structopt(long, help = "Fail on warnings of the design rule check as well.")]
+    deny_warnings: bool,
+}
+
+#[derive(Debug, StructOpt)]
+struct LintOpts {
+    #[structopt(
+        short,
+        help = "Streamlet Definition Files to lint.\n\
+                If not supplied, all .sdf files in the current directory are used."
+    )]
+    inputs: Option<Vec<PathBuf>>,
+
+    #[structopt(
+        long,
+        help = "Lint configuration file.\n\
+                If not supplied, tydi-lint.toml in the current directory is used, if it exists."
+    )]
+    config: Option<PathBuf>,
+
+    #[structopt(long, help = "Report the violations in JSON format.")]
+    json: bool,
+}
+
+#[derive(Debug, StructOpt)]
+struct FmtOpts {
+    #[structopt(
+        short,
+        help = "Streamlet Definition Files to format.\n\
+                If not supplied, all .sdf files in the current directory are used."
+    )]
+    inputs: Option<Vec<PathBuf>>,
+
+    #[structopt(
+        long,
+        help = "Only report the files that are not formatted, without changing them."
+    )]
+    check: bool,
 }
 
 /// Top-level CLI commands
 #[derive(Debug, StructOpt)]
 enum Command {
     /// Generate HDL output from Streamlet Definition Files.
-    Generate(GenerateOpts),
+    Generate(Box<GenerateOpts>),
+    /// Report estimated interface wire and register costs of streamlets.
+    Info(InfoOpts),
+    /// Check Streamlet Definition Files against the design rules.
+    Check(CheckOpts),
+    /// Report interface changes between two versions of Streamlet Definition Files.
+    Diff(DiffOpts),
+    /// Report the throughput and bottlenecks of structural implementations.
+    Throughput(ThroughputOpts),
+    /// Create a new project with an example library.
+    New(NewOpts),
+    /// Parse, elaborate and check Streamlet Definition Files, without generating output.
+    Validate(ValidateOpts),
+    /// Check Streamlet Definition Files against configurable lint rules.
+    Lint(LintOpts),
+    /// Format Streamlet Definition Files.
+    Fmt(FmtOpts),
+    /// Run a language server for Streamlet Definition Files on stdin and stdout.
+    #[cfg(feature = "lsp")]
+    Lsp,
 }
 
 #[derive(Debug, StructOpt)]
@@ -61,9 +313,13 @@ pub struct Opt {
     /// Enable verbose logging.
     #[structopt(short, long)]
     verbose: bool,
-    /// Enable debug-level logging.
+    /// Enable debug-level logging, including the duration of parsing,
+    /// elaboration and generation of every file.
     #[structopt(short, long)]
     debug: bool,
+    /// Write the log to a file instead of standard output.
+    #[structopt(long)]
+    log_file: Option<PathBuf>,
     #[structopt(subcommand)]
     cmd: Command,
 }
@@ -78,57 +334,553 @@ fn list_all_sdf(path: &Path) -> Result<Vec<PathBuf>> {
     Ok(sdf_files)
 }
 
-/// Generate sources from options.
+/// Load the project manifest from a path, or from tydi.toml in the current
+/// directory if no path is given and that file exists.
+fn load_manifest(path: Option<PathBuf>) -> Result<Option<Manifest>> {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let path = std::env::current_dir()?.join("tydi.toml");
+            if !path.exists() {
+                return Ok(None);
+            }
+            path
+        }
+    };
+    debug!("Manifest: {}", path.display());
+    Manifest::load(path).map(Some)
+}
+
+/// Print a unified diff of generated files against the files on disk, and
+/// return the number of files that would change.
+fn preview(files: &[GeneratedFile]) -> Result<usize> {
+    let mut changed = 0;
+    for file in files {
+        let path = file.path().display().to_string();
+        let (old, old_header) = if file.path().exists() {
+            (std::fs::read_to_string(file.path())?, path.clone())
+        } else {
+            (String::new(), "/dev/null".to_string())
+        };
+        if old == file.contents() {
+            continue;
+        }
+        print!(
+            "{}",
+            TextDiff::from_lines(old.as_str(), file.contents())
+                .unified_diff()
+                .header(&old_header, &path)
+        );
+        changed += 1;
+    }
+    Ok(changed)
+}
+
+/// Generate sources from options, with defaults from the project manifest.
 fn generate(opts: GenerateOpts) -> Result<()> {
+    let manifest = load_manifest(opts.manifest)?;
+    let settings = manifest
+        .as_ref()
+        .map(|m| m.generate().clone())
+        .unwrap_or_default();
+
+    let name = opts
+        .name
+        .or_else(|| manifest.as_ref().map(|m| m.name().to_string()))
+        .ok_or_else(|| {
+            Error::CLIError("no project name supplied and no tydi.toml found".to_string())
+        })?;
+    let _span = info_span!("project", name = %name).entered();
+
+    // Obtain all targets from options, generating each target only once.
+    // If no target is given, use the target of the manifest.
+    let mut targets: Vec<TargetOpt> = opts.target.into_iter().collect();
+    for name in &opts.targets {
+        let target = TargetOpt::from_name(name)?;
+        if targets.iter().all(|t| t.name() != target.name()) {
+            targets.push(target);
+        }
+    }
+    if targets.is_empty() {
+        match settings.target() {
+            Some(name) => targets.push(TargetOpt::from_name(name)?),
+            None => {
+                return Err(Error::CLIError(
+                    "no target supplied and no target in the project manifest".to_string(),
+                ));
+            }
+        }
+    }
+
     info!("Loading Streamlet Definition Files...");
     // Obtain all input files from options.
-    // If no option is given, get all .sdf files in the current path.
-    let input_files = opts
-        .inputs
-        .unwrap_or(list_all_sdf(std::env::current_dir()?.as_path())?);
+    // If no option is given, use the libraries of the manifest, or get all
+    // .sdf files in the current path if there is no manifest.
+    let input_files = match (opts.inputs, &manifest) {
+        (Some(inputs), _) => inputs,
+        (None, Some(manifest)) => manifest
+            .libraries()
+            .map(|l| l.sdf().to_path_buf())
+            .collect(),
+        (None, None) => list_all_sdf(std::env::current_dir()?.as_path())?,
+    };
 
     let input_file_names: Vec<&str> = input_files.iter().filter_map(|pb| pb.to_str()).collect();
     debug!("Inputs: {}", input_file_names.join(", "));
 
-    // Build up a set of uniquely named libraries.
-    let mut lib_builder = UniquelyNamedBuilder::new();
-    for i in input_files {
-        lib_builder.add_item(Library::from_file(i.as_path())?);
+    // Construct the project from the libraries.
+    let mut project = Project::new(name.try_into()?).with_identifier_policy(
+        manifest
+            .as_ref()
+            .map(|m| m.identifiers().clone())
+            .unwrap_or_default(),
+    );
+    project.add_lib_files(&input_files)?;
+
+    let mut filter = Filter::new();
+    for pattern in &opts.only {
+        filter = filter.with_only(pattern)?;
+    }
+    for pattern in &opts.exclude {
+        filter = filter.with_exclude(pattern)?;
     }
 
-    // Construct the project from the libraries.
-    let project = Project::from_builder(opts.name.try_into()?, lib_builder)?;
+    let output = match opts.output {
+        Some(output) => output,
+        None => match settings.output() {
+            Some(output) => output.to_path_buf(),
+            None => std::env::current_dir()?,
+        },
+    };
+
+    let header = match opts.header.as_deref().or_else(|| settings.header()) {
+        Some(path) => Some(HeaderTemplate::new(&std::fs::read_to_string(path)?)?),
+        None => None,
+    };
 
-    info!("Generating sources...");
-    match opts.target {
-        TargetOpt::VHDL(cfg) => {
-            let vhdl: VHDLBackEnd = cfg.into();
-            vhdl.generate(
-                &project,
-                opts.output.unwrap_or(std::env::current_dir()?).as_path(),
-            )?;
+    for target in targets {
+        info!("Generating {} sources...", target.name());
+        match target {
+            TargetOpt::VHDL(cfg) => {
+                let mut defaults = VHDLConfig::new(
+                    settings.abstraction().map(str::parse).transpose()?,
+                    settings.suffix().map(str::to_string),
+                );
+                if let Some(separator) = settings.path_separator() {
+                    defaults = defaults.with_path_separator(separator);
+                }
+                if let Some(flattening) = settings.flattening() {
+                    defaults = defaults.with_flattening(flattening.parse()?);
+                }
+                if let Some(support) = settings.support() {
+                    defaults = defaults.with_support(support);
+                }
+                if let Some(simulation_models) = settings.simulation_models() {
+                    defaults = defaults.with_simulation_models(simulation_models);
+                }
+                if let Some(readme) = settings.readme() {
+                    defaults = defaults.with_readme(readme);
+                }
+                if let Some(testbench) = settings.testbench() {
+                    defaults = defaults.with_testbench(testbench);
+                }
+                if let Some(flow_control) = settings.flow_control() {
+                    defaults = defaults.with_flow_control(flow_control.parse()?);
+                }
+                let mut style = defaults.style();
+                if let Some(indent) = settings.indent() {
+                    style = style.with_indent(indent);
+                }
+                if let Some(tabs) = settings.tabs() {
+                    style = style.with_tabs(tabs);
+                }
+                if let Some(align) = settings.align() {
+                    style = style.with_alignment(align);
+                }
+                if let Some(max_line_length) = settings.max_line_length() {
+                    style = style.with_max_line_length(max_line_length);
+                }
+                defaults = defaults.with_style(style);
+                let mut vhdl = VHDLBackEnd::from(cfg.or(defaults)).with_filter(filter.clone());
+                if let Some(header) = &header {
+                    vhdl = vhdl.with_header(header.clone());
+                }
+                if opts.dry_run {
+                    let changed = preview(&vhdl.render(&project, output.as_path())?)?;
+                    info!("{} file(s) would change.", changed);
+                } else {
+                    vhdl.generate_with_policy(&project, output.as_path(), opts.overwrite)?;
+                }
+            }
+            TargetOpt::Chisel => {}
+            TargetOpt::C => {
+                let mut c = CBackEnd::default().with_filter(filter.clone());
+                if let Some(header) = &header {
+                    c = c.with_header(header.clone());
+                }
+                if opts.dry_run {
+                    let changed = preview(&c.render(&project, output.as_path())?)?;
+                    info!("{} file(s) would change.", changed);
+                } else {
+                    c.generate_with_policy(&project, output.as_path(), opts.overwrite)?;
+                }
+            }
+            TargetOpt::Dpi => {
+                let mut dpi = DpiBackEnd::default().with_filter(filter.clone());
+                if let Some(header) = &header {
+                    dpi = dpi.with_header(header.clone());
+                }
+                if opts.dry_run {
+                    let changed = preview(&dpi.render(&project, output.as_path())?)?;
+                    info!("{} file(s) would change.", changed);
+                } else {
+                    dpi.generate_with_policy(&project, output.as_path(), opts.overwrite)?;
+                }
+            }
         }
-        TargetOpt::Chisel => {}
     }
     info!("Done.");
     Ok(())
 }
 
+/// Report the interface footprint or statistics of all streamlets from
+/// options.
+fn info(opts: InfoOpts) -> Result<()> {
+    let input_files = opts
+        .inputs
+        .unwrap_or(list_all_sdf(std::env::current_dir()?.as_path())?);
+
+    let mut project = Project::new("info".try_into()?);
+    let keys = project.add_lib_files(&input_files)?;
+    if opts.stats {
+        print!("{}", project.stats());
+        return Ok(());
+    }
+    if let Some(name) = opts.streamlet {
+        let (lib, streamlet) = match name.split_once('.') {
+            Some((lib, streamlet)) => (Some(lib), streamlet),
+            None => (None, name.as_str()),
+        };
+        let mut found = false;
+        for library in project.libraries() {
+            if lib.is_none_or(|lib| lib == library.identifier()) {
+                if let Ok(streamlet) = library.get_streamlet(streamlet.try_into()?) {
+                    println!("Library {}", library.identifier());
+                    print!("{}", streamlet.info());
+                    found = true;
+                }
+            }
+        }
+        return if found {
+            Ok(())
+        } else {
+            Err(Error::CLIError(format!("streamlet {} not found", name)))
+        };
+    }
+    for key in keys {
+        let library = project.get_lib(key)?;
+        let mut streamlets = library.streamlets().collect::<Vec<_>>();
+        streamlets.sort_by_key(|s| s.identifier().to_string());
+        println!("Library {}", library.identifier());
+        for streamlet in streamlets {
+            print!("{}", streamlet.footprint());
+        }
+    }
+    Ok(())
+}
+
+/// Check all streamlets from options against the design rules.
+fn check(opts: CheckOpts) -> Result<()> {
+    let input_files = opts
+        .inputs
+        .unwrap_or(list_all_sdf(std::env::current_dir()?.as_path())?);
+
+    let mut project = Project::new("check".try_into()?);
+    project.add_lib_files(&input_files)?;
+
+    let mut diagnostics = tydi::design::check(&project);
+    if let Some(path) = &opts.baseline {
+        if opts.update_baseline {
+            std::fs::write(path, tydi::design::baseline(&project))?;
+        } else {
+            let baseline = std::fs::read_to_string(path)?;
+            diagnostics.extend(tydi::design::check_baseline(&project, &baseline)?);
+        }
+    }
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    if errors > 0 {
+        Err(Error::CLIError(format!(
+            "design rule check failed with {} error(s)",
+            errors
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Report the interface changes between two versions of a project.
+fn diff(opts: DiffOpts) -> Result<()> {
+    let mut old = Project::new("old".try_into()?);
+    old.add_lib_files(&opts.old)?;
+    let mut new = Project::new("new".try_into()?);
+    new.add_lib_files(&opts.new)?;
+
+    let changes = tydi::design::diff(&old, &new);
+    for change in &changes {
+        println!("{}", change);
+    }
+    let breaking = changes.iter().filter(|c| c.is_breaking()).count();
+    if breaking > 0 {
+        Err(Error::CLIError(format!(
+            "found {} breaking interface change(s)",
+            breaking
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Report the throughput of all structural implementations from options.
+fn throughput(opts: ThroughputOpts) -> Result<()> {
+    let input_files = opts
+        .inputs
+        .unwrap_or(list_all_sdf(std::env::current_dir()?.as_path())?);
+
+    let mut project = Project::new("throughput".try_into()?);
+    project.add_lib_files(&input_files)?;
+
+    let mut libraries = project.libraries().collect::<Vec<_>>();
+    libraries.sort_by_key(|l| l.identifier().to_string());
+    for library in libraries {
+        let mut streamlets = library.streamlets().collect::<Vec<_>>();
+        streamlets.sort_by_key(|s| s.identifier().to_string());
+        for streamlet in streamlets {
+            if let Some(Implementation::Structural(structure)) =
+                streamlet.get_implementation().as_deref()
+            {
+                println!(
+                    "Streamlet {}.{}",
+                    library.identifier(),
+                    streamlet.identifier()
+                );
+                print!("{}", throughput::throughput(&project, structure)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The example library of new projects.
+const EXAMPLE_SDF: &str = r#"//! Example library of a new project.
+
+/// A stream of bytes, in sequences.
+type Bytes = Stream<Bits<8>, t=4, d=1>;
+
+/// Passes its input to its output.
+Streamlet passthrough (
+  /// The input bytes.
+  input : in Bytes,
+  /// The output bytes.
+  output : out Bytes
+)
+"#;
+
+/// Create a project skeleton from options: a manifest, an example library and
+/// an output directory.
+fn new(opts: NewOpts) -> Result<()> {
+    let name: tydi::Name = opts.name.as_str().try_into()?;
+    let dir = opts.path.unwrap_or_else(|| PathBuf::from(name.to_string()));
+    if dir.exists() && dir.read_dir()?.next().is_some() {
+        return Err(Error::CLIError(format!(
+            "directory {} is not empty",
+            dir.display()
+        )));
+    }
+
+    let sdf = PathBuf::from("src").join(format!("{}.sdf", name));
+    let output = PathBuf::from("out");
+    let manifest = Manifest::new(name.to_string())
+        .with_library(LibraryManifest::new(&sdf))
+        .with_generate(
+            GenerateSettings::default()
+                .with_target("vhdl")
+                .with_abstraction("fancy")
+                .with_output(&output),
+        );
+    std::fs::create_dir_all(dir.join("src"))?;
+    std::fs::create_dir_all(dir.join(&output))?;
+    std::fs::write(dir.join(&sdf), EXAMPLE_SDF)?;
+    std::fs::write(dir.join("tydi.toml"), manifest.to_toml()?)?;
+    info!("Created project {} in {}", name, dir.display());
+    Ok(())
+}
+
+/// Validate all streamlets from options: parse them, elaborate the VHDL
+/// packages of their libraries in memory, and check them against the design
+/// rules.
+fn validate(opts: ValidateOpts) -> Result<()> {
+    let input_files = opts
+        .inputs
+        .unwrap_or(list_all_sdf(std::env::current_dir()?.as_path())?);
+
+    let deny_warnings = opts.deny_warnings;
+    let mut project = Project::new("validate".try_into()?);
+    project.add_lib_files(&input_files)?;
+
+    for library in project.libraries() {
+        library.canonical().declare()?;
+        library.fancy().declare()?;
+    }
+
+    let diagnostics = tydi::design::check(&project);
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+    let problems = diagnostics
+        .iter()
+        .filter(|d| d.is_error() || deny_warnings)
+        .count();
+    if problems > 0 {
+        Err(Error::CLIError(format!(
+            "validation failed with {} problem(s)",
+            problems
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Check all streamlets from options against the lint rules.
+fn lint(opts: LintOpts) -> Result<()> {
+    let input_files = opts
+        .inputs
+        .unwrap_or(list_all_sdf(std::env::current_dir()?.as_path())?);
+
+    let config = match opts.config {
+        Some(path) => LintConfig::from_toml(&std::fs::read_to_string(path)?)?,
+        None => {
+            let path = std::env::current_dir()?.join("tydi-lint.toml");
+            if path.exists() {
+                LintConfig::from_toml(&std::fs::read_to_string(path)?)?
+            } else {
+                LintConfig::new()
+            }
+        }
+    };
+
+    let mut project = Project::new("lint".try_into()?);
+    project.add_lib_files(&input_files)?;
+
+    let lints = tydi::design::lint(&project, &config);
+    if opts.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&lints).map_err(|e| Error::CLIError(e.to_string()))?
+        );
+    } else {
+        for lint in &lints {
+            println!("{}", lint);
+        }
+    }
+    let errors = lints.iter().filter(|l| l.is_error()).count();
+    if errors > 0 {
+        Err(Error::CLIError(format!(
+            "lint failed with {} error(s)",
+            errors
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Format all Streamlet Definition Files from options, or report the files
+/// that are not formatted if requested.
+fn fmt(opts: FmtOpts) -> Result<()> {
+    let input_files = opts
+        .inputs
+        .unwrap_or(list_all_sdf(std::env::current_dir()?.as_path())?);
+
+    // Only format files that are valid.
+    let mut project = Project::new("fmt".try_into()?);
+    project.add_lib_files(&input_files)?;
+
+    let mut unformatted = 0;
+    for file in &input_files {
+        let source = std::fs::read_to_string(file)?;
+        let formatted = format(&source)?;
+        if formatted == source {
+            continue;
+        }
+        if opts.check {
+            println!("{} is not formatted", file.display());
+            unformatted += 1;
+        } else {
+            info!("Formatting {}", file.display());
+            std::fs::write(file, formatted)?;
+        }
+    }
+    if unformatted > 0 {
+        Err(Error::CLIError(format!(
+            "found {} unformatted file(s)",
+            unformatted
+        )))
+    } else {
+        Ok(())
+    }
+}
+
 /// Internal main function wrapped with CLI main function.
 /// Useful for tests.
 pub fn internal_main(options: Opt) -> Result<()> {
-    // Set up logger.
-    log::set_logger(&LOGGER)?;
-    if options.verbose {
-        log::set_max_level(LevelFilter::Info);
-    }
-    if options.debug {
-        log::set_max_level(LevelFilter::Debug);
-        debug!("Debug-level logging enabled.");
+    // Set up logging.
+    let level = if options.debug {
+        Level::DEBUG
+    } else if options.verbose {
+        Level::INFO
+    } else {
+        Level::WARN
+    };
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false)
+        .with_span_events(if options.debug {
+            FmtSpan::CLOSE
+        } else {
+            FmtSpan::NONE
+        });
+    match options.log_file {
+        Some(path) => subscriber
+            .with_ansi(false)
+            .with_writer(std::sync::Mutex::new(std::fs::File::create(path)?))
+            .try_init(),
+        None => subscriber.with_writer(std::io::stdout).try_init(),
     }
+    .map_err(|e| Error::CLIError(e.to_string()))?;
+    debug!("Debug-level logging enabled.");
 
     match options.cmd {
-        Command::Generate(gen_opts) => generate(gen_opts),
+        Command::Generate(gen_opts) => generate(*gen_opts),
+        Command::Info(info_opts) => info(info_opts),
+        Command::Check(check_opts) => check(check_opts),
+        Command::Diff(diff_opts) => diff(diff_opts),
+        Command::Throughput(throughput_opts) => throughput(throughput_opts),
+        Command::New(new_opts) => new(new_opts),
+        Command::Validate(validate_opts) => validate(validate_opts),
+        Command::Lint(lint_opts) => lint(lint_opts),
+        Command::Fmt(fmt_opts) => fmt(fmt_opts),
+        #[cfg(feature = "lsp")]
+        Command::Lsp => tydi::lsp::run(),
+    }
+}
+
+/// CLI main function.
+fn main() {
+    if let Err(error) = internal_main(Opt::from_args()) {
+        eprintln!("{}", error.report());
+        std::process::exit(1);
     }
 }
 
@@ -136,30 +888,207 @@ pub fn internal_main(options: Opt) -> Result<()> {
 mod tests {
     use super::*;
 
-    #[test]
-    fn cli() -> Result<()> {
-        let tmpdir = tempfile::tempdir()?;
-        std::env::set_current_dir(tmpdir.path())?;
-        let sdf_file = tmpdir.path().join("test.sdf");
+    /// Writes a Streamlet Definition File to a directory and returns its path.
+    fn sdf(dir: &Path) -> Result<PathBuf> {
+        let path = dir.join("test.sdf");
         std::fs::write(
-            sdf_file.as_path(),
+            &path,
             "Streamlet x ( a : in Stream<Bits<1>, d=1>, b : out Stream<Bits<32>> )",
         )?;
+        Ok(path)
+    }
+
+    /// Creates a new project named example in a directory and returns the
+    /// path of its manifest.
+    fn example(dir: &Path) -> Result<PathBuf> {
+        new(NewOpts {
+            name: "example".to_string(),
+            path: Some(dir.join("example")),
+        })?;
+        Ok(dir.join("example/tydi.toml"))
+    }
+
+    /// Returns options to generate from a project manifest.
+    fn generate_opts(manifest: PathBuf) -> GenerateOpts {
+        GenerateOpts {
+            name: None,
+            inputs: None,
+            output: None,
+            manifest: Some(manifest),
+            targets: vec![],
+            only: vec![],
+            exclude: vec![],
+            dry_run: false,
+            overwrite: OverwritePolicy::Error,
+            header: None,
+            target: None,
+        }
+    }
+
+    #[test]
+    fn cli_generate() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let sdf_file = sdf(tmpdir.path())?;
+        let log_file = tmpdir.path().join("tydi.log");
         internal_main(
             Opt::from_iter_safe(vec![
-                "tydi", "--debug", "generate", "test", "vhdl", "-a=fancy", "-s=gen",
+                "tydi",
+                "--debug",
+                "--log-file",
+                log_file.to_str().unwrap(),
+                "generate",
+                "test",
+                "-i",
+                sdf_file.to_str().unwrap(),
+                "-o",
+                tmpdir.path().to_str().unwrap(),
+                "vhdl",
+                "-a=fancy",
+                "-s=gen",
             ])
             .map_err(|e| panic!("{}", e))
             .unwrap(),
         )?;
         let expected_vhdl = tmpdir.path().join("test/test_pkg.gen.vhd");
         std::fs::metadata(expected_vhdl)?;
-        std::fs::remove_dir_all(tmpdir.path())?;
+        let log = std::fs::read_to_string(log_file)?;
+        assert!(log.contains("project{name=test}:parse{file="));
+        assert!(log.contains("project{name=test}:generate{target=\"vhdl\"}:write{file="));
+        assert!(log.contains("close"));
         Ok(())
     }
-}
 
-/// CLI main function.
-fn main() -> Result<()> {
-    internal_main(Opt::from_args())
+    #[test]
+    fn cli_info() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let sdf_file = sdf(tmpdir.path())?;
+        info(InfoOpts {
+            inputs: Some(vec![sdf_file.clone()]),
+            stats: false,
+            streamlet: Some("test.x".to_string()),
+        })?;
+        assert!(info(InfoOpts {
+            inputs: Some(vec![sdf_file]),
+            stats: false,
+            streamlet: Some("y".to_string()),
+        })
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn cli_validate() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        validate(ValidateOpts {
+            inputs: Some(vec![sdf(tmpdir.path())?]),
+            deny_warnings: true,
+        })
+    }
+
+    #[test]
+    fn cli_lint() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let sdf_file = sdf(tmpdir.path())?;
+        let config = tmpdir.path().join("tydi-lint.toml");
+        std::fs::write(&config, "[rules]\nundocumented-interfaces = \"error\"")?;
+        let lint_opts = |config: Option<&Path>, json| LintOpts {
+            inputs: Some(vec![sdf_file.clone()]),
+            config: config.map(Path::to_path_buf),
+            json,
+        };
+        assert!(lint(lint_opts(Some(&config), false)).is_err());
+        assert!(lint(lint_opts(Some(&config), true)).is_err());
+        lint(lint_opts(None, false))
+    }
+
+    #[test]
+    fn cli_fmt() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let sdf_file = sdf(tmpdir.path())?;
+        let fmt_opts = |check| FmtOpts {
+            inputs: Some(vec![sdf_file.clone()]),
+            check,
+        };
+        assert!(fmt(fmt_opts(true)).is_err());
+        fmt(fmt_opts(false))?;
+        fmt(fmt_opts(true))?;
+        assert_eq!(
+            std::fs::read_to_string(&sdf_file)?,
+            "Streamlet x (\n  a : in Stream<Bits<1>, d=1>,\n  b : out Stream<Bits<32>>\n)\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cli_new() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let manifest = example(tmpdir.path())?;
+        let project = Project::load(manifest)?;
+        assert_eq!(project.libraries().count(), 1);
+        assert!(tmpdir.path().join("example/out").is_dir());
+        assert!(new(NewOpts {
+            name: "example".to_string(),
+            path: Some(tmpdir.path().join("example")),
+        })
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn cli_generate_manifest() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let manifest = example(tmpdir.path())?;
+
+        // A dry run does not write any files.
+        generate(GenerateOpts {
+            dry_run: true,
+            ..generate_opts(manifest.clone())
+        })?;
+        assert!(!tmpdir.path().join("example/out/example").exists());
+
+        // Generate with the defaults of the manifest of the new project.
+        generate(generate_opts(manifest.clone()))?;
+        assert!(tmpdir
+            .path()
+            .join("example/out/example/example_pkg.vhd")
+            .is_file());
+        // Files modified by hand are protected.
+        let wrapper = tmpdir
+            .path()
+            .join("example/out/example/passthrough_wrapper.vhd");
+        std::fs::write(&wrapper, "-- edited\n")?;
+        let overwrite_opts = |overwrite| GenerateOpts {
+            overwrite,
+            ..generate_opts(manifest.clone())
+        };
+        assert!(generate(overwrite_opts(OverwritePolicy::Error)).is_err());
+        generate(overwrite_opts(OverwritePolicy::Skip))?;
+        assert_eq!(std::fs::read_to_string(&wrapper)?, "-- edited\n");
+        generate(overwrite_opts(OverwritePolicy::Force))?;
+        assert_ne!(std::fs::read_to_string(&wrapper)?, "-- edited\n");
+
+        // Options on the command line override the manifest.
+        let header = tmpdir.path().join("header.txt");
+        std::fs::write(&header, "Copyright {project}")?;
+        generate(GenerateOpts {
+            name: Some("other".to_string()),
+            targets: vec!["vhdl".to_string(), "chisel".to_string()],
+            only: vec!["example.passthrough".to_string()],
+            header: Some(header),
+            target: Some(TargetOpt::VHDL(VHDLConfig::new(
+                None,
+                Some("gen".to_string()),
+            ))),
+            ..generate_opts(manifest.clone())
+        })?;
+        let package =
+            std::fs::read_to_string(tmpdir.path().join("example/out/other/example_pkg.gen.vhd"))?;
+        assert_eq!(package.lines().nth(1), Some("-- Copyright other"));
+        assert!(generate(GenerateOpts {
+            targets: vec!["verilog".to_string()],
+            ..generate_opts(manifest)
+        })
+        .is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,634 @@
+//! Design rule checks.
+//!
+//! The [check] pass inspects a [Project] for problems that are not rejected
+//! while constructing it, such as interfaces of structural implementations
+//! that are left unconnected, or streamlets with the same name in different
+//! libraries.
+//!
+//! [check_baseline] compares the [fingerprints] of the streamlets of a
+//! [Project] against a stored [baseline], to detect interface changes.
+//!
+//! [fingerprints]: crate::design::Streamlet::fingerprint
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::design::implementation::composer::deadlock::deadlocks;
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::implementation::structure::Structure;
+use crate::design::implementation::Implementation;
+use crate::design::{Mode, NodeIFHandle, NodeKey, Project, StreamletKey};
+use crate::{Error, Identify, PathName, Result, ResultExt};
+
+/// The severity of a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The design is valid, but probably not what was intended.
+    Warning,
+    /// The design is invalid.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found by the design rule check.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    severity: Severity,
+    message: String,
+}
+
+impl Diagnostic {
+    /// Construct a new diagnostic.
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the severity of this diagnostic.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the message of this diagnostic.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns true if this diagnostic is an error.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Check a project against the design rules, and return all problems found.
+///
+/// The following problems are detected:
+/// - streamlets with the same name in different libraries (error),
+/// - imports of libraries that do not exist in the project (error),
+/// - streamlets instantiating themselves through structural implementations
+///   (error),
+/// - cycles of edges of structural implementations that do not contain a
+///   registered edge (error),
+/// - potential deadlocks in structural implementations, see
+///   [deadlocks] (warning),
+/// - nodes of structural implementations of which no interface is connected
+///   (warning),
+/// - sink interfaces of structural implementations that are not driven
+///   (error), and source interfaces that drive nothing (warning),
+/// - sink interfaces, or fields of sink interfaces, that are driven more
+///   than once (error), and fields of sink interfaces that are driven
+///   through other fields only, and are tied off (warning),
+/// - connections between interfaces that cannot drive each other, e.g.
+///   because of a complexity mismatch (error).
+pub fn check(project: &Project) -> Vec<Diagnostic> {
+    let mut result = Vec::new();
+
+    let mut libraries = project.libraries().collect::<Vec<_>>();
+    libraries.sort_by_key(|lib| lib.key().clone());
+
+    let mut streamlets: HashMap<StreamletKey, Vec<String>> = HashMap::new();
+    for library in &libraries {
+        for streamlet in library.streamlets() {
+            streamlets
+                .entry(streamlet.key().clone())
+                .or_default()
+                .push(library.identifier().to_string());
+        }
+    }
+    let mut collisions = streamlets
+        .into_iter()
+        .filter(|(_, libs)| libs.len() > 1)
+        .collect::<Vec<_>>();
+    collisions.sort();
+    for (streamlet, libs) in collisions {
+        result.push(Diagnostic::new(
+            Severity::Error,
+            format!(
+                "streamlet {} is defined in multiple libraries: {}",
+                streamlet,
+                libs.join(", ")
+            ),
+        ));
+    }
+
+    for library in &libraries {
+        for import in library.imports() {
+            if project.get_lib(import.clone()).is_err() {
+                result.push(Diagnostic::new(
+                    Severity::Error,
+                    format!(
+                        "library {} imports unknown library {}",
+                        library.identifier(),
+                        import
+                    ),
+                ));
+            }
+        }
+    }
+
+    for library in &libraries {
+        let mut streamlets = library.streamlets().collect::<Vec<_>>();
+        streamlets.sort_by_key(|s| s.key().clone());
+        for streamlet in streamlets {
+            if let Some(implementation) = streamlet.get_implementation() {
+                if let Implementation::Structural(structure) = implementation.as_ref() {
+                    result.extend(check_structure(project, structure).into_iter().map(|d| {
+                        Diagnostic::new(
+                            d.severity,
+                            format!(
+                                "structure of {}.{}: {}",
+                                library.identifier(),
+                                streamlet.identifier(),
+                                d.message
+                            ),
+                        )
+                    }));
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Check the connections of a structural implementation.
+fn check_structure(project: &Project, structure: &Structure) -> Vec<Diagnostic> {
+    let mut result = Vec::new();
+
+    if let Err(e) = structure.hierarchy(project) {
+        result.push(Diagnostic::new(Severity::Error, e.to_string()));
+    }
+    match structure.check_cycles() {
+        Ok(()) => match deadlocks(project, structure) {
+            Ok(deadlocks) => result.extend(
+                deadlocks
+                    .into_iter()
+                    .map(|d| Diagnostic::new(Severity::Warning, d.to_string())),
+            ),
+            Err(e) => result.push(Diagnostic::new(Severity::Error, e.to_string())),
+        },
+        Err(e) => result.push(Diagnostic::new(Severity::Error, e.to_string())),
+    }
+
+    for edge in structure.edges() {
+        let interfaces = (|| -> Result<()> {
+            let (source, sink) = structure.endpoints(project, edge)?;
+            source.can_drive(&sink)
+        })();
+        if let Err(e) = interfaces {
+            result.push(Diagnostic::new(
+                Severity::Error,
+                format!(
+                    "{} cannot drive {}: {}",
+                    field(&edge.source(), edge.source_field()),
+                    field(&edge.sink(), edge.sink_field()),
+                    e
+                ),
+            ));
+        }
+    }
+
+    let nodes = std::iter::once((NodeKey::this(), structure.streamlet_handle())).chain(
+        structure
+            .nodes()
+            .map(|(key, streamlet)| (key.clone(), streamlet.clone())),
+    );
+    for (node, streamlet) in nodes {
+        let streamlet = match project.get_streamlet(streamlet) {
+            Ok(streamlet) => streamlet,
+            Err(e) => {
+                result.push(Diagnostic::new(
+                    Severity::Error,
+                    format!("node {}: {}", node, e),
+                ));
+                continue;
+            }
+        };
+
+        let interfaces = streamlet
+            .interfaces()
+            .map(|iface| {
+                (
+                    NodeIFHandle::new(node.clone(), iface.key().clone()),
+                    iface.mode(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let drivers =
+            |iface: &NodeIFHandle| structure.edges().filter(|e| e.sink() == *iface).count();
+        let loads =
+            |iface: &NodeIFHandle| structure.edges().filter(|e| e.source() == *iface).count();
+
+        if node != NodeKey::this()
+            && !interfaces.is_empty()
+            && interfaces
+                .iter()
+                .all(|(iface, _)| drivers(iface) == 0 && loads(iface) == 0)
+        {
+            result.push(Diagnostic::new(
+                Severity::Warning,
+                format!("node {} is not connected", node),
+            ));
+            continue;
+        }
+
+        for (iface, mode) in interfaces {
+            // Inputs of the implemented streamlet are sources inside of the structure.
+            let is_sink = (node == NodeKey::this()) == (mode == Mode::Out);
+            if is_sink {
+                let fields = structure
+                    .edges()
+                    .filter(|e| e.sink() == iface)
+                    .map(|e| e.sink_field().clone())
+                    .collect::<Vec<_>>();
+                if fields.iter().all(|f| f.is_empty()) {
+                    match drivers(&iface) {
+                        0 => result.push(Diagnostic::new(
+                            Severity::Error,
+                            format!("{} is not driven", handle(&iface)),
+                        )),
+                        1 => {}
+                        n => result.push(Diagnostic::new(
+                            Severity::Error,
+                            format!("{} is driven {} times", handle(&iface), n),
+                        )),
+                    }
+                    continue;
+                }
+                // Fields driven by more than one edge, reported once for the
+                // outermost of the overlapping fields.
+                let mut overlapping = vec![];
+                for (i, a) in fields.iter().enumerate() {
+                    for b in fields.iter().skip(i + 1) {
+                        let outer = if a.starts_with(b) {
+                            b
+                        } else if b.starts_with(a) {
+                            a
+                        } else {
+                            continue;
+                        };
+                        if !overlapping.contains(outer) {
+                            overlapping.push(outer.clone());
+                        }
+                    }
+                }
+                for outer in &overlapping {
+                    result.push(Diagnostic::new(
+                        Severity::Error,
+                        format!("{} is driven more than once", field(&iface, outer)),
+                    ));
+                }
+                if overlapping.is_empty() {
+                    match structure.unconnected_fields(project, &iface) {
+                        Ok(unconnected) => result.extend(unconnected.iter().map(|f| {
+                            Diagnostic::new(
+                                Severity::Warning,
+                                format!("{} is not driven and is tied off", field(&iface, f)),
+                            )
+                        })),
+                        Err(e) => result.push(Diagnostic::new(Severity::Error, e.to_string())),
+                    }
+                }
+            } else if loads(&iface) == 0 {
+                result.push(Diagnostic::new(
+                    Severity::Warning,
+                    format!("{} is not connected", handle(&iface)),
+                ));
+            }
+        }
+    }
+
+    result
+}
+
+/// Returns the fingerprints of the streamlets of a project as a baseline
+/// for [check_baseline], with a line `<library>.<streamlet> <fingerprint>`
+/// per streamlet, sorted by name.
+pub fn baseline(project: &Project) -> String {
+    let lines = fingerprints(project)
+        .into_iter()
+        .map(|(name, fingerprint)| format!("{} {:016x}\n", name, fingerprint))
+        .collect::<String>();
+    format!(
+        "# Interface fingerprints of project {}.\n{}",
+        project.identifier(),
+        lines
+    )
+}
+
+/// Check the interfaces of the streamlets of a project against a baseline
+/// of their fingerprints, as returned by [baseline].
+///
+/// Streamlets of the baseline that are removed, or of which the interfaces
+/// have changed are errors, streamlets that are not in the baseline are
+/// warnings. Empty lines and lines starting with `#` are ignored. Returns an
+/// error if the baseline is malformed.
+pub fn check_baseline(project: &Project, baseline: &str) -> Result<Vec<Diagnostic>> {
+    let mut expected = BTreeMap::new();
+    for (idx, line) in baseline.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let entry = match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [name, fingerprint] => u64::from_str_radix(fingerprint, 16)
+                .map(|fingerprint| (name.to_string(), fingerprint))
+                .map_err(|_| {
+                    Error::ParsingError(format!("invalid fingerprint \"{}\"", fingerprint))
+                }),
+            _ => Err(Error::ParsingError(format!(
+                "expected a streamlet and its fingerprint, found \"{}\"",
+                line
+            ))),
+        };
+        let (name, fingerprint) =
+            entry.with_context(|| format!("while reading line {}", idx + 1))?;
+        expected.insert(name, fingerprint);
+    }
+
+    let actual = fingerprints(project);
+    let mut result = Vec::new();
+    for (name, fingerprint) in &expected {
+        match actual.get(name) {
+            None => result.push(Diagnostic::new(
+                Severity::Error,
+                format!("streamlet {} of the baseline is removed", name),
+            )),
+            Some(actual) if actual != fingerprint => result.push(Diagnostic::new(
+                Severity::Error,
+                format!("the interfaces of streamlet {} have changed", name),
+            )),
+            Some(_) => (),
+        }
+    }
+    for name in actual.keys().filter(|name| !expected.contains_key(*name)) {
+        result.push(Diagnostic::new(
+            Severity::Warning,
+            format!("streamlet {} is not in the baseline", name),
+        ));
+    }
+    Ok(result)
+}
+
+/// Returns the fingerprints of the streamlets of a project, by their names
+/// qualified with their libraries.
+fn fingerprints(project: &Project) -> BTreeMap<String, u64> {
+    project
+        .libraries()
+        .flat_map(|lib| {
+            lib.streamlets().map(move |streamlet| {
+                (
+                    format!("{}.{}", lib.identifier(), streamlet.identifier()),
+                    streamlet.fingerprint(),
+                )
+            })
+        })
+        .collect()
+}
+
+fn handle(handle: &NodeIFHandle) -> String {
+    format!("{}.{}", handle.node(), handle.iface())
+}
+
+fn field(iface: &NodeIFHandle, field: &PathName) -> String {
+    if field.is_empty() {
+        handle(iface)
+    } else {
+        format!("{}.{}", handle(iface), field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::implementation::composer::tests::composition_test_proj;
+    use crate::design::{Library, Streamlet, StreamletHandle};
+    use crate::parser::nom::{interface, streamlet};
+    use crate::{Name, UniqueKeyBuilder};
+
+    fn structure(project: &Project) -> Result<Structure> {
+        let handle = |lib: &str, streamlet: &str| -> Result<StreamletHandle> {
+            Ok(StreamletHandle {
+                lib: Name::try_new(lib)?,
+                streamlet: Name::try_new(streamlet)?,
+            })
+        };
+        let iface = |node: &str, iface: &str| -> Result<NodeIFHandle> {
+            Ok(NodeIFHandle::new(
+                Name::try_new(node)?,
+                Name::try_new(iface)?,
+            ))
+        };
+        let mut structure = Structure::new(handle("compositions", "Top_level")?);
+        structure.add_node(Name::try_new("a")?, handle("primitives", "test_op")?)?;
+        structure.add_node(Name::try_new("b")?, handle("primitives", "test_op")?)?;
+        structure.add_node(Name::try_new("c")?, handle("primitives", "Magic")?)?;
+        structure.add_node(Name::try_new("d")?, handle("primitives", "Magic")?)?;
+        structure.connect(project, iface("this", "in")?, iface("c", "in")?)?;
+        structure.connect(project, iface("a", "out")?, iface("this", "out")?)?;
+        structure.connect(project, iface("b", "out")?, iface("this", "out")?)?;
+        Ok(structure)
+    }
+
+    #[test]
+    fn check_project() -> Result<()> {
+        let mut prj = composition_test_proj()?;
+        assert!(check(&prj).is_empty());
+
+        let structure = structure(&prj)?;
+        prj.get_streamlet_mut(structure.streamlet_handle())?
+            .attach_implementation(Implementation::Structural(structure))?;
+        let mut lib = Library::new(Name::try_new("other")?);
+        lib.add_import(Name::try_new("primitives")?)?;
+        lib.add_import(Name::try_new("missing")?)?;
+        lib.add_streamlet(Streamlet::from_builder(
+            Name::try_new("Magic")?,
+            UniqueKeyBuilder::new(),
+            None,
+        )?)?;
+        prj.add_lib(lib)?;
+
+        assert_eq!(
+            check(&prj)
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "error: streamlet Magic is defined in multiple libraries: other, primitives",
+                "error: library other imports unknown library missing",
+                "warning: structure of compositions.Top_level: this.in2 is not connected",
+                "error: structure of compositions.Top_level: this.out is driven 2 times",
+                "error: structure of compositions.Top_level: a.in is not driven",
+                "error: structure of compositions.Top_level: b.in is not driven",
+                "warning: structure of compositions.Top_level: c.out is not connected",
+                "warning: structure of compositions.Top_level: node d is not connected",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn check_complexity() -> Result<()> {
+        let prj = composition_test_proj()?;
+        let structure = structure(&prj)?;
+
+        // Check the structure against a project in which the sink has a
+        // lower complexity than its source.
+        let mut other = composition_test_proj()?;
+        let mut lib = Library::new(Name::try_new("primitives")?);
+        lib.add_streamlet(Streamlet::from_builder(
+            Name::try_new("Magic")?,
+            UniqueKeyBuilder::new().with_items(vec![
+                interface("in: in Stream<Bits<32>, d=1, c=1>").unwrap().1,
+                interface("out: out Stream<Bits<32>, d=1, c=1>").unwrap().1,
+            ]),
+            None,
+        )?)?;
+        lib.add_streamlet(
+            prj.get_streamlet(StreamletHandle {
+                lib: Name::try_new("primitives")?,
+                streamlet: Name::try_new("test_op")?,
+            })?
+            .clone(),
+        )?;
+        *other.get_lib_mut(Name::try_new("primitives")?)? = lib;
+        let diagnostics = check_structure(&other, &structure);
+        assert!(diagnostics[0].is_error());
+        assert_eq!(
+            diagnostics[0].message(),
+            "this.in cannot drive c.in: Interface error: Interface in cannot drive interface \
+             in: Invalid argument: source complexity 4 exceeds sink complexity 1"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn check_fields() -> Result<()> {
+        let mut prj = Project::new(Name::try_new("proj")?);
+        prj.add_lib(Library::try_new(
+            Name::try_new("lib")?,
+            vec![],
+            vec![
+                streamlet(
+                    "Streamlet top (a : in Group<x: Stream<Bits<4>>, y: Stream<Bits<4>>>, \
+                 b : out Group<x: Stream<Bits<4>>, y: Group<u: Stream<Bits<4>>, \
+                 v: Stream<Bits<4>>>>)",
+                )
+                .unwrap()
+                .1,
+            ],
+        )?)?;
+        let iface = |name: &str| NodeIFHandle::new(NodeKey::this(), Name::try_new(name).unwrap());
+        let field = |names: Vec<&str>| PathName::try_new(names).unwrap();
+
+        let mut structure = Structure::new(StreamletHandle {
+            lib: Name::try_new("lib")?,
+            streamlet: Name::try_new("top")?,
+        });
+        structure.connect_fields(
+            &prj,
+            iface("a"),
+            field(vec!["x"]),
+            iface("b"),
+            field(vec!["x"]),
+        )?;
+        structure.connect_fields(
+            &prj,
+            iface("a"),
+            field(vec!["y"]),
+            iface("b"),
+            field(vec!["y", "u"]),
+        )?;
+        let messages = |structure: &Structure| {
+            check_structure(&prj, structure)
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            messages(&structure),
+            vec!["warning: this.b.y__v is not driven and is tied off"]
+        );
+
+        structure.connect_fields(
+            &prj,
+            iface("a"),
+            field(vec!["x"]),
+            iface("b"),
+            field(vec!["y", "v"]),
+        )?;
+        assert!(messages(&structure).is_empty());
+
+        structure.connect_fields(
+            &prj,
+            iface("a"),
+            field(vec!["x"]),
+            iface("b"),
+            field(vec!["x"]),
+        )?;
+        assert_eq!(
+            messages(&structure),
+            vec!["error: this.b.x is driven more than once"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn check_against_baseline() -> Result<()> {
+        let project = |sdf: &str| -> Result<Project> {
+            let mut prj = Project::new(Name::try_new("proj")?);
+            prj.add_lib(Library::from_sdf(Name::try_new("lib")?, sdf)?)?;
+            Ok(prj)
+        };
+        let prj = project("Streamlet a (x : in Bits<1>)\nStreamlet b (y : out Bits<2>)")?;
+        let stored = baseline(&prj);
+        assert_eq!(stored.lines().count(), 3);
+        assert!(stored.lines().nth(1).unwrap().starts_with("lib.a "));
+        assert!(check_baseline(&prj, &stored)?.is_empty());
+
+        let messages = |sdf: &str| -> Result<Vec<String>> {
+            Ok(check_baseline(&project(sdf)?, &stored)?
+                .iter()
+                .map(|d| d.to_string())
+                .collect())
+        };
+        assert_eq!(
+            messages(
+                "/// Documented.\nStreamlet a (x : in Bits<1>)\nStreamlet b (y : out Bits<2>)"
+            )?,
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            messages("Streamlet a (x : in Bits<2>)\nStreamlet c (y : out Bits<2>)")?,
+            vec![
+                "error: the interfaces of streamlet lib.a have changed",
+                "error: streamlet lib.b of the baseline is removed",
+                "warning: streamlet lib.c is not in the baseline",
+            ]
+        );
+
+        let err = check_baseline(&prj, "# comment\n\nlib.a 12 34").unwrap_err();
+        assert_eq!(err.notes(), &["while reading line 3".to_string()]);
+        assert!(check_baseline(&prj, "lib.a xyz").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,370 @@
+//! Interface-level differences between projects.
+//!
+//! The [diff] function compares the streamlets of two versions of a
+//! [Project], and reports streamlets and interfaces that were added or
+//! removed, and interfaces of which the mode or logical type changed. Changes
+//! of logical types are reported down to the fields of groups and unions, and
+//! the properties of streams.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::{InterfaceHandle, Mode, Project, StreamletHandle};
+use crate::logical::LogicalType;
+use crate::traits::Identify;
+use crate::Name;
+
+/// A difference between two projects.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// A streamlet only exists in the new project.
+    AddedStreamlet(StreamletHandle),
+    /// A streamlet only exists in the old project.
+    RemovedStreamlet(StreamletHandle),
+    /// An interface only exists in the new version of a streamlet.
+    AddedInterface(InterfaceHandle),
+    /// An interface only exists in the old version of a streamlet.
+    RemovedInterface(InterfaceHandle),
+    /// The mode of an interface changed.
+    ChangedMode {
+        interface: InterfaceHandle,
+        old: Mode,
+        new: Mode,
+    },
+    /// (Part of) the logical type of an interface changed.
+    ///
+    /// The path consists of the names of the fields of groups and unions
+    /// leading to the change, `user` for the user type of a stream, and ends
+    /// with the name of the stream property if a property of a stream
+    /// changed. The old or new value is None if a field was added or removed.
+    ChangedType {
+        interface: InterfaceHandle,
+        path: Vec<String>,
+        old: Option<String>,
+        new: Option<String>,
+    },
+}
+
+impl Change {
+    /// Returns true if this change can break users of the old project, i.e.
+    /// if it is anything other than the addition of a streamlet.
+    pub fn is_breaking(&self) -> bool {
+        !matches!(self, Change::AddedStreamlet(_))
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let streamlet = |s: &StreamletHandle| format!("{}.{}", s.lib, s.streamlet);
+        let interface = |i: &InterfaceHandle| format!("{}.{}", streamlet(&i.streamlet), i.iface);
+        match self {
+            Change::AddedStreamlet(s) => write!(f, "added streamlet {}", streamlet(s)),
+            Change::RemovedStreamlet(s) => write!(f, "removed streamlet {}", streamlet(s)),
+            Change::AddedInterface(i) => write!(f, "added interface {}", interface(i)),
+            Change::RemovedInterface(i) => write!(f, "removed interface {}", interface(i)),
+            Change::ChangedMode {
+                interface: i,
+                old,
+                new,
+            } => write!(f, "changed mode of {}: {} -> {}", interface(i), old, new),
+            Change::ChangedType {
+                interface: i,
+                path,
+                old,
+                new,
+            } => {
+                let location = std::iter::once(interface(i))
+                    .chain(path.iter().cloned())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                match (old, new) {
+                    (None, Some(new)) => write!(f, "added {}: {}", location, new),
+                    (Some(old), None) => write!(f, "removed {}: {}", location, old),
+                    (Some(old), Some(new)) => {
+                        write!(f, "changed {}: {} -> {}", location, old, new)
+                    }
+                    (None, None) => write!(f, "changed {}", location),
+                }
+            }
+        }
+    }
+}
+
+/// Compare the streamlets of two projects, and return all differences of
+/// their interfaces, ordered by library, streamlet and interface name.
+pub fn diff(old: &Project, new: &Project) -> Vec<Change> {
+    let handles = |project: &Project| {
+        project
+            .libraries()
+            .flat_map(|lib| lib.streamlets().map(move |s| (lib.key().clone(), s.key())))
+            .collect::<BTreeSet<_>>()
+    };
+    let (old_handles, new_handles) = (handles(old), handles(new));
+
+    let mut result = vec![];
+    for (lib, key) in old_handles.union(&new_handles) {
+        let handle = StreamletHandle {
+            lib: lib.clone(),
+            streamlet: key.clone(),
+        };
+        let (old_streamlet, new_streamlet) = match (
+            old.get_streamlet(handle.clone()),
+            new.get_streamlet(handle.clone()),
+        ) {
+            (Ok(old_streamlet), Ok(new_streamlet)) => (old_streamlet, new_streamlet),
+            (Ok(_), Err(_)) => {
+                result.push(Change::RemovedStreamlet(handle));
+                continue;
+            }
+            _ => {
+                result.push(Change::AddedStreamlet(handle));
+                continue;
+            }
+        };
+
+        let ifaces = |streamlet: &dyn GenericComponent| {
+            streamlet
+                .interfaces()
+                .map(|i| i.key().clone())
+                .collect::<BTreeSet<Name>>()
+        };
+        let (old_ifaces, new_ifaces) = (ifaces(old_streamlet), ifaces(new_streamlet));
+        for iface in old_ifaces.union(&new_ifaces) {
+            let interface = InterfaceHandle {
+                streamlet: handle.clone(),
+                iface: iface.clone(),
+            };
+            let (old_iface, new_iface) = match (
+                old_streamlet.get_interface(iface.clone()),
+                new_streamlet.get_interface(iface.clone()),
+            ) {
+                (Ok(old_iface), Ok(new_iface)) => (old_iface, new_iface),
+                (Ok(_), Err(_)) => {
+                    result.push(Change::RemovedInterface(interface));
+                    continue;
+                }
+                _ => {
+                    result.push(Change::AddedInterface(interface));
+                    continue;
+                }
+            };
+            if old_iface.mode() != new_iface.mode() {
+                result.push(Change::ChangedMode {
+                    interface: interface.clone(),
+                    old: old_iface.mode(),
+                    new: new_iface.mode(),
+                });
+            }
+            let mut changes = vec![];
+            diff_types(
+                &mut vec![],
+                &old_iface.typ(),
+                &new_iface.typ(),
+                &mut changes,
+            );
+            result.extend(
+                changes
+                    .into_iter()
+                    .map(|(path, old, new)| Change::ChangedType {
+                        interface: interface.clone(),
+                        path,
+                        old,
+                        new,
+                    }),
+            );
+        }
+    }
+    result
+}
+
+/// A change of (part of) a logical type: its path, old value and new value.
+type TypeChange = (Vec<String>, Option<String>, Option<String>);
+
+/// Compare two logical types, recursing into the fields of groups and unions
+/// with the same field names in the same order, and into streams.
+fn diff_types(
+    path: &mut Vec<String>,
+    old: &LogicalType,
+    new: &LogicalType,
+    result: &mut Vec<TypeChange>,
+) {
+    // Equality of groups and unions does not consider the order of their
+    // fields, which does matter for the generated hardware.
+    if old == new && old.to_string() == new.to_string() {
+        return;
+    }
+    let changed = |path: &[String], old: String, new: String| (path.to_vec(), Some(old), Some(new));
+    match (old, new) {
+        (LogicalType::Group(old_fields), LogicalType::Group(new_fields)) => {
+            diff_fields(path, old_fields.iter(), new_fields.iter(), old, new, result)
+        }
+        (LogicalType::Union(old_fields), LogicalType::Union(new_fields)) => {
+            diff_fields(path, old_fields.iter(), new_fields.iter(), old, new, result)
+        }
+        (LogicalType::Stream(old_stream), LogicalType::Stream(new_stream)) => {
+            diff_types(path, old_stream.data(), new_stream.data(), result);
+            let mut property = |name: &str, old: String, new: String| {
+                if old != new {
+                    path.push(name.to_string());
+                    result.push(changed(path, old, new));
+                    path.pop();
+                }
+            };
+            property(
+                "t",
+                old_stream.throughput().get().to_string(),
+                new_stream.throughput().get().to_string(),
+            );
+            property(
+                "d",
+                old_stream.dimensionality().to_string(),
+                new_stream.dimensionality().to_string(),
+            );
+            property(
+                "s",
+                old_stream.synchronicity().to_string(),
+                new_stream.synchronicity().to_string(),
+            );
+            property(
+                "c",
+                old_stream.complexity().to_string(),
+                new_stream.complexity().to_string(),
+            );
+            property(
+                "r",
+                old_stream.direction().to_string(),
+                new_stream.direction().to_string(),
+            );
+            property(
+                "x",
+                old_stream.keep().to_string(),
+                new_stream.keep().to_string(),
+            );
+            if old_stream.sideband() != new_stream.sideband() {
+                let sideband = |s: &crate::logical::Stream| {
+                    s.sideband()
+                        .iter()
+                        .map(|signal| signal.identifier().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                property("sideband", sideband(old_stream), sideband(new_stream));
+            }
+            path.push("user".to_string());
+            match (old_stream.user(), new_stream.user()) {
+                (Some(old_user), Some(new_user)) => diff_types(path, old_user, new_user, result),
+                (Some(old_user), None) => {
+                    result.push((path.clone(), Some(old_user.to_string()), None))
+                }
+                (None, Some(new_user)) => {
+                    result.push((path.clone(), None, Some(new_user.to_string())))
+                }
+                (None, None) => (),
+            }
+            path.pop();
+        }
+        _ => result.push(changed(path, old.to_string(), new.to_string())),
+    }
+}
+
+/// Compare the fields of two groups or unions. If the fields that exist in
+/// both are in a different order, the whole type is reported as changed.
+fn diff_fields<'a>(
+    path: &mut Vec<String>,
+    old_fields: impl Iterator<Item = (&'a Name, &'a LogicalType)>,
+    new_fields: impl Iterator<Item = (&'a Name, &'a LogicalType)>,
+    old: &LogicalType,
+    new: &LogicalType,
+    result: &mut Vec<TypeChange>,
+) {
+    let old_fields = old_fields.collect::<Vec<_>>();
+    let new_fields = new_fields.collect::<Vec<_>>();
+    fn common<'a>(
+        fields: &[(&'a Name, &LogicalType)],
+        other: &[(&Name, &LogicalType)],
+    ) -> Vec<&'a Name> {
+        fields
+            .iter()
+            .filter(|(name, _)| other.iter().any(|(other, _)| other == name))
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+    }
+    if common(&old_fields, &new_fields) != common(&new_fields, &old_fields) {
+        result.push((path.clone(), Some(old.to_string()), Some(new.to_string())));
+        return;
+    }
+    for (name, old_typ) in &old_fields {
+        path.push(name.to_string());
+        match new_fields.iter().find(|(new_name, _)| new_name == name) {
+            Some((_, new_typ)) => diff_types(path, old_typ, new_typ, result),
+            None => result.push((path.clone(), Some(old_typ.to_string()), None)),
+        }
+        path.pop();
+    }
+    for (name, new_typ) in &new_fields {
+        if !old_fields.iter().any(|(old_name, _)| old_name == name) {
+            path.push(name.to_string());
+            result.push((path.clone(), None, Some(new_typ.to_string())));
+            path.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::{Library, Streamlet};
+    use crate::parser::nom::streamlet;
+    use crate::{Result, UniqueKeyBuilder};
+
+    fn project(streamlets: &[&str]) -> Result<Project> {
+        let mut project = Project::new(Name::try_new("proj")?);
+        project.add_lib(Library::from_builder(
+            Name::try_new("lib")?,
+            UniqueKeyBuilder::new(),
+            UniqueKeyBuilder::new().with_items(
+                streamlets
+                    .iter()
+                    .map(|s| streamlet(s).unwrap().1)
+                    .collect::<Vec<Streamlet>>(),
+            ),
+        )?)?;
+        Ok(project)
+    }
+
+    #[test]
+    fn diff_projects() -> Result<()> {
+        let old = project(&[
+            "Streamlet a (x : in Stream<Group<p: Bits<8>, q: Bits<2>>, d=1>, y : out Null)",
+            "Streamlet b (x : in Null)",
+            "Streamlet c (x : in Stream<Union<p: Bits<1>, q: Null>>, y : in Null)",
+        ])?;
+        let new = project(&[
+            "Streamlet a (x : in Stream<Group<p: Bits<16>, r: Null>, d=2, u=Bits<1>>, y : in Null)",
+            "Streamlet c (x : in Stream<Union<q: Null, p: Bits<1>>>, z : in Null)",
+            "Streamlet d (x : in Null)",
+        ])?;
+        assert!(diff(&old, &old).is_empty());
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes.iter().map(|c| c.to_string()).collect::<Vec<_>>(),
+            vec![
+                "changed lib.a.x.p: Bits<8> -> Bits<16>",
+                "removed lib.a.x.q: Bits<2>",
+                "added lib.a.x.r: Null",
+                "changed lib.a.x.d: 1 -> 2",
+                "added lib.a.x.user: Bits<1>",
+                "changed mode of lib.a.y: out -> in",
+                "removed streamlet lib.b",
+                "changed lib.c.x: Union<p: Bits<1>, q: Null> -> Union<q: Null, p: Bits<1>>",
+                "removed interface lib.c.y",
+                "added interface lib.c.z",
+                "added streamlet lib.d",
+            ]
+        );
+        assert!(!changes.last().unwrap().is_breaking());
+        assert!(changes.first().unwrap().is_breaking());
+        Ok(())
+    }
+}
@@ -0,0 +1,458 @@
+//! Interoperability with Fletcher.
+//!
+//! [Fletcher] generates hardware interfaces to Arrow record batches from
+//! Arrow schemas, of which the metadata describes how the kernel accesses
+//! them. This module converts such schemas into a [Streamlet], such that
+//! Fletcher designs can be migrated to a project and generated with its
+//! back-ends, and converts streamlets back into schemas.
+//!
+//! Every field of a schema becomes an interface named
+//! `<schema>_<field>`, which carries a stream of the values of the field
+//! in the record batch, with one dimension for the batch:
+//!
+//! | Arrow type                            | Logical type                         |
+//! |---------------------------------------|--------------------------------------|
+//! | `Null`                                | `Null`                               |
+//! | `Boolean`                             | `Bits<1>`                            |
+//! | fixed-width types                     | `Bits<n>` of their width             |
+//! | `FixedSizeBinary(n)`                  | `Bits<8n>`                           |
+//! | `Utf8`, `Binary` and large variants   | `Stream<Bits<8>, d=1>`               |
+//! | `List<T>`, `LargeList<T>`             | `Stream<T, d=1>`                     |
+//! | `Struct<...>`                         | `Group<...>`                         |
+//! | nullable `T`                          | `Union<null: Null, value: T>`        |
+//!
+//! The conversion back into Arrow types is the inverse of this mapping,
+//! where `Bits<n>` of 8 to 64 bits becomes an unsigned integer, other
+//! multiples of 8 bits become `FixedSizeBinary`, and byte streams become
+//! `Binary`.
+//!
+//! The following Fletcher metadata is used:
+//! - `fletcher_name` on a schema: the name of the record batch,
+//! - `fletcher_mode` on a schema: `read` for interfaces that are inputs of
+//!   the kernel (the default), or `write` for outputs,
+//! - `fletcher_epc` on a field: the elements per cycle, i.e. the
+//!   throughput of the stream, and
+//! - `fletcher_ignore` on a field: `true` if the field is not accessed.
+//!
+//! [Fletcher]: https://github.com/abs-tudelft/fletcher
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use arrow_ipc::convert::try_schema_from_ipc_buffer;
+use arrow_ipc::reader::FileReader;
+use arrow_ipc::writer::{write_message, DictionaryTracker, IpcDataGenerator, IpcWriteOptions};
+use arrow_schema::{DataType, Field, Schema};
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::{Interface, Mode, Streamlet};
+use crate::logical::{Direction, LogicalType, Stream, Synchronicity};
+use crate::physical::Complexity;
+use crate::{Error, Identify, Name, PositiveReal, Result, UniqueKeyBuilder};
+
+/// Schema metadata key of the name of a record batch.
+pub const FLETCHER_NAME: &str = "fletcher_name";
+/// Schema metadata key of the access mode of a record batch.
+pub const FLETCHER_MODE: &str = "fletcher_mode";
+/// Field metadata key of the elements per cycle of a field.
+pub const FLETCHER_EPC: &str = "fletcher_epc";
+/// Field metadata key that excludes a field from the kernel interface.
+pub const FLETCHER_IGNORE: &str = "fletcher_ignore";
+
+/// The name of the field of the Union a nullable type is converted into.
+const VALUE: &str = "value";
+
+/// Returns an error for an Arrow error, in the context of a schema.
+fn arrow_error(e: impl std::fmt::Display) -> Error {
+    Error::ParsingError(format!("Invalid Arrow schema: {}", e))
+}
+
+/// Read an Arrow schema, e.g. of a Fletcher record batch, from a serialized
+/// schema message, an IPC stream or an IPC file.
+pub fn read_schema(bytes: &[u8]) -> Result<Schema> {
+    if bytes.starts_with(b"ARROW1") {
+        FileReader::try_new(Cursor::new(bytes), None)
+            .map(|reader| reader.schema().as_ref().clone())
+            .map_err(arrow_error)
+    } else {
+        try_schema_from_ipc_buffer(bytes).map_err(arrow_error)
+    }
+}
+
+/// Serialize an Arrow schema as a schema message.
+pub fn write_schema(schema: &Schema) -> Result<Vec<u8>> {
+    let options = IpcWriteOptions::default();
+    let encoded = IpcDataGenerator::default().schema_to_bytes_with_dictionary_tracker(
+        schema,
+        &mut DictionaryTracker::new(false),
+        &options,
+    );
+    let mut result = Vec::new();
+    write_message(&mut result, encoded, &options).map_err(arrow_error)?;
+    Ok(result)
+}
+
+/// Returns a stream of the given data type with one dimension.
+fn sequence(data: LogicalType, throughput: PositiveReal) -> LogicalType {
+    LogicalType::Stream(Stream::new(
+        data,
+        throughput,
+        1,
+        Synchronicity::Sync,
+        Complexity::default(),
+        Direction::Forward,
+        None,
+        false,
+    ))
+}
+
+/// Convert an Arrow field into the logical type of its values.
+pub fn logical_type(field: &Field) -> Result<LogicalType> {
+    let bits = |count: usize| LogicalType::try_new_bits(count as u32);
+    let one = PositiveReal::new(1.).unwrap();
+    let typ = match field.data_type() {
+        DataType::Null => return Ok(LogicalType::Null),
+        DataType::Boolean => bits(1)?,
+        DataType::FixedSizeBinary(bytes) => bits(*bytes as usize * 8)?,
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Binary | DataType::LargeBinary => {
+            sequence(bits(8)?, one)
+        }
+        DataType::List(item) | DataType::LargeList(item) => sequence(logical_type(item)?, one),
+        DataType::Struct(fields) => LogicalType::try_new_group(
+            fields
+                .iter()
+                .map(|f| Ok((Name::try_new(f.name())?, logical_type(f)?)))
+                .collect::<Result<Vec<_>>>()?,
+        )?,
+        other => match other.primitive_width() {
+            Some(bytes) => bits(bytes * 8)?,
+            None => {
+                return Err(Error::InvalidArgument(format!(
+                    "field {} has unsupported Arrow type {}",
+                    field.name(),
+                    other
+                )))
+            }
+        },
+    };
+    if field.is_nullable() {
+        LogicalType::try_new_union(vec![("null", LogicalType::Null), (VALUE, typ)])
+    } else {
+        Ok(typ)
+    }
+}
+
+/// Convert the logical type of the values of a field into an Arrow field.
+pub fn field(name: &str, typ: &LogicalType) -> Result<Field> {
+    let unsupported = || {
+        Error::InvalidArgument(format!(
+            "type {} of {} cannot be represented in Arrow",
+            typ, name
+        ))
+    };
+    let (data_type, nullable) = match typ {
+        LogicalType::Null => (DataType::Null, true),
+        LogicalType::Bits(bits) => match bits.get() {
+            1 => (DataType::Boolean, false),
+            8 => (DataType::UInt8, false),
+            16 => (DataType::UInt16, false),
+            32 => (DataType::UInt32, false),
+            64 => (DataType::UInt64, false),
+            n if n % 8 == 0 => (
+                DataType::FixedSizeBinary(i32::try_from(n / 8).map_err(|_| unsupported())?),
+                false,
+            ),
+            _ => return Err(unsupported()),
+        },
+        LogicalType::Group(group) => (
+            DataType::Struct(
+                group
+                    .iter()
+                    .map(|(name, typ)| field(name, typ))
+                    .collect::<Result<Vec<_>>>()?
+                    .into(),
+            ),
+            false,
+        ),
+        LogicalType::Union(union) => {
+            let fields = union.iter().collect::<Vec<_>>();
+            match fields.as_slice() {
+                [(_, LogicalType::Null), (_, value)] if !value.is_null() => {
+                    return Ok(field(name, value)?.with_nullable(true));
+                }
+                _ => return Err(unsupported()),
+            }
+        }
+        LogicalType::Stream(stream) if stream.dimensionality() == 1 => match stream.data() {
+            LogicalType::Bits(bits) if bits.get() == 8 => (DataType::Binary, false),
+            data => (DataType::List(Arc::new(field("item", data)?)), false),
+        },
+        _ => return Err(unsupported()),
+    };
+    Ok(Field::new(name, data_type, nullable))
+}
+
+/// Convert the schemas of the record batches of a Fletcher kernel into a
+/// streamlet with the given name.
+///
+/// Returns an error if a schema has no name, a field has a type that is
+/// not supported, or names are not valid.
+pub fn streamlet(name: Name, schemas: &[Schema]) -> Result<Streamlet> {
+    let mut interfaces = vec![];
+    for schema in schemas {
+        let batch = schema.metadata.get(FLETCHER_NAME).ok_or_else(|| {
+            Error::InvalidArgument(format!("schema has no {} metadata", FLETCHER_NAME))
+        })?;
+        let mode = match schema.metadata.get(FLETCHER_MODE).map(String::as_str) {
+            None | Some("read") => Mode::In,
+            Some("write") => Mode::Out,
+            Some(other) => {
+                return Err(Error::InvalidArgument(format!(
+                    "schema {} has invalid {}: {}",
+                    batch, FLETCHER_MODE, other
+                )))
+            }
+        };
+        for field in schema.fields().iter() {
+            let metadata = field.metadata();
+            if metadata.get(FLETCHER_IGNORE).map(String::as_str) == Some("true") {
+                continue;
+            }
+            let epc = match metadata.get(FLETCHER_EPC) {
+                Some(epc) => epc
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(|epc| PositiveReal::new(epc).ok())
+                    .ok_or_else(|| {
+                        Error::InvalidArgument(format!(
+                            "field {} has invalid {}: {}",
+                            field.name(),
+                            FLETCHER_EPC,
+                            epc
+                        ))
+                    })?,
+                None => PositiveReal::new(1.).unwrap(),
+            };
+            interfaces.push(Interface::try_new(
+                format!("{}_{}", batch, field.name()).as_str(),
+                mode,
+                sequence(logical_type(field)?, epc),
+                Some(&format!(
+                    "Field {} of record batch {}.",
+                    field.name(),
+                    batch
+                )),
+            )?);
+        }
+    }
+    Streamlet::from_builder(
+        name,
+        UniqueKeyBuilder::new().with_items(interfaces),
+        Some("Fletcher kernel."),
+    )
+}
+
+/// Convert the interfaces of a streamlet into the schemas of a Fletcher
+/// kernel: a `read` schema named `<streamlet>_in` with the input
+/// interfaces, and a `write` schema named `<streamlet>_out` with the output
+/// interfaces, if any.
+///
+/// Returns an error if an interface is not a stream with one dimension of
+/// values that can be represented in Arrow.
+pub fn schemas(streamlet: &Streamlet) -> Result<Vec<Schema>> {
+    let mut result = vec![];
+    for (mode, suffix, access) in &[(Mode::In, "in", "read"), (Mode::Out, "out", "write")] {
+        let mut fields = vec![];
+        for interface in streamlet.interfaces().filter(|i| i.mode() == *mode) {
+            let stream = match interface.typ() {
+                LogicalType::Stream(stream) if stream.dimensionality() == 1 => stream,
+                typ => {
+                    return Err(Error::InvalidArgument(format!(
+                        "interface {} of type {} is not a stream with one dimension",
+                        interface.identifier(),
+                        typ
+                    )))
+                }
+            };
+            let mut field = field(interface.identifier(), stream.data())?;
+            let epc = stream.throughput().get();
+            if epc != 1. {
+                let mut metadata = HashMap::new();
+                metadata.insert(FLETCHER_EPC.to_string(), epc.to_string());
+                field = field.with_metadata(metadata);
+            }
+            fields.push(field);
+        }
+        if !fields.is_empty() {
+            let mut metadata = HashMap::new();
+            metadata.insert(
+                FLETCHER_NAME.to_string(),
+                format!("{}_{}", streamlet.identifier(), suffix),
+            );
+            metadata.insert(FLETCHER_MODE.to_string(), access.to_string());
+            result.push(Schema::new(fields).with_metadata(metadata));
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(name: &str, mode: &str, fields: Vec<Field>) -> Schema {
+        let mut metadata = HashMap::new();
+        metadata.insert(FLETCHER_NAME.to_string(), name.to_string());
+        metadata.insert(FLETCHER_MODE.to_string(), mode.to_string());
+        Schema::new(fields).with_metadata(metadata)
+    }
+
+    #[test]
+    fn import() -> Result<()> {
+        let mut epc = HashMap::new();
+        epc.insert(FLETCHER_EPC.to_string(), "4".to_string());
+        let mut ignore = HashMap::new();
+        ignore.insert(FLETCHER_IGNORE.to_string(), "true".to_string());
+        let input = batch(
+            "src",
+            "read",
+            vec![
+                Field::new("number", DataType::Int64, false).with_metadata(epc),
+                Field::new("name", DataType::Utf8, true),
+                Field::new("skipped", DataType::Int8, false).with_metadata(ignore),
+                Field::new(
+                    "points",
+                    DataType::List(Arc::new(Field::new(
+                        "item",
+                        DataType::Struct(
+                            vec![
+                                Field::new("x", DataType::Float32, false),
+                                Field::new("y", DataType::FixedSizeBinary(3), false),
+                            ]
+                            .into(),
+                        ),
+                        false,
+                    ))),
+                    false,
+                ),
+            ],
+        );
+        let output = batch(
+            "dst",
+            "write",
+            vec![Field::new("flag", DataType::Boolean, false)],
+        );
+
+        // Schemas survive serialization, both as messages and IPC files.
+        let input = read_schema(&write_schema(&input)?)?;
+        let mut file = vec![];
+        arrow_ipc::writer::FileWriter::try_new(&mut file, &output)
+            .and_then(|mut writer| writer.finish())
+            .map_err(arrow_error)?;
+        let output = read_schema(&file)?;
+        assert!(read_schema(b"not a schema").is_err());
+
+        let kernel = streamlet(Name::try_new("kernel")?, &[input, output])?;
+        let types = kernel
+            .interfaces()
+            .map(|i| (i.identifier().to_string(), i.mode(), i.typ().to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                (
+                    "dst_flag".to_string(),
+                    Mode::Out,
+                    "Stream<Bits<1>, d=1>".to_string()
+                ),
+                (
+                    "src_name".to_string(),
+                    Mode::In,
+                    "Stream<Union<null: Null, value: Stream<Bits<8>, d=1>>, d=1>".to_string()
+                ),
+                (
+                    "src_number".to_string(),
+                    Mode::In,
+                    "Stream<Bits<64>, t=4, d=1>".to_string()
+                ),
+                (
+                    "src_points".to_string(),
+                    Mode::In,
+                    "Stream<Stream<Group<x: Bits<32>, y: Bits<24>>, d=1>, d=1>".to_string()
+                ),
+            ]
+        );
+
+        let unnamed = Schema::new(vec![Field::new("a", DataType::Int8, false)]);
+        assert!(streamlet(Name::try_new("kernel")?, &[unnamed]).is_err());
+        let unsupported = batch(
+            "src",
+            "read",
+            vec![Field::new(
+                "a",
+                DataType::Dictionary(Box::new(DataType::Int8), Box::new(DataType::Utf8)),
+                false,
+            )],
+        );
+        assert!(streamlet(Name::try_new("kernel")?, &[unsupported]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn export() -> Result<()> {
+        let kernel = crate::parser::nom::streamlet(
+            "Streamlet kernel (\
+             a : in Stream<Group<x: Bits<16>, y: Bits<24>>, t=2, d=1>, \
+             b : in Stream<Union<null: Null, value: Stream<Bits<8>, d=1>>, d=1>, \
+             c : out Stream<Stream<Bits<32>, d=1>, d=1>)",
+        )
+        .unwrap()
+        .1;
+        let schemas = schemas(&kernel)?;
+        assert_eq!(schemas.len(), 2);
+        assert_eq!(schemas[0].metadata[FLETCHER_NAME], "kernel_in");
+        assert_eq!(schemas[0].metadata[FLETCHER_MODE], "read");
+        assert_eq!(schemas[1].metadata[FLETCHER_MODE], "write");
+        let a = schemas[0].field_with_name("a").map_err(arrow_error)?;
+        assert_eq!(a.metadata()[FLETCHER_EPC], "2");
+        assert_eq!(
+            a.data_type(),
+            &DataType::Struct(
+                vec![
+                    Field::new("x", DataType::UInt16, false),
+                    Field::new("y", DataType::FixedSizeBinary(3), false),
+                ]
+                .into()
+            )
+        );
+        let b = schemas[0].field_with_name("b").map_err(arrow_error)?;
+        assert!(b.is_nullable());
+        assert_eq!(b.data_type(), &DataType::Binary);
+        let c = schemas[1].field_with_name("c").map_err(arrow_error)?;
+        assert_eq!(
+            c.data_type(),
+            &DataType::List(Arc::new(Field::new("item", DataType::UInt32, false)))
+        );
+
+        // Converting the schemas back results in the same types.
+        let imported = streamlet(Name::try_new("kernel")?, &schemas)?;
+        assert_eq!(
+            imported
+                .get_interface(Name::try_new("kernel_out_c")?)?
+                .typ(),
+            kernel.get_interface(Name::try_new("c")?)?.typ()
+        );
+
+        let invalid = crate::parser::nom::streamlet("Streamlet x (a : in Stream<Bits<3>, d=1>)")
+            .unwrap()
+            .1;
+        assert!(super::schemas(&invalid).is_err());
+        let invalid = crate::parser::nom::streamlet("Streamlet x (a : in Stream<Bits<8>>)")
+            .unwrap()
+            .1;
+        assert!(super::schemas(&invalid).is_err());
+        Ok(())
+    }
+}
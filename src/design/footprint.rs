@@ -0,0 +1,321 @@
+//! Interface footprint and cost estimation.
+//!
+//! This module estimates the number of wires and the number of registers that
+//! the interfaces of a [Streamlet] require once they are lowered to physical
+//! streams. This allows comparing interface design choices (e.g. element
+//! lanes, dimensionality or complexity) quantitatively, before generating any
+//! hardware.
+//!
+//! The register estimate assumes a single register slice per physical stream,
+//! i.e. all signals driven by the source (including `valid`) are registered
+//! once. Asynchronous signals outside of streamspace are never registered.
+
+use std::fmt;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::{IFKey, Interface, Mode, Streamlet, StreamletKey};
+use crate::physical::{Complexity, PhysicalStream};
+use crate::{NonNegative, PathName, WideNonNegative};
+
+/// The complexity levels defined by the specification.
+const COMPLEXITY_LEVELS: std::ops::RangeInclusive<NonNegative> = 1..=8;
+
+/// Footprint of a single physical stream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamFootprint {
+    /// The path name of the physical stream within its interface.
+    path_name: PathName,
+    /// The physical stream.
+    stream: PhysicalStream,
+}
+
+impl StreamFootprint {
+    /// Construct the footprint of a physical stream.
+    pub fn new(path_name: PathName, stream: PhysicalStream) -> Self {
+        StreamFootprint { path_name, stream }
+    }
+
+    /// Returns the path name of the physical stream.
+    pub fn path_name(&self) -> &PathName {
+        &self.path_name
+    }
+
+    /// Returns the physical stream.
+    pub fn physical_stream(&self) -> &PhysicalStream {
+        &self.stream
+    }
+
+    /// Returns the number of wires of this stream, including `valid` and
+    /// `ready`.
+    pub fn wires(&self) -> WideNonNegative {
+        self.stream.bit_count() + 2
+    }
+
+    /// Returns the estimated number of registers required to register this
+    /// stream once. This includes `valid`, but excludes `ready` and custom
+    /// sideband signals driven by the sink.
+    pub fn registers(&self) -> WideNonNegative {
+        self.stream.bit_count() + 1
+            - self
+                .stream
+                .sideband()
+                .iter()
+                .filter(|s| s.reversed())
+                .map(|s| s.bit_count())
+                .sum::<WideNonNegative>()
+    }
+
+    /// Returns the footprint of this stream if it had the given complexity.
+    pub fn with_complexity(&self, complexity: impl Into<Complexity>) -> Self {
+        StreamFootprint {
+            path_name: self.path_name.clone(),
+            stream: PhysicalStream::new(
+                self.stream.element_fields().clone(),
+                self.stream.element_lanes(),
+                self.stream.dimensionality(),
+                complexity,
+                self.stream.user().clone(),
+            ),
+        }
+    }
+
+    /// Returns the footprint of this stream at every major complexity level
+    /// defined by the specification, from lowest to highest.
+    pub fn complexity_levels(&self) -> Vec<StreamFootprint> {
+        COMPLEXITY_LEVELS
+            .map(|level| self.with_complexity(level))
+            .collect()
+    }
+}
+
+/// Footprint of a streamlet interface.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterfaceFootprint {
+    /// The name of the interface.
+    key: IFKey,
+    /// The mode of the interface.
+    mode: Mode,
+    /// The number of asynchronous signal bits outside of streamspace.
+    signals: WideNonNegative,
+    /// The physical streams of the interface.
+    streams: Vec<StreamFootprint>,
+}
+
+impl InterfaceFootprint {
+    /// Returns the name of the interface.
+    pub fn key(&self) -> &IFKey {
+        &self.key
+    }
+
+    /// Returns the mode of the interface.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Returns the number of asynchronous signal bits of the interface.
+    pub fn signals(&self) -> WideNonNegative {
+        self.signals
+    }
+
+    /// Returns an iterator over the footprints of the physical streams of the
+    /// interface.
+    pub fn streams(&self) -> impl Iterator<Item = &StreamFootprint> {
+        self.streams.iter()
+    }
+
+    /// Returns the total number of wires of the interface.
+    pub fn wires(&self) -> WideNonNegative {
+        self.signals
+            + self
+                .streams
+                .iter()
+                .map(|s| s.wires())
+                .sum::<WideNonNegative>()
+    }
+
+    /// Returns the estimated number of registers of the interface.
+    pub fn registers(&self) -> WideNonNegative {
+        self.streams.iter().map(|s| s.registers()).sum()
+    }
+}
+
+impl From<&Interface> for InterfaceFootprint {
+    fn from(interface: &Interface) -> Self {
+        let synthesized = interface.synthesize();
+        InterfaceFootprint {
+            key: interface.key().clone(),
+            mode: interface.mode(),
+            signals: synthesized.signals().map(|(_, b)| b.get()).sum(),
+            streams: synthesized
+                .streams()
+                .map(|(path_name, stream)| StreamFootprint::new(path_name.clone(), stream.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// Footprint of all interfaces of a streamlet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamletFootprint {
+    /// The name of the streamlet.
+    key: StreamletKey,
+    /// The interfaces of the streamlet.
+    interfaces: Vec<InterfaceFootprint>,
+}
+
+impl StreamletFootprint {
+    /// Returns the name of the streamlet.
+    pub fn key(&self) -> &StreamletKey {
+        &self.key
+    }
+
+    /// Returns an iterator over the footprints of the interfaces of the
+    /// streamlet.
+    pub fn interfaces(&self) -> impl Iterator<Item = &InterfaceFootprint> {
+        self.interfaces.iter()
+    }
+
+    /// Returns the total number of wires of the streamlet.
+    pub fn wires(&self) -> WideNonNegative {
+        self.interfaces.iter().map(|i| i.wires()).sum()
+    }
+
+    /// Returns the estimated number of registers of the streamlet.
+    pub fn registers(&self) -> WideNonNegative {
+        self.interfaces.iter().map(|i| i.registers()).sum()
+    }
+}
+
+impl From<&Streamlet> for StreamletFootprint {
+    fn from(streamlet: &Streamlet) -> Self {
+        StreamletFootprint {
+            key: streamlet.key().clone(),
+            interfaces: streamlet
+                .interfaces()
+                .map(|iface| InterfaceFootprint::from(&*iface))
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for StreamletFootprint {
+    /// Display a human-readable report of the footprint, including the wire
+    /// count of every physical stream at each complexity level.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Streamlet {}: {} wires, {} registers",
+            self.key,
+            self.wires(),
+            self.registers()
+        )?;
+        for iface in self.interfaces() {
+            writeln!(
+                f,
+                "  {} ({}): {} wires, {} registers",
+                iface.key(),
+                iface.mode(),
+                iface.wires(),
+                iface.registers()
+            )?;
+            if iface.signals() > 0 {
+                writeln!(f, "    signals: {} wires", iface.signals())?;
+            }
+            for stream in iface.streams() {
+                let name = if stream.path_name().is_empty() {
+                    "stream".to_string()
+                } else {
+                    format!("stream {}", stream.path_name())
+                };
+                writeln!(
+                    f,
+                    "    {} (c={}): {} wires, {} registers",
+                    name,
+                    stream.physical_stream().complexity(),
+                    stream.wires(),
+                    stream.registers()
+                )?;
+                writeln!(
+                    f,
+                    "      wires per complexity: {}",
+                    stream
+                        .complexity_levels()
+                        .iter()
+                        .map(|s| format!("c={}: {}", s.physical_stream().complexity(), s.wires()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Streamlet {
+    /// Estimate the wire and register footprint of the interfaces of this
+    /// streamlet.
+    pub fn footprint(&self) -> StreamletFootprint {
+        self.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryInto;
+
+    use super::*;
+    use crate::logical::LogicalType;
+    use crate::{Name, Result, UniqueKeyBuilder};
+
+    #[test]
+    fn footprint() -> Result<()> {
+        let streamlet = Streamlet::from_builder(
+            Name::try_new("x")?,
+            UniqueKeyBuilder::new().with_items(vec![
+                Interface::try_new(
+                    "a",
+                    Mode::In,
+                    crate::parser::nom::logical_type("Stream<Bits<8>, t=4, d=1, c=7>")?,
+                    None,
+                )?,
+                Interface::try_new(
+                    "b",
+                    Mode::Out,
+                    LogicalType::try_new_group(vec![
+                        ("s", LogicalType::try_new_bits(3)?),
+                        ("t", crate::parser::nom::logical_type("Stream<Bits<16>>")?),
+                    ])?,
+                    None,
+                )?,
+            ]),
+            None,
+        )?;
+
+        let footprint = streamlet.footprint();
+        let a = footprint.interfaces().next().unwrap();
+        let stream = a.streams().next().unwrap();
+        // data 32, last 1, stai 2, endi 2, strb 4, valid, ready
+        assert_eq!(stream.wires(), 43);
+        assert_eq!(stream.registers(), 42);
+        assert_eq!(a.signals(), 0);
+
+        let levels = stream.complexity_levels();
+        assert_eq!(levels.len(), 8);
+        // c=1: data 32, last 1, endi 2, strb 4, valid, ready
+        assert_eq!(levels[0].wires(), 41);
+        assert_eq!(levels[7].wires(), 43);
+
+        let b = footprint.interfaces().nth(1).unwrap();
+        assert_eq!(b.signals(), 3);
+        let stream = b.streams().next().unwrap();
+        assert_eq!(stream.path_name(), &"t".try_into()?);
+        assert_eq!(stream.wires(), 18);
+        assert_eq!(b.wires(), 21);
+        assert_eq!(b.registers(), 17);
+
+        assert_eq!(footprint.wires(), 64);
+        assert_eq!(footprint.registers(), 59);
+        assert!(footprint.to_string().starts_with("Streamlet x: 64 wires"));
+        Ok(())
+    }
+}
@@ -0,0 +1,275 @@
+//! Automatic insertion of adapters between mismatching interfaces.
+//!
+//! Interfaces of which the streams differ only in complexity, throughput or
+//! user fields cannot be connected directly, but only require a component
+//! converting between the two. When enabled, see
+//! [CompositionBuilder::with_adapters](crate::design::implementation::composer::builder::CompositionBuilder::with_adapters),
+//! [connect_adapted] inserts such an adapter, instead of returning an error.
+//!
+//! Adapters are added to the library named [GEN_LIB], with an `in` interface
+//! of the type of the source and an `out` interface of the type of the sink.
+//! The conversions they perform are recorded in their `adapter` metadata,
+//! see [ADAPTER].
+
+use std::fmt;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::implementation::structure::Structure;
+use crate::design::metadata::ADAPTER;
+use crate::design::{Interface, Library, Mode, NodeIFHandle, NodeKey, Project, Streamlet, GEN_LIB};
+use crate::logical::{Group, LogicalType, Stream, Union};
+use crate::{Name, Result, UniqueKeyBuilder};
+
+/// A conversion performed by an adapter.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Adaptation {
+    /// Lowers the complexity of a stream.
+    Complexity,
+    /// Changes the throughput, and thereby the number of element lanes, of a
+    /// stream.
+    Throughput,
+    /// Converts the user fields of a stream.
+    User,
+}
+
+impl fmt::Display for Adaptation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Adaptation::Complexity => write!(f, "complexity"),
+            Adaptation::Throughput => write!(f, "throughput"),
+            Adaptation::User => write!(f, "user"),
+        }
+    }
+}
+
+/// Returns the conversions required for a source interface to drive a sink
+/// interface, or None if the interfaces differ in other ways. Returns an
+/// empty list if the source can drive the sink as is.
+pub fn adaptations(source: &Interface, sink: &Interface) -> Result<Option<Vec<Adaptation>>> {
+    if source.can_drive(sink).is_ok() {
+        return Ok(Some(vec![]));
+    }
+    let mut result = vec![];
+    let conformed = Interface::try_new(
+        source.key().clone(),
+        source.mode(),
+        conform(&source.typ(), &sink.typ(), &mut result)?,
+        None,
+    )?;
+    if conformed.can_drive(sink).is_err() {
+        return Ok(None);
+    }
+    result.sort();
+    result.dedup();
+    Ok(Some(result))
+}
+
+/// Returns the source type, with the complexity, throughput and user fields
+/// of its streams replaced by those of the corresponding streams of the sink
+/// type where they prevent the source from driving the sink, recording the
+/// replacements.
+fn conform(
+    source: &LogicalType,
+    sink: &LogicalType,
+    result: &mut Vec<Adaptation>,
+) -> Result<LogicalType> {
+    Ok(match (source, sink) {
+        (LogicalType::Stream(source), LogicalType::Stream(sink)) => {
+            let data = conform(source.data(), sink.data(), result)?;
+            let mut throughput = source.throughput();
+            if source.throughput() != sink.throughput() {
+                result.push(Adaptation::Throughput);
+                throughput = sink.throughput();
+            }
+            let mut complexity = source.complexity().clone();
+            if source.complexity() > sink.complexity() {
+                result.push(Adaptation::Complexity);
+                complexity = sink.complexity().clone();
+            }
+            let mut user = source.user().cloned();
+            if source.user() != sink.user() {
+                result.push(Adaptation::User);
+                user = sink.user().cloned();
+            }
+            Stream::new(
+                data,
+                throughput,
+                source.dimensionality(),
+                source.synchronicity(),
+                complexity,
+                source.direction(),
+                user,
+                source.keep(),
+            )
+            .with_sideband(source.sideband().to_vec())?
+            .into()
+        }
+        (LogicalType::Group(a), LogicalType::Group(b)) => {
+            match conform_fields(a.iter().collect(), b.iter().collect(), result)? {
+                Some(fields) => LogicalType::from(Group::try_new(fields)?),
+                None => LogicalType::Group(a.clone()),
+            }
+        }
+        (LogicalType::Union(a), LogicalType::Union(b)) => {
+            match conform_fields(a.iter().collect(), b.iter().collect(), result)? {
+                Some(fields) => LogicalType::from(Union::try_new(fields)?),
+                None => LogicalType::Union(a.clone()),
+            }
+        }
+        _ => source.clone(),
+    })
+}
+
+/// Conform the fields of a Group or Union to those of another, or return None
+/// if their names differ.
+fn conform_fields(
+    source: Vec<(&Name, &LogicalType)>,
+    sink: Vec<(&Name, &LogicalType)>,
+    result: &mut Vec<Adaptation>,
+) -> Result<Option<Vec<(Name, LogicalType)>>> {
+    if source.len() != sink.len() || source.iter().zip(&sink).any(|(a, b)| a.0 != b.0) {
+        return Ok(None);
+    }
+    source
+        .into_iter()
+        .zip(sink)
+        .map(|((name, source), (_, sink))| Ok((name.clone(), conform(source, sink, result)?)))
+        .collect::<Result<Vec<_>>>()
+        .map(Some)
+}
+
+/// Connect a source interface to a sink interface, inserting an adapter if
+/// the source cannot drive the sink directly, but only differs from it in
+/// the complexity, throughput or user fields of its streams.
+///
+/// Returns the key of the inserted adapter node, if any, or the error of
+/// [Structure::connect] if the interfaces cannot be adapted.
+pub fn connect_adapted(
+    project: &mut Project,
+    structure: &mut Structure,
+    source: NodeIFHandle,
+    sink: NodeIFHandle,
+) -> Result<Option<NodeKey>> {
+    let err = match structure.connect(project, source.clone(), sink.clone()) {
+        Ok(()) => return Ok(None),
+        Err(err) => err,
+    };
+    let interface = |handle: &NodeIFHandle| -> Result<Interface> {
+        Ok(project
+            .get_streamlet(structure.get_node(&handle.node())?)?
+            .get_interface(handle.iface())?
+            .clone())
+    };
+    let (source_iface, sink_iface) = (interface(&source)?, interface(&sink)?);
+    let kinds = match adaptations(&source_iface, &sink_iface)? {
+        Some(kinds) if !kinds.is_empty() => kinds,
+        _ => return Err(err),
+    };
+
+    let gen = Name::try_new(GEN_LIB)?;
+    if project.get_lib(gen.clone()).is_err() {
+        project.add_lib(Library::new(gen.clone()))?;
+    }
+    let streamlet = structure.streamlet_handle();
+    let node = Name::try_new(format!("{}_{}_adapter", sink.node(), sink.iface()))?;
+    let kinds = kinds
+        .iter()
+        .map(|k| k.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut adapter = Streamlet::from_builder(
+        Name::try_new(format!("{}_{}", streamlet.streamlet(), node))?,
+        UniqueKeyBuilder::new().with_items(vec![
+            Interface::try_new("in", Mode::In, source_iface.typ(), None)?,
+            Interface::try_new("out", Mode::Out, sink_iface.typ(), None)?,
+        ]),
+        Some(&format!(
+            " Adapter of {}, from {}.{} to {}.{} in the structure of {}.{}.",
+            kinds,
+            source.node(),
+            source.iface(),
+            sink.node(),
+            sink.iface(),
+            streamlet.lib(),
+            streamlet.streamlet()
+        )),
+    )?;
+    adapter.metadata_mut().insert(ADAPTER, kinds);
+    let adapter = project.get_lib_mut(gen)?.add_streamlet(adapter)?;
+
+    structure.add_node(node.clone(), adapter)?;
+    structure.connect(
+        project,
+        source,
+        NodeIFHandle::new(node.clone(), Name::try_new("in")?),
+    )?;
+    structure.connect(
+        project,
+        NodeIFHandle::new(node.clone(), Name::try_new("out")?),
+        sink,
+    )?;
+    Ok(Some(node))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::implementation::composer::tests::{handle, iface, sdf_test_proj};
+    use crate::design::StreamletHandle;
+
+    #[test]
+    fn insert_adapters() -> Result<()> {
+        let mut prj = sdf_test_proj(&[
+            "Streamlet top (a : in Stream<Bits<8>, c=4, t=2>, \
+             b : out Stream<Bits<8>, c=1>)",
+            "Streamlet op (a : in Stream<Bits<8>, c=1>, b : out Stream<Bits<8>, c=1>, \
+             w : in Stream<Bits<4>>)",
+        ])?;
+
+        let mut structure = Structure::new(handle("top"));
+        structure.add_node(Name::try_new("op")?, handle("op"))?;
+        assert_eq!(
+            connect_adapted(
+                &mut prj,
+                &mut structure,
+                iface("this", "a"),
+                iface("op", "a")
+            )?,
+            Some(Name::try_new("op_a_adapter")?)
+        );
+        assert_eq!(
+            connect_adapted(
+                &mut prj,
+                &mut structure,
+                iface("op", "b"),
+                iface("this", "b")
+            )?,
+            None
+        );
+        // Interfaces that differ in their element type cannot be adapted.
+        assert!(connect_adapted(
+            &mut prj,
+            &mut structure,
+            iface("this", "a"),
+            iface("op", "w")
+        )
+        .is_err());
+        assert_eq!(structure.edges().count(), 3);
+
+        let adapter = prj.get_streamlet(StreamletHandle {
+            lib: Name::try_new(GEN_LIB)?,
+            streamlet: Name::try_new("top_op_a_adapter")?,
+        })?;
+        assert_eq!(
+            adapter.metadata().get(ADAPTER),
+            Some("complexity, throughput")
+        );
+        assert_eq!(
+            adapter.get_interface(Name::try_new("in")?)?.typ(),
+            prj.get_streamlet(handle("top"))?
+                .get_interface(Name::try_new("a")?)?
+                .typ()
+        );
+        Ok(())
+    }
+}
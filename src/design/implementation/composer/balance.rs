@@ -0,0 +1,354 @@
+//! Latency balancing of structural implementations.
+//!
+//! When paths through the nodes of a structure reconverge at a node, data
+//! travelling over the path with the lowest latency arrives early, and the
+//! paths must be balanced by buffering it. The latencies of the paths
+//! through each streamlet are declared by its metadata, see
+//! [Streamlet::latencies](crate::design::Streamlet::latencies).
+
+use std::collections::HashMap;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::implementation::structure::{Edge, Structure};
+use crate::design::implementation::Implementation;
+use crate::design::metadata::DEPTH;
+use crate::design::{
+    Interface, Library, Mode, NodeIFHandle, NodeKey, Project, Streamlet, StreamletHandle, GEN_LIB,
+};
+use crate::{Error, Name, NamingPolicy, NonNegative, PathName, Result, UniqueKeyBuilder};
+
+/// An edge of a structure of which the data arrives early at its sink,
+/// relative to the data arriving at other inputs of the same node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Imbalance {
+    edge: Edge,
+    cycles: NonNegative,
+}
+
+impl Imbalance {
+    /// Returns the edge that needs to be buffered.
+    pub fn edge(&self) -> &Edge {
+        &self.edge
+    }
+
+    /// Returns the number of clock cycles by which the data of the edge
+    /// arrives early, i.e. the depth of the required buffer.
+    pub fn cycles(&self) -> NonNegative {
+        self.cycles
+    }
+}
+
+/// Compute the imbalances of the edges of a structure.
+///
+/// The inputs of the implemented streamlet arrive at cycle zero. For every
+/// node, the arrival of each output is the latest arrival over the paths
+/// leading to it, and each input on such a path that arrives earlier is
+/// imbalanced. Registered edges are ignored. Returns an error if the edges
+/// of the structure form a cycle without a registered edge.
+pub fn imbalances(project: &Project, structure: &Structure) -> Result<Vec<Imbalance>> {
+    // The cycle at which the data of a source interface arrives.
+    let mut arrivals: HashMap<NodeIFHandle, NonNegative> = HashMap::new();
+    let this = project.get_streamlet(structure.streamlet_handle())?;
+    for input in this.inputs() {
+        arrivals.insert(NodeIFHandle::new(NodeKey::this(), input.key().clone()), 0);
+    }
+
+    // Registered edges carry data to the next iteration of a cycle.
+    let driver = |node: &NodeKey, iface: &Name| {
+        structure.edges().find(|e| {
+            !e.is_registered() && e.sink() == NodeIFHandle::new(node.clone(), iface.clone())
+        })
+    };
+    let mut result = vec![];
+    for node in structure.order()? {
+        let streamlet = structure.get_node(&node)?;
+        let paths = project
+            .get_streamlet(streamlet.clone())?
+            .latencies()?
+            .into_iter()
+            .filter_map(|(input, output, latency)| {
+                driver(&node, &input).map(|edge| {
+                    let arrival = arrivals[&edge.source()];
+                    (edge.clone(), output, arrival + latency)
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut outputs: HashMap<Name, NonNegative> = HashMap::new();
+        for (_, output, arrival) in &paths {
+            let latest = outputs.entry(output.clone()).or_insert(0);
+            *latest = (*latest).max(*arrival);
+        }
+        let mut delays: Vec<(Edge, NonNegative)> = vec![];
+        for (edge, output, arrival) in paths {
+            let delay = outputs[&output] - arrival;
+            match delays.iter_mut().find(|(e, _)| *e == edge) {
+                Some((_, cycles)) => *cycles = (*cycles).max(delay),
+                None => delays.push((edge, delay)),
+            }
+        }
+        result.extend(
+            delays
+                .into_iter()
+                .filter(|(_, cycles)| *cycles > 0)
+                .map(|(edge, cycles)| Imbalance { edge, cycles }),
+        );
+        for output in project.get_streamlet(streamlet)?.outputs() {
+            arrivals.insert(
+                NodeIFHandle::new(node.clone(), output.key().clone()),
+                outputs.get(output.key()).copied().unwrap_or(0),
+            );
+        }
+    }
+    Ok(result)
+}
+
+/// Balance the latency of the paths through the structural implementation
+/// of a streamlet, by inserting buffers on imbalanced edges.
+///
+/// Every buffer is a new streamlet in the library named [GEN_LIB], which is
+/// added to the project if it does not exist, with an `in` and `out`
+/// interface of the type of the buffered sink. Its depth is documented, and
+/// declared as its depth and latency. The buffer is instantiated as a node
+/// named after the sink, e.g. `node_iface_buffer`.
+///
+/// Registered edges that are not driven by an output with a latency of at
+/// least one cycle are buffered first, by a register of depth one, named
+/// e.g. `node_iface_register`. Registered edges are not balanced, as their
+/// data belongs to a later iteration of the cycle they are part of.
+///
+/// Returns the buffered edges, with the depth of their buffers.
+pub fn balance(project: &mut Project, streamlet: StreamletHandle) -> Result<Vec<Imbalance>> {
+    let mut structure = match project
+        .get_streamlet(streamlet.clone())?
+        .get_implementation()
+        .as_deref()
+    {
+        Some(Implementation::Structural(structure)) => structure.clone(),
+        _ => {
+            return Err(Error::ComposerError(format!(
+                "Streamlet {}.{} does not have a structural implementation",
+                streamlet.lib(),
+                streamlet.streamlet()
+            )))
+        }
+    };
+    structure.check_cycles()?;
+
+    let mut buffered = vec![];
+    for edge in structure.edges() {
+        if edge.is_registered() && !is_registered(project, &structure, &edge.source())? {
+            buffered.push(Imbalance {
+                edge: edge.clone(),
+                cycles: 1,
+            });
+        }
+    }
+    for register in &buffered {
+        insert_buffer(project, &mut structure, register, "register")?;
+    }
+    for imbalance in imbalances(project, &structure)? {
+        insert_buffer(project, &mut structure, &imbalance, "buffer")?;
+        buffered.push(imbalance);
+    }
+    if !buffered.is_empty() {
+        project.add_streamlet_impl(streamlet, Implementation::Structural(structure))?;
+    }
+    Ok(buffered)
+}
+
+/// Returns true if the data of a source interface of a structure is
+/// registered, i.e. it is an output with a latency of at least one cycle
+/// from every input.
+fn is_registered(project: &Project, structure: &Structure, source: &NodeIFHandle) -> Result<bool> {
+    if source.node() == NodeKey::this() {
+        return Ok(false);
+    }
+    let latencies = project
+        .get_streamlet(structure.get_node(&source.node())?)?
+        .latencies()?
+        .into_iter()
+        .filter(|(_, output, _)| *output == source.iface())
+        .collect::<Vec<_>>();
+    Ok(!latencies.is_empty() && latencies.iter().all(|(_, _, cycles)| *cycles > 0))
+}
+
+/// Replace an edge of the structure implementing a streamlet by a buffer of
+/// the given depth.
+fn insert_buffer(
+    project: &mut Project,
+    structure: &mut Structure,
+    imbalance: &Imbalance,
+    kind: &str,
+) -> Result<()> {
+    let gen = Name::try_new(GEN_LIB)?;
+    if project.get_lib(gen.clone()).is_err() {
+        project.add_lib(Library::new(gen.clone()))?;
+    }
+    let streamlet = structure.streamlet_handle();
+    let edge = imbalance.edge();
+    let sink = edge.sink();
+    let typ = structure.endpoints(project, edge)?.1.typ();
+    let mut node = format!("{}_{}", sink.node(), sink.iface());
+    if !edge.sink_field().is_empty() {
+        node = format!(
+            "{}_{}",
+            node,
+            edge.sink_field()
+                .render(&NamingPolicy::default().with_path_separator("_"))
+        );
+    }
+    let node = Name::try_new(format!("{}_{}", node, kind))?;
+    let mut buffer = Streamlet::from_builder(
+        Name::try_new(format!("{}_{}", streamlet.streamlet(), node))?,
+        UniqueKeyBuilder::new().with_items(vec![
+            Interface::try_new("in", Mode::In, typ.clone(), None)?,
+            Interface::try_new("out", Mode::Out, typ, None)?,
+        ]),
+        Some(&format!(
+            " FIFO of depth {}, buffering {}.{} in the structure of {}.{}.",
+            imbalance.cycles(),
+            sink.node(),
+            sink.iface(),
+            streamlet.lib(),
+            streamlet.streamlet()
+        )),
+    )?
+    .with_latency("in", "out", imbalance.cycles());
+    buffer
+        .metadata_mut()
+        .insert(DEPTH, imbalance.cycles().to_string());
+    let buffer = project.get_lib_mut(gen)?.add_streamlet(buffer)?;
+
+    structure.remove_edge(edge)?;
+    structure.add_node(node.clone(), buffer)?;
+    structure.connect_fields(
+        project,
+        edge.source(),
+        edge.source_field().clone(),
+        NodeIFHandle::new(node.clone(), Name::try_new("in")?),
+        PathName::new_empty(),
+    )?;
+    structure.add_edge(
+        project,
+        NodeIFHandle::new(node, Name::try_new("out")?),
+        PathName::new_empty(),
+        sink,
+        edge.sink_field().clone(),
+        edge.is_registered(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::implementation::composer::tests::{handle, iface, sdf_test_proj};
+
+    #[test]
+    fn balance_structure() -> Result<()> {
+        let mut prj = sdf_test_proj(&[
+            "Streamlet top (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>)",
+            "/// @latency a->x=1, a->y=4\n\
+             Streamlet split (a : in Stream<Bits<8>>, x : out Stream<Bits<8>>, \
+             y : out Stream<Bits<8>>)",
+            "/// @latency a->b=1\n\
+             Streamlet op (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>)",
+            "Streamlet merge (x : in Stream<Bits<8>>, y : in Stream<Bits<8>>, \
+             b : out Stream<Bits<8>>)",
+        ])?;
+
+        // split.x -> op -> merge.x arrives at cycle 2, split.y -> merge.y at 4.
+        let mut structure = Structure::new(handle("top"));
+        structure.add_node(Name::try_new("split")?, handle("split"))?;
+        structure.add_node(Name::try_new("op")?, handle("op"))?;
+        structure.add_node(Name::try_new("merge")?, handle("merge"))?;
+        structure.connect(&prj, iface("this", "a"), iface("split", "a"))?;
+        structure.connect(&prj, iface("split", "x"), iface("op", "a"))?;
+        structure.connect(&prj, iface("op", "b"), iface("merge", "x"))?;
+        structure.connect(&prj, iface("split", "y"), iface("merge", "y"))?;
+        structure.connect(&prj, iface("merge", "b"), iface("this", "b"))?;
+        prj.add_streamlet_impl(handle("top"), Implementation::Structural(structure))?;
+
+        let resolved = balance(&mut prj, handle("top"))?;
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].edge().sink(), iface("merge", "x"));
+        assert_eq!(resolved[0].cycles(), 2);
+
+        let buffer = prj.get_streamlet(StreamletHandle {
+            lib: Name::try_new(GEN_LIB)?,
+            streamlet: Name::try_new("top_merge_x_buffer")?,
+        })?;
+        assert_eq!(
+            buffer.latencies()?,
+            vec![(Name::try_new("in")?, Name::try_new("out")?, 2)]
+        );
+        match prj
+            .get_streamlet(handle("top"))?
+            .get_implementation()
+            .as_deref()
+        {
+            Some(Implementation::Structural(structure)) => {
+                assert!(imbalances(&prj, structure)?.is_empty());
+                assert!(structure
+                    .edges()
+                    .any(|e| e.sink() == iface("merge_x_buffer", "in")));
+            }
+            _ => unreachable!(),
+        }
+        assert!(crate::design::check(&prj).is_empty());
+        assert!(balance(&mut prj, handle("top"))?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn balance_feedback() -> Result<()> {
+        let mut prj = sdf_test_proj(&[
+            "Streamlet top (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>)",
+            "Streamlet op (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>)",
+            "Streamlet merge (x : in Stream<Bits<8>>, y : in Stream<Bits<8>>, \
+             b : out Stream<Bits<8>>)",
+        ])?;
+
+        // The output of op is fed back into merge.
+        let mut structure = Structure::new(handle("top"));
+        structure.add_node(Name::try_new("merge")?, handle("merge"))?;
+        structure.add_node(Name::try_new("op")?, handle("op"))?;
+        structure.connect(&prj, iface("this", "a"), iface("merge", "x"))?;
+        structure.connect(&prj, iface("merge", "b"), iface("op", "a"))?;
+        structure.connect(&prj, iface("op", "b"), iface("this", "b"))?;
+
+        let mut unregistered = structure.clone();
+        unregistered.connect(&prj, iface("op", "b"), iface("merge", "y"))?;
+        assert_eq!(
+            unregistered.check_cycles().unwrap_err().to_string(),
+            "Composer error: Nodes merge -> op -> merge form a cycle without a registered edge."
+        );
+        assert!(imbalances(&prj, &unregistered).is_err());
+
+        structure.connect_registered(&prj, iface("op", "b"), iface("merge", "y"))?;
+        structure.check_cycles()?;
+        prj.add_streamlet_impl(handle("top"), Implementation::Structural(structure))?;
+
+        let resolved = balance(&mut prj, handle("top"))?;
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].edge().sink(), iface("merge", "y"));
+        assert_eq!(resolved[0].cycles(), 1);
+        match prj
+            .get_streamlet(handle("top"))?
+            .get_implementation()
+            .as_deref()
+        {
+            Some(Implementation::Structural(structure)) => {
+                let feedback = structure
+                    .edges()
+                    .find(|e| e.sink() == iface("merge", "y"))
+                    .unwrap();
+                assert_eq!(feedback.source(), iface("merge_y_register", "out"));
+                assert!(feedback.is_registered());
+            }
+            _ => unreachable!(),
+        }
+        assert!(crate::design::check(&prj).is_empty());
+        assert!(balance(&mut prj, handle("top"))?.is_empty());
+        Ok(())
+    }
+}
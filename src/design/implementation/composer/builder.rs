@@ -0,0 +1,320 @@
+//! Programmatic construction of structural implementations.
+//!
+//! A [CompositionBuilder] instantiates streamlets, optionally with parameter
+//! values, and connects their interfaces. Instantiating a streamlet returns a
+//! [Node], through which the interfaces of the node are obtained, such that
+//! connections can only be made between nodes of the composition and
+//! interfaces that exist.
+//!
+//! # Example
+//! ```
+//! use tydi::Name;
+//! use tydi::design::{Library, Project};
+//! use tydi::design::implementation::composer::builder::CompositionBuilder;
+//! use tydi::parser::nom::streamlet;
+//!
+//! let mut project = Project::new(Name::try_new("proj")?);
+//! let lib = project.add_lib(Library::try_new(
+//!     Name::try_new("lib")?,
+//!     vec![],
+//!     vec![
+//!         streamlet("Streamlet top (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>)").unwrap().1,
+//!         streamlet("Streamlet op (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>)").unwrap().1,
+//!     ],
+//! )?)?;
+//! let top = project.resolve_streamlet(lib.clone(), Name::try_new("top")?)?;
+//! let op = project.resolve_streamlet(lib, Name::try_new("op")?)?;
+//!
+//! let mut builder = CompositionBuilder::try_new(&mut project, top)?;
+//! let this = builder.this();
+//! let op = builder.instantiate("op", op)?;
+//! builder.connect(this.io("a")?, op.io("a")?)?;
+//! builder.connect(op.io("b")?, this.io("b")?)?;
+//! builder.finish()?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::design::implementation::composer::adapter::connect_adapted;
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::implementation::structure::Structure;
+use crate::design::implementation::Implementation;
+use crate::design::{IFKey, Library, NodeIFHandle, NodeKey, Project, StreamletHandle, GEN_LIB};
+use crate::{Error, Name, NonNegative, PathName, Result};
+
+/// A node of a composition under construction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    key: NodeKey,
+    streamlet: StreamletHandle,
+    interfaces: Vec<IFKey>,
+}
+
+impl Node {
+    /// Returns the key of this node.
+    pub fn key(&self) -> &NodeKey {
+        &self.key
+    }
+
+    /// Returns the streamlet instantiated by this node.
+    pub fn streamlet(&self) -> &StreamletHandle {
+        &self.streamlet
+    }
+
+    /// Returns a handle to an interface of this node, or an error if the
+    /// streamlet of this node has no such interface.
+    ///
+    /// Elements of interface arrays can be addressed by index, e.g. `req[2]`.
+    pub fn io(&self, iface: &str) -> Result<NodeIFHandle> {
+        let name = iface
+            .strip_suffix(']')
+            .and_then(|iface| iface.split_once('['))
+            .and_then(|(array, index)| {
+                let index = index.trim().parse::<NonNegative>().ok()?;
+                Some(format!("{}_{}", array.trim(), index))
+            })
+            .unwrap_or_else(|| iface.to_string());
+        match self.interfaces.iter().find(|key| *key == &name) {
+            Some(key) => Ok(NodeIFHandle::new(self.key.clone(), key.clone())),
+            None => Err(Error::ComposerError(format!(
+                "Node {} (streamlet {}.{}) has no interface {}",
+                self.key,
+                self.streamlet.lib(),
+                self.streamlet.streamlet(),
+                iface
+            ))),
+        }
+    }
+}
+
+/// Builder of the structural implementation of a streamlet.
+///
+/// The implementation is attached to the streamlet by
+/// [CompositionBuilder::finish].
+pub struct CompositionBuilder<'p> {
+    project: &'p mut Project,
+    structure: Structure,
+    /// Whether to insert adapters between mismatching interfaces.
+    adapters: bool,
+}
+
+impl<'p> CompositionBuilder<'p> {
+    /// Start building the structural implementation of a streamlet of a
+    /// project.
+    pub fn try_new(project: &'p mut Project, streamlet: StreamletHandle) -> Result<Self> {
+        project.get_streamlet(streamlet.clone())?;
+        Ok(CompositionBuilder {
+            project,
+            structure: Structure::new(streamlet),
+            adapters: false,
+        })
+    }
+
+    /// Enable or disable the automatic insertion of adapters by
+    /// [CompositionBuilder::connect], for interfaces that only differ in the
+    /// complexity, throughput or user fields of their streams, see
+    /// [adapter](crate::design::implementation::composer::adapter).
+    /// Disabled by default.
+    pub fn with_adapters(&mut self, enabled: bool) -> &mut Self {
+        self.adapters = enabled;
+        self
+    }
+
+    /// Returns the node of the implemented streamlet itself.
+    pub fn this(&self) -> Node {
+        self.node(NodeKey::this(), self.structure.streamlet_handle())
+            .unwrap()
+    }
+
+    /// Instantiate a streamlet as a node.
+    pub fn instantiate(&mut self, name: &str, streamlet: StreamletHandle) -> Result<Node> {
+        let key = Name::try_new(name)?;
+        self.project.get_streamlet(streamlet.clone())?;
+        self.structure.add_node(key.clone(), streamlet.clone())?;
+        self.node(key, streamlet)
+    }
+
+    /// Instantiate a streamlet with the given parameter values as a node.
+    ///
+    /// The streamlet is elaborated with the values, see
+    /// [Streamlet::elaborate](crate::design::Streamlet::elaborate), and added
+    /// to the library named [GEN_LIB] under a name that includes the values,
+    /// e.g. `fifo_WIDTH_32`, unless it already exists.
+    pub fn instantiate_with(
+        &mut self,
+        name: &str,
+        streamlet: StreamletHandle,
+        parameters: &[(&str, NonNegative)],
+    ) -> Result<Node> {
+        let values = parameters
+            .iter()
+            .map(|(key, value)| Ok((Name::try_new(*key)?, *value)))
+            .collect::<Result<Vec<_>>>()?;
+        let elaborated = self
+            .project
+            .get_streamlet(streamlet.clone())?
+            .elaborate(&values)?;
+        let mut key = streamlet.streamlet().to_string();
+        for (param, value) in &values {
+            key.push_str(&format!("_{}_{}", param, value));
+        }
+        let handle = StreamletHandle {
+            lib: Name::try_new(GEN_LIB)?,
+            streamlet: Name::try_new(key)?,
+        };
+        if self.project.get_lib(handle.lib()).is_err() {
+            self.project.add_lib(Library::new(handle.lib()))?;
+        }
+        if self.project.get_streamlet(handle.clone()).is_err() {
+            let mut elaborated = elaborated;
+            elaborated.set_key(handle.streamlet());
+            self.project
+                .get_lib_mut(handle.lib())?
+                .add_streamlet(elaborated)?;
+        }
+        self.instantiate(name, handle)
+    }
+
+    /// Connect a source interface to a sink interface, see
+    /// [Structure::connect]. If adapters are enabled, an adapter is inserted
+    /// when needed, see [connect_adapted].
+    pub fn connect(&mut self, source: NodeIFHandle, sink: NodeIFHandle) -> Result<&mut Self> {
+        if self.adapters {
+            connect_adapted(self.project, &mut self.structure, source, sink)?;
+        } else {
+            self.structure.connect(self.project, source, sink)?;
+        }
+        Ok(self)
+    }
+
+    /// Connect a source interface to a sink interface through a registered
+    /// edge, see [Structure::connect_registered].
+    pub fn connect_registered(
+        &mut self,
+        source: NodeIFHandle,
+        sink: NodeIFHandle,
+    ) -> Result<&mut Self> {
+        self.structure
+            .connect_registered(self.project, source, sink)?;
+        Ok(self)
+    }
+
+    /// Connect a field of a source interface to a field of a sink interface,
+    /// see [Structure::connect_fields].
+    pub fn connect_fields(
+        &mut self,
+        source: NodeIFHandle,
+        source_field: PathName,
+        sink: NodeIFHandle,
+        sink_field: PathName,
+    ) -> Result<&mut Self> {
+        self.structure
+            .connect_fields(self.project, source, source_field, sink, sink_field)?;
+        Ok(self)
+    }
+
+    /// Returns the structure built so far.
+    pub fn structure(&self) -> &Structure {
+        &self.structure
+    }
+
+    /// Attach the structure to the implemented streamlet, and return a
+    /// handle to that streamlet.
+    pub fn finish(self) -> Result<StreamletHandle> {
+        let handle = self.structure.streamlet_handle();
+        self.project
+            .add_streamlet_impl(handle.clone(), Implementation::Structural(self.structure))?;
+        Ok(handle)
+    }
+
+    fn node(&self, key: NodeKey, streamlet: StreamletHandle) -> Result<Node> {
+        let interfaces = self
+            .project
+            .get_streamlet(streamlet.clone())?
+            .interfaces()
+            .map(|iface| iface.key().clone())
+            .collect();
+        Ok(Node {
+            key,
+            streamlet,
+            interfaces,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::implementation::composer::tests::{handle, sdf_test_proj};
+
+    #[test]
+    fn build_composition() -> Result<()> {
+        let mut prj = sdf_test_proj(&[
+            "Streamlet top (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>)",
+            "Streamlet fifo <WIDTH = 4> (a : in Stream<Bits<WIDTH>>, \
+             b : out Stream<Bits<WIDTH>>)",
+        ])?;
+
+        let mut builder = CompositionBuilder::try_new(&mut prj, handle("top"))?;
+        let this = builder.this();
+        assert_eq!(
+            this.io("c").unwrap_err().to_string(),
+            "Composer error: Node this (streamlet lib.top) has no interface c"
+        );
+        let narrow = builder.instantiate("narrow", handle("fifo"))?;
+        assert!(builder.connect(this.io("a")?, narrow.io("a")?).is_err());
+        // Adapters do not convert the element type.
+        builder.with_adapters(true);
+        assert!(builder.connect(this.io("a")?, narrow.io("a")?).is_err());
+        builder.with_adapters(false);
+        let wide = builder.instantiate_with("wide", handle("fifo"), &[("WIDTH", 8)])?;
+        assert_eq!(wide.streamlet().streamlet().to_string(), "fifo_WIDTH_8");
+        let other = builder.instantiate_with("other", handle("fifo"), &[("WIDTH", 8)])?;
+        assert_eq!(other.streamlet(), wide.streamlet());
+        assert!(builder.instantiate("wide", handle("fifo")).is_err());
+        assert!(builder
+            .instantiate_with("bad", handle("fifo"), &[("DEPTH", 8)])
+            .is_err());
+
+        builder
+            .connect(this.io("a")?, wide.io("a")?)?
+            .connect(wide.io("b")?, other.io("a")?)?
+            .connect(other.io("b")?, this.io("b")?)?;
+        assert_eq!(builder.structure().edges().count(), 3);
+        builder.finish()?;
+
+        let checks = crate::design::check(&prj)
+            .into_iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            checks,
+            vec!["warning: structure of lib.top: node narrow is not connected",]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn build_interface_array() -> Result<()> {
+        let mut prj = sdf_test_proj(&[
+            "Streamlet top (a[2] : in Stream<Bits<8>>, b[2] : out Stream<Bits<8>>)",
+            "Streamlet fifo (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>)",
+        ])?;
+
+        let mut builder = CompositionBuilder::try_new(&mut prj, handle("top"))?;
+        let this = builder.this();
+        assert_eq!(this.io("a[1]")?, this.io("a_1")?);
+        assert_eq!(
+            this.io("a[2]").unwrap_err().to_string(),
+            "Composer error: Node this (streamlet lib.top) has no interface a[2]"
+        );
+        for index in 0..2 {
+            let fifo = builder.instantiate(&format!("fifo_{}", index), handle("fifo"))?;
+            builder
+                .connect(this.io(&format!("a[{}]", index))?, fifo.io("a")?)?
+                .connect(fifo.io("b")?, this.io(&format!("b[{}]", index))?)?;
+        }
+        builder.finish()?;
+        assert!(crate::design::check(&prj).is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,335 @@
+//! Deadlock detection for structural implementations.
+//!
+//! A node with multiple driven inputs joins the data of its inputs: it can
+//! only proceed when all of them have data available. When the paths into a
+//! join start at the same node, the data of the path with the lowest latency
+//! has to wait for the data of the other path, and the path must be able to
+//! buffer the difference, or the source stalls and the join never receives
+//! the data of the other path. Likewise, a cycle through a join must be able
+//! to buffer at least one element.
+//!
+//! Every node on a path buffers as many elements as its declared depth, see
+//! [Streamlet::depth](crate::design::Streamlet::depth), or otherwise one
+//! element per cycle of latency of the path through it.
+
+use std::fmt;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::implementation::structure::Structure;
+use crate::design::{NodeIFHandle, NodeKey, Project};
+use crate::{NonNegative, Result};
+
+/// A potential deadlock in a structure.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deadlock {
+    cycle: Vec<NodeKey>,
+    join: NodeKey,
+    required: NonNegative,
+    capacity: NonNegative,
+}
+
+impl Deadlock {
+    /// Returns the nodes of the offending cycle, in order, starting and
+    /// ending with the same node. For reconvergent paths, the cycle follows
+    /// the path with the highest latency from the forking node to the join,
+    /// and the other path back.
+    pub fn cycle(&self) -> &[NodeKey] {
+        &self.cycle
+    }
+
+    /// Returns the joining node.
+    pub fn join(&self) -> &NodeKey {
+        &self.join
+    }
+
+    /// Returns the number of elements that must be buffered.
+    pub fn required(&self) -> NonNegative {
+        self.required
+    }
+
+    /// Returns the number of elements that can be buffered.
+    pub fn capacity(&self) -> NonNegative {
+        self.capacity
+    }
+}
+
+impl fmt::Display for Deadlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "potential deadlock through {}: join {} requires buffering of {} element(s), found {}",
+            self.cycle
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(" -> "),
+            self.join,
+            self.required,
+            self.capacity
+        )
+    }
+}
+
+/// A path of unregistered edges into a sink.
+#[derive(Clone, Debug)]
+struct Path {
+    /// The node the path starts at.
+    start: NodeKey,
+    /// The nodes in between the start and the sink, in order.
+    nodes: Vec<NodeKey>,
+    latency: NonNegative,
+    capacity: NonNegative,
+}
+
+/// Collect all paths of unregistered edges into a sink.
+fn upstream(
+    project: &Project,
+    structure: &Structure,
+    sink: NodeIFHandle,
+    path: Path,
+    result: &mut Vec<Path>,
+) -> Result<()> {
+    let edge = match structure
+        .edges()
+        .find(|e| !e.is_registered() && e.sink() == sink)
+    {
+        Some(edge) => edge,
+        None => return Ok(()),
+    };
+    let source = edge.source();
+    let node = source.node();
+    if node == NodeKey::this() {
+        result.push(Path {
+            start: node,
+            ..path
+        });
+        return Ok(());
+    }
+    let streamlet = project.get_streamlet(structure.get_node(&node)?)?;
+    let latencies = streamlet.latencies()?;
+    // Paths starting at this node include its latency to the source.
+    let offset = latencies
+        .iter()
+        .filter(|(_, output, _)| *output == source.iface())
+        .map(|(_, _, latency)| *latency)
+        .max()
+        .unwrap_or(0);
+    result.push(Path {
+        start: node.clone(),
+        latency: path.latency + offset,
+        ..path.clone()
+    });
+    if path.nodes.contains(&node) {
+        return Ok(());
+    }
+    let depth = streamlet.depth()?;
+    for (input, output, latency) in latencies {
+        if output == source.iface() {
+            let mut nodes = vec![node.clone()];
+            nodes.extend(path.nodes.iter().cloned());
+            upstream(
+                project,
+                structure,
+                NodeIFHandle::new(node.clone(), input),
+                Path {
+                    start: node.clone(),
+                    nodes,
+                    latency: path.latency + latency,
+                    capacity: path.capacity + depth.unwrap_or(latency),
+                },
+                result,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Detect potential deadlocks in a structure.
+///
+/// Reports reconvergent paths into a join of which the path with the lowest
+/// latency cannot buffer the difference in latency, and cycles through a
+/// join that cannot buffer a single element. The latter only occur when a
+/// registered edge is not yet driven by a register, see
+/// [balance](crate::design::implementation::composer::balance::balance).
+pub fn deadlocks(project: &Project, structure: &Structure) -> Result<Vec<Deadlock>> {
+    let mut result = vec![];
+    let empty = |start: NodeKey| Path {
+        start,
+        nodes: vec![],
+        latency: 0,
+        capacity: 0,
+    };
+
+    for node in structure.order()? {
+        let streamlet = project.get_streamlet(structure.get_node(&node)?)?;
+        let inputs = streamlet
+            .inputs()
+            .map(|i| NodeIFHandle::new(node.clone(), i.key().clone()))
+            .filter(|i| structure.edges().any(|e| e.sink() == *i))
+            .collect::<Vec<_>>();
+        if inputs.len() < 2 {
+            continue;
+        }
+
+        // Reconvergent paths.
+        let mut paths = vec![];
+        for input in &inputs {
+            let mut input_paths = vec![];
+            upstream(
+                project,
+                structure,
+                input.clone(),
+                empty(node.clone()),
+                &mut input_paths,
+            )?;
+            paths.push(input_paths);
+        }
+        for (i, long_paths) in paths.iter().enumerate() {
+            for short_paths in paths.iter().skip(i + 1) {
+                for (long, short) in long_paths.iter().flat_map(|a| {
+                    short_paths.iter().map(move |b| {
+                        if a.latency >= b.latency {
+                            (a, b)
+                        } else {
+                            (b, a)
+                        }
+                    })
+                }) {
+                    if long.start != short.start
+                        || long.nodes.iter().any(|n| short.nodes.contains(n))
+                    {
+                        continue;
+                    }
+                    let required = long.latency - short.latency;
+                    if short.capacity < required {
+                        let mut cycle = vec![long.start.clone()];
+                        cycle.extend(long.nodes.iter().cloned());
+                        cycle.push(node.clone());
+                        cycle.extend(short.nodes.iter().rev().cloned());
+                        cycle.push(short.start.clone());
+                        result.push(Deadlock {
+                            cycle,
+                            join: node.clone(),
+                            required,
+                            capacity: short.capacity,
+                        });
+                    }
+                }
+            }
+        }
+
+        // Cycles closed by a registered edge into this join.
+        for edge in structure
+            .edges()
+            .filter(|e| e.is_registered() && inputs.contains(&e.sink()))
+        {
+            let mut paths = vec![];
+            let source = edge.source();
+            if source.node() == NodeKey::this() {
+                continue;
+            }
+            let streamlet = project.get_streamlet(structure.get_node(&source.node())?)?;
+            let depth = streamlet.depth()?;
+            for (input, output, latency) in streamlet.latencies()? {
+                if output == source.iface() {
+                    upstream(
+                        project,
+                        structure,
+                        NodeIFHandle::new(source.node(), input),
+                        Path {
+                            nodes: vec![source.node()],
+                            capacity: depth.unwrap_or(latency),
+                            ..empty(source.node())
+                        },
+                        &mut paths,
+                    )?;
+                }
+            }
+            for path in paths.into_iter().filter(|p| p.start == node) {
+                if path.capacity < 1 {
+                    let mut cycle = vec![node.clone()];
+                    cycle.extend(path.nodes);
+                    cycle.push(node.clone());
+                    result.push(Deadlock {
+                        cycle,
+                        join: node.clone(),
+                        required: 1,
+                        capacity: path.capacity,
+                    });
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::implementation::composer::balance::balance;
+    use crate::design::implementation::Implementation;
+    use crate::design::implementation::composer::tests::{handle, iface, sdf_test_proj};
+    use crate::Name;
+
+    #[test]
+    fn detect_deadlocks() -> Result<()> {
+        let mut prj = sdf_test_proj(&[
+            "Streamlet top (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>)",
+            "Streamlet split (a : in Stream<Bits<8>>, x : out Stream<Bits<8>>, \
+             y : out Stream<Bits<8>>)",
+            "/// @latency a->b=3\n\
+             Streamlet op (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>)",
+            "Streamlet merge (x : in Stream<Bits<8>>, y : in Stream<Bits<8>>, \
+             b : out Stream<Bits<8>>)",
+        ])?;
+
+        let mut structure = Structure::new(handle("top"));
+        structure.add_node(Name::try_new("split")?, handle("split"))?;
+        structure.add_node(Name::try_new("op")?, handle("op"))?;
+        structure.add_node(Name::try_new("merge")?, handle("merge"))?;
+        structure.connect(&prj, iface("this", "a"), iface("split", "a"))?;
+        structure.connect(&prj, iface("split", "x"), iface("op", "a"))?;
+        structure.connect(&prj, iface("op", "b"), iface("merge", "x"))?;
+        structure.connect(&prj, iface("split", "y"), iface("merge", "y"))?;
+        structure.connect(&prj, iface("merge", "b"), iface("this", "b"))?;
+        assert_eq!(
+            deadlocks(&prj, &structure)?
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "potential deadlock through split -> op -> merge -> split: join merge \
+                 requires buffering of 3 element(s), found 0"
+            ]
+        );
+        prj.add_streamlet_impl(handle("top"), Implementation::Structural(structure))?;
+        balance(&mut prj, handle("top"))?;
+        match prj
+            .get_streamlet(handle("top"))?
+            .get_implementation()
+            .as_deref()
+        {
+            Some(Implementation::Structural(structure)) => {
+                assert!(deadlocks(&prj, structure)?.is_empty())
+            }
+            _ => unreachable!(),
+        }
+
+        // A feedback loop through merge, of which the registered edge is not
+        // driven by a register.
+        let mut structure = Structure::new(handle("top"));
+        structure.add_node(Name::try_new("merge")?, handle("merge"))?;
+        structure.add_node(Name::try_new("split")?, handle("split"))?;
+        structure.connect(&prj, iface("this", "a"), iface("merge", "x"))?;
+        structure.connect(&prj, iface("merge", "b"), iface("split", "a"))?;
+        structure.connect(&prj, iface("split", "x"), iface("this", "b"))?;
+        structure.connect_registered(&prj, iface("split", "y"), iface("merge", "y"))?;
+        let found = deadlocks(&prj, &structure)?;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].cycle().len(), 3);
+        assert_eq!(found[0].join().to_string(), "merge");
+        assert_eq!(found[0].required(), 1);
+        assert_eq!(found[0].capacity(), 0);
+        Ok(())
+    }
+}
@@ -5,8 +5,13 @@ use crate::design::implementation::Implementation;
 use crate::design::{ComponentKey, IFKey, Interface, Mode, Streamlet};
 use crate::Result;
 
+pub mod adapter;
+pub mod balance;
+pub mod builder;
+pub mod deadlock;
 pub mod impl_backend;
 pub mod patterns;
+pub mod throughput;
 
 /// Traits for components in the implementation graph
 pub trait GenHDL {
@@ -45,13 +50,41 @@ pub trait GenericComponent {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use crate::design::*;
     use std::convert::TryFrom;
 
-    use crate::parser::nom::interface;
+    use crate::parser::nom::{interface, streamlet};
     use crate::{Name, Result, UniqueKeyBuilder};
 
+    /// Returns a project with a library "lib" of streamlets in Streamlet
+    /// Definition File syntax.
+    pub(crate) fn sdf_test_proj(streamlets: &[&str]) -> Result<Project> {
+        let mut prj = Project::new(Name::try_new("proj")?);
+        prj.add_lib(Library::try_new(
+            Name::try_new("lib")?,
+            vec![],
+            streamlets
+                .iter()
+                .map(|sdf| streamlet(sdf).unwrap().1)
+                .collect(),
+        )?)?;
+        Ok(prj)
+    }
+
+    /// Returns the handle of a streamlet of the library of [sdf_test_proj].
+    pub(crate) fn handle(name: &str) -> StreamletHandle {
+        StreamletHandle {
+            lib: Name::try_new("lib").unwrap(),
+            streamlet: Name::try_new(name).unwrap(),
+        }
+    }
+
+    /// Returns the handle of an interface of a node of a structure.
+    pub(crate) fn iface(node: &str, iface: &str) -> NodeIFHandle {
+        NodeIFHandle::new(Name::try_new(node).unwrap(), Name::try_new(iface).unwrap())
+    }
+
     pub(crate) fn composition_test_proj() -> Result<Project> {
         let key1 = LibKey::try_new("primitives").unwrap();
         let key2 = LibKey::try_new("compositions").unwrap();
@@ -3,8 +3,12 @@ use std::convert::TryFrom;
 
 use crate::design::implementation::composer::impl_backend::ImplementationBackend;
 use crate::design::implementation::composer::GenericComponent;
+use crate::design::implementation::structure::Structure;
 use crate::design::implementation::Implementation;
-use crate::design::{IFKey, Interface, Mode, Project, Streamlet, StreamletHandle, StreamletKey};
+use crate::design::{
+    IFKey, Interface, Mode, NodeIFHandle, NodeKey, Project, Streamlet, StreamletHandle,
+    StreamletKey,
+};
 use crate::logical::{Direction, LogicalType, Stream, Synchronicity};
 use crate::physical::Complexity;
 use crate::{Error, Name, NonZeroReal, Result, UniqueKeyBuilder};
@@ -344,15 +348,260 @@ impl ImplementationBackend for FilterStreamBackend {
     }
 }
 
+/// Returns handles to the interfaces of a node with the given mode, in order
+/// of their names.
+fn node_interfaces(
+    project: &Project,
+    node: &NodeKey,
+    streamlet: &StreamletHandle,
+    mode: Mode,
+) -> Result<Vec<NodeIFHandle>> {
+    Ok(project
+        .get_streamlet(streamlet.clone())?
+        .interfaces()
+        .filter(|iface| iface.mode() == mode)
+        .map(|iface| NodeIFHandle::new(node.clone(), iface.key().clone()))
+        .collect())
+}
+
+/// Connect each source interface to the sink interface at the same position.
+fn connect_all(
+    structure: &mut Structure,
+    project: &Project,
+    sources: Vec<NodeIFHandle>,
+    sinks: Vec<NodeIFHandle>,
+) -> Result<()> {
+    if sources.len() != sinks.len() {
+        return Err(Error::ComposerError(format!(
+            "Cannot connect {} source interface(s) to {} sink interface(s)",
+            sources.len(),
+            sinks.len()
+        )));
+    }
+    for (source, sink) in sources.into_iter().zip(sinks) {
+        structure.connect(project, source, sink)?;
+    }
+    Ok(())
+}
+
+/// Instantiate streamlets as nodes with the given prefix, and return the
+/// handles to their inputs and outputs, each in order of the nodes.
+fn add_nodes(
+    structure: &mut Structure,
+    project: &Project,
+    prefix: &str,
+    streamlets: &[StreamletHandle],
+) -> Result<(Vec<NodeIFHandle>, Vec<NodeIFHandle>)> {
+    let (mut inputs, mut outputs) = (vec![], vec![]);
+    for (index, streamlet) in streamlets.iter().enumerate() {
+        let node = Name::try_new(format!("{}{}", prefix, index))?;
+        structure.add_node(node.clone(), streamlet.clone())?;
+        inputs.extend(node_interfaces(project, &node, streamlet, Mode::In)?);
+        outputs.extend(node_interfaces(project, &node, streamlet, Mode::Out)?);
+    }
+    Ok((inputs, outputs))
+}
+
+/// Construct a structural implementation of a streamlet that instantiates
+/// the given streamlets as a chain of nodes named `stage0`, `stage1`, etc.
+///
+/// The inputs of the implemented streamlet drive the inputs of the first
+/// stage, the outputs of each stage drive the inputs of the next stage, and
+/// the outputs of the last stage drive the outputs of the implemented
+/// streamlet. Interfaces are connected in order of their names, so the
+/// number of connected interfaces must match.
+pub fn chain(
+    project: &Project,
+    streamlet: StreamletHandle,
+    stages: &[StreamletHandle],
+) -> Result<Structure> {
+    if stages.is_empty() {
+        return Err(Error::ComposerError(
+            "A chain requires at least one stage".to_string(),
+        ));
+    }
+    let mut structure = Structure::new(streamlet.clone());
+    let mut sources = node_interfaces(project, &NodeKey::this(), &streamlet, Mode::In)?;
+    for (index, stage) in stages.iter().enumerate() {
+        let node = Name::try_new(format!("stage{}", index))?;
+        structure.add_node(node.clone(), stage.clone())?;
+        let inputs = node_interfaces(project, &node, stage, Mode::In)?;
+        connect_all(&mut structure, project, sources, inputs)?;
+        sources = node_interfaces(project, &node, stage, Mode::Out)?;
+    }
+    let sinks = node_interfaces(project, &NodeKey::this(), &streamlet, Mode::Out)?;
+    connect_all(&mut structure, project, sources, sinks)?;
+    Ok(structure)
+}
+
+/// Construct a structural implementation of a streamlet that splits its
+/// inputs over parallel branches.
+///
+/// The inputs of the implemented streamlet drive the inputs of a splitter
+/// node named `split`. The outputs of the splitter drive the inputs of the
+/// branches, nodes named `branch0`, `branch1`, etc., and the outputs of the
+/// branches drive the outputs of the implemented streamlet. The splitter
+/// must have as many outputs as the branches together have inputs.
+pub fn fan_out(
+    project: &Project,
+    streamlet: StreamletHandle,
+    splitter: StreamletHandle,
+    branches: &[StreamletHandle],
+) -> Result<Structure> {
+    let mut structure = Structure::new(streamlet.clone());
+    let split = Name::try_new("split")?;
+    structure.add_node(split.clone(), splitter.clone())?;
+    connect_all(
+        &mut structure,
+        project,
+        node_interfaces(project, &NodeKey::this(), &streamlet, Mode::In)?,
+        node_interfaces(project, &split, &splitter, Mode::In)?,
+    )?;
+    let (inputs, outputs) = add_nodes(&mut structure, project, "branch", branches)?;
+    connect_all(
+        &mut structure,
+        project,
+        node_interfaces(project, &split, &splitter, Mode::Out)?,
+        inputs,
+    )?;
+    connect_all(
+        &mut structure,
+        project,
+        outputs,
+        node_interfaces(project, &NodeKey::this(), &streamlet, Mode::Out)?,
+    )?;
+    Ok(structure)
+}
+
+/// Construct a structural implementation of a streamlet that merges the
+/// outputs of parallel branches.
+///
+/// The inputs of the implemented streamlet drive the inputs of the branches,
+/// nodes named `branch0`, `branch1`, etc. The outputs of the branches drive
+/// the inputs of a merger node named `merge`, of which the outputs drive the
+/// outputs of the implemented streamlet. The merger must have as many inputs
+/// as the branches together have outputs.
+pub fn fan_in(
+    project: &Project,
+    streamlet: StreamletHandle,
+    branches: &[StreamletHandle],
+    merger: StreamletHandle,
+) -> Result<Structure> {
+    let mut structure = Structure::new(streamlet.clone());
+    let (inputs, outputs) = add_nodes(&mut structure, project, "branch", branches)?;
+    connect_all(
+        &mut structure,
+        project,
+        node_interfaces(project, &NodeKey::this(), &streamlet, Mode::In)?,
+        inputs,
+    )?;
+    let merge = Name::try_new("merge")?;
+    structure.add_node(merge.clone(), merger.clone())?;
+    connect_all(
+        &mut structure,
+        project,
+        outputs,
+        node_interfaces(project, &merge, &merger, Mode::In)?,
+    )?;
+    connect_all(
+        &mut structure,
+        project,
+        node_interfaces(project, &merge, &merger, Mode::Out)?,
+        node_interfaces(project, &NodeKey::this(), &streamlet, Mode::Out)?,
+    )?;
+    Ok(structure)
+}
+
 #[cfg(test)]
 mod tests {
     use std::convert::TryFrom;
 
     use super::*;
-    use crate::design::implementation::composer::tests::composition_test_proj;
+    use crate::design::implementation::composer::tests::{
+        composition_test_proj, handle, sdf_test_proj,
+    };
     use crate::design::StreamletHandle;
     use crate::{Name, Result};
 
+    fn pattern_test_proj() -> Result<Project> {
+        sdf_test_proj(&[
+            "Streamlet top (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>)",
+            "Streamlet op (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>)",
+            "Streamlet split (a : in Stream<Bits<8>>, x : out Stream<Bits<8>>, y : out Stream<Bits<8>>)",
+            "Streamlet merge (x : in Stream<Bits<8>>, y : in Stream<Bits<8>>, b : out Stream<Bits<8>>)",
+            "Streamlet wide (a : in Stream<Bits<8>>, x : out Stream<Bits<8>>, y : out Stream<Bits<8>>)",
+        ])
+    }
+
+    fn edges(structure: &Structure) -> Vec<String> {
+        structure
+            .edges()
+            .map(|e| {
+                format!(
+                    "{}.{} -> {}.{}",
+                    e.source().node(),
+                    e.source().iface(),
+                    e.sink().node(),
+                    e.sink().iface()
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chain() -> Result<()> {
+        let prj = pattern_test_proj()?;
+        let structure = chain(&prj, handle("top"), &[handle("op"), handle("op")])?;
+        assert_eq!(
+            edges(&structure),
+            vec![
+                "this.a -> stage0.a",
+                "stage0.b -> stage1.a",
+                "stage1.b -> this.b"
+            ]
+        );
+        assert!(chain(&prj, handle("top"), &[]).is_err());
+        assert_eq!(
+            chain(&prj, handle("top"), &[handle("split")])
+                .unwrap_err()
+                .to_string(),
+            "Composer error: Cannot connect 2 source interface(s) to 1 sink interface(s)"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fan() -> Result<()> {
+        let prj = pattern_test_proj()?;
+        let structure = fan_out(
+            &prj,
+            handle("wide"),
+            handle("split"),
+            &[handle("op"), handle("op")],
+        )?;
+        assert_eq!(
+            edges(&structure),
+            vec![
+                "this.a -> split.a",
+                "split.x -> branch0.a",
+                "split.y -> branch1.a",
+                "branch0.b -> this.x",
+                "branch1.b -> this.y"
+            ]
+        );
+        let structure = fan_in(&prj, handle("top"), &[handle("split")], handle("merge"))?;
+        assert_eq!(
+            edges(&structure),
+            vec![
+                "this.a -> branch0.a",
+                "branch0.x -> merge.x",
+                "branch0.y -> merge.y",
+                "merge.b -> this.b"
+            ]
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_map() -> Result<()> {
         let prj = composition_test_proj()?;
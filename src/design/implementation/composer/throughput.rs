@@ -0,0 +1,269 @@
+//! Throughput analysis of structural implementations.
+//!
+//! The throughput of an interface, in elements per clock cycle, is limited by
+//! the throughput of the streams of its logical type, and by the throughput
+//! declared by its streamlet, see
+//! [Streamlet::throughputs](crate::design::Streamlet::throughputs). A
+//! streamlet that buffers at most `depth` elements over a path with a latency
+//! of `latency` cycles cannot sustain more than `depth / latency` elements
+//! per cycle. The [throughput] pass propagates these limits from the inputs
+//! to the outputs of a structure, and reports the edges at which the
+//! throughput drops.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::implementation::structure::{Edge, Structure};
+use crate::design::{IFKey, NodeIFHandle, NodeKey, Project, Streamlet};
+use crate::logical::LogicalType;
+use crate::Result;
+
+/// An edge of which the sink cannot sustain the throughput offered by its
+/// source, causing backpressure upstream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bottleneck {
+    edge: Edge,
+    offered: f64,
+    capacity: f64,
+}
+
+impl Bottleneck {
+    /// Returns the edge.
+    pub fn edge(&self) -> &Edge {
+        &self.edge
+    }
+
+    /// Returns the throughput offered by the source of the edge.
+    pub fn offered(&self) -> f64 {
+        self.offered
+    }
+
+    /// Returns the throughput the sink of the edge can sustain.
+    pub fn capacity(&self) -> f64 {
+        self.capacity
+    }
+}
+
+impl fmt::Display for Bottleneck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "bottleneck {}.{} -> {}.{}: offered {}, capacity {}",
+            self.edge.source().node(),
+            self.edge.source().iface(),
+            self.edge.sink().node(),
+            self.edge.sink().iface(),
+            rate(self.offered),
+            rate(self.capacity)
+        )
+    }
+}
+
+/// The result of the throughput analysis of a structure.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Throughput {
+    outputs: Vec<(IFKey, f64)>,
+    bottlenecks: Vec<Bottleneck>,
+}
+
+impl Throughput {
+    /// Returns the end-to-end throughput of every output of the implemented
+    /// streamlet, in elements per cycle. Unbounded throughputs are infinite.
+    pub fn outputs(&self) -> impl Iterator<Item = (&IFKey, f64)> {
+        self.outputs.iter().map(|(key, rate)| (key, *rate))
+    }
+
+    /// Returns the edges at which the throughput drops, in topological order.
+    pub fn bottlenecks(&self) -> impl Iterator<Item = &Bottleneck> {
+        self.bottlenecks.iter()
+    }
+}
+
+impl fmt::Display for Throughput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (output, throughput) in &self.outputs {
+            writeln!(f, "output {}: {}", output, rate(*throughput))?;
+        }
+        for bottleneck in &self.bottlenecks {
+            writeln!(f, "{}", bottleneck)?;
+        }
+        Ok(())
+    }
+}
+
+fn rate(rate: f64) -> String {
+    if rate.is_finite() {
+        format!("{} elements/cycle", rate)
+    } else {
+        "unbounded".to_string()
+    }
+}
+
+/// Returns the throughput of the streams of a logical type, or infinity if
+/// it has none.
+fn type_capacity(typ: &LogicalType) -> f64 {
+    match typ {
+        LogicalType::Stream(stream) => stream.throughput().get(),
+        LogicalType::Group(group) => group
+            .iter()
+            .map(|(_, typ)| type_capacity(typ))
+            .fold(f64::INFINITY, f64::min),
+        LogicalType::Union(union) => union
+            .iter()
+            .map(|(_, typ)| type_capacity(typ))
+            .fold(f64::INFINITY, f64::min),
+        _ => f64::INFINITY,
+    }
+}
+
+/// Returns the throughputs of the interfaces of a streamlet.
+fn capacities(streamlet: &Streamlet) -> Result<HashMap<IFKey, f64>> {
+    let mut result = streamlet
+        .interfaces()
+        .map(|iface| (iface.key().clone(), type_capacity(&iface.typ())))
+        .collect::<HashMap<_, _>>();
+    for (iface, declared) in streamlet.throughputs()? {
+        let capacity = result.get_mut(&iface).unwrap();
+        *capacity = capacity.min(declared);
+    }
+    Ok(result)
+}
+
+/// Analyze the throughput of a structure.
+///
+/// The inputs of the implemented streamlet offer their full throughput. For
+/// every node, the throughput of each output is limited by its own
+/// throughput, by the throughput of every input with a path to it, and by
+/// the depth and latency of that path. Registered edges are ignored. Returns
+/// an error if the edges of the structure form a cycle without a registered
+/// edge.
+pub fn throughput(project: &Project, structure: &Structure) -> Result<Throughput> {
+    let this = project.get_streamlet(structure.streamlet_handle())?;
+    let this_capacities = capacities(this)?;
+    // The throughput offered by every source interface.
+    let mut rates: HashMap<NodeIFHandle, f64> = this
+        .inputs()
+        .map(|input| {
+            (
+                NodeIFHandle::new(NodeKey::this(), input.key().clone()),
+                this_capacities[input.key()],
+            )
+        })
+        .collect();
+    let mut bottlenecks = vec![];
+
+    // Returns the throughput arriving at a sink with the given capacity.
+    let mut arrive = |rates: &HashMap<NodeIFHandle, f64>, sink: NodeIFHandle, capacity: f64| {
+        let edge = structure
+            .edges()
+            .find(|e| !e.is_registered() && e.sink() == sink)?;
+        let offered = rates[&edge.source()];
+        if capacity < offered {
+            bottlenecks.push(Bottleneck {
+                edge: edge.clone(),
+                offered,
+                capacity,
+            });
+        }
+        Some(offered.min(capacity))
+    };
+
+    for node in structure.order()? {
+        let streamlet = project.get_streamlet(structure.get_node(&node)?)?;
+        let capacities = capacities(streamlet)?;
+        let depth = streamlet.depth()?;
+        let inputs = streamlet
+            .inputs()
+            .filter_map(|input| {
+                let sink = NodeIFHandle::new(node.clone(), input.key().clone());
+                arrive(&rates, sink, capacities[input.key()]).map(|r| (input.key().clone(), r))
+            })
+            .collect::<HashMap<_, _>>();
+        let mut outputs = capacities.clone();
+        for (input, output, latency) in streamlet.latencies()? {
+            if let Some(rate) = inputs.get(&input) {
+                let mut limit = *rate;
+                if let (Some(depth), true) = (depth, latency > 0) {
+                    limit = limit.min(depth as f64 / latency as f64);
+                }
+                let capacity = outputs.get_mut(&output).unwrap();
+                *capacity = capacity.min(limit);
+            }
+        }
+        for output in streamlet.outputs() {
+            rates.insert(
+                NodeIFHandle::new(node.clone(), output.key().clone()),
+                outputs[output.key()],
+            );
+        }
+    }
+
+    let outputs = this
+        .outputs()
+        .filter_map(|output| {
+            let sink = NodeIFHandle::new(NodeKey::this(), output.key().clone());
+            arrive(&rates, sink, this_capacities[output.key()]).map(|r| (output.key().clone(), r))
+        })
+        .collect();
+    Ok(Throughput {
+        outputs,
+        bottlenecks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::implementation::composer::tests::{handle, iface, sdf_test_proj};
+    use crate::parser::nom::streamlet;
+    use crate::Name;
+
+    #[test]
+    fn structure_throughput() -> Result<()> {
+        let mut prj = sdf_test_proj(&[
+            "Streamlet top (a : in Stream<Bits<8>, t=4>, b : out Stream<Bits<8>, t=4>)",
+            "/// @throughput b=2\n\
+             Streamlet slow (a : in Stream<Bits<8>, t=4>, b : out Stream<Bits<8>, t=4>)",
+            "/// @depth 1\n\
+             /// @latency a->b=2\n\
+             Streamlet fifo (a : in Stream<Bits<8>, t=4>, b : out Stream<Bits<8>, t=4>)",
+        ])?;
+
+        let mut structure = Structure::new(handle("top"));
+        structure.add_node(Name::try_new("slow")?, handle("slow"))?;
+        structure.add_node(Name::try_new("fifo")?, handle("fifo"))?;
+        structure.connect(&prj, iface("this", "a"), iface("slow", "a"))?;
+        structure.connect(&prj, iface("slow", "b"), iface("fifo", "a"))?;
+        structure.connect(&prj, iface("fifo", "b"), iface("this", "b"))?;
+
+        let result = throughput(&prj, &structure)?;
+        assert_eq!(
+            result.outputs().collect::<Vec<_>>(),
+            vec![(&Name::try_new("b")?, 0.5)]
+        );
+        assert_eq!(result.bottlenecks().count(), 0);
+        assert_eq!(result.to_string(), "output b: 0.5 elements/cycle\n");
+
+        // A sink that cannot keep up with its source.
+        prj.get_lib_mut(Name::try_new("lib")?)?.add_streamlet(
+            streamlet(
+                "/// @throughput a=1\n\
+                 Streamlet sink (a : in Stream<Bits<8>, t=4>, b : out Stream<Bits<8>, t=4>)",
+            )
+            .unwrap()
+            .1,
+        )?;
+        let mut structure = Structure::new(handle("top"));
+        structure.add_node(Name::try_new("sink")?, handle("sink"))?;
+        structure.connect(&prj, iface("this", "a"), iface("sink", "a"))?;
+        structure.connect(&prj, iface("sink", "b"), iface("this", "b"))?;
+        let result = throughput(&prj, &structure)?;
+        assert_eq!(
+            result.to_string(),
+            "output b: 1 elements/cycle\n\
+             bottleneck this.a -> sink.a: offered 4 elements/cycle, capacity 1 elements/cycle\n"
+        );
+        Ok(())
+    }
+}
@@ -1,9 +1,11 @@
 use std::fmt::Debug;
 
 use crate::design::implementation::composer::impl_backend::ImplementationBackend;
+use crate::design::implementation::structure::Structure;
 use crate::design::StreamletHandle;
 
 pub mod composer;
+pub mod structure;
 
 impl PartialEq for Implementation {
     fn eq(&self, other: &Implementation) -> bool {
@@ -15,6 +17,9 @@ impl PartialEq for Implementation {
 #[derive(Debug)]
 pub enum Implementation {
     Backend(Box<dyn ImplementationBackend>),
+    /// A structural implementation, instantiating and connecting other
+    /// streamlets.
+    Structural(Structure),
 }
 
 impl Implementation {
@@ -22,6 +27,7 @@ impl Implementation {
     pub fn streamlet_handle(&self) -> StreamletHandle {
         match &self {
             Implementation::Backend(b) => b.streamlet_handle(),
+            Implementation::Structural(s) => s.streamlet_handle(),
         }
     }
 }
@@ -0,0 +1,715 @@
+//! Structural implementations of streamlets.
+//!
+//! A structural implementation instantiates other streamlets as nodes, and
+//! connects the interfaces of these nodes and of the implemented streamlet
+//! itself through edges. The implemented streamlet is available as the node
+//! with the reserved key [`NodeKey::this`].
+//!
+//! The edges of a structure may form cycles, e.g. to feed back the results
+//! of an iteration, as long as every cycle contains a registered edge, see
+//! [Structure::connect_registered].
+//!
+//! Edges connect whole interfaces, or fields of interfaces with a Group type,
+//! see [Structure::connect_fields]. The fields of a sink that are not driven
+//! by any edge are tied off.
+
+use std::cell::Ref;
+
+use indexmap::IndexMap;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::implementation::Implementation;
+use crate::design::{IFKey, Interface, Mode, NodeIFHandle, NodeKey, Project, StreamletHandle};
+use crate::logical::LogicalType;
+use crate::{Error, PathName, Result};
+
+/// A connection from a source interface to a sink interface.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Edge {
+    source: NodeIFHandle,
+    source_field: PathName,
+    sink: NodeIFHandle,
+    sink_field: PathName,
+    registered: bool,
+}
+
+impl Edge {
+    /// Returns the handle of the interface driving this edge.
+    pub fn source(&self) -> NodeIFHandle {
+        self.source.clone()
+    }
+
+    /// Returns the handle of the interface driven by this edge.
+    pub fn sink(&self) -> NodeIFHandle {
+        self.sink.clone()
+    }
+
+    /// Returns the field of the source interface driving this edge. The
+    /// empty path name refers to the whole interface.
+    pub fn source_field(&self) -> &PathName {
+        &self.source_field
+    }
+
+    /// Returns the field of the sink interface driven by this edge. The empty
+    /// path name refers to the whole interface.
+    pub fn sink_field(&self) -> &PathName {
+        &self.sink_field
+    }
+
+    /// Returns true if this edge connects a field of its source or sink
+    /// interface, rather than the whole interface.
+    pub fn is_partial(&self) -> bool {
+        !self.source_field.is_empty() || !self.sink_field.is_empty()
+    }
+
+    /// Returns true if this edge is registered, i.e. the data of its source
+    /// arrives at its sink at least one clock cycle later.
+    pub fn is_registered(&self) -> bool {
+        self.registered
+    }
+}
+
+/// A structural implementation of a streamlet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Structure {
+    /// The streamlet that is implemented.
+    streamlet: StreamletHandle,
+    /// The instantiated streamlets.
+    nodes: IndexMap<NodeKey, StreamletHandle>,
+    /// The connections between interfaces.
+    edges: Vec<Edge>,
+}
+
+impl Structure {
+    /// Construct an empty structural implementation of a streamlet.
+    pub fn new(streamlet: StreamletHandle) -> Self {
+        Structure {
+            streamlet,
+            nodes: IndexMap::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Returns a handle to the streamlet this structure implements.
+    pub fn streamlet_handle(&self) -> StreamletHandle {
+        self.streamlet.clone()
+    }
+
+    /// Returns an iterator over the nodes of this structure, excluding the
+    /// implemented streamlet itself.
+    pub fn nodes(&self) -> impl Iterator<Item = (&NodeKey, &StreamletHandle)> {
+        self.nodes.iter()
+    }
+
+    /// Returns an iterator over the edges of this structure.
+    pub fn edges(&self) -> impl Iterator<Item = &Edge> {
+        self.edges.iter()
+    }
+
+    /// Returns the streamlet of a node.
+    pub fn get_node(&self, key: &NodeKey) -> Result<StreamletHandle> {
+        if *key == NodeKey::this() {
+            Ok(self.streamlet.clone())
+        } else {
+            self.nodes.get(key).cloned().ok_or_else(|| {
+                Error::ComposerError(format!("Node {} does not exist in structure.", key))
+            })
+        }
+    }
+
+    /// Instantiate a streamlet as a node of this structure.
+    pub fn add_node(&mut self, key: NodeKey, streamlet: StreamletHandle) -> Result<()> {
+        if key == NodeKey::this() || self.nodes.contains_key(&key) {
+            return Err(Error::ComposerError(format!(
+                "Node {} already exists in structure.",
+                key
+            )));
+        }
+        self.nodes.insert(key, streamlet);
+        Ok(())
+    }
+
+    /// Connect a source interface to a sink interface.
+    ///
+    /// The source must be an output of a node or an input of the implemented
+    /// streamlet, and the sink must be an input of a node or an output of the
+    /// implemented streamlet. The logical type of the source must be able to
+    /// drive the logical type of the sink, as defined by
+    /// [Interface::can_drive].
+    pub fn connect(
+        &mut self,
+        project: &Project,
+        source: NodeIFHandle,
+        sink: NodeIFHandle,
+    ) -> Result<()> {
+        self.add_edge(
+            project,
+            source,
+            PathName::new_empty(),
+            sink,
+            PathName::new_empty(),
+            false,
+        )
+    }
+
+    /// Connect a source interface to a sink interface through a registered
+    /// edge, which breaks the cycles of the structure it is part of.
+    ///
+    /// A registered edge must be driven by an output with a latency of at
+    /// least one cycle, see
+    /// [Streamlet::latencies](crate::design::Streamlet::latencies).
+    /// Registers are inserted for registered edges that are not, by
+    /// [balance](crate::design::implementation::composer::balance::balance).
+    pub fn connect_registered(
+        &mut self,
+        project: &Project,
+        source: NodeIFHandle,
+        sink: NodeIFHandle,
+    ) -> Result<()> {
+        self.add_edge(
+            project,
+            source,
+            PathName::new_empty(),
+            sink,
+            PathName::new_empty(),
+            true,
+        )
+    }
+
+    /// Connect a field of a source interface to a field of a sink interface.
+    ///
+    /// Fields are selected by their path name in the Group type of their
+    /// interface, see [LogicalType::field](crate::logical::LogicalType::field),
+    /// where the empty path name selects the whole interface. Fields of
+    /// nested Groups can be selected, but the data of a Stream can only be
+    /// connected as a whole. The type of the source field must be able to
+    /// drive the type of the sink field, like for [Structure::connect].
+    ///
+    /// The fields of a sink may be driven by different sources. Fields that
+    /// are not driven at all are tied off by the generated implementation.
+    pub fn connect_fields(
+        &mut self,
+        project: &Project,
+        source: NodeIFHandle,
+        source_field: PathName,
+        sink: NodeIFHandle,
+        sink_field: PathName,
+    ) -> Result<()> {
+        self.add_edge(project, source, source_field, sink, sink_field, false)
+    }
+
+    pub(crate) fn add_edge(
+        &mut self,
+        project: &Project,
+        source: NodeIFHandle,
+        source_field: PathName,
+        sink: NodeIFHandle,
+        sink_field: PathName,
+        registered: bool,
+    ) -> Result<()> {
+        let source_streamlet = self.get_node(&source.node())?;
+        let sink_streamlet = self.get_node(&sink.node())?;
+        let name = |handle: &NodeIFHandle, field: &PathName, streamlet: &StreamletHandle| {
+            format!(
+                "{}.{}{} (streamlet {}.{})",
+                handle.node(),
+                handle.iface(),
+                if field.is_empty() {
+                    String::new()
+                } else {
+                    format!(".{}", field)
+                },
+                streamlet.lib(),
+                streamlet.streamlet()
+            )
+        };
+        let err = |msg: String| {
+            Error::ComposerError(format!(
+                "Cannot connect {} to {}: {}",
+                name(&source, &source_field, &source_streamlet),
+                name(&sink, &sink_field, &sink_streamlet),
+                msg
+            ))
+        };
+
+        let source_iface = self.interface(project, &source)?;
+        let sink_iface = self.interface(project, &sink)?;
+        if !Self::is_source(&source.node(), source_iface.mode()) {
+            return Err(err(format!(
+                "{}.{} is not a source",
+                source.node(),
+                source.iface()
+            )));
+        }
+        if Self::is_source(&sink.node(), sink_iface.mode()) {
+            return Err(err(format!(
+                "{}.{} is not a sink",
+                sink.node(),
+                sink.iface()
+            )));
+        }
+        let source_iface = source_iface
+            .field(&source_field)
+            .map_err(|e| err(e.to_string()))?;
+        let sink_iface = sink_iface
+            .field(&sink_field)
+            .map_err(|e| err(e.to_string()))?;
+        source_iface
+            .can_drive(&sink_iface)
+            .map_err(|e| err(e.to_string()))?;
+
+        self.edges.push(Edge {
+            source,
+            source_field,
+            sink,
+            sink_field,
+            registered,
+        });
+        Ok(())
+    }
+
+    /// Returns the connected fields of the source and sink interfaces of an
+    /// edge, see [Interface::field].
+    pub fn endpoints(&self, project: &Project, edge: &Edge) -> Result<(Interface, Interface)> {
+        Ok((
+            self.interface(project, &edge.source)?
+                .field(&edge.source_field)?,
+            self.interface(project, &edge.sink)?
+                .field(&edge.sink_field)?,
+        ))
+    }
+
+    /// Remove an edge from this structure.
+    pub(crate) fn remove_edge(&mut self, edge: &Edge) -> Result<()> {
+        match self.edges.iter().position(|e| e == edge) {
+            Some(index) => {
+                self.edges.remove(index);
+                Ok(())
+            }
+            None => Err(Error::ComposerError(format!(
+                "Edge from {}.{} to {}.{} does not exist in structure.",
+                edge.source.node(),
+                edge.source.iface(),
+                edge.sink.node(),
+                edge.sink.iface()
+            ))),
+        }
+    }
+
+    /// Returns an error if the edges of this structure form a cycle that does
+    /// not contain a registered edge.
+    pub fn check_cycles(&self) -> Result<()> {
+        let successors = |node: &NodeKey| {
+            self.edges
+                .iter()
+                .filter(|e| !e.registered && e.source.node() == *node)
+                .map(|e| e.sink.node())
+                .filter(|node| *node != NodeKey::this())
+                .collect::<Vec<_>>()
+        };
+        // Depth-first search, keeping the nodes on the current path.
+        let mut done: Vec<NodeKey> = vec![];
+        for start in self.nodes.keys() {
+            if done.contains(start) {
+                continue;
+            }
+            let mut path: Vec<(NodeKey, Vec<NodeKey>)> = vec![(start.clone(), successors(start))];
+            while let Some((node, next)) = path.last_mut() {
+                match next.pop() {
+                    Some(next) if done.contains(&next) => {}
+                    Some(next) => {
+                        if let Some(index) = path.iter().position(|(n, _)| *n == next) {
+                            return Err(Error::ComposerError(format!(
+                                "Nodes {} -> {} form a cycle without a registered edge.",
+                                path[index..]
+                                    .iter()
+                                    .map(|(n, _)| n.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join(" -> "),
+                                next
+                            )));
+                        }
+                        let successors = successors(&next);
+                        path.push((next, successors));
+                    }
+                    None => {
+                        done.push(node.clone());
+                        path.pop();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the nodes of this structure in topological order, such that
+    /// every node follows the nodes driving it through edges that are not
+    /// registered. Returns an error if such edges form a cycle.
+    pub fn order(&self) -> Result<Vec<NodeKey>> {
+        self.check_cycles()?;
+        let mut pending = self.nodes.keys().cloned().collect::<Vec<_>>();
+        let mut result: Vec<NodeKey> = Vec::with_capacity(pending.len());
+        while !pending.is_empty() {
+            let ready = pending
+                .iter()
+                .position(|node| {
+                    self.edges.iter().all(|e| {
+                        e.registered
+                            || e.sink.node() != *node
+                            || e.source.node() == NodeKey::this()
+                            || result.contains(&e.source.node())
+                    })
+                })
+                .unwrap();
+            result.push(pending.remove(ready));
+        }
+        Ok(result)
+    }
+
+    /// Returns the streamlets instantiated in this structure and, recursively,
+    /// in the structures of those streamlets, each streamlet appearing once,
+    /// in depth-first order.
+    ///
+    /// Returns an error if a streamlet instantiates itself, directly or
+    /// through other streamlets.
+    pub fn hierarchy(&self, project: &Project) -> Result<Vec<StreamletHandle>> {
+        let mut result = Vec::new();
+        self.visit(project, &mut vec![self.streamlet.clone()], &mut result)?;
+        Ok(result)
+    }
+
+    fn visit(
+        &self,
+        project: &Project,
+        path: &mut Vec<StreamletHandle>,
+        result: &mut Vec<StreamletHandle>,
+    ) -> Result<()> {
+        for streamlet in self.nodes.values() {
+            if path.contains(streamlet) {
+                return Err(Error::ComposerError(format!(
+                    "Streamlet {}.{} instantiates itself through {}.",
+                    streamlet.lib(),
+                    streamlet.streamlet(),
+                    path.iter()
+                        .map(|s| format!("{}.{}", s.lib(), s.streamlet()))
+                        .collect::<Vec<_>>()
+                        .join(" -> ")
+                )));
+            }
+            if !result.contains(streamlet) {
+                result.push(streamlet.clone());
+            }
+            if let Some(implementation) = project
+                .get_streamlet(streamlet.clone())?
+                .get_implementation()
+            {
+                if let Implementation::Structural(structure) = implementation.as_ref() {
+                    path.push(streamlet.clone());
+                    structure.visit(project, path, result)?;
+                    path.pop();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace all references to a streamlet that was renamed.
+    pub(crate) fn rename_streamlet(&mut self, old: &StreamletHandle, new: &StreamletHandle) {
+        if self.streamlet == *old {
+            self.streamlet = new.clone();
+        }
+        for streamlet in self.nodes.values_mut() {
+            if streamlet == old {
+                *streamlet = new.clone();
+            }
+        }
+    }
+
+    /// Replace all references to an interface of a streamlet that was
+    /// renamed.
+    pub(crate) fn rename_interface(
+        &mut self,
+        streamlet: &StreamletHandle,
+        old: &IFKey,
+        new: &IFKey,
+    ) {
+        let nodes = std::iter::once(NodeKey::this())
+            .chain(self.nodes.keys().cloned())
+            .filter(|node| self.get_node(node).ok().as_ref() == Some(streamlet))
+            .collect::<Vec<_>>();
+        for edge in self.edges.iter_mut() {
+            for handle in [&mut edge.source, &mut edge.sink].iter_mut() {
+                if handle.iface == *old && nodes.contains(&handle.node) {
+                    handle.iface = new.clone();
+                }
+            }
+        }
+    }
+
+    /// Returns the fields of an interface of a node that are not connected by
+    /// any edge, if the interface is only connected through its fields, see
+    /// [Structure::connect_fields]. Returns nothing if the interface is
+    /// connected as a whole, or not at all.
+    pub fn unconnected_fields(
+        &self,
+        project: &Project,
+        handle: &NodeIFHandle,
+    ) -> Result<Vec<PathName>> {
+        let mut fields = vec![];
+        for edge in &self.edges {
+            if edge.source == *handle {
+                fields.push(edge.source_field.clone());
+            }
+            if edge.sink == *handle {
+                fields.push(edge.sink_field.clone());
+            }
+        }
+        let mut result = vec![];
+        if !fields.is_empty() && fields.iter().all(|field| !field.is_empty()) {
+            let typ = self.interface(project, handle)?.typ();
+            unconnected(&typ, PathName::new_empty(), &fields, &mut result);
+        }
+        Ok(result)
+    }
+
+    /// Returns the interface of a node.
+    fn interface<'a>(
+        &self,
+        project: &'a Project,
+        handle: &NodeIFHandle,
+    ) -> Result<Ref<'a, Interface>> {
+        project
+            .get_streamlet(self.get_node(&handle.node())?)?
+            .get_interface(handle.iface())
+    }
+
+    /// Returns true if an interface with the given mode drives the inside of
+    /// this structure. Inputs of the implemented streamlet act as sources
+    /// inside of the structure.
+    fn is_source(node: &NodeKey, mode: Mode) -> bool {
+        (*node == NodeKey::this()) == (mode == Mode::In)
+    }
+}
+
+/// Collect the fields of a type that are not part of any of the connected
+/// fields, descending into Groups of which some fields are connected.
+fn unconnected(
+    typ: &LogicalType,
+    path: PathName,
+    connected: &[PathName],
+    result: &mut Vec<PathName>,
+) {
+    if typ.is_null() || connected.iter().any(|field| path.starts_with(field)) {
+        return;
+    }
+    match typ {
+        LogicalType::Group(group) if connected.iter().any(|field| field.starts_with(&path)) => {
+            for (name, typ) in group.iter() {
+                let mut field = path.clone();
+                field.push(name.clone());
+                unconnected(typ, field, connected, result);
+            }
+        }
+        _ => result.push(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::implementation::composer::tests::composition_test_proj;
+    use crate::parser::nom::streamlet;
+    use crate::Name;
+
+    #[test]
+    fn connect() -> Result<()> {
+        let mut prj = composition_test_proj()?;
+        let top = StreamletHandle {
+            lib: Name::try_new("compositions")?,
+            streamlet: Name::try_new("Top_level")?,
+        };
+        let handle = |streamlet: &str| -> Result<StreamletHandle> {
+            Ok(StreamletHandle {
+                lib: Name::try_new("primitives")?,
+                streamlet: Name::try_new(streamlet)?,
+            })
+        };
+        let iface = |node: &str, iface: &str| -> Result<NodeIFHandle> {
+            Ok(NodeIFHandle::new(
+                Name::try_new(node)?,
+                Name::try_new(iface)?,
+            ))
+        };
+
+        let mut structure = Structure::new(top.clone());
+        structure.add_node(Name::try_new("magic")?, handle("Magic")?)?;
+        structure.add_node(Name::try_new("op")?, handle("test_op")?)?;
+        assert!(structure
+            .add_node(NodeKey::this(), handle("test_op")?)
+            .is_err());
+
+        structure.connect(&prj, iface("this", "in")?, iface("magic", "in")?)?;
+        structure.connect(&prj, iface("op", "out")?, iface("this", "out")?)?;
+        assert_eq!(structure.edges().count(), 2);
+
+        assert_eq!(
+            structure
+                .connect(&prj, iface("magic", "out")?, iface("op", "in")?)
+                .unwrap_err()
+                .to_string(),
+            "Composer error: Cannot connect magic.out (streamlet primitives.Magic) to op.in \
+             (streamlet primitives.test_op): Interface error: Interface out cannot drive \
+             interface in: Invalid argument: dimensionality mismatch: source has 1, sink has 0"
+        );
+        assert_eq!(
+            structure
+                .connect(&prj, iface("op", "in")?, iface("magic", "in")?)
+                .unwrap_err()
+                .to_string(),
+            "Composer error: Cannot connect op.in (streamlet primitives.test_op) to magic.in \
+             (streamlet primitives.Magic): op.in is not a source"
+        );
+        assert!(structure
+            .connect(&prj, iface("this", "in")?, iface("this", "out")?)
+            .is_err());
+        assert!(structure
+            .connect(&prj, iface("nope", "out")?, iface("op", "in")?)
+            .is_err());
+        assert_eq!(structure.edges().count(), 2);
+
+        prj.add_streamlet_impl(top, Implementation::Structural(structure))?;
+        Ok(())
+    }
+
+    #[test]
+    fn connect_fields() -> Result<()> {
+        let mut prj = Project::new(Name::try_new("proj")?);
+        prj.add_lib(crate::design::Library::try_new(
+            Name::try_new("lib")?,
+            vec![],
+            vec![
+                streamlet(
+                    "Streamlet top (a : in Group<x: Stream<Bits<4>>, y: Stream<Bits<8>>>, \
+                     b : out Group<x: Stream<Bits<4>>, y: Stream<Bits<8>>>)",
+                )
+                .unwrap()
+                .1,
+                streamlet("Streamlet op (a : in Stream<Bits<4>>, b : out Stream<Bits<4>>)")
+                    .unwrap()
+                    .1,
+            ],
+        )?)?;
+        let top = StreamletHandle {
+            lib: Name::try_new("lib")?,
+            streamlet: Name::try_new("top")?,
+        };
+        let iface = |node: &str, iface: &str| {
+            NodeIFHandle::new(Name::try_new(node).unwrap(), Name::try_new(iface).unwrap())
+        };
+        let field = |name: &str| PathName::try_new(vec![name]).unwrap();
+
+        let mut structure = Structure::new(top.clone());
+        structure.add_node(
+            Name::try_new("op")?,
+            StreamletHandle {
+                lib: Name::try_new("lib")?,
+                streamlet: Name::try_new("op")?,
+            },
+        )?;
+        structure.connect_fields(
+            &prj,
+            iface("this", "a"),
+            field("x"),
+            iface("op", "a"),
+            PathName::new_empty(),
+        )?;
+        structure.connect_fields(
+            &prj,
+            iface("op", "b"),
+            PathName::new_empty(),
+            iface("this", "b"),
+            field("x"),
+        )?;
+        assert!(structure.edges().all(|e| e.is_partial()));
+        assert_eq!(
+            structure
+                .connect_fields(
+                    &prj,
+                    iface("this", "a"),
+                    field("y"),
+                    iface("this", "b"),
+                    field("x")
+                )
+                .unwrap_err()
+                .to_string(),
+            "Composer error: Cannot connect this.a.y (streamlet lib.top) to this.b.x \
+             (streamlet lib.top): Interface error: Interface a cannot drive interface b: \
+             Invalid argument: element fields mismatch: field ∅: source has 8 bits, sink has \
+             4 bits"
+        );
+        assert_eq!(
+            structure
+                .connect_fields(
+                    &prj,
+                    iface("this", "a"),
+                    field("z"),
+                    iface("this", "b"),
+                    field("y")
+                )
+                .unwrap_err()
+                .to_string(),
+            "Composer error: Cannot connect this.a.z (streamlet lib.top) to this.b.y \
+             (streamlet lib.top): Interface error: Interface a has no field z"
+        );
+        assert_eq!(
+            structure.unconnected_fields(&prj, &iface("this", "b"))?,
+            vec![field("y")]
+        );
+        assert!(structure
+            .unconnected_fields(&prj, &iface("op", "a"))?
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn hierarchy() -> Result<()> {
+        let mut prj = composition_test_proj()?;
+        let handle = |lib: &str, streamlet: &str| -> Result<StreamletHandle> {
+            Ok(StreamletHandle {
+                lib: Name::try_new(lib)?,
+                streamlet: Name::try_new(streamlet)?,
+            })
+        };
+        let structure = |streamlet: StreamletHandle, node: StreamletHandle| -> Result<Structure> {
+            let mut structure = Structure::new(streamlet);
+            structure.add_node(Name::try_new("inst")?, node)?;
+            Ok(structure)
+        };
+        let (top, magic, op) = (
+            handle("compositions", "Top_level")?,
+            handle("primitives", "Magic")?,
+            handle("primitives", "test_op")?,
+        );
+
+        let top_structure = structure(top.clone(), magic.clone())?;
+        prj.add_streamlet_impl(
+            magic.clone(),
+            Implementation::Structural(structure(magic.clone(), op.clone())?),
+        )?;
+        assert_eq!(
+            top_structure.hierarchy(&prj)?,
+            vec![magic.clone(), op.clone()]
+        );
+
+        prj.add_streamlet_impl(
+            op.clone(),
+            Implementation::Structural(structure(op.clone(), magic)?),
+        )?;
+        assert_eq!(
+            top_structure.hierarchy(&prj).unwrap_err().to_string(),
+            "Composer error: Streamlet primitives.Magic instantiates itself through \
+             compositions.Top_level -> primitives.Magic -> primitives.test_op."
+        );
+        Ok(())
+    }
+}
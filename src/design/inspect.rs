@@ -0,0 +1,139 @@
+//! Inspection of the physical streams of a streamlet.
+//!
+//! This module reports, for every interface of a [Streamlet], the physical
+//! streams its logical type is synthesized into, with the width of every
+//! signal of those streams, and the total bit counts of the interfaces and
+//! the streamlet.
+
+use std::fmt;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::Streamlet;
+use crate::traits::Identify;
+use crate::WideNonNegative;
+
+/// A report of the interfaces and physical streams of a streamlet.
+#[derive(Clone, Copy, Debug)]
+pub struct StreamletInfo<'a> {
+    streamlet: &'a Streamlet,
+}
+
+impl StreamletInfo<'_> {
+    /// Returns the total number of bits of all interfaces of the streamlet.
+    ///
+    /// This includes asynchronous signals and all signals of physical
+    /// streams, except `valid` and `ready`.
+    pub fn bit_count(&self) -> WideNonNegative {
+        self.streamlet
+            .interfaces()
+            .map(|interface| {
+                let synthesized = interface.synthesize();
+                synthesized
+                    .signals()
+                    .map(|(_, b)| b.get() as WideNonNegative)
+                    .sum::<WideNonNegative>()
+                    + synthesized
+                        .streams()
+                        .map(|(_, s)| s.bit_count())
+                        .sum::<WideNonNegative>()
+            })
+            .sum()
+    }
+}
+
+impl fmt::Display for StreamletInfo<'_> {
+    /// Display a human-readable report of the interfaces of the streamlet,
+    /// their physical streams and the widths of their signals.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Streamlet {}", self.streamlet.identifier())?;
+        for interface in self.streamlet.interfaces() {
+            let synthesized = interface.synthesize();
+            writeln!(
+                f,
+                "  {} : {} {}",
+                interface.identifier(),
+                interface.mode(),
+                interface.typ()
+            )?;
+            for (path_name, bits) in synthesized.signals() {
+                if path_name.is_empty() {
+                    writeln!(f, "    signal: {} bits", bits)?;
+                } else {
+                    writeln!(f, "    signal {}: {} bits", path_name, bits)?;
+                }
+            }
+            for (path_name, stream) in synthesized.streams() {
+                writeln!(
+                    f,
+                    "    stream{} ({} lane{}, d={}, c={}): {} bits",
+                    if path_name.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" {}", path_name)
+                    },
+                    stream.element_lanes(),
+                    if stream.element_lanes().get() == 1 {
+                        ""
+                    } else {
+                        "s"
+                    },
+                    stream.dimensionality(),
+                    stream.complexity(),
+                    stream.bit_count()
+                )?;
+                write!(
+                    f,
+                    "      data: {}, last: {}, stai: {}, endi: {}, strb: {}, user: {}",
+                    stream.data_bit_count(),
+                    stream.last_bit_count(),
+                    stream.stai_bit_count(),
+                    stream.endi_bit_count(),
+                    stream.strb_bit_count(),
+                    stream.user_bit_count()
+                )?;
+                if !stream.sideband().is_empty() {
+                    write!(f, ", sideband: {}", stream.sideband_bit_count())?;
+                }
+                writeln!(f)?;
+            }
+        }
+        writeln!(f, "  total: {} bits", self.bit_count())
+    }
+}
+
+impl Streamlet {
+    /// Returns a report of the interfaces of this streamlet and the physical
+    /// streams they are synthesized into.
+    pub fn info(&self) -> StreamletInfo {
+        StreamletInfo { streamlet: self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::nom::streamlet;
+
+    #[test]
+    fn streamlet_info() {
+        let streamlet = streamlet(
+            "Streamlet x (a : in Stream<Bits<8>, t=4, d=1, c=7>, \
+             b : out Group<s: Bits<3>, t: Stream<Bits<16>, u=Bits<2>>>)",
+        )
+        .unwrap()
+        .1;
+        let info = streamlet.info();
+        assert_eq!(info.bit_count(), 41 + 3 + 18);
+        assert_eq!(
+            info.to_string(),
+            "Streamlet x\n\
+             \x20 a : in Stream<Bits<8>, t=4, d=1, c=7>\n\
+             \x20   stream (4 lanes, d=1, c=7): 41 bits\n\
+             \x20     data: 32, last: 1, stai: 2, endi: 2, strb: 4, user: 0\n\
+             \x20 b : out Group<s: Bits<3>, t: Stream<Bits<16>, u=Bits<2>>>\n\
+             \x20   signal s: 3 bits\n\
+             \x20   stream t (1 lane, d=0, c=4): 18 bits\n\
+             \x20     data: 16, last: 0, stai: 0, endi: 0, strb: 0, user: 2\n\
+             \x20 total: 62 bits\n"
+        );
+    }
+}
@@ -6,22 +6,44 @@
 use std::collections::HashMap;
 use std::path::Path;
 
-use log::debug;
+use indexmap::IndexMap;
+use tracing::{debug, debug_span};
 
 use crate::design::implementation::composer::GenericComponent;
+use crate::design::metadata::Metadata;
 use crate::design::param::ParameterStore;
-use crate::design::{LibKey, ParamStoreKey, Streamlet, StreamletHandle, StreamletKey};
+use crate::design::{LibKey, ParamStoreKey, Project, Streamlet, StreamletHandle, StreamletKey};
 use crate::error::Error::{FileIOError, ParsingError};
-use crate::parser::nom::list_of_streamlets;
+use crate::logical::{LogicalType, SynthesisCache};
+use crate::parser::nom::{
+    error_at, imports, library_doc, report, trailing, with_recovery, LibraryContents, Scope,
+};
+use crate::parser::verilog;
 use crate::traits::Identify;
-use crate::{Error, Name, Result, UniqueKeyBuilder};
+use crate::{Document, Error, Name, Result, UniqueKeyBuilder};
 
 /// A collection of Streamlets.
 #[derive(PartialEq, Debug)]
 pub struct Library {
     key: Name,
-    parameter_stores: HashMap<ParamStoreKey, ParameterStore>,
-    streamlets: HashMap<StreamletKey, Streamlet>,
+    /// The libraries of which streamlets are used by this library.
+    imports: Vec<LibKey>,
+    /// The named types of this library, in order of declaration.
+    types: IndexMap<Name, LogicalType>,
+    /// An optional documentation string for the library to be used by back-ends.
+    doc: Option<String>,
+    /// Key/value metadata of the library, such as its author and version.
+    metadata: Metadata,
+    /// The parameter stores of this library, in order of addition.
+    parameter_stores: IndexMap<ParamStoreKey, ParameterStore>,
+    /// The streamlets of this library, in order of addition.
+    streamlets: IndexMap<StreamletKey, Streamlet>,
+}
+
+impl Document for Library {
+    fn doc(&self) -> Option<String> {
+        self.doc.clone()
+    }
 }
 
 impl crate::traits::Identify for Library {
@@ -38,8 +60,12 @@ impl Library {
     pub fn new(key: impl Into<LibKey>) -> Library {
         Library {
             key: key.into(),
-            parameter_stores: HashMap::new(),
-            streamlets: HashMap::new(),
+            imports: Vec::new(),
+            types: IndexMap::new(),
+            doc: None,
+            metadata: Metadata::new(),
+            parameter_stores: IndexMap::new(),
+            streamlets: IndexMap::new(),
         }
     }
 
@@ -63,21 +89,54 @@ impl Library {
     ) -> Result<Self> {
         Ok(Library {
             key: name,
+            imports: Vec::new(),
+            types: IndexMap::new(),
+            doc: None,
+            metadata: Metadata::new(),
             parameter_stores: parameter_stores
                 .finish()?
                 .into_iter()
                 .map(|s| (s.key().clone(), s))
-                .collect::<HashMap<ParamStoreKey, ParameterStore>>(),
+                .collect::<IndexMap<ParamStoreKey, ParameterStore>>(),
             streamlets: streamlets
                 .finish()?
                 .into_iter()
                 .map(|s| (s.key().clone(), s))
-                .collect::<HashMap<StreamletKey, Streamlet>>(),
+                .collect::<IndexMap<StreamletKey, Streamlet>>(),
         })
     }
 
     /// Construct a Library from a Streamlet Definition File.
+    ///
+    /// The libraries imported by the file are recorded, but not resolved;
+    /// see [Project::resolve_streamlet](crate::design::Project::resolve_streamlet).
+    /// Named types of imported libraries are therefore not available; use
+    /// [Library::from_file_in] to parse a file that refers to them.
+    ///
+    /// If the file contains syntax errors, the error lists all of them.
+    ///
+    /// Verilog source files, with a `.v` or `.sv` extension, are parsed with
+    /// [Library::from_verilog] instead.
     pub fn from_file(path: &Path) -> Result<Self> {
+        Library::parse_file(path, |_| Ok(HashMap::new()), &SynthesisCache::new())
+    }
+
+    /// Construct a Library from a Streamlet Definition File, that may refer
+    /// to the named types of the libraries it imports from the given
+    /// project.
+    pub fn from_file_in(path: &Path, project: &Project) -> Result<Self> {
+        Library::parse_file(
+            path,
+            |imports| project.imported_types(imports),
+            project.synthesis(),
+        )
+    }
+
+    fn parse_file(
+        path: &Path,
+        imported_types: impl FnOnce(&[LibKey]) -> Result<HashMap<Name, LogicalType>>,
+        cache: &SynthesisCache,
+    ) -> Result<Self> {
         if path.is_dir() {
             Err(FileIOError(format!(
                 "Expected Streamlet Definition File, got directory: \"{}\"",
@@ -85,40 +144,263 @@ impl Library {
                     .ok_or_else(|| FileIOError("Invalid path.".to_string()))?
             )))
         } else {
+            let _span = debug_span!("parse", file = %path.display()).entered();
             debug!(
                 "Parsing: {}",
                 path.to_str()
                     .ok_or_else(|| FileIOError("Invalid path.".to_string()))?
             );
-            let streamlets: Vec<Streamlet> = list_of_streamlets(
-                std::fs::read_to_string(&path)
-                    .map_err(|e| FileIOError(e.to_string()))?
-                    .as_str(),
-            )
-            .map_err(|e| ParsingError(e.to_string()))?
-            .1;
-            debug!("Parsed streamlets: {}", {
-                let sln: Vec<&str> = streamlets.iter().map(|s| s.identifier()).collect();
-                sln.join(", ")
-            });
-            Library::from_builder(
-                Name::try_new(
-                    path.file_stem()
-                        .ok_or_else(|| FileIOError("Invalid file name.".to_string()))?
-                        .to_str()
-                        .unwrap(),
-                )?,
-                // TODO: No support for parameter groups yet
-                UniqueKeyBuilder::new().with_items(vec![]),
-                UniqueKeyBuilder::new().with_items(streamlets),
+            let input = std::fs::read_to_string(&path).map_err(|e| FileIOError(e.to_string()))?;
+            let key = Name::try_new(
+                path.file_stem()
+                    .ok_or_else(|| FileIOError("Invalid file name.".to_string()))?
+                    .to_str()
+                    .unwrap(),
+            )?;
+            if is_verilog(path) {
+                return Library::parse_verilog(key, &input, cache);
+            }
+            Library::parse_source(key, &path.display().to_string(), &input, imported_types)
+        }
+    }
+
+    /// Construct a Library with the given name from the contents of a
+    /// Streamlet Definition File.
+    ///
+    /// As with [Library::from_file], imported libraries are recorded, but
+    /// their named types are not available; use [Library::from_sdf_in] to
+    /// parse contents that refer to them.
+    pub fn from_sdf(key: LibKey, input: &str) -> Result<Self> {
+        Library::parse_source(key.clone(), key.as_ref(), input, |_| Ok(HashMap::new()))
+    }
+
+    /// Construct a Library with the given name from the contents of a
+    /// Streamlet Definition File, that may refer to the named types of the
+    /// libraries it imports from the given project.
+    pub fn from_sdf_in(key: LibKey, input: &str, project: &Project) -> Result<Self> {
+        Library::parse_source(key.clone(), key.as_ref(), input, |imports| {
+            project.imported_types(imports)
+        })
+    }
+
+    /// Construct a Library with the given name from the contents of a
+    /// Verilog source file, with a blackbox streamlet for every module in the
+    /// source. See [crate::parser::verilog].
+    pub fn from_verilog(key: LibKey, input: &str) -> Result<Self> {
+        Library::parse_verilog(key, input, &SynthesisCache::new())
+    }
+
+    /// Parse the contents of a Verilog source file, synthesizing the stream
+    /// types of the blackbox streamlets with a cache.
+    fn parse_verilog(key: LibKey, input: &str, cache: &SynthesisCache) -> Result<Self> {
+        let streamlets = verilog::modules(input)?
+            .iter()
+            .map(|module| module.blackbox_with(cache))
+            .collect::<Result<Vec<_>>>()?;
+        Library::from_builder(
+            key,
+            UniqueKeyBuilder::new(),
+            UniqueKeyBuilder::new().with_items(streamlets),
+        )
+    }
+
+    /// Parse the contents of a Streamlet Definition File, reporting syntax
+    /// errors as errors in the given source, e.g. the path of the file.
+    fn parse_source(
+        key: LibKey,
+        source: &str,
+        input: &str,
+        imported_types: impl FnOnce(&[LibKey]) -> Result<HashMap<Name, LogicalType>>,
+    ) -> Result<Self> {
+        Library::parse_str(key, input, imported_types)?.map_err(|errors| {
+            ParsingError(
+                errors
+                    .iter()
+                    .map(|(rest, expected)| report(source, input, rest, expected))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
             )
+        })
+    }
+
+    /// Construct a Library with the given name from the contents of a
+    /// Streamlet Definition File.
+    ///
+    /// Returns the syntax errors of the input instead, if any, as the
+    /// remaining input at which they occur and what was expected there.
+    pub(crate) fn parse_str(
+        key: LibKey,
+        input: &str,
+        imported_types: impl FnOnce(&[LibKey]) -> Result<HashMap<Name, LogicalType>>,
+    ) -> Result<std::result::Result<Self, Vec<(&str, String)>>> {
+        let header = library_doc(input).and_then(|(_, doc)| Ok((doc, imports(input)?.1)));
+        let (doc, imports) = match header {
+            Ok(header) => header,
+            Err(e) => return Ok(Err(vec![error_at(input, &e)])),
+        };
+        let scope = imported_types(&imports)?;
+        let (parsed, mut errors) =
+            with_recovery(input, || Scope::new().with_types(scope).library(input));
+        let contents = match parsed {
+            Ok((rest, contents)) => {
+                match trailing(rest) {
+                    Ok((rest, _)) if !rest.is_empty() => {
+                        errors.push((rest, "streamlet".to_string()))
+                    }
+                    Ok(_) => (),
+                    Err(e) => errors.push(error_at(input, &e)),
+                }
+                Some(contents)
+            }
+            Err(e) => {
+                errors.push(error_at(input, &e));
+                None
+            }
+        };
+        let (imports, types, streamlets): LibraryContents = match contents {
+            Some(contents) if errors.is_empty() => contents,
+            _ => return Ok(Err(errors)),
+        };
+        debug!("Parsed imports: {}", {
+            let iln: Vec<&str> = imports.iter().map(|i| i.as_ref()).collect();
+            iln.join(", ")
+        });
+        debug!("Parsed types: {}", {
+            let tln: Vec<&str> = types.iter().map(|(n, _)| n.as_ref()).collect();
+            tln.join(", ")
+        });
+        debug!("Parsed streamlets: {}", {
+            let sln: Vec<&str> = streamlets.iter().map(|s| s.identifier()).collect();
+            sln.join(", ")
+        });
+        let mut result = Library::from_builder(
+            key,
+            // TODO: No support for parameter groups yet
+            UniqueKeyBuilder::new().with_items(vec![]),
+            UniqueKeyBuilder::new().with_items(streamlets),
+        )?;
+        for import in imports {
+            result.add_import(import)?;
+        }
+        for (name, typ) in types {
+            result.add_type(name, typ)?;
         }
+        if let Some(doc) = doc {
+            let (doc, metadata) = Metadata::from_doc(&doc);
+            result.doc = doc;
+            result.metadata = metadata;
+        }
+        Ok(Ok(result))
     }
 
     pub fn key(&self) -> &LibKey {
         &self.key
     }
 
+    /// Returns an iterator over the libraries imported by this library.
+    pub fn imports(&self) -> impl Iterator<Item = &LibKey> {
+        self.imports.iter()
+    }
+
+    /// Import another library, making its streamlets available to this
+    /// library.
+    pub fn add_import(&mut self, lib: LibKey) -> Result<()> {
+        if lib == self.key || self.imports.contains(&lib) {
+            Err(Error::ProjectError(format!(
+                "Library {} cannot import library {} twice or import itself",
+                self.key, lib
+            )))
+        } else {
+            self.imports.push(lib);
+            Ok(())
+        }
+    }
+
+    /// Returns an iterator over the named types of this library, in order of
+    /// declaration.
+    pub fn types(&self) -> impl Iterator<Item = (&Name, &LogicalType)> {
+        self.types.iter()
+    }
+
+    /// Declare a named type in this library.
+    pub fn add_type(&mut self, name: Name, typ: LogicalType) -> Result<()> {
+        if self.types.contains_key(&name) {
+            Err(Error::LibraryError(format!(
+                "Type {} is already declared in library {}",
+                name, self.key
+            )))
+        } else {
+            self.types.insert(name, typ);
+            Ok(())
+        }
+    }
+
+    /// Return this library with documentation added.
+    pub fn with_doc(mut self, doc: impl Into<String>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
+
+    /// Return this library with metadata added.
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Returns the metadata of this library.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns the metadata of this library, for modification.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    /// Returns a named type of this library.
+    pub fn get_type(&self, name: &Name) -> Result<&LogicalType> {
+        self.types.get(name).ok_or_else(|| {
+            Error::LibraryError(format!("Type {} not found in library {}", name, self.key))
+        })
+    }
+
+    /// Render the documentation, imports, named types and streamlets of this library in
+    /// Streamlet Definition File syntax. Imports and streamlets are ordered
+    /// by name, named types by order of declaration. References to named
+    /// types are rendered as the types they refer to.
+    pub fn to_sdf(&self) -> String {
+        let mut streamlets = self.streamlets().collect::<Vec<_>>();
+        streamlets.sort_by_key(|s| s.key());
+        let mut sections = vec![];
+        if let Some(doc) = self.metadata.document(self.doc.as_deref()) {
+            sections.push(
+                doc.lines()
+                    .map(|line| format!("//!{}\n", line))
+                    .collect::<String>(),
+            );
+        }
+        if !self.imports.is_empty() {
+            let mut imports = self.imports.clone();
+            imports.sort();
+            sections.push(
+                imports
+                    .iter()
+                    .map(|i| format!("import {};\n", i))
+                    .collect::<String>(),
+            );
+        }
+        if !self.types.is_empty() {
+            sections.push(
+                self.types
+                    .iter()
+                    .map(|(n, t)| format!("type {} = {};\n", n, t))
+                    .collect::<String>(),
+            );
+        }
+        sections.extend(streamlets.iter().map(|s| format!("{}\n", s.to_sdf())));
+        sections.join("\n")
+    }
+
     pub fn add_streamlet(&mut self, streamlet: Streamlet) -> Result<StreamletHandle> {
         let key = streamlet.key().clone();
         match self.streamlets.insert(streamlet.key().clone(), streamlet) {
@@ -142,6 +424,49 @@ impl Library {
         })
     }
 
+    pub(crate) fn streamlets_mut(&mut self) -> impl Iterator<Item = &mut Streamlet> {
+        self.streamlets.values_mut()
+    }
+
+    /// Set the name of this library.
+    pub(crate) fn set_key(&mut self, key: LibKey) {
+        self.key = key;
+    }
+
+    /// Rename a streamlet of this library.
+    pub(crate) fn rename_streamlet(
+        &mut self,
+        key: &StreamletKey,
+        name: StreamletKey,
+    ) -> Result<()> {
+        if self.streamlets.contains_key(&name) {
+            return Err(Error::ProjectError(format!(
+                "Streamlet {} already exists in library {}",
+                name, self.key
+            )));
+        }
+        if !self.streamlets.contains_key(key) {
+            return Err(Error::ProjectError(format!(
+                "Streamlet {} not found in library {}",
+                key, self.key
+            )));
+        }
+        // Rebuild the map, such that the streamlet keeps its position.
+        self.streamlets = self
+            .streamlets
+            .drain(..)
+            .map(|(k, mut streamlet)| {
+                if k == *key {
+                    streamlet.set_key(name.clone());
+                    (name.clone(), streamlet)
+                } else {
+                    (k, streamlet)
+                }
+            })
+            .collect();
+        Ok(())
+    }
+
     pub fn get_streamlet_mut(&mut self, streamlet: StreamletKey) -> Result<&mut Streamlet> {
         match self.streamlets.get_mut(&streamlet) {
             Some(s) => Ok(s),
@@ -153,9 +478,20 @@ impl Library {
     }
 }
 
+/// Returns true if a file is a Verilog source file, i.e. it has a `.v` or
+/// `.sv` extension.
+pub(crate) fn is_verilog(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("v") | Some("sv")
+    )
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::generator::common::convert::Packify;
+    use crate::generator::vhdl::Declare;
 
     #[test]
     pub(crate) fn test_library() -> Result<()> {
@@ -173,6 +509,102 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn library_imports() -> Result<()> {
+        let tmpdir = tempfile::tempdir().map_err(|e| FileIOError(e.to_string()))?;
+        let path = tmpdir.path().join("test.sdf");
+        let sdf = "import other;\n\nStreamlet a (\n  x : in Null\n)\n";
+        std::fs::write(path.as_path(), sdf).map_err(|e| FileIOError(e.to_string()))?;
+        let lib = Library::from_file(path.as_path())?;
+        assert_eq!(
+            lib.imports().collect::<Vec<_>>(),
+            vec![&Name::try_new("other")?]
+        );
+        assert_eq!(lib.to_sdf(), sdf);
+
+        let mut lib = Library::new(Name::try_new("test")?);
+        assert!(lib.add_import(Name::try_new("test")?).is_err());
+        lib.add_import(Name::try_new("other")?)?;
+        assert!(lib.add_import(Name::try_new("other")?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn library_errors() -> Result<()> {
+        let tmpdir = tempfile::tempdir().map_err(|e| FileIOError(e.to_string()))?;
+        let path = tmpdir.path().join("test.sdf");
+        let message = |sdf: &str| -> Result<String> {
+            std::fs::write(path.as_path(), sdf).map_err(|e| FileIOError(e.to_string()))?;
+            Ok(Library::from_file(path.as_path()).unwrap_err().to_string())
+        };
+        assert!(
+            message("Streamlet a (\n  x : in Bits<0>\n)")?.starts_with(&format!(
+                "Parsing error: expected bit count that is not zero, found \"0\" at {}:2:15",
+                path.display()
+            ))
+        );
+        assert_eq!(
+            message("Streamlet a (\n  x : in Null\n)\nStreamlte b ()\n")?,
+            format!(
+                "Parsing error: expected streamlet, found \"Streamlte\" at {}:4:1\n  |\n\
+                 4 | Streamlte b ()\n  | ^",
+                path.display()
+            )
+        );
+        let all = message(concat!(
+            "type A = Bits<>;\n",
+            "Streamlet a (\n",
+            "  x : inn Null,\n",
+            "  y : in Null\n",
+            "  z : out Null\n",
+            ")\n",
+            "Streamlet b (x : in Null)\n",
+        ))?;
+        assert_eq!(
+            all.lines()
+                .filter(|l| l.contains("expected"))
+                .map(|l| l.split(" at ").next().unwrap())
+                .collect::<Vec<_>>(),
+            vec![
+                "Parsing error: expected number, found \">\"",
+                "expected mode, found \"inn\"",
+                "expected ')', found \"z\""
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn library_metadata() -> Result<()> {
+        let tmpdir = tempfile::tempdir().map_err(|e| FileIOError(e.to_string()))?;
+        let path = tmpdir.path().join("test.sdf");
+        let sdf = concat!(
+            "//! Kitchen appliances.\n",
+            "//! @version 2.1\n",
+            "\n",
+            "/// Bakes cookies.\n",
+            "/// @author Jane Doe\n",
+            "/// @tags oven, kitchen\n",
+            "Streamlet baker (\n",
+            "  x : in Null\n",
+            ")\n"
+        );
+        std::fs::write(path.as_path(), sdf).map_err(|e| FileIOError(e.to_string()))?;
+        let lib = Library::from_file(path.as_path())?;
+        assert_eq!(lib.doc().as_deref(), Some(" Kitchen appliances."));
+        assert_eq!(lib.metadata().version(), Some("2.1"));
+        let baker = lib.get_streamlet(Name::try_new("baker")?)?;
+        assert_eq!(baker.doc().as_deref(), Some(" Bakes cookies."));
+        assert_eq!(baker.metadata().author(), Some("Jane Doe"));
+        assert_eq!(baker.metadata().tags(), vec!["oven", "kitchen"]);
+        assert_eq!(lib.to_sdf(), sdf);
+
+        let pkg = lib.canonical().declare()?;
+        assert!(pkg.starts_with("-- Kitchen appliances.\n-- @version 2.1\n\n"));
+        assert!(pkg.contains("-- Bakes cookies.\n-- @author Jane Doe\n-- @tags oven, kitchen\n"));
+        Ok(())
+    }
+
     /// Libraries that can be used for testing purposes throughout the crate.
     pub(crate) mod libs {
         use super::*;
@@ -180,8 +612,12 @@ pub mod tests {
         pub(crate) fn empty_lib() -> Library {
             Library {
                 key: Name::try_new("lib").unwrap(),
-                parameter_stores: HashMap::new(),
-                streamlets: HashMap::new(),
+                imports: Vec::new(),
+                types: IndexMap::new(),
+                doc: None,
+                metadata: Metadata::new(),
+                parameter_stores: IndexMap::new(),
+                streamlets: IndexMap::new(),
             }
         }
     }
@@ -0,0 +1,341 @@
+//! Lints: configurable checks of the conventions of a design.
+//!
+//! Unlike the design rule [check](crate::design::check), lints report
+//! designs that are valid, but do not follow the conventions of a project.
+//! Every lint [Rule] can be allowed, or reported as a warning or an error,
+//! with a [LintConfig], which is stored in TOML format, e.g.:
+//!
+//! ```toml
+//! max-complexity = 4
+//!
+//! [rules]
+//! snake-case-interfaces = "error"
+//! undocumented-interfaces = "allow"
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::{Project, Severity};
+use crate::physical::Complexity;
+use crate::{Document, Error, Identify, NonNegative, Result};
+
+/// A lint rule.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    /// Interface names should be snake_case.
+    SnakeCaseInterfaces,
+    /// Streams should not have a complexity above the configured maximum.
+    MaxComplexity,
+    /// Interfaces with streams should be documented.
+    UndocumentedInterfaces,
+}
+
+impl Rule {
+    /// All lint rules.
+    pub const ALL: [Rule; 3] = [
+        Rule::SnakeCaseInterfaces,
+        Rule::MaxComplexity,
+        Rule::UndocumentedInterfaces,
+    ];
+
+    /// Returns the severity of this rule if it is not configured.
+    pub fn default_level(self) -> Level {
+        match self {
+            Rule::SnakeCaseInterfaces | Rule::MaxComplexity => Level::Warning,
+            Rule::UndocumentedInterfaces => Level::Allow,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rule::SnakeCaseInterfaces => write!(f, "snake-case-interfaces"),
+            Rule::MaxComplexity => write!(f, "max-complexity"),
+            Rule::UndocumentedInterfaces => write!(f, "undocumented-interfaces"),
+        }
+    }
+}
+
+impl Serialize for Rule {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        Rule::ALL
+            .iter()
+            .find(|rule| rule.to_string() == input)
+            .copied()
+            .ok_or_else(|| Error::InvalidArgument(format!("unknown lint rule: {}", input)))
+    }
+}
+
+/// The level at which a lint rule is reported.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    /// The rule is not checked.
+    Allow,
+    /// Violations are reported as warnings.
+    Warning,
+    /// Violations are reported as errors.
+    Error,
+}
+
+/// The configuration of the lint rules.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct LintConfig {
+    /// The maximum major complexity level of streams, 4 if not configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_complexity: Option<NonNegative>,
+    /// The levels of the rules, by the name of the rule.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    rules: BTreeMap<String, Level>,
+}
+
+impl LintConfig {
+    /// Construct a configuration with the default levels of all rules.
+    pub fn new() -> Self {
+        LintConfig::default()
+    }
+
+    /// Parse a configuration in TOML format. Returns an error if it
+    /// configures unknown rules.
+    pub fn from_toml(input: &str) -> Result<Self> {
+        let result: LintConfig =
+            toml::from_str(input).map_err(|e| Error::ParsingError(e.to_string()))?;
+        for rule in result.rules.keys() {
+            rule.parse::<Rule>()?;
+        }
+        Ok(result)
+    }
+
+    /// Returns this configuration with the level of a rule set.
+    pub fn with_level(mut self, rule: Rule, level: Level) -> Self {
+        self.rules.insert(rule.to_string(), level);
+        self
+    }
+
+    /// Returns this configuration with the maximum major complexity level of
+    /// streams set.
+    pub fn with_max_complexity(mut self, max: NonNegative) -> Self {
+        self.max_complexity = Some(max);
+        self
+    }
+
+    /// Returns the level of a rule.
+    pub fn level(&self, rule: Rule) -> Level {
+        self.rules
+            .get(&rule.to_string())
+            .copied()
+            .unwrap_or_else(|| rule.default_level())
+    }
+
+    /// Returns the maximum major complexity level of streams.
+    pub fn max_complexity(&self) -> NonNegative {
+        self.max_complexity.unwrap_or(4)
+    }
+}
+
+/// A violation of a lint rule.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Lint {
+    rule: Rule,
+    severity: Severity,
+    location: String,
+    message: String,
+}
+
+impl Lint {
+    /// Returns the violated rule.
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Returns the severity of this violation.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the location of this violation, i.e. the path of the
+    /// offending interface, e.g. `lib.streamlet.interface`.
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    /// Returns the message of this violation.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns true if this violation is an error.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}]: {}: {}",
+            self.severity, self.rule, self.location, self.message
+        )
+    }
+}
+
+/// Returns true if a name is snake_case.
+fn is_snake_case(name: &str) -> bool {
+    !name.chars().any(char::is_uppercase)
+}
+
+/// Check a project against the lint rules, and return all violations,
+/// ordered by library, streamlet and interface.
+pub fn lint(project: &Project, config: &LintConfig) -> Vec<Lint> {
+    let mut result = Vec::new();
+    let max_complexity = Complexity::new_major(config.max_complexity());
+
+    let mut libraries = project.libraries().collect::<Vec<_>>();
+    libraries.sort_by_key(|lib| lib.key().clone());
+    for library in libraries {
+        let mut streamlets = library.streamlets().collect::<Vec<_>>();
+        streamlets.sort_by_key(|s| s.key().clone());
+        for streamlet in streamlets {
+            for interface in streamlet.interfaces() {
+                let location = format!(
+                    "{}.{}.{}",
+                    library.identifier(),
+                    streamlet.identifier(),
+                    interface.identifier()
+                );
+                let mut report = |rule: Rule, message: String| {
+                    let severity = match config.level(rule) {
+                        Level::Allow => return,
+                        Level::Warning => Severity::Warning,
+                        Level::Error => Severity::Error,
+                    };
+                    result.push(Lint {
+                        rule,
+                        severity,
+                        location: location.clone(),
+                        message,
+                    });
+                };
+
+                if !is_snake_case(interface.identifier()) {
+                    report(
+                        Rule::SnakeCaseInterfaces,
+                        "interface names should be snake_case".to_string(),
+                    );
+                }
+                let synthesized = interface.synthesize();
+                for (path, stream) in synthesized.streams() {
+                    if *stream.complexity() > max_complexity {
+                        report(
+                            Rule::MaxComplexity,
+                            format!(
+                                "stream {}has complexity {}, above {}",
+                                if path.is_empty() {
+                                    String::new()
+                                } else {
+                                    format!("{} ", path)
+                                },
+                                stream.complexity(),
+                                max_complexity
+                            ),
+                        );
+                    }
+                }
+                if synthesized.streams().next().is_some() && interface.doc().is_none() {
+                    report(
+                        Rule::UndocumentedInterfaces,
+                        "interfaces with streams should be documented".to_string(),
+                    );
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::Library;
+    use crate::parser::nom::streamlet;
+    use crate::Name;
+
+    #[test]
+    fn lint_project() -> Result<()> {
+        let mut prj = Project::new(Name::try_new("proj")?);
+        prj.add_lib(Library::try_new(
+            Name::try_new("lib")?,
+            vec![],
+            vec![
+                streamlet(
+                    "Streamlet x (\n\
+                 /// Documented.\n\
+                 inData : in Stream<Bits<8>, c=7>,\n\
+                 out_data : out Stream<Group<a: Stream<Bits<1>, c=5>, b: Bits<1>>>,\n\
+                 ctrl : in Bits<1>\n\
+                 )",
+                )
+                .unwrap()
+                .1,
+            ],
+        )?)?;
+
+        let lints = lint(&prj, &LintConfig::new());
+        assert_eq!(
+            lints.iter().map(|l| l.to_string()).collect::<Vec<_>>(),
+            vec![
+                "warning[snake-case-interfaces]: lib.x.inData: interface names should be snake_case",
+                "warning[max-complexity]: lib.x.inData: stream has complexity 7, above 4",
+                "warning[max-complexity]: lib.x.out_data: stream a has complexity 5, above 4",
+            ]
+        );
+
+        let config = LintConfig::from_toml(
+            "max-complexity = 6\n\
+             [rules]\n\
+             snake-case-interfaces = \"allow\"\n\
+             undocumented-interfaces = \"error\"\n",
+        )?;
+        assert_eq!(
+            config,
+            LintConfig::new()
+                .with_max_complexity(6)
+                .with_level(Rule::SnakeCaseInterfaces, Level::Allow)
+                .with_level(Rule::UndocumentedInterfaces, Level::Error)
+        );
+        let lints = lint(&prj, &config);
+        assert_eq!(
+            lints
+                .iter()
+                .map(|l| (l.rule(), l.location(), l.is_error()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Rule::MaxComplexity, "lib.x.inData", false),
+                (Rule::UndocumentedInterfaces, "lib.x.out_data", true),
+            ]
+        );
+
+        assert!(LintConfig::from_toml("[rules]\nunknown = \"error\"").is_err());
+        assert!(LintConfig::from_toml("[rules]\nmax-complexity = \"fatal\"").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,649 @@
+//! Project manifests.
+//!
+//! A manifest records everything needed to reassemble a [Project]: its name,
+//! the Streamlet Definition Files of its libraries, the implementation files
+//! of those libraries, and the settings used to generate output. Manifests
+//! are stored in TOML format, e.g.:
+//!
+//! ```toml
+//! name = "my_project"
+//!
+//! [[library]]
+//! sdf = "src/primitives.sdf"
+//! implementations = ["src/top.impl"]
+//!
+//! [generate]
+//! target = "vhdl"
+//! abstraction = "fancy"
+//! suffix = "gen"
+//! path-separator = "__"
+//! flattening = "separated"
+//! support = true
+//! simulation-models = true
+//! readme = true
+//! testbench = true
+//! flow-control = "credit"
+//! indent = 4
+//! align = true
+//! max-line-length = 100
+//! header = "header.txt"
+//! output = "out"
+//!
+//! [identifiers]
+//! targets = ["vhdl"]
+//! ```
+//!
+//! Relative paths in a manifest are relative to the directory of the
+//! manifest file.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::design::Project;
+use crate::identifier::IdentifierPolicy;
+use crate::{Error, Identify, Name, Result};
+
+/// The manifest of a project.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// The name of the project.
+    name: String,
+    /// The libraries of the project.
+    #[serde(default, rename = "library")]
+    libraries: Vec<LibraryManifest>,
+    /// The generation settings of the project.
+    #[serde(default, skip_serializing_if = "GenerateSettings::is_empty")]
+    generate: GenerateSettings,
+    /// The identifier policy for the names of the project.
+    #[serde(default, skip_serializing_if = "IdentifierPolicy::is_default")]
+    identifiers: IdentifierPolicy,
+}
+
+/// The sources of a library in a project manifest.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LibraryManifest {
+    /// The Streamlet Definition File of the library.
+    sdf: PathBuf,
+    /// The implementation files of the streamlets of the library.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    implementations: Vec<PathBuf>,
+}
+
+/// Settings used to generate output from a project.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct GenerateSettings {
+    /// The target back-end, e.g. `vhdl`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<String>,
+    /// The abstraction level, e.g. `canonical` or `fancy`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    abstraction: Option<String>,
+    /// The suffix of generated files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    suffix: Option<String>,
+    /// The separator between the names of nested fields in port names.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path_separator: Option<String>,
+    /// The scheme to flatten the names of nested fields in port names, e.g.
+    /// `separated` or `camel-case`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    flattening: Option<String>,
+    /// Whether the support packages that generated files depend on are
+    /// emitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    support: Option<bool>,
+    /// Whether behavioral simulation models of the support components are
+    /// emitted instead of their implementations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    simulation_models: Option<bool>,
+    /// Whether a summary of the generated files is written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    readme: Option<bool>,
+    /// Whether testbenches are generated for streamlets with a structural
+    /// implementation. Testbenches require VHDL-2008.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    testbench: Option<bool>,
+    /// The flow control of the streams at the boundaries of streamlets, e.g.
+    /// `handshake` or `credit`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    flow_control: Option<String>,
+    /// The number of spaces per indentation level of generated files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    indent: Option<usize>,
+    /// Whether generated files are indented with tabs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tabs: Option<bool>,
+    /// Whether consecutive declarations and associations in generated files
+    /// are aligned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    align: Option<bool>,
+    /// The maximum length of lines in generated files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_line_length: Option<usize>,
+    /// The file with the template of the header of generated files, see
+    /// [HeaderTemplate](crate::generator::header::HeaderTemplate).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    header: Option<PathBuf>,
+    /// The output directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    output: Option<PathBuf>,
+}
+
+impl Manifest {
+    /// Construct a manifest for a project without libraries.
+    pub fn new(name: impl Into<String>) -> Self {
+        Manifest {
+            name: name.into(),
+            libraries: Vec::new(),
+            generate: GenerateSettings::default(),
+            identifiers: IdentifierPolicy::default(),
+        }
+    }
+
+    /// Returns this manifest with a library added.
+    pub fn with_library(mut self, library: LibraryManifest) -> Self {
+        self.libraries.push(library);
+        self
+    }
+
+    /// Returns this manifest with the given generation settings.
+    pub fn with_generate(mut self, generate: GenerateSettings) -> Self {
+        self.generate = generate;
+        self
+    }
+
+    /// Returns this manifest with the given identifier policy for names.
+    pub fn with_identifiers(mut self, identifiers: IdentifierPolicy) -> Self {
+        self.identifiers = identifiers;
+        self
+    }
+
+    /// Returns the name of the project.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns an iterator over the libraries of the project.
+    pub fn libraries(&self) -> impl Iterator<Item = &LibraryManifest> {
+        self.libraries.iter()
+    }
+
+    /// Returns the generation settings of the project.
+    pub fn generate(&self) -> &GenerateSettings {
+        &self.generate
+    }
+
+    /// Returns the identifier policy for the names of the project.
+    pub fn identifiers(&self) -> &IdentifierPolicy {
+        &self.identifiers
+    }
+
+    /// Parse a manifest in TOML format.
+    pub fn from_toml(input: &str) -> Result<Self> {
+        toml::from_str(input).map_err(|e| Error::ParsingError(e.to_string()))
+    }
+
+    /// Load a manifest file, with all relative paths resolved against the
+    /// directory of the manifest file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        Ok(Manifest::from_toml(&std::fs::read_to_string(path)?)?.resolve(dir))
+    }
+
+    /// Render this manifest in TOML format.
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).map_err(|e| Error::ProjectError(e.to_string()))
+    }
+
+    /// Returns this manifest with all relative paths resolved against the
+    /// given directory.
+    fn resolve(mut self, dir: &Path) -> Self {
+        let resolve = |path: &mut PathBuf| {
+            if path.is_relative() {
+                *path = dir.join(&path);
+            }
+        };
+        for library in &mut self.libraries {
+            resolve(&mut library.sdf);
+            library.implementations.iter_mut().for_each(resolve);
+        }
+        if let Some(header) = &mut self.generate.header {
+            resolve(header);
+        }
+        if let Some(output) = &mut self.generate.output {
+            resolve(output);
+        }
+        self
+    }
+
+    /// Returns this manifest with all paths inside of the given directory
+    /// made relative to that directory.
+    fn relative_to(mut self, dir: &Path) -> Self {
+        let relative = |path: &mut PathBuf| {
+            if let Ok(stripped) = path.strip_prefix(dir) {
+                *path = stripped.to_path_buf();
+            }
+        };
+        for library in &mut self.libraries {
+            relative(&mut library.sdf);
+            library.implementations.iter_mut().for_each(relative);
+        }
+        if let Some(header) = &mut self.generate.header {
+            relative(header);
+        }
+        if let Some(output) = &mut self.generate.output {
+            relative(output);
+        }
+        self
+    }
+}
+
+impl LibraryManifest {
+    /// Construct the manifest of a library defined in a Streamlet Definition
+    /// File.
+    pub fn new(sdf: impl Into<PathBuf>) -> Self {
+        LibraryManifest {
+            sdf: sdf.into(),
+            implementations: Vec::new(),
+        }
+    }
+
+    /// Returns this library manifest with an implementation file added.
+    pub fn with_implementation(mut self, path: impl Into<PathBuf>) -> Self {
+        self.implementations.push(path.into());
+        self
+    }
+
+    /// Returns the path to the Streamlet Definition File of the library.
+    pub fn sdf(&self) -> &Path {
+        &self.sdf
+    }
+
+    /// Returns an iterator over the paths to the implementation files of the
+    /// library.
+    pub fn implementations(&self) -> impl Iterator<Item = &PathBuf> {
+        self.implementations.iter()
+    }
+}
+
+impl GenerateSettings {
+    /// Returns these settings with the given target back-end.
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Returns these settings with the given abstraction level.
+    pub fn with_abstraction(mut self, abstraction: impl Into<String>) -> Self {
+        self.abstraction = Some(abstraction.into());
+        self
+    }
+
+    /// Returns these settings with the given suffix of generated files.
+    pub fn with_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = Some(suffix.into());
+        self
+    }
+
+    /// Returns these settings with the given separator between the names of
+    /// nested fields in port names.
+    pub fn with_path_separator(mut self, separator: impl Into<String>) -> Self {
+        self.path_separator = Some(separator.into());
+        self
+    }
+
+    /// Returns these settings with the given scheme to flatten the names of
+    /// nested fields in port names.
+    pub fn with_flattening(mut self, flattening: impl Into<String>) -> Self {
+        self.flattening = Some(flattening.into());
+        self
+    }
+
+    /// Returns these settings, emitting the support packages that generated
+    /// files depend on if `support` is true.
+    pub fn with_support(mut self, support: bool) -> Self {
+        self.support = Some(support);
+        self
+    }
+
+    /// Returns these settings, emitting behavioral simulation models of the
+    /// support components if `simulation_models` is true.
+    pub fn with_simulation_models(mut self, simulation_models: bool) -> Self {
+        self.simulation_models = Some(simulation_models);
+        self
+    }
+
+    /// Returns these settings, writing a summary of the generated files if
+    /// `readme` is true.
+    pub fn with_readme(mut self, readme: bool) -> Self {
+        self.readme = Some(readme);
+        self
+    }
+
+    /// Returns these settings, generating testbenches for structural
+    /// streamlets if `testbench` is true.
+    pub fn with_testbench(mut self, testbench: bool) -> Self {
+        self.testbench = Some(testbench);
+        self
+    }
+
+    /// Returns these settings with the given flow control of the streams at
+    /// the boundaries of streamlets.
+    pub fn with_flow_control(mut self, flow_control: impl Into<String>) -> Self {
+        self.flow_control = Some(flow_control.into());
+        self
+    }
+
+    /// Returns these settings with the given number of spaces per
+    /// indentation level of generated files.
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = Some(indent);
+        self
+    }
+
+    /// Returns these settings, indenting generated files with tabs if `tabs`
+    /// is true.
+    pub fn with_tabs(mut self, tabs: bool) -> Self {
+        self.tabs = Some(tabs);
+        self
+    }
+
+    /// Returns these settings, aligning consecutive declarations and
+    /// associations in generated files if `align` is true.
+    pub fn with_alignment(mut self, align: bool) -> Self {
+        self.align = Some(align);
+        self
+    }
+
+    /// Returns these settings with the given maximum length of lines in
+    /// generated files.
+    pub fn with_max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = Some(max_line_length);
+        self
+    }
+
+    /// Returns these settings with the given file with the template of the
+    /// header of generated files.
+    pub fn with_header(mut self, header: impl Into<PathBuf>) -> Self {
+        self.header = Some(header.into());
+        self
+    }
+
+    /// Returns these settings with the given output directory.
+    pub fn with_output(mut self, output: impl Into<PathBuf>) -> Self {
+        self.output = Some(output.into());
+        self
+    }
+
+    /// Returns the target back-end.
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// Returns the abstraction level.
+    pub fn abstraction(&self) -> Option<&str> {
+        self.abstraction.as_deref()
+    }
+
+    /// Returns the suffix of generated files.
+    pub fn suffix(&self) -> Option<&str> {
+        self.suffix.as_deref()
+    }
+
+    /// Returns the separator between the names of nested fields in port
+    /// names.
+    pub fn path_separator(&self) -> Option<&str> {
+        self.path_separator.as_deref()
+    }
+
+    /// Returns the scheme to flatten the names of nested fields in port
+    /// names.
+    pub fn flattening(&self) -> Option<&str> {
+        self.flattening.as_deref()
+    }
+
+    /// Returns whether the support packages that generated files depend on
+    /// are emitted, if specified.
+    pub fn support(&self) -> Option<bool> {
+        self.support
+    }
+
+    /// Returns whether behavioral simulation models of the support
+    /// components are emitted instead of their implementations, if
+    /// specified.
+    pub fn simulation_models(&self) -> Option<bool> {
+        self.simulation_models
+    }
+
+    /// Returns whether a summary of the generated files is written, if
+    /// specified.
+    pub fn readme(&self) -> Option<bool> {
+        self.readme
+    }
+
+    /// Returns whether testbenches are generated for structural streamlets,
+    /// if specified.
+    pub fn testbench(&self) -> Option<bool> {
+        self.testbench
+    }
+
+    /// Returns the flow control of the streams at the boundaries of
+    /// streamlets, if specified.
+    pub fn flow_control(&self) -> Option<&str> {
+        self.flow_control.as_deref()
+    }
+
+    /// Returns the number of spaces per indentation level of generated
+    /// files, if specified.
+    pub fn indent(&self) -> Option<usize> {
+        self.indent
+    }
+
+    /// Returns whether generated files are indented with tabs, if specified.
+    pub fn tabs(&self) -> Option<bool> {
+        self.tabs
+    }
+
+    /// Returns whether consecutive declarations and associations in
+    /// generated files are aligned, if specified.
+    pub fn align(&self) -> Option<bool> {
+        self.align
+    }
+
+    /// Returns the maximum length of lines in generated files, if specified.
+    pub fn max_line_length(&self) -> Option<usize> {
+        self.max_line_length
+    }
+
+    /// Returns the file with the template of the header of generated files.
+    pub fn header(&self) -> Option<&Path> {
+        self.header.as_deref()
+    }
+
+    /// Returns the output directory.
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    /// Returns true if no settings are specified.
+    pub fn is_empty(&self) -> bool {
+        *self == GenerateSettings::default()
+    }
+}
+
+impl Project {
+    /// Load a project from a manifest file.
+    ///
+    /// The libraries of the project are parsed from the Streamlet Definition
+    /// Files listed in the manifest.
+    pub fn load(path: impl AsRef<Path>) -> Result<Project> {
+        let path = path.as_ref();
+        let manifest = Manifest::load(path)?;
+        let mut project = Project::new(Name::try_new(manifest.name())?)
+            .with_identifier_policy(manifest.identifiers().clone());
+        project.add_lib_files(
+            &manifest
+                .libraries()
+                .map(|l| l.sdf().to_path_buf())
+                .collect::<Vec<_>>(),
+        )?;
+        for library in project.libraries() {
+            for import in library.imports() {
+                project.get_lib(import.clone()).map_err(|_| {
+                    Error::ProjectError(format!(
+                        "Library {} imports library {}, which is not listed in {}",
+                        library.identifier(),
+                        import,
+                        path.display()
+                    ))
+                })?;
+            }
+        }
+        project.manifest = Some(manifest);
+        Ok(project)
+    }
+
+    /// Store this project as a manifest file.
+    ///
+    /// Libraries that were loaded from a manifest keep referring to their
+    /// original Streamlet Definition Files. Libraries that were constructed
+    /// otherwise are written to `<library name>.sdf` in the directory of the
+    /// manifest. Implementation files and generation settings of a loaded
+    /// manifest are preserved.
+    pub fn store(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+        let mut manifest =
+            Manifest::new(self.identifier()).with_identifiers(self.identifier_policy().clone());
+        if let Some(loaded) = &self.manifest {
+            manifest = manifest.with_generate(loaded.generate().clone());
+        }
+
+        let mut libraries = self.libraries().collect::<Vec<_>>();
+        libraries.sort_by_key(|lib| lib.key().clone());
+        for library in libraries {
+            let loaded = self.manifest.as_ref().and_then(|m| {
+                m.libraries().find(|l| {
+                    l.sdf().file_stem().and_then(|s| s.to_str()) == Some(library.identifier())
+                })
+            });
+            manifest = manifest.with_library(match loaded {
+                Some(loaded) => loaded.clone(),
+                None => {
+                    let sdf = dir.join(format!("{}.sdf", library.identifier()));
+                    std::fs::write(&sdf, library.to_sdf())?;
+                    LibraryManifest::new(sdf)
+                }
+            });
+        }
+
+        std::fs::write(path, manifest.relative_to(dir).to_toml()?)?;
+        Ok(())
+    }
+
+    /// Returns the manifest this project was loaded from, if any.
+    pub fn manifest(&self) -> Option<&Manifest> {
+        self.manifest.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::implementation::composer::tests::composition_test_proj;
+
+    #[test]
+    fn manifest_toml() -> Result<()> {
+        let manifest = Manifest::new("proj")
+            .with_library(LibraryManifest::new("a.sdf").with_implementation("top.impl"))
+            .with_library(LibraryManifest::new("b.sdf"))
+            .with_generate(
+                GenerateSettings::default()
+                    .with_target("vhdl")
+                    .with_abstraction("fancy")
+                    .with_path_separator("_")
+                    .with_indent(4)
+                    .with_max_line_length(100),
+            );
+        let toml = manifest.to_toml()?;
+        assert_eq!(
+            toml,
+            r#"name = "proj"
+
+[[library]]
+sdf = "a.sdf"
+implementations = ["top.impl"]
+
+[[library]]
+sdf = "b.sdf"
+
+[generate]
+target = "vhdl"
+abstraction = "fancy"
+path-separator = "_"
+indent = 4
+max-line-length = 100
+"#
+        );
+        assert_eq!(Manifest::from_toml(&toml)?, manifest);
+        assert_eq!(
+            Manifest::from_toml("name = \"proj\"")?,
+            Manifest::new("proj")
+        );
+        assert!(Manifest::from_toml("name = \"proj\"\nlibs = []").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn store_load() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let path = tmpdir.path().join("tydi.toml");
+
+        let project = composition_test_proj()?;
+        project.store(&path)?;
+        assert!(tmpdir.path().join("primitives.sdf").is_file());
+        assert!(tmpdir.path().join("compositions.sdf").is_file());
+
+        let loaded = Project::load(&path)?;
+        assert_eq!(loaded.identifier(), project.identifier());
+        for library in project.libraries() {
+            assert_eq!(loaded.get_lib(library.key().clone())?, library);
+        }
+        assert_eq!(
+            loaded
+                .manifest()
+                .unwrap()
+                .libraries()
+                .map(|l| l.sdf().to_path_buf())
+                .collect::<Vec<_>>(),
+            vec![
+                tmpdir.path().join("compositions.sdf"),
+                tmpdir.path().join("primitives.sdf")
+            ]
+        );
+
+        // Storing a loaded project keeps referring to the same files.
+        std::fs::remove_file(tmpdir.path().join("primitives.sdf"))?;
+        std::fs::write(
+            &path,
+            "name = \"TestProj\"\n\n[[library]]\nsdf = \"compositions.sdf\"\n\n\
+             [generate]\nsuffix = \"gen\"\n",
+        )?;
+        let loaded = Project::load(&path)?;
+        let other = tmpdir.path().join("other.toml");
+        loaded.store(&other)?;
+        assert_eq!(
+            std::fs::read_to_string(&other)?,
+            "name = \"TestProj\"\n\n[[library]]\nsdf = \"compositions.sdf\"\n\n\
+             [generate]\nsuffix = \"gen\"\n"
+        );
+        assert!(!tmpdir.path().join("primitives.sdf").exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+//! Key/value metadata of streamlets and libraries.
+//!
+//! Metadata, such as the author or the version of a streamlet, is written in
+//! the documentation of a streamlet or library in a Streamlet Definition File,
+//! as lines of the form `@key value`:
+//!
+//! ```text
+//! /// A streamlet that bakes cookies.
+//! /// @author Jane Doe
+//! /// @version 1.2.0
+//! /// @tags kitchen, oven
+//! Streamlet baker (dough: in Stream<Bits<8>>, cookies: out Stream<Bits<8>>)
+//! ```
+
+use std::fmt;
+
+use indexmap::IndexMap;
+
+/// Metadata key of the author of a design element.
+pub const AUTHOR: &str = "author";
+/// Metadata key of the version of a design element.
+pub const VERSION: &str = "version";
+/// Metadata key of the comma-separated tags of a design element.
+pub const TAGS: &str = "tags";
+/// Metadata key of the comma-separated latencies of the paths through a
+/// streamlet, each written as `input->output=cycles`.
+pub const LATENCY: &str = "latency";
+/// Metadata key of the comma-separated sustained throughputs of the
+/// interfaces of a streamlet in elements per cycle, each written as
+/// `interface=rate`.
+pub const THROUGHPUT: &str = "throughput";
+/// Metadata key of the number of elements a buffering streamlet can hold.
+pub const DEPTH: &str = "depth";
+/// Metadata key of the existing HDL entity implementing a streamlet, written
+/// as `library.entity`, or `entity` for an entity in the work library.
+pub const EXTERNAL: &str = "external";
+/// Metadata key of the comma-separated conversions performed by an adapter
+/// inserted between two interfaces, see
+/// [adapter](crate::design::implementation::composer::adapter).
+pub const ADAPTER: &str = "adapter";
+
+/// Key/value metadata of a design element, in order of insertion.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata(IndexMap<String, String>);
+
+impl Metadata {
+    /// Construct empty metadata.
+    pub fn new() -> Self {
+        Metadata::default()
+    }
+
+    /// Split a documentation string into its description and its metadata.
+    ///
+    /// Lines of the form `@key value` are metadata, all other lines are part
+    /// of the description. Returns None as description if no other lines
+    /// remain.
+    ///
+    /// # Example
+    /// ```
+    /// use tydi::design::metadata::Metadata;
+    ///
+    /// let (doc, metadata) = Metadata::from_doc(" Bakes cookies.\n @author Jane Doe");
+    /// assert_eq!(doc.as_deref(), Some(" Bakes cookies."));
+    /// assert_eq!(metadata.author(), Some("Jane Doe"));
+    /// ```
+    pub fn from_doc(doc: &str) -> (Option<String>, Metadata) {
+        let mut metadata = Metadata::new();
+        let description = doc
+            .lines()
+            .filter(|line| match Metadata::entry(line) {
+                Some((key, value)) => {
+                    metadata.insert(key, value);
+                    false
+                }
+                None => true,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        if description.trim().is_empty() {
+            (None, metadata)
+        } else {
+            (Some(description), metadata)
+        }
+    }
+
+    /// Parse a single `@key value` line.
+    fn entry(line: &str) -> Option<(&str, &str)> {
+        let line = line.trim().strip_prefix('@')?;
+        let (key, value) = match line.find(char::is_whitespace) {
+            Some(index) => (&line[..index], line[index..].trim()),
+            None => (line, ""),
+        };
+        if !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            Some((key, value))
+        } else {
+            None
+        }
+    }
+
+    /// Combine a description with this metadata into a documentation string,
+    /// the inverse of [Metadata::from_doc].
+    pub fn document(&self, doc: Option<&str>) -> Option<String> {
+        let lines = doc
+            .map(|doc| doc.to_string())
+            .into_iter()
+            .chain(self.0.iter().map(|(key, value)| {
+                if value.is_empty() {
+                    format!(" @{}", key)
+                } else {
+                    format!(" @{} {}", key, value)
+                }
+            }))
+            .collect::<Vec<_>>();
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
+
+    /// Set the value of a key, returning the previous value, if any.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        self.0.insert(key.into(), value.into())
+    }
+
+    /// Returns the value of a key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(|v| v.as_str())
+    }
+
+    /// Returns an iterator over the keys and values, in order of insertion.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Returns true if there is no metadata.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the author.
+    pub fn author(&self) -> Option<&str> {
+        self.get(AUTHOR)
+    }
+
+    /// Returns the version.
+    pub fn version(&self) -> Option<&str> {
+        self.get(VERSION)
+    }
+
+    /// Returns the tags.
+    pub fn tags(&self) -> Vec<&str> {
+        self.get(TAGS)
+            .map(|tags| {
+                tags.split(',')
+                    .map(|tag| tag.trim())
+                    .filter(|tag| !tag.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns true if the tags contain the given tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags().contains(&tag)
+    }
+}
+
+impl fmt::Display for Metadata {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let entries = self
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect::<Vec<_>>();
+        write!(f, "{}", entries.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_doc() {
+        let (doc, metadata) = Metadata::from_doc(
+            " Bakes cookies.\n @author Jane Doe\n\n More text.\n @tags a, b,\n @",
+        );
+        assert_eq!(doc.as_deref(), Some(" Bakes cookies.\n\n More text.\n @"));
+        assert_eq!(metadata.author(), Some("Jane Doe"));
+        assert_eq!(metadata.version(), None);
+        assert_eq!(metadata.tags(), vec!["a", "b"]);
+        assert!(metadata.has_tag("b"));
+        assert_eq!(metadata.to_string(), "author: Jane Doe, tags: a, b,");
+
+        let (doc, metadata) = Metadata::from_doc(" @version 1.0");
+        assert_eq!(doc, None);
+        assert_eq!(metadata.document(None).as_deref(), Some(" @version 1.0"));
+        assert_eq!(
+            metadata.document(Some(" Text")).as_deref(),
+            Some(" Text\n @version 1.0")
+        );
+        assert_eq!(Metadata::new().document(None), None);
+    }
+}
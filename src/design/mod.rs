@@ -1,16 +1,33 @@
 //! Constructs that are used to generate hardware designs, that are not
 //! part of the specification (yet).
 
+pub use check::{baseline, check, check_baseline, Diagnostic, Severity};
+pub use diff::{diff, Change};
+pub use footprint::{InterfaceFootprint, StreamFootprint, StreamletFootprint};
+pub use inspect::StreamletInfo;
 pub use library::Library;
-pub use project::Project;
-pub use streamlet::{Interface, Mode, Streamlet};
+pub use lint::{lint, Lint, LintConfig};
+pub use manifest::{GenerateSettings, LibraryManifest, Manifest};
+pub use metadata::Metadata;
+pub use project::{Connection, Project};
+pub use streamlet::{Interface, InterfaceIndex, Mode, Streamlet};
 
 use crate::Name;
 
+pub mod check;
+pub mod diff;
+#[cfg(feature = "fletcher")]
+pub mod fletcher;
+pub mod footprint;
 pub mod implementation;
+pub mod inspect;
 pub mod library;
+pub mod lint;
+pub mod manifest;
+pub mod metadata;
 pub mod param;
 pub mod project;
+pub mod stats;
 pub mod streamlet;
 
 /// Index types
@@ -51,13 +68,36 @@ impl StreamletHandle {
     }
 }
 
+/// Handle to an interface of a streamlet, through project hierarchy
 #[derive(Clone, Debug, PartialEq)]
+pub struct InterfaceHandle {
+    pub streamlet: StreamletHandle,
+    pub iface: IFKey,
+}
+
+impl InterfaceHandle {
+    pub fn streamlet(&self) -> StreamletHandle {
+        self.streamlet.clone()
+    }
+    pub fn iface(&self) -> IFKey {
+        self.iface.clone()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct NodeIFHandle {
     node: NodeKey,
     iface: IFKey,
 }
 
 impl NodeIFHandle {
+    /// Construct a handle to an interface of a node.
+    pub fn new(node: impl Into<NodeKey>, iface: impl Into<IFKey>) -> Self {
+        NodeIFHandle {
+            node: node.into(),
+            iface: iface.into(),
+        }
+    }
     pub fn node(&self) -> NodeKey {
         self.node.clone()
     }
@@ -6,7 +6,7 @@ use crate::design::{ParamHandle, ParamKey, ParamStoreKey};
 use crate::logical::LogicalType;
 use crate::{Document, Error, Identify, Result, UniqueKeyBuilder};
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ParameterVariant {
     Type(LogicalType),
     String(String),
@@ -14,7 +14,7 @@ pub enum ParameterVariant {
     //...
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct NamedParameter {
     key: ParamKey,
     item: ParameterVariant,
@@ -1,15 +1,57 @@
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
 
+use indexmap::IndexMap;
+use tracing::warn;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::implementation::structure::{Edge, Structure};
 use crate::design::implementation::Implementation;
-use crate::design::{LibKey, Library, Streamlet, StreamletHandle};
+use crate::design::library::is_verilog;
+use crate::design::manifest::Manifest;
+use crate::design::{
+    IFKey, InterfaceHandle, LibKey, Library, Streamlet, StreamletHandle, StreamletKey,
+};
+use crate::identifier::IdentifierPolicy;
+use crate::logical::{LogicalType, SynthesisCache};
 use crate::util::UniquelyNamedBuilder;
-use crate::{Error, Result};
+use crate::{Error, Location, Result, ResultExt};
 use crate::{Identify, Name};
 
+/// An edge of the structural implementation of a streamlet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Connection {
+    structure: StreamletHandle,
+    edge: Edge,
+}
+
+impl Connection {
+    /// Returns the streamlet of which the structural implementation contains
+    /// this connection.
+    pub fn structure(&self) -> StreamletHandle {
+        self.structure.clone()
+    }
+
+    /// Returns the edge of this connection.
+    pub fn edge(&self) -> &Edge {
+        &self.edge
+    }
+}
+
 /// A collection of Streamlets.
 pub struct Project {
     name: Name,
-    libraries: HashMap<LibKey, Library>,
+    /// The libraries of this project, in order of addition.
+    libraries: IndexMap<LibKey, Library>,
+    /// The manifest this project was loaded from.
+    pub(super) manifest: Option<Manifest>,
+    /// The identifier policy for the names of the libraries added to this
+    /// project.
+    identifier_policy: IdentifierPolicy,
+    /// The cache the types of the interfaces of the libraries added to this
+    /// project are synthesized with.
+    synthesis: Arc<SynthesisCache>,
 }
 
 impl Identify for Project {
@@ -22,19 +64,163 @@ impl Project {
     pub fn new(name: Name) -> Project {
         Project {
             name,
-            libraries: HashMap::new(),
+            libraries: IndexMap::new(),
+            manifest: None,
+            identifier_policy: IdentifierPolicy::default(),
+            synthesis: Arc::default(),
+        }
+    }
+
+    /// Returns this project with an identifier policy for the names of the
+    /// libraries that are added to it.
+    pub fn with_identifier_policy(mut self, policy: IdentifierPolicy) -> Self {
+        self.identifier_policy = policy;
+        self
+    }
+
+    /// Returns the identifier policy for the names of the libraries added to
+    /// this project.
+    pub fn identifier_policy(&self) -> &IdentifierPolicy {
+        &self.identifier_policy
+    }
+
+    /// Returns the cache the types of the interfaces of the libraries added
+    /// to this project are synthesized with, such that types with the same
+    /// structure are only synthesized once per project.
+    pub fn synthesis(&self) -> &SynthesisCache {
+        &self.synthesis
+    }
+
+    /// Check the names of a library, its streamlets and their interfaces
+    /// against the identifier policy of this project.
+    fn check_names(&self, lib: &Library) -> Result<()> {
+        if self.identifier_policy.is_default() {
+            return Ok(());
+        }
+        let mut names = vec![(lib.key().to_string(), lib.key().clone())];
+        for streamlet in lib.streamlets() {
+            let path = format!("{}.{}", lib.key(), streamlet.key());
+            for interface in streamlet.interfaces() {
+                names.push((
+                    format!("{}.{}", path, interface.key()),
+                    interface.key().clone(),
+                ));
+            }
+            names.push((path, streamlet.key()));
+        }
+        for (path, name) in names {
+            self.identifier_policy
+                .check(&name)
+                .map_err(|e| e.with_note(format!("in {}", path)))?;
+        }
+        Ok(())
+    }
+
+    /// Rename the library of this project with the given key, its
+    /// streamlets and their interfaces, of which the names violate the
+    /// lenient identifier policy of this project, to sanitized names, see
+    /// [IdentifierPolicy::sanitize]. References to them in the structural
+    /// implementations of this project are updated.
+    ///
+    /// Returns the key of the library.
+    fn sanitize_names(&mut self, lib: LibKey) -> Result<LibKey> {
+        let policy = self.identifier_policy.clone();
+        let sanitized = |path: &str, name: &Name| -> Result<Option<Name>> {
+            match policy.check(name) {
+                Ok(()) => Ok(None),
+                Err(e) => {
+                    let sanitized = policy.name(name.to_string())?;
+                    warn!("{}: {}, renamed to {}", path, e, sanitized);
+                    Ok(Some(sanitized))
+                }
+            }
+        };
+        let mut interfaces = vec![];
+        let mut streamlets = vec![];
+        for streamlet in self.get_lib(lib.clone())?.streamlets() {
+            let path = format!("{}.{}", lib, streamlet.key());
+            let handle = StreamletHandle {
+                lib: lib.clone(),
+                streamlet: streamlet.key(),
+            };
+            for interface in streamlet.interfaces() {
+                let path = format!("{}.{}", path, interface.key());
+                if let Some(name) = sanitized(&path, interface.key())? {
+                    interfaces.push((handle.clone(), interface.key().clone(), name));
+                }
+            }
+            if let Some(name) = sanitized(&path, &streamlet.key())? {
+                streamlets.push((handle, name));
+            }
+        }
+        for (streamlet, iface, name) in interfaces {
+            self.rename_interface(streamlet, iface, name)?;
+        }
+        for (streamlet, name) in streamlets {
+            self.rename_streamlet(streamlet, name)?;
+        }
+        match sanitized(lib.as_ref(), &lib)? {
+            Some(name) => self.rename_lib(lib, name),
+            None => Ok(lib),
         }
     }
 
+    /// Rename a library of this project, and update all references to its
+    /// streamlets in the structural implementations of this project.
+    fn rename_lib(&mut self, lib: LibKey, name: LibKey) -> Result<LibKey> {
+        if self.libraries.contains_key(&name) {
+            return Err(Error::ProjectError(format!(
+                "Library {} already exists in the project",
+                name
+            )));
+        }
+        let mut library = self.libraries.shift_remove(&lib).ok_or_else(|| {
+            Error::ProjectError(format!("Library {} not found in the project", lib))
+        })?;
+        let handles = library
+            .streamlets()
+            .map(|s| {
+                (
+                    StreamletHandle {
+                        lib: lib.clone(),
+                        streamlet: s.key(),
+                    },
+                    StreamletHandle {
+                        lib: name.clone(),
+                        streamlet: s.key(),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        library.set_key(name.clone());
+        self.libraries.insert(name.clone(), library);
+        self.update_structures(|s| {
+            for (old, new) in &handles {
+                s.rename_streamlet(old, new);
+            }
+        })?;
+        Ok(name)
+    }
+
     /// Construct a Project from a UniquelyNamedBuilder with Libraries.
     pub fn from_builder(name: Name, builder: UniquelyNamedBuilder<Library>) -> Result<Self> {
+        let synthesis = Arc::default();
+        let libraries = builder
+            .finish()?
+            .into_iter()
+            .map(|lib| {
+                for streamlet in lib.streamlets() {
+                    streamlet.set_synthesis(&synthesis);
+                }
+                (lib.key().clone(), lib)
+            })
+            .collect::<IndexMap<LibKey, Library>>();
         Ok(Project {
             name,
-            libraries: builder
-                .finish()?
-                .into_iter()
-                .map(|lib| (lib.key().clone(), lib))
-                .collect::<HashMap<LibKey, Library>>(),
+            libraries,
+            manifest: None,
+            identifier_policy: IdentifierPolicy::default(),
+            synthesis,
         })
     }
 
@@ -43,15 +229,34 @@ impl Project {
         self.libraries.iter().map(|(_, l)| l)
     }
 
+    /// Add a library to this project. The types of the interfaces of its
+    /// streamlets are synthesized with the cache of this project from now
+    /// on, see [Project::synthesis].
+    ///
+    /// Names of the library, its streamlets and their interfaces that
+    /// violate the identifier policy of this project are rejected, or
+    /// sanitized if the policy is lenient. Returns the key of the library.
     pub fn add_lib(&mut self, lib: Library) -> Result<LibKey> {
+        let lenient = self.identifier_policy.is_lenient();
+        if !lenient {
+            self.check_names(&lib)?;
+        }
+        for streamlet in lib.streamlets() {
+            streamlet.set_synthesis(&self.synthesis);
+        }
         let key = lib.key().clone();
-        match self.libraries.insert(lib.key().clone(), lib) {
-            None => Ok(key),
-            Some(_lib) => Err(Error::ProjectError(format!(
+        if let Some(_lib) = self.libraries.insert(lib.key().clone(), lib) {
+            return Err(Error::ProjectError(format!(
                 "Error while adding {} to the project",
                 key,
-            ))),
+            )));
         }
+        if lenient {
+            return self.sanitize_names(key.clone()).inspect_err(|_| {
+                self.libraries.shift_remove(&key);
+            });
+        }
+        Ok(key)
     }
 
     pub fn get_lib(&self, lib: LibKey) -> Result<&Library> {
@@ -82,6 +287,291 @@ impl Project {
             .get_streamlet_mut(streamlet.streamlet())
     }
 
+    /// Resolve the name of a streamlet as seen from within a library.
+    ///
+    /// Streamlets of the library itself take precedence over streamlets of
+    /// the libraries it imports. Returns an error if an imported library does
+    /// not exist in this project, or if the streamlet is not found or is
+    /// found in multiple imported libraries.
+    pub fn resolve_streamlet(
+        &self,
+        lib: LibKey,
+        streamlet: StreamletKey,
+    ) -> Result<StreamletHandle> {
+        let library = self.get_lib(lib.clone())?;
+        if library.get_streamlet(streamlet.clone()).is_ok() {
+            return Ok(StreamletHandle { lib, streamlet });
+        }
+        let mut candidates = vec![];
+        for import in library.imports() {
+            let imported = self.libraries.get(import).ok_or_else(|| {
+                Error::ProjectError(format!(
+                    "Library {} imports library {}, which does not exist in project.",
+                    lib, import
+                ))
+            })?;
+            if imported.get_streamlet(streamlet.clone()).is_ok() {
+                candidates.push(import.clone());
+            }
+        }
+        match candidates.as_slice() {
+            [import] => Ok(StreamletHandle {
+                lib: import.clone(),
+                streamlet,
+            }),
+            [] => Err(Error::ProjectError(format!(
+                "Streamlet {} not found in library {} or its imports",
+                streamlet, lib
+            ))),
+            _ => Err(Error::ProjectError(format!(
+                "Streamlet {} is ambiguous in library {}, it is defined in imported libraries {}",
+                streamlet,
+                lib,
+                candidates
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))),
+        }
+    }
+
+    /// Returns the named types of the given imported libraries.
+    ///
+    /// Imported libraries that do not exist in this project are ignored.
+    /// Returns an error if multiple imported libraries declare a type with
+    /// the same name.
+    pub fn imported_types(&self, imports: &[LibKey]) -> Result<HashMap<Name, LogicalType>> {
+        let mut result: HashMap<Name, (LibKey, LogicalType)> = HashMap::new();
+        for import in imports {
+            if let Some(library) = self.libraries.get(import) {
+                for (name, typ) in library.types() {
+                    if let Some((other, _)) = result.get(name) {
+                        return Err(Error::ProjectError(format!(
+                            "Type {} is declared in both imported libraries {} and {}",
+                            name, other, import
+                        )));
+                    }
+                    result.insert(name.clone(), (import.clone(), typ.clone()));
+                }
+            }
+        }
+        Ok(result
+            .into_iter()
+            .map(|(name, (_, typ))| (name, typ))
+            .collect())
+    }
+
+    /// Add libraries from Streamlet Definition Files to this project.
+    /// Verilog source files add a library of blackbox streamlets instead,
+    /// see [Library::from_verilog].
+    ///
+    /// Libraries are parsed after the libraries they import, such that they
+    /// can refer to their named types. Returns the keys of the added
+    /// libraries in the order of the given paths, or an error if the imports
+    /// of the libraries form a cycle.
+    pub fn add_lib_files(&mut self, paths: &[impl AsRef<Path>]) -> Result<Vec<LibKey>> {
+        let mut pending = vec![];
+        for path in paths {
+            let path = path.as_ref();
+            let name = path.file_stem().and_then(|s| s.to_str()).ok_or_else(|| {
+                Error::FileIOError(format!("Invalid file name: {}", path.display()))
+            })?;
+            let input = std::fs::read_to_string(path)?;
+            let imports = if is_verilog(path) {
+                vec![]
+            } else {
+                crate::parser::nom::imports(&input)
+                    .map_err(|e| {
+                        Error::ParsingError(crate::parser::nom::report_error(
+                            &path.display().to_string(),
+                            &input,
+                            &e,
+                        ))
+                    })?
+                    .1
+            };
+            pending.push((Name::try_new(name)?, path, imports));
+        }
+        let keys = pending.iter().map(|(key, _, _)| key.clone()).collect();
+
+        while !pending.is_empty() {
+            let ready = pending.iter().position(|(_, _, imports)| {
+                imports
+                    .iter()
+                    .all(|i| !pending.iter().any(|(key, _, _)| key == i))
+            });
+            match ready {
+                Some(index) => {
+                    let (_, path, _) = pending.remove(index);
+                    let library = Library::from_file_in(path, self)?;
+                    self.add_lib(library).at(|| Location::new(path.display()))?;
+                }
+                None => {
+                    return Err(Error::ProjectError(format!(
+                        "Imports of libraries {} form a cycle",
+                        pending
+                            .iter()
+                            .map(|(key, _, _)| key.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )))
+                }
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Returns handles to all streamlets in this project, with their
+    /// streamlets, ordered by library and streamlet name.
+    fn sorted_streamlets(&self) -> Vec<(StreamletHandle, &Streamlet)> {
+        let mut result = self
+            .libraries()
+            .flat_map(|lib| {
+                lib.streamlets().map(move |s| {
+                    (
+                        StreamletHandle {
+                            lib: lib.key().clone(),
+                            streamlet: s.key(),
+                        },
+                        s,
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        result.sort_by(|(a, _), (b, _)| (&a.lib, &a.streamlet).cmp(&(&b.lib, &b.streamlet)));
+        result
+    }
+
+    /// Returns the streamlets that have an interface of exactly the given
+    /// logical type, ordered by library and streamlet name.
+    pub fn find_streamlets_by_type(&self, typ: &LogicalType) -> Vec<StreamletHandle> {
+        self.sorted_streamlets()
+            .into_iter()
+            .filter(|(_, s)| s.interfaces().any(|iface| iface.typ() == *typ))
+            .map(|(handle, _)| handle)
+            .collect()
+    }
+
+    /// Returns the interfaces of which the logical type is, or contains, the
+    /// given logical type, ordered by library, streamlet and interface name.
+    ///
+    /// To find the users of a named type, pass the type as returned by
+    /// [Library::get_type].
+    pub fn find_type_users(&self, typ: &LogicalType) -> Vec<InterfaceHandle> {
+        self.sorted_streamlets()
+            .into_iter()
+            .flat_map(|(handle, s)| {
+                s.interfaces()
+                    .filter(|iface| iface.typ().contains(typ))
+                    .map(|iface| InterfaceHandle {
+                        streamlet: handle.clone(),
+                        iface: iface.key().clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Returns the connections of all structural implementations in this
+    /// project that have an interface of the given streamlet as source or
+    /// sink, either because it is instantiated as a node, or because it is
+    /// the implemented streamlet itself.
+    pub fn connections(&self, streamlet: &StreamletHandle) -> Vec<Connection> {
+        let mut result = vec![];
+        for (handle, s) in self.sorted_streamlets() {
+            if let Some(implementation) = s.get_implementation() {
+                if let Implementation::Structural(structure) = implementation.as_ref() {
+                    let touches = |node| structure.get_node(&node).ok().as_ref() == Some(streamlet);
+                    result.extend(
+                        structure
+                            .edges()
+                            .filter(|edge| {
+                                touches(edge.source().node()) || touches(edge.sink().node())
+                            })
+                            .map(|edge| Connection {
+                                structure: handle.clone(),
+                                edge: edge.clone(),
+                            }),
+                    );
+                }
+            }
+        }
+        result
+    }
+
+    /// Rename a streamlet, and update all references to it in the structural
+    /// implementations of this project.
+    ///
+    /// Returns the handle of the renamed streamlet. Nothing is changed if the
+    /// library already contains a streamlet with the new name, or if the
+    /// streamlet has a back-end implementation, which refers to the
+    /// streamlet by a handle that cannot be updated.
+    pub fn rename_streamlet(
+        &mut self,
+        streamlet: StreamletHandle,
+        name: StreamletKey,
+    ) -> Result<StreamletHandle> {
+        let renamed = StreamletHandle {
+            lib: streamlet.lib(),
+            streamlet: name.clone(),
+        };
+        if let Some(Implementation::Backend(_)) = self
+            .get_streamlet(streamlet.clone())?
+            .get_implementation()
+            .as_deref()
+        {
+            return Err(Error::ProjectError(format!(
+                "Cannot rename streamlet {}.{}, it has a back-end implementation",
+                streamlet.lib, streamlet.streamlet
+            )));
+        }
+        if name == streamlet.streamlet {
+            return Ok(renamed);
+        }
+        self.get_lib_mut(streamlet.lib())?
+            .rename_streamlet(&streamlet.streamlet, name)?;
+        self.update_structures(|s| s.rename_streamlet(&streamlet, &renamed))?;
+        Ok(renamed)
+    }
+
+    /// Rename an interface of a streamlet, and update all connections to it
+    /// in the structural implementations of this project.
+    ///
+    /// Nothing is changed if the streamlet already has an interface with the
+    /// new name.
+    pub fn rename_interface(
+        &mut self,
+        streamlet: StreamletHandle,
+        iface: IFKey,
+        name: IFKey,
+    ) -> Result<()> {
+        if name == iface {
+            self.get_streamlet(streamlet.clone())?
+                .get_interface(iface)?;
+            return Ok(());
+        }
+        self.get_streamlet_mut(streamlet.clone())?
+            .rename_interface(&iface, name.clone())?;
+        self.update_structures(|s| s.rename_interface(&streamlet, &iface, &name))
+    }
+
+    /// Apply a function to the structural implementations of all streamlets.
+    fn update_structures(&mut self, f: impl Fn(&mut Structure)) -> Result<()> {
+        for library in self.libraries.values_mut() {
+            for streamlet in library.streamlets_mut() {
+                if let Some(implementation) = streamlet.get_implementation() {
+                    if let Implementation::Structural(structure) = implementation.as_ref() {
+                        let mut structure = structure.clone();
+                        f(&mut structure);
+                        streamlet.attach_implementation(Implementation::Structural(structure))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Add the implementation of a streamlet to the project.
     pub fn add_streamlet_impl(
         &mut self,
@@ -96,6 +586,347 @@ impl Project {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::design::implementation::composer::tests::{composition_test_proj, sdf_test_proj};
+    use crate::design::implementation::structure::Structure;
+    use crate::design::NodeIFHandle;
+    use crate::generator::common::convert::Portify;
+    use crate::{NamingPolicy, UniqueKeyBuilder};
+
+    #[test]
+    fn synthesis() -> Result<()> {
+        let prj = sdf_test_proj(&[
+            "Streamlet a (x : in Stream<Bits<8>>, y : out Stream<Bits<8>>)",
+            "Streamlet b (z : in Stream<Bits<8>>)",
+        ])?;
+        let lib = prj.get_lib(Name::try_new("lib")?)?;
+        let interfaces = lib
+            .streamlets()
+            .flat_map(|s| s.interfaces().map(|i| i.clone()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(interfaces.len(), 3);
+        // Identical types of different interfaces are synthesized once.
+        let synthesized = interfaces[0].synthesize();
+        for interface in &interfaces {
+            assert!(Arc::ptr_eq(&synthesized, &interface.synthesize()));
+        }
+        assert_eq!(prj.synthesis().len(), 1);
+        // The fancy ports of the interfaces reuse the synthesized type.
+        interfaces[0].fancy_with("x", "a_x", &NamingPolicy::default());
+        interfaces[2].fancy_with("z", "b_z", &NamingPolicy::default());
+        assert_eq!(prj.synthesis().len(), 1);
+        // Other projects have their own cache.
+        let other = sdf_test_proj(&["Streamlet c (x : in Stream<Bits<8>>)"])?;
+        assert!(other.synthesis().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn rename() -> Result<()> {
+        let mut prj = composition_test_proj()?;
+        let handle = |lib: &str, streamlet: &str| -> Result<StreamletHandle> {
+            Ok(StreamletHandle {
+                lib: Name::try_new(lib)?,
+                streamlet: Name::try_new(streamlet)?,
+            })
+        };
+        let iface = |node: &str, iface: &str| -> Result<NodeIFHandle> {
+            Ok(NodeIFHandle::new(
+                Name::try_new(node)?,
+                Name::try_new(iface)?,
+            ))
+        };
+        let (top, magic, op) = (
+            handle("compositions", "Top_level")?,
+            handle("primitives", "Magic")?,
+            handle("primitives", "test_op")?,
+        );
+        let mut structure = Structure::new(top.clone());
+        structure.add_node(Name::try_new("magic")?, magic.clone())?;
+        structure.add_node(Name::try_new("op")?, op.clone())?;
+        structure.connect(&prj, iface("this", "in")?, iface("magic", "in")?)?;
+        structure.connect(&prj, iface("op", "out")?, iface("this", "out")?)?;
+        prj.add_streamlet_impl(top.clone(), Implementation::Structural(structure))?;
+
+        assert_eq!(
+            prj.rename_streamlet(op.clone(), Name::try_new("Magic")?)
+                .unwrap_err()
+                .to_string(),
+            "Project error: Streamlet Magic already exists in library primitives"
+        );
+        let renamed = prj.rename_streamlet(op.clone(), Name::try_new("op")?)?;
+        assert!(prj.get_streamlet(op).is_err());
+        assert_eq!(prj.get_streamlet(renamed.clone())?.identifier(), "op");
+
+        assert!(prj
+            .rename_interface(top.clone(), Name::try_new("in")?, Name::try_new("out")?)
+            .is_err());
+        prj.rename_interface(top.clone(), Name::try_new("out")?, Name::try_new("result")?)?;
+        prj.rename_interface(magic.clone(), Name::try_new("in")?, Name::try_new("input")?)?;
+
+        let implementation = prj
+            .get_streamlet(top.clone())?
+            .get_implementation()
+            .unwrap();
+        match implementation.as_ref() {
+            Implementation::Structural(structure) => {
+                assert_eq!(structure.get_node(&Name::try_new("op")?)?, renamed.clone());
+                let edges = structure
+                    .edges()
+                    .map(|e| (e.source(), e.sink()))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    edges,
+                    vec![
+                        (iface("this", "in")?, iface("magic", "input")?),
+                        (iface("op", "out")?, iface("this", "result")?)
+                    ]
+                );
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn queries() -> Result<()> {
+        let mut prj = composition_test_proj()?;
+        let handle = |lib: &str, streamlet: &str| -> Result<StreamletHandle> {
+            Ok(StreamletHandle {
+                lib: Name::try_new(lib)?,
+                streamlet: Name::try_new(streamlet)?,
+            })
+        };
+        let (top, magic, op) = (
+            handle("compositions", "Top_level")?,
+            handle("primitives", "Magic")?,
+            handle("primitives", "test_op")?,
+        );
+
+        assert_eq!(
+            prj.find_streamlets_by_type(&"Stream<Bits<32>>".parse()?),
+            vec![top.clone(), op.clone()]
+        );
+        assert!(prj.find_streamlets_by_type(&"Bits<32>".parse()?).is_empty());
+        assert_eq!(
+            prj.find_type_users(&"Bits<32>".parse()?)
+                .iter()
+                .map(|i| format!("{}.{}.{}", i.streamlet.lib, i.streamlet.streamlet, i.iface))
+                .collect::<Vec<_>>(),
+            vec![
+                "compositions.Top_level.in",
+                "compositions.Top_level.out",
+                "primitives.Magic.in",
+                "primitives.Magic.out",
+                "primitives.test_op.in",
+                "primitives.test_op.out",
+            ]
+        );
+
+        let iface = |node: &str, iface: &str| -> Result<NodeIFHandle> {
+            Ok(NodeIFHandle::new(
+                Name::try_new(node)?,
+                Name::try_new(iface)?,
+            ))
+        };
+        let mut structure = Structure::new(top.clone());
+        structure.add_node(Name::try_new("magic")?, magic.clone())?;
+        structure.add_node(Name::try_new("op")?, op.clone())?;
+        structure.connect(&prj, iface("this", "in")?, iface("magic", "in")?)?;
+        structure.connect(&prj, iface("op", "out")?, iface("this", "out")?)?;
+        prj.add_streamlet_impl(top.clone(), Implementation::Structural(structure))?;
+
+        let connections = prj.connections(&op);
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].structure(), top);
+        assert_eq!(connections[0].edge().source(), iface("op", "out")?);
+        assert_eq!(prj.connections(&top).len(), 2);
+        assert!(prj.connections(&handle("compositions", "nope")?).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_streamlet() -> Result<()> {
+        let streamlet = |name: &str| -> Result<Streamlet> {
+            Streamlet::from_builder(Name::try_new(name)?, UniqueKeyBuilder::new(), None)
+        };
+        let key = |name: &str| Name::try_new(name).unwrap();
+        let library = |name: &str, imports: &[&str], streamlets: &[&str]| -> Result<Library> {
+            let mut lib = Library::try_new(
+                key(name),
+                vec![],
+                streamlets
+                    .iter()
+                    .map(|s| streamlet(s))
+                    .collect::<Result<_>>()?,
+            )?;
+            for import in imports {
+                lib.add_import(key(import))?;
+            }
+            Ok(lib)
+        };
+        let mut prj = Project::new(key("proj"));
+        prj.add_lib(library("a", &[], &["x", "y"])?)?;
+        prj.add_lib(library("b", &[], &["y"])?)?;
+        prj.add_lib(library("c", &["a", "b"], &["z"])?)?;
+        prj.add_lib(library("d", &["e"], &[])?)?;
+
+        let resolve = |lib: &str, streamlet: &str| {
+            prj.resolve_streamlet(key(lib), key(streamlet))
+                .map(|h| format!("{}.{}", h.lib(), h.streamlet()))
+                .map_err(|e| e.to_string())
+        };
+        assert_eq!(resolve("c", "z"), Ok("c.z".to_string()));
+        assert_eq!(resolve("c", "x"), Ok("a.x".to_string()));
+        assert_eq!(
+            resolve("c", "y"),
+            Err(
+                "Project error: Streamlet y is ambiguous in library c, it is defined in \
+                 imported libraries a, b"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            resolve("b", "x"),
+            Err("Project error: Streamlet x not found in library b or its imports".to_string())
+        );
+        assert_eq!(
+            resolve("d", "x"),
+            Err(
+                "Project error: Library d imports library e, which does not exist in project."
+                    .to_string()
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn add_lib_files() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let file = |name: &str, contents: &str| -> Result<std::path::PathBuf> {
+            let path = tmpdir.path().join(name);
+            std::fs::write(&path, contents)?;
+            Ok(path)
+        };
+        let top = file(
+            "top.sdf",
+            "import types;\n\nStreamlet top (a : in Stream<Pixel>)\n",
+        )?;
+        let types = file(
+            "types.sdf",
+            "type Pixel = Group<r: Bits<8>, g: Bits<8>, b: Bits<8>>;\n",
+        )?;
+
+        let mut prj = Project::new(Name::try_new("proj")?);
+        assert_eq!(
+            prj.add_lib_files(&[&top, &types])?,
+            vec![Name::try_new("top")?, Name::try_new("types")?]
+        );
+        assert_eq!(
+            prj.get_streamlet(StreamletHandle {
+                lib: Name::try_new("top")?,
+                streamlet: Name::try_new("top")?,
+            })?
+            .to_string(),
+            "Streamlet top (\n  a : in Stream<Group<r: Bits<8>, g: Bits<8>, b: Bits<8>>>\n)"
+        );
+
+        // Verilog modules are added as blackbox streamlets.
+        let ip = file(
+            "ip.v",
+            "module fifo (input clk, input rst, input a_valid, output a_ready,\n\
+             input [7:0] a_data, output b_valid, input b_ready, output [7:0] b_data);\n\
+             endmodule\n",
+        )?;
+        assert_eq!(prj.add_lib_files(&[&ip])?, vec![Name::try_new("ip")?]);
+        let fifo = prj.get_streamlet(StreamletHandle {
+            lib: Name::try_new("ip")?,
+            streamlet: Name::try_new("fifo")?,
+        })?;
+        assert_eq!(fifo.external(), Some((None, "fifo")));
+        assert_eq!(fifo.interfaces().count(), 2);
+
+        // Without the imported library, the named type is unknown.
+        assert!(Project::new(Name::try_new("proj")?)
+            .add_lib_files(&[&top])
+            .is_err());
+
+        let a = file("a.sdf", "import b;\n")?;
+        let b = file("b.sdf", "import a;\n")?;
+        assert_eq!(
+            Project::new(Name::try_new("proj")?)
+                .add_lib_files(&[a, b])
+                .unwrap_err()
+                .to_string(),
+            "Project error: Imports of libraries a, b form a cycle"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn identifier_policy() -> Result<()> {
+        use crate::identifier::IdentifierTarget;
+        use crate::parser::nom::streamlet;
+
+        let lib = || {
+            Library::try_new(
+                Name::try_new("lib")?,
+                vec![],
+                vec![streamlet("Streamlet x (signal : in Bits<1>)").unwrap().1],
+            )
+        };
+        let policy = IdentifierPolicy::new().with_target(IdentifierTarget::Vhdl);
+
+        assert!(Project::new(Name::try_new("proj")?).add_lib(lib()?).is_ok());
+        let error = Project::new(Name::try_new("proj")?)
+            .with_identifier_policy(policy.clone())
+            .add_lib(lib()?)
+            .unwrap_err();
+        assert_eq!(
+            error.root(),
+            &Error::InvalidArgument("name signal is a reserved word in VHDL".to_string())
+        );
+        assert_eq!(error.notes(), &["in lib.x.signal".to_string()]);
+        // Lenient policies sanitize the names instead.
+        let mut prj = Project::new(Name::try_new("proj")?).with_identifier_policy(policy.lenient());
+        let key = prj.add_lib(Library::try_new(
+            Name::try_new("entity")?,
+            vec![],
+            vec![
+                streamlet("Streamlet process (signal : in Bits<1>, data : out Bits<1>)")
+                    .unwrap()
+                    .1,
+            ],
+        )?)?;
+        assert_eq!(key, Name::try_new("entity_i")?);
+        let process = prj.get_streamlet(StreamletHandle {
+            lib: key,
+            streamlet: Name::try_new("process_i")?,
+        })?;
+        assert_eq!(
+            process
+                .interfaces()
+                .map(|i| i.key().to_string())
+                .collect::<Vec<_>>(),
+            vec!["data", "signal_i"]
+        );
+        assert!(prj.get_lib(Name::try_new("entity")?).is_err());
+        // Sanitized names that collide are rejected, and the library is not
+        // added.
+        let error = prj
+            .add_lib(Library::try_new(
+                Name::try_new("lib")?,
+                vec![],
+                vec![
+                    streamlet("Streamlet x (signal : in Bits<1>, signal_i : in Bits<1>)")
+                        .unwrap()
+                        .1,
+                ],
+            )?)
+            .unwrap_err();
+        assert!(error.to_string().contains("signal_i"), "{}", error);
+        assert!(prj.get_lib(Name::try_new("lib")?).is_err());
+        Ok(())
+    }
 
     /// Some projects that can be used throughout the crate for testing.
     pub mod proj {
@@ -103,12 +934,15 @@ pub mod tests {
         use super::*;
 
         pub(crate) fn empty_proj() -> Project {
-            let mut libraries = HashMap::new();
+            let mut libraries = IndexMap::new();
             let empty_lib = crate::design::library::tests::libs::empty_lib();
             libraries.insert(empty_lib.key().clone(), empty_lib);
             Project {
                 name: Name::try_new("proj").unwrap(),
                 libraries: libraries,
+                manifest: None,
+                identifier_policy: IdentifierPolicy::default(),
+                synthesis: Arc::default(),
             }
         }
     }
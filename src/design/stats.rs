@@ -0,0 +1,298 @@
+//! Statistics of the interfaces of a design.
+//!
+//! This module counts the interfaces and physical streams of the streamlets
+//! of a [Project], their payload bits, and the distribution of the
+//! dimensionality and complexity of their physical streams, per streamlet,
+//! per library and for the whole project, e.g. for design reviews.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::{LibKey, Project, Streamlet, StreamletKey};
+use crate::physical::Complexity;
+use crate::{NonNegative, WideNonNegative};
+
+/// Interface statistics of a streamlet, a library or a project.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    interfaces: usize,
+    streams: usize,
+    payload_bits: WideNonNegative,
+    dimensionality: BTreeMap<NonNegative, usize>,
+    complexity: Vec<(Complexity, usize)>,
+}
+
+impl Stats {
+    /// Returns the number of interfaces.
+    pub fn interfaces(&self) -> usize {
+        self.interfaces
+    }
+
+    /// Returns the number of physical streams.
+    pub fn streams(&self) -> usize {
+        self.streams
+    }
+
+    /// Returns the total number of payload bits, i.e. the data and user bits
+    /// of all physical streams, and all asynchronous signal bits.
+    pub fn payload_bits(&self) -> WideNonNegative {
+        self.payload_bits
+    }
+
+    /// Returns the number of physical streams of every dimensionality, from
+    /// lowest to highest.
+    pub fn dimensionality(&self) -> impl Iterator<Item = (NonNegative, usize)> + '_ {
+        self.dimensionality.iter().map(|(d, n)| (*d, *n))
+    }
+
+    /// Returns the number of physical streams of every complexity, from
+    /// lowest to highest.
+    pub fn complexity(&self) -> impl Iterator<Item = (&Complexity, usize)> {
+        self.complexity.iter().map(|(c, n)| (c, *n))
+    }
+
+    fn add_complexity(&mut self, complexity: &Complexity, count: usize) {
+        match self.complexity.iter().position(|(c, _)| c >= complexity) {
+            Some(index) if self.complexity[index].0 == *complexity => {
+                self.complexity[index].1 += count
+            }
+            Some(index) => self.complexity.insert(index, (complexity.clone(), count)),
+            None => self.complexity.push((complexity.clone(), count)),
+        }
+    }
+
+    /// Add the statistics of another design element to these.
+    fn merge(&mut self, other: &Stats) {
+        self.interfaces += other.interfaces;
+        self.streams += other.streams;
+        self.payload_bits += other.payload_bits;
+        for (dimensionality, count) in other.dimensionality() {
+            *self.dimensionality.entry(dimensionality).or_default() += count;
+        }
+        for (complexity, count) in other.complexity() {
+            self.add_complexity(complexity, count);
+        }
+    }
+}
+
+impl From<&Streamlet> for Stats {
+    fn from(streamlet: &Streamlet) -> Self {
+        let mut result = Stats::default();
+        for interface in streamlet.interfaces() {
+            let synthesized = interface.synthesize();
+            result.interfaces += 1;
+            result.payload_bits += synthesized
+                .signals()
+                .map(|(_, b)| b.get() as WideNonNegative)
+                .sum::<WideNonNegative>();
+            for (_, stream) in synthesized.streams() {
+                result.streams += 1;
+                result.payload_bits += stream.data_bit_count() + stream.user_bit_count();
+                *result
+                    .dimensionality
+                    .entry(stream.dimensionality())
+                    .or_default() += 1;
+                result.add_complexity(stream.complexity(), 1);
+            }
+        }
+        result
+    }
+}
+
+impl fmt::Display for Stats {
+    /// Display the counts on a single line.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} interfaces, {} streams, {} payload bits",
+            self.interfaces, self.streams, self.payload_bits
+        )
+    }
+}
+
+/// Write the counts of statistics, followed by the distributions of
+/// dimensionality and complexity on lines with the given indentation, if
+/// there are any physical streams.
+fn write_stats(f: &mut fmt::Formatter, stats: &Stats, indent: &str) -> fmt::Result {
+    writeln!(f, "{}", stats)?;
+    if stats.streams > 0 {
+        writeln!(
+            f,
+            "{}dimensionality: {}",
+            indent,
+            stats
+                .dimensionality()
+                .map(|(d, n)| format!("d={}: {}", d, n))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        writeln!(
+            f,
+            "{}complexity: {}",
+            indent,
+            stats
+                .complexity()
+                .map(|(c, n)| format!("c={}: {}", c, n))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+    }
+    Ok(())
+}
+
+/// Statistics of the streamlets of a library.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LibraryStats {
+    key: LibKey,
+    streamlets: Vec<(StreamletKey, Stats)>,
+    total: Stats,
+}
+
+impl LibraryStats {
+    /// Returns the name of the library.
+    pub fn key(&self) -> &LibKey {
+        &self.key
+    }
+
+    /// Returns the statistics of every streamlet of the library, in order of
+    /// their names.
+    pub fn streamlets(&self) -> impl Iterator<Item = (&StreamletKey, &Stats)> {
+        self.streamlets.iter().map(|(k, s)| (k, s))
+    }
+
+    /// Returns the combined statistics of all streamlets of the library.
+    pub fn total(&self) -> &Stats {
+        &self.total
+    }
+}
+
+/// Statistics of the libraries of a project.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectStats {
+    libraries: Vec<LibraryStats>,
+    total: Stats,
+}
+
+impl ProjectStats {
+    /// Returns the statistics of every library of the project, in order of
+    /// their names.
+    pub fn libraries(&self) -> impl Iterator<Item = &LibraryStats> {
+        self.libraries.iter()
+    }
+
+    /// Returns the combined statistics of all streamlets of the project.
+    pub fn total(&self) -> &Stats {
+        &self.total
+    }
+}
+
+impl fmt::Display for ProjectStats {
+    /// Display a human-readable report of the statistics of every library and
+    /// streamlet, and the totals of the project.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for library in &self.libraries {
+            write!(
+                f,
+                "Library {}: {} streamlets, ",
+                library.key,
+                library.streamlets.len()
+            )?;
+            write_stats(f, &library.total, "  ")?;
+            for (streamlet, stats) in &library.streamlets {
+                write!(f, "  Streamlet {}: ", streamlet)?;
+                write_stats(f, stats, "    ")?;
+            }
+        }
+        write!(
+            f,
+            "Total: {} libraries, {} streamlets, ",
+            self.libraries.len(),
+            self.libraries
+                .iter()
+                .map(|l| l.streamlets.len())
+                .sum::<usize>()
+        )?;
+        write_stats(f, &self.total, "  ")
+    }
+}
+
+impl Project {
+    /// Collect the interface statistics of all streamlets of this project.
+    pub fn stats(&self) -> ProjectStats {
+        let mut libraries = self.libraries().collect::<Vec<_>>();
+        libraries.sort_by_key(|l| l.key().clone());
+        let mut total = Stats::default();
+        let libraries = libraries
+            .into_iter()
+            .map(|library| {
+                let mut streamlets = library.streamlets().collect::<Vec<_>>();
+                streamlets.sort_by_key(|s| s.key().clone());
+                let mut library_total = Stats::default();
+                let streamlets = streamlets
+                    .into_iter()
+                    .map(|streamlet| {
+                        let stats = Stats::from(streamlet);
+                        library_total.merge(&stats);
+                        (streamlet.key().clone(), stats)
+                    })
+                    .collect();
+                total.merge(&library_total);
+                LibraryStats {
+                    key: library.key().clone(),
+                    streamlets,
+                    total: library_total,
+                }
+            })
+            .collect();
+        ProjectStats { libraries, total }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::Library;
+    use crate::parser::nom::streamlet;
+    use crate::{Name, Result};
+
+    #[test]
+    fn project_stats() -> Result<()> {
+        let mut prj = Project::new(Name::try_new("proj")?);
+        prj.add_lib(Library::try_new(
+            Name::try_new("lib")?,
+            vec![],
+            vec![
+                streamlet(
+                    "Streamlet a (x : in Stream<Bits<8>, d=1, c=1>, \
+                     y : out Stream<Bits<8>, t=2, u=Bits<2>>)",
+                )
+                .unwrap()
+                .1,
+                streamlet("Streamlet b (x : in Bits<3>)").unwrap().1,
+            ],
+        )?)?;
+        let stats = prj.stats();
+        assert_eq!(stats.total().interfaces(), 3);
+        assert_eq!(stats.total().streams(), 2);
+        assert_eq!(stats.total().payload_bits(), 8 + 16 + 2 + 3);
+        assert_eq!(
+            stats.total().dimensionality().collect::<Vec<_>>(),
+            vec![(0, 1), (1, 1)]
+        );
+        assert_eq!(
+            stats.to_string(),
+            "Library lib: 2 streamlets, 3 interfaces, 2 streams, 29 payload bits\n\
+             \x20 dimensionality: d=0: 1, d=1: 1\n\
+             \x20 complexity: c=1: 1, c=4: 1\n\
+             \x20 Streamlet a: 2 interfaces, 2 streams, 26 payload bits\n\
+             \x20   dimensionality: d=0: 1, d=1: 1\n\
+             \x20   complexity: c=1: 1, c=4: 1\n\
+             \x20 Streamlet b: 1 interfaces, 0 streams, 3 payload bits\n\
+             Total: 1 libraries, 2 streamlets, 3 interfaces, 2 streams, 29 payload bits\n\
+             \x20 dimensionality: d=0: 1, d=1: 1\n\
+             \x20 complexity: c=1: 1, c=4: 1\n"
+        );
+        Ok(())
+    }
+}
@@ -3,18 +3,25 @@
 //! A streamlet is a component where every [Interface] has a [LogicalType].
 
 use std::cell::{Ref, RefCell, RefMut};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryInto;
+use std::fmt;
 use std::fmt::Debug;
 use std::rc::Rc;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use crate::design::implementation::composer::GenericComponent;
 use crate::design::implementation::Implementation;
-use crate::design::{ComponentKey, IFKey};
-use crate::logical::LogicalType;
+use crate::design::metadata::{Metadata, DEPTH, EXTERNAL, LATENCY, THROUGHPUT};
+use crate::design::param::{NamedParameter, ParameterVariant};
+use crate::design::{ComponentKey, IFKey, ParamKey};
+use crate::logical::{Direction, LogicalStream, LogicalType, SynthesisCache};
 use crate::traits::Identify;
-use crate::{Document, Error, Name, Result, Reverse, Reversed, UniqueKeyBuilder};
+use crate::util::fnv1a;
+use crate::{
+    Document, Error, Name, NonNegative, PathName, Result, Reverse, Reversed, UniqueKeyBuilder,
+};
 
 /// Streamlet interface mode.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -25,6 +32,15 @@ pub enum Mode {
     In,
 }
 
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Out => write!(f, "out"),
+            Mode::In => write!(f, "in"),
+        }
+    }
+}
+
 impl Reverse for Mode {
     fn reverse(&mut self) {
         match self {
@@ -49,6 +65,38 @@ impl FromStr for Mode {
     }
 }
 
+/// The position of an interface in an array of identical interfaces, e.g.
+/// `req[4] : in Stream<Bits<8>>`.
+///
+/// The elements of an array are separate interfaces named after the array and
+/// their index, e.g. `req_0` up to and including `req_3`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterfaceIndex {
+    /// The name of the array.
+    array: Name,
+    /// The index of the interface in the array.
+    index: NonNegative,
+    /// The number of interfaces in the array.
+    length: NonNegative,
+}
+
+impl InterfaceIndex {
+    /// Returns the name of the array.
+    pub fn array(&self) -> &Name {
+        &self.array
+    }
+
+    /// Returns the index of the interface in the array.
+    pub fn index(&self) -> NonNegative {
+        self.index
+    }
+
+    /// Returns the number of interfaces in the array.
+    pub fn length(&self) -> NonNegative {
+        self.length
+    }
+}
+
 /// A Streamlet interface.
 ///
 /// The names "clk" and "rst" are reserved.
@@ -60,10 +108,32 @@ pub struct Interface {
     mode: Mode,
     /// The type of the interface.
     typ: LogicalType,
+    /// The type of the interface in Streamlet Definition File syntax, which
+    /// may refer to the parameters of its streamlet.
+    template: Option<String>,
     /// Type inference function
     inf_f: Option<Box<fn(LogicalType) -> Result<LogicalType>>>,
     /// The documentation string of the interface, if any.
     doc: Option<String>,
+    /// The position of the interface in an interface array, if any.
+    array: Option<InterfaceIndex>,
+    /// The cache the type of the interface is synthesized with.
+    synthesis: Synthesis,
+}
+
+/// The cache an interface synthesizes its type with, which is shared by the
+/// interfaces of a project once they are added to it, see
+/// [Project::add_lib](crate::design::Project::add_lib).
+///
+/// It does not affect the interface, and is therefore ignored when comparing
+/// interfaces.
+#[derive(Clone, Debug, Default)]
+struct Synthesis(Arc<SynthesisCache>);
+
+impl PartialEq for Synthesis {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
 }
 
 impl Identify for Interface {
@@ -114,17 +184,95 @@ impl Interface {
                 key: n,
                 mode,
                 typ: t,
+                template: None,
                 inf_f: None,
                 doc: doc.map(|d| d.to_string()),
+                array: None,
+                synthesis: Synthesis::default(),
             }),
         }
     }
 
+    /// Try to construct an array of identical interfaces, e.g.
+    /// `req[4] : in Stream<Bits<8>>`, as separate interfaces named after the
+    /// array and their index.
+    ///
+    /// # Example:
+    /// ```
+    /// use tydi::logical::LogicalType;
+    /// use tydi::design::{Interface, Mode};
+    ///
+    /// let req = Interface::try_new_array("req", 4, Mode::In, LogicalType::try_new_bits(8)?, None)?;
+    /// assert_eq!(req.len(), 4);
+    /// assert_eq!(req[2].key().to_string(), "req_2");
+    /// assert_eq!(req[2].array().unwrap().index(), 2);
+    /// assert_eq!(req[2].to_string(), "req[4] : in Bits<8>");
+    ///
+    /// // Arrays cannot be empty.
+    /// assert!(Interface::try_new_array("req", 0, Mode::In, LogicalType::Null, None).is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn try_new_array(
+        name: impl TryInto<Name, Error = impl Into<Box<dyn std::error::Error>>>,
+        length: NonNegative,
+        mode: Mode,
+        typ: impl TryInto<LogicalType, Error = impl Into<Box<dyn std::error::Error>>>,
+        doc: Option<&str>,
+    ) -> Result<Vec<Self>> {
+        let array: Name = name
+            .try_into()
+            .map_err(|e| Error::InterfaceError(e.into().to_string()))?;
+        let typ: LogicalType = typ
+            .try_into()
+            .map_err(|e| Error::InterfaceError(e.into().to_string()))?;
+        if length == 0 {
+            return Err(Error::InterfaceError(format!(
+                "Interface array {} cannot be empty.",
+                array
+            )));
+        }
+        (0..length)
+            .map(|index| {
+                let mut iface =
+                    Interface::try_new(format!("{}_{}", array, index), mode, typ.clone(), doc)?;
+                iface.array = Some(InterfaceIndex {
+                    array: array.clone(),
+                    index,
+                    length,
+                });
+                Ok(iface)
+            })
+            .collect()
+    }
+
+    /// Returns the position of this interface in an interface array, if it
+    /// is an element of one.
+    pub fn array(&self) -> Option<&InterfaceIndex> {
+        self.array.as_ref()
+    }
+
     pub fn with_doc(mut self, doc: impl Into<String>) -> Self {
         self.doc = Some(doc.into());
         self
     }
 
+    /// Return this interface with a type template added.
+    ///
+    /// The template is the type of the interface in Streamlet Definition File
+    /// syntax, in which the parameters of the streamlet can be used wherever
+    /// a natural number is expected, e.g. `Stream<Bits<WIDTH>, d=DEPTH>`. The
+    /// type of the interface is elaborated from the template when it is added
+    /// to a streamlet with [Streamlet::with_parameters].
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+
+    /// Returns the type template of this interface, if any.
+    pub fn template(&self) -> Option<&str> {
+        self.template.as_deref()
+    }
+
     pub fn with_type_inference(mut self, inf_f: fn(LogicalType) -> Result<LogicalType>) -> Self {
         self.inf_f = Option::from(Box::new(inf_f));
         self
@@ -153,6 +301,123 @@ impl Interface {
     pub fn typ(&self) -> LogicalType {
         self.typ.clone()
     }
+
+    /// Returns the cache the type of this interface is synthesized with.
+    pub(crate) fn synthesis(&self) -> &SynthesisCache {
+        &self.synthesis.0
+    }
+
+    /// Synthesizes the type of this interface with a cache from now on,
+    /// e.g. the cache of the project it is added to.
+    pub(crate) fn set_synthesis(&mut self, cache: Arc<SynthesisCache>) {
+        self.synthesis = Synthesis(cache);
+    }
+
+    /// Returns the signals and physical streams of the type of this
+    /// interface. Types with the same structure are only synthesized once
+    /// per cache, see [SynthesisCache].
+    pub(crate) fn synthesize(&self) -> Arc<LogicalStream> {
+        self.synthesis.0.synthesize(&self.typ)
+    }
+
+    /// Returns an interface with the name and mode of this interface, of
+    /// which the type is the type of a field of the Group type of this
+    /// interface, see [LogicalType::field].
+    pub fn field(&self, path: &PathName) -> Result<Interface> {
+        match self.typ.field(path) {
+            Some(typ) => Ok(Interface {
+                key: self.key.clone(),
+                mode: self.mode,
+                typ: typ.clone(),
+                template: None,
+                inf_f: None,
+                doc: None,
+                array: self.array.clone(),
+                synthesis: self.synthesis.clone(),
+            }),
+            None => Err(Error::InterfaceError(format!(
+                "Interface {} has no field {}",
+                self.key, path
+            ))),
+        }
+    }
+
+    /// Render this interface in Streamlet Definition File syntax, including
+    /// its documentation.
+    pub fn to_sdf(&self) -> String {
+        format!("{}{}", sdf_doc(self.doc.as_deref(), ""), self)
+    }
+
+    /// Returns `Ok(())` if this interface, as the source of a connection, can
+    /// drive the provided sink interface.
+    ///
+    /// Both interfaces must result in the same asynchronous signals and
+    /// physical streams, and every physical stream must be able to drive its
+    /// counterpart as defined by
+    /// [PhysicalStream::can_drive](crate::physical::PhysicalStream::can_drive). Physical streams
+    /// with a reverse direction are checked from sink to source.
+    pub fn can_drive(&self, sink: &Interface) -> Result<()> {
+        let err = |msg: String| {
+            Error::InterfaceError(format!(
+                "Interface {} cannot drive interface {}: {}",
+                self.key, sink.key, msg
+            ))
+        };
+        let (source_synth, sink_synth) = (self.synthesize(), sink.synthesize());
+        if !source_synth.signals().eq(sink_synth.signals()) {
+            return Err(err("asynchronous signals do not match".to_string()));
+        }
+        if !source_synth
+            .streams()
+            .map(|(path_name, _)| path_name)
+            .eq(sink_synth.streams().map(|(path_name, _)| path_name))
+        {
+            return Err(err("physical streams do not match".to_string()));
+        }
+        for (((path_name, source), (_, sink)), (_, logical)) in source_synth
+            .streams()
+            .zip(sink_synth.streams())
+            .zip(self.typ.split_streams().streams())
+        {
+            match logical {
+                LogicalType::Stream(stream) if stream.direction() == Direction::Reverse => {
+                    sink.can_drive(source)
+                }
+                _ => source.can_drive(sink),
+            }
+            .map_err(|e| {
+                if path_name.is_empty() {
+                    err(e.to_string())
+                } else {
+                    err(format!("stream {}: {}", path_name, e))
+                }
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Interface {
+    /// Display an interface in Streamlet Definition File syntax, without its
+    /// documentation.
+    ///
+    /// An element of an interface array is displayed as the whole array.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.array {
+            Some(array) => write!(f, "{}[{}]", array.array, array.length)?,
+            None => write!(f, "{}", self.key)?,
+        }
+        match &self.template {
+            Some(template) => write!(f, " : {} {}", self.mode, template),
+            None => write!(f, " : {} {}", self.mode, self.typ),
+        }
+    }
+}
+
+impl Document for Interface {
+    fn doc(&self) -> Option<String> {
+        self.doc.clone()
+    }
 }
 
 impl Reverse for Interface {
@@ -166,10 +431,14 @@ impl Reverse for Interface {
 pub struct Streamlet {
     /// The name of the streamlet.
     key: Name,
+    /// The parameters of the streamlet, with their default values.
+    parameters: Vec<NamedParameter>,
     /// The interfaces of the streamlet.
     interfaces: BTreeMap<IFKey, Rc<RefCell<Interface>>>,
     /// An optional documentation string for the streamlet to be used by back-ends.
     doc: Option<String>,
+    /// Key/value metadata of the streamlet, such as its author and version.
+    metadata: Metadata,
     /// Placeholder for future implementation of the streamlet. If this is None, it is a primitive.
     implementation: Option<Rc<Implementation>>,
 }
@@ -232,6 +501,14 @@ impl Streamlet {
         Ok(())
     }
 
+    /// Synthesizes the types of the interfaces of this streamlet with a
+    /// cache from now on, see [Interface::synthesize].
+    pub(crate) fn set_synthesis(&self, cache: &Arc<SynthesisCache>) {
+        for iface in self.interfaces.values() {
+            iface.borrow_mut().set_synthesis(Arc::clone(cache));
+        }
+    }
+
     /// Construct a new streamlet from an interface builder that makes sure all interface names
     /// are unique.
     ///
@@ -264,26 +541,418 @@ impl Streamlet {
     ) -> Result<Self> {
         Ok(Streamlet {
             key: name,
+            parameters: Vec::new(),
             interfaces: builder
                 .finish()?
                 .into_iter()
                 .map(|iface| (iface.key().clone(), Rc::new(RefCell::new(iface))))
                 .collect::<BTreeMap<IFKey, Rc<RefCell<Interface>>>>(),
             doc: doc.map(|d| d.to_string()),
+            metadata: Metadata::new(),
             implementation: None,
         })
     }
 
-    #[allow(dead_code)]
+    /// Return this streamlet with parameters added.
+    ///
+    /// Parameters must be natural numbers, i.e. [ParameterVariant::UInt].
+    /// Their values are the defaults of the streamlet. The types of all
+    /// interfaces with a template are elaborated using these defaults.
+    ///
+    /// # Example
+    /// ```
+    /// use tydi::{Name, UniqueKeyBuilder};
+    /// use tydi::logical::LogicalType;
+    /// use tydi::design::{Mode, Interface, Streamlet};
+    /// use tydi::design::param::{NamedParameter, ParameterVariant};
+    ///
+    /// let streamlet = Streamlet::from_builder(
+    ///     Name::try_new("fifo")?,
+    ///     UniqueKeyBuilder::new().with_items(vec![
+    ///         Interface::try_new("a", Mode::In, LogicalType::Null, None)?
+    ///             .with_template("Stream<Bits<WIDTH>>"),
+    ///     ]),
+    ///     None,
+    /// )?
+    /// .with_parameters(UniqueKeyBuilder::new().with_items(vec![
+    ///     NamedParameter::try_new("WIDTH", ParameterVariant::UInt(8), None)?,
+    /// ]))?;
+    /// assert_eq!(streamlet.to_string(), "Streamlet fifo <WIDTH = 8> (\n  a : in Stream<Bits<WIDTH>>\n)");
+    ///
+    /// let wide = streamlet.elaborate(&[(Name::try_new("WIDTH")?, 32)])?;
+    /// assert_eq!(
+    ///     wide.parameters().next().unwrap().item(),
+    ///     &ParameterVariant::UInt(32)
+    /// );
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_parameters(mut self, builder: UniqueKeyBuilder<NamedParameter>) -> Result<Self> {
+        let parameters = builder.finish()?;
+        for parameter in &parameters {
+            if !matches!(parameter.item(), ParameterVariant::UInt(_)) {
+                return Err(Error::InvalidArgument(format!(
+                    "Parameter {} of streamlet {} is not a natural number",
+                    parameter.key(),
+                    self.key
+                )));
+            }
+        }
+        self.parameters = parameters;
+        self.elaborate(&[])
+    }
+
+    /// Returns an iterator over the parameters of this streamlet.
+    pub fn parameters(&self) -> impl Iterator<Item = &NamedParameter> {
+        self.parameters.iter()
+    }
+
+    /// Returns a copy of this streamlet in which the given parameters have
+    /// the given values, and the types of all interfaces with a template are
+    /// elaborated accordingly. Parameters that are not given keep their
+    /// values.
+    pub fn elaborate(&self, values: &[(ParamKey, NonNegative)]) -> Result<Streamlet> {
+        for (key, _) in values {
+            if !self.parameters.iter().any(|p| p.key() == key) {
+                return Err(Error::InvalidArgument(format!(
+                    "Streamlet {} has no parameter {}",
+                    self.key, key
+                )));
+            }
+        }
+        let parameters = self
+            .parameters
+            .iter()
+            .map(|p| match values.iter().find(|(key, _)| key == p.key()) {
+                Some((_, value)) => NamedParameter::try_new(
+                    p.key().clone(),
+                    ParameterVariant::UInt(*value),
+                    p.doc().as_deref(),
+                ),
+                None => Ok(p.clone()),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let bindings = parameters
+            .iter()
+            .filter_map(|p| match p.item() {
+                ParameterVariant::UInt(value) => Some((p.key().clone(), *value)),
+                _ => None,
+            })
+            .collect::<HashMap<_, _>>();
+        let interfaces = self
+            .interfaces
+            .iter()
+            .map(|(key, iface)| {
+                let mut iface = iface.borrow().clone();
+                if let Some(template) = &iface.template {
+                    iface.typ =
+                        crate::parser::nom::elaborate(template, bindings.clone()).map_err(|e| {
+                            Error::InterfaceError(format!(
+                                "Cannot elaborate interface {} of streamlet {}: {}",
+                                key, self.key, e
+                            ))
+                        })?;
+                }
+                Ok((key.clone(), Rc::new(RefCell::new(iface))))
+            })
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        Ok(Streamlet {
+            key: self.key.clone(),
+            parameters,
+            interfaces,
+            doc: self.doc.clone(),
+            metadata: self.metadata.clone(),
+            implementation: self.implementation.clone(),
+        })
+    }
+
     pub(crate) fn set_key(&mut self, key: ComponentKey) {
         self.key = key;
     }
 
+    /// Rename an interface of this streamlet.
+    pub(crate) fn rename_interface(&mut self, key: &IFKey, name: IFKey) -> Result<()> {
+        if self.interfaces.contains_key(&name) {
+            return Err(Error::InterfaceError(format!(
+                "Interface {} already exists for Streamlet {}.",
+                name, self.key
+            )));
+        }
+        let iface = self.interfaces.remove(key).ok_or_else(|| {
+            Error::InterfaceError(format!(
+                "Interface {} does not exist for Streamlet {}.",
+                key, self.key
+            ))
+        })?;
+        // A renamed interface is no longer named after its array.
+        iface.borrow_mut().key = name.clone();
+        iface.borrow_mut().array = None;
+        self.interfaces.insert(name, iface);
+        Ok(())
+    }
+
     /// Return this streamlet with documentation added.
     pub fn with_doc(mut self, doc: impl Into<String>) -> Self {
         self.doc = Some(doc.into());
         self
     }
+
+    /// Return this streamlet with metadata added.
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Returns the metadata of this streamlet.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns the metadata of this streamlet, for modification.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    /// Returns the latencies in clock cycles of the paths from inputs to
+    /// outputs of this streamlet, as declared by its `latency` metadata,
+    /// e.g. `@latency a->b=3, a->c=1`.
+    ///
+    /// A streamlet without latency metadata has a path with a latency of
+    /// zero cycles from each of its inputs to each of its outputs. A
+    /// streamlet with latency metadata only has the declared paths.
+    pub fn latencies(&self) -> Result<Vec<(IFKey, IFKey, NonNegative)>> {
+        match self.metadata.get(LATENCY) {
+            None => {
+                let names = |mode| {
+                    self.interfaces
+                        .values()
+                        .filter(|i| i.borrow().mode() == mode)
+                        .map(|i| i.borrow().key().clone())
+                        .collect::<Vec<_>>()
+                };
+                let outputs = names(Mode::Out);
+                Ok(names(Mode::In)
+                    .into_iter()
+                    .flat_map(|i| outputs.iter().map(move |o| (i.clone(), o.clone(), 0)))
+                    .collect())
+            }
+            Some(latencies) => latencies
+                .split(',')
+                .map(|path| path.trim())
+                .filter(|path| !path.is_empty())
+                .map(|path| {
+                    let err = || {
+                        Error::InvalidArgument(format!(
+                            "Invalid latency \"{}\" of streamlet {}, expected input->output=cycles",
+                            path, self.key
+                        ))
+                    };
+                    let (ifaces, cycles) = path.split_once('=').ok_or_else(err)?;
+                    let (input, output) = ifaces.split_once("->").ok_or_else(err)?;
+                    let input = Name::try_new(input.trim())?;
+                    let output = Name::try_new(output.trim())?;
+                    if self.get_interface(input.clone())?.mode() != Mode::In
+                        || self.get_interface(output.clone())?.mode() != Mode::Out
+                    {
+                        return Err(err());
+                    }
+                    let cycles = cycles.trim().parse::<NonNegative>().map_err(|_| err())?;
+                    Ok((input, output, cycles))
+                })
+                .collect(),
+        }
+    }
+
+    /// Return this streamlet with the latency of a path from an input to an
+    /// output declared. See [Streamlet::latencies].
+    pub fn with_latency(mut self, input: &str, output: &str, cycles: NonNegative) -> Self {
+        let path = format!("{}->{}={}", input, output, cycles);
+        let latencies = match self.metadata.get(LATENCY) {
+            Some(latencies) => format!("{}, {}", latencies, path),
+            None => path,
+        };
+        self.metadata.insert(LATENCY, latencies);
+        self
+    }
+
+    /// Returns the sustained throughputs in elements per cycle of the
+    /// interfaces of this streamlet, as declared by its `throughput`
+    /// metadata, e.g. `@throughput a=0.5, b=2`.
+    ///
+    /// Interfaces without a declared throughput are only limited by the
+    /// throughput of their logical type.
+    pub fn throughputs(&self) -> Result<Vec<(IFKey, f64)>> {
+        self.metadata
+            .get(THROUGHPUT)
+            .unwrap_or("")
+            .split(',')
+            .map(|entry| entry.trim())
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let err = || {
+                    Error::InvalidArgument(format!(
+                        "Invalid throughput \"{}\" of streamlet {}, expected interface=rate",
+                        entry, self.key
+                    ))
+                };
+                let (iface, rate) = entry.split_once('=').ok_or_else(err)?;
+                let iface = Name::try_new(iface.trim())?;
+                self.get_interface(iface.clone())?;
+                match rate.trim().parse::<f64>() {
+                    Ok(rate) if rate.is_finite() && rate > 0. => Ok((iface, rate)),
+                    _ => Err(err()),
+                }
+            })
+            .collect()
+    }
+
+    /// Return this streamlet with the sustained throughput of an interface
+    /// declared. See [Streamlet::throughputs].
+    pub fn with_throughput(mut self, iface: &str, rate: f64) -> Self {
+        let entry = format!("{}={}", iface, rate);
+        let throughputs = match self.metadata.get(THROUGHPUT) {
+            Some(throughputs) => format!("{}, {}", throughputs, entry),
+            None => entry,
+        };
+        self.metadata.insert(THROUGHPUT, throughputs);
+        self
+    }
+
+    /// Returns the number of elements this streamlet can buffer, as declared
+    /// by its `depth` metadata, if any.
+    pub fn depth(&self) -> Result<Option<NonNegative>> {
+        match self.metadata.get(DEPTH) {
+            Some(depth) => depth.trim().parse().map(Some).map_err(|_| {
+                Error::InvalidArgument(format!(
+                    "Invalid depth \"{}\" of streamlet {}",
+                    depth, self.key
+                ))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the library and the name of the existing entity implementing
+    /// this streamlet, if it is implemented externally, as declared by its
+    /// `external` metadata, e.g. `@external ip.axi_fifo`. The library is
+    /// None for entities in the work library.
+    ///
+    /// No wrapper is generated for externally implemented streamlets, and
+    /// their canonical component is named after the entity.
+    pub fn external(&self) -> Option<(Option<&str>, &str)> {
+        let external = self.metadata.get(EXTERNAL)?.trim();
+        if external.is_empty() {
+            return None;
+        }
+        Some(match external.rsplit_once('.') {
+            Some((library, entity)) => (Some(library), entity),
+            None => (None, external),
+        })
+    }
+
+    /// Return this streamlet, implemented externally by an existing entity.
+    /// See [Streamlet::external].
+    pub fn with_external(mut self, library: Option<&str>, entity: &str) -> Self {
+        let external = match library {
+            Some(library) => format!("{}.{}", library, entity),
+            None => entity.to_string(),
+        };
+        self.metadata.insert(EXTERNAL, external);
+        self
+    }
+
+    /// Returns a structural hash of the interfaces of this streamlet, i.e. of
+    /// their names, modes and logical types.
+    ///
+    /// The fingerprint does not depend on documentation, metadata or the
+    /// order of the interfaces, and is stable across platforms and compiler
+    /// versions, such that it can be stored to detect interface changes.
+    pub fn fingerprint(&self) -> u64 {
+        // Interfaces are ordered by name.
+        let interfaces = self
+            .interfaces
+            .values()
+            .map(|interface| {
+                let interface = interface.borrow();
+                format!(
+                    "{} : {} {}\n",
+                    interface.identifier(),
+                    interface.mode(),
+                    interface.typ()
+                )
+            })
+            .collect::<String>();
+        fnv1a(interfaces.as_bytes())
+    }
+}
+
+impl Streamlet {
+    /// Render this streamlet in Streamlet Definition File syntax, including
+    /// the documentation of the streamlet and its interfaces.
+    pub fn to_sdf(&self) -> String {
+        self.render(true)
+    }
+
+    fn render(&self, with_doc: bool) -> String {
+        let mut result = String::new();
+        if with_doc {
+            result.push_str(&sdf_doc(
+                self.metadata.document(self.doc.as_deref()).as_deref(),
+                "",
+            ));
+        }
+        result.push_str(&format!("Streamlet {} ", self.key));
+        if !self.parameters.is_empty() {
+            let parameters = self
+                .parameters
+                .iter()
+                .map(|p| match p.item() {
+                    ParameterVariant::UInt(value) => format!("{} = {}", p.key(), value),
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<_>>();
+            result.push_str(&format!("<{}> ", parameters.join(", ")));
+        }
+        result.push('(');
+        if !self.interfaces.is_empty() {
+            // Interface arrays are rendered once, for their first element.
+            let interfaces = self
+                .interfaces
+                .values()
+                .filter(|iface| iface.borrow().array.as_ref().is_none_or(|a| a.index == 0))
+                .map(|iface| {
+                    let iface = iface.borrow();
+                    if with_doc {
+                        format!("{}  {}", sdf_doc(iface.doc.as_deref(), "  "), iface)
+                    } else {
+                        format!("  {}", iface)
+                    }
+                })
+                .collect::<Vec<_>>();
+            result.push('\n');
+            result.push_str(&interfaces.join(",\n"));
+            result.push('\n');
+        }
+        result.push(')');
+        result
+    }
+}
+
+impl fmt::Display for Streamlet {
+    /// Display a streamlet in Streamlet Definition File syntax, without
+    /// documentation.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(false))
+    }
+}
+
+/// Render a documentation string as Streamlet Definition File doc comments.
+fn sdf_doc(doc: Option<&str>, indent: &str) -> String {
+    doc.map(|doc| {
+        doc.lines()
+            .map(|line| format!("{}///{}\n", indent, line))
+            .collect()
+    })
+    .unwrap_or_default()
 }
 
 impl Document for Streamlet {
@@ -318,4 +987,141 @@ pub mod tests {
             .unwrap()
         }
     }
+
+    #[test]
+    fn interface_can_drive() -> Result<()> {
+        let iface = |name: &str, mode: Mode, typ: &str| -> Result<Interface> {
+            Interface::try_new(name, mode, crate::parser::nom::logical_type(typ)?, None)
+        };
+        let source = iface(
+            "a",
+            Mode::Out,
+            "Group<x: Stream<Bits<8>, c=2>, y: Stream<Bits<1>, r=Reverse, c=4>>",
+        )?;
+        let sink = iface(
+            "b",
+            Mode::In,
+            "Group<x: Stream<Bits<8>, c=3>, y: Stream<Bits<1>, r=Reverse, c=1>>",
+        )?;
+        assert!(source.can_drive(&sink).is_ok());
+        assert_eq!(
+            sink.can_drive(&source).unwrap_err().to_string(),
+            "Interface error: Interface b cannot drive interface a: stream x: Invalid argument: \
+             source complexity 3 exceeds sink complexity 2"
+        );
+        assert!(source
+            .can_drive(&iface("c", Mode::In, "Stream<Bits<8>>")?)
+            .is_err());
+        assert!(iface("d", Mode::Out, "Stream<Bits<8>, d=1>")?
+            .can_drive(&iface("e", Mode::In, "Stream<Bits<8>, d=2>")?)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn interface_synthesize() -> Result<()> {
+        let typ = |fields: Vec<(&str, u32)>| -> Result<LogicalType> {
+            Ok(crate::logical::Stream::new_basic(LogicalType::try_new_group(fields)?).into())
+        };
+        let mut iface = Interface::try_new("a", Mode::In, typ(vec![("x", 4)])?, None)?;
+        let synth = iface.synthesize();
+        assert!(Arc::ptr_eq(&synth, &iface.synthesize()));
+        assert!(Arc::ptr_eq(&synth, &iface.clone().synthesize()));
+        assert_eq!(
+            iface,
+            Interface::try_new("a", Mode::In, typ(vec![("x", 4)])?, None)?
+        );
+
+        // Inferring the type of an interface synthesizes the new type.
+        iface = iface.with_type_inference(Ok);
+        iface.infer_type(typ(vec![("x", 4), ("y", 1)])?)?;
+        let inferred = iface.synthesize();
+        assert!(!Arc::ptr_eq(&synth, &inferred));
+        assert_eq!(
+            inferred
+                .streams()
+                .next()
+                .unwrap()
+                .1
+                .element_fields()
+                .iter()
+                .count(),
+            2
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn streamlet_latencies() -> Result<()> {
+        let streamlet = |doc: &str| {
+            crate::parser::nom::streamlet(&format!(
+                "{}Streamlet s (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>, \
+                 c : out Stream<Bits<8>>)",
+                doc
+            ))
+            .unwrap()
+            .1
+        };
+        let path = |i: &str, o: &str, cycles| {
+            (Name::try_new(i).unwrap(), Name::try_new(o).unwrap(), cycles)
+        };
+        assert_eq!(
+            streamlet("").latencies()?,
+            vec![path("a", "b", 0), path("a", "c", 0)]
+        );
+        assert_eq!(
+            streamlet("/// @latency a->c=3\n")
+                .with_latency("a", "b", 2)
+                .latencies()?,
+            vec![path("a", "c", 3), path("a", "b", 2)]
+        );
+        assert_eq!(
+            streamlet("/// @latency a=>b=1\n")
+                .latencies()
+                .unwrap_err()
+                .to_string(),
+            "Invalid argument: Invalid latency \"a=>b=1\" of streamlet s, \
+             expected input->output=cycles"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn streamlet_external() -> Result<()> {
+        let streamlet =
+            Streamlet::from_builder(Name::try_new("s")?, UniqueKeyBuilder::new(), None)?;
+        assert_eq!(streamlet.external(), None);
+        let streamlet = streamlet.with_external(Some("ip"), "axi_fifo");
+        assert_eq!(streamlet.external(), Some((Some("ip"), "axi_fifo")));
+        assert_eq!(streamlet.metadata().get("external"), Some("ip.axi_fifo"));
+        let streamlet = streamlet.with_external(None, "fifo");
+        assert_eq!(streamlet.external(), Some((None, "fifo")));
+        Ok(())
+    }
+
+    #[test]
+    fn streamlet_fingerprint() -> Result<()> {
+        let fingerprint = |sdf: &str| -> Result<u64> {
+            let lib = crate::design::Library::from_sdf(Name::try_new("lib")?, sdf)?;
+            let fingerprint = lib.streamlets().next().unwrap().fingerprint();
+            Ok(fingerprint)
+        };
+        let original = fingerprint("Streamlet s (a : in Stream<Bits<8>>, b : out Bits<1>)")?;
+        assert_eq!(
+            original,
+            fingerprint(
+                "/// Documented.\nStreamlet s (\n  /// Reordered.\n  \
+                 b : out Bits<1>,\n  a : in Stream<Bits<8>>\n)"
+            )?
+        );
+        for changed in &[
+            "Streamlet s (a : in Stream<Bits<8>, t=2>, b : out Bits<1>)",
+            "Streamlet s (a : in Stream<Bits<8>>, b : in Bits<1>)",
+            "Streamlet s (a : in Stream<Bits<8>>, c : out Bits<1>)",
+            "Streamlet s (a : in Stream<Bits<8>>)",
+        ] {
+            assert_ne!(original, fingerprint(changed)?, "{}", changed);
+        }
+        Ok(())
+    }
 }
@@ -1,8 +1,12 @@
 //! Error variants.
+//!
+//! Every [Error] has a code that identifies its variant, e.g. `E0002` for
+//! invalid arguments, and a message. Errors can be annotated, through
+//! [ResultExt], with the [Location] at which they occurred and with notes
+//! about the context in which they occurred, e.g. "while generating the
+//! wrapper of streamlet lib.x". [Error::report] renders all of these.
 use std::{error, fmt, result};
 
-use log::SetLoggerError;
-
 /// Result type with [`Error`] variants.
 ///
 /// [`Error`]: ./enum.Error.html
@@ -37,6 +41,172 @@ pub enum Error {
     ComposerError(String),
     /// Library error
     LibraryError(String),
+    /// An error with its location and notes about its context.
+    Contextual(Box<ErrorContext>),
+}
+
+/// A location in a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    file: String,
+    position: Option<(usize, usize)>,
+}
+
+impl Location {
+    /// Construct the location of a file.
+    pub fn new(file: impl fmt::Display) -> Self {
+        Location {
+            file: file.to_string(),
+            position: None,
+        }
+    }
+
+    /// Returns this location at a line and column of the file, starting at 1.
+    pub fn with_position(mut self, line: usize, column: usize) -> Self {
+        self.position = Some((line, column));
+        self
+    }
+
+    /// Returns the file.
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Returns the line and column in the file, if known.
+    pub fn position(&self) -> Option<(usize, usize)> {
+        self.position
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => write!(f, "{}:{}:{}", self.file, line, column),
+            None => write!(f, "{}", self.file),
+        }
+    }
+}
+
+/// An error with the location at which it occurred, if known, and notes
+/// about the context in which it occurred, from innermost to outermost.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorContext {
+    error: Error,
+    location: Option<Location>,
+    notes: Vec<String>,
+}
+
+impl Error {
+    /// Returns the code of this error, which identifies its variant.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::UnknownError => "E0000",
+            Error::CLIError(_) => "E0001",
+            Error::InvalidArgument(_) => "E0002",
+            Error::UnexpectedDuplicate => "E0003",
+            Error::FileIOError(_) => "E0004",
+            Error::ParsingError(_) => "E0005",
+            Error::ImplParsingError(_) => "E0006",
+            Error::InvalidTarget(_) => "E0007",
+            Error::BackEndError(_) => "E0008",
+            Error::InterfaceError(_) => "E0009",
+            Error::ProjectError(_) => "E0010",
+            Error::ComposerError(_) => "E0011",
+            Error::LibraryError(_) => "E0012",
+            Error::Contextual(context) => context.error.code(),
+        }
+    }
+
+    /// Returns this error without its location and context.
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Contextual(context) => &context.error,
+            _ => self,
+        }
+    }
+
+    /// Returns the location at which this error occurred, if known.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Error::Contextual(context) => context.location.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the notes about the context in which this error occurred,
+    /// from innermost to outermost.
+    pub fn notes(&self) -> &[String] {
+        match self {
+            Error::Contextual(context) => &context.notes,
+            _ => &[],
+        }
+    }
+
+    fn into_context(self) -> ErrorContext {
+        match self {
+            Error::Contextual(context) => *context,
+            error => ErrorContext {
+                error,
+                location: None,
+                notes: vec![],
+            },
+        }
+    }
+
+    /// Returns this error with a note about the context in which it
+    /// occurred.
+    pub fn with_note(self, note: impl Into<String>) -> Self {
+        let mut context = self.into_context();
+        context.notes.push(note.into());
+        Error::Contextual(Box::new(context))
+    }
+
+    /// Returns this error with the location at which it occurred, unless a
+    /// more precise location is already known.
+    pub fn at(self, location: Location) -> Self {
+        let mut context = self.into_context();
+        context.location.get_or_insert(location);
+        Error::Contextual(Box::new(context))
+    }
+
+    /// Render this error with its code, location and notes, e.g.:
+    ///
+    /// ```text
+    /// error[E0002]: Invalid argument: name cannot be empty
+    ///   --> lib.sdf:3:7
+    ///   = note: while generating the wrapper of streamlet lib.x
+    /// ```
+    pub fn report(&self) -> String {
+        format!("error[{}]: {}", self.code(), self)
+    }
+}
+
+/// Extension methods to annotate the errors of results.
+pub trait ResultExt<T> {
+    /// Add a note about the context in which the error occurred.
+    fn context(self, note: impl Into<String>) -> Result<T>;
+
+    /// Add a note about the context in which the error occurred, which is
+    /// only constructed if there is an error.
+    fn with_context(self, note: impl FnOnce() -> String) -> Result<T>;
+
+    /// Add the location at which the error occurred, unless a more precise
+    /// location is already known.
+    fn at(self, location: impl FnOnce() -> Location) -> Result<T>;
+}
+
+impl<T> ResultExt<T> for Result<T> {
+    fn context(self, note: impl Into<String>) -> Result<T> {
+        self.map_err(|e| e.with_note(note))
+    }
+
+    fn with_context(self, note: impl FnOnce() -> String) -> Result<T> {
+        self.map_err(|e| e.with_note(note()))
+    }
+
+    fn at(self, location: impl FnOnce() -> Location) -> Result<T> {
+        self.map_err(|e| e.at(location()))
+    }
 }
 
 ///Error variants for implementation parser
@@ -83,6 +253,16 @@ impl fmt::Display for Error {
             Error::ProjectError(ref msg) => write!(f, "Project error: {}", msg),
             Error::ComposerError(ref msg) => write!(f, "Composer error: {}", msg),
             Error::LibraryError(ref msg) => write!(f, "Library error: {}", msg),
+            Error::Contextual(ref context) => {
+                write!(f, "{}", context.error)?;
+                if let Some(location) = &context.location {
+                    write!(f, "\n  --> {}", location)?;
+                }
+                for note in &context.notes {
+                    write!(f, "\n  = note: {}", note)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -105,12 +285,6 @@ impl From<std::io::Error> for Error {
     }
 }
 
-impl From<SetLoggerError> for Error {
-    fn from(e: SetLoggerError) -> Self {
-        Error::CLIError(e.to_string())
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,4 +296,33 @@ mod tests {
         assert_eq!(a.to_string(), "Invalid argument: test");
         assert_eq!(b.to_string(), "Unexpected duplicate");
     }
+
+    #[test]
+    fn context() {
+        let result: Result<()> = Err(Error::InvalidArgument("name cannot be empty".to_string()));
+        let error = result
+            .at(|| Location::new("lib.sdf").with_position(3, 7))
+            .context("while generating the wrapper of streamlet lib.x")
+            .at(|| Location::new("other.sdf"))
+            .with_context(|| "while generating project p".to_string())
+            .unwrap_err();
+        assert_eq!(error.code(), "E0002");
+        assert_eq!(
+            error.root(),
+            &Error::InvalidArgument("name cannot be empty".to_string())
+        );
+        assert_eq!(error.location().unwrap().position(), Some((3, 7)));
+        assert_eq!(error.notes().len(), 2);
+        assert_eq!(
+            error.report(),
+            "error[E0002]: Invalid argument: name cannot be empty\n\
+             \x20 --> lib.sdf:3:7\n\
+             \x20 = note: while generating the wrapper of streamlet lib.x\n\
+             \x20 = note: while generating project p"
+        );
+        assert_eq!(
+            Error::UnexpectedDuplicate.report(),
+            "error[E0003]: Unexpected duplicate"
+        );
+    }
 }
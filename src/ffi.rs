@@ -0,0 +1,402 @@
+//! C-compatible interface to projects, libraries and streamlets.
+//!
+//! The interface is enabled by the `ffi` feature flag. To use it from C and
+//! C++ tools, build this crate as a dynamic library:
+//!
+//! ```bash
+//! cargo rustc --lib --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! Projects, libraries and streamlets are exposed as opaque handles.
+//! A project is owned by the caller and freed with [tydi_project_free];
+//! library and streamlet handles borrow from their project, and are valid
+//! until the project is modified or freed. Strings returned by this
+//! interface are owned by the caller and freed with [tydi_string_free].
+//!
+//! Functions that fail return a null pointer or a negative value, and
+//! record an error message that is returned by [tydi_last_error].
+//!
+//! The C header `include/tydi.h` is generated from this module with
+//! [cbindgen]:
+//!
+//! ```bash
+//! cbindgen --config cbindgen.toml --output include/tydi.h
+//! ```
+//!
+//! [cbindgen]: https://github.com/eqrion/cbindgen
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::design::{Library, Project, Streamlet};
+use crate::generator::vhdl::VHDLBackEnd;
+use crate::generator::GenerateProject;
+use crate::{Error, Identify, Name, Result};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Record the message of an error as the last error of this thread.
+fn set_last_error(message: impl Into<String>) {
+    let message = message.into().replace('\0', " ");
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(message).ok());
+}
+
+/// Run a function, recording its error as the last error of this thread.
+/// Panics are caught and recorded as well, such that they do not unwind
+/// into the caller.
+fn guard<T>(f: impl FnOnce() -> Result<T>) -> Option<T> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(e)) => {
+            set_last_error(e.to_string());
+            None
+        }
+        Err(_) => {
+            set_last_error("Unexpected panic");
+            None
+        }
+    }
+}
+
+/// Returns a reference to the object behind a handle, or an error if it is
+/// null.
+unsafe fn handle<'a, T>(ptr: *const T, name: &str) -> Result<&'a T> {
+    ptr.as_ref()
+        .ok_or_else(|| Error::InvalidArgument(format!("{} is null", name)))
+}
+
+/// Returns the string behind a C string argument, or an error if it is null
+/// or not valid UTF-8.
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str> {
+    if ptr.is_null() {
+        return Err(Error::InvalidArgument(format!("{} is null", name)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|e| Error::InvalidArgument(format!("{} is not valid UTF-8: {}", name, e)))
+}
+
+/// Returns a string as a C string owned by the caller.
+fn string_result(string: impl Into<String>) -> Result<*mut c_char> {
+    CString::new(string.into())
+        .map(CString::into_raw)
+        .map_err(|e| Error::InvalidArgument(e.to_string()))
+}
+
+/// Returns the message of the last error on this thread, or null if no
+/// error occurred. The message is owned by this library, and valid until
+/// the next error on this thread.
+#[no_mangle]
+pub extern "C" fn tydi_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map(|message| message.as_ptr())
+            .unwrap_or(ptr::null())
+    })
+}
+
+/// Free a string returned by this interface. Does nothing if the string is
+/// null.
+///
+/// # Safety
+///
+/// The string must be returned by this interface, and not be freed before.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Construct an empty project. Returns null if the name is invalid.
+///
+/// # Safety
+///
+/// The name must be null or a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_project_new(name: *const c_char) -> *mut Project {
+    guard(|| {
+        let name = Name::try_new(str_arg(name, "name")?)?;
+        Ok(Box::into_raw(Box::new(Project::new(name))))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Free a project. Does nothing if the project is null.
+///
+/// # Safety
+///
+/// The project must be returned by [tydi_project_new], and not be freed
+/// before.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_project_free(project: *mut Project) {
+    if !project.is_null() {
+        drop(Box::from_raw(project));
+    }
+}
+
+/// Parse a library from the contents of a Streamlet Definition File, and
+/// add it to a project. The library may refer to the named types of the
+/// libraries of the project it imports.
+///
+/// Returns 0 on success, or -1 if the contents are invalid or the project
+/// already contains a library with the name.
+///
+/// # Safety
+///
+/// The project must be a valid handle, the name and contents must be null
+/// or null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_project_add_library(
+    project: *mut Project,
+    name: *const c_char,
+    sdf: *const c_char,
+) -> c_int {
+    guard(|| {
+        let project = project
+            .as_mut()
+            .ok_or_else(|| Error::InvalidArgument("project is null".to_string()))?;
+        let name = str_arg(name, "name")?;
+        let input = str_arg(sdf, "sdf")?;
+        let library = Library::from_sdf_in(Name::try_new(name)?, input, project)?;
+        project.add_lib(library)?;
+        Ok(0)
+    })
+    .unwrap_or(-1)
+}
+
+/// Returns the number of libraries of a project, or 0 if it is null.
+///
+/// # Safety
+///
+/// The project must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_project_library_count(project: *const Project) -> usize {
+    guard(|| Ok(handle(project, "project")?.libraries().count())).unwrap_or(0)
+}
+
+/// Returns the library of a project at an index, in the order they were
+/// added, or null if the index is out of range.
+///
+/// # Safety
+///
+/// The project must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_project_library(
+    project: *const Project,
+    index: usize,
+) -> *const Library {
+    guard(|| {
+        handle(project, "project")?
+            .libraries()
+            .nth(index)
+            .map(|library| library as *const Library)
+            .ok_or_else(|| Error::InvalidArgument(format!("no library at index {}", index)))
+    })
+    .unwrap_or(ptr::null())
+}
+
+/// Returns the name of a library, or null if it is null.
+///
+/// # Safety
+///
+/// The library must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_library_name(library: *const Library) -> *mut c_char {
+    guard(|| string_result(handle(library, "library")?.identifier())).unwrap_or(ptr::null_mut())
+}
+
+/// Returns a library in Streamlet Definition File syntax, or null if it is
+/// null.
+///
+/// # Safety
+///
+/// The library must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_library_to_sdf(library: *const Library) -> *mut c_char {
+    guard(|| string_result(handle(library, "library")?.to_sdf())).unwrap_or(ptr::null_mut())
+}
+
+/// Returns the number of streamlets of a library, or 0 if it is null.
+///
+/// # Safety
+///
+/// The library must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_library_streamlet_count(library: *const Library) -> usize {
+    guard(|| Ok(handle(library, "library")?.streamlets().count())).unwrap_or(0)
+}
+
+/// Returns the streamlet of a library at an index, in order of declaration,
+/// or null if the index is out of range.
+///
+/// # Safety
+///
+/// The library must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_library_streamlet(
+    library: *const Library,
+    index: usize,
+) -> *const Streamlet {
+    guard(|| {
+        handle(library, "library")?
+            .streamlets()
+            .nth(index)
+            .map(|streamlet| streamlet as *const Streamlet)
+            .ok_or_else(|| Error::InvalidArgument(format!("no streamlet at index {}", index)))
+    })
+    .unwrap_or(ptr::null())
+}
+
+/// Returns the name of a streamlet, or null if it is null.
+///
+/// # Safety
+///
+/// The streamlet must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_streamlet_name(streamlet: *const Streamlet) -> *mut c_char {
+    guard(|| string_result(handle(streamlet, "streamlet")?.identifier())).unwrap_or(ptr::null_mut())
+}
+
+/// Returns the total number of bits of all interfaces of a streamlet, or 0
+/// if it is null.
+///
+/// # Safety
+///
+/// The streamlet must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_streamlet_bit_count(streamlet: *const Streamlet) -> u64 {
+    guard(|| Ok(handle(streamlet, "streamlet")?.info().bit_count())).unwrap_or(0)
+}
+
+/// Returns a human-readable report of the interfaces of a streamlet and the
+/// physical streams they are synthesized into, or null if it is null.
+///
+/// # Safety
+///
+/// The streamlet must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_streamlet_info(streamlet: *const Streamlet) -> *mut c_char {
+    guard(|| string_result(handle(streamlet, "streamlet")?.info().to_string()))
+        .unwrap_or(ptr::null_mut())
+}
+
+/// Generate VHDL sources for a project in a directory, with the default
+/// configuration of the VHDL back-end.
+///
+/// Returns 0 on success, or -1 on failure.
+///
+/// # Safety
+///
+/// The project must be a valid handle, and the path must be null or a
+/// null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn tydi_generate_vhdl(project: *const Project, path: *const c_char) -> c_int {
+    guard(|| {
+        VHDLBackEnd::default().generate(handle(project, "project")?, str_arg(path, "path")?)?;
+        Ok(0)
+    })
+    .unwrap_or(-1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take_string(string: *mut c_char) -> String {
+        assert!(!string.is_null());
+        let result = unsafe { CStr::from_ptr(string) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        unsafe { tydi_string_free(string) };
+        result
+    }
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(tydi_last_error()) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn project() -> Result<()> {
+        let name = CString::new("proj").unwrap();
+        let project = unsafe { tydi_project_new(name.as_ptr()) };
+        assert!(!project.is_null());
+
+        let lib = CString::new("lib").unwrap();
+        let sdf = CString::new(
+            "type Byte = Stream<Bits<8>>;\n\
+             Streamlet y (a : in Byte, b : out Byte)\n\
+             Streamlet x (a : in Byte)",
+        )
+        .unwrap();
+        assert_eq!(
+            unsafe { tydi_project_add_library(project, lib.as_ptr(), sdf.as_ptr()) },
+            0
+        );
+        assert_eq!(
+            unsafe { tydi_project_add_library(project, lib.as_ptr(), sdf.as_ptr()) },
+            -1
+        );
+        let invalid = CString::new("Streamlet x (a : in Bits<8>").unwrap();
+        let other = CString::new("other").unwrap();
+        assert_eq!(
+            unsafe { tydi_project_add_library(project, other.as_ptr(), invalid.as_ptr()) },
+            -1
+        );
+        assert!(last_error().starts_with("Parsing error"));
+
+        assert_eq!(unsafe { tydi_project_library_count(project) }, 1);
+        let library = unsafe { tydi_project_library(project, 0) };
+        assert_eq!(take_string(unsafe { tydi_library_name(library) }), "lib");
+        assert!(take_string(unsafe { tydi_library_to_sdf(library) }).contains("Streamlet x"));
+        assert!(unsafe { tydi_project_library(project, 1) }.is_null());
+        assert_eq!(last_error(), "Invalid argument: no library at index 1");
+
+        assert_eq!(unsafe { tydi_library_streamlet_count(library) }, 2);
+        let streamlet = unsafe { tydi_library_streamlet(library, 0) };
+        assert_eq!(take_string(unsafe { tydi_streamlet_name(streamlet) }), "y");
+        assert_eq!(unsafe { tydi_streamlet_bit_count(streamlet) }, 16);
+        assert!(take_string(unsafe { tydi_streamlet_info(streamlet) }).starts_with("Streamlet y"));
+
+        let tmpdir = tempfile::tempdir()?;
+        let path = CString::new(tmpdir.path().to_str().unwrap()).unwrap();
+        assert_eq!(unsafe { tydi_generate_vhdl(project, path.as_ptr()) }, 0);
+        assert!(tmpdir.path().join("proj/lib_pkg.gen.vhd").exists());
+
+        unsafe { tydi_project_free(project) };
+        assert!(unsafe { tydi_project_new(ptr::null()) }.is_null());
+        assert_eq!(last_error(), "Invalid argument: name is null");
+        Ok(())
+    }
+
+    #[test]
+    fn header() {
+        // Every function of the interface is declared in the generated header.
+        let header = include_str!("../include/tydi.h");
+        let source = include_str!("ffi.rs");
+        let functions = source
+            .lines()
+            .filter_map(|line| line.split("extern \"C\" fn ").nth(1))
+            .filter_map(|rest| rest.split('(').next())
+            .filter(|name| name.starts_with("tydi_"))
+            .collect::<Vec<_>>();
+        assert_eq!(functions.len(), 15);
+        for function in functions {
+            assert!(
+                header.contains(&format!("{}(", function)),
+                "{} is not declared in include/tydi.h",
+                function
+            );
+        }
+    }
+}
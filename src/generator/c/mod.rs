@@ -0,0 +1,350 @@
+//! C back-end.
+//!
+//! Generates a C header per library, that describes the element layout of
+//! every physical stream of the interfaces of its streamlets, such that
+//! software producing or consuming these streams shares one source of truth
+//! with the hardware.
+//!
+//! The data of a transfer is stored as a packed array of bytes, least
+//! significant bit first, matching the `data` signal of the physical stream:
+//! element lane `i` occupies the bits starting at `i * ELEMENT_BITS`, with
+//! the fields of an element laid out as described by
+//! [Fields::layout](crate::physical::Fields::layout). Fields are accessed
+//! through generated inline functions rather than C bitfields, of which the
+//! layout is implementation-defined.
+//!
+//! Names are derived from the canonical names of the physical streams, e.g.
+//! the functions to access field `r` of interface `a` of streamlet `x` of
+//! library `lib` are `lib_x_a_get_r` and `lib_x_a_set_r`.
+
+use std::path::Path;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::{Library, Mode, Project};
+use crate::generator::filter::Filter;
+use crate::generator::header::HeaderTemplate;
+use crate::generator::{GenerateProject, GeneratedFile};
+use crate::physical::PhysicalStream;
+use crate::{Identify, NamingPolicy, PathName, Result, WideNonNegative};
+
+/// Helper functions to access bits of packed data, shared by all headers.
+const HELPERS: &str = r#"#ifndef TYDI_BITS_H
+#define TYDI_BITS_H
+
+/* Returns width bits of data at bit offset, least significant bit first. */
+static inline uint64_t tydi_get_bits(const uint8_t *data, size_t offset, size_t width) {
+    uint64_t value = 0;
+    for (size_t i = 0; i < width; i++) {
+        size_t bit = offset + i;
+        value |= (uint64_t)((data[bit / 8] >> (bit % 8)) & 1u) << i;
+    }
+    return value;
+}
+
+/* Sets width bits of data at bit offset to value. */
+static inline void tydi_set_bits(uint8_t *data, size_t offset, size_t width, uint64_t value) {
+    for (size_t i = 0; i < width; i++) {
+        size_t bit = offset + i;
+        uint8_t mask = (uint8_t)(1u << (bit % 8));
+        if ((value >> i) & 1u) {
+            data[bit / 8] |= mask;
+        } else {
+            data[bit / 8] &= (uint8_t)~mask;
+        }
+    }
+}
+
+/* Copies width bits of data at bit offset to the bytes of value. */
+static inline void tydi_get_wide(const uint8_t *data, size_t offset, size_t width, uint8_t *value) {
+    for (size_t i = 0; i < width; i += 8) {
+        value[i / 8] = (uint8_t)tydi_get_bits(data, offset + i, width - i < 8 ? width - i : 8);
+    }
+}
+
+/* Copies the bytes of value to width bits of data at bit offset. */
+static inline void tydi_set_wide(uint8_t *data, size_t offset, size_t width, const uint8_t *value) {
+    for (size_t i = 0; i < width; i += 8) {
+        tydi_set_bits(data, offset + i, width - i < 8 ? width - i : 8, value[i / 8]);
+    }
+}
+
+#endif /* TYDI_BITS_H */
+"#;
+
+/// A configurable C back-end entry point.
+#[derive(Default)]
+pub struct CBackEnd {
+    /// The libraries and streamlets to generate headers for.
+    filter: Filter,
+    /// The header of generated files, if any.
+    header: Option<HeaderTemplate>,
+}
+
+impl CBackEnd {
+    /// Returns this back-end, only generating the headers of the libraries
+    /// selected by a filter, with the layouts of their selected streamlets.
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Returns the filter of the libraries and streamlets to generate
+    /// headers for.
+    pub fn filter(&self) -> &Filter {
+        &self.filter
+    }
+
+    /// Returns this back-end, prepending a header to every generated file.
+    pub fn with_header(mut self, header: HeaderTemplate) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    /// Returns the template of the header of generated files, if any.
+    pub fn header(&self) -> Option<&HeaderTemplate> {
+        self.header.as_ref()
+    }
+}
+
+impl GenerateProject for CBackEnd {
+    fn target(&self) -> &str {
+        "c"
+    }
+
+    /// Every library gets a header named after the library, in the project
+    /// directory.
+    fn render(&self, project: &Project, path: impl AsRef<Path>) -> Result<Vec<GeneratedFile>> {
+        let dir = path.as_ref().join(project.identifier());
+        let files = project
+            .libraries()
+            .filter(|lib| self.filter.includes_library(lib.identifier()))
+            .map(|lib| {
+                GeneratedFile::new(
+                    dir.join(format!("{}.h", lib.identifier())),
+                    self.layouts(project, lib),
+                )
+                .with_checksum("//")
+                .with_origin(lib.identifier())
+            })
+            .collect();
+        Ok(match &self.header {
+            Some(header) => header.apply(project, files),
+            None => files,
+        })
+    }
+}
+
+/// A physical stream of an interface of a streamlet.
+pub(crate) struct NamedStream {
+    /// The canonical name of the stream, that the names of its generated
+    /// declarations start with.
+    pub(crate) prefix: String,
+    /// A description of the stream for generated comments.
+    pub(crate) description: String,
+    /// The mode of the interface of the stream.
+    pub(crate) mode: Mode,
+    /// The physical stream.
+    pub(crate) stream: PhysicalStream,
+}
+
+/// Returns the physical streams of the interfaces of the streamlets of a
+/// library that are selected by a filter.
+pub(crate) fn named_streams(lib: &Library, filter: &Filter) -> Vec<NamedStream> {
+    let naming = NamingPolicy::default();
+    let mut result = Vec::new();
+    for streamlet in lib.streamlets() {
+        if !filter.includes_streamlet(lib.identifier(), streamlet.identifier()) {
+            continue;
+        }
+        for interface in streamlet.interfaces() {
+            let synthesized = interface.synthesize();
+            for (path, stream) in synthesized.streams() {
+                result.push(NamedStream {
+                    prefix: naming.join(&[
+                        lib.identifier(),
+                        streamlet.identifier(),
+                        interface.identifier(),
+                        &path.to_string(),
+                    ]),
+                    description: format!(
+                        "Stream {}of interface {} ({}) of streamlet {}",
+                        if path.is_empty() {
+                            String::new()
+                        } else {
+                            format!("{} ", path)
+                        },
+                        interface.identifier(),
+                        interface.mode(),
+                        streamlet.identifier(),
+                    ),
+                    mode: interface.mode(),
+                    stream: stream.clone(),
+                });
+            }
+        }
+    }
+    result
+}
+
+/// Returns the name of an element field in the names of its access
+/// functions.
+pub(crate) fn field_name(path: &PathName) -> String {
+    if path.is_empty() {
+        "value".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+impl CBackEnd {
+    /// Returns the header with the element layouts of a library.
+    pub(crate) fn layouts(&self, project: &Project, lib: &Library) -> String {
+        let guard = format!("{}_{}_H", project.identifier(), lib.identifier()).to_uppercase();
+        let mut result = format!(
+            "/* Element layouts of the physical streams of library {}. */\n\n\
+             #ifndef {}\n#define {}\n\n#include <stddef.h>\n#include <stdint.h>\n\n{}",
+            lib.identifier(),
+            guard,
+            guard,
+            HELPERS
+        );
+        for named in named_streams(lib, &self.filter) {
+            result.push_str(&layout(&named.prefix, &named.description, &named.stream));
+        }
+        result.push_str(&format!("\n#endif /* {} */\n", guard));
+        result
+    }
+}
+
+/// Returns the C type that holds unsigned integers of a number of bits, if
+/// any.
+fn uint_type(bits: WideNonNegative) -> Option<&'static str> {
+    match bits {
+        0..=8 => Some("uint8_t"),
+        9..=16 => Some("uint16_t"),
+        17..=32 => Some("uint32_t"),
+        33..=64 => Some("uint64_t"),
+        _ => None,
+    }
+}
+
+/// Returns the declarations that describe the element layout of a physical
+/// stream, with names starting with a prefix. Streams without element
+/// fields have no layout.
+fn layout(prefix: &str, description: &str, stream: &PhysicalStream) -> String {
+    let element_bits = stream.element_bit_count();
+    if element_bits == 0 {
+        return String::new();
+    }
+    let macro_prefix = prefix.to_uppercase();
+    let data = format!("{}_data", prefix);
+    let mut result = format!(
+        "\n/* {}: {} element lane(s) of {} bit(s). */\n\
+         #define {m}_LANES {}u\n\
+         #define {m}_ELEMENT_BITS {}u\n\
+         #define {m}_DATA_BYTES {}u\n",
+        description,
+        stream.element_lanes(),
+        element_bits,
+        stream.element_lanes(),
+        element_bits,
+        stream.data_bit_count().div_ceil(8),
+        m = macro_prefix,
+    );
+    let fields = stream
+        .element_fields()
+        .layout()
+        .map(|(path, offset, bits)| (field_name(path), offset, bits.get()))
+        .collect::<Vec<_>>();
+    for (name, offset, bits) in &fields {
+        let field = format!("{}_{}", macro_prefix, name.to_uppercase());
+        result.push_str(&format!(
+            "#define {}_OFFSET {}u\n#define {}_BITS {}u\n",
+            field, offset, field, bits
+        ));
+    }
+    result.push_str(&format!(
+        "\n/* The data of a transfer, least significant bit first. */\n\
+         typedef struct {data} {{\n    uint8_t bytes[{m}_DATA_BYTES];\n}} {data};\n",
+        data = data,
+        m = macro_prefix,
+    ));
+    for (name, _, bits) in &fields {
+        let field = format!("{}_{}", macro_prefix, name.to_uppercase());
+        let bit = format!(
+            "lane * {}_ELEMENT_BITS + {}_OFFSET, {}_BITS",
+            macro_prefix, field, field
+        );
+        result.push_str(&match uint_type(*bits) {
+            Some(typ) => format!(
+                "\nstatic inline {typ} {p}_get_{n}(const {d} *data, size_t lane) {{\n    \
+                 return ({typ})tydi_get_bits(data->bytes, {b});\n}}\n\n\
+                 static inline void {p}_set_{n}({d} *data, size_t lane, {typ} value) {{\n    \
+                 tydi_set_bits(data->bytes, {b}, value);\n}}\n",
+                typ = typ,
+                p = prefix,
+                n = name,
+                d = data,
+                b = bit,
+            ),
+            None => format!(
+                "\nstatic inline void {p}_get_{n}(const {d} *data, size_t lane, uint8_t *value) {{\n    \
+                 tydi_get_wide(data->bytes, {b}, value);\n}}\n\n\
+                 static inline void {p}_set_{n}({d} *data, size_t lane, const uint8_t *value) {{\n    \
+                 tydi_set_wide(data->bytes, {b}, value);\n}}\n",
+                p = prefix,
+                n = name,
+                d = data,
+                b = bit,
+            ),
+        });
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Name;
+
+    #[test]
+    fn header() -> Result<()> {
+        let mut prj = Project::new(Name::try_new("proj")?);
+        prj.add_lib(Library::from_sdf(
+            Name::try_new("lib")?,
+            "Streamlet x (\n  \
+               a : in Stream<Group<r: Bits<8>, g: Bits<8>, b: Bits<8>>, t=2, d=1>,\n  \
+               w : out Stream<Group<k: Bits<4>, v: Bits<100>>>,\n  \
+               n : in Stream<Null>\n)",
+        )?)?;
+        let files = CBackEnd::default().render(&prj, "out")?;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path(), Path::new("out/proj/lib.h"));
+        let header = files[0].contents();
+        assert!(!GeneratedFile::is_modified(header));
+        for line in &[
+            "#ifndef PROJ_LIB_H",
+            "#define LIB_X_A_LANES 2u",
+            "#define LIB_X_A_ELEMENT_BITS 24u",
+            "#define LIB_X_A_DATA_BYTES 6u",
+            "#define LIB_X_A_G_OFFSET 8u",
+            "#define LIB_X_A_G_BITS 8u",
+            "} lib_x_a_data;",
+            "static inline uint8_t lib_x_a_get_b(const lib_x_a_data *data, size_t lane) {",
+            "    return (uint8_t)tydi_get_bits(data->bytes, \
+             lane * LIB_X_A_ELEMENT_BITS + LIB_X_A_B_OFFSET, LIB_X_A_B_BITS);",
+            "#define LIB_X_W_V_OFFSET 4u",
+            "static inline void lib_x_w_set_v(lib_x_w_data *data, size_t lane, const uint8_t *value) {",
+            "#endif /* PROJ_LIB_H */",
+        ] {
+            assert!(header.lines().any(|l| l == *line), "missing {}", line);
+        }
+        assert!(!header.contains("LIB_X_N"));
+
+        let filtered = CBackEnd::default()
+            .with_filter(Filter::new().with_exclude("lib")?)
+            .render(&prj, "out")?;
+        assert!(filtered.is_empty());
+        Ok(())
+    }
+}
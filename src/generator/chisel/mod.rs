@@ -1,7 +1,7 @@
 //! Chisel back-end.
 
 use crate::design::Project;
-use crate::generator::GenerateProject;
+use crate::generator::{GenerateProject, GeneratedFile};
 use crate::Result;
 use std::path::Path;
 
@@ -35,7 +35,11 @@ pub struct ChiselBackEnd {
 
 #[allow(unused_variables)]
 impl GenerateProject for ChiselBackEnd {
-    fn generate(&self, project: &Project, path: impl AsRef<Path>) -> Result<()> {
+    fn target(&self) -> &str {
+        "chisel"
+    }
+
+    fn render(&self, project: &Project, path: impl AsRef<Path>) -> Result<Vec<GeneratedFile>> {
         unimplemented!();
     }
 }
@@ -4,15 +4,17 @@
 
 use std::borrow::Borrow;
 use std::cell::Ref;
+use std::collections::HashMap;
 
 use crate::design::implementation::composer::GenericComponent;
-use crate::design::{Interface, Streamlet};
+use crate::design::param::ParameterVariant;
+use crate::design::{Interface, ParamKey, Streamlet};
 pub use crate::error::{Error, Result};
-use crate::generator::common::{Component, Mode, Package, Port, Project, Record, Type};
-use crate::logical::{Group, LogicalType, Stream, Union};
+use crate::generator::common::{Component, Mode, Package, Parameter, Port, Project, Record, Type};
+use crate::logical::{Group, LogicalType, Stream, SynthesisCache, Union};
 use crate::physical::{Origin, Signal, Width};
 use crate::traits::Identify;
-use crate::{cat, Document, NonZeroReal};
+use crate::{cat, Document, NamingPolicy, NonZeroReal, PathName};
 
 // Generator-global constants:
 
@@ -24,8 +26,27 @@ pub const CANON_SUFFIX: Option<&str> = Some("com");
 /// Trait to create common representation types from things in the canonical
 /// way and user-friendly way.
 pub trait Typify {
-    fn canonical(&self, prefix: impl Into<String>) -> Vec<Signal>;
-    fn fancy(&self, _prefix: impl Into<String>) -> Option<Type> {
+    fn canonical(&self, prefix: impl Into<String>) -> Vec<Signal> {
+        self.canonical_with(prefix, &NamingPolicy::default())
+    }
+    /// Returns the canonical signals, named according to the naming policy.
+    fn canonical_with(&self, prefix: impl Into<String>, policy: &NamingPolicy) -> Vec<Signal> {
+        self.canonical_in(prefix, policy, &SynthesisCache::new())
+    }
+    /// Returns the canonical signals, named according to the naming policy,
+    /// synthesizing streams with a cache, e.g. the cache of a project.
+    fn canonical_in(
+        &self,
+        prefix: impl Into<String>,
+        policy: &NamingPolicy,
+        cache: &SynthesisCache,
+    ) -> Vec<Signal>;
+    fn fancy(&self, prefix: impl Into<String>) -> Option<Type> {
+        self.fancy_in(prefix, &SynthesisCache::new())
+    }
+    /// Returns the user-friendly type, synthesizing streams with a cache,
+    /// e.g. the cache of a project.
+    fn fancy_in(&self, _prefix: impl Into<String>, _cache: &SynthesisCache) -> Option<Type> {
         None
     }
 }
@@ -33,11 +54,24 @@ pub trait Typify {
 /// Trait to create common representation ports from things in the canonical
 /// way and user-friendly way.
 pub trait Portify {
-    fn canonical(&self, name: impl Into<String>) -> Vec<Port>;
+    fn canonical(&self, name: impl Into<String>) -> Vec<Port> {
+        self.canonical_with(name, &NamingPolicy::default())
+    }
+    /// Returns the canonical ports, named according to the naming policy.
+    fn canonical_with(&self, name: impl Into<String>, policy: &NamingPolicy) -> Vec<Port>;
     fn fancy(
+        &self,
+        port_name: impl Into<String>,
+        port_type_prefix: impl Into<String>,
+    ) -> Vec<Port> {
+        self.fancy_with(port_name, port_type_prefix, &NamingPolicy::default())
+    }
+    /// Returns the user-friendly ports, named according to the naming policy.
+    fn fancy_with(
         &self,
         _port_name: impl Into<String>,
         _port_type_prefix: impl Into<String>,
+        _policy: &NamingPolicy,
     ) -> Vec<Port> {
         Vec::new()
     }
@@ -46,16 +80,36 @@ pub trait Portify {
 /// Trait to create common representation components from things in the canonical
 /// way and user-friendly way.
 pub trait Componentify {
-    fn canonical(&self, suffix: Option<&str>) -> Component;
-    fn fancy(&self, _suffix: Option<&str>) -> Option<Component> {
+    fn canonical(&self, suffix: Option<&str>) -> Component {
+        self.canonical_with(suffix, &NamingPolicy::default())
+    }
+    /// Returns the canonical component, with ports named according to the
+    /// naming policy.
+    fn canonical_with(&self, suffix: Option<&str>, policy: &NamingPolicy) -> Component;
+    fn fancy(&self, suffix: Option<&str>) -> Option<Component> {
+        self.fancy_with(suffix, &NamingPolicy::default())
+    }
+    /// Returns the user-friendly component, with ports named according to the
+    /// naming policy.
+    fn fancy_with(&self, _suffix: Option<&str>, _policy: &NamingPolicy) -> Option<Component> {
         None
     }
 }
 
 /// Trait to create common representation of a package.
 pub trait Packify {
-    fn canonical(&self) -> Package;
-    fn fancy(&self) -> Package;
+    fn canonical(&self) -> Package {
+        self.canonical_with(&NamingPolicy::default())
+    }
+    /// Returns the canonical package, with ports named according to the
+    /// naming policy.
+    fn canonical_with(&self, policy: &NamingPolicy) -> Package;
+    fn fancy(&self) -> Package {
+        self.fancy_with(&NamingPolicy::default())
+    }
+    /// Returns the user-friendly package, with ports named according to the
+    /// naming policy.
+    fn fancy_with(&self, policy: &NamingPolicy) -> Package;
 }
 
 /// Trait to create common representation of a project.
@@ -73,47 +127,60 @@ pub trait Multilane {
 }
 
 impl Typify for LogicalType {
-    fn canonical(&self, prefix: impl Into<String>) -> Vec<Signal> {
+    fn canonical_in(
+        &self,
+        prefix: impl Into<String>,
+        policy: &NamingPolicy,
+        cache: &SynthesisCache,
+    ) -> Vec<Signal> {
         // This implementation for LogicalType assumes the LogicalType has already been
         // flattened through synthesize.
         match self {
             LogicalType::Null => Vec::new(),
             LogicalType::Bits(width) => vec![Signal::vec(prefix.into(), Origin::Source, *width)],
-            LogicalType::Group(group) => group.canonical(prefix),
-            LogicalType::Stream(stream) => stream.canonical(prefix),
-            LogicalType::Union(union) => union.canonical(prefix),
+            LogicalType::Group(group) => group.canonical_in(prefix, policy, cache),
+            LogicalType::Stream(stream) => stream.canonical_in(prefix, policy, cache),
+            LogicalType::Union(union) => union.canonical_in(prefix, policy, cache),
         }
     }
 
-    fn fancy(&self, prefix: impl Into<String>) -> Option<Type> {
+    fn fancy_in(&self, prefix: impl Into<String>, cache: &SynthesisCache) -> Option<Type> {
         // This implementation for LogicalType assumes the LogicalType has already been
         // flattened through synthesize.
         match self {
             LogicalType::Null => None,
-            LogicalType::Bits(width) => Some(Type::bitvec(width.get())),
-            LogicalType::Group(group) => group.fancy(prefix),
-            LogicalType::Stream(stream) => stream.fancy(prefix),
-            LogicalType::Union(union) => union.fancy(prefix),
+            LogicalType::Bits(width) => Some(Type::bitvec(width.get().into())),
+            LogicalType::Group(group) => group.fancy_in(prefix, cache),
+            LogicalType::Stream(stream) => stream.fancy_in(prefix, cache),
+            LogicalType::Union(union) => union.fancy_in(prefix, cache),
         }
     }
 }
 
 impl Typify for Group {
-    fn canonical(&self, prefix: impl Into<String>) -> Vec<Signal> {
+    fn canonical_in(
+        &self,
+        prefix: impl Into<String>,
+        policy: &NamingPolicy,
+        cache: &SynthesisCache,
+    ) -> Vec<Signal> {
         let n: String = prefix.into();
         let mut result = Vec::new();
         for (field_name, field_logical) in self.iter() {
-            let field_result = field_logical.canonical(cat!(n.clone(), field_name));
+            let field_result =
+                field_logical.canonical_in(policy.join(&[&n, field_name]), policy, cache);
             result.extend(field_result);
         }
         result
     }
 
-    fn fancy(&self, prefix: impl Into<String>) -> Option<Type> {
+    fn fancy_in(&self, prefix: impl Into<String>, cache: &SynthesisCache) -> Option<Type> {
         let n: String = prefix.into();
         let mut rec = Record::new_empty(n.clone());
         for (field_name, field_logical) in self.iter() {
-            if let Some(field_common_type) = field_logical.fancy(cat!(n.clone(), field_name)) {
+            if let Some(field_common_type) =
+                field_logical.fancy_in(cat!(n.clone(), field_name), cache)
+            {
                 rec.insert_new_field(field_name.to_string(), field_common_type, false, None)
             }
         }
@@ -122,20 +189,30 @@ impl Typify for Group {
 }
 
 impl Typify for Union {
-    fn canonical(&self, prefix: impl Into<String>) -> Vec<Signal> {
+    fn canonical_in(
+        &self,
+        prefix: impl Into<String>,
+        policy: &NamingPolicy,
+        cache: &SynthesisCache,
+    ) -> Vec<Signal> {
         let n: String = prefix.into();
         let mut result = Vec::new();
         if let Some((tag_name, tag_bc)) = self.tag() {
-            result.push(Signal::vec(cat!(n, tag_name), Origin::Source, tag_bc));
+            result.push(Signal::vec(
+                policy.join(&[&n, &tag_name]),
+                Origin::Source,
+                tag_bc,
+            ));
         }
         for (field_name, field_logical) in self.iter() {
-            let field_result = field_logical.canonical(cat!(n.clone(), field_name));
+            let field_result =
+                field_logical.canonical_in(policy.join(&[&n, field_name]), policy, cache);
             result.extend(field_result);
         }
         result
     }
 
-    fn fancy(&self, prefix: impl Into<String>) -> Option<Type> {
+    fn fancy_in(&self, prefix: impl Into<String>, cache: &SynthesisCache) -> Option<Type> {
         let n: String = prefix.into();
         let mut rec = Record::new_empty(n.clone());
         if let Some((tag_name, tag_bc)) = self.tag() {
@@ -151,7 +228,9 @@ impl Typify for Union {
             );
         }
         for (field_name, field_logical) in self.iter() {
-            if let Some(field_common_type) = field_logical.fancy(cat!(n.clone(), field_name)) {
+            if let Some(field_common_type) =
+                field_logical.fancy_in(cat!(n.clone(), field_name), cache)
+            {
                 rec.insert_new_field(field_name, field_common_type, false, None);
             }
         }
@@ -160,7 +239,12 @@ impl Typify for Union {
 }
 
 impl Typify for Stream {
-    fn canonical(&self, prefix: impl Into<String>) -> Vec<Signal> {
+    fn canonical_in(
+        &self,
+        prefix: impl Into<String>,
+        policy: &NamingPolicy,
+        cache: &SynthesisCache,
+    ) -> Vec<Signal> {
         // This implementation for Stream assumes the parent LogicalType has already been
         // flattened through synthesize.
         let n: String = prefix.into();
@@ -169,10 +253,10 @@ impl Typify for Stream {
         let logical = LogicalType::from(self.clone());
         assert!(logical.is_element_only());
         if !logical.is_null() {
-            let synth = logical.synthesize();
+            let synth = cache.synthesize(&logical);
             let (path, phys) = synth.streams().next().unwrap();
             for signal in phys.signal_list().into_iter() {
-                let n = cat!(n.clone(), path, signal.identifier());
+                let n = policy.join(&[&n, &path.render(policy), signal.identifier()]);
                 result.push(signal.with_name(n));
             }
         }
@@ -180,7 +264,7 @@ impl Typify for Stream {
         result
     }
 
-    fn fancy(&self, prefix: impl Into<String>) -> Option<Type> {
+    fn fancy_in(&self, prefix: impl Into<String>, cache: &SynthesisCache) -> Option<Type> {
         // This implementation for Stream assumes the parent LogicalType has already been
         // flattened through synthesize.
         let pre: String = prefix.into();
@@ -195,7 +279,7 @@ impl Typify for Stream {
         // Check if the logical stream is null.
         if !logical.is_null() {
             // Synthesize the logical stream into physical streams.
-            let synth = logical.synthesize();
+            let synth = cache.synthesize(&logical);
 
             // Obtain the path name and signal map from the physical stream.
             // There should only be one, since it is an element only stream.
@@ -210,9 +294,9 @@ impl Typify for Stream {
             });
 
             let prefix = cat!(pre, name, "data");
-            let data = self.data().fancy(&prefix).unwrap();
+            let data = self.data().fancy_in(&prefix, cache).unwrap();
             // Insert data record. There must be something there since it is not null.
-            // TODO: The fancy version doesn't account for throughput.
+            // Streams with a throughput above one get an array of element lanes.
             rec.insert_new_field(
                 "data",
                 data.with_throughput(&prefix, self.throughput()).unwrap(),
@@ -233,6 +317,10 @@ impl Typify for Stream {
             if let Some(sig) = signals.strb() {
                 rec.insert_new_field("strb", sig.width().into(), sig.reversed(), None);
             }
+            // Custom sideband signals.
+            for sig in signals.sideband() {
+                rec.insert_new_field(sig.identifier(), sig.width().into(), sig.reversed(), None);
+            }
 
             Some(Type::Record(rec))
         } else {
@@ -274,38 +362,21 @@ impl ModeFor for Origin {
 }
 
 impl Portify for Interface {
-    fn canonical(&self, prefix: impl Into<String>) -> Vec<Port> {
-        let n: String = prefix.into();
-        let mut ports = Vec::new();
-
-        let synth = self.typ().synthesize();
-
-        for (path, width) in synth.signals() {
-            ports.push(Port::new(
-                cat!(n.clone(), path.to_string()),
-                match self.mode() {
-                    crate::design::Mode::Out => Mode::Out,
-                    crate::design::Mode::In => Mode::In,
-                },
-                Type::bitvec(width.get()),
-            ));
+    /// The documentation of the interface is attached to its first port.
+    fn canonical_with(&self, prefix: impl Into<String>, policy: &NamingPolicy) -> Vec<Port> {
+        let mut ports = canonical_field(self, prefix, &PathName::new_empty(), policy);
+        if let (Some(doc), Some(port)) = (self.doc(), ports.first_mut()) {
+            port.set_doc(doc);
         }
-
-        for (path, phys) in synth.streams() {
-            for s in phys.signal_list().into_iter() {
-                let port_name = cat!(n.clone(), path, s.identifier());
-                ports.push(Port::new(
-                    port_name,
-                    s.origin().mode_for(self.mode()),
-                    s.width().into(),
-                ));
-            }
-        }
-
         ports
     }
 
-    fn fancy(&self, name: impl Into<String>, type_name: impl Into<String>) -> Vec<Port> {
+    fn fancy_with(
+        &self,
+        name: impl Into<String>,
+        type_name: impl Into<String>,
+        policy: &NamingPolicy,
+    ) -> Vec<Port> {
         let n: String = name.into();
         let tn: String = type_name.into();
 
@@ -313,21 +384,71 @@ impl Portify for Interface {
 
         let split = self.typ().split_streams();
 
-        if let Some(sig_type) = split.signal().fancy(tn.clone()) {
+        if let Some(sig_type) = split.signal().fancy_in(tn.clone(), self.synthesis()) {
             result.push(Port::new(cat!(n), self.mode().into(), sig_type));
         }
 
         // Split the LogicalType up into discrete, simple streams.
-        for (path, simple_stream) in self.typ().split_streams().streams() {
-            if let Some(typ) = simple_stream.fancy(cat!(tn.clone(), path)) {
-                result.push(Port::new(cat!(n, path), self.mode().into(), typ));
+        for (path, simple_stream) in split.streams() {
+            let path = path.render(policy);
+            if let Some(typ) = simple_stream.fancy_in(policy.join(&[&tn, &path]), self.synthesis())
+            {
+                result.push(Port::new(
+                    policy.join(&[&n, &path]),
+                    self.mode().into(),
+                    typ,
+                ));
             }
         }
 
+        if let (Some(doc), Some(port)) = (self.doc(), result.first_mut()) {
+            port.set_doc(doc);
+        }
         result
     }
 }
 
+/// Returns the canonical ports of a field of an interface, see
+/// [Interface::field], named like the ports of the whole interface.
+pub(crate) fn canonical_field(
+    interface: &Interface,
+    prefix: impl Into<String>,
+    field: &PathName,
+    policy: &NamingPolicy,
+) -> Vec<Port> {
+    let n: String = prefix.into();
+    let mut ports = Vec::new();
+
+    let synth = interface.synthesize();
+
+    for (path, width) in synth.signals().filter(|(path, _)| path.starts_with(field)) {
+        ports.push(Port::new(
+            policy.port(policy.join(&[&n, &path.render(policy)]), interface.mode()),
+            match interface.mode() {
+                crate::design::Mode::Out => Mode::Out,
+                crate::design::Mode::In => Mode::In,
+            },
+            Type::bitvec(width.get()),
+        ));
+    }
+
+    for (path, phys) in synth.streams().filter(|(path, _)| path.starts_with(field)) {
+        for s in phys.signal_list().into_iter() {
+            let mode = s.origin().mode_for(interface.mode());
+            let port_name = policy.port(
+                policy.join(&[&n, &path.render(policy), s.identifier()]),
+                match mode {
+                    Mode::In => crate::design::Mode::In,
+                    Mode::Out => crate::design::Mode::Out,
+                },
+            );
+            ports.push(Port::new(port_name, mode, s.width().into()));
+        }
+    }
+
+    ports
+}
+
 impl From<crate::design::Mode> for Mode {
     fn from(m: crate::design::Mode) -> Self {
         match m {
@@ -337,31 +458,255 @@ impl From<crate::design::Mode> for Mode {
     }
 }
 
+/// Returns the canonical ports of a streamlet.
+pub(crate) fn canonical_ports(streamlet: &Streamlet, policy: &NamingPolicy) -> Vec<Port> {
+    // Always add clock and reset for now.
+    // TODO(johanpel): at some point we need to associate interfaces with clock domains.
+    let mut all_ports = vec![
+        Port::new_documented("clk", Mode::In, Type::Bit, None),
+        Port::new_documented("rst", Mode::In, Type::Bit, None),
+    ];
+    streamlet.inputs().for_each(|interface| {
+        all_ports.extend(
+            interface
+                .borrow()
+                .canonical_with(interface.identifier(), policy),
+        );
+    });
+    streamlet.outputs().for_each(|interface| {
+        all_ports.extend(
+            interface
+                .borrow()
+                .canonical_with(interface.identifier(), policy),
+        );
+    });
+    all_ports
+}
+
+/// Returns an error if two canonical ports of a streamlet get the same name
+/// when their paths are flattened according to the naming policy, e.g. `a.bC`
+/// and `a.b.c` with [Flattening::CamelCase](crate::Flattening::CamelCase).
+/// Names are compared case-insensitively, like VHDL identifiers.
+pub(crate) fn check_port_names(streamlet: &Streamlet, policy: &NamingPolicy) -> Result<()> {
+    let dotted = |interface: &Interface, path: &PathName| {
+        std::iter::once(interface.identifier())
+            .chain(path.as_ref().iter().map(|name| name.as_ref()))
+            .collect::<Vec<&str>>()
+            .join(".")
+    };
+    let mut names: HashMap<String, String> = ["clk", "rst"]
+        .iter()
+        .map(|name| (name.to_string(), name.to_string()))
+        .collect();
+    for interface in streamlet.inputs().chain(streamlet.outputs()) {
+        let synth = interface.synthesize();
+        let signals = synth.signals().map(|(path, _)| {
+            (
+                policy.join(&[interface.identifier(), &path.render(policy)]),
+                interface.mode(),
+                dotted(&interface, path),
+            )
+        });
+        let streams = synth.streams().flat_map(|(path, phys)| {
+            phys.signal_list()
+                .into_iter()
+                .map(|s| {
+                    (
+                        policy.join(&[
+                            interface.identifier(),
+                            &path.render(policy),
+                            s.identifier(),
+                        ]),
+                        match s.origin().mode_for(interface.mode()) {
+                            Mode::In => crate::design::Mode::In,
+                            Mode::Out => crate::design::Mode::Out,
+                        },
+                        format!("{} ({})", dotted(&interface, path), s.identifier()),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+        for (name, mode, source) in signals.chain(streams) {
+            let name = policy.port(name, mode);
+            if let Some(other) = names.insert(name.to_lowercase(), source.clone()) {
+                return Err(Error::BackEndError(format!(
+                    "Ports {} and {} of streamlet {} both flatten to {}, which VHDL does not \
+                     distinguish",
+                    other,
+                    source,
+                    streamlet.identifier(),
+                    name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A term of an expression for the width of a port: a parameter, its
+/// coefficient, and its default value.
+type WidthTerm = (ParamKey, i64, i64);
+
+/// Returns expressions for the widths of the canonical bit vector ports of a
+/// streamlet in terms of its parameters.
+///
+/// The widths are determined by elaborating the streamlet for different
+/// values of each parameter, and for a few combinations of values of all
+/// parameters. Only ports of which the width appears to depend linearly on
+/// the parameters get an expression; other ports keep the width that results
+/// from the default values of the parameters.
+fn canonical_widths(streamlet: &Streamlet, policy: &NamingPolicy) -> HashMap<String, String> {
+    let widths = |ports: Vec<Port>| {
+        ports
+            .into_iter()
+            .filter_map(|p| match p.typ() {
+                Type::BitVec { width } => Some((p.identifier().to_string(), width as i64)),
+                _ => None,
+            })
+            .collect::<HashMap<String, i64>>()
+    };
+    let base = widths(canonical_ports(streamlet, policy));
+
+    // The terms of the width of every port, or None if the width of the port
+    // does not depend linearly on some parameter.
+    let mut terms: HashMap<String, Option<Vec<WidthTerm>>> = base
+        .keys()
+        .map(|port| (port.clone(), Some(vec![])))
+        .collect();
+    for parameter in streamlet.parameters() {
+        let default = match parameter.item() {
+            ParameterVariant::UInt(default) => *default,
+            _ => continue,
+        };
+        let elaborate = |value| {
+            streamlet
+                .elaborate(&[(parameter.key().clone(), value)])
+                .map(|s| widths(canonical_ports(&s, policy)))
+        };
+        let (once, twice) = match (elaborate(default + 1), elaborate(default + 2)) {
+            (Ok(once), Ok(twice)) => (once, twice),
+            _ => continue,
+        };
+        for (port, width) in &base {
+            let coefficient = match (once.get(port), twice.get(port)) {
+                (Some(once), Some(twice)) if twice - once == once - width => Some(once - width),
+                _ => None,
+            };
+            let entry = terms.get_mut(port).unwrap();
+            match (coefficient, entry.as_mut()) {
+                (Some(0), _) | (_, None) => {}
+                (Some(c), Some(t)) => t.push((parameter.key().clone(), c, default as i64)),
+                (None, Some(_)) => *entry = None,
+            }
+        }
+    }
+
+    // Verify the terms for combinations of parameter values, to catch
+    // widths that depend on multiple parameters at once, e.g. unions.
+    let parameters = streamlet
+        .parameters()
+        .filter(|p| matches!(p.item(), ParameterVariant::UInt(_)))
+        .map(|p| p.key().clone())
+        .collect::<Vec<_>>();
+    for order in &[false, true] {
+        let values = parameters
+            .iter()
+            .enumerate()
+            .map(|(i, key)| {
+                let i = if *order { parameters.len() - 1 - i } else { i };
+                (key.clone(), 64 + 16 * i as u32)
+            })
+            .collect::<Vec<_>>();
+        let probe = match streamlet.elaborate(&values) {
+            Ok(probe) => widths(canonical_ports(&probe, policy)),
+            Err(_) => continue,
+        };
+        for (port, entry) in terms.iter_mut() {
+            if let Some(t) = entry {
+                let expected = base[port]
+                    + t.iter()
+                        .map(|(name, c, default)| {
+                            let value = values.iter().find(|(k, _)| k == name).unwrap();
+                            c * (value.1 as i64 - default)
+                        })
+                        .sum::<i64>();
+                if probe.get(port) != Some(&expected) {
+                    *entry = None;
+                }
+            }
+        }
+    }
+
+    terms
+        .into_iter()
+        .filter_map(|(port, terms)| match terms {
+            Some(terms) if !terms.is_empty() => {
+                let constant = base[&port] - terms.iter().map(|(_, c, d)| c * d).sum::<i64>();
+                let mut expr = String::new();
+                for (name, c, _) in &terms {
+                    match (expr.is_empty(), *c) {
+                        (true, 1) => expr.push_str(name),
+                        (true, c) => expr.push_str(&format!("{}*{}", c, name)),
+                        (false, 1) => expr.push_str(&format!(" + {}", name)),
+                        (false, -1) => expr.push_str(&format!(" - {}", name)),
+                        (false, c) if c < 0 => expr.push_str(&format!(" - {}*{}", -c, name)),
+                        (false, c) => expr.push_str(&format!(" + {}*{}", c, name)),
+                    }
+                }
+                match constant {
+                    0 => {}
+                    c if c < 0 => expr.push_str(&format!(" - {}", -c)),
+                    c => expr.push_str(&format!(" + {}", c)),
+                }
+                Some((port, expr))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 impl Componentify for Streamlet {
-    fn canonical(&self, suffix: Option<&str>) -> Component {
+    /// Returns the canonical component of this streamlet.
+    ///
+    /// The parameters of the streamlet become generics of the component. The
+    /// widths of ports that depend on these parameters are expressed in terms
+    /// of the generics, where possible. The component of an externally
+    /// implemented streamlet is named after its entity, see
+    /// [Streamlet::external].
+    fn canonical_with(&self, suffix: Option<&str>, policy: &NamingPolicy) -> Component {
+        let mut ports = canonical_ports(self, policy);
+        if self.parameters().next().is_some() {
+            let widths = canonical_widths(self, policy);
+            ports = ports
+                .into_iter()
+                .map(|port| match widths.get(port.identifier()) {
+                    Some(width) => port.with_width(width.clone()),
+                    None => port,
+                })
+                .collect();
+        }
+        let identifier = match self.external() {
+            Some((_, entity)) => entity.to_string(),
+            None => cat!(self.identifier().to_string(), suffix.unwrap_or("")),
+        };
         Component::new(
-            cat!(self.identifier().to_string(), suffix.unwrap_or("")),
-            vec![],
-            {
-                // Always add clock and reset for now.
-                // TODO(johanpel): at some point we need to associate interfaces with clock domains.
-                let mut all_ports = vec![
-                    Port::new_documented("clk", Mode::In, Type::Bit, None),
-                    Port::new_documented("rst", Mode::In, Type::Bit, None),
-                ];
-                self.inputs().for_each(|interface| {
-                    all_ports.extend(interface.borrow().canonical(interface.identifier()));
-                });
-                self.outputs().for_each(|interface| {
-                    all_ports.extend(interface.borrow().canonical(interface.identifier()));
-                });
-                all_ports
-            },
-            self.doc(),
+            identifier,
+            self.parameters()
+                .filter_map(|p| match p.item() {
+                    ParameterVariant::UInt(default) => Some(Parameter {
+                        name: p.key().to_string(),
+                        typ: Type::Natural,
+                        default: Some(*default),
+                    }),
+                    _ => None,
+                })
+                .collect(),
+            ports,
+            self.metadata().document(self.doc().as_deref()),
         )
     }
 
-    fn fancy(&self, suffix: Option<&str>) -> Option<Component> {
+    fn fancy_with(&self, suffix: Option<&str>, policy: &NamingPolicy) -> Option<Component> {
         Some(Component::new(
             cat!(self.identifier().to_string(), suffix.unwrap_or("")),
             vec![],
@@ -370,9 +715,10 @@ impl Componentify for Streamlet {
                     |interfaces: Box<(dyn Iterator<Item = Ref<Interface>>)>| -> Vec<Port> {
                         interfaces
                             .flat_map(|interface| {
-                                interface.borrow().fancy(
+                                interface.borrow().fancy_with(
                                     interface.identifier(),
                                     cat!(self.identifier().to_string(), interface.identifier()),
+                                    policy,
                                 )
                             })
                             .collect::<Vec<Port>>()
@@ -386,33 +732,38 @@ impl Componentify for Streamlet {
                 all_ports.extend(collect_ports(self.outputs()));
                 all_ports
             },
-            self.doc(),
+            self.metadata().document(self.doc().as_deref()),
         ))
     }
 }
 
 impl Packify for crate::design::Library {
-    fn canonical(&self) -> Package {
+    fn canonical_with(&self, policy: &NamingPolicy) -> Package {
         Package {
             identifier: self.identifier().to_string(),
+            doc: self.metadata().document(self.doc().as_deref()),
             components: self
                 .streamlets()
                 .into_iter()
-                .map(|s| s.canonical(CANON_SUFFIX))
+                .map(|s| s.canonical_with(CANON_SUFFIX, policy))
                 .collect(),
         }
     }
 
-    fn fancy(&self) -> Package {
+    fn fancy_with(&self, policy: &NamingPolicy) -> Package {
         Package {
             identifier: self.identifier().to_string(),
+            doc: self.metadata().document(self.doc().as_deref()),
             components: self
                 .streamlets()
                 .into_iter()
                 .flat_map(|s| {
-                    let mut result = vec![s.canonical(CANON_SUFFIX)];
-                    if let Some(user) = s.fancy(None) {
-                        result.push(user);
+                    let mut result = vec![s.canonical_with(CANON_SUFFIX, policy)];
+                    // Externally implemented streamlets have no user component.
+                    if s.external().is_none() {
+                        if let Some(user) = s.fancy_with(None, policy) {
+                            result.push(user);
+                        }
                     }
                     result
                 })
@@ -453,17 +804,23 @@ impl Multilane for Type {
         if element_lanes > 1 {
             match self {
                 Type::Bit => Ok(Type::BitVec {
-                    width: element_lanes,
+                    width: element_lanes.into(),
                 }),
-                Type::Natural => unimplemented!("natural currently not supported outside of generics"),
-                Type::Positive => unimplemented!("positive currently not supported outside of generics"),
-                Type::BitVec { width: _ } | Type::Record(_) | Type::Union(_) | Type::Array(_) => {
-                    Ok(Type::array(
-                        format!("{}_array", identity.into()),
-                        self.clone(),
-                        element_lanes,
-                    ))
+                Type::Natural => {
+                    unimplemented!("natural currently not supported outside of generics")
+                }
+                Type::Positive => {
+                    unimplemented!("positive currently not supported outside of generics")
                 }
+                Type::BitVec { width: _ }
+                | Type::Record(_)
+                | Type::Union(_)
+                | Type::Array(_)
+                | Type::Enum(_) => Ok(Type::array(
+                    format!("{}_array", identity.into()),
+                    self.clone(),
+                    element_lanes,
+                )),
             }
         } else {
             return Ok(self.clone());
@@ -477,7 +834,9 @@ pub(crate) mod tests {
     use crate::generator::common::test::records;
     use crate::generator::vhdl::Declare;
     use crate::logical::tests::{elements, streams};
-    use crate::{Name, Positive, Result, UniqueKeyBuilder};
+    use crate::logical::{Direction, Synchronicity};
+    use crate::physical::Complexity;
+    use crate::{Name, Positive, PositiveReal, Result, UniqueKeyBuilder};
 
     use super::*;
 
@@ -572,6 +931,83 @@ pub(crate) mod tests {
             // TODO(johanpel): implement actual test
         }
 
+        #[test]
+        fn logical_to_common_multilane_streams() {
+            // t=2.5 requires three element lanes.
+            let typ = LogicalType::from(Stream::new(
+                elements::prim(8),
+                PositiveReal::new(2.5).unwrap(),
+                1,
+                Synchronicity::Sync,
+                Complexity::new_major(7),
+                Direction::Forward,
+                None,
+                false,
+            ))
+            .canonical("test");
+            assert_eq!(
+                typ,
+                vec![
+                    Signal::bit("test_valid", Origin::Source),
+                    Signal::bit("test_ready", Origin::Sink),
+                    Signal::vec("test_data", Origin::Source, Positive::new(24).unwrap()),
+                    Signal::vec("test_last", Origin::Source, Positive::new(1).unwrap()),
+                    Signal::vec("test_stai", Origin::Source, Positive::new(2).unwrap()),
+                    Signal::vec("test_endi", Origin::Source, Positive::new(2).unwrap()),
+                    Signal::vec("test_strb", Origin::Source, Positive::new(3).unwrap()),
+                ]
+            );
+
+            // Lanes of nested streams scale with the throughput of their parent.
+            let nested = LogicalType::from(Stream::new(
+                streams::prim(8),
+                PositiveReal::new(2.).unwrap(),
+                0,
+                Synchronicity::Sync,
+                Complexity::default(),
+                Direction::Forward,
+                None,
+                false,
+            ));
+            let ports = Interface::try_new("x", crate::design::Mode::In, nested, None)
+                .unwrap()
+                .canonical("x");
+            assert_eq!(ports[2].identifier(), "x_data");
+            assert_eq!(ports[2].typ(), Type::bitvec(16));
+        }
+
+        #[test]
+        fn logical_to_common_sideband() -> Result<()> {
+            let typ =
+                LogicalType::from(Stream::new_basic(elements::prim(8)).with_sideband(vec![
+                    Signal::vec("id", Origin::Source, Positive::new(4).unwrap()),
+                    Signal::bit("err", Origin::Sink),
+                ])?);
+            let ports =
+                Interface::try_new("x", crate::design::Mode::Out, typ, None)?.canonical("x");
+            assert_eq!(
+                ports
+                    .iter()
+                    .map(|p| (p.identifier().to_string(), p.mode(), p.typ()))
+                    .collect::<Vec<_>>(),
+                vec![
+                    ("x_valid".to_string(), Mode::Out, Type::Bit),
+                    ("x_ready".to_string(), Mode::In, Type::Bit),
+                    ("x_data".to_string(), Mode::Out, Type::bitvec(8)),
+                    ("x_id".to_string(), Mode::Out, Type::bitvec(4)),
+                    ("x_err".to_string(), Mode::In, Type::Bit),
+                ]
+            );
+
+            // A stream with only sideband signals is not optimized away.
+            let typ = LogicalType::from(
+                Stream::new_basic(LogicalType::Null)
+                    .with_sideband(vec![Signal::bit("parity", Origin::Source)])?,
+            );
+            assert_eq!(typ.canonical("x").len(), 3);
+            Ok(())
+        }
+
         #[test]
         fn interface_to_port() {
             let if0 = Interface::try_new("test", crate::design::Mode::In, streams::prim(8), None)
@@ -582,6 +1018,88 @@ pub(crate) mod tests {
             dbg!(if1.canonical("test"));
             // TODO(johanpel): implement actual test
         }
+
+        #[test]
+        fn naming_policy() -> Result<()> {
+            let typ = LogicalType::try_new_group(vec![
+                ("c", LogicalType::try_new_bits(2)?),
+                (
+                    "a",
+                    LogicalType::from(Stream::new_basic(LogicalType::try_new_bits(4)?)),
+                ),
+            ])?;
+            let iface = Interface::try_new("x", crate::design::Mode::In, typ, None)?;
+            let names = |ports: Vec<Port>| {
+                ports
+                    .iter()
+                    .map(|p| p.identifier().to_string())
+                    .collect::<Vec<_>>()
+            };
+
+            // The default policy matches the canonical naming.
+            assert_eq!(
+                names(iface.canonical("x")),
+                names(iface.canonical_with("x", &NamingPolicy::default()))
+            );
+            assert_eq!(
+                names(iface.canonical("x")),
+                vec!["x_c", "x_a_valid", "x_a_ready", "x_a_data"]
+            );
+
+            let policy = NamingPolicy::default()
+                .with_separator("__")
+                .with_port_suffixes("_i", "_o");
+            assert_eq!(
+                names(iface.canonical_with("x", &policy)),
+                vec!["x__c_i", "x__a__valid_i", "x__a__ready_o", "x__a__data_i"]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn generic_streamlet() -> Result<()> {
+            use crate::generator::vhdl::Declare;
+
+            let (_, streamlet) = crate::parser::nom::streamlet(
+                "Streamlet fifo <W = 8, D = 2, N = 1> (
+                   a : in Stream<Group<x: Bits<W>, y: Bits<W>>, d=D>,
+                   b : out Union<p: Bits<W>, q: Bits<N>>
+                 )",
+            )
+            .unwrap();
+            let component = streamlet.canonical(Some("_com"));
+            assert_eq!(
+                component
+                    .parameters()
+                    .iter()
+                    .map(|p| (p.name.clone(), p.default))
+                    .collect::<Vec<_>>(),
+                vec![
+                    ("W".to_string(), Some(8)),
+                    ("D".to_string(), Some(2)),
+                    ("N".to_string(), Some(1))
+                ]
+            );
+            let width = |name: &str| {
+                component
+                    .ports()
+                    .iter()
+                    .find(|p| p.identifier() == name)
+                    .and_then(|p| p.width().map(|w| w.to_string()))
+            };
+            assert_eq!(width("a_data"), Some("2*W".to_string()));
+            assert_eq!(width("a_last"), Some("D".to_string()));
+            assert_eq!(width("a_strb"), None);
+            // The width of a union depends on its largest variant.
+            assert_eq!(width("b_union"), None);
+
+            let vhdl = component.declare()?;
+            assert!(vhdl.contains(
+                "  generic(\n    W : natural := 8;\n    D : natural := 2;\n    N : natural := 1\n  );"
+            ));
+            assert!(vhdl.contains("a_data : in std_logic_vector(2*W-1 downto 0)"));
+            Ok(())
+        }
     }
 
     mod fancy {
@@ -657,6 +1175,26 @@ pub(crate) mod tests {
                     ]
                 )
             );
+
+            let typ2: Type = LogicalType::from(
+                Stream::new_basic(elements::prim(8))
+                    .with_sideband(vec![Signal::bit("err", Origin::Sink)])
+                    .unwrap(),
+            )
+            .fancy("test")
+            .unwrap();
+            assert_eq!(
+                typ2,
+                Type::record(
+                    "test",
+                    vec![
+                        Field::new("valid", Type::Bit, false, None),
+                        Field::new("ready", Type::Bit, true, None),
+                        Field::new("data", Type::bitvec(8), false, None),
+                        Field::new("err", Type::Bit, true, None)
+                    ]
+                )
+            );
         }
 
         #[test]
@@ -685,6 +1223,7 @@ pub(crate) mod tests {
         let common_streamlet = streamlet.fancy(None).unwrap();
         let pkg = Package {
             identifier: "boomer".to_string(),
+            doc: None,
             components: vec![common_streamlet],
         };
         println!("{}", pkg.declare()?);
@@ -705,6 +1244,7 @@ pub(crate) mod tests {
         let common_streamlet = streamlet.fancy(None).unwrap();
         let pkg = Package {
             identifier: "testing".to_string(),
+            doc: None,
             components: vec![common_streamlet],
         };
         println!("{}", pkg.declare()?);
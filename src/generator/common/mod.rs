@@ -6,7 +6,7 @@
 use crate::traits::Identify;
 use crate::{cat, Document};
 use crate::{Error, Result};
-use crate::{NonNegative, Reversed};
+use crate::{NonNegative, Reversed, WideNonNegative};
 
 pub mod convert;
 
@@ -237,9 +237,11 @@ impl Array {
         let p: String = with.into();
         let new_name = cat!(self.identifier(), p);
         match self.typ() {
-            Type::Bit | Type::Natural | Type::Positive | Type::BitVec { width: _ } => {
-                Array::new(new_name, self.typ().clone(), self.width())
-            }
+            Type::Bit
+            | Type::Natural
+            | Type::Positive
+            | Type::BitVec { width: _ }
+            | Type::Enum(_) => Array::new(new_name, self.typ().clone(), self.width()),
             Type::Record(rec) => Array::new(
                 new_name,
                 Type::Record(rec.append_name_nested(p.clone())),
@@ -265,6 +267,59 @@ impl Identify for Array {
     }
 }
 
+/// Inner struct for `Type::Enum`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enumeration {
+    identifier: String,
+    literals: Vec<String>,
+}
+
+impl Enumeration {
+    /// Construct a new enumeration. Returns an error if it has no literals,
+    /// or if a literal occurs more than once.
+    pub fn try_new(
+        identifier: impl Into<String>,
+        literals: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Enumeration> {
+        let identifier = identifier.into();
+        let literals = literals.into_iter().map(Into::into).collect::<Vec<String>>();
+        if literals.is_empty() {
+            return Err(Error::InvalidArgument(format!(
+                "Enumeration {} has no literals",
+                identifier
+            )));
+        }
+        for (index, literal) in literals.iter().enumerate() {
+            if literals[..index].contains(literal) {
+                return Err(Error::InvalidArgument(format!(
+                    "Literal {} occurs more than once in enumeration {}",
+                    literal, identifier
+                )));
+            }
+        }
+        Ok(Enumeration {
+            identifier,
+            literals,
+        })
+    }
+
+    /// Returns the literals of the enumeration, in order.
+    pub fn literals(&self) -> &[String] {
+        &self.literals
+    }
+
+    /// Returns true if the enumeration has a literal.
+    pub fn has_literal(&self, literal: &str) -> bool {
+        self.literals.iter().any(|l| l == literal)
+    }
+}
+
+impl Identify for Enumeration {
+    fn identifier(&self) -> &str {
+        self.identifier.as_str()
+    }
+}
+
 /// Hardware types.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
@@ -273,7 +328,7 @@ pub enum Type {
     /// A vector of bits.
     BitVec {
         /// The width of the vector.
-        width: NonNegative,
+        width: WideNonNegative,
     },
     /// Not implemented as hardware type only for generics
     Natural,
@@ -288,6 +343,8 @@ pub enum Type {
     Union(Record),
     /// An array of any type, used to represent multiple element lanes.
     Array(Array),
+    /// An enumeration of named literals, e.g. the states of a state machine.
+    Enum(Enumeration),
 }
 
 /// Bundle of names and types. Useful to represent flattened types.
@@ -295,7 +352,7 @@ pub type TypeBundle = Vec<(Vec<String>, Type, bool)>;
 
 impl Type {
     /// Construct a bit vector type.
-    pub fn bitvec(width: NonNegative) -> Type {
+    pub fn bitvec(width: WideNonNegative) -> Type {
         Type::BitVec { width }
     }
 
@@ -309,6 +366,14 @@ impl Type {
         Type::Union(Record::new(name.into(), fields))
     }
 
+    /// Construct an enumeration type.
+    pub fn enumeration(
+        name: impl Into<String>,
+        literals: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Type> {
+        Ok(Type::Enum(Enumeration::try_new(name, literals)?))
+    }
+
     /// Construct an array type.
     pub fn array(name: impl Into<String>, typ: Type, width: u32) -> Type {
         Type::Array(Array {
@@ -354,6 +419,8 @@ impl Type {
 pub struct Parameter {
     pub name: String,
     pub typ: Type,
+    /// The default value of the parameter, if any.
+    pub default: Option<NonNegative>,
 }
 
 /// Modes for ports.
@@ -383,6 +450,9 @@ pub struct Port {
     mode: Mode,
     /// Port type.
     typ: Type,
+    /// An expression for the width of a bit vector port in terms of the
+    /// parameters of its component, if the width depends on them.
+    width: Option<String>,
     /// Port documentation.
     doc: Option<String>,
 }
@@ -394,6 +464,7 @@ impl Port {
             identifier: name.into(),
             mode,
             typ,
+            width: None,
             doc: None,
         }
     }
@@ -409,6 +480,7 @@ impl Port {
             identifier: name.into(),
             mode,
             typ,
+            width: None,
             doc,
         }
     }
@@ -423,6 +495,20 @@ impl Port {
         self.typ.clone()
     }
 
+    /// Returns the expression for the width of this port in terms of the
+    /// parameters of its component, if any.
+    pub fn width(&self) -> Option<&str> {
+        self.width.as_deref()
+    }
+
+    /// Return this port with an expression for its width in terms of the
+    /// parameters of its component. The expression is only used when the
+    /// port is a bit vector.
+    pub fn with_width(mut self, width: impl Into<String>) -> Self {
+        self.width = Some(width.into());
+        self
+    }
+
     /// Returns true if the port type contains reversed fields.
     pub fn has_reversed(&self) -> bool {
         self.typ.has_reversed()
@@ -520,6 +606,8 @@ impl Component {
 pub struct Package {
     /// The identifier.
     pub identifier: String,
+    /// The documentation of the library, if any.
+    pub doc: Option<String>,
     /// The components declared within the library.66
     pub components: Vec<Component>,
 }
@@ -559,7 +647,7 @@ pub(crate) mod test {
     pub(crate) mod records {
         use super::*;
 
-        pub(crate) fn prim(bits: u32) -> Type {
+        pub(crate) fn prim(bits: WideNonNegative) -> Type {
             Type::bitvec(bits)
         }
 
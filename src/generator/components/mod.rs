@@ -1,9 +1,10 @@
 
 
 mod components {
+    use std::convert::TryFrom;
     use std::sync::atomic::{AtomicU32, Ordering};
     use crate::{
-        Name, cat, Result, Error, Identify, Natural
+        Name, cat, Result, Error, Identify, Natural, WideNonNegative
     };
     use crate::logical::{
         LogicalType
@@ -37,7 +38,7 @@ mod components {
         Component::new(
             "StreamSlice",
             vec![
-                Parameter{name: String::from("DATA_WIDTH"), typ: Type::Natural}
+                Parameter{name: String::from("DATA_WIDTH"), typ: Type::Natural, default: None}
             ],
             vec![
                 Port::new_documented("clk", Mode::In, Type::Bit, None),
@@ -56,7 +57,7 @@ mod components {
     }
 
 
-    pub fn logical_slice<'a>(logical_type : LogicalType, package: &'a mut Package, name: Option<&str>) -> Result<Architecture<'a>> {
+    pub fn logical_slice(logical_type : LogicalType, package: &mut Package, name: Option<&str>) -> Result<Architecture> {
 
         fn gen_ports (l_type: &LogicalType, mode: crate::design::Mode) -> Vec<Port> {
             let mut ports = vec![];
@@ -100,7 +101,7 @@ mod components {
         entity_ports.extend(gen_ports(&logical_type, crate::design::Mode::Out));
 
         // DATA_WIDTH spans all input ports except the valid/ready handshake signals
-        let data_width: Natural = entity_ports
+        let data_width = entity_ports
             .iter()
             .filter(|port| {
                 port.identifier().starts_with("in_")
@@ -112,7 +113,10 @@ mod components {
                 Type::Bit => 1,
                 _ => 0,
             })
-            .sum();
+            .sum::<WideNonNegative>();
+        let data_width = Natural::try_from(data_width).map_err(|_| {
+            Error::InvalidArgument(format!("slice data width {} is too wide", data_width))
+        })?;
 
         static SLICE_COUNTER: AtomicU32 = AtomicU32::new(0);
         let slice_count = SLICE_COUNTER.fetch_add(1, Ordering::Relaxed);
@@ -128,7 +132,7 @@ mod components {
         package.components.push(slice_entity);
 
         // the slice's data ports carry the full concatenated payload of width DATA_WIDTH
-        let slice = slice_comp(Type::bitvec(data_width));
+        let slice = slice_comp(Type::bitvec(data_width.into()));
 
         let mut slice_portmap = PortMapping::from_component(&slice, "canonical")?;
 
@@ -398,7 +402,7 @@ mod test {
         let streamslice_comp = Component::new(
             "StreamSlice",
             vec![
-                Parameter{name: String::from("DATA_WIDTH"), typ: Type::Natural}
+                Parameter{name: String::from("DATA_WIDTH"), typ: Type::Natural, default: None}
             ],
             vec![
                 Port::new_documented("clk", Mode::In, Type::Bit, None),
@@ -0,0 +1,431 @@
+//! SystemVerilog DPI-C back-end.
+//!
+//! Generates SystemVerilog transactors for every physical stream of the
+//! interfaces of the streamlets of a library, such that a software model
+//! can drive and monitor the RTL in any simulator that supports the Direct
+//! Programming Interface, together with C stubs of the model.
+//!
+//! Streams of `in` interfaces are driven by a `<stream>_driver` module, that
+//! calls the imported function `<stream>_source` whenever it can present a
+//! new transfer to the RTL. Streams of `out` interfaces are consumed by a
+//! `<stream>_monitor` module, that calls `<stream>_ready` every cycle to
+//! determine whether it accepts a transfer, and `<stream>_sink` for every
+//! transfer it accepts.
+//!
+//! The signals of a transfer are passed as packed SystemVerilog vectors. The
+//! C stubs convert the `data` vector from and to the element layouts
+//! generated by the [C back-end](crate::generator::c), which is included in
+//! the output.
+
+use std::path::Path;
+
+use crate::design::{Library, Mode, Project};
+use crate::generator::c::{field_name, named_streams, CBackEnd, NamedStream};
+use crate::generator::filter::Filter;
+use crate::generator::header::HeaderTemplate;
+use crate::generator::{GenerateProject, GeneratedFile};
+use crate::physical::{Origin, Signal, Width};
+use crate::{Identify, Result};
+
+/// Helper functions to convert between packed SystemVerilog vectors and
+/// bytes, shared by all stubs.
+const HELPERS: &str = r#"/* Copies count bytes of a packed vector, least significant bit first. */
+static inline void tydi_from_sv(const svBitVecVal *vector, uint8_t *bytes, size_t count) {
+    for (size_t i = 0; i < count; i++) {
+        bytes[i] = (uint8_t)(vector[i / 4] >> (8 * (i % 4)));
+    }
+}
+
+/* Copies count bytes to a packed vector, least significant bit first. */
+static inline void tydi_to_sv(const uint8_t *bytes, svBitVecVal *vector, size_t count) {
+    for (size_t i = 0; i < count; i++) {
+        if (i % 4 == 0) {
+            vector[i / 4] = 0;
+        }
+        vector[i / 4] |= (svBitVecVal)bytes[i] << (8 * (i % 4));
+    }
+}
+"#;
+
+/// A configurable DPI-C back-end entry point.
+#[derive(Default)]
+pub struct DpiBackEnd {
+    /// The libraries and streamlets to generate transactors for.
+    filter: Filter,
+    /// The header of generated files, if any.
+    header: Option<HeaderTemplate>,
+}
+
+impl DpiBackEnd {
+    /// Returns this back-end, only generating the transactors of the
+    /// libraries and streamlets selected by a filter.
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Returns the filter of the libraries and streamlets to generate
+    /// transactors for.
+    pub fn filter(&self) -> &Filter {
+        &self.filter
+    }
+
+    /// Returns this back-end, prepending a header to every generated file.
+    pub fn with_header(mut self, header: HeaderTemplate) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    /// Returns the template of the header of generated files, if any.
+    pub fn header(&self) -> Option<&HeaderTemplate> {
+        self.header.as_ref()
+    }
+}
+
+impl GenerateProject for DpiBackEnd {
+    fn target(&self) -> &str {
+        "dpi"
+    }
+
+    /// Every library gets a SystemVerilog source with its transactors, a C
+    /// source with the stubs of its model, and the C header with its element
+    /// layouts, in the project directory.
+    fn render(&self, project: &Project, path: impl AsRef<Path>) -> Result<Vec<GeneratedFile>> {
+        let dir = path.as_ref().join(project.identifier());
+        let c = CBackEnd::default().with_filter(self.filter.clone());
+        let mut result = Vec::new();
+        for lib in project
+            .libraries()
+            .filter(|lib| self.filter.includes_library(lib.identifier()))
+        {
+            let streams = named_streams(lib, &self.filter);
+            for (file, contents) in [
+                (format!("{}.h", lib.identifier()), c.layouts(project, lib)),
+                (
+                    format!("{}_dpi.sv", lib.identifier()),
+                    transactors(lib, &streams),
+                ),
+                (format!("{}_dpi.c", lib.identifier()), stubs(lib, &streams)),
+            ] {
+                result.push(
+                    GeneratedFile::new(dir.join(file), contents)
+                        .with_checksum("//")
+                        .with_origin(lib.identifier()),
+                );
+            }
+        }
+        Ok(match &self.header {
+            Some(header) => header.apply(project, result),
+            None => result,
+        })
+    }
+}
+
+/// Returns whether the transactor of a stream drives it, rather than
+/// monitors it.
+fn drives(named: &NamedStream) -> bool {
+    named.mode == Mode::In
+}
+
+/// Returns whether a signal is driven by the transactor of a stream.
+fn driven(named: &NamedStream, signal: &Signal) -> bool {
+    (signal.origin() == Origin::Source) == drives(named)
+}
+
+/// Returns the signals of a stream that are passed to the model, i.e. the
+/// signals driven by its source, except `valid`.
+fn arguments(named: &NamedStream) -> Vec<Signal> {
+    named
+        .stream
+        .signal_list()
+        .into_iter()
+        .filter(|s| s.origin() == Origin::Source && s.identifier() != "valid")
+        .collect()
+}
+
+/// Returns the SystemVerilog range of a signal, if it is a vector.
+fn range(signal: &Signal) -> String {
+    match signal.width() {
+        Width::Scalar => String::new(),
+        Width::Vector(width) => format!(" [{}:0]", width - 1),
+    }
+}
+
+/// Returns the C parameter that holds a signal, given whether it is an
+/// output of the imported function.
+fn parameter(signal: &Signal, output: bool) -> String {
+    match (signal.width(), output) {
+        (Width::Scalar, false) => format!("svBit {}", signal.identifier()),
+        (Width::Scalar, true) => format!("svBit *{}", signal.identifier()),
+        (Width::Vector(_), false) => format!("const svBitVecVal *{}", signal.identifier()),
+        (Width::Vector(_), true) => format!("svBitVecVal *{}", signal.identifier()),
+    }
+}
+
+/// Returns the SystemVerilog source with the transactors of the streams of
+/// a library.
+fn transactors(lib: &Library, streams: &[NamedStream]) -> String {
+    let mut result = format!(
+        "// DPI-C transactors of the physical streams of library {}.\n",
+        lib.identifier()
+    );
+    for named in streams {
+        let p = &named.prefix;
+        let args = arguments(named);
+        let mut ports = vec![
+            "  input  logic clk".to_string(),
+            "  input  logic rst".to_string(),
+        ];
+        for signal in named.stream.signal_list().into_iter() {
+            ports.push(format!(
+                "  {} logic{} {}",
+                if driven(named, &signal) {
+                    "output"
+                } else {
+                    "input "
+                },
+                range(&signal),
+                signal.identifier()
+            ));
+        }
+        let formals = |direction: &str| {
+            args.iter()
+                .map(|s| format!("{} bit{} {}", direction, range(s), s.identifier()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let locals = args
+            .iter()
+            .map(|s| format!("    bit{} t_{};\n", range(s), s.identifier()))
+            .collect::<String>();
+        let actuals = args
+            .iter()
+            .map(|s| format!("t_{}", s.identifier()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let unused = named
+            .stream
+            .signal_list()
+            .into_iter()
+            .filter(|s| driven(named, s) && s.origin() == Origin::Sink && s.identifier() != "ready")
+            .map(|s| format!("  assign {} = '0;\n", s.identifier()))
+            .collect::<String>();
+        if drives(named) {
+            result.push_str(&format!(
+                "\n// {d}.\n// Driven with the transfers of {p}_source.\n\
+                 module {p}_driver (\n{ports}\n);\n\n  \
+                 import \"DPI-C\" function int {p}_source({formals});\n\n{unused}  \
+                 always_ff @(posedge clk) begin : transfer\n{locals}    \
+                 if (rst) begin\n      valid <= 1'b0;\n    \
+                 end else if (!valid || ready) begin\n      \
+                 valid <= {p}_source({actuals}) != 0;\n{assign}    end\n  end\n\n\
+                 endmodule\n",
+                d = named.description,
+                p = p,
+                ports = ports.join(",\n"),
+                formals = formals("output"),
+                unused = unused,
+                locals = locals,
+                actuals = actuals,
+                assign = args
+                    .iter()
+                    .map(|s| format!("      {s} <= t_{s};\n", s = s.identifier()))
+                    .collect::<String>(),
+            ));
+        } else {
+            result.push_str(&format!(
+                "\n// {d}.\n// Monitored by {p}_ready and {p}_sink.\n\
+                 module {p}_monitor (\n{ports}\n);\n\n  \
+                 import \"DPI-C\" function int {p}_ready();\n  \
+                 import \"DPI-C\" function void {p}_sink({formals});\n\n{unused}  \
+                 always_ff @(posedge clk) begin\n    \
+                 if (rst) begin\n      ready <= 1'b0;\n    \
+                 end else begin\n      \
+                 if (valid && ready) begin\n        {p}_sink({actuals});\n      end\n      \
+                 ready <= {p}_ready() != 0;\n    end\n  end\n\n\
+                 endmodule\n",
+                d = named.description,
+                p = p,
+                ports = ports.join(",\n"),
+                formals = formals("input"),
+                unused = unused,
+                actuals = args
+                    .iter()
+                    .map(|s| s.identifier())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
+        }
+    }
+    result
+}
+
+/// Returns the C source with the stubs of the model of the streams of a
+/// library.
+fn stubs(lib: &Library, streams: &[NamedStream]) -> String {
+    let mut result = format!(
+        "/* Stubs of the software model of the physical streams of library {}. */\n\n\
+         #include <string.h>\n\n#include \"svdpi.h\"\n\n#include \"{}.h\"\n\n{}",
+        lib.identifier(),
+        lib.identifier(),
+        HELPERS
+    );
+    for named in streams {
+        let p = &named.prefix;
+        let args = arguments(named);
+        let has_data = named.stream.element_bit_count() > 0;
+        let example = named
+            .stream
+            .element_fields()
+            .layout()
+            .next()
+            .map(|(path, _, _)| field_name(path));
+        let others = args
+            .iter()
+            .filter(|s| !has_data || s.identifier() != "data")
+            .collect::<Vec<_>>();
+        if drives(named) {
+            let parameters = args.iter().map(|s| parameter(s, true)).collect::<Vec<_>>();
+            let mut body = String::new();
+            if has_data {
+                body.push_str(&format!(
+                    "    {p}_data element;\n    memset(&element, 0, sizeof(element));\n    \
+                     /* Set the element lanes, e.g. with {p}_set_{f}(&element, 0, ...). */\n    \
+                     tydi_to_sv(element.bytes, data, {m}_DATA_BYTES);\n",
+                    p = p,
+                    f = example.unwrap_or_default(),
+                    m = p.to_uppercase(),
+                ));
+            }
+            for s in &others {
+                body.push_str(&match s.width() {
+                    Width::Scalar => format!("    *{} = 0;\n", s.identifier()),
+                    Width::Vector(width) => format!(
+                        "    memset({}, 0, {} * sizeof(svBitVecVal));\n",
+                        s.identifier(),
+                        width.div_ceil(32)
+                    ),
+                });
+            }
+            result.push_str(&format!(
+                "\n/* {d}.\n \
+                 * Returns whether a transfer is presented to the RTL, with its signals\n \
+                 * in the arguments. */\n\
+                 int {p}_source({params}) {{\n{body}    return 0;\n}}\n",
+                d = named.description,
+                p = p,
+                params = if parameters.is_empty() {
+                    "void".to_string()
+                } else {
+                    parameters.join(", ")
+                },
+                body = body,
+            ));
+        } else {
+            let parameters = args.iter().map(|s| parameter(s, false)).collect::<Vec<_>>();
+            let mut body = String::new();
+            if has_data {
+                body.push_str(&format!(
+                    "    {p}_data element;\n    \
+                     tydi_from_sv(data, element.bytes, {m}_DATA_BYTES);\n    \
+                     /* Get the element lanes, e.g. with {p}_get_{f}(&element, 0). */\n    \
+                     (void)element;\n",
+                    p = p,
+                    f = example.unwrap_or_default(),
+                    m = p.to_uppercase(),
+                ));
+            }
+            for s in &others {
+                body.push_str(&format!("    (void){};\n", s.identifier()));
+            }
+            result.push_str(&format!(
+                "\n/* {d}.\n \
+                 * Returns whether a transfer is accepted in the next cycle. */\n\
+                 int {p}_ready(void) {{\n    return 1;\n}}\n\n\
+                 /* {d}.\n \
+                 * Called for every accepted transfer. */\n\
+                 void {p}_sink({params}) {{\n{body}}}\n",
+                d = named.description,
+                p = p,
+                params = if parameters.is_empty() {
+                    "void".to_string()
+                } else {
+                    parameters.join(", ")
+                },
+                body = body,
+            ));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Name;
+
+    #[test]
+    fn transactors() -> Result<()> {
+        let mut prj = Project::new(Name::try_new("proj")?);
+        prj.add_lib(Library::from_sdf(
+            Name::try_new("lib")?,
+            "Streamlet x (\n  \
+               a : in Stream<Group<r: Bits<8>, g: Bits<8>>, t=2, d=1>,\n  \
+               w : out Stream<Bits<40>, c=7>\n)",
+        )?)?;
+        let files = DpiBackEnd::default().render(&prj, "out")?;
+        assert_eq!(
+            files.iter().map(GeneratedFile::path).collect::<Vec<_>>(),
+            vec![
+                Path::new("out/proj/lib.h"),
+                Path::new("out/proj/lib_dpi.sv"),
+                Path::new("out/proj/lib_dpi.c")
+            ]
+        );
+        assert!(files
+            .iter()
+            .all(|f| !GeneratedFile::is_modified(f.contents())));
+
+        let sv = files[1].contents();
+        for line in &[
+            "module lib_x_a_driver (",
+            "  output logic [31:0] data,",
+            "  input  logic ready,",
+            "  import \"DPI-C\" function int lib_x_a_source(\
+             output bit [31:0] data, output bit [0:0] last, output bit [0:0] endi, \
+             output bit [1:0] strb);",
+            "      valid <= lib_x_a_source(t_data, t_last, t_endi, t_strb) != 0;",
+            "      strb <= t_strb;",
+            "module lib_x_w_monitor (",
+            "  output logic ready,",
+            "  import \"DPI-C\" function int lib_x_w_ready();",
+            "  import \"DPI-C\" function void lib_x_w_sink(\
+             input bit [39:0] data, input bit [0:0] strb);",
+            "        lib_x_w_sink(data, strb);",
+        ] {
+            assert!(sv.lines().any(|l| l == *line), "missing {}", line);
+        }
+
+        let c = files[2].contents();
+        for line in &[
+            "#include \"lib.h\"",
+            "int lib_x_a_source(svBitVecVal *data, svBitVecVal *last, svBitVecVal *endi, \
+             svBitVecVal *strb) {",
+            "    /* Set the element lanes, e.g. with lib_x_a_set_r(&element, 0, ...). */",
+            "    tydi_to_sv(element.bytes, data, LIB_X_A_DATA_BYTES);",
+            "    memset(last, 0, 1 * sizeof(svBitVecVal));",
+            "int lib_x_w_ready(void) {",
+            "void lib_x_w_sink(const svBitVecVal *data, const svBitVecVal *strb) {",
+            "    tydi_from_sv(data, element.bytes, LIB_X_W_DATA_BYTES);",
+            "    /* Get the element lanes, e.g. with lib_x_w_get_value(&element, 0). */",
+        ] {
+            assert!(c.lines().any(|l| l == *line), "missing {}", line);
+        }
+
+        let filtered = DpiBackEnd::default()
+            .with_filter(Filter::new().with_exclude("lib")?)
+            .render(&prj, "out")?;
+        assert!(filtered.is_empty());
+        Ok(())
+    }
+}
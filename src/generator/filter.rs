@@ -0,0 +1,147 @@
+//! Selection of the parts of a project to generate output for.
+//!
+//! A [Filter] selects libraries and streamlets with patterns of the form
+//! `library` or `library.streamlet`, such that regeneration can be limited to
+//! the parts of a project that are being worked on.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Error, Name, Result};
+
+/// A pattern that matches a library, or a streamlet of a library.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    library: Name,
+    streamlet: Option<Name>,
+}
+
+impl Pattern {
+    /// Returns true if this pattern matches a library, i.e. the library
+    /// itself or one of its streamlets.
+    pub fn matches_library(&self, library: &str) -> bool {
+        self.library == *library
+    }
+
+    /// Returns true if this pattern matches a streamlet of a library.
+    pub fn matches_streamlet(&self, library: &str, streamlet: &str) -> bool {
+        self.matches_library(library) && self.streamlet.iter().all(|name| *name == *streamlet)
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = Error;
+
+    /// Parse a pattern of the form `library` or `library.streamlet`.
+    fn from_str(input: &str) -> Result<Self> {
+        let (library, streamlet) = match input.split_once('.') {
+            Some((library, streamlet)) => (library, Some(streamlet)),
+            None => (input, None),
+        };
+        Ok(Pattern {
+            library: Name::try_new(library)?,
+            streamlet: streamlet.map(Name::try_new).transpose()?,
+        })
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.streamlet {
+            Some(streamlet) => write!(f, "{}.{}", self.library, streamlet),
+            None => write!(f, "{}", self.library),
+        }
+    }
+}
+
+/// A selection of the libraries and streamlets of a project.
+///
+/// If no patterns are added, everything is selected. Otherwise, a streamlet
+/// is selected if it matches any of the `only` patterns, if there are any,
+/// and none of the `exclude` patterns. A library is selected if any `only`
+/// pattern refers to it, if there are any, and it is not excluded as a whole.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    only: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl Filter {
+    /// Construct a filter that selects everything.
+    pub fn new() -> Self {
+        Filter::default()
+    }
+
+    /// Returns this filter, limited to the parts matching a pattern.
+    pub fn with_only(mut self, pattern: &str) -> Result<Self> {
+        self.only.push(pattern.parse()?);
+        Ok(self)
+    }
+
+    /// Returns this filter, excluding the parts matching a pattern.
+    pub fn with_exclude(mut self, pattern: &str) -> Result<Self> {
+        self.exclude.push(pattern.parse()?);
+        Ok(self)
+    }
+
+    /// Returns true if this filter selects everything.
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Returns true if output should be generated for a library.
+    pub fn includes_library(&self, library: &str) -> bool {
+        !self
+            .exclude
+            .iter()
+            .any(|p| p.streamlet.is_none() && p.matches_library(library))
+            && (self.only.is_empty() || self.only.iter().any(|p| p.matches_library(library)))
+    }
+
+    /// Returns true if output should be generated for a streamlet of a
+    /// library.
+    pub fn includes_streamlet(&self, library: &str, streamlet: &str) -> bool {
+        self.includes_library(library)
+            && !self
+                .exclude
+                .iter()
+                .any(|p| p.matches_streamlet(library, streamlet))
+            && (self.only.is_empty()
+                || self
+                    .only
+                    .iter()
+                    .any(|p| p.matches_streamlet(library, streamlet)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter() -> Result<()> {
+        let filter = Filter::new();
+        assert!(filter.is_empty());
+        assert!(filter.includes_library("a"));
+        assert!(filter.includes_streamlet("a", "x"));
+
+        let filter = Filter::new().with_only("a.x")?.with_only("b")?;
+        assert!(filter.includes_library("a"));
+        assert!(filter.includes_streamlet("a", "x"));
+        assert!(!filter.includes_streamlet("a", "y"));
+        assert!(filter.includes_streamlet("b", "y"));
+        assert!(!filter.includes_library("c"));
+
+        let filter = Filter::new().with_exclude("a.x")?.with_exclude("b")?;
+        assert!(filter.includes_library("a"));
+        assert!(!filter.includes_streamlet("a", "x"));
+        assert!(filter.includes_streamlet("a", "y"));
+        assert!(!filter.includes_library("b"));
+        assert!(!filter.includes_streamlet("b", "y"));
+
+        assert_eq!("a.x".parse::<Pattern>()?.to_string(), "a.x");
+        assert!(Filter::new().with_only("a..x").is_err());
+        assert!(Filter::new().with_exclude("").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+//! Headers of generated files.
+//!
+//! A [HeaderTemplate] describes a header, such as a copyright banner, that
+//! back-ends prepend to every file they generate, in a comment of the
+//! language of the file. Templates can refer to the following variables:
+//!
+//! - `{project}`: the name of the project,
+//! - `{library}`: the library the file was generated from, if any,
+//! - `{streamlet}`: the streamlet the file was generated from, if any,
+//! - `{fingerprint}`: the [fingerprint] of that streamlet, if any,
+//! - `{date}`: the date of generation, in `YYYY-MM-DD` format, and
+//! - `{version}`: the version of this crate.
+//!
+//! Braces are escaped by doubling them, i.e. `{{` and `}}`.
+//!
+//! [fingerprint]: crate::design::Streamlet::fingerprint
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::design::Project;
+use crate::generator::GeneratedFile;
+use crate::{Error, Identify, Result};
+
+/// The variables a header template can refer to.
+const VARIABLES: &[&str] = &[
+    "project",
+    "library",
+    "streamlet",
+    "fingerprint",
+    "date",
+    "version",
+];
+
+/// A part of a header template.
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    /// Literal text.
+    Text(String),
+    /// A variable, substituted when the header is rendered.
+    Variable(String),
+}
+
+/// A template of the header of generated files.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeaderTemplate {
+    parts: Vec<Part>,
+    /// The date substituted for `{date}`, if not the current date.
+    date: Option<String>,
+}
+
+impl HeaderTemplate {
+    /// Parse a header template. Returns an error if the template refers to
+    /// an unknown variable, or contains an unmatched brace.
+    pub fn new(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => {
+                                return Err(Error::InvalidArgument(
+                                    "unmatched { in header template".to_string(),
+                                ))
+                            }
+                        }
+                    }
+                    if !VARIABLES.contains(&name.as_str()) {
+                        return Err(Error::InvalidArgument(format!(
+                            "unknown variable {{{}}} in header template, expected one of: {}",
+                            name,
+                            VARIABLES.join(", ")
+                        )));
+                    }
+                    parts.push(Part::Text(std::mem::take(&mut text)));
+                    parts.push(Part::Variable(name));
+                }
+                '}' => {
+                    return Err(Error::InvalidArgument(
+                        "unmatched } in header template".to_string(),
+                    ))
+                }
+                c => text.push(c),
+            }
+        }
+        parts.push(Part::Text(text));
+        Ok(HeaderTemplate { parts, date: None })
+    }
+
+    /// Returns this template, substituting a fixed date for `{date}` rather
+    /// than the current date, e.g. for reproducible output.
+    pub fn with_date(mut self, date: impl Into<String>) -> Self {
+        self.date = Some(date.into());
+        self
+    }
+
+    /// Returns the header of a file generated from a project.
+    pub fn render(&self, project: &Project, file: &GeneratedFile) -> String {
+        let (library, streamlet) = match file.origin() {
+            Some(origin) => match origin.split_once('.') {
+                Some((library, streamlet)) => (library, streamlet),
+                None => (origin, ""),
+            },
+            None => ("", ""),
+        };
+        let fingerprint = project
+            .libraries()
+            .filter(|lib| lib.identifier() == library)
+            .flat_map(|lib| lib.streamlets())
+            .find(|s| s.identifier() == streamlet)
+            .map(|s| format!("{:016x}", s.fingerprint()))
+            .unwrap_or_default();
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Variable(name) => match name.as_str() {
+                    "project" => project.identifier().to_string(),
+                    "library" => library.to_string(),
+                    "streamlet" => streamlet.to_string(),
+                    "fingerprint" => fingerprint.clone(),
+                    "date" => self.date.clone().unwrap_or_else(today),
+                    _ => env!("CARGO_PKG_VERSION").to_string(),
+                },
+            })
+            .collect()
+    }
+
+    /// Returns files generated from a project, with their headers.
+    pub fn apply(&self, project: &Project, files: Vec<GeneratedFile>) -> Vec<GeneratedFile> {
+        files
+            .into_iter()
+            .map(|file| {
+                let header = self.render(project, &file);
+                file.with_header(&header)
+            })
+            .collect()
+    }
+}
+
+/// Returns the current date in UTC, in `YYYY-MM-DD` format.
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0);
+    date(days as i64)
+}
+
+/// Returns the date a number of days after 1970-01-01, in `YYYY-MM-DD`
+/// format, in the proleptic Gregorian calendar.
+fn date(days: i64) -> String {
+    // Count from 0000-03-01, such that leap days end a year.
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::Library;
+    use crate::Name;
+
+    #[test]
+    fn header() -> Result<()> {
+        let mut prj = Project::new(Name::try_new("proj")?);
+        prj.add_lib(Library::from_sdf(
+            Name::try_new("lib")?,
+            "Streamlet x (a : in Bits<1>)",
+        )?)?;
+        let template = HeaderTemplate::new(
+            "Copyright {{ACME}}, {date}\n\n{project} {library}.{streamlet} {fingerprint}",
+        )?
+        .with_date("2020-02-29");
+        let fingerprint = prj
+            .libraries()
+            .next()
+            .unwrap()
+            .streamlets()
+            .next()
+            .unwrap()
+            .fingerprint();
+
+        let file = GeneratedFile::new("x.vhd", "entity x is\nend x;\n")
+            .with_checksum("--")
+            .with_origin("lib.x");
+        assert_eq!(
+            template.render(&prj, &file),
+            format!(
+                "Copyright {{ACME}}, 2020-02-29\n\nproj lib.x {:016x}",
+                fingerprint
+            )
+        );
+        let files = template.apply(&prj, vec![file]);
+        let contents = files[0].contents();
+        assert!(!GeneratedFile::is_modified(contents));
+        assert_eq!(
+            contents.lines().skip(1).collect::<Vec<_>>(),
+            vec![
+                "-- Copyright {ACME}, 2020-02-29",
+                "--",
+                &format!("-- proj lib.x {:016x}", fingerprint),
+                "entity x is",
+                "end x;",
+            ]
+        );
+
+        let file = GeneratedFile::new("lib.h", "").with_origin("lib");
+        assert_eq!(
+            template.render(&prj, &file),
+            "Copyright {ACME}, 2020-02-29\n\nproj lib. "
+        );
+
+        for invalid in &["{name}", "{project", "project}"] {
+            assert!(HeaderTemplate::new(invalid).is_err(), "{}", invalid);
+        }
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(11_016), "2000-02-29");
+        assert_eq!(date(20_742), "2026-10-16");
+        assert_eq!(today().len(), 10);
+        Ok(())
+    }
+}
@@ -1,10 +1,20 @@
-use std::path::Path;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use tracing::{debug, debug_span, info, info_span};
+use serde::{Deserialize, Serialize};
 
 use crate::design::Project;
-use crate::Result;
+use crate::util::fnv1a;
+use crate::{Error, Identify, Result};
 
+pub mod c;
 pub mod chisel;
 pub mod common;
+pub mod dpi;
+pub mod filter;
+pub mod header;
 pub mod vhdl;
 pub mod components;
 
@@ -29,9 +39,580 @@ macro_rules! cat {
     }};
 }
 
+/// The marker of the checksum comment embedded in generated files.
+const CHECKSUM_MARKER: &str = "tydi-checksum: ";
+
+/// The marker of the comment that begins a user region in generated files.
+const USER_BEGIN_MARKER: &str = "tydi-user-begin: ";
+
+/// The marker of the comment that ends a user region in generated files.
+const USER_END_MARKER: &str = "tydi-user-end: ";
+
+/// Returns the checksum of the contents of a generated file, i.e. its 64-bit
+/// FNV-1a hash, which is stable across platforms and compiler versions.
+///
+/// The contents of user regions are not part of the checksum, such that
+/// files which are only modified within them are not considered modified.
+fn checksum(contents: &str) -> u64 {
+    match split_user_regions(contents) {
+        Some((generated, _)) => fnv1a(generated.as_bytes()),
+        None => fnv1a(contents.as_bytes()),
+    }
+}
+
+/// Returns the comments that begin and end a user region with the given name,
+/// e.g. `-- tydi-user-begin: statements`, as lines prefixed with `pre`.
+///
+/// The contents of user regions in existing files are kept when the files are
+/// regenerated.
+pub(crate) fn user_region(comment: &str, pre: &str, name: &str) -> String {
+    format!(
+        "{0}{1} {2}{4}\n{0}{1} {3}{4}\n",
+        pre, comment, USER_BEGIN_MARKER, USER_END_MARKER, name
+    )
+}
+
+/// Splits contents into the contents without the lines within user regions,
+/// and the lines within each user region by name. Returns None if the user
+/// regions are not properly closed.
+fn split_user_regions(contents: &str) -> Option<(String, Vec<(String, String)>)> {
+    let mut generated = String::new();
+    let mut regions = Vec::new();
+    let mut current: Option<(String, String)> = None;
+    for line in contents.split_inclusive('\n') {
+        let marker = |marker: &str| {
+            line.split_once(marker)
+                .map(|(_, name)| name.trim().to_string())
+        };
+        match (current.take(), marker(USER_BEGIN_MARKER), marker(USER_END_MARKER)) {
+            (None, Some(name), None) => {
+                generated.push_str(line);
+                current = Some((name, String::new()));
+            }
+            (None, None, None) => generated.push_str(line),
+            (Some((name, body)), None, Some(end)) if name == end => {
+                generated.push_str(line);
+                regions.push((name, body));
+            }
+            (Some((name, mut body)), None, None) => {
+                body.push_str(line);
+                current = Some((name, body));
+            }
+            _ => return None,
+        }
+    }
+    match current {
+        None => Some((generated, regions)),
+        Some(_) => None,
+    }
+}
+
+/// What to do when a generated file is saved to a path where a file exists
+/// that was modified by hand since it was generated.
+///
+/// Whether a file was modified is detected through the checksum comment on
+/// its first line. Files without a checksum comment are considered to be
+/// modified. Changes within user regions are not considered modifications.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Return an error.
+    #[default]
+    Error,
+    /// Keep the modified file.
+    Skip,
+    /// Overwrite the modified file.
+    Force,
+}
+
+impl FromStr for OverwritePolicy {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "error" => Ok(OverwritePolicy::Error),
+            "skip" => Ok(OverwritePolicy::Skip),
+            "force" => Ok(OverwritePolicy::Force),
+            _ => Err(Error::InvalidArgument(format!(
+                "unknown overwrite policy: {}",
+                input
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for OverwritePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OverwritePolicy::Error => write!(f, "error"),
+            OverwritePolicy::Skip => write!(f, "skip"),
+            OverwritePolicy::Force => write!(f, "force"),
+        }
+    }
+}
+
+/// A source file rendered by a back-end.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratedFile {
+    /// The path to save the file to.
+    path: PathBuf,
+    /// The contents of the file.
+    contents: String,
+    /// The library or streamlet the file was generated from.
+    origin: Option<String>,
+    /// The line comment of the language of the file, if known.
+    comment: Option<String>,
+}
+
+impl GeneratedFile {
+    /// Construct a generated file.
+    pub fn new(path: impl Into<PathBuf>, contents: impl Into<String>) -> Self {
+        GeneratedFile {
+            path: path.into(),
+            contents: contents.into(),
+            origin: None,
+            comment: None,
+        }
+    }
+
+    /// Returns this file with the library or streamlet it was generated
+    /// from, e.g. `my_library` or `my_library.my_streamlet`.
+    pub fn with_origin(mut self, origin: impl Into<String>) -> Self {
+        self.origin = Some(origin.into());
+        self
+    }
+
+    /// Returns the path to save the file to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the contents of the file.
+    pub fn contents(&self) -> &str {
+        &self.contents
+    }
+
+    /// Returns the library or streamlet the file was generated from, if any.
+    pub fn origin(&self) -> Option<&str> {
+        self.origin.as_deref()
+    }
+
+    /// Returns the hash of the contents of the file, in hexadecimal.
+    pub fn hash(&self) -> String {
+        format!("{:016x}", checksum(&self.contents))
+    }
+
+    /// Returns this file with a checksum of its contents embedded in a
+    /// comment on its first line, e.g. `--` for VHDL, such that files that
+    /// are modified by hand after generation can be detected.
+    pub fn with_checksum(mut self, comment: &str) -> Self {
+        self.contents = format!(
+            "{} {}{:016x}\n{}",
+            comment,
+            CHECKSUM_MARKER,
+            checksum(&self.contents),
+            self.contents
+        );
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    /// Returns this file with a header prepended to its contents, e.g. a
+    /// copyright banner, in comments below its checksum comment.
+    ///
+    /// Files without a checksum comment are returned unchanged, as the
+    /// comment syntax of their language is unknown.
+    pub fn with_header(mut self, header: &str) -> Self {
+        if let Some(comment) = self.comment.take() {
+            let (_, contents) = self.contents.split_once('\n').unwrap_or(("", ""));
+            let mut result = String::new();
+            for line in header.lines() {
+                if line.is_empty() {
+                    result.push_str(&format!("{}\n", comment));
+                } else {
+                    result.push_str(&format!("{} {}\n", comment, line));
+                }
+            }
+            self.contents = result + contents;
+            self = self.with_checksum(&comment);
+        }
+        self
+    }
+
+    /// Returns true if the contents of a file were modified since they were
+    /// generated, i.e. if the file has no checksum comment on its first line
+    /// or the checksum does not match the rest of the file.
+    pub fn is_modified(contents: &str) -> bool {
+        let (first, rest) = contents.split_once('\n').unwrap_or((contents, ""));
+        match first.split_once(CHECKSUM_MARKER) {
+            Some((_, sum)) => u64::from_str_radix(sum.trim(), 16).ok() != Some(checksum(rest)),
+            None => true,
+        }
+    }
+
+    /// Returns the contents of the file, with the contents of the user
+    /// regions of an existing version of the file in their place.
+    ///
+    /// Returns an error if the existing file has a non-empty user region
+    /// which is not in the contents of this file, as its contents would be
+    /// lost.
+    pub fn merge_user_regions(&self, existing: &str) -> Result<String> {
+        let regions = match split_user_regions(existing) {
+            Some((_, regions)) => regions,
+            None => return Ok(self.contents.clone()),
+        };
+        let mut result = String::new();
+        let mut kept = Vec::new();
+        let mut replacing = false;
+        for line in self.contents.split_inclusive('\n') {
+            if let Some((_, name)) = line.split_once(USER_END_MARKER) {
+                replacing = false;
+                if let Some((name, body)) = regions.iter().find(|(n, _)| n == name.trim()) {
+                    result.push_str(body);
+                    kept.push(name);
+                }
+            } else if replacing {
+                continue;
+            }
+            if let Some((_, name)) = line.split_once(USER_BEGIN_MARKER) {
+                replacing = regions.iter().any(|(n, _)| n == name.trim());
+            }
+            result.push_str(line);
+        }
+        let lost = regions
+            .iter()
+            .filter(|(name, body)| !body.is_empty() && !kept.contains(&name))
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        if !lost.is_empty() {
+            return Err(Error::FileIOError(format!(
+                "{} has user regions which are no longer generated: {}",
+                self.path.display(),
+                lost.join(", ")
+            )));
+        }
+        Ok(result)
+    }
+
+    /// Save the file, creating its directory if it does not exist. Returns
+    /// false if the file was not saved, because an existing file was
+    /// modified and the policy is to skip it.
+    ///
+    /// The contents of the user regions of an existing file are kept. If
+    /// they cannot be kept, modified files are overwritten when the policy is
+    /// to force it.
+    pub fn write(&self, policy: OverwritePolicy) -> Result<bool> {
+        let _span = debug_span!("write", file = %self.path.display()).entered();
+        let mut contents = self.contents.clone();
+        if self.path.exists() {
+            let existing = std::fs::read_to_string(&self.path)?;
+            if policy != OverwritePolicy::Force && GeneratedFile::is_modified(&existing) {
+                if policy == OverwritePolicy::Skip {
+                    info!("Skipped {}, which was modified.", self.path.display());
+                    return Ok(false);
+                }
+                return Err(Error::FileIOError(format!(
+                    "{} was modified since it was generated",
+                    self.path.display()
+                )));
+            }
+            match self.merge_user_regions(&existing) {
+                Ok(merged) => contents = merged,
+                Err(_) if policy == OverwritePolicy::Force => {}
+                Err(err) => return Err(err),
+            }
+        }
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&self.path, &contents)?;
+        debug!("Wrote {}.", self.path.display());
+        Ok(true)
+    }
+}
+
+/// A machine-readable list of the files generated by a back-end, for build
+/// systems and packagers. It is stored in TOML format, e.g.:
+///
+/// ```toml
+/// target = "vhdl"
+///
+/// [[file]]
+/// path = "my_library_pkg.gen.vhd"
+/// hash = "8a1f3c5e7b9d0246"
+/// origin = "my_library"
+/// ```
+///
+/// Paths are relative to the directory of the manifest. Hashes are 64-bit
+/// FNV-1a hashes of the generated contents. Files that were not saved,
+/// because they were modified by hand, are marked with `skipped = true`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenerationManifest {
+    /// The target back-end.
+    target: String,
+    /// The generated files.
+    #[serde(default, rename = "file")]
+    files: Vec<GenerationManifestFile>,
+}
+
+/// A file in a [GenerationManifest].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenerationManifestFile {
+    /// The path of the file, relative to the manifest.
+    path: PathBuf,
+    /// The hash of the generated contents of the file.
+    hash: String,
+    /// The library or streamlet the file was generated from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    origin: Option<String>,
+    /// Whether the file was not saved because it was modified by hand.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    skipped: bool,
+}
+
+impl GenerationManifest {
+    /// Returns the target back-end.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns an iterator over the generated files.
+    pub fn files(&self) -> impl Iterator<Item = &GenerationManifestFile> {
+        self.files.iter()
+    }
+
+    /// Parse a generation manifest in TOML format.
+    pub fn from_toml(input: &str) -> Result<Self> {
+        toml::from_str(input).map_err(|e| Error::ParsingError(e.to_string()))
+    }
+
+    /// Render this generation manifest in TOML format.
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).map_err(|e| Error::BackEndError(e.to_string()))
+    }
+}
+
+impl GenerationManifestFile {
+    /// Returns the path of the file, relative to the manifest.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the hash of the generated contents of the file.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// Returns the library or streamlet the file was generated from, if any.
+    pub fn origin(&self) -> Option<&str> {
+        self.origin.as_deref()
+    }
+
+    /// Returns true if the file was not saved because it was modified by
+    /// hand.
+    pub fn is_skipped(&self) -> bool {
+        self.skipped
+    }
+}
+
+/// A job that renders a single file, independently of other jobs.
+pub(crate) type RenderJob<'a> = Box<dyn FnOnce() -> Result<GeneratedFile> + Send + 'a>;
+
+/// Run render jobs and return their files in the order of the jobs.
+///
+/// With the `parallel` feature, jobs run in parallel. The result does not
+/// depend on the order in which jobs finish: if jobs fail, the error of the
+/// first failing job is returned.
+pub(crate) fn render_jobs(jobs: Vec<RenderJob>) -> Result<Vec<GeneratedFile>> {
+    #[cfg(feature = "parallel")]
+    let results: Vec<Result<GeneratedFile>> = {
+        use rayon::prelude::*;
+        jobs.into_par_iter().map(|job| job()).collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let results: Vec<Result<GeneratedFile>> = jobs.into_iter().map(|job| job()).collect();
+    results.into_iter().collect()
+}
+
 /// Trait to generate back-end specific source files from the common hardware representation
 /// of a project.
 pub trait GenerateProject {
+    /// Returns the name of the target of this back-end, e.g. `vhdl`.
+    fn target(&self) -> &str;
+
+    /// Render the source files of a [common::Project] in memory, with paths in \[path\],
+    /// without saving them.
+    fn render(&self, project: &Project, path: impl AsRef<Path>) -> Result<Vec<GeneratedFile>>;
+
     /// Generate source files from a [common::Project] and save them to \[path\].
-    fn generate(&self, project: &Project, path: impl AsRef<Path>) -> Result<()>;
+    ///
+    /// Returns an error if a file that was modified since it was generated
+    /// would be overwritten.
+    fn generate(&self, project: &Project, path: impl AsRef<Path>) -> Result<()> {
+        self.generate_with_policy(project, path, OverwritePolicy::default())
+    }
+
+    /// Generate source files from a [common::Project] and save them to \[path\],
+    /// with a policy for existing files that were modified since they were
+    /// generated.
+    ///
+    /// A [GenerationManifest] of the files is saved to
+    /// `<path>/<project>/<target>.manifest.toml`.
+    fn generate_with_policy(
+        &self,
+        project: &Project,
+        path: impl AsRef<Path>,
+        policy: OverwritePolicy,
+    ) -> Result<()> {
+        let _span = info_span!("generate", target = self.target()).entered();
+        let dir = path.as_ref().join(project.identifier());
+        let files = self.render(project, path)?;
+        // Check all files before saving any, such that an error does not
+        // leave the output partially regenerated.
+        if policy == OverwritePolicy::Error {
+            for file in &files {
+                if file.path().exists() {
+                    let existing = std::fs::read_to_string(file.path())?;
+                    if GeneratedFile::is_modified(&existing) {
+                        return Err(Error::FileIOError(format!(
+                            "{} was modified since it was generated",
+                            file.path().display()
+                        )));
+                    }
+                    file.merge_user_regions(&existing)?;
+                }
+            }
+        }
+        let mut manifest = GenerationManifest {
+            target: self.target().to_string(),
+            files: Vec::new(),
+        };
+        for file in files {
+            let written = file.write(policy)?;
+            manifest.files.push(GenerationManifestFile {
+                path: file
+                    .path()
+                    .strip_prefix(&dir)
+                    .unwrap_or_else(|_| file.path())
+                    .to_path_buf(),
+                hash: file.hash(),
+                origin: file.origin,
+                skipped: !written,
+            });
+        }
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join(format!("{}.manifest.toml", self.target())),
+            manifest.to_toml()?,
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overwrite_policy() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let path = tmpdir.path().join("dir/file.vhd");
+        let file = GeneratedFile::new(&path, "entity x is\nend x;\n").with_checksum("--");
+        assert!(!GeneratedFile::is_modified(file.contents()));
+        assert!(GeneratedFile::is_modified("entity x is\nend x;\n"));
+
+        assert!(file.write(OverwritePolicy::Error)?);
+        // Unmodified files are overwritten.
+        assert!(file.write(OverwritePolicy::Error)?);
+
+        std::fs::write(&path, format!("{}-- edit\n", file.contents()))?;
+        assert!(file.write(OverwritePolicy::Error).is_err());
+        assert!(!file.write(OverwritePolicy::Skip)?);
+        assert!(GeneratedFile::is_modified(&std::fs::read_to_string(&path)?));
+        assert!(file.write(OverwritePolicy::Force)?);
+        assert_eq!(std::fs::read_to_string(&path)?, file.contents());
+
+        assert_eq!("skip".parse::<OverwritePolicy>()?, OverwritePolicy::Skip);
+        assert!("keep".parse::<OverwritePolicy>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn user_regions() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let path = tmpdir.path().join("file.vhd");
+        let generate = |ports: &str| {
+            GeneratedFile::new(
+                &path,
+                format!(
+                    "entity x is\n{}end x;\narchitecture a of x is\n{}begin\n{}end a;\n",
+                    ports,
+                    user_region("--", "  ", "declarations"),
+                    user_region("--", "  ", "statements")
+                ),
+            )
+            .with_checksum("--")
+        };
+        let file = generate("");
+        assert!(file.write(OverwritePolicy::Error)?);
+
+        // Changes within user regions are not modifications, and are kept.
+        let edited = file.contents().replace(
+            "  -- tydi-user-end: statements",
+            "  b <= a;\n  -- tydi-user-end: statements",
+        );
+        std::fs::write(&path, &edited)?;
+        assert!(!GeneratedFile::is_modified(&edited));
+        let file = generate("  port(a : in std_logic);\n");
+        assert!(file.write(OverwritePolicy::Error)?);
+        let contents = std::fs::read_to_string(&path)?;
+        assert!(!GeneratedFile::is_modified(&contents));
+        assert!(contents.contains("  port(a : in std_logic);\n"));
+        assert!(contents.contains("  b <= a;\n  -- tydi-user-end: statements"));
+
+        // Unclosed regions are modifications.
+        assert!(GeneratedFile::is_modified(
+            &contents.replace("  -- tydi-user-end: statements\n", "")
+        ));
+
+        // Regions which are no longer generated would lose their contents.
+        let file = GeneratedFile::new(&path, "entity x is\nend x;\n").with_checksum("--");
+        assert!(file
+            .write(OverwritePolicy::Error)
+            .unwrap_err()
+            .to_string()
+            .contains("no longer generated: statements"));
+        assert!(file.write(OverwritePolicy::Force)?);
+        Ok(())
+    }
+
+    #[test]
+    fn render_jobs_order() -> Result<()> {
+        let job = |index: usize| -> RenderJob {
+            Box::new(move || {
+                // Later jobs finish first when run in parallel.
+                std::thread::sleep(std::time::Duration::from_millis(20 - index as u64));
+                if index % 7 == 6 {
+                    Err(Error::BackEndError(format!("job {}", index)))
+                } else {
+                    Ok(GeneratedFile::new(format!("{}.vhd", index), ""))
+                }
+            })
+        };
+        let files = render_jobs((0..6).map(job).collect())?;
+        assert_eq!(
+            files.iter().map(|f| f.path()).collect::<Vec<_>>(),
+            (0..6)
+                .map(|index| PathBuf::from(format!("{}.vhd", index)))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            render_jobs((0..16).map(job).collect())
+                .unwrap_err()
+                .to_string(),
+            Error::BackEndError("job 6".to_string()).to_string()
+        );
+        Ok(())
+    }
 }
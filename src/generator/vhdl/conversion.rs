@@ -0,0 +1,257 @@
+//! Conversion functions between the record and array types of a package and
+//! their flattened (std_logic_vector) representation.
+//!
+//! For every type, a `to_slv` function flattens an object of that type, and a
+//! `from_slv` function converts a std_logic_vector back to that type. The bit
+//! layout is the same as that of the flattened assignments of the
+//! [FlatAssignment](crate::stdlib::common::architecture::assignment::flatten::FlatAssignment)
+//! trait: the first field of a record or the lowest index of an array is in
+//! the least significant bits. The fields of a union share the least
+//! significant bits, with the tag in the most significant bits.
+
+use indexmap::IndexMap;
+
+use crate::stdlib::common::architecture::{assignment::flatten::FlatLength, object::ObjectType};
+use crate::Result;
+
+/// Collects the record and array types nested in a type (including the type
+/// itself) which have a (non-empty) flattened representation, nested types first.
+pub(crate) fn convertible_types(typ: &ObjectType, result: &mut IndexMap<String, ObjectType>) {
+    if !typ.has_flat_length() || !typ.flat_length().is_ok_and(|length| length > 0) {
+        return;
+    }
+    match typ {
+        ObjectType::Record(rec) => {
+            for field in rec.fields().values() {
+                convertible_types(field, result);
+            }
+        }
+        ObjectType::Array(arr) if !arr.is_bitvector() => {
+            convertible_types(arr.typ(), result);
+        }
+        _ => return,
+    }
+    if !result.contains_key(typ.type_name()) {
+        result.insert(typ.type_name().to_string(), typ.clone());
+    }
+}
+
+/// Declares the conversion functions of a type, for the package declaration.
+pub(crate) fn declare_conversions(typ: &ObjectType) -> String {
+    format!(
+        "function to_slv(value : {0}) return std_logic_vector;\nfunction from_slv(value : std_logic_vector) return {0};",
+        typ.type_name()
+    )
+}
+
+/// Defines the conversion functions of a type, for the package body.
+pub(crate) fn define_conversions(typ: &ObjectType) -> Result<String> {
+    let length = typ.flat_length()?;
+    let elements = flat_elements(typ)?;
+    let mut to_slv = format!(
+        "function to_slv(value : {}) return std_logic_vector is\n  variable result : std_logic_vector({} downto 0);\nbegin\n",
+        typ.type_name(),
+        length - 1
+    );
+    let mut from_slv = format!(
+        "function from_slv(value : std_logic_vector) return {} is\n  variable result : {};\n  alias flat : std_logic_vector(value'length - 1 downto 0) is value;\nbegin\n",
+        typ.type_name(),
+        typ.type_name()
+    );
+    for element in &elements {
+        if element.driven {
+            to_slv.push_str(&format!(
+                "  result{} := {};\n",
+                element.range(),
+                element.flattened()
+            ));
+        }
+        from_slv.push_str(&format!(
+            "  result{} := {};\n",
+            element.selection,
+            element.unflattened()
+        ));
+    }
+    to_slv.push_str("  return result;\nend function to_slv;");
+    from_slv.push_str("  return result;\nend function from_slv;");
+    Ok(format!("{}\n\n{}", to_slv, from_slv))
+}
+
+/// A field or element of a type, and the bits it occupies when flattened
+struct FlatElement {
+    /// The VHDL selection of the element, e.g. `.field` or `(0)`
+    selection: String,
+    typ: ObjectType,
+    high: u32,
+    low: u32,
+    /// Whether the element drives its bits when flattening, only one of the
+    /// fields of a union can
+    driven: bool,
+}
+
+impl FlatElement {
+    fn range(&self) -> String {
+        if let ObjectType::Bit = self.typ {
+            format!("({})", self.low)
+        } else {
+            format!("({} downto {})", self.high, self.low)
+        }
+    }
+
+    fn flattened(&self) -> String {
+        let value = format!("value{}", self.selection);
+        match &self.typ {
+            ObjectType::Signed(_) | ObjectType::Unsigned(_) => {
+                format!("std_logic_vector({})", value)
+            }
+            ObjectType::Array(arr) if !arr.is_bitvector() => format!("to_slv({})", value),
+            ObjectType::Record(_) => format!("to_slv({})", value),
+            _ => value,
+        }
+    }
+
+    fn unflattened(&self) -> String {
+        let flat = format!("flat{}", self.range());
+        match &self.typ {
+            ObjectType::Signed(_) => format!("signed({})", flat),
+            ObjectType::Unsigned(_) => format!("unsigned({})", flat),
+            ObjectType::Array(arr) if !arr.is_bitvector() => format!("from_slv({})", flat),
+            ObjectType::Record(_) => format!("from_slv({})", flat),
+            _ => flat,
+        }
+    }
+}
+
+/// Lists the fields or elements of a record or array type, with the bits
+/// they occupy when flattened.
+fn flat_elements(typ: &ObjectType) -> Result<Vec<FlatElement>> {
+    let mut result = vec![];
+    let mut element = |selection: String, typ: &ObjectType, low: u32, driven: bool| {
+        let length = typ.flat_length()?;
+        // Empty elements have no bits to convert
+        if length > 0 {
+            result.push(FlatElement {
+                selection,
+                typ: typ.clone(),
+                high: low + length - 1,
+                low,
+                driven,
+            });
+        }
+        Ok::<_, crate::Error>(length)
+    };
+    match typ {
+        ObjectType::Record(rec) if rec.is_union() => {
+            let tag_length = rec.get_field("tag")?.flat_length()?;
+            let remainder = typ.flat_length()? - tag_length;
+            // Only the widest field drives the shared bits, as for flattened assignments
+            let mut widest: Option<(&String, u32)> = None;
+            for (name, field) in rec.fields() {
+                let length = field.flat_length()?;
+                if name != "tag" && widest.is_none_or(|(_, max)| length > max) {
+                    widest = Some((name, length));
+                }
+            }
+            for (name, field) in rec.fields() {
+                if name == "tag" {
+                    element(format!(".{}", name), field, remainder, true)?;
+                } else {
+                    let driven = widest.map(|(widest, _)| widest) == Some(name);
+                    element(format!(".{}", name), field, 0, driven)?;
+                }
+            }
+        }
+        ObjectType::Record(rec) => {
+            let mut preceding_length = 0;
+            for (name, field) in rec.fields() {
+                preceding_length += element(format!(".{}", name), field, preceding_length, true)?;
+            }
+        }
+        ObjectType::Array(arr) => {
+            let mut preceding_length = 0;
+            for index in arr.low()..(arr.high() + 1) {
+                preceding_length +=
+                    element(format!("({})", index), arr.typ(), preceding_length, true)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stdlib::common::architecture::{
+        assignment::{Assign, Assignment, FlatConversion, ObjectAssignment},
+        declaration::ObjectDeclaration,
+        object::RecordObject,
+        ArchitectureDeclare,
+    };
+
+    use super::*;
+
+    #[test]
+    fn record_conversions() -> Result<()> {
+        let mut fields = IndexMap::new();
+        fields.insert("a".to_string(), ObjectType::Bit);
+        fields.insert("b".to_string(), ObjectType::bit_vector(3, 0)?);
+        fields.insert(
+            "c".to_string(),
+            ObjectType::array(1, 0, ObjectType::bit_vector(1, 0)?, "c_type")?,
+        );
+        let rec = ObjectType::Record(RecordObject::new("rec_type", fields));
+        let mut types = IndexMap::new();
+        convertible_types(&rec, &mut types);
+        assert_eq!(types.keys().collect::<Vec<_>>(), vec!["c_type", "rec_type"]);
+
+        assert_eq!(
+            declare_conversions(&rec),
+            "function to_slv(value : rec_type) return std_logic_vector;\nfunction from_slv(value : std_logic_vector) return rec_type;"
+        );
+        assert_eq!(
+            define_conversions(&rec)?,
+            r#"function to_slv(value : rec_type) return std_logic_vector is
+  variable result : std_logic_vector(8 downto 0);
+begin
+  result(0) := value.a;
+  result(4 downto 1) := value.b;
+  result(8 downto 5) := to_slv(value.c);
+  return result;
+end function to_slv;
+
+function from_slv(value : std_logic_vector) return rec_type is
+  variable result : rec_type;
+  alias flat : std_logic_vector(value'length - 1 downto 0) is value;
+begin
+  result.a := flat(0);
+  result.b := flat(4 downto 1);
+  result.c := from_slv(flat(8 downto 5));
+  return result;
+end function from_slv;"#
+        );
+
+        // Objects are assigned through the conversion functions
+        let signal = ObjectDeclaration::signal("rec", rec, None);
+        let flat = ObjectDeclaration::signal("flat", ObjectType::bit_vector(8, 0)?, None);
+        let to_flat = flat.assign(&Assignment::from(FlatConversion::ToFlat(
+            ObjectAssignment::from(signal.clone()),
+        )))?;
+        assert_eq!(to_flat.declare("", ";")?, "flat <= to_slv(rec);");
+        assert_eq!(
+            to_flat.reverse()?.declare("", ";")?,
+            "rec <= from_slv(flat);"
+        );
+        let short = ObjectDeclaration::signal("short", ObjectType::bit_vector(7, 0)?, None);
+        assert!(short
+            .assign(&Assignment::from(FlatConversion::FromFlat(
+                ObjectAssignment::from(signal.clone())
+            )))
+            .is_err());
+        assert!(signal
+            .assign(&Assignment::from(FlatConversion::FromFlat(
+                ObjectAssignment::from(short)
+            )))
+            .is_err());
+        Ok(())
+    }
+}
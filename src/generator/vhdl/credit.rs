@@ -0,0 +1,432 @@
+//! Credit-based flow control wrappers of canonical components.
+//!
+//! The valid/ready handshake of a stream requires the ready signal to return
+//! from the sink within the cycle in which the source asserts valid. For
+//! streams between streamlets that are placed far apart, e.g. on different
+//! dies of a large FPGA, that round trip may not meet timing.
+//!
+//! A credit wrapper instantiates the canonical component of a streamlet, and
+//! replaces the ready port of every physical stream with a credit port, that
+//! carries credits in the same direction:
+//!
+//! * The source of a stream starts with `CREDITS` credits. It sends a
+//!   transfer by asserting valid for one cycle, which costs a credit, and
+//!   only while it has credits left. Every cycle in which credit is asserted
+//!   returns a credit.
+//! * The sink of a stream buffers up to `CREDITS` transfers, and returns a
+//!   credit for every transfer that leaves its buffer.
+//!
+//! All outputs of the wrapper are registered, such that any number of
+//! register stages may be inserted in both directions of a link, as long as
+//! `CREDITS` covers its round-trip latency. Ports that are not part of a
+//! physical stream are passed through.
+
+use std::collections::HashMap;
+
+use crate::design::Streamlet;
+use crate::generator::common::convert::{Componentify, CANON_SUFFIX};
+use crate::generator::common::{Mode as PortMode, Type};
+use crate::traits::Identify;
+use crate::{cat, design, Error, NamingPolicy, Result};
+
+use super::streams::{stream_ports, StreamPorts};
+use super::Declare;
+
+/// The suffix of the entity of credit wrappers.
+pub const CREDIT_SUFFIX: &str = "cr";
+
+/// Splits a width into the expression in terms of the generics of a
+/// streamlet and the number that is added to it.
+fn split(width: &str) -> (Option<&str>, u64) {
+    match width.parse::<u64>() {
+        Ok(number) => (None, number),
+        Err(_) => match width.rsplit_once(" + ") {
+            Some((expr, number)) if number.parse::<u64>().is_ok() => {
+                (Some(expr), number.parse().unwrap())
+            }
+            _ => (Some(width), 0),
+        },
+    }
+}
+
+/// Joins an expression and a number into a width, see [split].
+fn join(expr: Option<&str>, number: u64) -> String {
+    match (expr, number) {
+        (None, number) => number.to_string(),
+        (Some(expr), 0) => expr.to_string(),
+        (Some(expr), number) => format!("{} + {}", expr, number),
+    }
+}
+
+/// Returns the sum of two widths, which are numbers or expressions in terms
+/// of the generics of a streamlet.
+fn add(a: &str, b: &str) -> String {
+    let ((a, m), (b, n)) = (split(a), split(b));
+    let expr = match (a, b) {
+        (Some(a), Some(b)) => Some(format!("{} + {}", a, b)),
+        (a, b) => a.or(b).map(str::to_string),
+    };
+    join(expr.as_deref(), m + n)
+}
+
+/// Returns the index of the highest bit of a vector of a width.
+fn high(width: &str) -> String {
+    match split(width) {
+        (expr, 0) => join(expr, 0) + " - 1",
+        (expr, number) => join(expr, number - 1),
+    }
+}
+
+/// Returns the VHDL type of a signal with an optional vector width.
+fn signal_type(width: Option<&String>) -> String {
+    match width {
+        Some(width) => format!("std_logic_vector({} downto 0)", high(width)),
+        None => "std_logic".to_string(),
+    }
+}
+
+/// Returns the name of the signal connecting a port of the canonical
+/// component.
+fn com(port: &str) -> String {
+    cat!(port, CANON_SUFFIX.unwrap())
+}
+
+impl StreamPorts {
+    /// Returns the signals of the transfers of the stream, with the range of
+    /// the bits they occupy in the buffer of the stream, given the widths of
+    /// the vector ports of the streamlet.
+    fn slices(&self, widths: &HashMap<String, String>) -> Vec<(&str, String)> {
+        let mut low = "0".to_string();
+        self.signals
+            .iter()
+            .map(|(_, port, _)| {
+                let (range, next) = match widths.get(port) {
+                    Some(width) => {
+                        let next = add(&low, width);
+                        (format!("({} downto {})", high(&next), low), next)
+                    }
+                    None => (format!("({})", low), add(&low, "1")),
+                };
+                low = next;
+                (port.as_str(), range)
+            })
+            .collect()
+    }
+
+    /// The width of the buffer of the stream.
+    fn buffer_width(&self, widths: &HashMap<String, String>) -> String {
+        self.signals
+            .iter()
+            .fold("0".to_string(), |sum, (_, port, _)| {
+                add(&sum, widths.get(port).map_or("1", String::as_str))
+            })
+    }
+
+    /// Declares the signals of a stream.
+    fn declarations(&self, widths: &HashMap<String, String>) -> String {
+        let mut result = format!(
+            "  signal {} : std_logic;\n  signal {} : std_logic;\n",
+            com(&self.valid),
+            com(&self.ready)
+        );
+        for (_, port, _) in &self.signals {
+            result.push_str(&format!(
+                "  signal {} : {};\n",
+                com(port),
+                signal_type(widths.get(port))
+            ));
+        }
+        if self.input {
+            if !self.signals.is_empty() {
+                result.push_str(&format!(
+                    "  type {0}_buffer_type is array (0 to CREDITS - 1) of std_logic_vector({1} downto 0);\n  signal {0}_buffer : {0}_buffer_type;\n  signal {0}_head : natural range 0 to CREDITS - 1;\n  signal {0}_tail : natural range 0 to CREDITS - 1;\n",
+                    self.name,
+                    high(&self.buffer_width(widths))
+                ));
+            }
+            result.push_str(&format!(
+                "  signal {}_count : natural range 0 to CREDITS;\n",
+                self.name
+            ));
+        } else {
+            result.push_str(&format!(
+                "  signal {}_credits : natural range 0 to CREDITS;\n",
+                self.name
+            ));
+        }
+        result
+    }
+
+    /// The statements that buffer the transfers of a stream into the
+    /// streamlet, and return its credits.
+    fn receive(&self, credit: &str, widths: &HashMap<String, String>) -> String {
+        let buffered = !self.signals.is_empty();
+        let mut result = format!(
+            "\n  -- Transfers of {0} are buffered, and a credit is returned for every\n  -- transfer that is handed to the component.\n  {1} <= '1' when {0}_count > 0 else '0';\n",
+            self.name,
+            com(&self.valid)
+        );
+        for (port, range) in self.slices(widths) {
+            result.push_str(&format!(
+                "  {} <= {}_buffer({}_head){};\n",
+                com(port),
+                self.name,
+                self.name,
+                range
+            ));
+        }
+        result.push_str(&format!(
+            "  {0}_flow: process (clk)\n    variable push : boolean;\n    variable pop : boolean;\n  begin\n    if rising_edge(clk) then\n      push := {1} = '1';\n      pop := {0}_count > 0 and {2} = '1';\n      assert not (push and not pop and {0}_count = CREDITS)\n        report \"{0}: transfer without credit\" severity error;\n      if push then\n",
+            self.name,
+            self.valid,
+            com(&self.ready)
+        ));
+        for (port, range) in self.slices(widths) {
+            result.push_str(&format!(
+                "        {}_buffer({}_tail){} <= {};\n",
+                self.name, self.name, range, port
+            ));
+        }
+        if buffered {
+            result.push_str(&format!(
+                "        if {0}_tail = CREDITS - 1 then\n          {0}_tail <= 0;\n        else\n          {0}_tail <= {0}_tail + 1;\n        end if;\n      end if;\n      if pop then\n        if {0}_head = CREDITS - 1 then\n          {0}_head <= 0;\n        else\n          {0}_head <= {0}_head + 1;\n        end if;\n",
+                self.name
+            ));
+        } else {
+            result.push_str("        null;\n");
+        }
+        result.push_str(&format!(
+            "      end if;\n      if push and not pop then\n        {0}_count <= {0}_count + 1;\n      elsif pop and not push then\n        {0}_count <= {0}_count - 1;\n      end if;\n      if pop then\n        {1} <= '1';\n      else\n        {1} <= '0';\n      end if;\n      if rst = '1' then\n",
+            self.name, credit
+        ));
+        if buffered {
+            result.push_str(&format!(
+                "        {0}_head <= 0;\n        {0}_tail <= 0;\n",
+                self.name
+            ));
+        }
+        result.push_str(&format!(
+            "        {}_count <= 0;\n        {} <= '0';\n      end if;\n    end if;\n  end process;\n",
+            self.name, credit
+        ));
+        result
+    }
+
+    /// The statements that send the transfers of a stream out of the
+    /// streamlet while credits are left.
+    fn send(&self, credit: &str) -> String {
+        let mut result = format!(
+            "\n  -- Transfers of {0} are sent while credits are left, which are\n  -- returned by the sink.\n  {1} <= '1' when {0}_credits > 0 else '0';\n  {0}_flow: process (clk)\n    variable send : boolean;\n    variable returned : boolean;\n  begin\n    if rising_edge(clk) then\n      send := {2} = '1' and {0}_credits > 0;\n      returned := {3} = '1';\n      if send then\n        {4} <= '1';\n",
+            self.name,
+            com(&self.ready),
+            com(&self.valid),
+            credit,
+            self.valid
+        );
+        for (_, port, _) in &self.signals {
+            result.push_str(&format!("        {} <= {};\n", port, com(port)));
+        }
+        result.push_str(&format!(
+            "      else\n        {1} <= '0';\n      end if;\n      if send and not returned then\n        {0}_credits <= {0}_credits - 1;\n      elsif returned and not send then\n        {0}_credits <= {0}_credits + 1;\n      end if;\n      if rst = '1' then\n        {1} <= '0';\n        {0}_credits <= CREDITS;\n      end if;\n    end if;\n  end process;\n",
+            self.name, self.valid
+        ));
+        result
+    }
+}
+
+/// Generate the credit wrapper of the canonical component of a streamlet of
+/// a library, see the [module documentation](self).
+///
+/// The wrapper is named after the streamlet with the [CREDIT_SUFFIX], and
+/// instantiates the canonical component with ports named according to a
+/// naming policy. The parameters of the streamlet are generics of the
+/// wrapper, which are passed on to the canonical component.
+pub fn generate_credit_wrapper(
+    library: &str,
+    streamlet: &Streamlet,
+    policy: &NamingPolicy,
+) -> Result<String> {
+    let component = streamlet.canonical_with(CANON_SUFFIX, policy);
+    let ports = component.ports();
+    let parameters = component.parameters();
+    if parameters.iter().any(|p| p.name == "CREDITS") {
+        return Err(Error::BackEndError(format!(
+            "Parameter CREDITS of streamlet {}.{} collides with the generic of its credit wrapper",
+            library,
+            streamlet.identifier()
+        )));
+    }
+    // The widths of the vector ports, in terms of the generics where they
+    // depend on them.
+    let widths = ports
+        .iter()
+        .filter_map(|port| match port.typ() {
+            Type::BitVec { width } => Some((
+                port.identifier().to_string(),
+                port.width().map_or(width.to_string(), str::to_string),
+            )),
+            _ => None,
+        })
+        .collect::<HashMap<_, _>>();
+    let streams = stream_ports(streamlet, policy);
+    let entity = cat!(streamlet.identifier(), CREDIT_SUFFIX);
+    // The credit port of every stream, which replaces its ready port.
+    let credit = |stream: &StreamPorts| {
+        let mode = match stream.input {
+            true => design::Mode::Out,
+            false => design::Mode::In,
+        };
+        policy.port(policy.join(&[&stream.name, "credit"]), mode)
+    };
+
+    let mut result = format!(
+        "library ieee;\nuse ieee.std_logic_1164.all;\n\nlibrary work;\nuse work.{}.all;\n\n",
+        library
+    );
+    let generics = parameters
+        .iter()
+        .map(|p| p.declare())
+        .chain(std::iter::once(Ok("CREDITS : positive := 4".to_string())))
+        .map(|generic| Ok(format!("    {}", generic?)))
+        .collect::<Result<Vec<_>>>()?;
+    result.push_str(&format!(
+        "-- Credit-based flow control wrapper of streamlet {}.{}.\nentity {} is\n  generic (\n{}\n  );\n  port (\n",
+        library,
+        streamlet.identifier(),
+        entity,
+        generics.join(";\n")
+    ));
+    let declarations = ports
+        .iter()
+        .map(|port| {
+            let identifier = match streams.iter().find(|s| s.ready == port.identifier()) {
+                Some(stream) => credit(stream),
+                None => port.identifier().to_string(),
+            };
+            let mode = match port.mode() {
+                PortMode::In => "in",
+                PortMode::Out => "out",
+            };
+            let typ = signal_type(widths.get(port.identifier()));
+            format!("    {} : {} {}", identifier, mode, typ)
+        })
+        .collect::<Vec<_>>();
+    result.push_str(&declarations.join(";\n"));
+    result.push_str(&format!("\n  );\nend {};\n\n", entity));
+
+    result.push_str(&format!("architecture behavioral of {} is\n", entity));
+    for stream in &streams {
+        result.push_str(&stream.declarations(&widths));
+    }
+    result.push_str("begin\n");
+
+    // Ports of streams are connected through the flow control logic, other
+    // ports are connected directly.
+    let stream_port = |identifier: &str| {
+        streams.iter().any(|s| {
+            s.valid == identifier
+                || s.ready == identifier
+                || s.signals.iter().any(|(_, port, _)| port == identifier)
+        })
+    };
+    result.push_str(&format!(
+        "  {}: {} ",
+        CANON_SUFFIX.unwrap(),
+        cat!(streamlet.identifier(), CANON_SUFFIX.unwrap())
+    ));
+    if !parameters.is_empty() {
+        result.push_str(&format!(
+            "generic map(\n{}\n  ) ",
+            parameters
+                .iter()
+                .map(|p| format!("    {0} => {0}", p.name))
+                .collect::<Vec<_>>()
+                .join(",\n")
+        ));
+    }
+    result.push_str("port map(\n");
+    result.push_str(
+        &ports
+            .iter()
+            .map(|port| match stream_port(port.identifier()) {
+                true => format!("    {} => {}", port.identifier(), com(port.identifier())),
+                false => format!("    {0} => {0}", port.identifier()),
+            })
+            .collect::<Vec<_>>()
+            .join(",\n"),
+    );
+    result.push_str("\n  );\n");
+
+    for stream in &streams {
+        if stream.input {
+            result.push_str(&stream.receive(&credit(stream), &widths));
+        } else {
+            result.push_str(&stream.send(&credit(stream)));
+        }
+    }
+    result.push_str("end architecture behavioral;\n");
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::nom::streamlet;
+
+    use super::*;
+
+    #[test]
+    fn credit_wrapper() -> Result<()> {
+        let (_, s) = streamlet(
+            "Streamlet top (a : in Stream<Bits<8>, d=1>, b : out Stream<Bits<4>>, c : in Bits<2>)",
+        )
+        .unwrap();
+        let cr = generate_credit_wrapper("lib", &s, &NamingPolicy::default())?;
+        assert!(cr.contains("entity top_cr is\n  generic (\n    CREDITS : positive := 4\n  );"));
+        // Ready ports are replaced by credit ports
+        assert!(cr.contains("    a_valid : in std_logic;\n    a_credit : out std_logic;\n"));
+        assert!(cr.contains("    b_valid : out std_logic;\n    b_credit : in std_logic;\n"));
+        assert!(!cr.contains("a_ready :"));
+        assert!(cr.contains("  com: top_com port map(\n    clk => clk,\n    rst => rst,\n    a_valid => a_valid_com,\n    a_ready => a_ready_com,\n"));
+        assert!(cr.contains("    c => c,\n"));
+        // Transfers into the streamlet are buffered
+        assert!(cr.contains(
+            "  type a_buffer_type is array (0 to CREDITS - 1) of std_logic_vector(9 downto 0);"
+        ));
+        assert!(cr.contains("  a_data_com <= a_buffer(a_head)(7 downto 0);\n  a_last_com <= a_buffer(a_head)(8 downto 8);\n  a_strb_com <= a_buffer(a_head)(9 downto 9);\n"));
+        assert!(cr.contains("        a_buffer(a_tail)(7 downto 0) <= a_data;\n"));
+        assert!(cr.contains("      if pop then\n        a_credit <= '1';\n"));
+        // Transfers out of the streamlet cost credits
+        assert!(cr.contains("  b_ready_com <= '1' when b_credits > 0 else '0';\n"));
+        assert!(cr.contains("      returned := b_credit = '1';\n"));
+        assert!(cr.contains("        b_data <= b_data_com;\n"));
+        assert!(cr.contains("        b_credits <= CREDITS;\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn credit_wrapper_generics() -> Result<()> {
+        let (_, s) = streamlet(
+            "Streamlet top <W = 8> (a : in Stream<Bits<W>, d=1>, b : out Stream<Bits<4>, t=2>)",
+        )
+        .unwrap();
+        let cr = generate_credit_wrapper("lib", &s, &NamingPolicy::default())?;
+        // The generics of the streamlet are declared and mapped through
+        assert!(
+            cr.contains("  generic (\n    W : natural := 8;\n    CREDITS : positive := 4\n  );")
+        );
+        assert!(cr.contains("  com: top_com generic map(\n    W => W\n  ) port map(\n"));
+        // Widths follow the generics rather than their default values, so the
+        // wrapper can be instantiated with W other than 8
+        assert!(cr.contains("    a_data : in std_logic_vector(W - 1 downto 0);\n"));
+        assert!(cr.contains("  signal a_data_com : std_logic_vector(W - 1 downto 0);\n"));
+        assert!(!cr.contains("(7 downto 0) <= a_data"));
+        assert!(cr.contains(
+            "  type a_buffer_type is array (0 to CREDITS - 1) of std_logic_vector(W + 1 downto 0);"
+        ));
+        assert!(cr.contains("  a_last_com <= a_buffer(a_head)(W downto W);\n"));
+        assert!(cr.contains("        a_buffer(a_tail)(W + 1 downto W + 1) <= a_strb;\n"));
+        // Ports that do not depend on the generics keep their widths
+        assert!(cr.contains("    b_data : out std_logic_vector(7 downto 0)\n"));
+        Ok(())
+    }
+}
@@ -2,14 +2,20 @@
 
 use std::collections::HashMap;
 
+use indexmap::IndexMap;
+
 use crate::error::Error::BackEndError;
-use crate::generator::common::{Array, Component, Mode, Package, Port, Record, Type};
+use crate::generator::common::{
+    Array, Component, Enumeration, Mode, Package, Parameter, Port, Record, Type,
+};
 use crate::generator::vhdl::{
-    Analyze, Declare, DeclareType, DeclareUsings, Split, Usings, VHDLIdentifier,
+    split_ports, Analyze, Declare, DeclareType, DeclareUsings, Split, Usings, VHDLIdentifier,
 };
+use crate::stdlib::common::architecture::object::ObjectType;
 use crate::traits::Identify;
 use crate::{cat, Document, Name, Result};
 
+use super::conversion::{convertible_types, declare_conversions, define_conversions};
 use super::ListUsings;
 
 impl VHDLIdentifier for Mode {
@@ -36,6 +42,10 @@ fn declare_rec(rec: &Record) -> Result<String> {
                 children.push_str(nested.declare(false)?.clone().as_str());
                 children.push_str("\n\n");
             }
+            Type::Enum(nested) => {
+                children.push_str(nested.declare(false)?.as_str());
+                children.push_str("\n\n");
+            }
             _ => (),
         }
 
@@ -91,6 +101,11 @@ fn declare_arr(arr: &Array) -> Result<String> {
             children.push_str("\n\n");
             this.push_str(arr.vhdl_identifier()?.as_str());
         }
+        Type::Enum(enumeration) => {
+            children.push_str(enumeration.declare(false)?.as_str());
+            children.push_str("\n\n");
+            this.push_str(enumeration.vhdl_identifier()?.as_str());
+        }
     }
 
     this.push_str(";");
@@ -143,6 +158,16 @@ impl DeclareType for Array {
     }
 }
 
+impl DeclareType for Enumeration {
+    fn declare(&self, _is_root_type: bool) -> Result<String> {
+        Ok(format!(
+            "type {} is ({});",
+            self.vhdl_identifier()?,
+            self.literals().join(", ")
+        ))
+    }
+}
+
 impl DeclareType for Type {
     fn declare(&self, is_root_type: bool) -> Result<String> {
         match self {
@@ -160,6 +185,7 @@ impl DeclareType for Type {
             Type::Record(rec) => rec.declare(is_root_type),
             Type::Union(rec) => rec.declare(is_root_type),
             Type::Array(arr) => arr.declare(is_root_type),
+            Type::Enum(enumeration) => enumeration.declare(is_root_type),
         }
     }
 }
@@ -172,6 +198,7 @@ impl VHDLIdentifier for Type {
             Type::Record(rec) => rec.vhdl_identifier(),
             Type::Union(rec) => rec.vhdl_identifier(),
             Type::Array(arr) => arr.vhdl_identifier(),
+            Type::Enum(enumeration) => enumeration.vhdl_identifier(),
             _ => self.declare(true),
         }
     }
@@ -189,6 +216,12 @@ impl VHDLIdentifier for Array {
     }
 }
 
+impl VHDLIdentifier for Enumeration {
+    fn vhdl_identifier(&self) -> Result<String> {
+        Ok(cat!(self.identifier().to_string(), "type"))
+    }
+}
+
 impl Analyze for Type {
     fn list_nested_types(&self) -> Vec<Type> {
         match self {
@@ -207,6 +240,7 @@ impl Analyze for Type {
                 result.extend(arr.typ().list_nested_types().into_iter());
                 result
             }
+            Type::Enum(_) => vec![self.clone()],
             _ => vec![],
         }
     }
@@ -220,12 +254,16 @@ impl Declare for Port {
             result.push_str(doc.replace("\n", "\n    --").as_str());
             result.push_str("\n    ");
         }
+        let typ = match (self.typ(), self.width()) {
+            (Type::BitVec { .. }, Some(width)) => format!("std_logic_vector({}-1 downto 0)", width),
+            (typ, _) => typ.vhdl_identifier()?,
+        };
         result.push_str(
             format!(
                 "{} : {} {}",
                 self.identifier(),
                 self.mode().vhdl_identifier()?,
-                self.typ().vhdl_identifier()?
+                typ
             )
             .as_str(),
         );
@@ -233,6 +271,32 @@ impl Declare for Port {
     }
 }
 
+impl Declare for Parameter {
+    fn declare(&self) -> Result<String> {
+        let mut result = format!("{} : {}", self.name, self.typ.declare(false)?);
+        if let Some(default) = self.default {
+            result.push_str(&format!(" := {}", default));
+        }
+        Ok(result)
+    }
+}
+
+impl Declare for Vec<Parameter> {
+    fn declare(&self) -> Result<String> {
+        let mut result = String::new();
+        if !self.is_empty() {
+            result.push_str("  generic(\n");
+            let parameters = self
+                .iter()
+                .map(|p| Ok(format!("    {}", p.declare()?)))
+                .collect::<Result<Vec<_>>>()?;
+            result.push_str(&parameters.join(";\n"));
+            result.push_str("\n  );\n");
+        }
+        Ok(result)
+    }
+}
+
 impl VHDLIdentifier for Port {
     fn vhdl_identifier(&self) -> Result<String> {
         Ok(self.identifier().to_string())
@@ -243,37 +307,15 @@ impl Declare for Vec<Port> {
     fn declare(&self) -> Result<String> {
         let mut result = String::new();
         if !self.is_empty() {
-            let mut ports = self.iter().peekable();
+            // Ports with reversed fields are split up, because VHDL does not
+            // support bundles of wires with opposite directions.
+            let ports = split_ports(self)
+                .iter()
+                .map(|p| Ok(format!("    {}", p.declare()?)))
+                .collect::<Result<Vec<_>>>()?;
             result.push_str("  port(\n");
-            while let Some(p) = ports.next() {
-                result.push_str("    ");
-                // If the port type has reversed fields, we need to split it up because VHDL.
-                if p.has_reversed() {
-                    let (dn, up) = p.split();
-                    match dn {
-                        None => unreachable!(),
-                        Some(dn_port) => {
-                            result.push_str(dn_port.declare()?.as_str());
-                            result.push_str(";\n");
-                        }
-                    };
-                    match up {
-                        None => unreachable!(),
-                        Some(up_port) => {
-                            result.push_str("    ");
-                            result.push_str(up_port.declare()?.as_str());
-                        }
-                    };
-                } else {
-                    result.push_str(p.declare()?.as_str());
-                }
-
-                if ports.peek().is_some() {
-                    result.push_str(";\n");
-                } else {
-                    result.push('\n');
-                }
-            }
+            result.push_str(&ports.join(";\n"));
+            result.push('\n');
             result.push_str("  );\n");
         }
         Ok(result)
@@ -289,6 +331,7 @@ impl Declare for Component {
             result.push('\n');
         }
         result.push_str(format!("component {}\n", self.identifier()).as_str());
+        result.push_str(self.parameters().declare()?.as_str());
         result.push_str(self.ports().declare()?.as_str());
         result.push_str("end component;");
         Ok(result)
@@ -300,7 +343,9 @@ impl Analyze for Component {
         let mut result: Vec<Type> = vec![];
         for p in self.ports().iter() {
             match p.typ() {
-                Type::Record(_) | Type::Union(_) | Type::Array(_) => result.push(p.typ()),
+                Type::Record(_) | Type::Union(_) | Type::Array(_) | Type::Enum(_) => {
+                    result.push(p.typ())
+                }
                 _ => (),
             }
         }
@@ -311,6 +356,11 @@ impl Analyze for Component {
 impl Declare for Package {
     fn declare(&self) -> Result<String> {
         let mut result = String::new();
+        if let Some(doc) = &self.doc {
+            result.push_str("--");
+            result.push_str(doc.replace("\n", "\n--").as_str());
+            result.push_str("\n\n");
+        }
         result.push_str(self.declare_usings()?.as_str());
         result.push_str(format!("package {} is\n\n", self.identifier).as_str());
 
@@ -319,6 +369,8 @@ impl Declare for Package {
         // Use a set to remember which type identifiers we've already used, so we don't declare
         // them twice, and produce an error otherwise.
         let mut type_ids = HashMap::<String, Type>::new();
+        // The types (as declared, i.e. split) for which conversion functions are generated
+        let mut conversions = IndexMap::<String, ObjectType>::new();
         for c in &self.components {
             let comp_nested = c.list_nested_types();
             for t in comp_nested.iter() {
@@ -326,6 +378,10 @@ impl Declare for Package {
                     None => {
                         type_ids.insert(t.vhdl_identifier()?, t.clone());
                         result.push_str(format!("{}\n\n", t.declare(true)?).as_str());
+                        let (dn, up) = ObjectType::try_from_splittable(t.clone())?;
+                        for typ in dn.iter().chain(up.iter()) {
+                            convertible_types(typ, &mut conversions);
+                        }
                     }
                     Some(already_defined_type) => {
                         if t != already_defined_type {
@@ -341,8 +397,19 @@ impl Declare for Package {
             }
             result.push_str(format!("{}\n\n", c.declare()?).as_str());
         }
+        for typ in conversions.values() {
+            result.push_str(format!("{}\n\n", declare_conversions(typ)).as_str());
+        }
         result.push_str(format!("end package {};", self.identifier).as_str());
 
+        if !conversions.is_empty() {
+            result.push_str(format!("\n\npackage body {} is\n\n", self.identifier).as_str());
+            for typ in conversions.values() {
+                result.push_str(format!("{}\n\n", define_conversions(typ)?).as_str());
+            }
+            result.push_str(format!("end package body {};", self.identifier).as_str());
+        }
+
         Ok(result)
     }
 }
@@ -364,6 +431,7 @@ impl ListUsings for Package {
                 Type::Record(rec) => rec.fields().any(|field| uses_std_logic(field.typ())),
                 Type::Union(rec) => rec.fields().any(|field| uses_std_logic(field.typ())),
                 Type::Array(arr) => uses_std_logic(arr.typ()),
+                Type::Enum(_) => false,
             }
         }
 
@@ -476,6 +544,7 @@ end component;"
     fn package_usings_decl() {
         let p = Package {
             identifier: "test".to_string(),
+            doc: None,
             components: vec![test_comp()],
         };
         assert_eq!(
@@ -491,6 +560,7 @@ use ieee.std_logic_1164.all;
     fn package_decl() {
         let p = Package {
             identifier: "test".to_string(),
+            doc: None,
             components: vec![test_comp()],
         };
         assert_eq!(
@@ -3,27 +3,43 @@
 //! This module contains functionality to convert hardware defined in the common hardware
 //! representation to VHDL source files.
 
-use std::collections::HashSet;
+use std::fmt;
 use std::path::Path;
 use std::str::FromStr;
 
-use indexmap::IndexMap;
-use log::debug;
+use indexmap::{IndexMap, IndexSet};
 #[cfg(feature = "cli")]
 use structopt::StructOpt;
+use tracing::debug_span;
 
 use crate::cat;
 use crate::design::implementation::composer::GenericComponent;
+use crate::design::implementation::Implementation;
 use crate::design::Project;
-use crate::generator::common::convert::Packify;
+use crate::generator::common::convert::{check_port_names, Packify};
 use crate::generator::common::*;
-use crate::generator::GenerateProject;
-use crate::stdlib::utils::fancy_wrapper::generate_fancy_wrapper;
-use crate::traits::Identify;
-use crate::Name;
-use crate::{Error, Result, Reversed};
-
+use crate::generator::filter::Filter;
+use crate::generator::header::HeaderTemplate;
+use crate::generator::{render_jobs, GenerateProject, GeneratedFile, RenderJob};
+use crate::stdlib::utils::fancy_wrapper::generate_fancy_wrapper_with;
+use crate::stdlib::utils::structure::generate_structure_with;
+use crate::traits::{Document, Identify};
+use crate::{Error, Result, ResultExt, Reversed};
+use crate::{Flattening, Name, NamingPolicy};
+use credit::generate_credit_wrapper;
+use readme::readme;
+use style::Style;
+use support::support_files;
+use testbench::generate_testbench;
+
+mod conversion;
+pub mod credit;
 mod impls;
+mod readme;
+mod streams;
+pub mod style;
+mod support;
+pub mod testbench;
 
 /// Generate trait for generic VHDL declarations.
 pub trait Declare {
@@ -44,8 +60,10 @@ pub trait DeclareLibrary {
 }
 
 /// A list of VHDL usings, indexed by library
+///
+/// Libraries and their usings are declared in the order they were added.
 #[derive(Debug, Clone)]
-pub struct Usings(IndexMap<Name, HashSet<String>>);
+pub struct Usings(IndexMap<Name, IndexSet<String>>);
 
 impl Usings {
     pub fn new_empty() -> Usings {
@@ -56,20 +74,21 @@ impl Usings {
     ///
     /// If the set did have this value present, `false` is returned.
     pub fn add_using(&mut self, library: Name, using: impl Into<String>) -> bool {
-        self.0
-            .entry(library)
-            .or_insert(HashSet::new())
-            .insert(using.into())
+        self.0.entry(library).or_default().insert(using.into())
     }
 
-    pub fn usings(&self) -> &IndexMap<Name, HashSet<String>> {
+    pub fn usings(&self) -> &IndexMap<Name, IndexSet<String>> {
         &self.0
     }
 
-    /// Combine two usings
+    /// Combine two usings, adding the usings of the other list to those of
+    /// this list.
     pub fn combine(&mut self, other: &Usings) {
-        for (library, using) in other.usings() {
-            self.0.insert(library.clone(), using.clone());
+        for (library, usings) in other.usings() {
+            self.0
+                .entry(library.clone())
+                .or_default()
+                .extend(usings.iter().cloned());
         }
     }
 }
@@ -130,13 +149,54 @@ impl FromStr for AbstractionLevel {
 
     fn from_str(s: &str) -> Result<Self> {
         match s {
-            "canon" => Ok(AbstractionLevel::Canonical),
+            "canon" | "canonical" => Ok(AbstractionLevel::Canonical),
             "fancy" => Ok(AbstractionLevel::Fancy),
             _ => Err(Error::InvalidArgument(s.to_string())),
         }
     }
 }
 
+impl fmt::Display for AbstractionLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AbstractionLevel::Canonical => write!(f, "canonical"),
+            AbstractionLevel::Fancy => write!(f, "fancy"),
+        }
+    }
+}
+
+/// Flow control of the streams at the boundaries of generated components.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FlowControl {
+    /// Streams use the valid/ready handshakes of the canonical components.
+    #[default]
+    Handshake,
+    /// Streams are wrapped with credit-based flow control, see the
+    /// [credit] module.
+    Credit,
+}
+
+impl FromStr for FlowControl {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "handshake" => Ok(FlowControl::Handshake),
+            "credit" => Ok(FlowControl::Credit),
+            _ => Err(Error::InvalidArgument(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for FlowControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FlowControl::Handshake => write!(f, "handshake"),
+            FlowControl::Credit => write!(f, "credit"),
+        }
+    }
+}
+
 /// VHDL back-end configuration parameters.
 #[derive(Debug)]
 #[cfg_attr(feature = "cli", derive(StructOpt))]
@@ -146,7 +206,7 @@ pub struct VHDLConfig {
     ///   canonical: generates the canonical Tydi representation of streamlets as components in a
     ///              package.
     ///   fancy: generates the canonical components that wrap a more user-friendly version for the
-    ///          user to implement.
+    ///          user to implement. Not supported for streamlets with parameters.
     #[cfg_attr(feature = "cli", structopt(short, long))]
     abstraction: Option<AbstractionLevel>,
 
@@ -154,12 +214,232 @@ pub struct VHDLConfig {
     /// generated files are named <name>.gen.vhd.
     #[cfg_attr(feature = "cli", structopt(short, long))]
     suffix: Option<String>,
+
+    /// Separator between the names of nested fields in port names.
+    /// Default = "__".
+    #[cfg_attr(feature = "cli", structopt(long))]
+    path_separator: Option<String>,
+
+    /// Scheme to flatten the names of nested fields in port names.
+    /// Possible options: separated, camel-case.
+    ///   separated: joins the names with the path separator, e.g. a__b.
+    ///   camel-case: capitalizes and concatenates the names, e.g. aB.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    flattening: Option<Flattening>,
+
+    /// Emit the vhlib support packages that generated files depend on, e.g.
+    /// Stream_pkg, into a support directory, such that the output compiles
+    /// standalone.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    support: bool,
+
+    /// Emit behavioral simulation models of the support components instead
+    /// of their implementations, which are not synthesizable. Implies
+    /// --support.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    simulation_models: bool,
+
+    /// Write a README.md next to the generated files, that summarizes the
+    /// components of every library, their ports, and these settings.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    readme: bool,
+
+    /// Generate a self-checking testbench for every streamlet with a
+    /// structural implementation, that drives and checks its streams using
+    /// transfer vector files. Testbenches require VHDL-2008.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    testbench: bool,
+
+    /// Flow control of the streams at the boundaries of streamlets.
+    /// Possible options: handshake, credit. Default = handshake.
+    ///   handshake: streams use valid/ready handshakes.
+    ///   credit: additionally generates a wrapper of every canonical component, that replaces the
+    ///           ready signal of every stream by a credit return path, for long-distance links.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    flow_control: Option<FlowControl>,
+
+    /// Number of spaces per indentation level of generated files.
+    /// Default = 2.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    indent: Option<usize>,
+
+    /// Indent generated files with tabs rather than spaces.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    tabs: bool,
+
+    /// Align the colons of consecutive declarations and the arrows of
+    /// consecutive associations in generated files.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    align: bool,
+
+    /// Maximum length of lines in generated files. Lines are not aligned if
+    /// that would make them exceed it.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    max_line_length: Option<usize>,
 }
 
 impl VHDLConfig {
+    /// Construct a configuration. Unset options use the back-end defaults.
+    pub fn new(abstraction: Option<AbstractionLevel>, suffix: Option<String>) -> Self {
+        VHDLConfig {
+            abstraction,
+            suffix,
+            path_separator: None,
+            flattening: None,
+            support: false,
+            simulation_models: false,
+            readme: false,
+            testbench: false,
+            flow_control: None,
+            indent: None,
+            tabs: false,
+            align: false,
+            max_line_length: None,
+        }
+    }
+
+    /// Returns this configuration with the given separator between the names
+    /// of nested fields in port names.
+    pub fn with_path_separator(mut self, separator: impl Into<String>) -> Self {
+        self.path_separator = Some(separator.into());
+        self
+    }
+
+    /// Returns this configuration with the given scheme to flatten the names
+    /// of nested fields in port names.
+    pub fn with_flattening(mut self, flattening: Flattening) -> Self {
+        self.flattening = Some(flattening);
+        self
+    }
+
+    /// Returns this configuration, emitting the support packages that
+    /// generated files depend on if `support` is true.
+    pub fn with_support(mut self, support: bool) -> Self {
+        self.support = support;
+        self
+    }
+
+    /// Returns this configuration, emitting behavioral simulation models of
+    /// the support components if `simulation_models` is true.
+    pub fn with_simulation_models(mut self, simulation_models: bool) -> Self {
+        self.simulation_models = simulation_models;
+        self
+    }
+
+    /// Returns this configuration, writing a summary of the generated files
+    /// if `readme` is true.
+    pub fn with_readme(mut self, readme: bool) -> Self {
+        self.readme = readme;
+        self
+    }
+
+    /// Returns this configuration, generating testbenches for structural
+    /// streamlets if `testbench` is true. Testbenches require VHDL-2008, see
+    /// the [testbench] module.
+    pub fn with_testbench(mut self, testbench: bool) -> Self {
+        self.testbench = testbench;
+        self
+    }
+
+    /// Returns this configuration with the given flow control of the streams
+    /// at the boundaries of streamlets.
+    pub fn with_flow_control(mut self, flow_control: FlowControl) -> Self {
+        self.flow_control = Some(flow_control);
+        self
+    }
+
+    /// Returns this configuration with the code style of generated files.
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.indent = Some(style.indent());
+        self.tabs = style.tabs();
+        self.align = style.align();
+        self.max_line_length = style.max_line_length();
+        self
+    }
+
     pub fn abstraction(&self) -> AbstractionLevel {
         self.abstraction.unwrap_or_default()
     }
+
+    /// Returns the suffix of generated files, if any.
+    pub fn suffix(&self) -> Option<&str> {
+        self.suffix.as_deref()
+    }
+
+    /// Returns true if the support packages that generated files depend on
+    /// are emitted.
+    pub fn support(&self) -> bool {
+        self.support
+    }
+
+    /// Returns true if behavioral simulation models of the support
+    /// components are emitted instead of their implementations.
+    pub fn simulation_models(&self) -> bool {
+        self.simulation_models
+    }
+
+    /// Returns true if a summary of the generated files is written.
+    pub fn readme(&self) -> bool {
+        self.readme
+    }
+
+    /// Returns true if testbenches are generated for structural streamlets.
+    pub fn testbench(&self) -> bool {
+        self.testbench
+    }
+
+    /// Returns the flow control of the streams at the boundaries of
+    /// streamlets.
+    pub fn flow_control(&self) -> FlowControl {
+        self.flow_control.unwrap_or_default()
+    }
+
+    /// Returns the code style of generated files.
+    pub fn style(&self) -> Style {
+        let mut style = Style::default()
+            .with_tabs(self.tabs)
+            .with_alignment(self.align);
+        if let Some(indent) = self.indent {
+            style = style.with_indent(indent);
+        }
+        if let Some(max_line_length) = self.max_line_length {
+            style = style.with_max_line_length(max_line_length);
+        }
+        style
+    }
+
+    /// Returns the naming policy of ports, derived from the path separator
+    /// and flattening scheme.
+    pub fn naming(&self) -> NamingPolicy {
+        let mut policy = NamingPolicy::default();
+        if let Some(separator) = &self.path_separator {
+            policy = policy.with_path_separator(separator.clone());
+        }
+        if let Some(flattening) = self.flattening {
+            policy = policy.with_flattening(flattening);
+        }
+        policy
+    }
+
+    /// Returns this configuration with the options that are not set taken
+    /// from another configuration.
+    pub fn or(self, defaults: VHDLConfig) -> Self {
+        VHDLConfig {
+            abstraction: self.abstraction.or(defaults.abstraction),
+            suffix: self.suffix.or(defaults.suffix),
+            path_separator: self.path_separator.or(defaults.path_separator),
+            flattening: self.flattening.or(defaults.flattening),
+            support: self.support || defaults.support,
+            simulation_models: self.simulation_models || defaults.simulation_models,
+            readme: self.readme || defaults.readme,
+            testbench: self.testbench || defaults.testbench,
+            flow_control: self.flow_control.or(defaults.flow_control),
+            indent: self.indent.or(defaults.indent),
+            tabs: self.tabs || defaults.tabs,
+            align: self.align || defaults.align,
+            max_line_length: self.max_line_length.or(defaults.max_line_length),
+        }
+    }
 }
 
 impl Default for VHDLConfig {
@@ -167,6 +447,17 @@ impl Default for VHDLConfig {
         VHDLConfig {
             suffix: Some("gen".to_string()),
             abstraction: Some(AbstractionLevel::Fancy),
+            path_separator: None,
+            flattening: None,
+            support: false,
+            simulation_models: false,
+            readme: false,
+            testbench: false,
+            flow_control: None,
+            indent: None,
+            tabs: false,
+            align: false,
+            max_line_length: None,
         }
     }
 }
@@ -176,54 +467,239 @@ impl Default for VHDLConfig {
 pub struct VHDLBackEnd {
     /// Configuration for the VHDL back-end.
     config: VHDLConfig,
+    /// The libraries and streamlets to generate files for.
+    filter: Filter,
+    /// The header of generated files, if any.
+    header: Option<HeaderTemplate>,
 }
 
 impl VHDLBackEnd {
     pub fn config(&self) -> &VHDLConfig {
         &self.config
     }
+
+    /// Returns this back-end, only generating the files of the libraries and
+    /// streamlets selected by a filter.
+    ///
+    /// The package of a library is generated if the library is selected, and
+    /// declares all streamlets of the library.
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Returns the filter of the libraries and streamlets to generate files
+    /// for.
+    pub fn filter(&self) -> &Filter {
+        &self.filter
+    }
+
+    /// Returns this back-end, prepending a header to every generated file.
+    pub fn with_header(mut self, header: HeaderTemplate) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    /// Returns the template of the header of generated files, if any.
+    pub fn header(&self) -> Option<&HeaderTemplate> {
+        self.header.as_ref()
+    }
 }
 
 impl From<VHDLConfig> for VHDLBackEnd {
     fn from(config: VHDLConfig) -> Self {
-        VHDLBackEnd { config }
+        VHDLBackEnd {
+            config,
+            filter: Filter::default(),
+            header: None,
+        }
     }
 }
 
 impl GenerateProject for VHDLBackEnd {
-    fn generate(&self, project: &Project, path: impl AsRef<Path>) -> Result<()> {
-        // Create the project directory.
+    fn target(&self) -> &str {
+        "vhdl"
+    }
+
+    /// The common representation of every library is elaborated in turn,
+    /// after which its package and wrappers are declared as separate
+    /// [RenderJob]s, which may run in parallel. Files are returned in a
+    /// deterministic order: per library, the package, then the structures
+    /// (each followed by its testbench if enabled), then the wrappers, then
+    /// the credit-based flow control wrappers if enabled, followed by the support packages they depend on and
+    /// the summary of all files if enabled.
+    fn render(&self, project: &Project, path: impl AsRef<Path>) -> Result<Vec<GeneratedFile>> {
+        // All files are placed in the project directory.
         let mut dir = path.as_ref().to_path_buf();
         dir.push(project.identifier());
-        std::fs::create_dir_all(dir.as_path())?;
-
-        for lib in project.libraries() {
-            let mut pkg = dir.clone();
-            pkg.push(format!("{}_pkg", lib.identifier()));
-            pkg.set_extension(match self.config.suffix.clone() {
+        let file = |name: String| {
+            let mut file = dir.join(name);
+            file.set_extension(match self.config.suffix.clone() {
                 None => "vhd".to_string(),
                 Some(s) => format!("{}.vhd", s),
             });
+            file
+        };
+        let naming = self.config.naming();
+        let style = self.config.style();
+
+        // Elaborate the packages, and generate the structures, which depend
+        // on the project.
+        let mut libraries = Vec::new();
+        let mut structures = Vec::new();
+        for lib in project.libraries() {
+            if !self.filter.includes_library(lib.identifier()) {
+                continue;
+            }
+            let _span = debug_span!("elaborate", library = lib.identifier()).entered();
+            for streamlet in lib.streamlets() {
+                check_port_names(streamlet, &naming)?;
+                // The records of fancy components have the widths that
+                // result from the default values of the parameters.
+                if matches!(self.config().abstraction(), AbstractionLevel::Fancy)
+                    && streamlet.external().is_none()
+                    && streamlet.parameters().next().is_some()
+                {
+                    return Err(Error::BackEndError(format!(
+                        "Cannot generate the fancy component of streamlet {}.{}, because it has \
+                         parameters; use the canonical abstraction level instead",
+                        lib.identifier(),
+                        streamlet.identifier()
+                    )));
+                }
+            }
             let pak = match self.config().abstraction() {
-                AbstractionLevel::Canonical => lib.canonical(),
-                AbstractionLevel::Fancy => lib.fancy(),
+                AbstractionLevel::Canonical => lib.canonical_with(&naming),
+                AbstractionLevel::Fancy => lib.fancy_with(&naming),
             };
-            std::fs::write(pkg.as_path(), pak.declare()?)?;
-            debug!("Wrote {}.", pkg.as_path().to_str().unwrap_or(""));
-            if let AbstractionLevel::Fancy = self.config().abstraction() {
-                for streamlet in lib.streamlets() {
-                    let mut wrapper = dir.clone();
-                    wrapper.push(format!("{}_wrapper", streamlet.identifier()));
-                    wrapper.set_extension(match self.config.suffix.clone() {
-                        None => "vhd".to_string(),
-                        Some(s) => format!("{}.vhd", s),
-                    });
-                    let arch = generate_fancy_wrapper(&pak, &streamlet.key())?;
-                    std::fs::write(wrapper.as_path(), arch.declare()?)?;
+            // Streamlets with a structural implementation get a generated
+            // architecture for their canonical component.
+            let mut lib_structures = Vec::new();
+            let mut lib_credits = Vec::new();
+            let mut wrappers = Vec::new();
+            for streamlet in lib.streamlets() {
+                if !self
+                    .filter
+                    .includes_streamlet(lib.identifier(), streamlet.identifier())
+                {
+                    continue;
+                }
+                let origin = format!("{}.{}", lib.identifier(), streamlet.identifier());
+                if let FlowControl::Credit = self.config.flow_control() {
+                    let contents = generate_credit_wrapper(lib.identifier(), streamlet, &naming)
+                        .with_context(|| {
+                            format!(
+                                "while generating the credit wrapper of streamlet {}",
+                                origin
+                            )
+                        })?;
+                    lib_credits.push(
+                        GeneratedFile::new(
+                            file(format!("{}_credit", streamlet.identifier())),
+                            style.apply(&contents),
+                        )
+                        .with_checksum("--")
+                        .with_origin(origin.clone()),
+                    );
+                }
+                match streamlet.get_implementation().as_deref() {
+                    Some(Implementation::Structural(structure)) => {
+                        let contents = generate_structure_with(&pak, project, structure, &naming)
+                            .and_then(|arch| arch.declare())
+                            .with_context(|| {
+                                format!("while generating the structure of streamlet {}", origin)
+                            })?;
+                        lib_structures.push(
+                            GeneratedFile::new(
+                                file(format!("{}_structure", streamlet.identifier())),
+                                style.apply(&contents),
+                            )
+                            .with_checksum("--")
+                            .with_origin(origin.clone()),
+                        );
+                        if self.config.testbench() {
+                            let contents = generate_testbench(lib.identifier(), streamlet, &naming)
+                                .with_context(|| {
+                                    format!(
+                                        "while generating the testbench of streamlet {}",
+                                        origin
+                                    )
+                                })?;
+                            lib_structures.push(
+                                GeneratedFile::new(
+                                    file(format!("{}_tb", streamlet.identifier())),
+                                    style.apply(&contents),
+                                )
+                                .with_checksum("--")
+                                .with_origin(origin),
+                            );
+                        }
+                    }
+                    // The canonical component of structural implementations
+                    // does not wrap a fancy component, and externally
+                    // implemented streamlets need no wrapper.
+                    _ if streamlet.external().is_some() => {}
+                    _ => {
+                        if let AbstractionLevel::Fancy = self.config().abstraction() {
+                            wrappers.push((streamlet.key(), origin));
+                        }
+                    }
                 }
             }
+            libraries.push((lib.identifier().to_string(), pak, wrappers));
+            structures.push((lib_structures, lib_credits));
         }
-        Ok(())
+
+        // Declare the packages and wrappers, which only depend on the
+        // common representation.
+        let mut jobs: Vec<RenderJob> = Vec::new();
+        for ((lib, pak, wrappers), (structures, credits)) in libraries.iter().zip(structures) {
+            let path = file(format!("{}_pkg", lib));
+            let style = &style;
+            jobs.push(Box::new(move || {
+                let contents = pak
+                    .declare()
+                    .with_context(|| format!("while generating the package of library {}", lib))?;
+                Ok(GeneratedFile::new(path, style.apply(&contents))
+                    .with_checksum("--")
+                    .with_origin(lib.clone()))
+            }));
+            for structure in structures {
+                jobs.push(Box::new(move || Ok(structure)));
+            }
+            for (key, origin) in wrappers {
+                let path = file(format!("{}_wrapper", key));
+                let naming = &naming;
+                jobs.push(Box::new(move || {
+                    let contents = generate_fancy_wrapper_with(pak, key, naming)
+                        .and_then(|arch| arch.declare())
+                        .with_context(|| {
+                            format!("while generating the wrapper of streamlet {}", origin)
+                        })?;
+                    Ok(GeneratedFile::new(path, style.apply(&contents))
+                        .with_checksum("--")
+                        .with_origin(origin.clone()))
+                }));
+            }
+            for credit in credits {
+                jobs.push(Box::new(move || Ok(credit)));
+            }
+        }
+        let mut files = render_jobs(jobs)?;
+        if self.config.support() || self.config.simulation_models() {
+            let support =
+                support_files(&files, dir.join("support"), self.config.simulation_models());
+            files.extend(support);
+        }
+        if self.config.readme() {
+            let packages = libraries.iter().map(|(_, pak, _)| pak).collect::<Vec<_>>();
+            let summary = readme(&self.config, project.identifier(), &packages, &files, &dir)?;
+            files.push(summary);
+        }
+        Ok(match &self.header {
+            Some(header) => header.apply(project, files),
+            None => files,
+        })
     }
 }
 
@@ -313,37 +789,60 @@ impl Split for Array {
 impl Split for Port {
     fn split(&self) -> (Option<Self>, Option<Self>) {
         let (type_down, type_up) = self.typ().split();
-        (
-            type_down.map(|t| {
-                Port::new(
-                    cat!(self.identifier(), "dn"),
-                    self.mode(),
-                    match t {
-                        Type::Record(r) => Type::Record(r.append_name_nested("dn")),
-                        Type::Union(r) => Type::Union(r.append_name_nested("dn")),
-                        _ => t,
-                    },
-                )
-            }),
-            type_up.map(|t| {
-                Port::new(
-                    cat!(self.identifier(), "up"),
-                    self.mode().reversed(),
-                    match t {
-                        Type::Record(r) => Type::Record(r.append_name_nested("up")),
-                        Type::Union(r) => Type::Union(r.append_name_nested("up")),
-                        _ => t,
-                    },
-                )
-            }),
-        )
+        let mut down = type_down.map(|t| {
+            Port::new(
+                cat!(self.identifier(), "dn"),
+                self.mode(),
+                match t {
+                    Type::Record(r) => Type::Record(r.append_name_nested("dn")),
+                    Type::Union(r) => Type::Union(r.append_name_nested("dn")),
+                    _ => t,
+                },
+            )
+        });
+        let mut up = type_up.map(|t| {
+            Port::new(
+                cat!(self.identifier(), "up"),
+                self.mode().reversed(),
+                match t {
+                    Type::Record(r) => Type::Record(r.append_name_nested("up")),
+                    Type::Union(r) => Type::Union(r.append_name_nested("up")),
+                    _ => t,
+                },
+            )
+        });
+        // Keep the documentation on the first of the split ports.
+        if let Some(doc) = self.doc() {
+            if let Some(port) = down.as_mut().or(up.as_mut()) {
+                port.set_doc(doc);
+            }
+        }
+        (down, up)
     }
 }
 
+/// Returns the ports as they are declared in VHDL, where ports with reversed
+/// fields are split into a downstream and an upstream port.
+pub(crate) fn split_ports(ports: &[Port]) -> Vec<Port> {
+    ports
+        .iter()
+        .flat_map(|port| {
+            if port.has_reversed() {
+                let (dn, up) = port.split();
+                vec![dn, up].into_iter().flatten().collect()
+            } else {
+                vec![port.clone()]
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use std::fs;
 
+    use crate::design::implementation::composer::tests::sdf_test_proj;
+    use crate::generator::GenerationManifest;
     use crate::Reversed;
 
     use super::*;
@@ -502,6 +1001,12 @@ mod test {
         let tmpdir = tempfile::tempdir()?;
         let path = tmpdir.path().join("__test");
 
+        // Rendering does not write any files.
+        let files = v.render(&crate::design::project::tests::proj::empty_proj(), &path)?;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path(), path.join("proj/lib_pkg.gen.vhd"));
+        assert!(fs::metadata(&path).is_err());
+
         assert!(v
             .generate(&crate::design::project::tests::proj::empty_proj(), &path)
             .is_ok());
@@ -513,4 +1018,401 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn backend_structure() -> Result<()> {
+        use crate::design::implementation::structure::Structure;
+        use crate::design::{Library, NodeIFHandle, Streamlet, StreamletHandle};
+        use crate::parser::nom::interface;
+        use crate::UniqueKeyBuilder;
+
+        let streamlet = |name: &str| -> Result<Streamlet> {
+            Streamlet::from_builder(
+                Name::try_new(name)?,
+                UniqueKeyBuilder::new().with_items(vec![
+                    interface("a: in Stream<Bits<4>>").unwrap().1,
+                    interface("b: out Stream<Bits<4>>").unwrap().1,
+                ]),
+                None,
+            )
+        };
+        let handle = |lib: &str, name: &str| -> Result<StreamletHandle> {
+            Ok(StreamletHandle {
+                lib: Name::try_new(lib)?,
+                streamlet: Name::try_new(name)?,
+            })
+        };
+        let iface = |node: &str, iface: &str| -> Result<NodeIFHandle> {
+            Ok(NodeIFHandle::new(
+                Name::try_new(node)?,
+                Name::try_new(iface)?,
+            ))
+        };
+        let structure = |prj: &Project, top: StreamletHandle, node: StreamletHandle| {
+            let mut structure = Structure::new(top);
+            structure.add_node(Name::try_new("inst")?, node)?;
+            structure.connect(prj, iface("this", "a")?, iface("inst", "a")?)?;
+            structure.connect(prj, iface("inst", "b")?, iface("this", "b")?)?;
+            Ok::<_, Error>(Implementation::Structural(structure))
+        };
+
+        // A hierarchy of top -> mid -> leaf, where top and mid are structural.
+        let mut prj = Project::new(Name::try_new("proj")?);
+        prj.add_lib(Library::try_new(
+            Name::try_new("comp")?,
+            vec![],
+            vec![streamlet("top")?],
+        )?)?;
+        prj.add_lib(Library::try_new(
+            Name::try_new("prim")?,
+            vec![],
+            vec![streamlet("mid")?, streamlet("leaf")?],
+        )?)?;
+        let top = structure(&prj, handle("comp", "top")?, handle("prim", "mid")?)?;
+        let mid = structure(&prj, handle("prim", "mid")?, handle("prim", "leaf")?)?;
+        prj.add_streamlet_impl(handle("comp", "top")?, top)?;
+        prj.add_streamlet_impl(handle("prim", "mid")?, mid)?;
+
+        let tmpdir = tempfile::tempdir()?;
+        let v = VHDLBackEnd::from(VHDLConfig {
+            abstraction: Some(AbstractionLevel::Fancy),
+            ..VHDLConfig::default()
+        });
+        v.generate(&prj, tmpdir.path())?;
+
+        let top = fs::read_to_string(tmpdir.path().join("proj/top_structure.gen.vhd"))?;
+        assert!(top.contains("use work.prim.all;"));
+        assert!(top.contains("inst: mid_com"));
+        let mid = fs::read_to_string(tmpdir.path().join("proj/mid_structure.gen.vhd"))?;
+        assert!(mid.contains("inst: leaf_com"));
+        assert!(fs::metadata(tmpdir.path().join("proj/leaf_wrapper.gen.vhd")).is_ok());
+        assert!(fs::metadata(tmpdir.path().join("proj/mid_wrapper.gen.vhd")).is_err());
+        assert!(fs::metadata(tmpdir.path().join("proj/leaf_structure.gen.vhd")).is_err());
+
+        let manifest = GenerationManifest::from_toml(&fs::read_to_string(
+            tmpdir.path().join("proj/vhdl.manifest.toml"),
+        )?)?;
+        assert_eq!(manifest.target(), "vhdl");
+        assert_eq!(manifest.files().count(), 5);
+        let top = manifest
+            .files()
+            .find(|f| f.path() == Path::new("top_structure.gen.vhd"))
+            .unwrap();
+        assert_eq!(top.origin(), Some("comp.top"));
+        assert!(!top.is_skipped());
+        let contents = fs::read_to_string(tmpdir.path().join("proj/top_structure.gen.vhd"))?;
+        assert_eq!(GeneratedFile::new("", contents).hash(), top.hash());
+
+        // Only regenerate the top-level library and the leaf.
+        let tmpdir = tempfile::tempdir()?;
+        VHDLBackEnd::default()
+            .with_filter(Filter::new().with_only("comp")?.with_only("prim.leaf")?)
+            .generate(&prj, tmpdir.path())?;
+        assert!(fs::metadata(tmpdir.path().join("proj/comp_pkg.gen.vhd")).is_ok());
+        assert!(fs::metadata(tmpdir.path().join("proj/prim_pkg.gen.vhd")).is_ok());
+        assert!(fs::metadata(tmpdir.path().join("proj/top_structure.gen.vhd")).is_ok());
+        assert!(fs::metadata(tmpdir.path().join("proj/leaf_wrapper.gen.vhd")).is_ok());
+        assert!(fs::metadata(tmpdir.path().join("proj/mid_structure.gen.vhd")).is_err());
+
+        let tmpdir = tempfile::tempdir()?;
+        VHDLBackEnd::default()
+            .with_filter(
+                Filter::new()
+                    .with_exclude("comp")?
+                    .with_exclude("prim.leaf")?,
+            )
+            .generate(&prj, tmpdir.path())?;
+        assert!(fs::metadata(tmpdir.path().join("proj/comp_pkg.gen.vhd")).is_err());
+        assert!(fs::metadata(tmpdir.path().join("proj/top_structure.gen.vhd")).is_err());
+        assert!(fs::metadata(tmpdir.path().join("proj/leaf_wrapper.gen.vhd")).is_err());
+        assert!(fs::metadata(tmpdir.path().join("proj/mid_structure.gen.vhd")).is_ok());
+
+        // Structural streamlets get a testbench.
+        let tmpdir = tempfile::tempdir()?;
+        VHDLBackEnd::from(VHDLConfig::default().with_testbench(true))
+            .generate(&prj, tmpdir.path())?;
+        let tb = fs::read_to_string(tmpdir.path().join("proj/top_tb.gen.vhd"))?;
+        assert!(tb.contains("entity top_tb is"));
+        assert!(tb.contains("  dut: top_com\n    port map(\n"));
+        assert!(fs::metadata(tmpdir.path().join("proj/mid_tb.gen.vhd")).is_ok());
+        assert!(fs::metadata(tmpdir.path().join("proj/leaf_tb.gen.vhd")).is_err());
+        // Testbenches follow the code style of generated files.
+        let tmpdir = tempfile::tempdir()?;
+        let style = Style::default().with_tabs(true).with_alignment(true);
+        VHDLBackEnd::from(VHDLConfig::default().with_testbench(true).with_style(style))
+            .generate(&prj, tmpdir.path())?;
+        let tb = fs::read_to_string(tmpdir.path().join("proj/top_tb.gen.vhd"))?;
+        assert!(tb.contains("\tdut: top_com\n\t\tport map(\n\t\t\tclk "));
+        assert!(
+            tb.lines()
+                .any(|line| line.starts_with("\tsignal clk ")
+                    && line.contains("  : std_logic := '0'"))
+        );
+
+        // Every streamlet gets a credit wrapper.
+        let tmpdir = tempfile::tempdir()?;
+        VHDLBackEnd::from(VHDLConfig::default().with_flow_control(FlowControl::Credit))
+            .generate(&prj, tmpdir.path())?;
+        let credit = fs::read_to_string(tmpdir.path().join("proj/top_credit.gen.vhd"))?;
+        assert!(credit.contains("entity top_cr is"));
+        assert!(credit.contains("com: top_com port map("));
+        assert!(fs::metadata(tmpdir.path().join("proj/leaf_credit.gen.vhd")).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn usings() -> Result<()> {
+        struct List(Usings);
+        impl ListUsings for List {
+            fn list_usings(&self) -> Result<Usings> {
+                Ok(self.0.clone())
+            }
+        }
+
+        let mut usings = Usings::new_empty();
+        assert!(usings.add_using(Name::try_new("work")?, "z.all"));
+        assert!(usings.add_using(Name::try_new("ieee")?, "std_logic_1164.all"));
+        assert!(!usings.add_using(Name::try_new("work")?, "z.all"));
+        let mut other = Usings::new_empty();
+        other.add_using(Name::try_new("ieee")?, "numeric_std.all");
+        other.add_using(Name::try_new("ieee")?, "std_logic_1164.all");
+        other.add_using(Name::try_new("work")?, "a.all");
+        usings.combine(&other);
+        assert_eq!(
+            List(usings).declare_usings()?,
+            "library work;\n\
+             use work.z.all;\n\
+             use work.a.all;\n\
+             \n\
+             library ieee;\n\
+             use ieee.std_logic_1164.all;\n\
+             use ieee.numeric_std.all;\n\
+             \n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn backend_deterministic() -> Result<()> {
+        use crate::design::implementation::structure::Structure;
+        use crate::design::{Library, NodeIFHandle, StreamletHandle};
+        use crate::parser::nom::streamlet;
+
+        // Streamlets are declared out of alphabetical order, with a
+        // structure that maps ports of multiple types.
+        let project = || -> Result<Project> {
+            let mut prj = Project::new(Name::try_new("proj")?);
+            for lib in &["q", "b", "k"] {
+                let streamlets = ["z", "c", "x", "a", "m"]
+                    .iter()
+                    .map(|name| {
+                        streamlet(&format!(
+                            "Streamlet {}_{} (a : in Group<p: Bits<2>, q: Stream<Bits<4>>>, \
+                             b : out Group<p: Bits<2>, q: Stream<Bits<4>>>)",
+                            lib, name
+                        ))
+                        .unwrap()
+                        .1
+                    })
+                    .collect();
+                prj.add_lib(Library::try_new(Name::try_new(*lib)?, vec![], streamlets)?)?;
+            }
+            let handle = |name: &str| -> Result<StreamletHandle> {
+                Ok(StreamletHandle {
+                    lib: Name::try_new("q")?,
+                    streamlet: Name::try_new(name)?,
+                })
+            };
+            let iface = |node: &str, iface: &str| -> Result<NodeIFHandle> {
+                Ok(NodeIFHandle::new(
+                    Name::try_new(node)?,
+                    Name::try_new(iface)?,
+                ))
+            };
+            let mut structure = Structure::new(handle("q_z")?);
+            structure.add_node(Name::try_new("inst")?, handle("q_c")?)?;
+            structure.connect(&prj, iface("this", "a")?, iface("inst", "a")?)?;
+            structure.connect(&prj, iface("inst", "b")?, iface("this", "b")?)?;
+            prj.add_streamlet_impl(handle("q_z")?, Implementation::Structural(structure))?;
+            Ok(prj)
+        };
+
+        let render = || -> Result<Vec<(String, String)>> {
+            Ok(VHDLBackEnd::default()
+                .render(&project()?, "")?
+                .iter()
+                .map(|f| (f.path().display().to_string(), f.contents().to_string()))
+                .collect())
+        };
+        let files = render()?;
+        for _ in 0..8 {
+            assert_eq!(render()?, files);
+        }
+
+        // Packages follow the order of the libraries, components the order
+        // of the streamlets.
+        let packages = files
+            .iter()
+            .filter(|(path, _)| path.ends_with("_pkg.gen.vhd"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            packages
+                .iter()
+                .map(|(path, _)| path.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "proj/q_pkg.gen.vhd",
+                "proj/b_pkg.gen.vhd",
+                "proj/k_pkg.gen.vhd"
+            ]
+        );
+        let positions = ["z", "c", "x", "a", "m"]
+            .iter()
+            .map(|name| packages[0].1.find(&format!("component q_{}_com", name)))
+            .collect::<Option<Vec<_>>>()
+            .unwrap();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+        Ok(())
+    }
+
+    #[test]
+    fn backend_docs() -> Result<()> {
+        use crate::design::Library;
+        use crate::parser::nom::library;
+
+        let (_, (_, _, streamlets)) = library(
+            "/// Doubles its input.\n\
+             Streamlet double (\n\
+               /// Input values.\n\
+               a : in Stream<Bits<4>>,\n\
+               b : out Stream<Bits<4>>\n\
+             )",
+        )
+        .unwrap();
+        let mut prj = Project::new(Name::try_new("proj")?);
+        prj.add_lib(Library::try_new(Name::try_new("lib")?, vec![], streamlets)?)?;
+
+        let tmpdir = tempfile::tempdir()?;
+        VHDLBackEnd::default().generate(&prj, tmpdir.path())?;
+        let pkg = fs::read_to_string(tmpdir.path().join("proj/lib_pkg.gen.vhd"))?;
+        assert!(pkg.contains("-- Doubles its input.\ncomponent double_com"));
+        assert!(pkg.contains("-- Input values.\n    a_valid : in std_logic;"));
+        assert!(pkg.contains("-- Input values.\n    a_dn : in"));
+        Ok(())
+    }
+
+    #[test]
+    fn backend_naming() -> Result<()> {
+        let prj = sdf_test_proj(&[
+            "Streamlet x (a : in Group<b: Group<c: Stream<Bits<4>>, e: Stream<Bits<2>>>>, \
+             d : out Stream<Bits<4>>)",
+        ])?;
+
+        let package = |config: VHDLConfig| -> Result<String> {
+            let files = VHDLBackEnd::from(config.or(VHDLConfig::default())).render(&prj, "")?;
+            assert_eq!(files.len(), 2);
+            Ok(files[0].contents().to_string())
+        };
+        let pkg = package(VHDLConfig::new(None, None))?;
+        assert!(pkg.contains("a_b__c_valid : in std_logic;"));
+        assert!(pkg.contains("a_b__c_dn : in x_a_b__c_dn_type;"));
+        let pkg = package(VHDLConfig::new(None, None).with_path_separator("_"))?;
+        assert!(pkg.contains("a_b_c_valid : in std_logic;"));
+        assert!(pkg.contains("a_b_c_dn : in x_a_b_c_dn_type;"));
+        assert!(!pkg.contains("__"));
+        let pkg = package(VHDLConfig::new(None, None).with_flattening(Flattening::CamelCase))?;
+        assert!(pkg.contains("a_bC_valid : in std_logic;"));
+        assert!(pkg.contains("a_bE_data : in std_logic_vector(1 downto 0);"));
+        let style = Style::default().with_tabs(true).with_alignment(true);
+        let pkg = package(VHDLConfig::new(None, None).with_style(style))?;
+        assert!(!GeneratedFile::is_modified(&pkg));
+        assert!(pkg.contains("\t\ta_b__c_valid : in std_logic;\n"));
+        assert!(pkg.contains("\t\td_ready      : in std_logic;\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn backend_naming_collision() -> Result<()> {
+        let render = |prj: &Project, config: VHDLConfig| {
+            VHDLBackEnd::from(config.or(VHDLConfig::default())).render(prj, "")
+        };
+
+        let prj = sdf_test_proj(&[
+            "Streamlet x (a : in Group<b_c: Stream<Bits<4>>, b: Group<c: Stream<Bits<2>>>>)",
+        ])?;
+        assert!(render(&prj, VHDLConfig::new(None, None)).is_ok());
+        assert_eq!(
+            render(&prj, VHDLConfig::new(None, None).with_path_separator("_"))
+                .unwrap_err()
+                .to_string(),
+            "Back-end error: Ports a.b_c (valid) and a.b.c (valid) of streamlet x both flatten \
+             to a_b_c_valid, which VHDL does not distinguish"
+        );
+
+        // VHDL identifiers are case-insensitive.
+        let prj =
+            sdf_test_proj(&["Streamlet x (a : in Group<bc: Bits<4>, b: Group<c: Bits<2>>>)"])?;
+        assert!(render(&prj, VHDLConfig::new(None, None)).is_ok());
+        assert_eq!(
+            render(
+                &prj,
+                VHDLConfig::new(None, None).with_flattening(Flattening::CamelCase)
+            )
+            .unwrap_err()
+            .to_string(),
+            "Back-end error: Ports a.bc and a.b.c of streamlet x both flatten to a_bC, \
+             which VHDL does not distinguish"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn backend_generics() -> Result<()> {
+        let prj = sdf_test_proj(&["Streamlet x <W = 8> (a : in Stream<Bits<W>>)"])?;
+        let render = |abstraction| {
+            VHDLBackEnd::from(VHDLConfig::new(Some(abstraction), None).or(VHDLConfig::default()))
+                .render(&prj, "")
+        };
+        assert_eq!(
+            render(AbstractionLevel::Fancy).unwrap_err().to_string(),
+            "Back-end error: Cannot generate the fancy component of streamlet lib.x, because it \
+             has parameters; use the canonical abstraction level instead"
+        );
+        let files = render(AbstractionLevel::Canonical)?;
+        assert!(files[0]
+            .contents()
+            .contains("std_logic_vector(W-1 downto 0)"));
+        Ok(())
+    }
+
+    #[test]
+    fn backend_external() -> Result<()> {
+        use crate::design::implementation::composer::builder::CompositionBuilder;
+        use crate::design::implementation::composer::tests::handle;
+
+        let mut prj = sdf_test_proj(&[
+            "Streamlet top (a : in Stream<Bits<4>>, b : out Stream<Bits<4>>)",
+            "/// @external ip.axi_fifo\n\
+             Streamlet fifo (a : in Stream<Bits<4>>, b : out Stream<Bits<4>>)",
+        ])?;
+        let mut builder = CompositionBuilder::try_new(&mut prj, handle("top"))?;
+        let this = builder.this();
+        let fifo = builder.instantiate("inst", handle("fifo"))?;
+        builder
+            .connect(this.io("a")?, fifo.io("a")?)?
+            .connect(fifo.io("b")?, this.io("b")?)?;
+        builder.finish()?;
+
+        let tmpdir = tempfile::tempdir()?;
+        VHDLBackEnd::default().generate(&prj, tmpdir.path())?;
+
+        let pkg = fs::read_to_string(tmpdir.path().join("proj/lib_pkg.gen.vhd"))?;
+        assert!(pkg.contains("component axi_fifo"));
+        assert!(!pkg.contains("component fifo"));
+        let top = fs::read_to_string(tmpdir.path().join("proj/top_structure.gen.vhd"))?;
+        assert!(top.contains("library ip;\nuse ip.axi_fifo;"));
+        assert!(top.contains("inst: axi_fifo"));
+        assert!(fs::metadata(tmpdir.path().join("proj/fifo_wrapper.gen.vhd")).is_err());
+        Ok(())
+    }
 }
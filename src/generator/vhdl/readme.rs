@@ -0,0 +1,213 @@
+//! Summaries of generated VHDL.
+//!
+//! Consumers of a generated tree often only need to know which components
+//! it declares and how to connect them. This module writes a `README.md`
+//! next to the generated files, that summarizes the components of every
+//! library with their ports, and the settings they were generated with.
+
+use std::path::Path;
+
+use crate::generator::common::{Package, Port, Type};
+use crate::generator::vhdl::{split_ports, VHDLConfig, VHDLIdentifier};
+use crate::generator::GeneratedFile;
+use crate::traits::{Document, Identify};
+use crate::Result;
+
+/// Returns the VHDL type of a port.
+fn port_type(port: &Port) -> Result<String> {
+    match (port.typ(), port.width()) {
+        (Type::BitVec { .. }, Some(width)) => Ok(format!("std_logic_vector({}-1 downto 0)", width)),
+        (typ, _) => typ.vhdl_identifier(),
+    }
+}
+
+/// Returns documentation as a Markdown paragraph, if any.
+fn paragraph(doc: Option<&str>) -> String {
+    doc.map(str::trim)
+        .filter(|doc| !doc.is_empty())
+        .map(|doc| format!("{}\n\n", doc))
+        .unwrap_or_default()
+}
+
+/// Returns the summary of the packages of a project, generated with a
+/// configuration into the files in a directory.
+pub fn readme(
+    config: &VHDLConfig,
+    project: &str,
+    packages: &[&Package],
+    files: &[GeneratedFile],
+    dir: impl AsRef<Path>,
+) -> Result<GeneratedFile> {
+    let naming = config.naming();
+    // The checksum comment is closed on the next line, such that it is
+    // hidden when the file is rendered.
+    let mut result = format!(
+        "-->\n# {}\n\nGenerated VHDL of project `{}`, with the following settings:\n\n\
+         - abstraction: {}\n- suffix: {}\n- path separator: `{}`\n- flattening: {}\n\
+         - flow control: {}\n",
+        project,
+        project,
+        config.abstraction(),
+        config.suffix().unwrap_or("none"),
+        naming.path_separator(),
+        naming.flattening(),
+        config.flow_control(),
+    );
+    // Support files are only emitted when generated files depend on them.
+    let support = files
+        .iter()
+        .filter_map(|file| file.path().strip_prefix(dir.as_ref()).ok())
+        .filter(|path| path.starts_with("support"))
+        .collect::<Vec<_>>();
+    if !support.is_empty() {
+        result.push_str(&format!(
+            "- support packages{}: {}\n",
+            if config.simulation_models() {
+                " with simulation models"
+            } else {
+                ""
+            },
+            support
+                .iter()
+                .map(|path| format!("`{}`", path.display()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    for package in packages {
+        result.push_str(&format!(
+            "\n## Library `{}`\n\n{}",
+            package.identifier,
+            paragraph(package.doc.as_deref())
+        ));
+        let origin = |file: &&GeneratedFile| {
+            file.origin().is_some_and(|origin| {
+                origin == package.identifier
+                    || origin.starts_with(&format!("{}.", package.identifier))
+            })
+        };
+        result.push_str("Generated files:\n\n");
+        for file in files.iter().filter(origin) {
+            let path = file
+                .path()
+                .strip_prefix(dir.as_ref())
+                .unwrap_or(file.path());
+            result.push_str(&format!("- `{}`\n", path.display()));
+        }
+        for component in &package.components {
+            result.push_str(&format!(
+                "\n### Component `{}`\n\n{}",
+                component.identifier(),
+                paragraph(component.doc().as_deref())
+            ));
+            for parameter in component.parameters() {
+                result.push_str(&format!("- parameter `{}`\n", parameter.name));
+            }
+            if !component.parameters().is_empty() {
+                result.push('\n');
+            }
+            result.push_str("| Port | Mode | Type |\n| --- | --- | --- |\n");
+            // The ports as declared, where ports with reversed fields are
+            // split into a downstream and an upstream port.
+            for port in split_ports(component.ports()) {
+                result.push_str(&format!(
+                    "| `{}` | {} | `{}` |\n",
+                    port.identifier(),
+                    port.mode().vhdl_identifier()?,
+                    port_type(&port)?
+                ));
+            }
+        }
+    }
+    Ok(GeneratedFile::new(dir.as_ref().join("README.md"), result).with_checksum("<!--"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::design::{Library, Project};
+    use crate::generator::vhdl::{AbstractionLevel, VHDLBackEnd, VHDLConfig};
+    use crate::generator::{GenerateProject, GeneratedFile};
+    use crate::{Name, Result};
+    use std::path::Path;
+
+    #[test]
+    fn readme() -> Result<()> {
+        let mut prj = Project::new(Name::try_new("proj")?);
+        prj.add_lib(Library::from_sdf(
+            Name::try_new("lib")?,
+            "//! A library.\n\
+             /// A streamlet.\n\
+             Streamlet x (a : in Stream<Bits<8>>, b : out Stream<Bits<4>>)",
+        )?)?;
+        let config = || VHDLConfig::new(Some(AbstractionLevel::Canonical), Some("gen".to_string()));
+        let files = VHDLBackEnd::from(config()).render(&prj, "out")?;
+        assert!(files
+            .iter()
+            .all(|f| f.path() != Path::new("out/proj/README.md")));
+
+        let files = VHDLBackEnd::from(config().with_readme(true)).render(&prj, "out")?;
+        let summary = files.last().unwrap();
+        assert_eq!(summary.path(), Path::new("out/proj/README.md"));
+        assert!(!GeneratedFile::is_modified(summary.contents()));
+        let contents = summary.contents();
+        assert_eq!(contents.lines().nth(1), Some("-->"));
+        for line in &[
+            "# proj",
+            "- abstraction: canonical",
+            "- suffix: gen",
+            "- path separator: `__`",
+            "- flow control: handshake",
+            "## Library `lib`",
+            "A library.",
+            "Generated files:",
+            "- `lib_pkg.gen.vhd`",
+            "### Component `x_com`",
+            "| `a_data` | in | `std_logic_vector(7 downto 0)` |",
+            "| `b_ready` | in | `std_logic` |",
+        ] {
+            assert!(contents.lines().any(|l| l == *line), "missing {}", line);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn readme_split_ports() -> Result<()> {
+        let mut prj = Project::new(Name::try_new("proj")?);
+        prj.add_lib(Library::from_sdf(
+            Name::try_new("lib")?,
+            "Streamlet x (a : in Stream<Bits<8>>, b : out Stream<Bits<4>>)",
+        )?)?;
+        let config = VHDLConfig::new(Some(AbstractionLevel::Fancy), Some("gen".to_string()))
+            .with_readme(true);
+        let files = VHDLBackEnd::from(config).render(&prj, "out")?;
+        let contents = files.last().unwrap().contents();
+        // The ports of the fancy component are listed as declared
+        for line in &[
+            "### Component `x`",
+            "| `a_dn` | in | `x_a_dn_type` |",
+            "| `a_up` | out | `x_a_up_type` |",
+            "| `b_dn` | out | `x_b_dn_type` |",
+            "| `b_up` | in | `x_b_up_type` |",
+        ] {
+            assert!(contents.lines().any(|l| l == *line), "missing {}", line);
+        }
+        assert!(!contents.contains("| `a` |"));
+        // No support files were emitted
+        assert!(!contents.contains("support"));
+        Ok(())
+    }
+
+    #[test]
+    fn readme_support() -> Result<()> {
+        let config = VHDLConfig::new(None, None).with_support(true);
+        let files = vec![GeneratedFile::new("out/proj/support/Stream_pkg.vhd", "")];
+        let summary = super::readme(&config, "proj", &[], &files, "out/proj")?;
+        assert!(summary
+            .contents()
+            .lines()
+            .any(|l| l == "- support packages: `support/Stream_pkg.vhd`"));
+        let summary = super::readme(&config, "proj", &[], &[], "out/proj")?;
+        assert!(!summary.contents().contains("support"));
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+//! The physical streams of the interfaces of a streamlet, and the canonical
+//! ports of their signals, for generators that wire every physical stream of
+//! a streamlet.
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::{Mode, Streamlet};
+use crate::generator::common::convert::ModeFor;
+use crate::generator::common::Mode as PortMode;
+use crate::physical::{Signal, Width};
+use crate::traits::Identify;
+use crate::NamingPolicy;
+
+/// A physical stream of a streamlet.
+pub(super) struct StreamPorts {
+    /// The name of the stream, the prefix of its ports.
+    pub(super) name: String,
+    /// Whether the stream is an input of the streamlet, i.e. whether its
+    /// valid port is an input.
+    pub(super) input: bool,
+    pub(super) valid: String,
+    pub(super) ready: String,
+    /// The identifiers and ports of the signals of the transfers of the
+    /// stream, in the order of transfer vectors, with their width if they are
+    /// vectors.
+    pub(super) signals: Vec<(String, String, Option<u64>)>,
+}
+
+/// Returns the physical streams of the interfaces of a streamlet, with the
+/// names of their ports.
+pub(super) fn stream_ports(streamlet: &Streamlet, policy: &NamingPolicy) -> Vec<StreamPorts> {
+    let mut result = vec![];
+    for interface in streamlet.interfaces() {
+        for (path, physical) in interface.synthesize().streams() {
+            let name = policy.join(&[interface.identifier(), &path.render(policy)]);
+            let port = |signal: &Signal| {
+                // The mode of the port of the signal, from the perspective of the streamlet
+                let mode = match signal.origin().mode_for(interface.mode()) {
+                    PortMode::In => Mode::In,
+                    PortMode::Out => Mode::Out,
+                };
+                policy.port(policy.join(&[&name, signal.identifier()]), mode)
+            };
+            let list = physical.signal_list();
+            let signals = [
+                list.data(),
+                list.last(),
+                list.stai(),
+                list.endi(),
+                list.strb(),
+                list.user(),
+            ]
+            .iter()
+            .flatten()
+            .map(|signal| {
+                let width = match signal.width() {
+                    Width::Scalar => None,
+                    Width::Vector(width) => Some(width),
+                };
+                (signal.identifier().to_string(), port(signal), width)
+            })
+            .collect();
+            result.push(StreamPorts {
+                input: list.valid().origin().mode_for(interface.mode()) == PortMode::In,
+                valid: port(&list.valid()),
+                ready: port(&list.ready()),
+                name,
+                signals,
+            });
+        }
+    }
+    result
+}
@@ -0,0 +1,219 @@
+//! Code style of generated VHDL.
+//!
+//! The [Declare](super::Declare) and
+//! [ArchitectureDeclare](crate::stdlib::common::architecture::ArchitectureDeclare)
+//! implementations indent nested code by two spaces per level. A [Style]
+//! formats their output afterwards, such that all generated VHDL follows a
+//! single, configurable style: the indentation of every level, and whether
+//! consecutive declarations and associations are aligned.
+
+/// The code style of generated VHDL.
+///
+/// The default style reproduces the output of the declarations as is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Style {
+    /// The number of spaces per indentation level.
+    indent: usize,
+    /// Whether every indentation level is a tab rather than spaces.
+    tabs: bool,
+    /// Whether the `:` of consecutive declarations and the `=>` of
+    /// consecutive associations are aligned.
+    align: bool,
+    /// The maximum length of lines, if any.
+    max_line_length: Option<usize>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            indent: 2,
+            tabs: false,
+            align: false,
+            max_line_length: None,
+        }
+    }
+}
+
+/// The width of an indentation level of the declarations.
+const LEVEL: usize = 2;
+
+/// The tokens that are aligned between consecutive lines.
+const ALIGNED: &[&str] = &[" : ", " => "];
+
+impl Style {
+    /// Returns this style with the given number of spaces per indentation
+    /// level.
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Returns this style, indenting every level by a tab if `tabs` is true.
+    pub fn with_tabs(mut self, tabs: bool) -> Self {
+        self.tabs = tabs;
+        self
+    }
+
+    /// Returns this style, aligning the `:` of consecutive declarations and
+    /// the `=>` of consecutive associations if `align` is true.
+    pub fn with_alignment(mut self, align: bool) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Returns this style with the given maximum length of lines. Lines are
+    /// not aligned if that would make them exceed it.
+    pub fn with_max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = Some(max_line_length);
+        self
+    }
+
+    /// Returns the number of spaces per indentation level.
+    pub fn indent(&self) -> usize {
+        self.indent
+    }
+
+    /// Returns true if every indentation level is a tab.
+    pub fn tabs(&self) -> bool {
+        self.tabs
+    }
+
+    /// Returns true if consecutive declarations and associations are
+    /// aligned.
+    pub fn align(&self) -> bool {
+        self.align
+    }
+
+    /// Returns the maximum length of lines, if any.
+    pub fn max_line_length(&self) -> Option<usize> {
+        self.max_line_length
+    }
+
+    /// Formats VHDL declared with two spaces per indentation level in this
+    /// style.
+    pub fn apply(&self, vhdl: &str) -> String {
+        let unit = if self.tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(self.indent)
+        };
+        let mut lines = vhdl
+            .lines()
+            .map(|line| {
+                let content = line.trim_start_matches(' ');
+                let spaces = line.len() - content.len();
+                if content.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "{}{}{}",
+                        unit.repeat(spaces / LEVEL),
+                        " ".repeat(spaces % LEVEL),
+                        content
+                    )
+                }
+            })
+            .collect::<Vec<_>>();
+        if self.align {
+            for token in ALIGNED {
+                self.align_token(&mut lines, token);
+            }
+        }
+        let mut result = lines.join("\n");
+        if vhdl.ends_with('\n') {
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Aligns a token between groups of consecutive lines with the same
+    /// indentation that contain it.
+    fn align_token(&self, lines: &mut [String], token: &str) {
+        let position = |line: &str| position(line, token);
+        let mut start = 0;
+        while start < lines.len() {
+            let indent = match position(&lines[start]) {
+                Some((indent, _)) => indent,
+                None => {
+                    start += 1;
+                    continue;
+                }
+            };
+            // Comments between the lines do not end the group.
+            let mut end = start + 1;
+            while end < lines.len()
+                && (is_comment(&lines[end])
+                    || position(&lines[end])
+                        .is_some_and(|(other, _)| lines[end][..other] == lines[start][..indent]))
+            {
+                end += 1;
+            }
+            let column = lines[start..end]
+                .iter()
+                .filter_map(|line| position(line).map(|(_, pos)| pos))
+                .max()
+                .unwrap_or(0);
+            let fits = |line: &String| {
+                let pos = position(line).map(|(_, pos)| pos).unwrap_or(column);
+                self.max_line_length
+                    .is_none_or(|max| line.chars().count() + column - pos <= max)
+            };
+            if lines[start..end].iter().all(fits) {
+                for line in &mut lines[start..end] {
+                    if let Some((_, pos)) = position(line) {
+                        line.insert_str(pos, &" ".repeat(column - pos));
+                    }
+                }
+            }
+            start = end;
+        }
+    }
+}
+
+/// Returns the length of the indentation of a line and the position of a
+/// token in it, if the line is not a comment and contains the token.
+fn position(line: &str, token: &str) -> Option<(usize, usize)> {
+    if is_comment(line) {
+        return None;
+    }
+    let indent = line.len() - line.trim_start().len();
+    line.find(token).map(|pos| (indent, pos))
+}
+
+/// Returns true if a line is a comment.
+fn is_comment(line: &str) -> bool {
+    line.trim_start().starts_with("--")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn style() {
+        let vhdl = "entity x is\n  port(\n    clk : in std_logic;\n    a_valid : in std_logic\n  );\nend x;\n";
+        assert_eq!(Style::default().apply(vhdl), vhdl);
+        assert_eq!(
+            Style::default().with_indent(4).apply(vhdl),
+            "entity x is\n    port(\n        clk : in std_logic;\n        a_valid : in std_logic\n    );\nend x;\n"
+        );
+        assert_eq!(
+            Style::default().with_tabs(true).with_alignment(true).apply(vhdl),
+            "entity x is\n\tport(\n\t\tclk     : in std_logic;\n\t\ta_valid : in std_logic\n\t);\nend x;\n"
+        );
+
+        let map = "  port map(\n    a => a_sig,\n    -- b => c\n    long_name => b_sig\n  );";
+        assert_eq!(
+            Style::default().with_alignment(true).apply(map),
+            "  port map(\n    a         => a_sig,\n    -- b => c\n    long_name => b_sig\n  );"
+        );
+        // Lines are not aligned if they would exceed the maximum length.
+        assert_eq!(
+            Style::default()
+                .with_alignment(true)
+                .with_max_line_length(20)
+                .apply(map),
+            map
+        );
+    }
+}
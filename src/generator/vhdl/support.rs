@@ -0,0 +1,128 @@
+//! Support packages for generated VHDL.
+//!
+//! Generated architectures may use components of [vhlib], e.g. `StreamSlice`
+//! from `work.Stream_pkg`. This module provides vendored equivalents of these
+//! design units, such that generated output can be compiled without a
+//! checkout of vhlib.
+//!
+//! Components may also have a behavioral simulation model, which is emitted
+//! instead of the component if simulation models are selected. Models keep
+//! the entity of the component, but hold transfers in a queue rather than in
+//! registers, and are not synthesizable. They allow generated output to be
+//! simulated before the optimized implementations are available.
+//!
+//! [vhlib]: https://github.com/abs-tudelft/vhlib
+
+use std::path::Path;
+
+use crate::generator::GeneratedFile;
+
+/// A vendored VHDL source file.
+struct SupportSource {
+    /// The name of the file.
+    file: &'static str,
+    /// The lowercase identifiers of which a reference in generated VHDL
+    /// requires this file.
+    references: &'static [&'static str],
+    /// The contents of the file.
+    contents: &'static str,
+    /// The name and contents of the behavioral simulation model of the
+    /// component of the file, if any.
+    model: Option<(&'static str, &'static str)>,
+}
+
+/// The vendored VHDL source files, in compilation order.
+const SOURCES: &[SupportSource] = &[
+    SupportSource {
+        file: "Stream_pkg.vhd",
+        references: &["stream_pkg", "streamslice"],
+        contents: include_str!("support/Stream_pkg.vhd"),
+        model: None,
+    },
+    SupportSource {
+        file: "StreamSlice.vhd",
+        references: &["streamslice"],
+        contents: include_str!("support/StreamSlice.vhd"),
+        model: Some((
+            "StreamSlice_model.vhd",
+            include_str!("support/StreamSlice_model.vhd"),
+        )),
+    },
+];
+
+/// Returns the vendored support sources that generated files depend on,
+/// placed in a directory, in compilation order. If `models` is true, the
+/// behavioral simulation models of components are returned instead of their
+/// implementations.
+///
+/// VHDL is case-insensitive, so references are matched regardless of case.
+pub fn support_files(
+    files: &[GeneratedFile],
+    dir: impl AsRef<Path>,
+    models: bool,
+) -> Vec<GeneratedFile> {
+    let contents = files
+        .iter()
+        .map(|file| file.contents().to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    SOURCES
+        .iter()
+        .filter(|source| {
+            source
+                .references
+                .iter()
+                .any(|reference| contents.iter().any(|c| c.contains(reference)))
+        })
+        .map(|source| {
+            let (file, contents) = match source.model {
+                Some(model) if models => model,
+                _ => (source.file, source.contents),
+            };
+            GeneratedFile::new(dir.as_ref().join(file), contents).with_checksum("--")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn support() {
+        let file = |contents: &str| GeneratedFile::new("a.vhd", contents);
+        assert!(support_files(&[file("entity a is end a;")], "support", false).is_empty());
+
+        let files = support_files(&[file("use work.Stream_pkg.all;")], "support", false);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path(), Path::new("support/Stream_pkg.vhd"));
+
+        let files = support_files(
+            &[file("entity a is end a;"), file("s: STREAMSLICE port map")],
+            "support",
+            false,
+        );
+        assert_eq!(
+            files.iter().map(GeneratedFile::path).collect::<Vec<_>>(),
+            vec![
+                Path::new("support/Stream_pkg.vhd"),
+                Path::new("support/StreamSlice.vhd")
+            ]
+        );
+        assert!(files[1].contents().contains("entity StreamSlice is"));
+        assert!(!GeneratedFile::is_modified(files[1].contents()));
+
+        // Simulation models replace the components that have one.
+        let files = support_files(&[file("s: StreamSlice port map")], "support", true);
+        assert_eq!(
+            files.iter().map(GeneratedFile::path).collect::<Vec<_>>(),
+            vec![
+                Path::new("support/Stream_pkg.vhd"),
+                Path::new("support/StreamSlice_model.vhd")
+            ]
+        );
+        assert!(files[1].contents().contains("entity StreamSlice is"));
+        assert!(files[1]
+            .contents()
+            .contains("architecture model of StreamSlice is"));
+    }
+}
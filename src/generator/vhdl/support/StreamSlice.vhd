@@ -0,0 +1,66 @@
+-- Register slice for a stream, breaking the combinatorial paths of both the
+-- handshake and the data, without reducing the throughput. Compatible with
+-- the StreamSlice of vhlib.
+--
+-- The slice holds up to two transfers: the output register, and a skid
+-- register that accepts the transfer that was in flight when the output
+-- stalled. The reset is active-high and synchronous.
+
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity StreamSlice is
+  generic (
+    DATA_WIDTH : natural := 1
+  );
+  port (
+    clk       : in  std_logic;
+    reset     : in  std_logic;
+    in_valid  : in  std_logic;
+    in_ready  : out std_logic;
+    in_data   : in  std_logic_vector(DATA_WIDTH-1 downto 0);
+    out_valid : out std_logic;
+    out_ready : in  std_logic;
+    out_data  : out std_logic_vector(DATA_WIDTH-1 downto 0)
+  );
+end entity StreamSlice;
+
+architecture behavioral of StreamSlice is
+  signal main_valid : std_logic;
+  signal main_data  : std_logic_vector(DATA_WIDTH-1 downto 0);
+  signal skid_valid : std_logic;
+  signal skid_data  : std_logic_vector(DATA_WIDTH-1 downto 0);
+begin
+
+  reg_proc: process (clk) is
+  begin
+    if rising_edge(clk) then
+      if skid_valid = '0' then
+        if main_valid = '0' or out_ready = '1' then
+          -- The output register is empty or drained: load the input.
+          main_valid <= in_valid;
+          main_data  <= in_data;
+        elsif in_valid = '1' then
+          -- The output stalled: store the input in the skid register.
+          skid_valid <= '1';
+          skid_data  <= in_data;
+        end if;
+      elsif out_ready = '1' then
+        -- The output register is drained: move the skid register into it.
+        main_valid <= '1';
+        main_data  <= skid_data;
+        skid_valid <= '0';
+      end if;
+
+      if reset = '1' then
+        main_valid <= '0';
+        skid_valid <= '0';
+      end if;
+    end if;
+  end process;
+
+  in_ready  <= not skid_valid;
+  out_valid <= main_valid;
+  out_data  <= main_data;
+
+end architecture behavioral;
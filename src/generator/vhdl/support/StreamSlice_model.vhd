@@ -0,0 +1,93 @@
+-- Behavioral simulation model of the StreamSlice, for simulating generated
+-- output before the optimized implementation is available. This model is
+-- not synthesizable.
+--
+-- Transfers are held in a queue with the capacity of the implementation: a
+-- transfer is accepted on a rising edge while the queue is not full, and the
+-- oldest transfer is presented at the output from the next cycle on. The
+-- reset is active-high and synchronous.
+
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity StreamSlice is
+  generic (
+    DATA_WIDTH : natural := 1
+  );
+  port (
+    clk       : in  std_logic;
+    reset     : in  std_logic;
+    in_valid  : in  std_logic;
+    in_ready  : out std_logic;
+    in_data   : in  std_logic_vector(DATA_WIDTH-1 downto 0);
+    out_valid : out std_logic;
+    out_ready : in  std_logic;
+    out_data  : out std_logic_vector(DATA_WIDTH-1 downto 0)
+  );
+end entity StreamSlice;
+
+architecture model of StreamSlice is
+  constant CAPACITY : natural := 2;
+begin
+
+  model_proc: process (clk) is
+    type transfer_type;
+    type transfer_ptr is access transfer_type;
+    type transfer_type is record
+      data : std_logic_vector(DATA_WIDTH-1 downto 0);
+      next_transfer : transfer_ptr;
+    end record;
+    variable head   : transfer_ptr := null;
+    variable tail   : transfer_ptr := null;
+    variable count  : natural := 0;
+    variable item   : transfer_ptr;
+    variable accept : boolean;
+  begin
+    if rising_edge(clk) then
+      if reset = '1' then
+        while head /= null loop
+          item := head;
+          head := head.next_transfer;
+          deallocate(item);
+        end loop;
+        tail  := null;
+        count := 0;
+      else
+        -- The handshakes are evaluated against the queue before this edge.
+        accept := in_valid = '1' and count < CAPACITY;
+        if count > 0 and out_ready = '1' then
+          item  := head;
+          head  := head.next_transfer;
+          deallocate(item);
+          count := count - 1;
+          if head = null then
+            tail := null;
+          end if;
+        end if;
+        if accept then
+          item := new transfer_type'(data => in_data, next_transfer => null);
+          if tail = null then
+            head := item;
+          else
+            tail.next_transfer := item;
+          end if;
+          tail  := item;
+          count := count + 1;
+        end if;
+      end if;
+
+      if count < CAPACITY then
+        in_ready <= '1';
+      else
+        in_ready <= '0';
+      end if;
+      if head /= null then
+        out_valid <= '1';
+        out_data  <= head.data;
+      else
+        out_valid <= '0';
+      end if;
+    end if;
+  end process;
+
+end architecture model;
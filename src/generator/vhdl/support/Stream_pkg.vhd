@@ -0,0 +1,27 @@
+-- Stream support package, declaring the stream components that generated
+-- architectures may instantiate. Compatible with the Stream_pkg of vhlib.
+
+library ieee;
+use ieee.std_logic_1164.all;
+
+package Stream_pkg is
+
+  -- Register slice for a stream, breaking the combinatorial paths of both the
+  -- handshake and the data, without reducing the throughput.
+  component StreamSlice is
+    generic (
+      DATA_WIDTH : natural := 1
+    );
+    port (
+      clk       : in  std_logic;
+      reset     : in  std_logic;
+      in_valid  : in  std_logic;
+      in_ready  : out std_logic;
+      in_data   : in  std_logic_vector(DATA_WIDTH-1 downto 0);
+      out_valid : out std_logic;
+      out_ready : in  std_logic;
+      out_data  : out std_logic_vector(DATA_WIDTH-1 downto 0)
+    );
+  end component;
+
+end package Stream_pkg;
@@ -0,0 +1,337 @@
+//! Self-checking testbenches for streamlets with a structural implementation.
+//!
+//! A testbench instantiates the canonical component of a streamlet, and has a
+//! process for every physical stream of its interfaces:
+//!
+//! * The streams into the streamlet are driven from transfer vectors read
+//!   from `<stream>.in.txt`.
+//! * The transfers of the streams out of the streamlet are written to
+//!   `<stream>.out.txt`, and compared with the transfer vectors read from
+//!   `<stream>.expected.txt`. Bits written as `-` are not compared.
+//!
+//! The files are read from and written to the directory given by the
+//! `VECTORS` generic of the testbench, and use the format of
+//! [write_transfers](crate::vectors::write_transfers), such that they can be
+//! obtained from element vectors using [encode](crate::transfer::encode).
+//! Streams are named like the prefix of their canonical ports, e.g. `a` or
+//! `a__b`. Other inputs of the streamlet, e.g. asynchronous signals, are
+//! driven low.
+//!
+//! The testbench passes once every output stream has carried its expected
+//! transfers without mismatches, and fails if that does not happen within the
+//! time given by its `TIMEOUT` generic.
+//!
+//! Testbenches require VHDL-2008: they read transfer vectors into
+//! `std_logic_vector` variables, and end the simulation with
+//! `std.env.finish`. The header of a testbench states this as well.
+
+use std::convert::TryInto;
+
+use indexmap::IndexSet;
+
+use crate::design::Streamlet;
+use crate::generator::common::convert::{canonical_ports, CANON_SUFFIX};
+use crate::generator::common::{Component, Mode as PortMode, Type};
+use crate::stdlib::common::architecture::assignment::bitvec::BitVecValue;
+use crate::stdlib::common::architecture::assignment::{Assign, StdLogicValue, ValueAssignment};
+use crate::stdlib::common::architecture::declaration::ObjectDeclaration;
+use crate::stdlib::common::architecture::object::{EnumObject, ObjectType};
+use crate::stdlib::common::architecture::statement::{PortMapping, Statement};
+use crate::stdlib::common::architecture::ArchitectureDeclare;
+use crate::traits::Identify;
+use crate::{cat, Error, NamingPolicy, Natural, Result};
+
+use super::streams::{stream_ports, StreamPorts};
+
+impl StreamPorts {
+    /// Declares a variable for every signal of the transfer vectors.
+    fn variables(&self) -> String {
+        self.signals
+            .iter()
+            .map(|(variable, _, width)| match width {
+                Some(width) => format!(
+                    "    variable {} : std_logic_vector({} downto 0);\n",
+                    variable,
+                    width - 1
+                ),
+                None => format!("    variable {} : std_logic;\n", variable),
+            })
+            .collect()
+    }
+
+    /// Reads the signals of a transfer vector from line `l` into the
+    /// variables.
+    fn read(&self, indent: &str) -> String {
+        self.signals
+            .iter()
+            .map(|(variable, _, _)| format!("{}read(l, {});\n", indent, variable))
+            .collect()
+    }
+
+    /// Writes the signals of the current transfer to line `o`.
+    fn write(&self, indent: &str) -> String {
+        self.signals
+            .iter()
+            .enumerate()
+            .map(|(index, (_, port, _))| match index {
+                0 => format!("{}write(o, {});\n", indent, port),
+                _ => format!("{}write(o, ' ');\n{}write(o, {});\n", indent, indent, port),
+            })
+            .collect()
+    }
+
+    /// The process that drives the stream from transfer vectors.
+    fn source(&self) -> String {
+        let mut result = format!(
+            "  {0}_source: process\n    file vectors : text;\n    variable l : line;\n    variable found : boolean;\n{1}  begin\n    {2} <= '0';\n    file_open(vectors, VECTORS & \"/{0}.in.txt\", read_mode);\n    wait until rising_edge(clk) and rst = '0';\n    loop\n      next_vector(vectors, l, found);\n      exit when not found;\n",
+            self.name,
+            self.variables(),
+            self.valid
+        );
+        result.push_str(&self.read("      "));
+        for (variable, port, _) in &self.signals {
+            result.push_str(&format!("      {} <= {};\n", port, variable));
+        }
+        result.push_str(&format!(
+            "      {0} <= '1';\n      loop\n        wait until rising_edge(clk);\n        exit when {1} = '1';\n      end loop;\n      {0} <= '0';\n    end loop;\n    file_close(vectors);\n    wait;\n  end process;\n",
+            self.valid, self.ready
+        ));
+        result
+    }
+
+    /// The process that collects the transfers of the stream, and compares
+    /// them with the expected transfer vectors.
+    fn sink(&self) -> String {
+        let mut result = format!(
+            "  {0}_sink: process\n    file expected : text;\n    file observed : text;\n    variable l : line;\n    variable o : line;\n    variable found : boolean;\n    variable transfer : natural := 0;\n    variable errors : natural := 0;\n{1}  begin\n    {2} <= '0';\n    file_open(expected, VECTORS & \"/{0}.expected.txt\", read_mode);\n    file_open(observed, VECTORS & \"/{0}.out.txt\", write_mode);\n    next_vector(expected, l, found);\n    {0}_done <= not found;\n    wait until rising_edge(clk) and rst = '0';\n    {2} <= '1';\n    loop\n      wait until rising_edge(clk);\n      if {3} = '1' then\n        transfer := transfer + 1;\n        if not found then\n          report \"{0}: unexpected transfer \" & integer'image(transfer) severity error;\n          errors := errors + 1;\n        else\n",
+            self.name,
+            self.variables(),
+            self.ready,
+            self.valid
+        );
+        result.push_str(&self.read("          "));
+        let matches = self
+            .signals
+            .iter()
+            .map(|(variable, port, _)| format!("std_match({}, {})", port, variable))
+            .collect::<Vec<_>>();
+        if !matches.is_empty() {
+            result.push_str(&format!(
+                "          if not ({}) then\n            report \"{}: transfer \" & integer'image(transfer) & \" does not match the expected vector\" severity error;\n            errors := errors + 1;\n          end if;\n",
+                matches.join(" and "),
+                self.name
+            ));
+        }
+        result.push_str(&format!(
+            "          next_vector(expected, l, found);\n          {0}_done <= not found;\n        end if;\n        {0}_errors <= errors;\n",
+            self.name
+        ));
+        result.push_str(&self.write("        "));
+        result.push_str(
+            "        writeline(observed, o);\n      end if;\n    end loop;\n  end process;\n",
+        );
+        result
+    }
+}
+
+/// Generate a self-checking testbench for a streamlet of a library, see the
+/// [module documentation](self).
+///
+/// The testbench instantiates the canonical component of the streamlet with
+/// ports named according to a naming policy, with the default values of the
+/// parameters of the streamlet.
+pub fn generate_testbench(
+    library: &str,
+    streamlet: &Streamlet,
+    policy: &NamingPolicy,
+) -> Result<String> {
+    if streamlet.external().is_some() {
+        return Err(Error::BackEndError(format!(
+            "Streamlet {}.{} is implemented externally",
+            library,
+            streamlet.identifier()
+        )));
+    }
+    let ports = canonical_ports(streamlet, policy);
+    let streams = stream_ports(streamlet, policy);
+    let sinks = streams.iter().filter(|s| !s.input).collect::<Vec<_>>();
+    let entity = cat!(streamlet.identifier(), "tb");
+
+    // The signals of the ports of the streamlet, mapped to the ports of the
+    // component under test.
+    let dut = Component::new(
+        cat!(streamlet.identifier(), CANON_SUFFIX.unwrap()),
+        vec![],
+        ports.clone(),
+        None,
+    );
+    let mut mapping = PortMapping::from_component(&dut, "dut")?;
+    let mut signals = vec![];
+    for port in &ports {
+        let default = match port.identifier() {
+            "clk" => Some(StdLogicValue::Logic(false).into()),
+            "rst" => Some(StdLogicValue::Logic(true).into()),
+            _ => None,
+        };
+        let signal =
+            ObjectDeclaration::signal(port.identifier(), port.typ().clone().try_into()?, default);
+        mapping.map_port(port.identifier(), &signal)?;
+        signals.push(signal);
+    }
+    let boolean = ObjectType::Enum(EnumObject::new(
+        "boolean",
+        vec!["false".to_string(), "true".to_string()],
+    ));
+    for sink in &sinks {
+        signals.push(ObjectDeclaration::signal(
+            cat!(sink.name, "done"),
+            boolean.clone(),
+            Some(ValueAssignment::Enum("false".to_string()).into()),
+        ));
+        signals.push(ObjectDeclaration::signal(
+            cat!(sink.name, "errors"),
+            ObjectType::Natural,
+            Some(Natural::from(0u32).into()),
+        ));
+    }
+
+    // Inputs of the streamlet that are not driven by the processes of the streams
+    let mut driven = IndexSet::new();
+    driven.insert("clk".to_string());
+    driven.insert("rst".to_string());
+    for stream in &streams {
+        if stream.input {
+            driven.insert(stream.valid.clone());
+            driven.extend(stream.signals.iter().map(|(_, port, _)| port.clone()));
+        } else {
+            driven.insert(stream.ready.clone());
+        }
+    }
+    let mut statements: Vec<Statement> = vec![mapping.finish()?.into()];
+    for (port, signal) in ports.iter().zip(&signals) {
+        if port.mode() == PortMode::In && !driven.contains(port.identifier()) {
+            let assignment = match port.typ() {
+                Type::BitVec { .. } => {
+                    signal.assign(&BitVecValue::Others(StdLogicValue::Logic(false)))
+                }
+                _ => signal.assign(&StdLogicValue::Logic(false)),
+            }?;
+            statements.push(assignment.into());
+        }
+    }
+
+    let mut result = format!(
+        "library ieee;\nuse ieee.std_logic_1164.all;\nuse ieee.numeric_std.all;\n\nlibrary std;\nuse std.textio.all;\n\nlibrary work;\nuse work.{}.all;\n\n",
+        library
+    );
+    result.push_str(&format!(
+        "-- Self-checking testbench of streamlet {}.{}.\n-- Requires VHDL-2008, to read transfer vectors into std_logic_vector\n-- variables and to end the simulation with std.env.finish.\nentity {} is\n  generic (\n    VECTORS : string := \"vectors\";\n    CLOCK_PERIOD : time := 10 ns;\n    TIMEOUT : time := 1 ms\n  );\nend {};\n\n",
+        library,
+        streamlet.identifier(),
+        entity,
+        entity
+    ));
+
+    result.push_str(&format!("architecture behavioral of {} is\n", entity));
+    for signal in &signals {
+        result.push_str(&signal.declare("  ", ";\n")?);
+    }
+    result.push_str(concat!(
+        "\n",
+        "  -- Reads the next transfer vector of a file into l, skipping empty lines and comments.\n",
+        "  procedure next_vector(file vectors : text; l : inout line; found : out boolean) is\n",
+        "  begin\n",
+        "    found := false;\n",
+        "    while not endfile(vectors) loop\n",
+        "      readline(vectors, l);\n",
+        "      if l'length > 0 and l(l'left) /= '#' then\n",
+        "        found := true;\n",
+        "        return;\n",
+        "      end if;\n",
+        "    end loop;\n",
+        "  end procedure;\n",
+        "begin\n",
+        "  clk <= not clk after CLOCK_PERIOD / 2;\n",
+        "  rst <= '0' after 2 * CLOCK_PERIOD;\n\n",
+    ));
+    for statement in &statements {
+        result.push_str(&statement.declare("  ", ";\n")?);
+    }
+
+    for stream in &streams {
+        result.push('\n');
+        if stream.input {
+            result.push_str(&stream.source());
+        } else {
+            result.push_str(&stream.sink());
+        }
+    }
+
+    let done = match sinks.is_empty() {
+        true => "true".to_string(),
+        false => sinks
+            .iter()
+            .map(|sink| format!("{}_done", sink.name))
+            .collect::<Vec<_>>()
+            .join(" and "),
+    };
+    let errors = match sinks.is_empty() {
+        true => "0".to_string(),
+        false => sinks
+            .iter()
+            .map(|sink| format!("{}_errors", sink.name))
+            .collect::<Vec<_>>()
+            .join(" + "),
+    };
+    result.push_str(&format!(
+        "\n  check: process\n  begin\n    if not ({0}) then\n      wait until ({0}) for TIMEOUT;\n    end if;\n    assert {0} report \"Timed out waiting for the expected transfers\" severity failure;\n    -- Allow unexpected transfers to arrive.\n    wait for 10 * CLOCK_PERIOD;\n    assert {1} = 0 report \"{2} failed with \" & integer'image({1}) & \" mismatches\" severity failure;\n    report \"{2} passed\";\n    std.env.finish;\n  end process;\nend architecture behavioral;\n",
+        done,
+        errors,
+        entity
+    ));
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::nom::streamlet;
+
+    use super::*;
+
+    #[test]
+    fn testbench() -> Result<()> {
+        let (_, s) = streamlet(
+            "Streamlet top (a : in Stream<Bits<8>, d=1>, b : out Stream<Bits<4>, d=1, c=4>, c : in Bits<2>)",
+        )
+        .unwrap();
+        let tb = generate_testbench("lib", &s, &NamingPolicy::default())?;
+        assert!(tb.contains("use work.lib.all;"));
+        assert!(tb.contains("entity top_tb is"));
+        assert!(tb.contains("-- Requires VHDL-2008"));
+        assert!(tb.contains("  signal clk : std_logic := '0';\n  signal rst : std_logic := '1';\n"));
+        assert!(
+            tb.contains("  signal b_done : boolean := false;\n  signal b_errors : natural := 0;\n")
+        );
+        assert!(
+            tb.contains("  dut: top_com\n    port map(\n      clk => clk,\n      rst => rst,\n")
+        );
+        // Inputs are driven from vectors, outputs are compared
+        assert!(tb.contains("  a_source: process\n"));
+        assert!(tb.contains("file_open(vectors, VECTORS & \"/a.in.txt\", read_mode);"));
+        assert!(tb.contains("      a_data <= data;\n      a_last <= last;\n"));
+        assert!(tb.contains("  b_sink: process\n"));
+        assert!(tb.contains("file_open(expected, VECTORS & \"/b.expected.txt\", read_mode);"));
+        assert!(tb.contains(
+            "if not (std_match(b_data, data) and std_match(b_last, last) and std_match(b_strb, strb)) then"
+        ));
+        assert!(tb.contains("      if b_valid = '1' then\n"));
+        assert!(tb.contains(
+            "        write(o, b_data);\n        write(o, ' ');\n        write(o, b_last);\n"
+        ));
+        // Other inputs are driven low
+        assert!(tb.contains("  c <= (others => '0');\n"));
+        assert!(tb.contains("wait until (b_done) for TIMEOUT;"));
+        assert!(tb.contains("assert b_errors = 0 report \"top_tb failed with \""));
+        Ok(())
+    }
+}
@@ -0,0 +1,477 @@
+//! Identifier policies.
+//!
+//! Every [Name] follows the Tydi rules for names. An [IdentifierPolicy] adds
+//! the rules for identifiers of the targets that names are used in, i.e.
+//! their reserved words and a maximum length, such that names that are valid
+//! in Tydi but not in a target are rejected early. A lenient policy sanitizes
+//! names instead.
+//!
+//! Identifier policies decide which names are valid, whereas a
+//! [NamingPolicy](crate::NamingPolicy) decides how generated names are
+//! composed from them.
+//!
+//! Policies are stored in TOML format, e.g. in a project manifest:
+//!
+//! ```toml
+//! [identifiers]
+//! targets = ["vhdl", "verilog"]
+//! max-length = 32
+//! lenient = false
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Name, Result};
+
+/// Reserved words of VHDL-2008. VHDL is case-insensitive.
+const VHDL_RESERVED: &[&str] = &[
+    "abs",
+    "access",
+    "after",
+    "alias",
+    "all",
+    "and",
+    "architecture",
+    "array",
+    "assert",
+    "assume",
+    "assume_guarantee",
+    "attribute",
+    "begin",
+    "block",
+    "body",
+    "buffer",
+    "bus",
+    "case",
+    "component",
+    "configuration",
+    "constant",
+    "context",
+    "cover",
+    "default",
+    "disconnect",
+    "downto",
+    "else",
+    "elsif",
+    "end",
+    "entity",
+    "exit",
+    "fairness",
+    "file",
+    "for",
+    "force",
+    "function",
+    "generate",
+    "generic",
+    "group",
+    "guarded",
+    "if",
+    "impure",
+    "in",
+    "inertial",
+    "inout",
+    "is",
+    "label",
+    "library",
+    "linkage",
+    "literal",
+    "loop",
+    "map",
+    "mod",
+    "nand",
+    "new",
+    "next",
+    "nor",
+    "not",
+    "null",
+    "of",
+    "on",
+    "open",
+    "or",
+    "others",
+    "out",
+    "package",
+    "parameter",
+    "port",
+    "postponed",
+    "procedure",
+    "process",
+    "property",
+    "protected",
+    "pure",
+    "range",
+    "record",
+    "register",
+    "reject",
+    "release",
+    "rem",
+    "report",
+    "restrict",
+    "restrict_guarantee",
+    "return",
+    "rol",
+    "ror",
+    "select",
+    "sequence",
+    "severity",
+    "shared",
+    "signal",
+    "sla",
+    "sll",
+    "sra",
+    "srl",
+    "strong",
+    "subtype",
+    "then",
+    "to",
+    "transport",
+    "type",
+    "unaffected",
+    "units",
+    "until",
+    "use",
+    "variable",
+    "vmode",
+    "vprop",
+    "vunit",
+    "wait",
+    "when",
+    "while",
+    "with",
+    "xnor",
+    "xor",
+];
+
+/// Reserved words of Verilog-2005. Verilog is case-sensitive.
+const VERILOG_RESERVED: &[&str] = &[
+    "always",
+    "and",
+    "assign",
+    "automatic",
+    "begin",
+    "buf",
+    "bufif0",
+    "bufif1",
+    "case",
+    "casex",
+    "casez",
+    "cell",
+    "cmos",
+    "config",
+    "deassign",
+    "default",
+    "defparam",
+    "design",
+    "disable",
+    "edge",
+    "else",
+    "end",
+    "endcase",
+    "endconfig",
+    "endfunction",
+    "endgenerate",
+    "endmodule",
+    "endprimitive",
+    "endspecify",
+    "endtable",
+    "endtask",
+    "event",
+    "for",
+    "force",
+    "forever",
+    "fork",
+    "function",
+    "generate",
+    "genvar",
+    "highz0",
+    "highz1",
+    "if",
+    "ifnone",
+    "incdir",
+    "include",
+    "initial",
+    "inout",
+    "input",
+    "instance",
+    "integer",
+    "join",
+    "large",
+    "liblist",
+    "library",
+    "localparam",
+    "macromodule",
+    "medium",
+    "module",
+    "nand",
+    "negedge",
+    "nmos",
+    "nor",
+    "noshowcancelled",
+    "not",
+    "notif0",
+    "notif1",
+    "or",
+    "output",
+    "parameter",
+    "pmos",
+    "posedge",
+    "primitive",
+    "pull0",
+    "pull1",
+    "pulldown",
+    "pullup",
+    "pulsestyle_ondetect",
+    "pulsestyle_onevent",
+    "rcmos",
+    "real",
+    "realtime",
+    "reg",
+    "release",
+    "repeat",
+    "rnmos",
+    "rpmos",
+    "rtran",
+    "rtranif0",
+    "rtranif1",
+    "scalared",
+    "showcancelled",
+    "signed",
+    "small",
+    "specify",
+    "specparam",
+    "strong0",
+    "strong1",
+    "supply0",
+    "supply1",
+    "table",
+    "task",
+    "time",
+    "tran",
+    "tranif0",
+    "tranif1",
+    "tri",
+    "tri0",
+    "tri1",
+    "triand",
+    "trior",
+    "trireg",
+    "unsigned",
+    "use",
+    "uwire",
+    "vectored",
+    "wait",
+    "wand",
+    "weak0",
+    "weak1",
+    "while",
+    "wire",
+    "wor",
+    "xnor",
+    "xor",
+];
+
+/// A target language with its own rules for names.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentifierTarget {
+    /// VHDL, of which reserved words are case-insensitive.
+    Vhdl,
+    /// Verilog, of which reserved words are case-sensitive.
+    Verilog,
+}
+
+impl IdentifierTarget {
+    /// Returns true if a name is a reserved word of this target.
+    pub fn is_reserved(self, name: &str) -> bool {
+        match self {
+            IdentifierTarget::Vhdl => VHDL_RESERVED.contains(&name.to_ascii_lowercase().as_str()),
+            IdentifierTarget::Verilog => VERILOG_RESERVED.contains(&name),
+        }
+    }
+}
+
+impl fmt::Display for IdentifierTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IdentifierTarget::Vhdl => write!(f, "VHDL"),
+            IdentifierTarget::Verilog => write!(f, "Verilog"),
+        }
+    }
+}
+
+/// A policy for names that are valid identifiers in the targets they are used
+/// in, in addition to the Tydi rules for names.
+///
+/// The default policy only applies the Tydi rules.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct IdentifierPolicy {
+    /// The targets of which names cannot be reserved words.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    targets: Vec<IdentifierTarget>,
+    /// The maximum length of names.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_length: Option<usize>,
+    /// Whether invalid names are sanitized instead of rejected.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    lenient: bool,
+}
+
+impl IdentifierPolicy {
+    /// Construct a policy that only applies the Tydi rules for names.
+    pub fn new() -> Self {
+        IdentifierPolicy::default()
+    }
+
+    /// Returns this policy, also rejecting the reserved words of a target.
+    pub fn with_target(mut self, target: IdentifierTarget) -> Self {
+        if !self.targets.contains(&target) {
+            self.targets.push(target);
+        }
+        self
+    }
+
+    /// Returns this policy, also rejecting names longer than a maximum.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Returns this policy, sanitizing invalid names instead of rejecting them.
+    pub fn lenient(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
+    /// Returns the targets of which names cannot be reserved words.
+    pub fn targets(&self) -> impl Iterator<Item = IdentifierTarget> + '_ {
+        self.targets.iter().copied()
+    }
+
+    /// Returns the maximum length of names, if any.
+    pub fn max_length(&self) -> Option<usize> {
+        self.max_length
+    }
+
+    /// Returns true if invalid names are sanitized instead of rejected.
+    pub fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Returns true if this policy only applies the Tydi rules for names.
+    pub fn is_default(&self) -> bool {
+        *self == IdentifierPolicy::default()
+    }
+
+    /// Parse a policy in TOML format.
+    pub fn from_toml(input: &str) -> Result<Self> {
+        toml::from_str(input).map_err(|e| Error::ParsingError(e.to_string()))
+    }
+
+    /// Check a name against the target rules of this policy.
+    pub fn check(&self, name: &Name) -> Result<()> {
+        if let Some(target) = self.targets().find(|t| t.is_reserved(name)) {
+            return Err(Error::InvalidArgument(format!(
+                "name {} is a reserved word in {}",
+                name, target
+            )));
+        }
+        match self.max_length {
+            Some(max) if name.len() > max => Err(Error::InvalidArgument(format!(
+                "name {} is longer than {} characters",
+                name, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns a name that follows the Tydi rules and the target rules of
+    /// this policy, derived from an arbitrary string.
+    ///
+    /// Characters other than letters, digits and underscores are replaced by
+    /// underscores, redundant underscores are removed, names that start with
+    /// a digit are prefixed with `n`, names are truncated to the maximum
+    /// length, and reserved words get an `_i` suffix.
+    pub fn sanitize(&self, name: &str) -> String {
+        let mut result = String::new();
+        for c in name.chars() {
+            let c = if c.is_ascii_alphanumeric() { c } else { '_' };
+            if c != '_' || !(result.is_empty() || result.ends_with('_')) {
+                result.push(c);
+            }
+        }
+        if result.is_empty() {
+            result.push_str("unnamed");
+        }
+        if result.starts_with(|c: char| c.is_ascii_digit()) {
+            result.insert(0, 'n');
+        }
+        let truncate = |result: &mut String, max: usize| {
+            result.truncate(max);
+            while result.ends_with('_') {
+                result.pop();
+            }
+        };
+        truncate(&mut result, self.max_length.unwrap_or(usize::MAX));
+        if self.targets().any(|t| t.is_reserved(&result)) {
+            if let Some(max) = self.max_length {
+                truncate(&mut result, max.saturating_sub(2));
+            }
+            result.push_str("_i");
+        }
+        result
+    }
+
+    /// Construct a name according to this policy. Lenient policies sanitize
+    /// the name first, other policies return an error if it is invalid.
+    pub fn name(&self, name: impl Into<String>) -> Result<Name> {
+        let name = name.into();
+        let name = Name::try_new(if self.lenient {
+            self.sanitize(&name)
+        } else {
+            name
+        })?;
+        self.check(&name)?;
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifier_policy() -> Result<()> {
+        let tydi = IdentifierPolicy::new();
+        assert!(tydi.name("signal").is_ok());
+        assert!(tydi.name("a b").is_err());
+
+        let strict = IdentifierPolicy::new()
+            .with_target(IdentifierTarget::Vhdl)
+            .with_target(IdentifierTarget::Verilog)
+            .with_max_length(8);
+        assert!(strict.name("Signal").is_err());
+        assert!(strict.name("wire").is_err());
+        assert!(strict.name("Wire").is_ok());
+        assert!(strict.name("very_long_name").is_err());
+        assert_eq!(strict.name("data")?, Name::try_new("data")?);
+
+        let lenient = strict.clone().lenient();
+        assert_eq!(lenient.name("Signal")?, Name::try_new("Signal_i")?);
+        assert_eq!(lenient.name("1st value!")?, Name::try_new("n1st_val")?);
+        assert_eq!(lenient.name("__a--b__")?, Name::try_new("a_b")?);
+        assert_eq!(lenient.name("")?, Name::try_new("unnamed")?);
+
+        assert_eq!(
+            IdentifierPolicy::from_toml("targets = [\"vhdl\"]\nmax-length = 8\nlenient = true")?,
+            IdentifierPolicy::new()
+                .with_target(IdentifierTarget::Vhdl)
+                .with_max_length(8)
+                .lenient()
+        );
+        assert!(IdentifierPolicy::from_toml("targets = [\"chisel\"]").is_err());
+        Ok(())
+    }
+}
@@ -16,6 +16,10 @@
 //! - [`logical`]: for logical stream types as described in the Tydi specification.
 //! - [`design`]: for constructs that are not (yet) described in the Tydi specification,
 //!               such as streamlets.
+//! - [`value`]: for values carried by logical stream types.
+//! - [`transfer`]: for the transfers of values over physical streams.
+//! - [`vectors`]: for textual test vectors of the elements of physical streams.
+//! - [`vcd`]: for waveform traces of the signals of physical streams.
 //!
 //! ## Features
 //!
@@ -24,6 +28,10 @@
 //! - [`cli`] command-line-interface generator tool.
 //! - [`generator`] module for generation of HDL templates.
 //! - [`parser`] module with parser for Streamlet Definition Files.
+//! - `lsp` module with a language server for Streamlet Definition Files.
+//! - [`ffi`] module with a C-compatible interface, see `include/tydi.h`.
+//! - `fletcher` module to convert Fletcher schemas from and to streamlets.
+//! - [`wasm`] module with a JavaScript interface for WebAssembly builds.
 //!
 //! # Tools
 //!
@@ -65,8 +73,12 @@
 //! [`logical`]: ./logical/index.html
 //! [`generator`]: ./generator/index.html
 //! [`design`]: ./design/index.html
+//! [`value`]: ./value/index.html
+//! [`transfer`]: ./transfer/index.html
 //! [`cli`]: ./design/index.html
 //! [`parser`]: ./parser/index.html
+//! [`ffi`]: ./ffi/index.html
+//! [`wasm`]: ./wasm/index.html
 //! [`tydi` command-line-interface]: #tydi-command-line-interface
 
 #![doc(html_favicon_url = "https://abs-tudelft.github.io/tydi/tydi_logo.svg")]
@@ -79,12 +91,13 @@ use std::iter::FromIterator;
 use std::ops::Deref;
 use std::ops::Mul;
 use std::str::FromStr;
+use std::sync::Arc;
 
 // Root re-exports
 // TODO(mb): discuss
-pub use error::{Error, Result};
+pub use error::{Error, ErrorContext, Location, Result, ResultExt};
 pub use traits::{Document, Identify, Reverse, Reversed};
-pub use util::{Logger, UniqueKeyBuilder, UniquelyNamedBuilder};
+pub use util::{UniqueKeyBuilder, UniquelyNamedBuilder};
 
 // Crate utils
 pub(crate) mod util;
@@ -92,17 +105,28 @@ pub(crate) mod util;
 // Core
 pub mod design;
 mod error;
+pub mod identifier;
 pub mod logical;
 pub mod physical;
 mod traits;
+pub mod transfer;
+pub mod value;
+pub mod vcd;
+pub mod vectors;
 
 // Tools
+#[cfg(feature = "ffi")]
+pub mod ffi;
 #[cfg(feature = "generator")]
 pub mod generator;
+#[cfg(feature = "lsp")]
+pub mod lsp;
 #[cfg(feature = "parser")]
 pub mod parser;
 #[cfg(feature = "stdlib")]
 pub mod stdlib;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 // Types for positive and non-negative integers.
 
@@ -112,6 +136,12 @@ pub type Positive = std::num::NonZeroU32;
 pub type NonNegative = u32;
 /// Natural integer as defined by VHDL.
 pub type Natural = NonNegative;
+/// Positive integer for bit counts that may exceed the range of [`Positive`],
+/// e.g. wide, heavily multi-lane physical streams.
+pub type WidePositive = std::num::NonZeroU64;
+/// Non-negative integer for bit counts that may exceed the range of
+/// [`NonNegative`].
+pub type WideNonNegative = u64;
 /// Positive real.
 pub type PositiveReal = NonZeroReal<f64>;
 
@@ -160,8 +190,10 @@ where
 /// - The name does not start with a digit
 /// - The name does not contain double underscores
 ///
+/// Names are immutable and shared, such that cloning a name does not
+/// allocate.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Name(String);
+pub struct Name(Arc<str>);
 
 impl Name {
     /// Constructs a new name wrapper. Returns an error when the provided name
@@ -194,20 +226,30 @@ impl Name {
                 .to_string(),
             ))
         } else {
-            Ok(Name(name))
+            Ok(Name(name.into()))
         }
     }
+
+    /// Constructs a new name wrapper according to an
+    /// [identifier::IdentifierPolicy], which may add target-specific rules or
+    /// sanitize the name.
+    pub fn try_new_with(
+        name: impl Into<String>,
+        policy: &identifier::IdentifierPolicy,
+    ) -> Result<Self> {
+        policy.name(name)
+    }
 }
 
 impl From<Name> for String {
     fn from(name: Name) -> Self {
-        name.0
+        name.0.to_string()
     }
 }
 
 impl From<&Name> for String {
     fn from(name: &Name) -> Self {
-        name.0.clone()
+        name.0.to_string()
     }
 }
 
@@ -241,13 +283,13 @@ impl FromStr for Name {
 
 impl PartialEq<String> for Name {
     fn eq(&self, other: &String) -> bool {
-        &self.0 == other
+        *self.0 == **other
     }
 }
 
 impl PartialEq<str> for Name {
     fn eq(&self, other: &str) -> bool {
-        self.0 == other
+        *self.0 == *other
     }
 }
 
@@ -259,13 +301,15 @@ impl fmt::Display for Name {
 
 /// Type-safe path for names.
 ///
-/// Allows wrapping a set of valid names in a hierarchy.
+/// Allows wrapping a set of valid names in a hierarchy. Like names, path
+/// names are immutable and shared, such that cloning a path name does not
+/// allocate.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct PathName(Vec<Name>);
+pub struct PathName(Arc<[Name]>);
 
 impl PathName {
     pub(crate) fn new_empty() -> Self {
-        PathName(Vec::new())
+        PathName(Arc::new([]))
     }
 
     pub fn new(names: impl Iterator<Item = Name>) -> Self {
@@ -288,22 +332,24 @@ impl PathName {
     }
 
     pub fn push(&mut self, name: impl Into<Name>) {
-        self.0.push(name.into())
+        let mut result: Vec<Name> = Vec::with_capacity(self.len() + 1);
+        result.extend(self.0.iter().cloned());
+        result.push(name.into());
+        self.0 = result.into();
     }
 
     pub(crate) fn with_parents(&self, path: impl Into<PathName>) -> PathName {
         let parent = path.into();
-        let mut result: Vec<Name> = Vec::with_capacity(self.len() + parent.len());
-        result.extend(parent.0.into_iter());
-        result.extend(self.0.clone().into_iter());
-        PathName::new(result.into_iter())
+        if parent.is_empty() {
+            return self.clone();
+        }
+        parent.0.iter().chain(self.0.iter()).cloned().collect()
     }
 
     pub(crate) fn with_parent(&self, name: impl Into<Name>) -> PathName {
-        let mut result: Vec<Name> = Vec::with_capacity(self.len() + 1);
-        result.push(name.into());
-        result.extend(self.0.clone().into_iter());
-        PathName::new(result.into_iter())
+        std::iter::once(name.into())
+            .chain(self.0.iter().cloned())
+            .collect()
     }
 
     pub fn len(&self) -> usize {
@@ -314,35 +360,219 @@ impl PathName {
         self.0.last()
     }
 
+    /// Returns true if the names of the given PathName are the first names of
+    /// this PathName. Every PathName starts with the empty PathName.
+    pub fn starts_with(&self, prefix: &PathName) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+
+    /// Renders this PathName using the given naming policy.
+    pub fn render(&self, policy: &NamingPolicy) -> String {
+        policy.path(self)
+    }
+
     pub fn parent(&self) -> Option<PathName> {
         if self.is_empty() {
             None
         } else {
-            Some(PathName(self.0[..self.len() - 1].to_vec()))
+            Some(PathName(self.0[..self.len() - 1].into()))
         }
     }
 }
 
 impl fmt::Display for PathName {
+    /// Display the PathName using the default [NamingPolicy].
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut result = String::new();
-        let mut names = self.0.iter().map(|x| x.as_ref());
-        if let Some(x) = names.next() {
-            result.push_str(x);
-            names.for_each(|name| {
-                result.push_str("__");
-                result.push_str(name);
-            });
-        } else {
-            result.push_str("");
+        write!(f, "{}", NamingPolicy::default().path(self))
+    }
+}
+
+/// Policy for the names of signals and ports derived from path names.
+///
+/// The canonical name of a signal is `<prefix>_<path>_<signal>`, where the
+/// names of a path are joined with `__`. A NamingPolicy allows replacing
+/// both separators, flattening paths with another [Flattening] scheme, and
+/// adding fixed suffixes to the names of input and output ports.
+///
+/// # Examples
+///
+/// ```
+/// use tydi::{Flattening, NamingPolicy, PathName};
+/// use tydi::design::Mode;
+///
+/// let path = PathName::try_new(vec!["a", "b"])?;
+/// assert_eq!(NamingPolicy::default().join(&["x", &path.to_string(), "data"]), "x_a__b_data");
+///
+/// let policy = NamingPolicy::default()
+///     .with_path_separator("_p_")
+///     .with_port_suffixes("_i", "_o");
+/// let name = policy.join(&["x", &path.render(&policy), "data"]);
+/// assert_eq!(name, "x_a_p_b_data");
+/// assert_eq!(policy.port(name, Mode::In), "x_a_p_b_data_i");
+///
+/// let policy = NamingPolicy::default().with_flattening(Flattening::CamelCase);
+/// assert_eq!(path.render(&policy), "aB");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NamingPolicy {
+    /// Scheme to flatten path names.
+    flattening: Flattening,
+    /// Separator between the names of a path name.
+    path_separator: String,
+    /// Separator between prefix, path name and signal name.
+    separator: String,
+    /// Suffix of input port names.
+    input_suffix: String,
+    /// Suffix of output port names.
+    output_suffix: String,
+}
+
+impl Default for NamingPolicy {
+    fn default() -> Self {
+        NamingPolicy {
+            flattening: Flattening::default(),
+            path_separator: "__".to_string(),
+            separator: "_".to_string(),
+            input_suffix: String::new(),
+            output_suffix: String::new(),
+        }
+    }
+}
+
+impl NamingPolicy {
+    /// Returns this policy with the given scheme to flatten path names.
+    pub fn with_flattening(mut self, flattening: Flattening) -> Self {
+        self.flattening = flattening;
+        self
+    }
+
+    /// Returns this policy with the given separator between the names of
+    /// path names.
+    pub fn with_path_separator(mut self, separator: impl Into<String>) -> Self {
+        self.path_separator = separator.into();
+        self
+    }
+
+    /// Returns this policy with the given separator between the prefix, path
+    /// name and signal name.
+    pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Returns this policy with the given suffixes for input and output port
+    /// names.
+    pub fn with_port_suffixes(
+        mut self,
+        input: impl Into<String>,
+        output: impl Into<String>,
+    ) -> Self {
+        self.input_suffix = input.into();
+        self.output_suffix = output.into();
+        self
+    }
+
+    /// Returns the scheme to flatten path names.
+    pub fn flattening(&self) -> Flattening {
+        self.flattening
+    }
+
+    /// Returns the separator between the names of path names.
+    pub fn path_separator(&self) -> &str {
+        &self.path_separator
+    }
+
+    /// Returns the separator between the prefix, path name and signal name.
+    pub fn separator(&self) -> &str {
+        &self.separator
+    }
+
+    /// Renders a path name.
+    pub fn path(&self, path: &PathName) -> String {
+        match self.flattening {
+            Flattening::Separated => path
+                .0
+                .iter()
+                .map(|name| name.as_ref())
+                .collect::<Vec<&str>>()
+                .join(&self.path_separator),
+            Flattening::CamelCase => path
+                .0
+                .iter()
+                .enumerate()
+                .map(|(index, name)| {
+                    let mut chars = name.chars();
+                    match chars.next() {
+                        Some(first) if index > 0 => {
+                            first.to_uppercase().chain(chars).collect::<String>()
+                        }
+                        _ => name.to_string(),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Joins the non-empty parts of a name with the separator.
+    pub fn join(&self, parts: &[&str]) -> String {
+        parts
+            .iter()
+            .filter(|part| !part.is_empty())
+            .copied()
+            .collect::<Vec<&str>>()
+            .join(&self.separator)
+    }
+
+    /// Returns the name of a port with the given direction, with the suffix
+    /// of that direction applied.
+    pub fn port(&self, name: impl Into<String>, mode: design::Mode) -> String {
+        let name = name.into();
+        match mode {
+            design::Mode::In => name + &self.input_suffix,
+            design::Mode::Out => name + &self.output_suffix,
+        }
+    }
+}
+
+/// Scheme to flatten the names of a path name into a single name.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum Flattening {
+    /// Names are joined with the path separator, e.g. `a__b`.
+    #[default]
+    Separated,
+    /// Names after the first are capitalized and concatenated, e.g. `aB`.
+    /// The path separator is not used.
+    CamelCase,
+}
+
+impl FromStr for Flattening {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "separated" => Ok(Flattening::Separated),
+            "camel-case" => Ok(Flattening::CamelCase),
+            _ => Err(Error::InvalidArgument(format!(
+                "unknown flattening scheme: {}",
+                input
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Flattening {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Flattening::Separated => write!(f, "separated"),
+            Flattening::CamelCase => write!(f, "camel-case"),
         }
-        write!(f, "{}", result)
     }
 }
 
 impl AsRef<[Name]> for PathName {
     fn as_ref(&self) -> &[Name] {
-        self.0.as_slice()
+        &self.0
     }
 }
 
@@ -363,7 +593,7 @@ impl FromIterator<Name> for PathName {
 
 impl From<Name> for PathName {
     fn from(name: Name) -> Self {
-        PathName(vec![name])
+        PathName(Arc::new([name]))
     }
 }
 
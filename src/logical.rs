@@ -4,16 +4,19 @@
 //!
 //! [Reference]: https://abs-tudelft.github.io/tydi/specification/logical.html
 
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use std::{
     convert::{TryFrom, TryInto},
-    error,
+    error, fmt,
 };
 
 use indexmap::IndexMap;
 
 use crate::{
-    physical::{BitCount, Complexity, Fields, PhysicalStream},
+    physical::{check_sideband, BitCount, Complexity, Fields, PhysicalStream, Signal},
     util::log2_ceil,
     Error, Name, NonNegative, PathName, Positive, PositiveReal, Result, Reverse,
 };
@@ -59,6 +62,15 @@ impl FromStr for Direction {
     }
 }
 
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Direction::Forward => write!(f, "Forward"),
+            Direction::Reverse => write!(f, "Reverse"),
+        }
+    }
+}
+
 impl Reverse for Direction {
     /// Reverse this direction.
     ///
@@ -129,6 +141,17 @@ impl FromStr for Synchronicity {
     }
 }
 
+impl fmt::Display for Synchronicity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Synchronicity::Sync => write!(f, "Sync"),
+            Synchronicity::Flatten => write!(f, "Flatten"),
+            Synchronicity::Desync => write!(f, "Desync"),
+            Synchronicity::FlatDesync => write!(f, "FlatDesync"),
+        }
+    }
+}
+
 /// The stream-manipulating logical stream type.
 ///
 /// Defines a new physical stream.
@@ -140,7 +163,7 @@ pub struct Stream {
     ///
     /// Any logical stream type representing the data type carried by the
     /// logical stream.
-    data: Box<LogicalType>,
+    data: Arc<LogicalType>,
     /// Throughput ratio of the stream.
     ///
     /// Positive real number, representing the minimum number of elements that
@@ -175,7 +198,7 @@ pub struct Stream {
     /// An optional logical stream type consisting of only
     /// element-manipulating nodes, representing the user data carried by
     /// this logical stream.
-    user: Option<Box<LogicalType>>,
+    user: Option<Arc<LogicalType>>,
     /// Stream carries extra information.
     ///
     /// Keep specifies whether the stream carries "extra" information
@@ -185,6 +208,13 @@ pub struct Stream {
     /// to the rest of this specification; it is effectively optimized
     /// away. Setting keep to true simply overrides this behavior.
     keep: bool,
+    /// Custom sideband signals.
+    ///
+    /// Extra signals that are not covered by the specification, e.g. for
+    /// platform-specific extensions. These are added to the physical stream
+    /// of this stream as-is. A stream with sideband signals is never
+    /// optimized away.
+    sideband: Vec<Signal>,
 }
 
 impl Reverse for Stream {
@@ -211,20 +241,21 @@ impl Stream {
         keep: bool,
     ) -> Self {
         Stream {
-            data: Box::new(data),
+            data: Arc::new(data),
             throughput,
             dimensionality,
             synchronicity,
             complexity: complexity.into(),
             direction,
-            user: user.map(Box::new),
+            user: user.map(Arc::new),
             keep,
+            sideband: Vec::new(),
         }
     }
 
     pub fn new_basic(data: LogicalType) -> Self {
         Stream {
-            data: Box::new(data),
+            data: Arc::new(data),
             throughput: PositiveReal::new(1.).unwrap(),
             dimensionality: 0,
             synchronicity: Synchronicity::Sync,
@@ -232,6 +263,7 @@ impl Stream {
             direction: Direction::Forward,
             user: None,
             keep: false,
+            sideband: Vec::new(),
         }
     }
 
@@ -259,6 +291,59 @@ impl Stream {
         self.throughput
     }
 
+    /// Returns the complexity of this stream.
+    pub fn complexity(&self) -> &Complexity {
+        &self.complexity
+    }
+
+    /// Returns the logical type of the user data carried by this stream, if any.
+    pub fn user(&self) -> Option<&LogicalType> {
+        self.user.as_deref()
+    }
+
+    /// Returns true if this stream is kept even if it carries no data.
+    pub fn keep(&self) -> bool {
+        self.keep
+    }
+
+    /// Returns this stream with the provided custom sideband signals added
+    /// to its physical stream. Returns an error when a signal name is not a
+    /// valid name or collides with another signal of the physical stream.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::logical::Stream;
+    /// use tydi::physical::{Origin, Signal};
+    /// use tydi::Positive;
+    ///
+    /// let stream = Stream::new_basic(tydi::logical::LogicalType::try_new_bits(8)?)
+    ///     .with_sideband(vec![Signal::vec("id", Origin::Source, Positive::new(4).unwrap())])?;
+    /// assert_eq!(stream.sideband().len(), 1);
+    /// assert!(Stream::new_basic(tydi::logical::LogicalType::Null)
+    ///     .with_sideband(vec![Signal::bit("valid", Origin::Source)])
+    ///     .is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_sideband(mut self, signals: impl IntoIterator<Item = Signal>) -> Result<Self> {
+        self.sideband.extend(signals);
+        check_sideband(&self.sideband)?;
+        Ok(self)
+    }
+
+    /// Returns the custom sideband signals of this stream.
+    pub fn sideband(&self) -> &[Signal] {
+        &self.sideband
+    }
+
+    /// Returns the number of element lanes required to carry this stream,
+    /// i.e. the ceiling of its throughput ratio.
+    ///
+    /// [Reference](https://abs-tudelft.github.io/tydi/specification/physical.html#element-lanes)
+    pub fn element_lanes(&self) -> Positive {
+        Positive::new(self.throughput.get().ceil() as NonNegative).unwrap()
+    }
+
     /// Returns true if this stream is null i.e. it results in no signals.
     ///
     /// [Reference](https://abs-tudelft.github.io/tydi/specification/logical.html#null-detection-function)
@@ -266,6 +351,7 @@ impl Stream {
         self.data.is_null()
             && (self.user.is_some() && self.user.as_ref().unwrap().is_null())
             && !self.keep
+            && self.sideband.is_empty()
     }
 
     /// Set the throughput ratio of this stream.
@@ -297,7 +383,7 @@ impl From<Stream> for LogicalType {
 ///
 /// [Reference](https://abs-tudelft.github.io/tydi/specification/logical.html#group)
 #[derive(Debug, Clone, PartialEq)]
-pub struct Group(IndexMap<Name, LogicalType>);
+pub struct Group(Arc<IndexMap<Name, LogicalType>>);
 
 impl Group {
     /// Returns a new Group logical stream type. Returns an error when either
@@ -327,7 +413,7 @@ impl Group {
                 .map(|_| -> Result<()> { Err(Error::UnexpectedDuplicate) })
                 .transpose()?;
         }
-        Ok(Group(map))
+        Ok(Group(Arc::new(map)))
     }
 
     /// Returns an iterator over the fields of the Group.
@@ -349,7 +435,7 @@ impl From<Group> for LogicalType {
 ///
 /// [Reference](https://abs-tudelft.github.io/tydi/specification/logical.html#union)
 #[derive(Debug, Clone, PartialEq)]
-pub struct Union(IndexMap<Name, LogicalType>);
+pub struct Union(Arc<IndexMap<Name, LogicalType>>);
 
 impl Union {
     /// Returns a new Union logical stream type. Returns an error when either
@@ -379,7 +465,7 @@ impl Union {
                 .map(|_| -> Result<()> { Err(Error::UnexpectedDuplicate) })
                 .transpose()?;
         }
-        Ok(Union(map))
+        Ok(Union(Arc::new(map)))
     }
 
     /// Returns the tag name and width of this union.
@@ -388,9 +474,9 @@ impl Union {
         if self.0.len() > 1 {
             Some((
                 "tag".to_string(),
-                BitCount::new(log2_ceil(
-                    BitCount::new(self.0.len() as NonNegative).unwrap(),
-                ))
+                BitCount::new(
+                    log2_ceil(Positive::new(self.0.len() as NonNegative).unwrap()).into(),
+                )
                 .unwrap(),
             ))
         } else {
@@ -452,6 +538,96 @@ pub enum LogicalType {
     Stream(Stream),
 }
 
+impl fmt::Display for LogicalType {
+    /// Display a logical type in Streamlet Definition File syntax. Stream
+    /// parameters are only displayed if they differ from their default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::logical::{LogicalType, Stream};
+    ///
+    /// let typ = LogicalType::try_new_group(vec![
+    ///     ("a", LogicalType::try_new_bits(8)?),
+    ///     ("b", LogicalType::Null),
+    /// ])?;
+    /// assert_eq!(typ.to_string(), "Group<a: Bits<8>, b: Null>");
+    ///
+    /// let stream = LogicalType::from(Stream::new_basic(typ));
+    /// assert_eq!(stream.to_string(), "Stream<Group<a: Bits<8>, b: Null>>");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields = |f: &mut fmt::Formatter, fields: &IndexMap<Name, LogicalType>| {
+            fields
+                .iter()
+                .map(|(name, typ)| format!("{}: {}", name, typ))
+                .collect::<Vec<_>>()
+                .join(", ")
+                .fmt(f)
+        };
+        match self {
+            LogicalType::Null => write!(f, "Null"),
+            LogicalType::Bits(width) => write!(f, "Bits<{}>", width),
+            LogicalType::Group(Group(inner)) => {
+                write!(f, "Group<")?;
+                fields(f, inner)?;
+                write!(f, ">")
+            }
+            LogicalType::Union(Union(inner)) => {
+                write!(f, "Union<")?;
+                fields(f, inner)?;
+                write!(f, ">")
+            }
+            LogicalType::Stream(stream) => {
+                write!(f, "Stream<{}", stream.data)?;
+                if stream.throughput.get() != 1. {
+                    write!(f, ", t={}", stream.throughput.get())?;
+                }
+                if stream.dimensionality != 0 {
+                    write!(f, ", d={}", stream.dimensionality)?;
+                }
+                if stream.synchronicity != Synchronicity::default() {
+                    write!(f, ", s={}", stream.synchronicity)?;
+                }
+                if stream.complexity != Complexity::default() {
+                    write!(f, ", c={}", stream.complexity)?;
+                }
+                if stream.direction != Direction::default() {
+                    write!(f, ", r={}", stream.direction)?;
+                }
+                if let Some(user) = &stream.user {
+                    write!(f, ", u={}", user)?;
+                }
+                if stream.keep {
+                    write!(f, ", x=true")?;
+                }
+                write!(f, ">")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "parser")]
+impl FromStr for LogicalType {
+    type Err = Error;
+
+    /// Parse a logical type from Streamlet Definition File syntax.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::logical::LogicalType;
+    ///
+    /// let typ: LogicalType = "Stream<Bits<8>, d=1>".parse()?;
+    /// assert_eq!(typ.to_string(), "Stream<Bits<8>, d=1>");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    fn from_str(input: &str) -> Result<Self> {
+        crate::parser::nom::logical_type(input)
+    }
+}
+
 impl TryFrom<NonNegative> for LogicalType {
     type Error = Error;
 
@@ -570,6 +746,64 @@ impl LogicalType {
         }
     }
 
+    /// Returns true if this logical type is equal to the given type, or
+    /// contains it as (part of) the data or user type of a nested stream or
+    /// as a field of a group or union.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::logical::{LogicalType, Stream};
+    ///
+    /// let bits = LogicalType::try_new_bits(8)?;
+    /// let stream = LogicalType::from(Stream::new_basic(
+    ///     LogicalType::try_new_group(vec![("a", bits.clone())])?,
+    /// ));
+    /// assert!(stream.contains(&bits));
+    /// assert!(!stream.contains(&LogicalType::Null));
+    ///
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn contains(&self, other: &LogicalType) -> bool {
+        self == other
+            || match self {
+                LogicalType::Null | LogicalType::Bits(_) => false,
+                LogicalType::Group(Group(fields)) | LogicalType::Union(Union(fields)) => {
+                    fields.values().any(|typ| typ.contains(other))
+                }
+                LogicalType::Stream(stream) => {
+                    stream.data.contains(other)
+                        || stream.user.as_ref().is_some_and(|u| u.contains(other))
+                }
+            }
+    }
+
+    /// Returns the type of a field of this Group, or of a field of a nested
+    /// Group for path names of multiple names. The empty path name refers to
+    /// this type itself. Returns None if there is no such field.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::logical::LogicalType;
+    /// use tydi::PathName;
+    ///
+    /// let bits = LogicalType::try_new_bits(8)?;
+    /// let group = LogicalType::try_new_group(vec![
+    ///     ("a", LogicalType::try_new_group(vec![("b", bits.clone())])?),
+    /// ])?;
+    /// assert_eq!(group.field(&PathName::try_new(vec!["a", "b"])?), Some(&bits));
+    /// assert_eq!(group.field(&PathName::try_new(vec!["b"])?), None);
+    ///
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn field(&self, path: &PathName) -> Option<&LogicalType> {
+        path.0.iter().try_fold(self, |typ, name| match typ {
+            LogicalType::Group(Group(fields)) => fields.get(name),
+            _ => None,
+        })
+    }
+
     /// Returns true if and only if this logical stream does not result in any
     /// signals.
     ///
@@ -599,22 +833,23 @@ impl LogicalType {
                 if !element.is_null()
                     || (stream_in.user.is_some() && !stream_in.user.as_ref().unwrap().is_null())
                     || stream_in.keep
+                    || !stream_in.sideband.is_empty()
                 {
-                    streams.insert(
-                        PathName::new_empty(),
-                        // todo: add method
-                        Stream::new(
-                            element,
-                            stream_in.throughput,
-                            stream_in.dimensionality,
-                            stream_in.synchronicity,
-                            stream_in.complexity.clone(),
-                            stream_in.direction,
-                            stream_in.user.clone().map(|stream| *stream),
-                            stream_in.keep,
-                        )
-                        .into(),
+                    // todo: add method
+                    let mut element_stream = Stream::new(
+                        element,
+                        stream_in.throughput,
+                        stream_in.dimensionality,
+                        stream_in.synchronicity,
+                        stream_in.complexity.clone(),
+                        stream_in.direction,
+                        None,
+                        stream_in.keep,
                     );
+                    // The user type is shared rather than copied.
+                    element_stream.user = stream_in.user.clone();
+                    element_stream.sideband = stream_in.sideband.clone();
+                    streams.insert(PathName::new_empty(), element_stream.into());
                 }
 
                 streams.extend(rest.into_iter().map(|(name, stream)| match stream {
@@ -650,29 +885,26 @@ impl LogicalType {
                 streams: IndexMap::new(),
             },
             LogicalType::Group(Group(fields)) | LogicalType::Union(Union(fields)) => {
-                let signals = fields
-                    .into_iter()
-                    .map(|(name, stream)| (name.clone(), stream.split_streams().signals))
-                    .collect();
+                // Every field is split once; splitting it again for its
+                // streams would make splitting exponential in the depth of
+                // nested groups and unions.
+                let mut signals = IndexMap::with_capacity(fields.len());
+                let mut streams = IndexMap::new();
+                for (name, stream) in fields.iter() {
+                    let split = stream.split_streams();
+                    signals.insert(name.clone(), split.signals);
+                    streams.extend(split.streams.into_iter().map(|(path_name, stream_)| {
+                        (path_name.with_parent(name.clone()), stream_)
+                    }));
+                }
 
                 SplitStreams {
                     signals: match self {
-                        LogicalType::Group(_) => LogicalType::Group(Group(signals)),
-                        LogicalType::Union(_) => LogicalType::Union(Union(signals)),
+                        LogicalType::Group(_) => LogicalType::Group(Group(Arc::new(signals))),
+                        LogicalType::Union(_) => LogicalType::Union(Union(Arc::new(signals))),
                         _ => unreachable!(),
                     },
-                    streams: fields
-                        .into_iter()
-                        .map(|(name, stream)| {
-                            stream.split_streams().streams.into_iter().map(
-                                move |(mut path_name, stream_)| {
-                                    path_name.push(name.clone());
-                                    (path_name, stream_)
-                                },
-                            )
-                        })
-                        .flatten()
-                        .collect(),
+                    streams,
                 }
             }
         }
@@ -689,7 +921,7 @@ impl LogicalType {
         match self {
             LogicalType::Null | LogicalType::Stream(_) => fields,
             LogicalType::Bits(b) => {
-                fields.insert(PathName::new_empty(), *b).unwrap();
+                fields.insert(PathName::new_empty(), (*b).into()).unwrap();
                 fields
             }
             LogicalType::Group(Group(inner)) => {
@@ -707,9 +939,10 @@ impl LogicalType {
                     fields
                         .insert(
                             PathName::try_new(vec!["tag"]).unwrap(),
-                            BitCount::new(log2_ceil(
-                                BitCount::new(inner.len() as NonNegative).unwrap(),
-                            ))
+                            BitCount::new(
+                                log2_ceil(Positive::new(inner.len() as NonNegative).unwrap())
+                                    .into(),
+                            )
                             .unwrap(),
                         )
                         .unwrap();
@@ -747,14 +980,17 @@ impl LogicalType {
                         path_name,
                         PhysicalStream::new(
                             stream.data.fields(),
-                            Positive::new(stream.throughput.get().ceil() as NonNegative).unwrap(),
+                            stream.element_lanes(),
                             stream.dimensionality,
                             stream.complexity,
                             stream
                                 .user
                                 .map(|stream| stream.fields())
                                 .unwrap_or_else(Fields::new_empty),
-                        ),
+                        )
+                        // Sideband signals are checked when added to the stream.
+                        .with_sideband(stream.sideband)
+                        .unwrap(),
                     ),
                     _ => unreachable!(),
                 })
@@ -762,6 +998,68 @@ impl LogicalType {
         }
     }
 
+    /// Feeds the structure of this type into a hasher, consistently with
+    /// [LogicalType::same_structure].
+    fn hash_structure<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            LogicalType::Null => {}
+            LogicalType::Bits(width) => width.hash(state),
+            LogicalType::Group(Group(fields)) | LogicalType::Union(Union(fields)) => {
+                fields.len().hash(state);
+                for (name, typ) in fields.iter() {
+                    name.hash(state);
+                    typ.hash_structure(state);
+                }
+            }
+            LogicalType::Stream(stream) => {
+                stream.data.hash_structure(state);
+                stream.throughput.get().to_bits().hash(state);
+                stream.dimensionality.hash(state);
+                stream.complexity.major().hash(state);
+                stream.keep.hash(state);
+                stream.sideband.len().hash(state);
+                if let Some(user) = &stream.user {
+                    user.hash_structure(state);
+                }
+            }
+        }
+    }
+
+    /// Returns true if this type is equal to another type, including the
+    /// order of the fields of groups and unions, which the equality of
+    /// types ignores.
+    fn same_structure(&self, other: &LogicalType) -> bool {
+        let same_fields = |a: &IndexMap<Name, LogicalType>, b: &IndexMap<Name, LogicalType>| {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|((a, x), (b, y))| a == b && x.same_structure(y))
+        };
+        match (self, other) {
+            (LogicalType::Null, LogicalType::Null) => true,
+            (LogicalType::Bits(a), LogicalType::Bits(b)) => a == b,
+            (LogicalType::Group(Group(a)), LogicalType::Group(Group(b)))
+            | (LogicalType::Union(Union(a)), LogicalType::Union(Union(b))) => same_fields(a, b),
+            (LogicalType::Stream(a), LogicalType::Stream(b)) => {
+                a.data.same_structure(&b.data)
+                    && a.throughput == b.throughput
+                    && a.dimensionality == b.dimensionality
+                    && a.synchronicity == b.synchronicity
+                    && a.complexity == b.complexity
+                    && a.direction == b.direction
+                    && a.keep == b.keep
+                    && a.sideband == b.sideband
+                    && match (&a.user, &b.user) {
+                        (Some(a), Some(b)) => a.same_structure(b),
+                        (None, None) => true,
+                        _ => false,
+                    }
+            }
+            _ => false,
+        }
+    }
+
     pub fn compatible(&self, other: &LogicalType) -> bool {
         self == other
             || match other {
@@ -843,7 +1141,7 @@ impl ElementStream {
             LogicalType::Stream(stream) => match &*stream.data {
                 LogicalType::Null => fields,
                 LogicalType::Bits(b) => {
-                    fields.insert(self.path_name.clone(), *b).unwrap();
+                    fields.insert(self.path_name.clone(), (*b).into()).unwrap();
                     fields
                 }
                 LogicalType::Group(Group(inner)) => {
@@ -868,9 +1166,10 @@ impl ElementStream {
                                 PathName::try_new(vec!["tag"])
                                     .unwrap()
                                     .with_parents(self.path_name.clone()),
-                                BitCount::new(log2_ceil(
-                                    BitCount::new(inner.len() as NonNegative).unwrap(),
-                                ))
+                                BitCount::new(
+                                    log2_ceil(Positive::new(inner.len() as NonNegative).unwrap())
+                                        .into(),
+                                )
                                 .unwrap(),
                             )
                             .unwrap();
@@ -907,14 +1206,17 @@ impl From<ElementStream> for PhysicalStream {
         match element_stream.logical_type {
             LogicalType::Stream(stream) => PhysicalStream::new(
                 stream.data.fields(),
-                Positive::new(stream.throughput.get().ceil() as NonNegative).unwrap(),
+                stream.element_lanes(),
                 stream.dimensionality,
                 stream.complexity,
                 stream
                     .user
                     .map(|stream| stream.fields())
                     .unwrap_or_else(Fields::new_empty),
-            ),
+            )
+            // Sideband signals are checked when added to the stream.
+            .with_sideband(stream.sideband)
+            .unwrap(),
             _ => unreachable!(),
         }
     }
@@ -991,6 +1293,66 @@ impl SplitStreams {
     }
 }
 
+/// A logical type as a key of a [SynthesisCache]. Keys are only equal if
+/// their types have the same structure, see [LogicalType::same_structure].
+#[derive(Debug)]
+struct SynthesisKey(LogicalType);
+
+impl Hash for SynthesisKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash_structure(state)
+    }
+}
+
+impl PartialEq for SynthesisKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.same_structure(&other.0)
+    }
+}
+
+impl Eq for SynthesisKey {}
+
+/// The synthesized logical types of a project, keyed by the structure of
+/// the types.
+///
+/// Types with the same structure, e.g. of different interfaces, or of the
+/// canonical and fancy ports and the wrappers of one interface, are only
+/// synthesized once. Every [Project](crate::design::Project) has its own
+/// cache, which is shared by the interfaces of its libraries.
+#[derive(Debug, Default)]
+pub struct SynthesisCache {
+    streams: Mutex<HashMap<SynthesisKey, Arc<LogicalStream>>>,
+}
+
+impl SynthesisCache {
+    /// Constructs an empty cache.
+    pub fn new() -> Self {
+        SynthesisCache::default()
+    }
+
+    /// Returns the number of distinct types synthesized with this cache.
+    pub fn len(&self) -> usize {
+        self.streams.lock().unwrap().len()
+    }
+
+    /// Returns true if no types were synthesized with this cache.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the signals and physical streams of a logical type, which is
+    /// only synthesized if no type with the same structure was synthesized
+    /// with this cache before.
+    pub(crate) fn synthesize(&self, typ: &LogicalType) -> Arc<LogicalStream> {
+        let key = SynthesisKey(typ.clone());
+        if let Some(result) = self.streams.lock().unwrap().get(&key) {
+            return Arc::clone(result);
+        }
+        let result = Arc::new(typ.synthesize());
+        Arc::clone(self.streams.lock().unwrap().entry(key).or_insert(result))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) struct LogicalStream {
     signals: Fields,
@@ -998,7 +1360,6 @@ pub(crate) struct LogicalStream {
 }
 
 impl LogicalStream {
-    #[allow(dead_code)]
     pub fn signals(&self) -> impl Iterator<Item = (&PathName, &BitCount)> {
         self.signals.iter()
     }
@@ -1049,7 +1410,7 @@ pub(crate) mod tests {
 
         pub(crate) fn nested() -> LogicalType {
             LogicalType::from(Stream::new_basic(LogicalType::from(Stream {
-                data: Box::new(elements::prim(8)),
+                data: Arc::new(elements::prim(8)),
                 throughput: PositiveReal::new(1.).unwrap(),
                 dimensionality: 1,
                 synchronicity: Synchronicity::Sync,
@@ -1057,6 +1418,7 @@ pub(crate) mod tests {
                 direction: Direction::Forward,
                 user: None,
                 keep: false,
+                sideband: Vec::new(),
             })))
         }
     }
@@ -1102,6 +1464,71 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[test]
+    fn nested_synthesize() -> Result<()> {
+        // Splitting is linear in the depth of nested groups.
+        let depth = 64;
+        let mut typ: LogicalType =
+            Stream::new_basic(LogicalType::try_new_group(vec![("x", 4), ("y", 1)])?).into();
+        for _ in 0..depth {
+            typ = LogicalType::try_new_group(vec![("a", typ.clone()), ("b", 1.try_into()?)])?;
+        }
+        let logical_stream = typ.synthesize();
+        assert_eq!(logical_stream.signals().count(), depth);
+        assert_eq!(
+            logical_stream
+                .streams()
+                .map(|(path, _)| path.len())
+                .collect::<Vec<_>>(),
+            vec![depth]
+        );
+        assert_eq!(
+            logical_stream.streams().next().unwrap().1.data_bit_count(),
+            5
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn synthesis_cache() -> Result<()> {
+        let stream = |fields: Vec<(&str, u32)>| -> Result<LogicalType> {
+            Ok(Stream::new_basic(LogicalType::try_new_group(fields)?).into())
+        };
+        let cache = SynthesisCache::new();
+        let a = stream(vec![("x", 4), ("y", 1)])?;
+        let b = stream(vec![("x", 4), ("y", 1)])?;
+        assert!(Arc::ptr_eq(&cache.synthesize(&a), &cache.synthesize(&b)));
+        assert_eq!(cache.len(), 1);
+        // Other caches synthesize types again.
+        assert!(!Arc::ptr_eq(
+            &cache.synthesize(&a),
+            &SynthesisCache::new().synthesize(&a)
+        ));
+
+        // Types that only differ in the order of their fields are equal, but
+        // synthesize into different physical streams.
+        let c = stream(vec![("y", 1), ("x", 4)])?;
+        assert_eq!(a, c);
+        assert!(!Arc::ptr_eq(&cache.synthesize(&a), &cache.synthesize(&c)));
+        assert_eq!(cache.len(), 2);
+        let order = |typ: &LogicalType| {
+            cache
+                .synthesize(typ)
+                .streams()
+                .flat_map(|(_, stream)| stream.element_fields().keys().cloned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            order(&c),
+            vec![PathName::try_new(vec!["y"])?, PathName::try_new(vec!["x"])?]
+        );
+        assert_eq!(
+            order(&a),
+            vec![PathName::try_new(vec!["x"])?, PathName::try_new(vec!["y"])?]
+        );
+        Ok(())
+    }
+
     #[test]
     fn union() -> Result<()> {
         let b = LogicalType::try_new_group(vec![("x", 2), ("y", 2)])?;
@@ -1146,12 +1573,12 @@ pub(crate) mod tests {
                 .flatten()
                 .collect::<Vec<_>>(),
             vec![
-                (&PathName::try_new(vec!["tag"])?, &Positive::new(2).unwrap()),
+                (&PathName::try_new(vec!["tag"])?, &BitCount::new(2).unwrap()),
                 (
                     &PathName::try_new(vec!["union"])?,
-                    &Positive::new(3).unwrap()
+                    &BitCount::new(3).unwrap()
                 ),
-                (&PathName::new_empty(), &Positive::new(4).unwrap()),
+                (&PathName::new_empty(), &BitCount::new(4).unwrap()),
             ]
         );
         assert_eq!(
@@ -0,0 +1,551 @@
+//! Language server for Streamlet Definition Files.
+//!
+//! The language server is enabled by the `lsp` feature flag, and started with
+//! `tydi lsp`. It communicates with an editor over standard input and output
+//! using the [Language Server Protocol], and provides:
+//! - diagnostics for syntax errors, and for the problems found by the design
+//!   rule check, see [check](crate::design::check),
+//! - go to definition of imported libraries, named types, constants and
+//!   streamlets, and
+//! - hover information of interfaces and named types, showing their logical
+//!   type and the physical streams it is synthesized into.
+//!
+//! The libraries imported by a file are resolved to the Streamlet Definition
+//! Files with the same name in its directory, using the contents of files
+//! that are open in the editor where available.
+//!
+//! [Language Server Protocol]: https://microsoft.github.io/language-server-protocol/
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use lsp_server::{Connection, Message, Notification, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{GotoDefinition, HoverRequest, Request as _};
+use lsp_types::{
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents,
+    HoverParams, HoverProviderCapability, Location, MarkupContent, MarkupKind, OneOf, Position,
+    PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url,
+};
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::{check, Library, Project, Severity};
+use crate::logical::LogicalType;
+use crate::parser::format::{lex, Token};
+use crate::parser::nom::{describe, imports};
+use crate::{Error, Identify, Name, Result};
+
+/// Returns the position of an offset in a file.
+fn position(input: &str, offset: usize) -> Position {
+    let location = crate::parser::nom::Location::of(input, &input[offset..]);
+    Position::new(location.line as u32 - 1, location.column as u32 - 1)
+}
+
+/// Returns the offset of a position in a file, if it is in the file.
+fn offset(input: &str, position: Position) -> Option<usize> {
+    let start = input
+        .split_inclusive('\n')
+        .take(position.line as usize)
+        .map(str::len)
+        .sum::<usize>();
+    input[start..]
+        .char_indices()
+        .chain(std::iter::once((input.len() - start, '\n')))
+        .take_while(|(_, c)| *c != '\n')
+        .nth(position.character as usize)
+        .map(|(i, _)| start + i)
+}
+
+/// Returns the range of the text at an offset in a file.
+fn range(input: &str, offset: usize, len: usize) -> Range {
+    Range::new(position(input, offset), position(input, offset + len))
+}
+
+/// Returns the name at an offset in a file, and the offset at which it
+/// starts.
+fn name_at(input: &str, offset: usize) -> Option<(&str, usize)> {
+    let is_name = |c: char| c.is_alphanumeric() || c == '_';
+    let start = input[..offset].rfind(|c| !is_name(c)).map_or(0, |i| i + 1);
+    let end = input[offset..]
+        .find(|c| !is_name(c))
+        .map_or(input.len(), |i| offset + i);
+    if start < end {
+        Some((&input[start..end], start))
+    } else {
+        None
+    }
+}
+
+/// Returns the imports, named types, constants and streamlets declared in a
+/// file, as their keyword, name, and the offset of their name.
+fn declarations(input: &str) -> Vec<(&str, &str, usize)> {
+    let tokens = match lex(input) {
+        Ok(tokens) => tokens,
+        Err(_) => return vec![],
+    };
+    tokens
+        .iter()
+        .filter(|t| !matches!(t.token, Token::Comment(_)))
+        .collect::<Vec<_>>()
+        .windows(2)
+        .filter_map(|pair| match (pair[0].token, pair[1].token) {
+            (Token::Word(keyword), Token::Word(name))
+                if ["import", "type", "const", "Streamlet"].contains(&keyword) =>
+            {
+                Some((keyword, name, input.len() - pair[1].rest.len()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Describes a logical type and the physical streams it is synthesized into
+/// with the cache of a project, as Markdown.
+fn describe_type(header: &str, name: &str, typ: &LogicalType, project: &Project) -> String {
+    let synthesized = project.synthesis().synthesize(typ);
+    let mut result = format!("```\n{}\n```\n", header);
+    let path = |path: String| {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            path
+        }
+    };
+    for (path_name, bits) in synthesized.signals() {
+        result.push_str(&format!(
+            "\n- signal `{}`: {} bits",
+            path(path_name.to_string()),
+            bits
+        ));
+    }
+    for (path_name, stream) in synthesized.streams() {
+        result.push_str(&format!(
+            "\n- stream `{}`: {} data bits, {} element lanes, d={}, c={}, {} user bits",
+            path(path_name.to_string()),
+            stream.data_bit_count(),
+            stream.element_lanes(),
+            stream.dimensionality(),
+            stream.complexity(),
+            stream.user_bit_count()
+        ));
+    }
+    result
+}
+
+/// The files that are open in the editor.
+#[derive(Clone, Debug, Default)]
+pub struct Documents {
+    open: HashMap<PathBuf, String>,
+}
+
+impl Documents {
+    /// Construct an empty set of open files.
+    pub fn new() -> Self {
+        Documents::default()
+    }
+
+    /// Open a file in the editor, or change its contents.
+    pub fn open(&mut self, path: impl Into<PathBuf>, text: impl Into<String>) {
+        self.open.insert(path.into(), text.into());
+    }
+
+    /// Close a file in the editor.
+    pub fn close(&mut self, path: &Path) {
+        self.open.remove(path);
+    }
+
+    /// Returns the contents of a file, from the editor if it is open.
+    fn read(&self, path: &Path) -> Option<String> {
+        self.open
+            .get(path)
+            .cloned()
+            .or_else(|| std::fs::read_to_string(path).ok())
+    }
+
+    /// Returns the library name of a file.
+    fn key(path: &Path) -> Option<Name> {
+        Name::try_new(path.file_stem()?.to_str()?).ok()
+    }
+
+    /// Returns the paths and contents of the files of the libraries imported
+    /// by a file, directly or indirectly, that exist.
+    fn imported_files(&self, path: &Path, input: &str) -> Vec<(Name, PathBuf, String)> {
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut result = vec![];
+        let mut visited = HashSet::new();
+        let mut pending = imports(input).map(|(_, i)| i).unwrap_or_default();
+        while let Some(import) = pending.pop() {
+            if !visited.insert(import.clone()) {
+                continue;
+            }
+            let path = dir.join(format!("{}.sdf", import));
+            if let Some(text) = self.read(&path) {
+                pending.extend(imports(&text).map(|(_, i)| i).unwrap_or_default());
+                result.push((import, path, text));
+            }
+        }
+        result
+    }
+
+    /// Returns a project of the libraries imported by a file, directly or
+    /// indirectly, that exist and are valid.
+    fn imported(&self, path: &Path, input: &str) -> Project {
+        let mut project = Project::new(Name::try_new("lsp").unwrap());
+        let mut pending = self.imported_files(path, input);
+        // Parse libraries after the libraries they import.
+        while let Some(index) = pending.iter().position(|(_, _, text)| {
+            imports(text).map_or(true, |(_, imports)| {
+                imports
+                    .iter()
+                    .all(|i| !pending.iter().any(|(key, _, _)| key == i))
+            })
+        }) {
+            let (key, _, text) = pending.remove(index);
+            if let Ok(Ok(library)) =
+                Library::parse_str(key, &text, |imports| project.imported_types(imports))
+            {
+                let _ = project.add_lib(library);
+            }
+        }
+        project
+    }
+
+    /// Returns the diagnostics of a file: its syntax errors, or the problems
+    /// found by the design rule check of its library and the libraries it
+    /// imports.
+    pub fn diagnostics(&self, path: &Path) -> Vec<Diagnostic> {
+        let input = self.read(path).unwrap_or_default();
+        let key = match Documents::key(path) {
+            Some(key) => key,
+            None => return vec![],
+        };
+        let diagnostic = |range, severity, message| Diagnostic {
+            range,
+            severity: Some(severity),
+            source: Some("tydi".to_string()),
+            message,
+            ..Default::default()
+        };
+        let start = Range::default();
+
+        let mut project = self.imported(path, &input);
+        let library = match Library::parse_str(key.clone(), &input, |imports| {
+            project.imported_types(imports)
+        }) {
+            Ok(Ok(library)) => library,
+            Ok(Err(errors)) => {
+                return errors
+                    .into_iter()
+                    .map(|(rest, expected)| {
+                        let offset = input.len() - rest.len();
+                        let len = name_at(&input, offset).map_or(1, |(name, _)| name.len());
+                        diagnostic(
+                            range(&input, offset, len.min(rest.len())),
+                            DiagnosticSeverity::ERROR,
+                            describe(rest, &expected),
+                        )
+                    })
+                    .collect()
+            }
+            Err(e) => return vec![diagnostic(start, DiagnosticSeverity::ERROR, e.to_string())],
+        };
+        if let Err(e) = project.add_lib(library) {
+            return vec![diagnostic(start, DiagnosticSeverity::ERROR, e.to_string())];
+        }
+
+        // Report the problems that involve this library at the first
+        // declaration they mention.
+        let declarations = declarations(&input);
+        check(&project)
+            .into_iter()
+            .filter_map(|d| {
+                let words = d
+                    .message()
+                    .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .collect::<Vec<_>>();
+                if !words.contains(&key.as_ref()) {
+                    return None;
+                }
+                let range = words
+                    .iter()
+                    .find_map(|w| declarations.iter().find(|(_, name, _)| name == w))
+                    .map_or(start, |(_, name, offset)| {
+                        range(&input, *offset, name.len())
+                    });
+                let severity = match d.severity() {
+                    Severity::Error => DiagnosticSeverity::ERROR,
+                    Severity::Warning => DiagnosticSeverity::WARNING,
+                };
+                Some(diagnostic(range, severity, d.message().to_string()))
+            })
+            .collect()
+    }
+
+    /// Returns the location of the declaration of the name at a position in
+    /// a file, if any.
+    pub fn definition(&self, path: &Path, position: Position) -> Option<Location> {
+        let input = self.read(path)?;
+        let (name, _) = name_at(&input, offset(&input, position)?)?;
+        let location =
+            |path: &Path, range| Some(Location::new(Url::from_file_path(path).ok()?, range));
+
+        let imported = self.imported_files(path, &input);
+        if let Some((_, path, _)) = imported.iter().find(|(key, _, _)| **key == *name) {
+            return location(path, Range::default());
+        }
+        std::iter::once((path.to_path_buf(), input.clone()))
+            .chain(imported.into_iter().map(|(_, path, text)| (path, text)))
+            .find_map(|(path, text)| {
+                declarations(&text)
+                    .into_iter()
+                    .find(|(keyword, n, _)| *keyword != "import" && *n == name)
+                    .map(|(_, _, offset)| (path, range(&text, offset, name.len())))
+            })
+            .and_then(|(path, range)| location(&path, range))
+    }
+
+    /// Returns the logical type and physical streams of the interface or
+    /// named type at a position in a file, if any, as Markdown.
+    pub fn hover(&self, path: &Path, position: Position) -> Option<String> {
+        let input = self.read(path)?;
+        let offset = offset(&input, position)?;
+        let (name, _) = name_at(&input, offset)?;
+        let project = self.imported(path, &input);
+        let library = Library::parse_str(Documents::key(path)?, &input, |imports| {
+            project.imported_types(imports)
+        })
+        .ok()?
+        .ok()?;
+
+        let key = Name::try_new(name).ok()?;
+        if let Ok(typ) = library.get_type(&key) {
+            return Some(describe_type(
+                &format!("type {} = {}", name, typ),
+                name,
+                typ,
+                &project,
+            ));
+        }
+        if let Some(typ) = project
+            .imported_types(&library.imports().cloned().collect::<Vec<_>>())
+            .ok()?
+            .get(&key)
+        {
+            return Some(describe_type(
+                &format!("type {} = {}", name, typ),
+                name,
+                typ,
+                &project,
+            ));
+        }
+        // Interfaces of the streamlet declared before the position.
+        let (_, streamlet, _) = declarations(&input)
+            .into_iter()
+            .rev()
+            .find(|(keyword, _, start)| *keyword == "Streamlet" && *start < offset)?;
+        let streamlet = library.get_streamlet(Name::try_new(streamlet).ok()?).ok()?;
+        let interface = streamlet.interfaces().find(|i| i.identifier() == name)?;
+        Some(describe_type(
+            &format!("{} : {} {}", name, interface.mode(), interface.typ()),
+            name,
+            &interface.typ(),
+            &project,
+        ))
+    }
+}
+
+fn error(e: impl std::fmt::Display) -> Error {
+    Error::CLIError(format!("language server: {}", e))
+}
+
+/// Run the language server on standard input and output, until the editor
+/// shuts it down.
+pub fn run() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        ..Default::default()
+    };
+    connection
+        .initialize(serde_json::to_value(capabilities).map_err(error)?)
+        .map_err(error)?;
+
+    let mut documents = Documents::new();
+    let publish = |documents: &Documents, path: &Path| -> Result<()> {
+        let params = PublishDiagnosticsParams::new(
+            Url::from_file_path(path).map_err(|_| error("invalid path"))?,
+            if documents.open.contains_key(path) {
+                documents.diagnostics(path)
+            } else {
+                vec![]
+            },
+            None,
+        );
+        connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                PublishDiagnostics::METHOD.to_string(),
+                params,
+            )))
+            .map_err(error)
+    };
+    let path = |uri: &Url| uri.to_file_path().map_err(|_| error("invalid URI"));
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request).map_err(error)? {
+                    break;
+                }
+                let response = match request.method.as_str() {
+                    GotoDefinition::METHOD => {
+                        let params: GotoDefinitionParams =
+                            serde_json::from_value(request.params).map_err(error)?;
+                        let position = params.text_document_position_params;
+                        Response::new_ok(
+                            request.id,
+                            documents
+                                .definition(&path(&position.text_document.uri)?, position.position)
+                                .map(GotoDefinitionResponse::Scalar),
+                        )
+                    }
+                    HoverRequest::METHOD => {
+                        let params: HoverParams =
+                            serde_json::from_value(request.params).map_err(error)?;
+                        let position = params.text_document_position_params;
+                        Response::new_ok(
+                            request.id,
+                            documents
+                                .hover(&path(&position.text_document.uri)?, position.position)
+                                .map(|value| Hover {
+                                    contents: HoverContents::Markup(MarkupContent {
+                                        kind: MarkupKind::Markdown,
+                                        value,
+                                    }),
+                                    range: None,
+                                }),
+                        )
+                    }
+                    method => Response::new_err(
+                        request.id,
+                        lsp_server::ErrorCode::MethodNotFound as i32,
+                        format!("unsupported method: {}", method),
+                    ),
+                };
+                connection
+                    .sender
+                    .send(Message::Response(response))
+                    .map_err(error)?;
+            }
+            Message::Notification(notification) => match notification.method.as_str() {
+                DidOpenTextDocument::METHOD => {
+                    let params: DidOpenTextDocumentParams =
+                        serde_json::from_value(notification.params).map_err(error)?;
+                    let file = path(&params.text_document.uri)?;
+                    documents.open(file.clone(), params.text_document.text);
+                    publish(&documents, &file)?;
+                }
+                DidChangeTextDocument::METHOD => {
+                    let params: DidChangeTextDocumentParams =
+                        serde_json::from_value(notification.params).map_err(error)?;
+                    let file = path(&params.text_document.uri)?;
+                    if let Some(change) = params.content_changes.into_iter().last() {
+                        documents.open(file.clone(), change.text);
+                    }
+                    publish(&documents, &file)?;
+                }
+                DidCloseTextDocument::METHOD => {
+                    let params: DidCloseTextDocumentParams =
+                        serde_json::from_value(notification.params).map_err(error)?;
+                    let file = path(&params.text_document.uri)?;
+                    documents.close(&file);
+                    publish(&documents, &file)?;
+                }
+                // Saving a file may change the imports of other open files.
+                DidSaveTextDocument::METHOD => {
+                    for file in documents.open.keys() {
+                        publish(&documents, file)?;
+                    }
+                }
+                _ => (),
+            },
+            Message::Response(_) => (),
+        }
+    }
+    io_threads.join()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_server() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let types = tmpdir.path().join("types.sdf");
+        let top = tmpdir.path().join("top.sdf");
+        std::fs::write(&types, "type Byte = Stream<Bits<8>, d=1>;\n")?;
+        let mut documents = Documents::new();
+        documents.open(
+            &top,
+            "import types;\n\
+             type Pair = Group<a: Bits<1>, b: Bits<2>>;\n\
+             Streamlet x (\n  a : in Byte,\n  b : out Pair\n)\n",
+        );
+        assert!(documents.diagnostics(&top).is_empty());
+
+        // Go to the named type of an imported library.
+        let location = documents.definition(&top, Position::new(3, 10)).unwrap();
+        assert_eq!(location.uri, Url::from_file_path(&types).unwrap());
+        assert_eq!(location.range, range("type Byte", 5, 4));
+        assert_eq!(
+            documents
+                .definition(&top, Position::new(4, 12))
+                .unwrap()
+                .range,
+            Range::new(Position::new(1, 5), Position::new(1, 9))
+        );
+        assert_eq!(
+            documents.definition(&top, Position::new(0, 9)).unwrap().uri,
+            Url::from_file_path(&types).unwrap()
+        );
+
+        let hover = documents.hover(&top, Position::new(3, 2)).unwrap();
+        assert!(hover.starts_with("```\na : in Stream<Bits<8>, d=1>\n```\n"));
+        assert!(hover.contains("- stream `a`: 8 data bits, 1 element lanes, d=1, c=4, 0 user bits"));
+        assert_eq!(
+            documents.hover(&top, Position::new(1, 6)).unwrap(),
+            "```\ntype Pair = Group<a: Bits<1>, b: Bits<2>>\n```\n\n\
+             - signal `a`: 1 bits\n- signal `b`: 2 bits"
+        );
+
+        documents.open(
+            &top,
+            "import types;\nStreamlet x (a : in Byte b : out Null)\n",
+        );
+        let diagnostics = documents.diagnostics(&top);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "expected ')', found \"b\"");
+        assert_eq!(diagnostics[0].range.start, Position::new(1, 25));
+
+        documents.open(&top, "import other;\nStreamlet x (a : in Null)\n");
+        let diagnostics = documents.diagnostics(&top);
+        assert_eq!(
+            diagnostics[0].message,
+            "library top imports unknown library other"
+        );
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(0, 7), Position::new(0, 12))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,584 @@
+//! Formatter for Streamlet Definition Files.
+//!
+//! [format] lays out a Streamlet Definition File like
+//! [Library::to_sdf](crate::design::Library::to_sdf) does: one import or
+//! declaration per line, one interface per line, canonical spacing within
+//! types and expressions, and stream parameters in canonical order. Unlike
+//! printing a parsed library, formatting keeps the comments, named types,
+//! constants and expressions of the file as written.
+
+use crate::parser::nom::report;
+use crate::{Error, Result};
+
+/// The order in which the parameters of a stream are formatted.
+const STREAM_PARAMETERS: [&str; 7] = ["t", "d", "s", "c", "r", "u", "x"];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Token<'a> {
+    /// A name, keyword or number.
+    Word(&'a str),
+    Punct(char),
+    Comment(&'a str),
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Lexed<'a> {
+    pub(crate) token: Token<'a>,
+    /// The number of line breaks preceding the token.
+    pub(crate) breaks: usize,
+    /// The input starting at the token.
+    pub(crate) rest: &'a str,
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.'
+}
+
+/// Splits the input into tokens. Comments that precede a `,` or `;` are
+/// moved after it, such that they stay on the line they belong to.
+pub(crate) fn lex(input: &str) -> Result<Vec<Lexed>> {
+    let mut result = vec![];
+    let mut rest = input;
+    let mut breaks = 0;
+    loop {
+        let trimmed = rest.trim_start();
+        breaks += rest[..rest.len() - trimmed.len()].matches('\n').count();
+        rest = trimmed;
+        let c = match rest.chars().next() {
+            Some(c) => c,
+            None => break,
+        };
+        let (len, token) = if rest.starts_with("//") || c == '#' {
+            let len = rest.find('\n').unwrap_or(rest.len());
+            (len, Token::Comment(rest[..len].trim_end()))
+        } else if rest.starts_with("/*") {
+            let len = rest.find("*/").map(|i| i + 2).ok_or_else(|| {
+                Error::ParsingError(report("", input, &input[input.len()..], "\"*/\""))
+            })?;
+            (len, Token::Comment(&rest[..len]))
+        } else if is_word(c) {
+            let len = rest.find(|c| !is_word(c)).unwrap_or(rest.len());
+            (len, Token::Word(&rest[..len]))
+        } else if "()<>[],:;=+-*/".contains(c) {
+            (1, Token::Punct(c))
+        } else {
+            return Err(Error::ParsingError(report("", input, rest, "token")));
+        };
+        result.push(Lexed {
+            token,
+            breaks,
+            rest,
+        });
+        breaks = 0;
+        rest = &rest[len..];
+    }
+
+    for i in 0..result.len() {
+        if let Token::Punct(',') | Token::Punct(';') = result[i].token {
+            let mut first = i;
+            while first > 0 && matches!(result[first - 1].token, Token::Comment(_)) {
+                first -= 1;
+            }
+            if first < i {
+                let mut punct = result.remove(i);
+                punct.breaks = 0;
+                result.insert(first, punct);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Returns whether nothing but indentation was written to the current line.
+fn at_line_start(out: &str) -> bool {
+    let trimmed = out.trim_end_matches(' ');
+    trimmed.is_empty() || trimmed.ends_with('\n')
+}
+
+/// Appends text, without leading spaces at the start of a line.
+fn push(out: &mut String, text: &str) {
+    if at_line_start(out) {
+        out.push_str(text.trim_start());
+    } else {
+        out.push_str(text);
+    }
+}
+
+struct Formatter<'a> {
+    input: &'a str,
+    tokens: Vec<Lexed<'a>>,
+    pos: usize,
+}
+
+impl<'a> Formatter<'a> {
+    /// Returns the next token that is not a comment.
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens[self.pos..]
+            .iter()
+            .map(|t| t.token)
+            .find(|t| !matches!(t, Token::Comment(_)))
+    }
+
+    fn error(&self, expected: &str) -> Error {
+        let rest = self
+            .tokens
+            .get(self.pos)
+            .map_or(&self.input[self.input.len()..], |t| t.rest);
+        Error::ParsingError(report("", self.input, rest, expected))
+    }
+
+    /// Writes the comments at the current position within a line, breaking
+    /// the line after every comment and continuing with the indentation.
+    fn inline_comments(&mut self, out: &mut String, indent: &str) {
+        while let Some(Lexed {
+            token: Token::Comment(comment),
+            breaks,
+            ..
+        }) = self.tokens.get(self.pos).copied()
+        {
+            self.pos += 1;
+            if !at_line_start(out) {
+                if breaks == 0 {
+                    out.truncate(out.trim_end_matches(' ').len());
+                    out.push(' ');
+                } else {
+                    out.push('\n');
+                    out.push_str(indent);
+                }
+            }
+            out.push_str(comment);
+            out.push('\n');
+            out.push_str(indent);
+        }
+    }
+
+    /// Writes the comments on the current line of the input, if any.
+    fn trailing_comments(&mut self, out: &mut String) {
+        while let Some(Lexed {
+            token: Token::Comment(comment),
+            breaks: 0,
+            ..
+        }) = self.tokens.get(self.pos).copied()
+        {
+            self.pos += 1;
+            out.push(' ');
+            out.push_str(comment);
+        }
+    }
+
+    /// Writes the comments at the current position on lines of their own,
+    /// keeping single blank lines between them.
+    fn line_comments(&mut self, out: &mut String, indent: &str) {
+        let mut first = true;
+        while let Some(Lexed {
+            token: Token::Comment(comment),
+            breaks,
+            ..
+        }) = self.tokens.get(self.pos).copied()
+        {
+            self.pos += 1;
+            if breaks > 1 && !first {
+                out.push('\n');
+            }
+            out.push_str(indent);
+            out.push_str(comment);
+            out.push('\n');
+            first = false;
+        }
+    }
+
+    /// Consumes the next token, writing the comments preceding it.
+    fn take(&mut self, out: &mut String, indent: &str) -> Option<Token<'a>> {
+        self.inline_comments(out, indent);
+        let token = self.tokens.get(self.pos).map(|t| t.token);
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, out: &mut String, indent: &str, c: char) -> Result<()> {
+        if self.peek() == Some(Token::Punct(c)) {
+            self.take(out, indent);
+            Ok(())
+        } else {
+            self.inline_comments(out, indent);
+            Err(self.error(&format!("'{}'", c)))
+        }
+    }
+
+    fn word(&mut self, out: &mut String, indent: &str, expected: &str) -> Result<&'a str> {
+        match self.peek() {
+            Some(Token::Word(word)) => {
+                self.take(out, indent);
+                Ok(word)
+            }
+            _ => {
+                self.inline_comments(out, indent);
+                Err(self.error(expected))
+            }
+        }
+    }
+
+    /// Formats a natural number expression, or a single stream parameter
+    /// value.
+    fn expr(&mut self, out: &mut String, indent: &str) -> Result<()> {
+        let mut depth = 0;
+        let mut operand = true;
+        loop {
+            match self.peek() {
+                Some(Token::Word(word)) if operand => {
+                    self.take(out, indent);
+                    push(out, word);
+                    operand = false;
+                }
+                Some(Token::Punct('(')) if operand => {
+                    self.take(out, indent);
+                    push(out, "(");
+                    depth += 1;
+                }
+                Some(Token::Punct(')')) if !operand && depth > 0 => {
+                    self.take(out, indent);
+                    push(out, ")");
+                    depth -= 1;
+                }
+                Some(Token::Punct(op)) if !operand && "+-*/".contains(op) => {
+                    self.take(out, indent);
+                    push(out, &format!(" {} ", op));
+                    operand = true;
+                }
+                _ if operand || depth > 0 => {
+                    self.inline_comments(out, indent);
+                    return Err(self.error(if operand { "number" } else { "')'" }));
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn typ(&mut self, out: &mut String, indent: &str) -> Result<()> {
+        let word = self.word(out, indent, "logical type")?;
+        push(out, word);
+        match word {
+            "Stream" => self.stream(out, indent),
+            "Group" | "Union" => {
+                self.expect(out, indent, '<')?;
+                push(out, "<");
+                while let Some(Token::Word(_)) = self.peek() {
+                    let field = self.word(out, indent, "field name")?;
+                    push(out, field);
+                    self.expect(out, indent, ':')?;
+                    push(out, ": ");
+                    self.typ(out, indent)?;
+                    if self.peek() != Some(Token::Punct(',')) {
+                        break;
+                    }
+                    self.take(out, indent);
+                    push(out, ", ");
+                }
+                self.expect(out, indent, '>')?;
+                push(out, ">");
+                Ok(())
+            }
+            "Bits" => {
+                self.expect(out, indent, '<')?;
+                push(out, "<");
+                self.expr(out, indent)?;
+                self.expect(out, indent, '>')?;
+                push(out, ">");
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn stream(&mut self, out: &mut String, indent: &str) -> Result<()> {
+        self.expect(out, indent, '<')?;
+        push(out, "<");
+        self.typ(out, indent)?;
+        let mut parameters = vec![];
+        while self.peek() == Some(Token::Punct(',')) {
+            self.take(out, indent);
+            let mut parameter = String::new();
+            let key = self.word(&mut parameter, indent, "stream property")?;
+            let order = STREAM_PARAMETERS
+                .iter()
+                .position(|k| *k == key)
+                .ok_or_else(|| {
+                    self.pos -= 1;
+                    self.error("stream property")
+                })?;
+            push(&mut parameter, key);
+            self.expect(&mut parameter, indent, '=')?;
+            push(&mut parameter, "=");
+            if key == "u" {
+                self.typ(&mut parameter, indent)?;
+            } else {
+                self.expr(&mut parameter, indent)?;
+            }
+            parameters.push((order, parameter));
+        }
+        parameters.sort_by_key(|(order, _)| *order);
+        for (_, parameter) in parameters {
+            push(out, ", ");
+            out.push_str(&parameter);
+        }
+        self.expect(out, indent, '>')?;
+        push(out, ">");
+        Ok(())
+    }
+
+    fn streamlet(&mut self, out: &mut String) -> Result<()> {
+        let name = self.word(out, "", "streamlet name")?;
+        push(out, &format!(" {}", name));
+        if self.peek() == Some(Token::Punct('<')) {
+            self.take(out, "    ");
+            push(out, " <");
+            while let Some(Token::Word(_)) = self.peek() {
+                let parameter = self.word(out, "    ", "parameter name")?;
+                push(out, parameter);
+                self.expect(out, "    ", '=')?;
+                push(out, " = ");
+                self.expr(out, "    ")?;
+                if self.peek() != Some(Token::Punct(',')) {
+                    break;
+                }
+                self.take(out, "    ");
+                push(out, ", ");
+            }
+            self.expect(out, "    ", '>')?;
+            push(out, ">");
+        }
+        self.expect(out, "", '(')?;
+        push(out, " (");
+        self.trailing_comments(out);
+        out.push('\n');
+        loop {
+            self.line_comments(out, "  ");
+            if self.peek() == Some(Token::Punct(')')) {
+                break;
+            }
+            out.push_str("  ");
+            let name = self.word(out, "    ", "interface")?;
+            push(out, name);
+            if self.peek() == Some(Token::Punct('[')) {
+                self.take(out, "    ");
+                push(out, "[");
+                self.expr(out, "    ")?;
+                self.expect(out, "    ", ']')?;
+                push(out, "]");
+            }
+            self.expect(out, "    ", ':')?;
+            push(out, " : ");
+            let mode = self.word(out, "    ", "mode")?;
+            push(out, mode);
+            push(out, " ");
+            self.typ(out, "    ")?;
+            let more = self.peek() == Some(Token::Punct(','));
+            if more {
+                self.take(out, "    ");
+                out.push(',');
+            }
+            self.trailing_comments(out);
+            out.push('\n');
+            if !more {
+                self.line_comments(out, "  ");
+                break;
+            }
+        }
+        self.expect(out, "", ')')?;
+        if out.ends_with("(\n") {
+            out.pop();
+        }
+        out.push(')');
+        Ok(())
+    }
+
+    /// Formats the next import, declaration or streamlet, and returns its
+    /// kind.
+    fn item(&mut self, out: &mut String) -> Result<&'static str> {
+        let keyword = self.word(out, "", "import, declaration or streamlet")?;
+        let kind = match keyword {
+            "import" => {
+                let name = self.word(out, "", "library name")?;
+                push(out, &format!("import {}", name));
+                "import"
+            }
+            "const" | "type" => {
+                let name = self.word(out, "", "name")?;
+                push(out, &format!("{} {}", keyword, name));
+                self.expect(out, "  ", '=')?;
+                push(out, " = ");
+                if keyword == "const" {
+                    self.expr(out, "  ")?;
+                } else {
+                    self.typ(out, "  ")?;
+                }
+                "declaration"
+            }
+            "Streamlet" => {
+                push(out, "Streamlet");
+                self.streamlet(out)?;
+                return Ok("streamlet");
+            }
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("import, declaration or streamlet"));
+            }
+        };
+        self.expect(out, "", ';')?;
+        push(out, ";");
+        Ok(kind)
+    }
+
+    fn file(&mut self) -> Result<String> {
+        let mut out = String::new();
+        let mut previous = None;
+        while self.pos < self.tokens.len() {
+            let Lexed { token, breaks, .. } = self.tokens[self.pos];
+            let mut item = String::new();
+            let kind = match token {
+                Token::Comment(comment) if comment.starts_with("//!") => {
+                    self.pos += 1;
+                    item.push_str(comment);
+                    "doc"
+                }
+                _ => {
+                    self.line_comments(&mut item, "");
+                    if self.pos < self.tokens.len() {
+                        if self.tokens[self.pos].breaks > 1 && !item.is_empty() {
+                            item.push('\n');
+                        }
+                        self.item(&mut item)?
+                    } else {
+                        item.pop();
+                        "comment"
+                    }
+                }
+            };
+            self.trailing_comments(&mut item);
+            if let Some(previous) = previous {
+                if previous != kind || kind == "streamlet" || breaks > 1 {
+                    out.push('\n');
+                }
+            }
+            out.push_str(&item);
+            out.push('\n');
+            previous = Some(kind);
+        }
+        Ok(out)
+    }
+}
+
+/// Formats a Streamlet Definition File.
+///
+/// The input is only checked for the syntax that is required to lay it out,
+/// see [Library::from_file](crate::design::Library::from_file) to validate
+/// it. Formatting the output of
+/// [Library::to_sdf](crate::design::Library::to_sdf) returns it unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use tydi::parser::format::format;
+///
+/// assert_eq!(
+///     format("Streamlet x(a:in Stream<Bits<W+1>,d=1,t=2>,b : out Null)")?,
+///     "Streamlet x (\n  a : in Stream<Bits<W + 1>, t=2, d=1>,\n  b : out Null\n)\n"
+/// );
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn format(input: &str) -> Result<String> {
+    Formatter {
+        input,
+        tokens: lex(input)?,
+        pos: 0,
+    }
+    .file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_sdf() -> Result<()> {
+        let input = concat!(
+            "//! A library.\n",
+            "import  other ;\n",
+            "import more;\n",
+            "const W=8 ; // the width\n",
+            "\n",
+            "type Pair=Group<a:Bits<W*2>,b : Null>;\n",
+            "// Streamlets.\n",
+            "/// Doubles.\n",
+            "Streamlet double<N=2>( a : in Stream<Pair,x=true,d=N+1>, // input\n",
+            "  /// The output.\n",
+            "  b:out Stream<Bits<(W+1)/2>, u=Bits<1>, c=4.2>\n",
+            ") Streamlet empty ( )\n",
+            "# The end.\n",
+        );
+        let expected = concat!(
+            "//! A library.\n",
+            "\n",
+            "import other;\n",
+            "import more;\n",
+            "\n",
+            "const W = 8; // the width\n",
+            "\n",
+            "type Pair = Group<a: Bits<W * 2>, b: Null>;\n",
+            "\n",
+            "// Streamlets.\n",
+            "/// Doubles.\n",
+            "Streamlet double <N = 2> (\n",
+            "  a : in Stream<Pair, d=N + 1, x=true>, // input\n",
+            "  /// The output.\n",
+            "  b : out Stream<Bits<(W + 1) / 2>, c=4.2, u=Bits<1>>\n",
+            ")\n",
+            "\n",
+            "Streamlet empty ()\n",
+            "\n",
+            "# The end.\n",
+        );
+        assert_eq!(format(input)?, expected);
+        assert_eq!(format(expected)?, expected);
+
+        assert_eq!(
+            format("Streamlet x ( a [ W*2 ]:in Null )")?,
+            "Streamlet x (\n  a[W * 2] : in Null\n)\n"
+        );
+        assert!(format("Streamlet x (a[2 : in Null)").is_err());
+        assert!(format("Streamlet x (a : in Bits<8>").is_err());
+        assert!(format("Streamlet x (a : in Stream<Null, q=1>)").is_err());
+        assert!(format("Streamlet x (a : in Null) $").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn format_to_sdf() -> Result<()> {
+        use crate::design::Library;
+        use crate::parser::nom::streamlet;
+        use crate::Name;
+
+        let lib = Library::try_new(
+            Name::try_new("lib")?,
+            vec![],
+            vec![
+                streamlet(concat!(
+                    "/// A streamlet.\n",
+                    "/// @author Jane Doe\n",
+                    "Streamlet a <W = 4> (\n",
+                    "  /// Input.\n",
+                    "  x : in Stream<Group<a: Bits<W>, b: Union<c: Null, d: Bits<2>>>, t=0.5, d=2, \
+                     s=FlatDesync, c=7, r=Reverse, u=Bits<2>, x=true>,\n",
+                    "  y : out Null\n",
+                    ")",
+                ))
+                .unwrap()
+                .1,
+                streamlet("Streamlet b ()").unwrap().1,
+            ],
+        )?;
+        let sdf = lib.to_sdf();
+        assert_eq!(format(&sdf)?, sdf);
+        Ok(())
+    }
+}
@@ -4,11 +4,15 @@
 //! utitity parser methods and implementations of parsers for Tydi stream and
 //! streamlet types, and libraries with streamlets.
 //!
-//! The current parsers are built using [`nom`].
+//! The current parsers are built using [`nom`]. The [format] module
+//! formats Streamlet Definition Files, and the [verilog] module parses the
+//! headers of Verilog modules into blackbox streamlets.
 //!
 //! [`nom`]: https://crates.io/crates/nom
 
+pub mod format;
 pub mod nom;
+pub mod verilog;
 
 #[cfg(test)]
 mod tests {}
@@ -1,21 +1,25 @@
 //! Nom-based parsers for Streamlet Definition Files.
 
+use std::cell::RefCell;
 use std::collections::HashMap;
 
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_until, take_while},
-    character::complete::{digit1, multispace1, none_of, one_of},
-    combinator::{map, map_res, opt, recognize},
+    character::complete::{char, digit1, multispace1, one_of},
+    combinator::{consumed, cut, eof, map, map_res, not, opt, peek, recognize, verify},
+    error::{context, VerboseError, VerboseErrorKind},
     multi::{many0, many1, separated_list0},
     number::complete::float,
-    sequence::{delimited, preceded, separated_pair, terminated, tuple},
+    sequence::{delimited, preceded, terminated, tuple},
 };
 
+use crate::design::metadata::Metadata;
+use crate::design::param::{NamedParameter, ParameterVariant};
 use crate::design::{Interface, Mode, Streamlet};
 use crate::logical::{Direction, Group, LogicalType, Stream, Synchronicity, Union};
 use crate::physical::Complexity;
-use crate::{Name, PositiveReal};
+use crate::{Name, NonNegative, PositiveReal, UniqueKeyBuilder};
 
 // #[derive(Debug, PartialEq)]
 // pub struct ParserError<I> {
@@ -49,14 +53,331 @@ use crate::{Name, PositiveReal};
 //     }
 // }
 
-type Result<I, T> = nom::IResult<I, T, nom::error::VerboseError<I>>;
+type Result<I, T> = nom::IResult<I, T, VerboseError<I>>;
 
+/// A location in the input of a parser.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    /// The line number, starting at 1.
+    pub line: usize,
+    /// The column number in characters, starting at 1.
+    pub column: usize,
+}
+
+impl Location {
+    /// Returns the location of the remaining input of a parser in its full
+    /// input. The remaining input must be a suffix of the full input, as is
+    /// the case for all parsers of this module.
+    pub fn of(input: &str, rest: &str) -> Location {
+        let before = &input[..input.len().saturating_sub(rest.len())];
+        let start = before.rfind('\n').map_or(0, |i| i + 1);
+        Location {
+            line: before.matches('\n').count() + 1,
+            column: before[start..].chars().count() + 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Describes the token at the start of the remaining input of a parser.
+fn found(rest: &str) -> String {
+    let word = take_while::<_, _, ()>(|c: char| c.is_alphanumeric() || c == '_')(rest)
+        .map(|(_, word)| word)
+        .unwrap_or_default();
+    match (word, rest.chars().next()) {
+        (_, None) => "end of input".to_string(),
+        (_, Some('\n')) | (_, Some('\r')) => "end of line".to_string(),
+        ("", Some(c)) => format!("\"{}\"", c),
+        (word, _) => format!("\"{}\"", word),
+    }
+}
+
+/// Returns a message reporting what was expected at the start of the
+/// remaining input of a parser, and what was found instead, e.g.
+/// `expected ')', found "b" at lib.sdf:3:5`, followed by the line of the
+/// input with the column marked. The source is omitted from the location if
+/// it is empty.
+pub fn report(source: &str, input: &str, rest: &str, expected: &str) -> String {
+    let location = Location::of(input, rest);
+    let line = input.lines().nth(location.line - 1).unwrap_or_default();
+    let gutter = " ".repeat(location.line.to_string().len());
+    let marker = line
+        .chars()
+        .take(location.column - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+    format!(
+        "{} at {}{}\n{} |\n{} | {}\n{} | {}^",
+        describe(rest, expected),
+        if source.is_empty() {
+            String::new()
+        } else {
+            format!("{}:", source)
+        },
+        location,
+        gutter,
+        location.line,
+        line,
+        gutter,
+        marker
+    )
+}
+
+/// Returns a message reporting what was expected at the start of the
+/// remaining input of a parser, and what was found instead, without its
+/// location, e.g. `expected ')', found "b"`.
+pub fn describe(rest: &str, expected: &str) -> String {
+    format!("expected {}, found {}", expected, found(rest))
+}
+
+/// Returns a message reporting a parser error, see [report]. The error is
+/// reported at the innermost expected character or context of the error.
+pub fn report_error(source: &str, input: &str, err: &nom::Err<VerboseError<&str>>) -> String {
+    let (rest, expected) = error_at(input, err);
+    report(source, input, rest, &expected)
+}
+
+/// Returns the remaining input at which a parser error is reported, see
+/// [report_error], and what was expected there.
+pub fn error_at<'a>(input: &'a str, err: &nom::Err<VerboseError<&'a str>>) -> (&'a str, String) {
+    match err {
+        nom::Err::Incomplete(_) => (&input[input.len()..], "more input".to_string()),
+        nom::Err::Error(e) | nom::Err::Failure(e) => {
+            expectation(e).unwrap_or((input, "valid input".to_string()))
+        }
+    }
+}
+
+/// Returns the remaining input at which a parser error is reported, and what
+/// was expected there.
+fn expectation<'a>(e: &VerboseError<&'a str>) -> Option<(&'a str, String)> {
+    e.errors
+        .iter()
+        .find_map(|(rest, kind)| match kind {
+            VerboseErrorKind::Char(c) => Some((*rest, format!("'{}'", c))),
+            VerboseErrorKind::Context(ctx) => Some((*rest, ctx.to_string())),
+            VerboseErrorKind::Nom(_) => None,
+        })
+        .or_else(|| {
+            e.errors
+                .first()
+                .map(|(rest, _)| (*rest, "valid input".to_string()))
+        })
+}
+
+/// The names that are in scope while parsing, which can be referenced by
+/// name wherever a logical type or a natural number is expected.
+///
+/// The parsers that refer to names in scope are methods of a scope. The
+/// functions of this module of the same name parse in an empty scope.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scope {
+    /// The values of the streamlet parameters, if parsing the interfaces of
+    /// a parameterized streamlet.
+    parameters: Option<HashMap<Name, NonNegative>>,
+    /// The named types.
+    types: HashMap<Name, LogicalType>,
+    /// The named constants of the library being parsed.
+    constants: HashMap<Name, NonNegative>,
+}
+
+impl Scope {
+    /// Returns an empty scope.
+    pub fn new() -> Self {
+        Scope::default()
+    }
+
+    /// Returns this scope with the given streamlet parameters in scope, such
+    /// that they can be referenced by name wherever a natural number is
+    /// expected.
+    pub fn with_parameters(mut self, parameters: HashMap<Name, NonNegative>) -> Self {
+        self.parameters = Some(parameters);
+        self
+    }
+
+    /// Returns this scope with the given named types in scope, e.g. those of
+    /// imported libraries, such that they can be referenced by name wherever
+    /// a logical type is expected.
+    pub fn with_types(mut self, types: HashMap<Name, LogicalType>) -> Self {
+        self.types.extend(types);
+        self
+    }
+
+    /// Returns this scope without streamlet parameters in scope.
+    fn without_parameters(mut self) -> Self {
+        self.parameters = None;
+        self
+    }
+
+    /// Returns the value of a streamlet parameter in scope.
+    fn parameter(&self, name: &str) -> Option<NonNegative> {
+        let name = Name::try_new(name).ok()?;
+        self.parameters.as_ref()?.get(&name).copied()
+    }
+
+    /// Returns the value of a named constant in scope.
+    fn constant(&self, name: &str) -> Option<NonNegative> {
+        let name = Name::try_new(name).ok()?;
+        self.constants.get(&name).copied()
+    }
+
+    /// Replaces the references to named types and constants in scope in a
+    /// type template by the types and values they refer to, such that the
+    /// template can be elaborated without the named types and constants in
+    /// scope. Field names and parameters are left untouched.
+    fn expand_template(&self, template: &str) -> String {
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+        let mut result = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find(is_ident) {
+            result.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find(|c: char| !is_ident(c))
+                .map_or(rest.len(), |end| start + end);
+            let (ident, tail) = (&rest[start..end], &rest[end..]);
+            let expanded = Name::try_new(ident)
+                .ok()
+                .filter(|_| !tail.trim_start().starts_with(':') && self.parameter(ident).is_none())
+                .and_then(|n| {
+                    self.types
+                        .get(&n)
+                        .map(|typ| typ.to_string())
+                        .or_else(|| self.constant(ident).map(|value| value.to_string()))
+                });
+            match expanded {
+                Some(expanded) => result.push_str(&expanded),
+                None => result.push_str(ident),
+            }
+            rest = tail;
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+/// Names that cannot be used for named types, because they are used by the
+/// logical type syntax itself.
+const TYPE_KEYWORDS: [&str; 5] = ["Null", "Bits", "Group", "Union", "Stream"];
+
+thread_local! {
+    /// The errors that were recovered from while parsing, if error recovery
+    /// is enabled, as the length of the remaining input at which they occurred
+    /// and what was expected there.
+    static RECOVERED: RefCell<Option<Vec<(usize, String)>>> = const { RefCell::new(None) };
+}
+
+/// Run a parser of the given input with error recovery enabled.
+///
+/// Instead of failing at the first error, the parser skips interfaces,
+/// streamlets and named type declarations that contain errors, and continues
+/// at the next `,`, `)` or `;` that ends them, such that all errors of the
+/// input can be reported at once. Returns the result of the parser, and the
+/// errors that were recovered from as the remaining input at which they
+/// occurred and what was expected there, see [report].
+pub fn with_recovery<T>(input: &str, f: impl FnOnce() -> T) -> (T, Vec<(&str, String)>) {
+    let outer = RECOVERED.with(|r| r.replace(Some(vec![])));
+    let result = f();
+    let recovered = RECOVERED.with(|r| r.replace(outer)).unwrap_or_default();
+    (
+        result,
+        recovered
+            .into_iter()
+            .map(|(len, expected)| (&input[input.len().saturating_sub(len)..], expected))
+            .collect(),
+    )
+}
+
+/// Records a failure if error recovery is enabled, and returns whether it
+/// is.
+fn recover(e: &VerboseError<&str>) -> bool {
+    RECOVERED.with(|r| match r.borrow_mut().as_mut() {
+        Some(recovered) => {
+            if let Some((rest, expected)) = expectation(e) {
+                recovered.push((rest.len(), expected));
+            }
+            true
+        }
+        None => false,
+    })
+}
+
+/// Returns the input at the first `,` or `)` that is not nested in
+/// parentheses or angle brackets, or the end of the input.
+fn sync_element(input: &str) -> &str {
+    let mut depth = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            '(' | '<' => depth += 1,
+            ')' | '>' if depth > 0 => depth -= 1,
+            ',' | ')' if depth == 0 => return &input[i..],
+            _ => (),
+        }
+    }
+    &input[input.len()..]
+}
+
+/// Returns the input after the `)` that closes the first `(`, or after the
+/// first `)` if it comes first, or the end of the input.
+fn sync_streamlet(input: &str) -> &str {
+    let mut depth = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth <= 1 => return &input[i + 1..],
+            ')' => depth -= 1,
+            _ => (),
+        }
+    }
+    &input[input.len()..]
+}
+
+/// Parses a list of elements separated by a separator, like
+/// [separated_list0]. If error recovery is enabled, elements that fail are
+/// recorded and skipped: parsing continues at the input returned by `sync`
+/// for the start of the element and the remaining input of the failure.
+fn recovering_list<'a, O, S>(
+    mut sep: impl FnMut(&'a str) -> Result<&'a str, S>,
+    mut f: impl FnMut(&'a str) -> Result<&'a str, O>,
+    sync: impl Fn(&'a str, &'a str) -> &'a str,
+) -> impl FnMut(&'a str) -> Result<&'a str, Vec<O>> {
+    move |mut input: &'a str| {
+        let mut result = vec![];
+        let mut element = input;
+        loop {
+            match f(element) {
+                Ok((rest, o)) => {
+                    result.push(o);
+                    input = rest;
+                }
+                Err(nom::Err::Failure(e)) if recover(&e) => {
+                    let failed = expectation(&e).map_or(element, |(rest, _)| rest);
+                    input = sync(element, failed);
+                }
+                Err(nom::Err::Error(_)) => return Ok((input, result)),
+                Err(e) => return Err(e),
+            }
+            match sep(input) {
+                Ok((rest, _)) => element = rest,
+                Err(nom::Err::Error(_)) => return Ok((input, result)),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Optional whitespace and comments that are not doc strings.
 fn ws0(input: &str) -> Result<&str, Vec<&str>> {
-    many0(multispace1)(input)
+    many0(alt((multispace1, comment_plain)))(input)
 }
 
+/// Whitespace and comments that are not doc strings.
 fn ws1(input: &str) -> Result<&str, Vec<&str>> {
-    many1(multispace1)(input)
+    many1(alt((multispace1, comment_plain)))(input)
 }
 
 fn w<'a, T>(
@@ -86,11 +407,26 @@ pub fn take_until_newline_or_eof(input: &str) -> Result<&str, &str> {
 /// it produces an empty str.
 pub fn comment_line(input: &str) -> Result<&str, &str> {
     map(
-        tuple((tag("//"), none_of("/"), take_until_newline_or_eof)),
+        tuple((tag("//"), not(tag("/")), take_until_newline_or_eof)),
         |_| "",
     )(input)
 }
 
+/// Line or eof delimited comment starting with `#`, not meant for doc
+/// strings, so if it succeeds, it produces an empty str.
+pub fn comment_hash(input: &str) -> Result<&str, &str> {
+    map(tuple((tag("#"), take_until_newline_or_eof)), |_| "")(input)
+}
+
+/// Comment that is not a doc string, which may appear wherever whitespace
+/// is allowed. The doc strings of a library (`//!`) are not comments.
+fn comment_plain(input: &str) -> Result<&str, &str> {
+    preceded(
+        not(tag("//!")),
+        alt((comment_line, comment_hash, comment_delimited)),
+    )(input)
+}
+
 /// Line comment meant for doc strings.
 pub fn comment_doc(input: &str) -> Result<&str, &str> {
     map(
@@ -99,8 +435,16 @@ pub fn comment_doc(input: &str) -> Result<&str, &str> {
     )(input)
 }
 
+/// Line comment meant for the doc string of the enclosing library.
+pub fn comment_inner_doc(input: &str) -> Result<&str, &str> {
+    map(
+        tuple((tag("//!"), take_until_newline_or_eof)),
+        |(_, s): (_, &str)| s,
+    )(input)
+}
+
 pub fn comment(input: &str) -> Result<&str, &str> {
-    alt((comment_doc, comment_line, comment_delimited))(input)
+    alt((comment_doc, comment_line, comment_hash, comment_delimited))(input)
 }
 
 pub fn comment_doc_block(input: &str) -> Result<&str, Vec<&str>> {
@@ -132,36 +476,20 @@ pub fn null(input: &str) -> Result<&str, LogicalType> {
     map(tag("Null"), |_| LogicalType::Null)(input)
 }
 
-pub fn bits(input: &str) -> Result<&str, LogicalType> {
-    map_res(
-        delimited(w(tag("Bits<")), w(digit1), tag(">")),
-        |x: &str| LogicalType::try_new_bits(x.parse().unwrap()).map_err(|_| ()),
-    )(input)
-}
-
-pub fn logical_stream_type(input: &str) -> Result<&str, LogicalType> {
-    alt((null, bits, group, union, stream))(input)
-}
-
-fn fields(input: &str) -> Result<&str, Vec<(Name, LogicalType)>> {
-    separated_list0(
-        w(tag(",")),
-        separated_pair(w(name), w(tag(":")), w(logical_stream_type)),
-    )(input)
-}
-
-pub fn group(input: &str) -> Result<&str, LogicalType> {
-    map_res(
-        delimited(w(tag("Group<")), w(fields), tag(">")),
-        |fields: Vec<(Name, LogicalType)>| Group::try_new(fields).map(Into::into).map_err(|_| ()),
-    )(input)
-}
-
-pub fn union(input: &str) -> Result<&str, LogicalType> {
-    map_res(
-        delimited(w(tag("Union<")), w(fields), tag(">")),
-        |fields: Vec<(Name, LogicalType)>| Union::try_new(fields).map(Into::into).map_err(|_| ()),
-    )(input)
+/// Applies the arithmetic operators of an expression from left to right,
+/// failing if a result is not a natural number.
+fn apply(
+    (first, rest): (NonNegative, Vec<(char, NonNegative)>),
+) -> std::result::Result<NonNegative, ()> {
+    rest.into_iter().try_fold(first, |acc, (op, x)| {
+        match op {
+            '+' => acc.checked_add(x),
+            '-' => acc.checked_sub(x),
+            '*' => acc.checked_mul(x),
+            _ => acc.checked_div(x),
+        }
+        .ok_or(())
+    })
 }
 
 pub fn complexity(input: &str) -> Result<&str, Complexity> {
@@ -188,148 +516,656 @@ pub fn direction(input: &str) -> Result<&str, Direction> {
     })(input)
 }
 
-pub fn stream(input: &str) -> Result<&str, LogicalType> {
-    map_res(
-        tuple((
-            w(tag("Stream<")),
-            w(logical_stream_type),
-            opt(preceded(
-                w(tag(",")),
-                map(
-                    separated_list0(
-                        w(tag(",")),
-                        separated_pair(
-                            w(one_of("tdscrux")),
-                            w(tag("=")),
-                            w(alt((
-                                recognize(float),
-                                recognize(digit1),
-                                recognize(synchronicity),
-                                recognize(complexity),
-                                recognize(direction),
-                                recognize(logical_stream_type),
-                                recognize(bool),
-                            ))),
-                        ),
-                    ),
-                    |opts| opts.into_iter().collect::<HashMap<char, &str>>(),
-                ),
-            )),
-            tag(">"),
-        )),
-        |(_, data, opt, _)| -> std::result::Result<LogicalType, ()> {
-            let throughput = PositiveReal::new(
-                opt.as_ref()
-                    .and_then(|opts| opts.get(&'t').map(|x| x.parse().ok()))
-                    .flatten()
-                    .unwrap_or(1.),
-            )
-            .map_err(|_| ())?;
-
-            let dimensionality = opt
-                .as_ref()
-                .and_then(|opts| opts.get(&'d').map(|x| x.parse().ok()))
-                .flatten()
-                .unwrap_or(0);
-
-            let synchronicity = opt
-                .as_ref()
-                .and_then(|opts| {
-                    opts.get(&'s')
-                        .map(|x| synchronicity(x).ok().map(|(_, x)| x))
-                })
-                .flatten()
-                .unwrap_or_else(Synchronicity::default);
-
-            let complexity = opt
-                .as_ref()
-                .and_then(|opts| opts.get(&'c').map(|x| complexity(x).ok().map(|(_, x)| x)))
-                .flatten()
-                .unwrap_or_else(Complexity::default);
-
-            let direction = opt
-                .as_ref()
-                .and_then(|opts| opts.get(&'r').map(|x| direction(x).ok().map(|(_, x)| x)))
-                .flatten()
-                .unwrap_or_else(Direction::default);
-
-            let user = opt
-                .as_ref()
-                .and_then(|opts| {
-                    opts.get(&'u')
-                        .map(|x| logical_stream_type(x).ok().map(|(_, x)| x))
-                })
-                .unwrap_or(Option::None);
-
-            let keep = opt
-                .as_ref()
-                .and_then(|opts| opts.get(&'x').map(|x| bool(x).ok().map(|(_, x)| x)))
-                .flatten()
-                .unwrap_or(false);
-
-            Ok(Stream::new(
-                data,
-                throughput,
-                dimensionality,
-                synchronicity,
-                complexity,
-                direction,
-                user,
-                keep,
-            )
-            .into())
-        },
-    )(input)
+/// A parameter of a stream, e.g. `d=1`.
+enum StreamProperty {
+    Throughput(PositiveReal),
+    Dimensionality(NonNegative),
+    Synchronicity(Synchronicity),
+    Complexity(Complexity),
+    Direction(Direction),
+    User(LogicalType),
+    Keep(bool),
+}
+
+/// Parses the value of a stream property with the given key.
+fn property<'a, T>(
+    key: &'static str,
+    expected: &'static str,
+    value: impl FnMut(&'a str) -> Result<&'a str, T>,
+) -> impl FnMut(&'a str) -> Result<&'a str, T> {
+    preceded(
+        tuple((w(tag(key)), cut(w(char('='))))),
+        cut(context(expected, value)),
+    )
 }
 
 pub fn mode(input: &str) -> Result<&str, Mode> {
     map(alt((tag("in"), tag("out"))), |x: &str| x.parse().unwrap())(input)
 }
 
-pub fn interface(input: &str) -> Result<&str, Interface> {
-    map_res(
+/// Parses an import of another library, e.g. `import other_lib;`.
+pub fn import(input: &str) -> Result<&str, Name> {
+    map(
         tuple((
             w(doc),
-            w(name),
-            w(tag(":")),
-            mode,
-            multispace1,
-            logical_stream_type,
+            terminated(tag("import"), multispace1),
+            cut(context("library name", w(name))),
+            cut(char(';')),
         )),
-        |(d, n, _, m, _, t): (Option<String>, Name, _, Mode, _, LogicalType)| {
-            Interface::try_new(n, m, t, d.as_deref()).map_err(|_| ())
-        },
+        |(_, _, n, _): (_, _, Name, _)| n,
     )(input)
 }
 
+/// Parses the doc string of a library at the start of a Streamlet Definition
+/// File, written as `//!` comments.
+pub fn library_doc(input: &str) -> Result<&str, Option<String>> {
+    map(preceded(ws0, many0(w(comment_inner_doc))), |v| {
+        let s = v.join("\n");
+        if s.is_empty() {
+            None
+        } else {
+            Some(s)
+        }
+    })(input)
+}
+
+/// Parses the imports at the start of a Streamlet Definition File.
+pub fn imports(input: &str) -> Result<&str, Vec<Name>> {
+    preceded(ws0, many0(w(import)))(input)
+}
+
+/// A declaration of a named constant or a named type.
+enum Declaration {
+    Constant(Name, NonNegative),
+    Type(Name, LogicalType),
+}
+
+/// The contents of a Streamlet Definition File: the imported libraries, the
+/// named types, and the streamlets.
+pub type LibraryContents = (Vec<Name>, Vec<(Name, LogicalType)>, Vec<Streamlet>);
+
+impl Scope {
+    /// Parses a natural number, or the name of a streamlet parameter or
+    /// constant in scope, or a parenthesized expression.
+    fn factor<'a>(&self, input: &'a str) -> Result<&'a str, NonNegative> {
+        alt((
+            map_res(digit1, |x: &str| x.parse()),
+            map_res(name, |x: Name| {
+                self.parameter(x.as_ref())
+                    .or_else(|| self.constant(x.as_ref()))
+                    .ok_or(())
+            }),
+            delimited(w(tag("(")), w(|input| self.natural(input)), cut(char(')'))),
+        ))(input)
+    }
+
+    /// Parses a natural number expression, e.g. `ID_WIDTH + 1`, of natural
+    /// numbers, streamlet parameters and constants in scope, with the
+    /// operators `+`, `-`, `*` and `/` and parentheses. Expressions of which
+    /// an intermediate result is not a natural number are rejected.
+    pub fn natural<'a>(&self, input: &'a str) -> Result<&'a str, NonNegative> {
+        let factor = |input| self.factor(input);
+        let term = move |input| {
+            map_res(
+                tuple((w(factor), many0(tuple((w(one_of("*/")), w(factor)))))),
+                apply,
+            )(input)
+        };
+        map_res(tuple((term, many0(tuple((w(one_of("+-")), term))))), apply)(input)
+    }
+
+    pub fn bits<'a>(&self, input: &'a str) -> Result<&'a str, LogicalType> {
+        map_res(
+            delimited(
+                w(tag("Bits<")),
+                cut(context(
+                    "bit count that is not zero",
+                    verify(context("number", w(|input| self.natural(input))), |x| {
+                        *x > 0
+                    }),
+                )),
+                cut(char('>')),
+            ),
+            |x: NonNegative| LogicalType::try_new_bits(x).map_err(|_| ()),
+        )(input)
+    }
+
+    /// Parses a reference to a named type in scope.
+    pub fn named_type<'a>(&self, input: &'a str) -> Result<&'a str, LogicalType> {
+        map_res(name, |n: Name| self.types.get(&n).cloned().ok_or(()))(input)
+    }
+
+    pub fn logical_stream_type<'a>(&self, input: &'a str) -> Result<&'a str, LogicalType> {
+        alt((
+            |input| self.named_type(input),
+            null,
+            |input| self.bits(input),
+            |input| self.group(input),
+            |input| self.union(input),
+            |input| self.stream(input),
+        ))(input)
+    }
+
+    /// Parse a complete logical type expression, ignoring surrounding
+    /// whitespace, see [logical_type].
+    pub fn logical_type(&self, input: &str) -> crate::Result<LogicalType> {
+        terminated(
+            delimited(
+                ws0,
+                w(context("logical type", |input| {
+                    self.logical_stream_type(input)
+                })),
+                ws0,
+            ),
+            context("end of input", eof),
+        )(input)
+        .map(|(_, typ)| typ)
+        .map_err(|e| crate::Error::ParsingError(report_error("", input, &e)))
+    }
+
+    /// Parses the fields of a group or union separated by commas, e.g.
+    /// `a: Bits<8>, b: Null`. A field name may only be used once.
+    fn fields<'a>(&self, mut input: &'a str) -> Result<&'a str, Vec<(Name, LogicalType)>> {
+        let mut result: Vec<(Name, LogicalType)> = vec![];
+        let mut field = input;
+        loop {
+            let (rest, n) = match w(name)(field) {
+                Ok(ok) => ok,
+                Err(nom::Err::Error(_)) => return Ok((input, result)),
+                Err(e) => return Err(e),
+            };
+            if result.iter().any(|(other, _)| other == &n) {
+                return Err(nom::Err::Failure(VerboseError {
+                    errors: vec![(
+                        field,
+                        VerboseErrorKind::Context("field name that is not yet used"),
+                    )],
+                }));
+            }
+            let (rest, t) = preceded(
+                cut(w(char(':'))),
+                cut(w(context("logical type", |input| {
+                    self.logical_stream_type(input)
+                }))),
+            )(rest)?;
+            result.push((n, t));
+            input = rest;
+            match w(char(','))(input) {
+                Ok((rest, _)) => field = rest,
+                Err(nom::Err::Error(_)) => return Ok((input, result)),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub fn group<'a>(&self, input: &'a str) -> Result<&'a str, LogicalType> {
+        map_res(
+            delimited(
+                w(tag("Group<")),
+                cut(w(|input| self.fields(input))),
+                cut(char('>')),
+            ),
+            |fields: Vec<(Name, LogicalType)>| {
+                Group::try_new(fields).map(Into::into).map_err(|_| ())
+            },
+        )(input)
+    }
+
+    pub fn union<'a>(&self, input: &'a str) -> Result<&'a str, LogicalType> {
+        map_res(
+            delimited(
+                w(tag("Union<")),
+                cut(w(|input| self.fields(input))),
+                cut(char('>')),
+            ),
+            |fields: Vec<(Name, LogicalType)>| {
+                Union::try_new(fields).map(Into::into).map_err(|_| ())
+            },
+        )(input)
+    }
+
+    /// Parses a stream property, e.g. `t=2`, `d=1`, `s=Sync`, `c=4`,
+    /// `r=Reverse`, `u=Bits<8>` or `x=true`.
+    fn stream_property<'a>(&self, input: &'a str) -> Result<&'a str, StreamProperty> {
+        let natural = |input| self.natural(input);
+        context(
+            "stream property",
+            alt((
+                property(
+                    "t",
+                    "positive throughput",
+                    map_res(
+                        alt((
+                            map(
+                                terminated(natural, peek(preceded(ws0, one_of(",>")))),
+                                f64::from,
+                            ),
+                            map_res(recognize(float), |x: &str| x.parse::<f64>()),
+                        )),
+                        |x| PositiveReal::new(x).map(StreamProperty::Throughput),
+                    ),
+                ),
+                property(
+                    "d",
+                    "dimensionality",
+                    map(natural, StreamProperty::Dimensionality),
+                ),
+                property(
+                    "s",
+                    "synchronicity",
+                    map(synchronicity, StreamProperty::Synchronicity),
+                ),
+                property(
+                    "c",
+                    "complexity",
+                    map(complexity, StreamProperty::Complexity),
+                ),
+                property("r", "direction", map(direction, StreamProperty::Direction)),
+                property(
+                    "u",
+                    "logical type",
+                    map(
+                        |input| self.logical_stream_type(input),
+                        StreamProperty::User,
+                    ),
+                ),
+                property("x", "keep", map(bool, StreamProperty::Keep)),
+            )),
+        )(input)
+    }
+
+    /// Parses the properties of a stream following its data type, each
+    /// preceded by a comma. Properties may be given in any order, but only
+    /// once.
+    fn stream_properties<'a>(&self, mut input: &'a str) -> Result<&'a str, Vec<StreamProperty>> {
+        let mut result: Vec<StreamProperty> = vec![];
+        while let Ok((rest, _)) = w(char(','))(input) {
+            let (rest, property) = cut(w(|input| self.stream_property(input)))(rest)?;
+            if result
+                .iter()
+                .any(|p| std::mem::discriminant(p) == std::mem::discriminant(&property))
+            {
+                let key = input.len() - w(char(','))(input)?.0.len();
+                return Err(nom::Err::Failure(VerboseError {
+                    errors: vec![(
+                        &input[key..],
+                        VerboseErrorKind::Context("stream property that is not yet set"),
+                    )],
+                }));
+            }
+            result.push(property);
+            input = rest;
+        }
+        Ok((input, result))
+    }
+
+    /// Parses a stream, e.g. `Stream<Bits<8>, t=2, d=1, c=7>`. Its properties
+    /// default to those of [Stream::new_basic].
+    pub fn stream<'a>(&self, input: &'a str) -> Result<&'a str, LogicalType> {
+        map(
+            tuple((
+                w(tag("Stream<")),
+                cut(w(context("logical type", |input| {
+                    self.logical_stream_type(input)
+                }))),
+                |input| self.stream_properties(input),
+                cut(char('>')),
+            )),
+            |(_, data, properties, _)| {
+                let mut throughput = PositiveReal::new(1.).unwrap();
+                let mut dimensionality = 0;
+                let mut synchronicity = Synchronicity::default();
+                let mut complexity = Complexity::default();
+                let mut direction = Direction::default();
+                let mut user = None;
+                let mut keep = false;
+                for property in properties {
+                    match property {
+                        StreamProperty::Throughput(x) => throughput = x,
+                        StreamProperty::Dimensionality(x) => dimensionality = x,
+                        StreamProperty::Synchronicity(x) => synchronicity = x,
+                        StreamProperty::Complexity(x) => complexity = x,
+                        StreamProperty::Direction(x) => direction = x,
+                        StreamProperty::User(x) => user = Some(x),
+                        StreamProperty::Keep(x) => keep = x,
+                    }
+                }
+                Stream::new(
+                    data,
+                    throughput,
+                    dimensionality,
+                    synchronicity,
+                    complexity,
+                    direction,
+                    user,
+                    keep,
+                )
+                .into()
+            },
+        )(input)
+    }
+
+    /// Parses an interface. If streamlet parameters are in scope, the type of
+    /// the interface is kept as a template, such that it can be elaborated
+    /// for other parameter values.
+    pub fn interface<'a>(&self, input: &'a str) -> Result<&'a str, Interface> {
+        map_res(
+            |input| self.interfaces(input),
+            |mut il: Vec<Interface>| match il.len() {
+                1 => Ok(il.remove(0)),
+                _ => Err(()),
+            },
+        )(input)
+    }
+
+    /// Parses an interface, or an array of identical interfaces, e.g.
+    /// `req[4] : in Stream<Bits<8>>`, of which the length cannot refer to
+    /// streamlet parameters.
+    pub fn interfaces<'a>(&self, input: &'a str) -> Result<&'a str, Vec<Interface>> {
+        let outer = self.clone().without_parameters();
+        map_res(
+            tuple((
+                w(doc),
+                w(name),
+                opt(w(delimited(
+                    w(char('[')),
+                    cut(context("number", w(move |input| outer.natural(input)))),
+                    cut(char(']')),
+                ))),
+                w(char(':')),
+                cut(context("mode", terminated(mode, ws1))),
+                cut(context(
+                    "logical type",
+                    consumed(|input| self.logical_stream_type(input)),
+                )),
+            )),
+            |(d, n, l, _, m, (s, t)): (
+                Option<String>,
+                Name,
+                Option<NonNegative>,
+                _,
+                Mode,
+                (&str, LogicalType),
+            )|
+             -> std::result::Result<Vec<Interface>, ()> {
+                let il = match l {
+                    Some(length) => Interface::try_new_array(n, length, m, t, d.as_deref()),
+                    None => Interface::try_new(n, m, t, d.as_deref()).map(|iface| vec![iface]),
+                }
+                .map_err(|_| ())?;
+                if self.parameters.is_some() {
+                    let template = self.expand_template(s);
+                    Ok(il
+                        .into_iter()
+                        .map(|iface| iface.with_template(template.clone()))
+                        .collect())
+                } else {
+                    Ok(il)
+                }
+            },
+        )(input)
+    }
+
+    /// Parses a streamlet parameter with its default value, e.g. `WIDTH = 8`.
+    pub fn streamlet_parameter<'a>(&self, input: &'a str) -> Result<&'a str, NamedParameter> {
+        map_res(
+            tuple((
+                w(doc),
+                w(name),
+                cut(w(char('='))),
+                cut(context("number", |input| self.natural(input))),
+            )),
+            |(d, n, _, v): (Option<String>, Name, _, NonNegative)| {
+                NamedParameter::try_new(n, ParameterVariant::UInt(v), d.as_deref()).map_err(|_| ())
+            },
+        )(input)
+    }
+
+    /// Parses a streamlet. Its parameters are in scope in the types of its
+    /// interfaces.
+    pub fn streamlet<'a>(&self, input: &'a str) -> Result<&'a str, Streamlet> {
+        let (input, (d, _)) = tuple((w(doc), w(tag("Streamlet"))))(input)?;
+        let (input, n) = cut(context("streamlet name", w(name)))(input)?;
+        let (input, params) = opt(w(delimited(
+            w(char('<')),
+            separated_list0(w(char(',')), w(|input| self.streamlet_parameter(input))),
+            cut(char('>')),
+        )))(input)?;
+        let scope = match &params {
+            Some(params) => self.clone().with_parameters(
+                params
+                    .iter()
+                    .filter_map(|p| match p.item() {
+                        ParameterVariant::UInt(v) => Some((p.key().clone(), *v)),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            None => self.clone(),
+        };
+        let (rest, il) = delimited(
+            cut(w(char('('))),
+            recovering_list(
+                w(char(',')),
+                w(|input| scope.interfaces(input)),
+                |element, failed| match sync_element(element) {
+                    "" => sync_element(failed),
+                    rest => rest,
+                },
+            ),
+            cut(char(')')),
+        )(input)?;
+        let (d, metadata) = match d {
+            Some(d) => Metadata::from_doc(&d),
+            None => (None, Metadata::new()),
+        };
+        let result = Streamlet::from_builder(n, il.into_iter().flatten().collect(), d.as_deref())
+            .map(|s| s.with_metadata(metadata))
+            .and_then(|s| match params {
+                Some(params) => s.with_parameters(UniqueKeyBuilder::new().with_items(params)),
+                None => Ok(s),
+            })
+            .map_err(|_| {
+                nom::Err::Failure(VerboseError {
+                    errors: vec![(input, VerboseErrorKind::Context("valid streamlet"))],
+                })
+            })?;
+        Ok((rest, result))
+    }
+
+    pub fn list_of_streamlets<'a>(&self, input: &'a str) -> Result<&'a str, Vec<Streamlet>> {
+        preceded(ws0, separated_list0(ws1, |input| self.streamlet(input)))(input)
+    }
+
+    /// Parses a named constant declaration, e.g. `const WIDTH = 8;`.
+    pub fn constant_declaration<'a>(&self, input: &'a str) -> Result<&'a str, (Name, NonNegative)> {
+        map(
+            tuple((
+                w(doc),
+                terminated(tag("const"), multispace1),
+                cut(context(
+                    "constant name",
+                    w(map_res(name, |n: Name| match self.constant(n.as_ref()) {
+                        Some(_) => Err(()),
+                        None => Ok(n),
+                    })),
+                )),
+                cut(w(char('='))),
+                cut(context("number", w(|input| self.natural(input)))),
+                cut(char(';')),
+            )),
+            |(_, _, n, _, v, _): (_, _, Name, _, NonNegative, _)| (n, v),
+        )(input)
+    }
+
+    /// Parses a named type declaration, e.g. `type Byte = Bits<8>;`.
+    pub fn type_declaration<'a>(&self, input: &'a str) -> Result<&'a str, (Name, LogicalType)> {
+        map(
+            tuple((
+                w(doc),
+                terminated(tag("type"), multispace1),
+                cut(context(
+                    "type name",
+                    w(map_res(name, |n: Name| {
+                        if TYPE_KEYWORDS.contains(&n.as_ref()) {
+                            Err(())
+                        } else {
+                            Ok(n)
+                        }
+                    })),
+                )),
+                cut(w(char('='))),
+                cut(w(context("logical type", |input| {
+                    self.logical_stream_type(input)
+                }))),
+                cut(char(';')),
+            )),
+            |(_, _, n, _, t, _): (_, _, Name, _, LogicalType, _)| (n, t),
+        )(input)
+    }
+
+    /// Parses a named constant or named type declaration.
+    fn declaration<'a>(&self, input: &'a str) -> Result<&'a str, Declaration> {
+        alt((
+            map(
+                |input| self.constant_declaration(input),
+                |(n, v)| Declaration::Constant(n, v),
+            ),
+            map(
+                |input| self.type_declaration(input),
+                |(n, t)| Declaration::Type(n, t),
+            ),
+        ))(input)
+    }
+
+    /// Parses a Streamlet Definition File, consisting of a list of imports of
+    /// other libraries, followed by a list of named constant and named type
+    /// declarations, followed by a list of streamlets.
+    ///
+    /// Named types can be used in the declarations that follow them, in
+    /// addition to the named types in this scope, e.g. those of imported
+    /// libraries. Named constants can be used in the declarations that
+    /// follow them wherever a natural number is expected, e.g.
+    /// `Bits<WIDTH + 1>`, and are only in scope within the file.
+    pub fn library<'a>(&self, input: &'a str) -> Result<&'a str, LibraryContents> {
+        let (mut input, imports) = imports(input)?;
+        let mut scope = self.clone();
+        let mut types = vec![];
+        loop {
+            let declaration = w(|input| scope.declaration(input))(input);
+            match declaration {
+                Ok((rest, Declaration::Constant(n, v))) => {
+                    scope.constants.insert(n, v);
+                    input = rest;
+                }
+                Ok((rest, Declaration::Type(n, t))) => {
+                    scope.types.insert(n.clone(), t.clone());
+                    types.push((n, t));
+                    input = rest;
+                }
+                Err(nom::Err::Failure(e)) if recover(&e) => {
+                    input = input
+                        .find(';')
+                        .map_or(&input[input.len()..], |i| &input[i + 1..]);
+                }
+                Err(nom::Err::Error(_)) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        let (input, streamlets) = recovering_list(
+            ws1,
+            |input| scope.streamlet(input),
+            |element, _| sync_streamlet(element),
+        )(input)?;
+        Ok((input, (imports, types, streamlets)))
+    }
+}
+
+pub fn bits(input: &str) -> Result<&str, LogicalType> {
+    Scope::new().bits(input)
+}
+
+pub fn logical_stream_type(input: &str) -> Result<&str, LogicalType> {
+    Scope::new().logical_stream_type(input)
+}
+
+/// Parse a complete logical type expression, e.g.
+/// `Stream<Group<a: Bits<8>>, d=1>`, ignoring surrounding whitespace.
+///
+/// # Examples
+///
+/// ```rust
+/// use tydi::logical::LogicalType;
+/// use tydi::parser::nom::logical_type;
+///
+/// let typ = logical_type("Group<a: Bits<8>, b: Null>")?;
+/// assert_eq!(
+///     typ,
+///     LogicalType::try_new_group(vec![
+///         ("a", LogicalType::try_new_bits(8)?),
+///         ("b", LogicalType::Null),
+///     ])?
+/// );
+/// assert!(logical_type("Bits<8> trailing").is_err());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn logical_type(input: &str) -> crate::Result<LogicalType> {
+    Scope::new().logical_type(input)
+}
+
+pub fn group(input: &str) -> Result<&str, LogicalType> {
+    Scope::new().group(input)
+}
+
+pub fn union(input: &str) -> Result<&str, LogicalType> {
+    Scope::new().union(input)
+}
+
+/// Parses a stream, see [Scope::stream].
+pub fn stream(input: &str) -> Result<&str, LogicalType> {
+    Scope::new().stream(input)
+}
+
+pub fn interface(input: &str) -> Result<&str, Interface> {
+    Scope::new().interface(input)
+}
+
 pub fn streamlet(input: &str) -> Result<&str, Streamlet> {
-    map_res(
-        tuple((
-            w(doc),
-            w(tag("Streamlet")),
-            w(name),
-            w(tag("(")),
-            separated_list0(w(tag(",")), w(interface)),
-            tag(")"),
-        )),
-        |(d, _, n, _, il, _): (Option<String>, _, Name, _, Vec<Interface>, _)| {
-            Streamlet::from_builder(n, il.into_iter().collect(), d.as_deref())
-        },
-    )(input)
+    Scope::new().streamlet(input)
+}
+
+/// Elaborate a logical type template, substituting the given values for the
+/// streamlet parameters it refers to.
+pub fn elaborate(
+    template: &str,
+    parameters: HashMap<Name, NonNegative>,
+) -> crate::Result<LogicalType> {
+    Scope::new()
+        .with_parameters(parameters)
+        .logical_type(template)
 }
 
 pub fn list_of_streamlets(input: &str) -> Result<&str, Vec<Streamlet>> {
-    map(
-        preceded(ws0, separated_list0(ws1, streamlet)),
-        |l: Vec<Streamlet>| l,
-    )(input)
+    Scope::new().list_of_streamlets(input)
+}
+
+/// Parses a Streamlet Definition File without named types in scope, see
+/// [Scope::library].
+pub fn library(input: &str) -> Result<&str, LibraryContents> {
+    Scope::new().library(input)
+}
+
+/// Whitespace and comments, e.g. at the end of a file.
+pub fn trailing(input: &str) -> Result<&str, ()> {
+    map(many0(alt((multispace1, comment))), |_| ())(input)
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::design::implementation::composer::GenericComponent;
     use crate::design::streamlet::tests::streamlets;
     use crate::util::UniquelyNamedBuilder;
-    use crate::UniqueKeyBuilder;
+    use crate::{Document, UniqueKeyBuilder};
 
     use super::*;
 
@@ -338,12 +1174,34 @@ mod tests {
         assert_eq!(comment("/* this is a comment */"), Ok(("", "")));
         assert_eq!(comment("/* this is a ****** / comment */"), Ok(("", "")));
         assert_eq!(comment("// this is a line comment..."), Ok(("", "")));
+        assert_eq!(comment("# this is a line comment..."), Ok(("", "")));
+        assert_eq!(comment("//\nnext line"), Ok(("\nnext line", "")));
         assert_eq!(
             comment("/// this is a doc comment..."),
             Ok(("", " this is a doc comment..."))
         );
     }
 
+    #[test]
+    fn parse_comments_in_library() {
+        let (rest, (_, _, streamlets)) = library(concat!(
+            "# A library with comments.\n",
+            "Streamlet a ( // interfaces\n",
+            "  x: in /* bytes */ Stream<Bits<8>, /* d=1, */ c=1>, # x\n",
+            "  y: out Bits<8> // y\n",
+            ") /* end of a */\n",
+            "//\n",
+            "Streamlet b (x: in Null)"
+        ))
+        .unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(streamlets.len(), 2);
+        assert_eq!(
+            streamlets[0].to_string(),
+            "Streamlet a (\n  x : in Stream<Bits<8>, c=1>,\n  y : out Bits<8>\n)"
+        );
+    }
+
     #[test]
     fn parse_docstring() {
         assert_eq!(
@@ -475,6 +1333,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_interface_array() {
+        let (_, il) = Scope::new()
+            .interfaces("/// Requests\nreq [ 1 + 2 ] : in Bits<8>")
+            .unwrap();
+        assert_eq!(
+            il.iter().map(|i| i.key().to_string()).collect::<Vec<_>>(),
+            vec!["req_0", "req_1", "req_2"]
+        );
+        assert_eq!(il[1].array().unwrap().index(), 1);
+        assert_eq!(il[1].doc(), Some(" Requests".to_string()));
+        assert!(interface("req[3] : in Bits<8>").is_err());
+        assert!(Scope::new().interfaces("req[0] : in Bits<8>").is_err());
+
+        let (_, s) = streamlet(concat!(
+            "Streamlet arbiter <N = 2> (\n",
+            "  /// Requests\n",
+            "  req[4] : in Stream<Bits<N>>,\n",
+            "  grant : out Stream<Bits<2>>\n",
+            ")",
+        ))
+        .unwrap();
+        assert_eq!(s.interfaces().count(), 5);
+        assert_eq!(
+            s.to_sdf(),
+            concat!(
+                "Streamlet arbiter <N = 2> (\n",
+                "  grant : out Stream<Bits<2>>,\n",
+                "  /// Requests\n",
+                "  req[4] : in Stream<Bits<N>>\n",
+                ")",
+            )
+        );
+        let (_, parsed) = streamlet(s.to_sdf().as_str()).unwrap();
+        assert_eq!(parsed.to_sdf(), s.to_sdf());
+        // The length of an array cannot depend on the parameters of its streamlet.
+        assert!(streamlet("Streamlet arbiter <N = 2> (req[N] : in Null)").is_err());
+    }
+
     #[test]
     fn parse_stream() {
         assert_eq!(
@@ -507,6 +1404,67 @@ mod tests {
                 ).into()
             ))
         );
+
+        // Properties may be given in any order, but only once, and invalid
+        // values are rejected instead of replaced by defaults.
+        assert_eq!(
+            stream("Stream<Null, x=true, u=Bits<2>, r=Reverse, s=Desync>")
+                .unwrap()
+                .1,
+            stream("Stream<Null, s=Desync, r=Reverse, u=Bits<2>, x=true>")
+                .unwrap()
+                .1
+        );
+        for input in &[
+            "Stream<Null, r=Backward>",
+            "Stream<Null, x=1>",
+            "Stream<Null, s=Async>",
+            "Stream<Null, u=Bitz<2>>",
+            "Stream<Null, t=0>",
+            "Stream<Null, q=1>",
+            "Stream<Null, d=1, d=2>",
+        ] {
+            assert!(
+                matches!(stream(input), Err(nom::Err::Failure(_))),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for input in &[
+            "Null",
+            "Bits<8>",
+            "Group<a: Bits<1>, b: Union<c: Null, d: Bits<3>>>",
+            "Stream<Bits<8>>",
+            "Stream<Bits<8>, t=0.5, d=2, s=FlatDesync, c=4.2, r=Reverse, u=Bits<2>, x=true>",
+            "Stream<Group<a: Stream<Bits<1>, d=1>, b: Bits<2>>, t=4>",
+        ] {
+            let (_, typ) = logical_stream_type(input).unwrap();
+            assert_eq!(&typ.to_string(), input);
+        }
+
+        let (_, s) = streamlet(concat!(
+            "/// A streamlet\n",
+            "Streamlet test (\n",
+            "  /// An input\n",
+            "  a : in Stream<Bits<1>, c=7>,\n",
+            "  b : out Null\n",
+            ")",
+        ))
+        .unwrap();
+        assert_eq!(
+            s.to_string(),
+            "Streamlet test (\n  a : in Stream<Bits<1>, c=7>,\n  b : out Null\n)"
+        );
+        let (_, parsed) = streamlet(s.to_sdf().as_str()).unwrap();
+        assert_eq!(parsed.to_sdf(), s.to_sdf());
+        assert_eq!(parsed.doc(), Some(" A streamlet".to_string()));
+
+        let (_, empty) = streamlet("Streamlet empty ()").unwrap();
+        assert_eq!(empty.to_sdf(), "Streamlet empty ()");
     }
 
     #[test]
@@ -611,4 +1569,286 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn parse_library() {
+        assert_eq!(
+            import("import other;"),
+            Ok(("", Name::try_new("other").unwrap()))
+        );
+        assert!(import("importother;").is_err());
+        assert!(import("import other").is_err());
+        assert_eq!(
+            library(concat!(
+                "// Imports\n",
+                "import a;\n",
+                "import b ;\n",
+                "/// Doc string\n",
+                "Streamlet c ( a: in Null, b: out Null)",
+            )),
+            Ok((
+                "",
+                (
+                    vec![Name::try_new("a").unwrap(), Name::try_new("b").unwrap()],
+                    vec![],
+                    vec![streamlets::nulls_streamlet("c").with_doc(" Doc string")]
+                )
+            ))
+        );
+        assert_eq!(
+            library("Streamlet c ( a: in Null, b: out Null)"),
+            Ok(("", (vec![], vec![], vec![streamlets::nulls_streamlet("c")])))
+        );
+    }
+
+    #[test]
+    fn parse_generic_streamlet() {
+        let sdf = concat!(
+            "Streamlet fifo <WIDTH = 8, DEPTH = 2> (\n",
+            "  a : in Stream<Bits<WIDTH>, d=DEPTH, t=DEPTH>,\n",
+            "  b : out Stream<Bits<WIDTH>>\n",
+            ")"
+        );
+        let (_, parsed) = streamlet(sdf).unwrap();
+        assert_eq!(parsed.to_sdf(), sdf);
+        assert_eq!(parsed.parameters().count(), 2);
+        let a = parsed.get_interface(Name::try_new("a").unwrap()).unwrap();
+        assert_eq!(a.template(), Some("Stream<Bits<WIDTH>, d=DEPTH, t=DEPTH>"));
+        assert_eq!(a.typ(), logical_type("Stream<Bits<8>, d=2, t=2>").unwrap());
+        drop(a);
+
+        let elaborated = parsed
+            .elaborate(&[(Name::try_new("DEPTH").unwrap(), 3)])
+            .unwrap();
+        assert_eq!(
+            elaborated
+                .get_interface(Name::try_new("a").unwrap())
+                .unwrap()
+                .typ(),
+            logical_type("Stream<Bits<8>, d=3, t=3>").unwrap()
+        );
+        assert!(parsed
+            .elaborate(&[(Name::try_new("OTHER").unwrap(), 3)])
+            .is_err());
+        assert!(parsed
+            .elaborate(&[(Name::try_new("WIDTH").unwrap(), 0)])
+            .is_err());
+
+        // Parameters are only in scope within their streamlet.
+        assert!(streamlet("Streamlet x <W = 1> ( a : in Bits<V> )").is_err());
+        assert!(bits("Bits<W>").is_err());
+        let scope = Scope::new()
+            .with_parameters(vec![(Name::try_new("W").unwrap(), 3)].into_iter().collect());
+        assert_eq!(
+            scope.bits("Bits<W + 1>"),
+            Ok(("", LogicalType::try_new_bits(4).unwrap()))
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Location::of("ab\ncd", &"ab\ncd"[4..]),
+            Location { line: 2, column: 2 }
+        );
+        assert_eq!(
+            logical_type("Bits<8").unwrap_err().to_string(),
+            "Parsing error: expected '>', found end of input at 1:7\n  |\n1 | Bits<8\n  |       ^"
+        );
+        assert_eq!(
+            logical_type("Stream<Bitz<8>>").unwrap_err().to_string(),
+            "Parsing error: expected logical type, found \"Bitz\" at 1:8\n  |\n\
+             1 | Stream<Bitz<8>>\n  |        ^"
+        );
+        assert!(logical_type("Bits<8> trailing")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Parsing error: expected end of input, found \"trailing\" at 1:9"));
+
+        let report = |input: &str| match library(input) {
+            Ok((rest, _)) => panic!("unexpected success, remaining: {}", rest),
+            Err(e) => report_error("lib.sdf", input, &e),
+        };
+        assert_eq!(
+            report("Streamlet a (\n  x : inn Null\n)"),
+            "expected mode, found \"inn\" at lib.sdf:2:7\n  |\n2 |   x : inn Null\n  |       ^"
+        );
+        assert!(report("Streamlet a (\n  x : in Null\n  y : out Null\n)")
+            .starts_with("expected ')', found \"y\" at lib.sdf:3:3"));
+        assert!(report("Streamlet a (\n  x : in Stream<Null, t=>\n)")
+            .starts_with("expected positive throughput, found \">\" at lib.sdf:2:25"));
+        assert!(
+            report("Streamlet a (x : in Stream<Null, d=1, d=2>)").starts_with(
+                "expected stream property that is not yet set, found \"d\" at lib.sdf:1:39"
+            )
+        );
+        assert!(report("type Bits = Bits<8>;")
+            .starts_with("expected type name, found \"Bits\" at lib.sdf:1:6"));
+        assert!(
+            report("import lib").starts_with("expected ';', found end of input at lib.sdf:1:11")
+        );
+        assert!(
+            report("Streamlet a (x : in Group<a: Null, b: Null, a: Null>)").starts_with(
+                "expected field name that is not yet used, found \"a\" at lib.sdf:1:45"
+            )
+        );
+        assert!(report("Streamlet a (x : in Union<a: Null, a: Bits<1>>)")
+            .starts_with("expected field name that is not yet used, found \"a\" at lib.sdf:1:36"));
+        assert!(
+            report("const W = 8;\nStreamlet a (x : in Stream<Bits<W - 8>>)")
+                .starts_with("expected bit count that is not zero, found \"W\" at lib.sdf:2:33")
+        );
+        assert_eq!(
+            logical_type("Bits<0>").unwrap_err().to_string(),
+            "Parsing error: expected bit count that is not zero, found \"0\" at 1:6\n  |\n\
+             1 | Bits<0>\n  |      ^"
+        );
+        assert!(report("Streamlet a (x : in Null, x : out Null)")
+            .starts_with("expected valid streamlet, found \"(\" at lib.sdf:1:13"));
+    }
+
+    #[test]
+    fn parse_recovery() {
+        let input = concat!(
+            "Streamlet a (\n",
+            "  x : inn Null,\n",
+            "  y : in Stream<Bitz<8>, d=1>,\n",
+            "  z : out Null\n",
+            ")\n",
+            "Streamlet 1b (x : in Null)\n",
+            "Streamlet c (x : in Null)",
+        );
+        let (result, recovered) = with_recovery(input, || library(input));
+        let (rest, (_, _, streamlets)) = result.unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            recovered
+                .iter()
+                .map(|(rest, expected)| (Location::of(input, rest), expected.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Location { line: 2, column: 7 }, "mode"),
+                (
+                    Location {
+                        line: 3,
+                        column: 17
+                    },
+                    "logical type"
+                ),
+                (
+                    Location {
+                        line: 6,
+                        column: 11
+                    },
+                    "streamlet name"
+                ),
+            ]
+        );
+        assert_eq!(
+            streamlets
+                .iter()
+                .map(|s| s.interfaces().map(|i| i.key().to_string()).collect())
+                .collect::<Vec<Vec<_>>>(),
+            vec![vec!["z".to_string()], vec!["x".to_string()]]
+        );
+
+        // Without recovery, parsing stops at the first error.
+        assert!(library(input).is_err());
+    }
+
+    #[test]
+    fn parse_constants() {
+        assert_eq!(Scope::new().natural("2 + 3 * (4 - 1) / 2"), Ok(("", 6)));
+        assert!(Scope::new().natural("1 - 2").is_err());
+        assert!(Scope::new().natural("1 / 0").is_err());
+
+        let (_, (_, types, streamlets)) = library(concat!(
+            "const WIDTH = 8;\n",
+            "const ID_WIDTH = WIDTH * 2 + 1;\n",
+            "type Id = Bits<ID_WIDTH>;\n",
+            "Streamlet x <N = WIDTH / 4> (\n",
+            "  a : in Stream<Bits<ID_WIDTH - 1>, d=N+1, t=(N + 2) * 2>\n",
+            ")",
+        ))
+        .unwrap();
+        assert_eq!(types[0].1, LogicalType::try_new_bits(17).unwrap());
+        let x = &streamlets[0];
+        assert_eq!(
+            x.parameters().next().unwrap().item(),
+            &ParameterVariant::UInt(2)
+        );
+        let a = x.get_interface(Name::try_new("a").unwrap()).unwrap();
+        assert_eq!(
+            a.template(),
+            Some("Stream<Bits<17 - 1>, d=N+1, t=(N + 2) * 2>")
+        );
+        assert_eq!(a.typ(), logical_type("Stream<Bits<16>, d=3, t=8>").unwrap());
+        drop(a);
+        assert_eq!(
+            x.elaborate(&[(Name::try_new("N").unwrap(), 1)])
+                .unwrap()
+                .get_interface(Name::try_new("a").unwrap())
+                .unwrap()
+                .typ(),
+            logical_type("Stream<Bits<16>, d=2, t=6>").unwrap()
+        );
+
+        // Constants are only in scope within their library, and cannot be
+        // declared twice.
+        assert!(library("Streamlet x (a : in Bits<WIDTH>)").is_err());
+        assert!(library("const A = 1;\nconst A = 2;").is_err());
+        assert!(library("const A = 1;\nStreamlet x (a : in Bits<A - 2>)").is_err());
+    }
+
+    #[test]
+    fn parse_named_types() {
+        assert!(Scope::new()
+            .type_declaration("type Bits = Bits<8>;")
+            .is_err());
+        assert!(Scope::new().named_type("Byte").is_err());
+
+        let (_, (imports, types, streamlets)) = library(concat!(
+            "type Byte = Bits<8>;\n",
+            "/// A pair of bytes.\n",
+            "type Pair = Group<a: Byte, b: Byte>;\n",
+            "Streamlet x (a : in Stream<Pair>, b : out Byte)\n",
+            "Streamlet y <N = 1> (a : in Stream<Pair, d=N>)",
+        ))
+        .unwrap();
+        assert!(imports.is_empty());
+        let byte = LogicalType::try_new_bits(8).unwrap();
+        let pair =
+            LogicalType::try_new_group(vec![("a", byte.clone()), ("b", byte.clone())]).unwrap();
+        assert_eq!(
+            types,
+            vec![
+                (Name::try_new("Byte").unwrap(), byte.clone()),
+                (Name::try_new("Pair").unwrap(), pair.clone())
+            ]
+        );
+        let iface = |s: &Streamlet, name: &str| {
+            s.get_interface(Name::try_new(name).unwrap())
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(iface(&streamlets[0], "b").typ(), byte);
+        assert_eq!(
+            iface(&streamlets[1], "a").template(),
+            Some("Stream<Group<a: Bits<8>, b: Bits<8>>, d=N>")
+        );
+
+        // Named types are only in scope within their library, unless they
+        // are explicitly brought into scope.
+        assert!(Scope::new().named_type("Byte").is_err());
+        let scope = vec![(Name::try_new("Byte").unwrap(), byte.clone())]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            Scope::new()
+                .with_types(scope)
+                .logical_type("Stream<Byte>")
+                .unwrap(),
+            logical_type("Stream<Bits<8>>").unwrap()
+        );
+    }
 }
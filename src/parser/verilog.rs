@@ -0,0 +1,1010 @@
+//! Parser for the headers of Verilog modules.
+//!
+//! Verilog IP can be referenced from a project as blackbox streamlets, that
+//! are implemented externally by their module. The ports of a module are
+//! grouped into physical streams by the naming convention of canonical
+//! components: the ports `<name>_valid` and `<name>_ready`, and optionally
+//! `<name>_data`, `<name>_last`, `<name>_stai`, `<name>_endi`, `<name>_strb`
+//! and `<name>_user`, form the stream interface `<name>`. The element lanes,
+//! dimensionality and complexity of the stream are inferred from the widths
+//! of these ports. Other ports become interfaces of type `Bits`, except for
+//! the `clk` and `rst` ports of the streamlet.
+//!
+//! Only the headers of modules are parsed, i.e. their names, parameters and
+//! ports, in both ANSI and non-ANSI style. Port widths may refer to the
+//! parameters of a module and to macros, and are evaluated with the default
+//! values of the parameters.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use crate::design::{Interface, Mode, Streamlet};
+use crate::logical::{Direction, LogicalType, Stream, Synchronicity, SynthesisCache};
+use crate::physical::Complexity;
+use crate::{Error, Name, NonNegative, PositiveReal, Result, UniqueKeyBuilder};
+
+/// The signals of a physical stream besides its handshake, by the suffix of
+/// their port names.
+const STREAM_SIGNALS: &[&str] = &["data", "last", "stai", "endi", "strb", "user"];
+
+/// The direction of a port of a Verilog module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PortDirection {
+    Input,
+    Output,
+    Inout,
+}
+
+/// A port of a Verilog module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Port {
+    name: String,
+    direction: PortDirection,
+    width: NonNegative,
+}
+
+impl Port {
+    /// Returns the name of this port.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the direction of this port.
+    pub fn direction(&self) -> PortDirection {
+        self.direction
+    }
+
+    /// Returns the width of this port in bits.
+    pub fn width(&self) -> NonNegative {
+        self.width
+    }
+}
+
+/// The header of a Verilog module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Module {
+    name: String,
+    ports: Vec<Port>,
+}
+
+impl Module {
+    /// Returns the name of this module.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns an iterator over the ports of this module, in declaration
+    /// order.
+    pub fn ports(&self) -> impl Iterator<Item = &Port> {
+        self.ports.iter()
+    }
+
+    /// Returns a blackbox streamlet for this module, with its ports grouped
+    /// into interfaces as described in the [module documentation](self),
+    /// and implemented externally by this module.
+    pub fn blackbox(&self) -> Result<Streamlet> {
+        self.blackbox_with(&SynthesisCache::new())
+    }
+
+    /// Returns a blackbox streamlet for this module, see [Module::blackbox],
+    /// synthesizing the stream types of its interfaces with a cache.
+    pub(crate) fn blackbox_with(&self, cache: &SynthesisCache) -> Result<Streamlet> {
+        let port = |name: &str| self.ports.iter().find(|p| p.name == name);
+        let mut used = vec![];
+        let mut interfaces = vec![];
+
+        for valid in &self.ports {
+            let prefix = match valid.name.strip_suffix("_valid") {
+                Some(prefix) => prefix,
+                None => continue,
+            };
+            let ready = match port(&format!("{}_ready", prefix)) {
+                Some(ready) => ready,
+                None => continue,
+            };
+            let error = |message: String| {
+                Error::InterfaceError(format!(
+                    "Ports of stream {} of module {}: {}",
+                    prefix, self.name, message
+                ))
+            };
+            let mode = match (valid.direction, ready.direction) {
+                (PortDirection::Input, PortDirection::Output) => Mode::In,
+                (PortDirection::Output, PortDirection::Input) => Mode::Out,
+                _ => {
+                    return Err(error(
+                        "valid and ready must be an input and an output".to_string(),
+                    ))
+                }
+            };
+            if valid.width != 1 || ready.width != 1 {
+                return Err(error("valid and ready must be single bits".to_string()));
+            }
+            used.push(valid.name.as_str());
+            used.push(ready.name.as_str());
+
+            let mut widths = [0; 6];
+            for (signal, width) in STREAM_SIGNALS.iter().zip(widths.iter_mut()) {
+                if let Some(port) = port(&format!("{}_{}", prefix, signal)) {
+                    if port.direction != valid.direction {
+                        return Err(error(format!(
+                            "{} must have the same direction as valid",
+                            signal
+                        )));
+                    }
+                    *width = port.width;
+                    used.push(port.name.as_str());
+                }
+            }
+            interfaces.push(Interface::try_new(
+                prefix,
+                mode,
+                stream_type(widths, cache).map_err(error)?,
+                None,
+            )?);
+        }
+
+        for port in &self.ports {
+            if used.contains(&port.name.as_str()) || port.name == "clk" || port.name == "rst" {
+                continue;
+            }
+            let mode = match port.direction {
+                PortDirection::Input => Mode::In,
+                PortDirection::Output => Mode::Out,
+                PortDirection::Inout => {
+                    return Err(Error::InterfaceError(format!(
+                        "Port {} of module {} is bidirectional",
+                        port.name, self.name
+                    )))
+                }
+            };
+            interfaces.push(Interface::try_new(
+                port.name.as_str(),
+                mode,
+                LogicalType::try_new_bits(port.width)?,
+                None,
+            )?);
+        }
+
+        Ok(Streamlet::from_builder(
+            Name::try_new(self.name.as_str())?,
+            UniqueKeyBuilder::new().with_items(interfaces),
+            None,
+        )?
+        .with_external(None, &self.name))
+    }
+}
+
+/// Returns the stream type of which the physical stream has the given
+/// widths of its `data`, `last`, `stai`, `endi`, `strb` and `user` signals,
+/// or a message describing why there is none.
+fn stream_type(
+    widths: [NonNegative; 6],
+    cache: &SynthesisCache,
+) -> std::result::Result<LogicalType, String> {
+    let [data, last, stai, endi, strb, user] = widths;
+    let index = stai.max(endi);
+    if index > 16 {
+        return Err(format!("index signals of {} bits are too wide", index));
+    }
+    let lanes = if strb > 0 {
+        strb
+    } else if index > 0 {
+        // The index signals have log2_ceil(lanes) bits.
+        ((1 << (index - 1)) + 1..=1 << index)
+            .rev()
+            .find(|lanes| data % lanes == 0)
+            .ok_or_else(|| format!("no number of lanes matches data width {}", data))?
+    } else {
+        1
+    };
+    if data % lanes != 0 {
+        return Err(format!(
+            "data width {} is not a multiple of the {} lanes",
+            data, lanes
+        ));
+    }
+
+    let mut complexity = 4;
+    if endi > 0 && last == 0 {
+        complexity = 5;
+    }
+    if stai > 0 {
+        complexity = 6;
+    }
+    if strb > 0 && last == 0 {
+        complexity = 7;
+    }
+    let bits = |width| LogicalType::try_new_bits(width).map_err(|e| e.to_string());
+    let typ = LogicalType::from(Stream::new(
+        if data == 0 {
+            LogicalType::Null
+        } else {
+            bits(data / lanes)?
+        },
+        PositiveReal::new(lanes.into()).map_err(|e| e.to_string())?,
+        last,
+        Synchronicity::Sync,
+        Complexity::new_major(complexity),
+        Direction::Forward,
+        if user == 0 { None } else { Some(bits(user)?) },
+        false,
+    ));
+
+    let synthesized = cache.synthesize(&typ);
+    let (_, stream) = synthesized
+        .streams()
+        .next()
+        .ok_or_else(|| "no physical stream".to_string())?;
+    let expected = [
+        stream.data_bit_count(),
+        stream.last_bit_count(),
+        stream.stai_bit_count(),
+        stream.endi_bit_count(),
+        stream.strb_bit_count(),
+        stream.user_bit_count(),
+    ];
+    match STREAM_SIGNALS
+        .iter()
+        .zip(widths.iter().zip(expected.iter()))
+        .find(|(_, (width, expected))| u64::from(**width) != **expected)
+    {
+        Some((signal, (width, expected))) => Err(format!(
+            "{} has {} bits, but a {} stream has {}",
+            signal, width, typ, expected
+        )),
+        None => Ok(typ),
+    }
+}
+
+/// Parse the headers of all modules in a Verilog source.
+pub fn modules(input: &str) -> Result<Vec<Module>> {
+    let mut parser = Parser {
+        input,
+        tokens: tokenize(input)?,
+        pos: 0,
+    };
+    let mut result = vec![];
+    while let Some(token) = parser.advance() {
+        if token == Token::Ident("module") || token == Token::Ident("macromodule") {
+            result.push(parser.module()?);
+        }
+    }
+    Ok(result)
+}
+
+/// A token of a Verilog source.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token<'a> {
+    /// An identifier or keyword.
+    Ident(&'a str),
+    /// A number, possibly sized and based, e.g. `8'hff`.
+    Number(&'a str),
+    /// A string literal.
+    Str,
+    /// An operator or punctuation.
+    Symbol(&'a str),
+}
+
+/// Split a Verilog source into tokens, paired with their offsets in the
+/// source. Comments and attributes are skipped, and macros are expanded.
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>> {
+    let mut macros = HashMap::new();
+    tokenize_with(input, None, &mut macros, 0)
+}
+
+fn tokenize_with<'a>(
+    input: &'a str,
+    offset: Option<usize>,
+    macros: &mut HashMap<&'a str, &'a str>,
+    depth: usize,
+) -> Result<Vec<(usize, Token<'a>)>> {
+    if depth > 16 {
+        return Err(Error::ParsingError(
+            "Verilog macros are nested too deeply".to_string(),
+        ));
+    }
+    let bytes = input.as_bytes();
+    let ident = |c: u8| c.is_ascii_alphanumeric() || c == b'_' || c == b'$';
+    let skip = |pos: usize, pred: &dyn Fn(u8) -> bool| {
+        pos + bytes[pos..].iter().take_while(|c| pred(**c)).count()
+    };
+    let find = |pos: usize, pattern: &str| {
+        input[pos..]
+            .find(pattern)
+            .map(|i| pos + i + pattern.len())
+            .unwrap_or_else(|| input.len())
+    };
+    let mut result = vec![];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let start = pos;
+        let at = offset.unwrap_or(start);
+        let c = bytes[pos];
+        if c.is_ascii_whitespace() {
+            pos += 1;
+        } else if input[pos..].starts_with("//") {
+            pos = find(pos, "\n");
+        } else if input[pos..].starts_with("/*") {
+            pos = find(pos + 2, "*/");
+        } else if input[pos..].starts_with("(*") && !input[pos + 2..].starts_with(')') {
+            pos = find(pos + 2, "*)");
+        } else if c == b'"' {
+            pos += 1;
+            while pos < bytes.len() && bytes[pos] != b'"' {
+                pos += if bytes[pos] == b'\\' { 2 } else { 1 };
+            }
+            pos = (pos + 1).min(bytes.len());
+            result.push((at, Token::Str));
+        } else if c == b'`' {
+            pos = skip(pos + 1, &ident);
+            let directive = &input[start + 1..pos];
+            let line = find(pos, "\n");
+            match directive {
+                "define" => {
+                    let name_start = skip(pos, &|c| c == b' ' || c == b'\t');
+                    let name_end = skip(name_start, &ident);
+                    macros.insert(&input[name_start..name_end], input[name_end..line].trim());
+                    pos = line;
+                }
+                "timescale" | "include" | "default_nettype" | "ifdef" | "ifndef" | "elsif"
+                | "undef" | "line" | "pragma" => pos = line,
+                "else" | "endif" | "resetall" | "celldefine" | "endcelldefine" => {}
+                _ => match macros.get(directive) {
+                    Some(text) => {
+                        let text = *text;
+                        result.extend(tokenize_with(text, Some(at), macros, depth + 1)?);
+                    }
+                    None => {
+                        return Err(Error::ParsingError(format!(
+                            "Verilog macro {} is not defined",
+                            directive
+                        )))
+                    }
+                },
+            }
+        } else if c == b'\\' {
+            pos = skip(pos, &|c| !c.is_ascii_whitespace());
+            result.push((at, Token::Ident(&input[start..pos])));
+        } else if c.is_ascii_alphabetic() || c == b'_' || c == b'$' {
+            pos = skip(pos, &ident);
+            result.push((at, Token::Ident(&input[start..pos])));
+        } else if c.is_ascii_digit() || c == b'\'' {
+            pos = skip(pos, &|c| c.is_ascii_digit() || c == b'_');
+            let base = skip(pos, &|c| c == b' ' || c == b'\t');
+            if bytes.get(base) == Some(&b'\'') {
+                pos = skip(base + 1, &|c| c == b's' || c == b'S');
+                pos = skip(pos, &|c| {
+                    c.is_ascii_alphanumeric() || c == b'_' || c == b'?'
+                });
+            }
+            result.push((at, Token::Number(&input[start..pos])));
+        } else {
+            pos += ["**", "<<", ">>"]
+                .iter()
+                .find(|s| input[pos..].starts_with(*s))
+                .map(|s| s.len())
+                .unwrap_or_else(|| input[pos..].chars().next().unwrap().len_utf8());
+            result.push((at, Token::Symbol(&input[start..pos])));
+        }
+    }
+    Ok(result)
+}
+
+/// A parser of the headers of Verilog modules.
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<(usize, Token<'a>)>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).map(|(_, token)| *token)
+    }
+
+    fn advance(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    /// Returns a parsing error at the current token.
+    fn error(&self, message: impl std::fmt::Display) -> Error {
+        self.error_at(None, message)
+    }
+
+    /// Returns a parsing error at an offset in the source, or at the
+    /// current token if there is none.
+    fn error_at(&self, offset: Option<usize>, message: impl std::fmt::Display) -> Error {
+        let offset = offset
+            .or_else(|| {
+                self.tokens
+                    .get(self.pos.min(self.tokens.len().saturating_sub(1)))
+                    .map(|(offset, _)| *offset)
+            })
+            .unwrap_or(0);
+        let line = self.input[..offset].matches('\n').count() + 1;
+        Error::ParsingError(format!("Verilog line {}: {}", line, message))
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<()> {
+        match self.advance() {
+            Some(Token::Symbol(s)) if s == symbol => Ok(()),
+            _ => {
+                self.pos -= 1;
+                Err(self.error(format!("expected {}", symbol)))
+            }
+        }
+    }
+
+    fn ident(&mut self) -> Result<&'a str> {
+        match self.advance() {
+            Some(Token::Ident(ident)) => Ok(ident),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("expected identifier"))
+            }
+        }
+    }
+
+    /// Returns the tokens up to a separator or closing bracket at the
+    /// current nesting level, without consuming the closing bracket.
+    fn group(&mut self, separators: &[&str]) -> Vec<(usize, Token<'a>)> {
+        let start = self.pos;
+        let mut depth = 0;
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Symbol("(") | Token::Symbol("[") | Token::Symbol("{") => depth += 1,
+                Token::Symbol(")") | Token::Symbol("]") | Token::Symbol("}") if depth == 0 => break,
+                Token::Symbol(")") | Token::Symbol("]") | Token::Symbol("}") => depth -= 1,
+                Token::Symbol(s) if depth == 0 && separators.contains(&s) => break,
+                _ => (),
+            }
+            self.pos += 1;
+        }
+        self.tokens[start..self.pos].to_vec()
+    }
+
+    /// Parse a module after the `module` keyword.
+    fn module(&mut self) -> Result<Module> {
+        let name = self.ident()?;
+        let mut parameters = HashMap::new();
+        let mut ports: Vec<(&str, Option<(PortDirection, NonNegative)>)> = vec![];
+
+        // Package imports in the header.
+        while self.peek() == Some(Token::Ident("import")) {
+            self.group(&[";"]);
+            self.expect(";")?;
+        }
+        if self.peek() == Some(Token::Symbol("#")) {
+            self.advance();
+            self.expect("(")?;
+            while self.peek() != Some(Token::Symbol(")")) {
+                self.parameter(&mut parameters)?;
+                if self.peek() == Some(Token::Symbol(",")) {
+                    self.advance();
+                }
+            }
+            self.expect(")")?;
+        }
+        if self.peek() == Some(Token::Symbol("(")) {
+            self.advance();
+            let mut previous = None;
+            while self.peek() != Some(Token::Symbol(")")) {
+                let item = self.group(&[","]);
+                let port = self.port(&item, previous, &parameters)?;
+                previous = port.1;
+                ports.push(port);
+                if self.peek() == Some(Token::Symbol(",")) {
+                    self.advance();
+                }
+            }
+            self.expect(")")?;
+        }
+        self.expect(";")?;
+
+        // Declarations in the body, of parameters and non-ANSI ports.
+        loop {
+            match self.advance() {
+                None => return Err(self.error(format!("module {} has no endmodule", name))),
+                Some(Token::Ident("endmodule")) => break,
+                Some(Token::Ident(block @ "function")) | Some(Token::Ident(block @ "task")) => {
+                    let end = format!("end{}", block);
+                    while !matches!(self.advance(), Some(Token::Ident(e)) if e == end) {
+                        if self.peek().is_none() {
+                            return Err(self.error(format!("{} has no {}", block, end)));
+                        }
+                    }
+                }
+                Some(Token::Ident("parameter")) | Some(Token::Ident("localparam")) => {
+                    while self.peek() != Some(Token::Symbol(";")) {
+                        self.parameter(&mut parameters)?;
+                        if self.peek() == Some(Token::Symbol(",")) {
+                            self.advance();
+                        }
+                    }
+                }
+                Some(Token::Ident(keyword @ "input"))
+                | Some(Token::Ident(keyword @ "output"))
+                | Some(Token::Ident(keyword @ "inout")) => {
+                    self.pos -= 1;
+                    let declaration = self.group(&[";"]);
+                    let (types, names) = split_declaration(&declaration);
+                    let (_, port) = self.port(&types, None, &parameters)?;
+                    for port_name in names {
+                        match ports.iter_mut().find(|(n, _)| *n == port_name) {
+                            Some((_, declared)) => *declared = port,
+                            None => {
+                                return Err(self.error(format!(
+                                    "{} {} is not a port of module {}",
+                                    keyword, port_name, name
+                                )))
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Ok(Module {
+            name: name.to_string(),
+            ports: ports
+                .into_iter()
+                .map(|(port, declaration)| match declaration {
+                    Some((direction, width)) => Ok(Port {
+                        name: port.to_string(),
+                        direction,
+                        width,
+                    }),
+                    None => {
+                        Err(self
+                            .error(format!("port {} of module {} has no direction", port, name)))
+                    }
+                })
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Parse a parameter declaration up to the next comma, semicolon or
+    /// closing parenthesis, and record its value if it can be evaluated.
+    fn parameter(&mut self, parameters: &mut HashMap<&'a str, i64>) -> Result<()> {
+        let declaration = self.group(&[",", ";"]);
+        let assignment = declaration
+            .iter()
+            .position(|(_, t)| *t == Token::Symbol("="));
+        let name = declaration[..assignment.unwrap_or(declaration.len())]
+            .iter()
+            .rev()
+            .find_map(|(_, token)| match token {
+                Token::Ident(ident) if !matches!(*ident, "parameter" | "localparam") => {
+                    Some(*ident)
+                }
+                _ => None,
+            });
+        match (name, assignment) {
+            (Some(name), Some(assignment)) => {
+                let value = Expression {
+                    tokens: &declaration[assignment + 1..],
+                    pos: 0,
+                    parameters,
+                }
+                .evaluate();
+                if let Ok(value) = value {
+                    parameters.insert(name, value);
+                }
+                Ok(())
+            }
+            _ => Err(self.error("expected parameter assignment")),
+        }
+    }
+
+    /// Parse a port of a port list, or the type of a port declaration, that
+    /// starts at the given token. ANSI ports without a direction take the
+    /// direction and width of the previous port. Returns the name of the
+    /// port, and its direction and width if they are declared.
+    fn port(
+        &self,
+        tokens: &[(usize, Token<'a>)],
+        previous: Option<(PortDirection, NonNegative)>,
+        parameters: &HashMap<&'a str, i64>,
+    ) -> Result<(&'a str, Option<(PortDirection, NonNegative)>)> {
+        let error = |index: usize, message: String| {
+            let offset = tokens.get(index).or_else(|| tokens.last());
+            self.error_at(offset.map(|(offset, _)| *offset), message)
+        };
+        let mut index = 0;
+        let direction = match tokens.first() {
+            Some((_, Token::Ident("input"))) => Some(PortDirection::Input),
+            Some((_, Token::Ident("output"))) => Some(PortDirection::Output),
+            Some((_, Token::Ident("inout"))) => Some(PortDirection::Inout),
+            _ => None,
+        };
+        if direction.is_some() {
+            index += 1;
+        }
+        let mut width: Option<i64> = None;
+        while let Some((_, token)) = tokens.get(index) {
+            match token {
+                Token::Ident(
+                    "wire" | "reg" | "logic" | "var" | "tri" | "wand" | "wor" | "signed"
+                    | "unsigned" | "bit",
+                ) => index += 1,
+                Token::Ident(
+                    keyword @ ("integer" | "int" | "byte" | "shortint" | "longint" | "time"),
+                ) => {
+                    width = Some(match *keyword {
+                        "byte" => 8,
+                        "shortint" => 16,
+                        "longint" | "time" => 64,
+                        _ => 32,
+                    });
+                    index += 1;
+                }
+                Token::Symbol("[") => {
+                    let close = matching(tokens, index)
+                        .ok_or_else(|| error(index, "unclosed range".to_string()))?;
+                    let range = &tokens[index + 1..close];
+                    let colon = range
+                        .iter()
+                        .position(|(_, t)| *t == Token::Symbol(":"))
+                        .ok_or_else(|| error(index, "expected range".to_string()))?;
+                    let bound = |tokens: &[(usize, Token<'a>)]| {
+                        Expression {
+                            tokens,
+                            pos: 0,
+                            parameters,
+                        }
+                        .evaluate()
+                        .map_err(|e| error(index, e))
+                    };
+                    let msb = bound(&range[..colon])?;
+                    let lsb = bound(&range[colon + 1..])?;
+                    width = Some(width.unwrap_or(1) * ((msb - lsb).abs() + 1));
+                    index = close + 1;
+                }
+                _ => break,
+            }
+        }
+        let name = match tokens.get(index) {
+            Some((_, Token::Ident(name))) => *name,
+            Some((_, Token::Symbol("."))) => {
+                return Err(error(
+                    index,
+                    "port expressions are not supported".to_string(),
+                ))
+            }
+            _ => return Err(error(index, "expected port name".to_string())),
+        };
+        match tokens.get(index + 1) {
+            None | Some((_, Token::Symbol("="))) => (),
+            Some((_, Token::Symbol("["))) => {
+                return Err(error(
+                    index + 1,
+                    format!("unpacked dimensions of port {} are not supported", name),
+                ))
+            }
+            Some(_) => {
+                return Err(error(
+                    index + 1,
+                    format!("interface port {} is not supported", name),
+                ))
+            }
+        }
+        let width = width.unwrap_or(1);
+        let width = NonNegative::try_from(width)
+            .map_err(|_| error(index, format!("port {} has width {}", name, width)))?;
+        Ok((
+            name,
+            match direction {
+                Some(direction) => Some((direction, width)),
+                None if index == 0 => previous,
+                None => previous.map(|(direction, _)| (direction, width)),
+            },
+        ))
+    }
+}
+
+/// Split a non-ANSI port declaration into the tokens of its type and the
+/// names of its ports, e.g. `input [7:0] a, b`.
+fn split_declaration<'a>(tokens: &[(usize, Token<'a>)]) -> (Vec<(usize, Token<'a>)>, Vec<&'a str>) {
+    let first = tokens
+        .iter()
+        .position(|(_, t)| *t == Token::Symbol(","))
+        .unwrap_or(tokens.len());
+    let mut types = tokens[..first].to_vec();
+    let mut names = vec![];
+    if let Some((offset, Token::Ident(name))) = types.pop() {
+        names.push(name);
+        // Keep a placeholder for the name, such that the type parses as a
+        // port.
+        types.push((offset, Token::Ident("_")));
+    }
+    names.extend(tokens[first..].iter().filter_map(|(_, t)| match t {
+        Token::Ident(name) => Some(*name),
+        _ => None,
+    }));
+    (types, names)
+}
+
+/// Returns the index of the bracket that closes the bracket at an index.
+fn matching(tokens: &[(usize, Token)], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (index, (_, token)) in tokens.iter().enumerate().skip(open) {
+        match token {
+            Token::Symbol("(") | Token::Symbol("[") | Token::Symbol("{") => depth += 1,
+            Token::Symbol(")") | Token::Symbol("]") | Token::Symbol("}") => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+/// A constant integer expression, e.g. the bound of a range.
+struct Expression<'t, 'a> {
+    tokens: &'t [(usize, Token<'a>)],
+    pos: usize,
+    parameters: &'t HashMap<&'a str, i64>,
+}
+
+impl<'t, 'a> Expression<'t, 'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).map(|(_, token)| *token)
+    }
+
+    /// Evaluate the complete expression.
+    fn evaluate(mut self) -> std::result::Result<i64, String> {
+        let value = self.shift()?;
+        match self.peek() {
+            None => Ok(value),
+            Some(token) => Err(format!("unsupported expression at {:?}", token)),
+        }
+    }
+
+    fn binary(
+        &mut self,
+        operators: &[&str],
+        operand: fn(&mut Self) -> std::result::Result<i64, String>,
+    ) -> std::result::Result<i64, String> {
+        let mut value = operand(self)?;
+        while let Some(Token::Symbol(operator)) = self.peek() {
+            if !operators.contains(&operator) {
+                break;
+            }
+            self.pos += 1;
+            let rhs = operand(self)?;
+            value = match operator {
+                "<<" => value.checked_shl(rhs as u32),
+                ">>" => value.checked_shr(rhs as u32),
+                "+" => value.checked_add(rhs),
+                "-" => value.checked_sub(rhs),
+                "*" => value.checked_mul(rhs),
+                "/" => value.checked_div(rhs),
+                _ => value.checked_rem(rhs),
+            }
+            .ok_or_else(|| format!("invalid operation {} {} {}", value, operator, rhs))?;
+        }
+        Ok(value)
+    }
+
+    fn shift(&mut self) -> std::result::Result<i64, String> {
+        self.binary(&["<<", ">>"], Self::sum)
+    }
+
+    fn sum(&mut self) -> std::result::Result<i64, String> {
+        self.binary(&["+", "-"], Self::product)
+    }
+
+    fn product(&mut self) -> std::result::Result<i64, String> {
+        self.binary(&["*", "/", "%"], Self::power)
+    }
+
+    fn power(&mut self) -> std::result::Result<i64, String> {
+        let base = self.unary()?;
+        if self.peek() == Some(Token::Symbol("**")) {
+            self.pos += 1;
+            let exponent = self.power()?;
+            return u32::try_from(exponent)
+                .ok()
+                .and_then(|exponent| base.checked_pow(exponent))
+                .ok_or_else(|| format!("invalid operation {} ** {}", base, exponent));
+        }
+        Ok(base)
+    }
+
+    fn unary(&mut self) -> std::result::Result<i64, String> {
+        let token = self
+            .peek()
+            .ok_or_else(|| "incomplete expression".to_string())?;
+        self.pos += 1;
+        match token {
+            Token::Symbol("-") => Ok(-self.unary()?),
+            Token::Symbol("+") => self.unary(),
+            Token::Symbol("(") => {
+                let value = self.shift()?;
+                self.close()?;
+                Ok(value)
+            }
+            Token::Ident("$clog2") => {
+                self.open()?;
+                let value = self.shift()?;
+                self.close()?;
+                Ok(match value {
+                    v if v <= 1 => 0,
+                    v => 64 - i64::from((v - 1).leading_zeros()),
+                })
+            }
+            Token::Ident(name) => self
+                .parameters
+                .get(name)
+                .copied()
+                .ok_or_else(|| format!("unknown parameter {}", name)),
+            Token::Number(number) => number_value(number),
+            token => Err(format!("unsupported expression at {:?}", token)),
+        }
+    }
+
+    fn open(&mut self) -> std::result::Result<(), String> {
+        match self.peek() {
+            Some(Token::Symbol("(")) => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err("expected (".to_string()),
+        }
+    }
+
+    fn close(&mut self) -> std::result::Result<(), String> {
+        match self.peek() {
+            Some(Token::Symbol(")")) => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err("expected )".to_string()),
+        }
+    }
+}
+
+/// Returns the value of a number, e.g. `42`, `8'hff` or `'b1010`.
+fn number_value(number: &str) -> std::result::Result<i64, String> {
+    let number = number.replace('_', "");
+    let (radix, digits) = match number.split_once('\'') {
+        None => (10, number.as_str()),
+        Some((_, based)) => {
+            let based = based.trim_start_matches(['s', 'S']);
+            let mut chars = based.chars();
+            match chars.next().map(|c| c.to_ascii_lowercase()) {
+                Some('b') => (2, chars.as_str()),
+                Some('o') => (8, chars.as_str()),
+                Some('d') => (10, chars.as_str()),
+                Some('h') => (16, chars.as_str()),
+                _ => (2, based),
+            }
+        }
+    };
+    i64::from_str_radix(digits.trim(), radix).map_err(|_| format!("invalid number {}", number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ansi() -> Result<()> {
+        let modules = modules(
+            "`timescale 1ns/1ps
+            `define USER 2
+            // A module with streams.
+            module stream_ip #(
+              parameter integer LANES = 4,
+              parameter WIDTH = 8 * LANES
+            ) (
+              input  wire                clk,
+              input  wire                rst,
+              (* mark_debug = \"true\" *)
+              input  wire                a_valid,
+              output wire                a_ready,
+              input  wire [WIDTH-1:0]    a_data,
+              input  wire                a_last,
+              input  wire [$clog2(LANES)-1:0] a_endi,
+              input  wire [LANES-1:0]    a_strb,
+              output reg                 b_valid,
+              input                      b_ready,
+              output reg  [`USER-1:0]    b_user,
+              output [3:0]               count, status
+            );
+              /* The body is not parsed. */
+              always @(*) b_valid = a_valid;
+            endmodule
+
+            module empty; endmodule",
+        )?;
+        assert_eq!(modules.len(), 2);
+        let ip = &modules[0];
+        assert_eq!(ip.name(), "stream_ip");
+        assert_eq!(
+            ip.ports()
+                .map(|p| (p.name(), p.direction(), p.width()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("clk", PortDirection::Input, 1),
+                ("rst", PortDirection::Input, 1),
+                ("a_valid", PortDirection::Input, 1),
+                ("a_ready", PortDirection::Output, 1),
+                ("a_data", PortDirection::Input, 32),
+                ("a_last", PortDirection::Input, 1),
+                ("a_endi", PortDirection::Input, 2),
+                ("a_strb", PortDirection::Input, 4),
+                ("b_valid", PortDirection::Output, 1),
+                ("b_ready", PortDirection::Input, 1),
+                ("b_user", PortDirection::Output, 2),
+                ("count", PortDirection::Output, 4),
+                ("status", PortDirection::Output, 4),
+            ]
+        );
+        assert_eq!(
+            ip.blackbox()?.to_string(),
+            "Streamlet stream_ip (\n  \
+               a : in Stream<Bits<8>, t=4, d=1>,\n  \
+               b : out Stream<Null, u=Bits<2>>,\n  \
+               count : out Bits<4>,\n  \
+               status : out Bits<4>\n)"
+        );
+        assert_eq!(ip.blackbox()?.external(), Some((None, "stream_ip")));
+        assert_eq!(modules[1].ports().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn non_ansi() -> Result<()> {
+        let modules = modules(
+            "module fifo(clk, rst, a_valid, a_ready, a_data, b_valid, b_ready, b_data);
+              parameter DEPTH = 16, WIDTH = 'h10;
+              localparam AW = $clog2(DEPTH);
+              input clk, rst;
+              input a_valid; output a_ready;
+              input [WIDTH-1:0] a_data;
+              output b_valid;
+              input b_ready;
+              output [WIDTH-1:0] b_data;
+              function [AW-1:0] next; input [AW-1:0] x; next = x + 1; endfunction
+              reg [WIDTH-1:0] mem [0:DEPTH-1];
+            endmodule",
+        )?;
+        assert_eq!(
+            modules[0].blackbox()?.to_string(),
+            "Streamlet fifo (\n  \
+               a : in Stream<Bits<16>>,\n  \
+               b : out Stream<Bits<16>>\n)"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        let blackbox = |input: &str| modules(input).and_then(|modules| modules[0].blackbox());
+        assert!(blackbox("module m (inout [7:0] bus); endmodule").is_err());
+        assert!(blackbox("module m (input [W-1:0] a); endmodule").is_err());
+        assert!(blackbox("module m (input [7:0] a [0:3]); endmodule").is_err());
+        assert!(blackbox("module m (axi_if.master m); endmodule").is_err());
+        assert!(blackbox("module m (a); endmodule").is_err());
+        assert!(blackbox("module m (input [`W:0] a); endmodule").is_err());
+        assert!(blackbox("module m (input a); ").is_err());
+        // Strobes must match the lanes of the data.
+        assert!(blackbox(
+            "module m (input a_valid, output a_ready, input [7:0] a_data, input [2:0] a_strb);
+            endmodule"
+        )
+        .is_err());
+        assert_eq!(
+            modules("\n\nmodule m (input [7:0").unwrap_err().to_string(),
+            "Parsing error: Verilog line 3: unclosed range"
+        );
+    }
+}
@@ -52,17 +52,22 @@
 use std::str::FromStr;
 use std::{
     cmp::Ordering,
+    collections::HashSet,
     convert::{TryFrom, TryInto},
     fmt,
+    ops::Range,
 };
 
 use indexmap::IndexMap;
 
 use crate::traits::Identify;
-use crate::{util::log2_ceil, Error, NonNegative, PathName, Positive, Result};
+use crate::{
+    util::log2_ceil, Error, Name, NonNegative, PathName, Positive, Result, WideNonNegative,
+    WidePositive,
+};
 
 /// Positive number of bits.
-pub type BitCount = Positive;
+pub type BitCount = WidePositive;
 
 /// Interface complexity level.
 ///
@@ -252,6 +257,78 @@ impl Complexity {
     pub fn major(&self) -> NonNegative {
         self.level[0]
     }
+
+    /// Returns true if a source may release `valid` within an innermost
+    /// sequence (C >= 2).
+    pub fn allows_valid_gaps_within_sequences(&self) -> bool {
+        self.major() >= 2
+    }
+
+    /// Returns true if a source may release `valid` between innermost
+    /// sequences (C >= 3).
+    pub fn allows_valid_gaps_between_sequences(&self) -> bool {
+        self.major() >= 3
+    }
+
+    /// Returns true if a source may postpone `last` to a transfer after the
+    /// one carrying the last element of a sequence (C >= 4).
+    pub fn allows_postponed_last(&self) -> bool {
+        self.major() >= 4
+    }
+
+    /// Returns true if a source may send transfers without any active
+    /// element lanes, other than to terminate an empty sequence (C >= 4).
+    pub fn allows_empty_transfers(&self) -> bool {
+        self.major() >= 4
+    }
+
+    /// Returns true if a source may leave element lanes at the end of any
+    /// transfer inactive, by means of the `endi` signal (C >= 5). Below this
+    /// level, only the last transfer of an innermost sequence may do so.
+    pub fn allows_partial_lanes(&self) -> bool {
+        self.major() >= 5
+    }
+
+    /// Returns true if a source may leave element lanes at the start of a
+    /// transfer inactive, by means of the `stai` signal (C >= 6).
+    pub fn allows_start_index(&self) -> bool {
+        self.major() >= 6
+    }
+
+    /// Returns true if a source may mark a whole transfer as empty by means
+    /// of the `strb` signal (C >= 7). Below complexity 8, all `strb` bits
+    /// must be driven to the same value, see
+    /// [allows_lane_strobe](Complexity::allows_lane_strobe).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::physical::Complexity;
+    ///
+    /// assert!(!Complexity::new(vec![6, 9])?.allows_strobe());
+    /// assert!(Complexity::new_major(7).allows_strobe());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn allows_strobe(&self) -> bool {
+        self.major() >= 7
+    }
+
+    /// Returns true if a source may deactivate arbitrary element lanes of a
+    /// transfer, by driving the bits of the `strb` signal to different values
+    /// (C >= 8).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::physical::Complexity;
+    ///
+    /// assert!(!Complexity::new(vec![7, 9])?.allows_lane_strobe());
+    /// assert!(Complexity::new_major(8).allows_lane_strobe());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn allows_lane_strobe(&self) -> bool {
+        self.major() >= 8
+    }
 }
 
 impl fmt::Display for Complexity {
@@ -322,6 +399,62 @@ impl Fields {
     pub fn values(&self) -> impl Iterator<Item = &BitCount> {
         self.0.values()
     }
+
+    /// Returns the layout of these fields when they are packed, i.e. every
+    /// field with its bit offset and bit count. The first field occupies the
+    /// least significant bits.
+    pub fn layout(&self) -> impl Iterator<Item = (&PathName, WideNonNegative, BitCount)> {
+        self.0
+            .iter()
+            .scan(0, |offset: &mut WideNonNegative, (path_name, bit_count)| {
+                let field = (path_name, *offset, *bit_count);
+                *offset += bit_count.get();
+                Some(field)
+            })
+    }
+
+    /// Returns a description of the first field of these fields that does not
+    /// match the field at the same position in the sink fields, or None if
+    /// all fields match.
+    pub(crate) fn first_mismatch(&self, sink: &Fields) -> Option<String> {
+        let name = |path_name: &PathName| {
+            if path_name.is_empty() {
+                "∅".to_string()
+            } else {
+                path_name.to_string()
+            }
+        };
+        let (mut source, mut sink) = (self.iter(), sink.iter());
+        loop {
+            match (source.next(), sink.next()) {
+                (None, None) => return None,
+                (Some((path_name, _)), None) => {
+                    return Some(format!("field {} is missing at sink", name(path_name)))
+                }
+                (None, Some((path_name, _))) => {
+                    return Some(format!("field {} is missing at source", name(path_name)))
+                }
+                (Some((source_name, _)), Some((sink_name, _))) if source_name != sink_name => {
+                    return Some(format!(
+                        "field {} at source does not match field {} at sink",
+                        name(source_name),
+                        name(sink_name)
+                    ))
+                }
+                (Some((path_name, source_bits)), Some((_, sink_bits)))
+                    if source_bits != sink_bits =>
+                {
+                    return Some(format!(
+                        "field {}: source has {} bits, sink has {} bits",
+                        name(path_name),
+                        source_bits,
+                        sink_bits
+                    ))
+                }
+                _ => {}
+            }
+        }
+    }
 }
 
 impl<'a> IntoIterator for &'a Fields {
@@ -354,6 +487,8 @@ pub struct PhysicalStream {
     complexity: Complexity,
     /// User-defined transfer content.
     user: Fields,
+    /// Custom sideband signals that are not covered by the specification.
+    sideband: Vec<Signal>,
 }
 
 impl PhysicalStream {
@@ -374,7 +509,7 @@ impl PhysicalStream {
                 .map(|(path_name, bit_count)| {
                     (
                         path_name.try_into(),
-                        Positive::new(bit_count as NonNegative),
+                        BitCount::new(bit_count as WideNonNegative),
                     )
                 })
                 .map(|(path_name, bit_count)| match (path_name, bit_count) {
@@ -395,7 +530,7 @@ impl PhysicalStream {
                 .map(|(path_name, bit_count)| {
                     (
                         path_name.try_into(),
-                        Positive::new(bit_count as NonNegative),
+                        BitCount::new(bit_count as WideNonNegative),
                     )
                 })
                 .map(|(path_name, bit_count)| match (path_name, bit_count) {
@@ -430,9 +565,20 @@ impl PhysicalStream {
             dimensionality,
             complexity: complexity.into(),
             user: user.into(),
+            sideband: Vec::new(),
         }
     }
 
+    /// Returns this physical stream with the provided custom sideband
+    /// signals added, e.g. `id`, `dest` or parity signals for
+    /// platform-specific extensions. Returns an error when a signal name is
+    /// not a valid name or collides with another signal of the stream.
+    pub fn with_sideband(mut self, signals: impl IntoIterator<Item = Signal>) -> Result<Self> {
+        self.sideband.extend(signals);
+        check_sideband(&self.sideband)?;
+        Ok(self)
+    }
+
     /// Returns the element fields in this physical stream.
     pub fn element_fields(&self) -> &Fields {
         &self.element_fields
@@ -458,56 +604,131 @@ impl PhysicalStream {
         &self.user
     }
 
+    /// Returns the custom sideband signals of this physical stream.
+    pub fn sideband(&self) -> &[Signal] {
+        &self.sideband
+    }
+
     /// Returns the bit count of the data (element) fields in this physical
     /// stream. The bit count is equal to the combined bit count of all fields
     /// multiplied by the number of lanes.
-    pub fn data_bit_count(&self) -> NonNegative {
-        self.element_fields
-            .values()
-            .map(|b| b.get())
-            .sum::<NonNegative>()
-            * self.element_lanes.get()
+    pub fn data_bit_count(&self) -> WideNonNegative {
+        self.element_bit_count() * WideNonNegative::from(self.element_lanes.get())
+    }
+
+    /// Returns the number of bits of a single element lane in this physical
+    /// stream. Lane `i` occupies the bits of the data signal starting at
+    /// `i` times this number, laid out as in [Fields::layout].
+    pub fn element_bit_count(&self) -> WideNonNegative {
+        self.element_fields.values().map(|b| b.get()).sum()
     }
 
     /// Returns the number of last bits in this physical stream. The number of
     /// last bits equals the dimensionality.
-    pub fn last_bit_count(&self) -> NonNegative {
-        self.dimensionality
+    pub fn last_bit_count(&self) -> WideNonNegative {
+        self.dimensionality.into()
     }
 
     /// Returns the number of `stai` (start index) bits in this physical
     /// stream.
-    pub fn stai_bit_count(&self) -> NonNegative {
-        if self.complexity.major() >= 6 && self.element_lanes.get() > 1 {
-            log2_ceil(self.element_lanes)
+    pub fn stai_bit_count(&self) -> WideNonNegative {
+        if self.complexity.allows_start_index() && self.element_lanes.get() > 1 {
+            log2_ceil(self.element_lanes).into()
         } else {
             0
         }
     }
 
     /// Returns the number of `endi` (end index) bits in this physical stream.
-    pub fn endi_bit_count(&self) -> NonNegative {
-        if (self.complexity.major() >= 5 || self.dimensionality >= 1)
+    pub fn endi_bit_count(&self) -> WideNonNegative {
+        if (self.complexity.allows_partial_lanes() || self.dimensionality >= 1)
             && self.element_lanes.get() > 1
         {
-            log2_ceil(self.element_lanes)
+            log2_ceil(self.element_lanes).into()
         } else {
             0
         }
     }
 
     /// Returns the number of `strb` (strobe) bits in this physical stream.
-    pub fn strb_bit_count(&self) -> NonNegative {
-        if self.complexity.major() >= 7 || self.dimensionality >= 1 {
-            self.element_lanes.get()
+    pub fn strb_bit_count(&self) -> WideNonNegative {
+        if self.complexity.allows_strobe() || self.dimensionality >= 1 {
+            self.element_lanes.get().into()
         } else {
             0
         }
     }
 
     /// Returns the bit count of the user fields in this physical stream.
-    pub fn user_bit_count(&self) -> NonNegative {
-        self.user.values().map(|b| b.get()).sum::<NonNegative>()
+    pub fn user_bit_count(&self) -> WideNonNegative {
+        self.user.values().map(|b| b.get()).sum::<WideNonNegative>()
+    }
+
+    /// Returns the combined bit count of the custom sideband signals in this
+    /// physical stream.
+    pub fn sideband_bit_count(&self) -> WideNonNegative {
+        self.sideband.iter().map(|s| s.bit_count()).sum()
+    }
+
+    /// Returns `Ok(())` if this physical stream, as a source, can be connected
+    /// to the provided sink physical stream. This requires both streams to
+    /// have the same element fields, number of element lanes,
+    /// dimensionality, user fields and sideband signals, and the complexity
+    /// of the source to be lower than or equal to the complexity of the sink.
+    /// Returns an error describing the first mismatch otherwise.
+    ///
+    /// [Reference](https://abs-tudelft.github.io/tydi/specification/physical.html#complexity-c)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::convert::TryInto;
+    /// use tydi::physical::{BitCount, Fields, PhysicalStream};
+    /// use tydi::Positive;
+    ///
+    /// let fields = Fields::new(vec![("a".try_into()?, BitCount::new(8).unwrap())])?;
+    /// let lanes = Positive::new(1).unwrap();
+    /// let source = PhysicalStream::new(fields.clone(), lanes, 1, 2, Fields::new(vec![])?);
+    /// let sink = PhysicalStream::new(fields, lanes, 1, 4, Fields::new(vec![])?);
+    /// assert!(source.can_drive(&sink).is_ok());
+    /// assert!(sink.can_drive(&source).is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn can_drive(&self, sink: &PhysicalStream) -> Result<()> {
+        let mismatch = |what: &str, source: &dyn fmt::Debug, sink: &dyn fmt::Debug| {
+            Err(Error::InvalidArgument(format!(
+                "{} mismatch: source has {:?}, sink has {:?}",
+                what, source, sink
+            )))
+        };
+        if let Some(field) = self.element_fields.first_mismatch(&sink.element_fields) {
+            return Err(Error::InvalidArgument(format!(
+                "element fields mismatch: {}",
+                field
+            )));
+        }
+        if self.element_lanes != sink.element_lanes {
+            return mismatch("element lanes", &self.element_lanes, &sink.element_lanes);
+        }
+        if self.dimensionality != sink.dimensionality {
+            return mismatch("dimensionality", &self.dimensionality, &sink.dimensionality);
+        }
+        if let Some(field) = self.user.first_mismatch(&sink.user) {
+            return Err(Error::InvalidArgument(format!(
+                "user fields mismatch: {}",
+                field
+            )));
+        }
+        if self.sideband != sink.sideband {
+            return mismatch("sideband signals", &self.sideband, &sink.sideband);
+        }
+        if self.complexity > sink.complexity {
+            return Err(Error::InvalidArgument(format!(
+                "source complexity {} exceeds sink complexity {}",
+                self.complexity, sink.complexity
+            )));
+        }
+        Ok(())
     }
 
     /// Returns the signal list for this physical stream.
@@ -520,18 +741,22 @@ impl PhysicalStream {
             endi: opt(self.endi_bit_count()),
             strb: opt(self.strb_bit_count()),
             user: opt(self.user_bit_count()),
+            sideband: self.sideband.clone(),
+            element_lanes: self.element_lanes,
         }
     }
 
     /// Returns the combined bit count of all signals in this physical stream.
-    /// This excludes the `valid` and `ready` signals.
-    pub fn bit_count(&self) -> NonNegative {
+    /// This excludes the `valid` and `ready` signals, but includes custom
+    /// sideband signals.
+    pub fn bit_count(&self) -> WideNonNegative {
         self.data_bit_count()
             + self.last_bit_count()
             + self.stai_bit_count()
             + self.endi_bit_count()
             + self.strb_bit_count()
             + self.user_bit_count()
+            + self.sideband_bit_count()
     }
 }
 
@@ -558,7 +783,7 @@ pub enum Width {
     /// Non-vectorized single bit.
     Scalar,
     /// Vectorized multiple bits.
-    Vector(NonNegative),
+    Vector(WideNonNegative),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -575,11 +800,11 @@ impl Identify for Signal {
 }
 
 impl Signal {
-    /// Returns a vector-style signal if the input width is Some(NonNegative)
+    /// Returns a vector-style signal if the input width is Some(WideNonNegative)
     pub fn opt_vec(
         name: impl Into<String>,
         origin: Origin,
-        width: Option<NonNegative>,
+        width: Option<WideNonNegative>,
     ) -> Option<Signal> {
         width.map(|w| Signal {
             name: name.into(),
@@ -589,11 +814,11 @@ impl Signal {
     }
 
     /// Returns a vector-style signal.
-    pub fn vec(name: impl Into<String>, origin: Origin, width: Positive) -> Signal {
+    pub fn vec(name: impl Into<String>, origin: Origin, width: impl Into<WidePositive>) -> Signal {
         Signal {
             name: name.into(),
             origin,
-            width: Width::Vector(width.get()),
+            width: Width::Vector(width.into().get()),
         }
     }
 
@@ -619,6 +844,14 @@ impl Signal {
         self.width
     }
 
+    /// Returns the number of bits of this signal.
+    pub fn bit_count(&self) -> WideNonNegative {
+        match self.width {
+            Width::Scalar => 1,
+            Width::Vector(width) => width,
+        }
+    }
+
     pub fn with_name(&self, name: String) -> Signal {
         Signal {
             name,
@@ -638,14 +871,16 @@ impl Signal {
 /// [`PhysicalStream`]: ./struct.PhysicalStream.html
 /// [`signal_list`]: ./struct.PhysicalStream.html#method.signal_list
 /// [Reference]: https://abs-tudelft.github.io/tydi/specification/physical.html#signals
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SignalList {
-    data: Option<NonNegative>,
-    last: Option<NonNegative>,
-    stai: Option<NonNegative>,
-    endi: Option<NonNegative>,
-    strb: Option<NonNegative>,
-    user: Option<NonNegative>,
+    data: Option<WideNonNegative>,
+    last: Option<WideNonNegative>,
+    stai: Option<WideNonNegative>,
+    endi: Option<WideNonNegative>,
+    strb: Option<WideNonNegative>,
+    user: Option<WideNonNegative>,
+    sideband: Vec<Signal>,
+    element_lanes: Positive,
 }
 
 impl SignalList {
@@ -697,14 +932,61 @@ impl SignalList {
         Signal::opt_vec("user", Origin::Source, self.user)
     }
 
+    /// Returns the custom sideband signals.
+    pub fn sideband(&self) -> &[Signal] {
+        &self.sideband
+    }
+
+    /// Returns the number of element lanes.
+    pub fn element_lanes(&self) -> Positive {
+        self.element_lanes
+    }
+
+    /// Returns the bit indices of every element lane within these signals,
+    /// ordered by lane index.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::physical::PhysicalStream;
+    ///
+    /// let signal_list = PhysicalStream::try_new(vec![("a", 4)], 3, 1, 8, vec![])?.signal_list();
+    /// let lanes = signal_list.lanes();
+    /// assert_eq!(lanes.len(), 3);
+    /// assert_eq!(lanes[1].data(), 4..8);
+    /// assert_eq!(lanes[1].strb(), Some(1));
+    /// assert_eq!(lanes[1].last(), Some(0..1));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn lanes(&self) -> Vec<LaneSlice> {
+        let lanes = self.element_lanes.get();
+        let width = self.data.unwrap_or(0) / WideNonNegative::from(lanes);
+        (0..lanes)
+            .map(|index| {
+                let offset = WideNonNegative::from(index) * width;
+                LaneSlice {
+                    index,
+                    data: offset..offset + width,
+                    strb: self.strb.map(|_| index.into()),
+                    last: self.last.map(|last| 0..last),
+                }
+            })
+            .collect()
+    }
+
     /// Returns the bit count of all combined signals in this map.
-    pub fn opt_bit_count(&self) -> Option<NonNegative> {
+    pub fn opt_bit_count(&self) -> Option<WideNonNegative> {
         match self.data.unwrap_or(0)
             + self.last.unwrap_or(0)
             + self.stai.unwrap_or(0)
             + self.endi.unwrap_or(0)
             + self.strb.unwrap_or(0)
             + self.user.unwrap_or(0)
+            + self
+                .sideband
+                .iter()
+                .map(|s| s.bit_count())
+                .sum::<WideNonNegative>()
         {
             0 => None,
             x => Some(x),
@@ -712,7 +994,7 @@ impl SignalList {
     }
 
     /// Returns the bit count of all combined signals in this map.
-    pub fn bit_count(&self) -> NonNegative {
+    pub fn bit_count(&self) -> WideNonNegative {
         self.opt_bit_count().unwrap_or(0)
     }
 }
@@ -735,11 +1017,80 @@ impl<'a> IntoIterator for &'a SignalList {
         .iter()
         .filter(|o| o.is_some())
         .map(|s| s.clone().unwrap())
+        .chain(self.sideband.iter().cloned())
         .collect::<Vec<_>>()
         .into_iter()
     }
 }
 
+/// The bit indices of a single element lane within the signals of a
+/// physical stream.
+///
+/// Lane slices can be obtained using [`SignalList::lanes`].
+///
+/// [`SignalList::lanes`]: ./struct.SignalList.html#method.lanes
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaneSlice {
+    index: NonNegative,
+    data: Range<WideNonNegative>,
+    strb: Option<WideNonNegative>,
+    last: Option<Range<WideNonNegative>>,
+}
+
+impl LaneSlice {
+    /// Returns the index of the element lane.
+    pub fn index(&self) -> NonNegative {
+        self.index
+    }
+
+    /// Returns the range of bits of the element lane in the `data` signal.
+    pub fn data(&self) -> Range<WideNonNegative> {
+        self.data.clone()
+    }
+
+    /// Returns the index of the bit of the element lane in the `strb`
+    /// signal, if applicable.
+    pub fn strb(&self) -> Option<WideNonNegative> {
+        self.strb
+    }
+
+    /// Returns the range of bits in the `last` signal that apply to the
+    /// element lane, if applicable. The `last` signal applies to the last
+    /// active element lane of a transfer, so this is the full `last` signal
+    /// for every lane.
+    pub fn last(&self) -> Option<Range<WideNonNegative>> {
+        self.last.clone()
+    }
+}
+
+/// Names of the signals defined by the specification, which cannot be used
+/// for custom sideband signals.
+const STANDARD_SIGNALS: [&str; 8] = [
+    "valid", "ready", "data", "last", "stai", "endi", "strb", "user",
+];
+
+/// Checks that custom sideband signals have valid, unique names that do not
+/// collide with the signals defined by the specification.
+pub(crate) fn check_sideband(signals: &[Signal]) -> Result<()> {
+    let mut names = HashSet::new();
+    for signal in signals {
+        Name::try_new(signal.identifier())?;
+        if STANDARD_SIGNALS.contains(&signal.identifier()) {
+            return Err(Error::InvalidArgument(format!(
+                "sideband signal {} collides with a standard signal",
+                signal.identifier()
+            )));
+        }
+        if !names.insert(signal.identifier()) {
+            return Err(Error::InvalidArgument(format!(
+                "duplicate sideband signal {}",
+                signal.identifier()
+            )));
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::convert::TryInto;
@@ -796,6 +1147,33 @@ mod tests {
 
         assert_eq!(c4.level(), &[4]);
         assert_eq!(c400.level(), &[4, 0, 0]);
+
+        let capabilities = |c: &Complexity| {
+            vec![
+                c.allows_valid_gaps_within_sequences(),
+                c.allows_valid_gaps_between_sequences(),
+                c.allows_postponed_last(),
+                c.allows_empty_transfers(),
+                c.allows_partial_lanes(),
+                c.allows_start_index(),
+                c.allows_strobe(),
+                c.allows_lane_strobe(),
+            ]
+        };
+        assert!(capabilities(&Complexity::new_major(1)).iter().all(|&b| !b));
+        assert_eq!(
+            capabilities(&c31),
+            vec![true, true, false, false, false, false, false, false]
+        );
+        assert_eq!(
+            capabilities(&c401),
+            vec![true, true, true, true, false, false, false, false]
+        );
+        assert_eq!(
+            capabilities(&Complexity::new_major(7)),
+            vec![true, true, true, true, true, true, true, false]
+        );
+        assert!(capabilities(&Complexity::new_major(8)).iter().all(|&b| b));
         Ok(())
     }
 
@@ -828,6 +1206,23 @@ mod tests {
             Some((&("c".try_into()?), &BitCount::new(1).unwrap()))
         );
         assert_eq!(element.next(), None);
+        assert_eq!(
+            physical_stream
+                .element_fields()
+                .layout()
+                .map(|(path_name, offset, bit_count)| (
+                    path_name.to_string(),
+                    offset,
+                    bit_count.get()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a".to_string(), 0, 8),
+                ("b".to_string(), 8, 16),
+                ("c".to_string(), 24, 1)
+            ]
+        );
+        assert_eq!(physical_stream.element_bit_count(), 25);
         assert_eq!(physical_stream.element_lanes(), Positive::new(3).unwrap());
         assert_eq!(physical_stream.dimensionality(), 4);
         assert_eq!(physical_stream.complexity(), &Complexity::new_major(8));
@@ -850,7 +1245,9 @@ mod tests {
                 stai: Some(2),
                 endi: Some(2),
                 strb: Some(3),
-                user: Some(1)
+                user: Some(1),
+                sideband: vec![],
+                element_lanes: Positive::new(3).unwrap()
             }
         );
 
@@ -883,7 +1280,9 @@ mod tests {
                 stai: None,
                 endi: None,
                 strb: None,
-                user: None
+                user: None,
+                sideband: vec![],
+                element_lanes: Positive::new(1).unwrap()
             }
         );
 
@@ -955,6 +1354,127 @@ mod tests {
             ]
         );
 
+        let physical_stream = PhysicalStream::new(
+            Fields::new(vec![
+                ("a".try_into()?, BitCount::new(3).unwrap()),
+                ("b".try_into()?, BitCount::new(2).unwrap()),
+            ])?,
+            Positive::new(2).unwrap(),
+            3,
+            8,
+            Fields::new(vec![])?,
+        )
+        .with_sideband(vec![
+            Signal::vec("id", Origin::Source, Positive::new(4).unwrap()),
+            Signal::bit("err", Origin::Sink),
+        ])?;
+        assert_eq!(physical_stream.sideband_bit_count(), 5);
+        assert_eq!(physical_stream.bit_count(), 22);
+        let signal_list = physical_stream.signal_list();
+        assert_eq!(signal_list.bit_count(), 22);
+        assert_eq!(
+            signal_list.into_iter().skip(7).collect::<Vec<_>>(),
+            vec![
+                Signal::vec("id", Origin::Source, Positive::new(4).unwrap()),
+                Signal::bit("err", Origin::Sink),
+            ]
+        );
+        assert!(physical_stream
+            .clone()
+            .with_sideband(vec![Signal::bit("id", Origin::Source)])
+            .is_err());
+        assert!(physical_stream
+            .with_sideband(vec![Signal::bit("strb", Origin::Source)])
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn wide_physical_stream() -> Result<()> {
+        let physical_stream = PhysicalStream::new(
+            Fields::new(vec![(
+                "a".try_into()?,
+                BitCount::new(u64::from(u32::MAX)).unwrap(),
+            )])?,
+            Positive::new(64).unwrap(),
+            0,
+            1,
+            Fields::new(vec![])?,
+        );
+        let data = u64::from(u32::MAX) * 64;
+        assert_eq!(physical_stream.data_bit_count(), data);
+        assert_eq!(physical_stream.bit_count(), data);
+        assert_eq!(
+            physical_stream.signal_list().data().unwrap().width(),
+            Width::Vector(data)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn can_drive() -> Result<()> {
+        let stream =
+            |fields: Vec<(&str, u64)>, lanes, dimensionality, complexity| {
+                PhysicalStream::new(
+                    Fields::new(fields.into_iter().map(|(name, bits)| {
+                        (name.try_into().unwrap(), BitCount::new(bits).unwrap())
+                    }))
+                    .unwrap(),
+                    Positive::new(lanes).unwrap(),
+                    dimensionality,
+                    complexity,
+                    Fields::new(vec![]).unwrap(),
+                )
+            };
+        let source = stream(vec![("a", 8), ("b", 2)], 2, 1, 4);
+        assert!(source.can_drive(&source).is_ok());
+        assert!(source
+            .can_drive(&stream(vec![("a", 8), ("b", 2)], 2, 1, 5))
+            .is_ok());
+        assert_eq!(
+            source
+                .can_drive(&stream(vec![("a", 8), ("b", 2)], 2, 1, 3))
+                .unwrap_err()
+                .to_string(),
+            "Invalid argument: source complexity 4 exceeds sink complexity 3"
+        );
+        assert_eq!(
+            source
+                .can_drive(&stream(vec![("b", 2), ("a", 8)], 2, 1, 4))
+                .unwrap_err()
+                .to_string(),
+            "Invalid argument: element fields mismatch: field a at source does not match field b \
+             at sink"
+        );
+        assert_eq!(
+            source
+                .can_drive(&stream(vec![("a", 8), ("b", 3)], 2, 1, 4))
+                .unwrap_err()
+                .to_string(),
+            "Invalid argument: element fields mismatch: field b: source has 2 bits, sink has 3 \
+             bits"
+        );
+        assert_eq!(
+            source
+                .can_drive(&stream(vec![("a", 8)], 2, 1, 4))
+                .unwrap_err()
+                .to_string(),
+            "Invalid argument: element fields mismatch: field b is missing at sink"
+        );
+        assert!(source
+            .can_drive(&stream(vec![("a", 8), ("b", 2)], 1, 1, 4))
+            .is_err());
+        assert!(source
+            .can_drive(&stream(vec![("a", 8), ("b", 2)], 2, 0, 4))
+            .is_err());
+        assert!(source
+            .can_drive(
+                &source
+                    .clone()
+                    .with_sideband(vec![Signal::bit("id", Origin::Source)])?
+            )
+            .is_err());
         Ok(())
     }
 }
@@ -61,7 +61,7 @@ impl Stub {
                 ifaces.push(i.clone());
             }
         } else {
-            log::info!("Attempting to implement as source.");
+            tracing::info!("Attempting to implement as source.");
             is_source = true;
         }
 
@@ -70,7 +70,7 @@ impl Stub {
                 ifaces.push(i.clone());
             }
         } else if !is_source {
-            log::info!("Implementing as sink.");
+            tracing::info!("Implementing as sink.");
             is_sink = true;
         } else {
             return Err(Error::ComposerError(format!(
@@ -23,6 +23,18 @@ impl ObjectDeclaration {
         );
         self.assign(&Assignment::from(concatenation))
     }
+
+    /// Assign this object its default value, e.g. in the reset branch of a
+    /// clocked process. Returns an error if the object has no default.
+    pub fn reset(&self) -> Result<AssignDeclaration> {
+        match self.default() {
+            Some(default) => self.assign(&Assignment::from(default.clone())),
+            None => Err(Error::InvalidArgument(format!(
+                "{} has no default value to reset to",
+                self.identifier()
+            ))),
+        }
+    }
 }
 
 impl<T> Assign for T
@@ -1,10 +1,10 @@
-use crate::stdlib::common::architecture::declaration::ObjectDeclaration;
+use crate::stdlib::common::architecture::declaration::{AliasDeclaration, ObjectDeclaration};
 
 use crate::{Positive, Natural};
 
 use super::{
     array_assignment::ArrayAssignment, bitvec::BitVecValue, Assignment, AssignmentKind,
-    DirectAssignment, ObjectAssignment, StdLogicValue, ValueAssignment,
+    DirectAssignment, NumericValue, ObjectAssignment, StdLogicValue, ValueAssignment,
 };
 
 // I feel like there should be some way for Rust to recognize these connections automatically but unfortunately we can't just string "T: Into<...>"s together,
@@ -34,6 +34,12 @@ impl From<ObjectDeclaration> for AssignmentKind {
     }
 }
 
+impl From<AliasDeclaration> for AssignmentKind {
+    fn from(alias: AliasDeclaration) -> Self {
+        AssignmentKind::Object(alias.into())
+    }
+}
+
 // As there are more Direct kinds, this one gets to use the where T: Into...
 impl<T> From<T> for AssignmentKind
 where
@@ -56,6 +62,12 @@ where
     }
 }
 
+impl From<AliasDeclaration> for ObjectAssignment {
+    fn from(alias: AliasDeclaration) -> Self {
+        alias.to_object().into()
+    }
+}
+
 impl<T> From<T> for DirectAssignment
 where
     T: Into<ValueAssignment>,
@@ -94,3 +106,9 @@ impl From<BitVecValue> for ValueAssignment {
         ValueAssignment::BitVec(assignment.into())
     }
 }
+
+impl From<NumericValue> for ValueAssignment {
+    fn from(assignment: NumericValue) -> Self {
+        ValueAssignment::Numeric(assignment)
+    }
+}
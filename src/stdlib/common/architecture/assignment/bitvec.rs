@@ -1,5 +1,5 @@
 use crate::stdlib::common::architecture::assignment::*;
-use crate::Error;
+use crate::{Error, WideNonNegative};
 
 /// Quick way to get the minimum number of binary values required for an unsigned integer
 fn min_length_unsigned(value: u32) -> u32 {
@@ -21,6 +21,24 @@ fn min_length_signed(value: i32) -> u32 {
     }
 }
 
+/// Verifies that a value consists of digits of a radix, which may be
+/// separated (but not started or ended) by single underscores
+fn validate_digits(value: &str, radix: u32) -> Result<String> {
+    let valid = !value.is_empty()
+        && !value.starts_with('_')
+        && !value.ends_with('_')
+        && !value.contains("__")
+        && value.chars().all(|c| c == '_' || c.is_digit(radix));
+    if valid {
+        Ok(value.to_string())
+    } else {
+        Err(Error::InvalidArgument(format!(
+            "{} is not a valid value of base {} digits",
+            value, radix
+        )))
+    }
+}
+
 /// A struct for describing value assigned to a bit vector
 #[derive(Debug, Clone)]
 pub enum BitVecValue {
@@ -42,6 +60,14 @@ pub enum BitVecValue {
     ///
     /// Or: std_logic_vector(to_signed(\[value\], [range length]))
     Signed(i32),
+    /// A value is assigned from hexadecimal digits, each describing four bits
+    ///
+    /// Result (example): x"DEAD_BEEF"
+    Hex(String),
+    /// A value is assigned from octal digits, each describing three bits
+    ///
+    /// Result (example): o"777"
+    Octal(String),
 }
 
 impl BitVecValue {
@@ -54,11 +80,23 @@ impl BitVecValue {
         Ok(BitVecValue::Full(logicvals))
     }
 
-    pub fn validate_width(&self, width: u32) -> Result<()> {
+    /// Create a bit vector value from hexadecimal digits, which may be
+    /// separated by underscores
+    pub fn from_hex(value: &str) -> Result<BitVecValue> {
+        Ok(BitVecValue::Hex(validate_digits(value, 16)?))
+    }
+
+    /// Create a bit vector value from octal digits, which may be separated
+    /// by underscores
+    pub fn from_octal(value: &str) -> Result<BitVecValue> {
+        Ok(BitVecValue::Octal(validate_digits(value, 8)?))
+    }
+
+    pub fn validate_width(&self, width: WideNonNegative) -> Result<()> {
         match self {
             BitVecValue::Others(_) => Ok(()),
             BitVecValue::Full(full) => {
-                if full.len() == width.try_into().unwrap() {
+                if full.len() == TryInto::<usize>::try_into(width).unwrap() {
                     Ok(())
                 } else {
                     Err(Error::InvalidArgument(format!(
@@ -69,7 +107,7 @@ impl BitVecValue {
                 }
             }
             BitVecValue::Unsigned(value) => {
-                if min_length_unsigned(*value) > width {
+                if WideNonNegative::from(min_length_unsigned(*value)) > width {
                     Err(Error::InvalidArgument(format!(
                         "Cannot assign unsigned integer {} to range with width {}",
                         value, width
@@ -79,7 +117,7 @@ impl BitVecValue {
                 }
             }
             BitVecValue::Signed(value) => {
-                if min_length_signed(*value) > width {
+                if WideNonNegative::from(min_length_signed(*value)) > width {
                     Err(Error::InvalidArgument(format!(
                         "Cannot assign signed integer {} to range with width {}",
                         value, width
@@ -88,6 +126,20 @@ impl BitVecValue {
                     Ok(())
                 }
             }
+            BitVecValue::Hex(digits) | BitVecValue::Octal(digits) => {
+                let bits_per_digit = if let BitVecValue::Hex(_) = self { 4 } else { 3 };
+                let length = digits.chars().filter(|c| *c != '_').count() * bits_per_digit;
+                if length == TryInto::<usize>::try_into(width).unwrap() {
+                    Ok(())
+                } else {
+                    Err(Error::InvalidArgument(format!(
+                        "Value {} with length {} cannot be assigned to bit vector with length {}",
+                        self.declare()?,
+                        length,
+                        width
+                    )))
+                }
+            }
         }
     }
 
@@ -101,6 +153,8 @@ impl BitVecValue {
                 }
                 Ok(format!("\"{}\"", result))
             }
+            BitVecValue::Hex(digits) => Ok(format!("x\"{}\"", digits)),
+            BitVecValue::Octal(digits) => Ok(format!("o\"{}\"", digits)),
             BitVecValue::Unsigned(_) | BitVecValue::Signed(_) => Err(Error::InvalidTarget("Unable to declare bit vector value, signed and unsigned values require a width or object identifier.".to_string())),
         }
     }
@@ -108,7 +162,10 @@ impl BitVecValue {
     /// Declares the value assigned for the object being assigned to (identifier required in case Range is empty)
     pub fn declare_for(&self, object_identifier: impl Into<String>) -> String {
         match self {
-            BitVecValue::Others(_) | BitVecValue::Full(_) => self.declare().unwrap(),
+            BitVecValue::Others(_)
+            | BitVecValue::Full(_)
+            | BitVecValue::Hex(_)
+            | BitVecValue::Octal(_) => self.declare().unwrap(),
             BitVecValue::Unsigned(value) => format!(
                 "std_logic_vector(to_unsigned({}, {}'length))",
                 value,
@@ -125,7 +182,10 @@ impl BitVecValue {
     /// Declares the value assigned for the range being assigned to
     pub fn declare_for_range(&self, range: &RangeConstraint) -> Result<String> {
         match self {
-            BitVecValue::Others(_) | BitVecValue::Full(_) => self.declare(),
+            BitVecValue::Others(_)
+            | BitVecValue::Full(_)
+            | BitVecValue::Hex(_)
+            | BitVecValue::Octal(_) => self.declare(),
             BitVecValue::Unsigned(value) => match range.width() {
                 Width::Scalar => Err(Error::InvalidTarget(
                     "Cannot assign an std_logic_vector(unsigned) to indexed std_logic".to_string(),
@@ -134,7 +194,8 @@ impl BitVecValue {
                     self.validate_width(width)?;
                     Ok(format!(
                         "std_logic_vector(to_unsigned({}, {}))",
-                        value, width
+                        value,
+                        range.width_string()
                     ))
                 }
             },
@@ -144,7 +205,11 @@ impl BitVecValue {
                 )),
                 Width::Vector(width) => {
                     self.validate_width(width)?;
-                    Ok(format!("std_logic_vector(to_signed({}, {}))", value, width))
+                    Ok(format!(
+                        "std_logic_vector(to_signed({}, {}))",
+                        value,
+                        range.width_string()
+                    ))
                 }
             },
         }
@@ -171,4 +236,21 @@ mod tests {
         assert_eq!(2, min_length_unsigned(2));
         assert_eq!(32, min_length_unsigned(u32::MAX));
     }
+
+    #[test]
+    fn hex_octal_test() -> Result<()> {
+        let hex = BitVecValue::from_hex("DEAD_beef")?;
+        assert_eq!("x\"DEAD_beef\"", hex.declare()?);
+        assert!(hex.validate_width(32).is_ok());
+        assert!(hex.validate_width(31).is_err());
+        let octal = BitVecValue::from_octal("777")?;
+        assert_eq!("o\"777\"", octal.declare_for("a"));
+        assert!(octal.validate_width(9).is_ok());
+        assert!(octal.validate_width(12).is_err());
+        for invalid in &["", "_A", "A_", "A__B", "G"] {
+            assert!(BitVecValue::from_hex(invalid).is_err(), "{}", invalid);
+        }
+        assert!(BitVecValue::from_octal("8").is_err());
+        Ok(())
+    }
 }
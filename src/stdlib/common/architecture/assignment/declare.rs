@@ -18,6 +18,7 @@ impl ArchitectureDeclare for AssignDeclaration {
             ObjectKind::Variable => " := ",
             ObjectKind::Constant => " := ",
             ObjectKind::EntityPort => " <= ",
+            ObjectKind::EntityGeneric => " := ",
             ObjectKind::ComponentPort => " => ",
         });
         result.push_str(
@@ -37,9 +38,11 @@ pub(crate) mod tests {
     use indexmap::IndexMap;
 
     use crate::generator::common::test::records;
-    use crate::generator::common::Mode;
+    use crate::generator::common::{Field, Mode, Type};
+    use crate::generator::vhdl::DeclareUsings;
     use crate::stdlib::common::architecture::assignment::{
-        Assign, Assignment, AssignmentKind, ObjectAssignment, StdLogicValue,
+        Assign, Assignment, AssignmentKind, FieldSelection, NumericValue, ObjectAssignment,
+        RangeAttributeKind, StdLogicValue,
     };
     use crate::stdlib::common::architecture::declaration::ObjectDeclaration;
     use crate::stdlib::common::architecture::object::ObjectType;
@@ -202,6 +205,110 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_numeric_assign() -> Result<()> {
+        let signed = ObjectDeclaration::signal("s", ObjectType::signed(7, 0)?, None);
+        let unsigned = ObjectDeclaration::signal("u", ObjectType::unsigned(3, 0)?, None);
+        let integer = ObjectDeclaration::signal("i", ObjectType::integer(-4, 10)?, None);
+        assert_eq!(signed.typ().type_name(), "signed(7 downto 0)");
+        assert_eq!(integer.typ().type_name(), "integer range -4 to 10");
+        assert_eq!(
+            signed
+                .assign(&NumericValue::Signed(-128))?
+                .declare("", ";\n")?,
+            "s <= to_signed(-128, s'length);\n"
+        );
+        assert_eq!(
+            unsigned
+                .assign(&NumericValue::Unsigned(15))?
+                .declare("", ";\n")?,
+            "u <= to_unsigned(15, u'length);\n"
+        );
+        assert_eq!(
+            unsigned
+                .assign(&BitVecValue::from_str("1010")?)?
+                .declare("", ";\n")?,
+            "u <= \"1010\";\n"
+        );
+        assert_eq!(
+            signed
+                .assign(&Assignment::from(StdLogicValue::Logic(true)).to_index(7))?
+                .declare("", ";\n")?,
+            "s(7) <= '1';\n"
+        );
+        assert_eq!(integer.assign(&10u32)?.declare("", ";\n")?, "i <= 10;\n");
+        assert!(unsigned.assign(&NumericValue::Unsigned(16)).is_err());
+        assert!(unsigned.assign(&NumericValue::Signed(1)).is_err());
+        assert!(unsigned.assign(&BitVecValue::Unsigned(1)).is_err());
+        assert!(integer.assign(&11u32).is_err());
+        assert!(signed.assign(&unsigned).is_err());
+        assert!(integer
+            .assign(&ObjectDeclaration::signal("n", ObjectType::Natural, None))
+            .is_ok());
+        assert_eq!(
+            signed
+                .typ()
+                .get_field(&FieldSelection::downto(3, 0)?)?
+                .type_name(),
+            "signed(3 downto 0)"
+        );
+        assert!(signed
+            .declare_usings()?
+            .contains("use ieee.numeric_std.all;"));
+        assert!(integer.declare_usings()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_attribute_assign() -> Result<()> {
+        let a = ObjectDeclaration::signal("a", ObjectType::bit_vector(7, 0)?, None);
+        let b = ObjectDeclaration::signal("b", ObjectType::bit_vector(15, 0)?, None);
+        assert_eq!(
+            b.assign(&Assignment::from(a.clone()).to_attribute(&a, RangeAttributeKind::Range)?)?
+                .declare("", ";\n")?,
+            "b(a'range) <= a;\n"
+        );
+        assert_eq!(
+            b.assign(&Assignment::from(a.clone()).to_attribute(&a, RangeAttributeKind::Length)?)?
+                .declare("", ";\n")?,
+            "b(a'length-1 downto 0) <= a;\n"
+        );
+        assert_eq!(
+            a.assign(&ObjectAssignment::from(b.clone()).assign_from(&vec![
+                FieldSelection::attribute(&a, RangeAttributeKind::Range)?
+            ])?)?
+            .declare("", ";\n")?,
+            "a <= b(a'range);\n"
+        );
+        assert_eq!(
+            b.assign(
+                &Assignment::from(BitVecValue::Unsigned(3))
+                    .to_attribute(&a, RangeAttributeKind::Range)?
+            )?
+            .declare("", ";\n")?,
+            "b(a'range) <= std_logic_vector(to_unsigned(3, a'length));\n"
+        );
+        assert_eq!(
+            a.assign(
+                &Assignment::from(StdLogicValue::Logic(true))
+                    .to_attribute(&a, RangeAttributeKind::High)?
+            )?
+            .declare("", ";\n")?,
+            "a(a'high) <= '1';\n"
+        );
+        // The bounds of the attribute are verified
+        assert!(a
+            .assign(&Assignment::from(b.clone()).to_attribute(&b, RangeAttributeKind::Range)?)
+            .is_err());
+        assert!(a
+            .assign(&Assignment::from(StdLogicValue::U).to_attribute(&b, RangeAttributeKind::High)?)
+            .is_err());
+        assert!(
+            FieldSelection::attribute(&bit_signal_object()?, RangeAttributeKind::Range).is_err()
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_record_assign() -> Result<()> {
         let a_single = BitVecValue::Others(StdLogicValue::H);
@@ -258,6 +365,61 @@ pub(crate) mod tests {
                 .declare("", ";\n")?
         );
 
+        let flags = ObjectDeclaration::signal(
+            "flags",
+            Type::record(
+                "flags_type",
+                vec![
+                    Field::new("a", Type::Bit, false, None),
+                    Field::new("b", Type::Bit, false, None),
+                    Field::new("c", Type::Bit, false, None),
+                    Field::new("d", Type::bitvec(4), false, None),
+                ],
+            )
+            .try_into()?,
+            None,
+        );
+        let mut direct = IndexMap::new();
+        direct.insert("a".to_string(), StdLogicValue::Logic(true).into());
+        direct.insert("d".to_string(), BitVecValue::Unsigned(3).into());
+        assert_eq!(
+            r#"flags <= (
+  a => '1',
+  d => std_logic_vector(to_unsigned(3, flags.d'length)),
+  others => '0'
+);
+"#,
+            flags
+                .assign(&AssignmentKind::partial_record(
+                    direct.clone(),
+                    StdLogicValue::Logic(false).into()
+                ))?
+                .declare("", ";\n")?
+        );
+        // The remaining fields are not of the same type
+        assert!(flags
+            .assign(&AssignmentKind::partial_record(
+                IndexMap::new(),
+                StdLogicValue::Logic(false).into()
+            ))
+            .is_err());
+        // The remaining fields can not be assigned the value
+        assert!(flags
+            .assign(&AssignmentKind::partial_record(
+                direct.clone(),
+                BitVecValue::Others(StdLogicValue::Logic(false)).into()
+            ))
+            .is_err());
+        direct.insert("b".to_string(), StdLogicValue::Logic(true).into());
+        direct.insert("c".to_string(), StdLogicValue::Logic(true).into());
+        // All fields are already assigned
+        assert!(flags
+            .assign(&AssignmentKind::partial_record(
+                direct,
+                StdLogicValue::Logic(false).into()
+            ))
+            .is_err());
+
         Ok(())
     }
 
@@ -275,6 +437,15 @@ pub(crate) mod tests {
                 .assign(&BitVecValue::from_str("10ZWUHLX-")?)?
                 .declare("", ";\n")?
         );
+        assert_eq!(
+            "arr(7 downto 0) <= x\"A5\";\n",
+            bitvec_signal("arr", 10, 0)?
+                .assign(&Assignment::from(BitVecValue::from_hex("A5")?).to_downto(7, 0)?)?
+                .declare("", ";\n")?
+        );
+        assert!(bitvec_signal("arr", 10, 0)?
+            .assign(&BitVecValue::from_octal("7")?)
+            .is_err());
 
         assert_eq!(
             "arr <= arr2;\n",
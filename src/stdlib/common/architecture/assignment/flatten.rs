@@ -43,6 +43,9 @@ impl FlatLength for ObjectType {
             ObjectType::Bit => 1,
             ObjectType::Natural => unimplemented!("Length calculation for Natural not implemented, Natural only supported in generics for now"),
             ObjectType::Positive => unimplemented!("Length calculation for Positive not implemented, Positive only supported in generics for now"),
+            ObjectType::Integer(_) => unimplemented!("Length calculation for Integer not implemented, Integer has no fixed representation"),
+            ObjectType::Signed(number) | ObjectType::Unsigned(number) => number.width(),
+            ObjectType::Enum(_) => unimplemented!("Length calculation for Enum not implemented, Enum has no fixed representation"),
             ObjectType::Array(arr) => arr.width() * arr.typ().flat_length()?,
             ObjectType::Record(rec) => {
                 let mut total: u32 = 0;
@@ -178,6 +181,9 @@ impl FlatAssignment for ObjectDeclaration {
                 ObjectType::Bit => finalize()?,
                 ObjectType::Natural => finalize()?,
                 ObjectType::Positive => finalize()?,
+                ObjectType::Integer(_) => finalize()?,
+                ObjectType::Signed(_) | ObjectType::Unsigned(_) => finalize()?,
+                ObjectType::Enum(_) => finalize()?,
                 ObjectType::Array(arr) if arr.is_bitvector() => finalize()?,
                 ObjectType::Array(arr) => {
                     // If the length is 1, make the last range selection an index selection, or introduce an index selection
@@ -213,11 +219,22 @@ impl FlatAssignment for ObjectDeclaration {
                 }
                 ObjectType::Record(rec) => {
                     if rec.is_union() {
-                        // TODO: This is incorrect. Driving the signal from multiple sources doesn't work.
-                        // Ideally, figure out some way to generate an "or" on multiple signals
+                        // The fields of a union share the same bits, driving all of them would
+                        // result in multiple drivers. Only the tag and the widest field are driven.
+                        // TODO: Ideally, figure out some way to generate an "or" on multiple signals
                         let tag_length = rec.get_field("tag")?.flat_length()?;
                         let remainder = self_typ.flat_length()? - tag_length;
+                        let mut widest: Option<(&String, u32)> = None;
                         for (name, field) in rec.fields() {
+                            let length = field.flat_length()?;
+                            if name != "tag" && widest.is_none_or(|(_, max)| length > max) {
+                                widest = Some((name, length));
+                            }
+                        }
+                        for (name, field) in rec.fields() {
+                            if name != "tag" && widest.map(|(widest, _)| widest) != Some(name) {
+                                continue;
+                            }
                             let mut new_to = to_field.clone();
                             let mut new_from = from_field.clone();
                             new_from.push(FieldSelection::name(name));
@@ -456,7 +473,6 @@ rec.b.d <= flat(2757 downto 1421);
         }
         assert_eq!(
             r#"flat(1338 downto 1337) <= union.tag;
-flat(41 downto 0) <= union.c;
 flat(1336 downto 0) <= union.d;
 "#,
             full_flat
@@ -489,11 +505,7 @@ union.d <= flat(1336 downto 0);
         assert_eq!(
             r#"flat(1340 downto 1339) <= union.tag;
 flat(1338 downto 1337) <= union.a.tag;
-flat(41 downto 0) <= union.a.c;
 flat(1336 downto 0) <= union.a.d;
-flat(1338 downto 1337) <= union.b.tag;
-flat(41 downto 0) <= union.b.c;
-flat(1336 downto 0) <= union.b.d;
 "#,
             full_flat
         );
@@ -15,13 +15,19 @@ impl ListUsings for AssignmentKind {
         match self {
             AssignmentKind::Object(_) => (),
             AssignmentKind::Concatenation(_) => (),
+            AssignmentKind::Conversion(_) => (),
             AssignmentKind::Direct(direct) => match direct {
                 DirectAssignment::Value(value) => match value {
                     ValueAssignment::Bit(_) => (),
                     ValueAssignment::Integer(_) => (),
+                    ValueAssignment::Enum(_) => (),
+                    ValueAssignment::Numeric(_) => {
+                        usings.add_using(Name::try_new("ieee")?, "numeric_std.all");
+                    }
                     ValueAssignment::BitVec(bitvec) => match bitvec {
                         BitVecValue::Others(_) => (),
                         BitVecValue::Full(_) => (),
+                        BitVecValue::Hex(_) | BitVecValue::Octal(_) => (),
                         BitVecValue::Unsigned(_) | BitVecValue::Signed(_) => {
                             usings.add_using(Name::try_new("ieee")?, "numeric_std.all");
                         }
@@ -32,6 +38,12 @@ impl ListUsings for AssignmentKind {
                         usings.combine(&ak.list_usings()?);
                     }
                 }
+                DirectAssignment::PartialRecord { direct, others } => {
+                    for (_, ak) in direct {
+                        usings.combine(&ak.list_usings()?);
+                    }
+                    usings.combine(&others.list_usings()?);
+                }
                 DirectAssignment::FullArray(arr) => match arr {
                     ArrayAssignment::Direct(direct) => {
                         for ak in direct {
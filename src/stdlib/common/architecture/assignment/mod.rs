@@ -88,6 +88,18 @@ impl AssignDeclaration {
             AssignmentKind::Concatenation(_) => Err(Error::InvalidTarget(
                 "Cannot reverse a concatenation assignment.".to_string(),
             )),
+            AssignmentKind::Conversion(conversion) => {
+                let object = ObjectAssignment::from(self.object().clone())
+                    .assign_from(self.assignment().to_field())?;
+                let reversed = match conversion {
+                    FlatConversion::ToFlat(_) => FlatConversion::FromFlat(object),
+                    FlatConversion::FromFlat(_) => FlatConversion::ToFlat(object),
+                };
+                conversion.object().object().assign(
+                    &Assignment::from(AssignmentKind::Conversion(reversed))
+                        .to_nested(conversion.object().from_field()),
+                )
+            }
         }
     }
 }
@@ -143,6 +155,15 @@ impl Assignment {
         self.to_range(RangeConstraint::Index(index))
     }
 
+    /// Append a range or index field selection by an attribute of another object
+    pub fn to_attribute(
+        self,
+        object: &(impl Into<ObjectAssignment> + Clone),
+        kind: RangeAttributeKind,
+    ) -> Result<Self> {
+        Ok(self.to_range(RangeConstraint::attribute(object, kind)?))
+    }
+
     /// Returns the fields selected
     pub fn to_field(&self) -> &Vec<FieldSelection> {
         &self.to_field
@@ -175,6 +196,9 @@ pub enum AssignmentKind {
     /// An object is assigned from the concatenation (VHDL `&`) of multiple
     /// objects, listed most-significant first
     Concatenation(Vec<ObjectAssignment>),
+    /// An object is assigned another object, converted between a record or
+    /// array type and its flattened representation
+    Conversion(FlatConversion),
 }
 
 impl AssignmentKind {
@@ -182,6 +206,17 @@ impl AssignmentKind {
         AssignmentKind::Direct(DirectAssignment::FullRecord(fields))
     }
 
+    /// Assign some fields of a record directly, and all other fields the `others` value
+    pub fn partial_record(
+        direct: IndexMap<String, AssignmentKind>,
+        others: AssignmentKind,
+    ) -> AssignmentKind {
+        AssignmentKind::Direct(DirectAssignment::PartialRecord {
+            direct,
+            others: Box::new(others),
+        })
+    }
+
     /// Converts an object assignment into a direct assignment. Useful when array or record types have identical fields but different type names.
     ///
     /// `convert_all` will also unwrap further nested objects
@@ -194,6 +229,9 @@ impl AssignmentKind {
             ObjectType::Bit => Ok(object.into()),
             ObjectType::Natural => Ok(object.into()),
             ObjectType::Positive => Ok(object.into()),
+            ObjectType::Integer(_) => Ok(object.into()),
+            ObjectType::Signed(_) | ObjectType::Unsigned(_) => Ok(object.into()),
+            ObjectType::Enum(_) => Ok(object.into()),
             ObjectType::Record(rec) => {
                 let mut fields = IndexMap::new();
                 for (field, typ) in rec.fields() {
@@ -292,11 +330,14 @@ impl AssignmentKind {
                 .map(|object| object.to_string())
                 .collect::<Vec<String>>()
                 .join(" & ")),
+            AssignmentKind::Conversion(conversion) => Ok(conversion.to_string()),
             AssignmentKind::Direct(direct) => match direct {
                 DirectAssignment::Value(value) => match value {
                     ValueAssignment::Bit(bit) => Ok(format!("'{}'", bit)),
                     ValueAssignment::Integer(integer) => Ok(integer.to_string()),
                     ValueAssignment::BitVec(bitvec) => Ok(bitvec.declare_for(object_identifier)),
+                    ValueAssignment::Numeric(numeric) => Ok(numeric.declare_for(object_identifier)),
+                    ValueAssignment::Enum(literal) => Ok(literal.clone()),
                 },
                 DirectAssignment::FullRecord(record) => {
                     let mut field_assignments = Vec::new();
@@ -315,6 +356,28 @@ impl AssignmentKind {
                     }
                     Ok(format!("({}\n{})", field_assignments.join(","), pre))
                 }
+                DirectAssignment::PartialRecord { direct, others } => {
+                    let mut field_assignments = Vec::new();
+                    let nested_pre = &format!("{}  ", pre);
+                    for (field, value) in direct {
+                        field_assignments.push(format!(
+                            "\n{}{} => {}",
+                            nested_pre,
+                            field,
+                            value.declare_for(
+                                format!("{}.{}", object_identifier, field),
+                                nested_pre,
+                                post
+                            )?
+                        ));
+                    }
+                    field_assignments.push(format!(
+                        "\n{}others => {}",
+                        nested_pre,
+                        others.declare_for(object_identifier, nested_pre, post)?
+                    ));
+                    Ok(format!("({}\n{})", field_assignments.join(","), pre))
+                }
                 DirectAssignment::FullArray(array) => match array {
                     ArrayAssignment::Direct(direct) => {
                         let mut positionals = Vec::new();
@@ -419,6 +482,41 @@ impl ObjectAssignment {
     }
 }
 
+/// Converts an object between a record or array type and its flattened
+/// (std_logic_vector) representation, using the `to_slv` and `from_slv`
+/// functions generated in the package declaring the type
+#[derive(Debug, Clone)]
+pub enum FlatConversion {
+    /// Flattens a record or array object, `to_slv(object)`
+    ToFlat(ObjectAssignment),
+    /// Converts a flat object to the record or array type being assigned, `from_slv(object)`
+    FromFlat(ObjectAssignment),
+}
+
+impl FlatConversion {
+    /// Returns the object being converted
+    pub fn object(&self) -> &ObjectAssignment {
+        match self {
+            FlatConversion::ToFlat(object) | FlatConversion::FromFlat(object) => object,
+        }
+    }
+}
+
+impl fmt::Display for FlatConversion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlatConversion::ToFlat(object) => write!(f, "to_slv({})", object),
+            FlatConversion::FromFlat(object) => write!(f, "from_slv({})", object),
+        }
+    }
+}
+
+impl From<FlatConversion> for AssignmentKind {
+    fn from(conversion: FlatConversion) -> Self {
+        AssignmentKind::Conversion(conversion)
+    }
+}
+
 /// Possible values which can be assigned to std_logic
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StdLogicValue {
@@ -489,6 +587,13 @@ pub enum DirectAssignment {
     Value(ValueAssignment),
     /// Assigning all fields of a Record
     FullRecord(IndexMap<String, AssignmentKind>),
+    /// Assign some fields of a Record directly, and all other fields a single value (e.g. ( a => '1', others => '0' ))
+    ///
+    /// The fields assigned by `others` must all be of the same type.
+    PartialRecord {
+        direct: IndexMap<String, AssignmentKind>,
+        others: Box<AssignmentKind>,
+    },
     /// Assigning all fields of an Array
     FullArray(ArrayAssignment),
 }
@@ -502,6 +607,46 @@ pub enum ValueAssignment {
     Integer(NonNegative),
     /// Assigning a value to a (part of) a bit vector
     BitVec(BitVecValue),
+    /// Assigning an integer value to a signed or unsigned number
+    Numeric(NumericValue),
+    /// Assigning a literal to an enumeration
+    Enum(String),
+}
+
+/// An integer value assigned to a signed or unsigned number
+#[derive(Debug, Clone)]
+pub enum NumericValue {
+    /// A value assigned to a signed number
+    ///
+    /// Result: to_signed(\[value\], \[name\]'length)
+    Signed(i32),
+    /// A value assigned to an unsigned number
+    ///
+    /// Result: to_unsigned(\[value\], \[name\]'length)
+    Unsigned(u32),
+}
+
+impl fmt::Display for NumericValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NumericValue::Signed(value) => write!(f, "signed value {}", value),
+            NumericValue::Unsigned(value) => write!(f, "unsigned value {}", value),
+        }
+    }
+}
+
+impl NumericValue {
+    /// Declares the value assigned for the object being assigned to
+    pub fn declare_for(&self, object_identifier: impl Into<String>) -> String {
+        match self {
+            NumericValue::Signed(value) => {
+                format!("to_signed({}, {}'length)", value, object_identifier.into())
+            }
+            NumericValue::Unsigned(value) => {
+                format!("to_unsigned({}, {}'length)", value, object_identifier.into())
+            }
+        }
+    }
 }
 
 /// A VHDL assignment constraint
@@ -541,9 +686,38 @@ impl FieldSelection {
         FieldSelection::Range(RangeConstraint::Index(index.into()))
     }
 
+    pub fn attribute(
+        object: &(impl Into<ObjectAssignment> + Clone),
+        kind: RangeAttributeKind,
+    ) -> Result<FieldSelection> {
+        Ok(FieldSelection::Range(RangeConstraint::attribute(
+            object, kind,
+        )?))
+    }
+
     pub fn name(name: impl Into<String>) -> FieldSelection {
         FieldSelection::Name(name.into())
     }
+
+    /// Verifies whether a field selection selects (part of) the same field
+    /// as this field selection
+    pub fn overlaps(&self, other: &FieldSelection) -> bool {
+        match (self, other) {
+            (FieldSelection::Name(name), FieldSelection::Name(other)) => name == other,
+            (FieldSelection::Range(range), FieldSelection::Range(other)) => range.overlaps(other),
+            // Cannot be selected on the same object
+            _ => true,
+        }
+    }
+
+    /// Verifies whether two nested field selections on the same object
+    /// overlap, i.e., whether one selects (part of) the other
+    pub fn overlap(selection: &[FieldSelection], other: &[FieldSelection]) -> bool {
+        selection
+            .iter()
+            .zip(other.iter())
+            .all(|(field, other)| field.overlaps(other))
+    }
 }
 
 /// A VHDL range constraint
@@ -555,6 +729,8 @@ pub enum RangeConstraint {
     Downto { start: i32, end: i32 },
     /// An index within a range
     Index(i32),
+    /// A range or index given by an attribute of another object, e.g. \[object\]'range
+    Attribute(RangeAttribute),
 }
 
 impl fmt::Display for RangeConstraint {
@@ -563,10 +739,110 @@ impl fmt::Display for RangeConstraint {
             RangeConstraint::To { start, end } => write!(f, "({} to {})", start, end),
             RangeConstraint::Downto { start, end } => write!(f, "({} downto {})", start, end),
             RangeConstraint::Index(index) => write!(f, "({})", index),
+            RangeConstraint::Attribute(attribute) => write!(f, "({})", attribute),
+        }
+    }
+}
+
+/// The attributes of an object which can select a range or index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeAttributeKind {
+    /// The range of the object
+    ///
+    /// Result: \[object\]'range
+    Range,
+    /// A range with the length of the object, starting at 0
+    ///
+    /// Result: \[object\]'length-1 downto 0
+    Length,
+    /// The greatest index of the object
+    ///
+    /// Result: \[object\]'high
+    High,
+    /// The smallest index of the object
+    ///
+    /// Result: \[object\]'low
+    Low,
+}
+
+/// A range or index selection referring to an attribute of another object
+///
+/// The bounds the attribute evaluates to are recorded when the selection is
+/// created, and are only used to verify assignments. The declared selection
+/// refers to the attribute, and stays correct when the bounds of the object
+/// change.
+#[derive(Debug, Clone)]
+pub struct RangeAttribute {
+    /// The object (and any fields selected on it) of the attribute
+    object: String,
+    kind: RangeAttributeKind,
+    high: i32,
+    low: i32,
+}
+
+impl fmt::Display for RangeAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            RangeAttributeKind::Range => write!(f, "{}'range", self.object),
+            RangeAttributeKind::Length => write!(f, "{}'length-1 downto 0", self.object),
+            RangeAttributeKind::High => write!(f, "{}'high", self.object),
+            RangeAttributeKind::Low => write!(f, "{}'low", self.object),
         }
     }
 }
 
+impl RangeAttribute {
+    /// Create an attribute of an object, the object must be an array or a
+    /// signed or unsigned number
+    pub fn try_new(
+        object: &(impl Into<ObjectAssignment> + Clone),
+        kind: RangeAttributeKind,
+    ) -> Result<RangeAttribute> {
+        let object = object.clone().into();
+        let (high, low) = match object.typ()? {
+            ObjectType::Array(array) => (array.high(), array.low()),
+            ObjectType::Signed(number) | ObjectType::Unsigned(number) => {
+                (number.high(), number.low())
+            }
+            typ => {
+                return Err(Error::InvalidArgument(format!(
+                    "Cannot select a range or index by an attribute of {}",
+                    typ
+                )))
+            }
+        };
+        let (high, low) = match kind {
+            RangeAttributeKind::Range => (high, low),
+            RangeAttributeKind::Length => (high - low, 0),
+            RangeAttributeKind::High => (high, high),
+            RangeAttributeKind::Low => (low, low),
+        };
+        Ok(RangeAttribute {
+            object: object.to_string(),
+            kind,
+            high,
+            low,
+        })
+    }
+
+    /// Returns the object (and any fields selected on it) of the attribute
+    pub fn object(&self) -> &str {
+        self.object.as_str()
+    }
+
+    pub fn kind(&self) -> RangeAttributeKind {
+        self.kind
+    }
+
+    /// Returns true if the attribute selects an index rather than a range
+    pub fn is_index(&self) -> bool {
+        matches!(
+            self.kind,
+            RangeAttributeKind::High | RangeAttributeKind::Low
+        )
+    }
+}
+
 impl RangeConstraint {
     /// Create a `RangeConstraint::To` and ensure correctness (end > start)
     pub fn to(start: i32, end: i32) -> crate::Result<RangeConstraint> {
@@ -592,6 +868,16 @@ impl RangeConstraint {
         }
     }
 
+    /// Create a `RangeConstraint::Attribute` selecting by an attribute of an object
+    pub fn attribute(
+        object: &(impl Into<ObjectAssignment> + Clone),
+        kind: RangeAttributeKind,
+    ) -> Result<RangeConstraint> {
+        Ok(RangeConstraint::Attribute(RangeAttribute::try_new(
+            object, kind,
+        )?))
+    }
+
     /// Returns the width of the range
     pub fn width(&self) -> Width {
         match self {
@@ -602,6 +888,33 @@ impl RangeConstraint {
                 Width::Vector((1 + start - end).try_into().unwrap())
             }
             RangeConstraint::Index(_) => Width::Scalar,
+            RangeConstraint::Attribute(attribute) => {
+                if attribute.is_index() {
+                    Width::Scalar
+                } else {
+                    Width::Vector((1 + attribute.high - attribute.low).try_into().unwrap())
+                }
+            }
+        }
+    }
+
+    /// Returns the VHDL expression of the width of the range, which refers
+    /// to the length of the object of an attribute range
+    pub fn width_string(&self) -> String {
+        match self {
+            RangeConstraint::Attribute(attribute) if !attribute.is_index() => {
+                format!("{}'length", attribute.object())
+            }
+            _ => self.width_u32().to_string(),
+        }
+    }
+
+    /// Returns true if the constraint selects an index rather than a range
+    pub fn is_index(&self) -> bool {
+        match self {
+            RangeConstraint::Index(_) => true,
+            RangeConstraint::Attribute(attribute) => attribute.is_index(),
+            _ => false,
         }
     }
 
@@ -609,7 +922,7 @@ impl RangeConstraint {
     pub fn width_u32(&self) -> u32 {
         match self.width() {
             Width::Scalar => 1,
-            Width::Vector(width) => width,
+            Width::Vector(width) => width.try_into().unwrap(),
         }
     }
 
@@ -619,6 +932,7 @@ impl RangeConstraint {
             RangeConstraint::To { start: _, end } => *end,
             RangeConstraint::Downto { start, end: _ } => *start,
             RangeConstraint::Index(index) => *index,
+            RangeConstraint::Attribute(attribute) => attribute.high,
         }
     }
 
@@ -628,6 +942,7 @@ impl RangeConstraint {
             RangeConstraint::To { start, end: _ } => *start,
             RangeConstraint::Downto { start: _, end } => *end,
             RangeConstraint::Index(index) => *index,
+            RangeConstraint::Attribute(attribute) => attribute.low,
         }
     }
 
@@ -1,27 +1,60 @@
+use crate::generator::vhdl::DeclareType;
 use crate::Result;
 use crate::{stdlib::common::architecture::ArchitectureDeclare, Error};
 
-use super::{ArchitectureDeclaration, ObjectDeclaration, ObjectKind, ObjectMode};
+use super::{AliasDeclaration, ArchitectureDeclaration, ObjectDeclaration, ObjectKind, ObjectMode};
 
-impl ArchitectureDeclare for ArchitectureDeclaration<'_> {
+impl ArchitectureDeclare for ArchitectureDeclaration {
     fn declare(&self, pre: &str, post: &str) -> crate::Result<String> {
         match self {
-            ArchitectureDeclaration::Type(_) => todo!(),
+            ArchitectureDeclaration::Type(typ) => {
+                let declaration = typ.declare(true)?;
+                let declaration = declaration.trim_end();
+                let declaration = declaration.strip_suffix(';').unwrap_or(declaration);
+                let lines = declaration
+                    .lines()
+                    .map(|line| {
+                        if line.is_empty() {
+                            String::new()
+                        } else {
+                            format!("{}{}", pre, line)
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                Ok(format!("{}{}", lines.join("\n"), post))
+            }
             ArchitectureDeclaration::SubType(_) => todo!(),
             ArchitectureDeclaration::Procedure(_) => todo!(),
             ArchitectureDeclaration::Function(_) => todo!(),
             ArchitectureDeclaration::Object(object) => object.declare(pre, post),
-            ArchitectureDeclaration::Alias(_) => todo!(),
+            ArchitectureDeclaration::Alias(alias) => alias.declare(pre, post),
             ArchitectureDeclaration::Component(_) => todo!(),
             ArchitectureDeclaration::Custom(_) => todo!(),
         }
     }
 }
 
+impl ArchitectureDeclare for AliasDeclaration {
+    fn declare(&self, pre: &str, post: &str) -> Result<String> {
+        let mut result = format!(
+            "{}alias {} : {} is {}",
+            pre,
+            self.identifier(),
+            self.typ().type_name(),
+            self.object().identifier()
+        );
+        for field in self.field_selection() {
+            result.push_str(&field.to_string());
+        }
+        result.push_str(post);
+        Ok(result)
+    }
+}
+
 impl ArchitectureDeclare for ObjectDeclaration {
     fn declare(&self, pre: &str, post: &str) -> Result<String> {
-        if self.kind() == ObjectKind::EntityPort {
-            // Entity ports are part of the architecture, but aren't declared in the declaration part
+        if self.kind() == ObjectKind::EntityPort || self.kind() == ObjectKind::EntityGeneric {
+            // Entity ports and generics are part of the architecture, but aren't declared in the declaration part
             return Ok("".to_string());
         }
         let mut result = pre.to_string();
@@ -29,7 +62,7 @@ impl ArchitectureDeclare for ObjectDeclaration {
             ObjectKind::Signal => "signal ",
             ObjectKind::Variable => "variable ",
             ObjectKind::Constant => "constant ",
-            ObjectKind::EntityPort => "", // Should be unreachable
+            ObjectKind::EntityPort | ObjectKind::EntityGeneric => "", // Should be unreachable
             ObjectKind::ComponentPort => "",
         });
         result.push_str(&self.identifier());
@@ -58,7 +91,16 @@ impl ArchitectureDeclare for ObjectDeclaration {
 
 #[cfg(test)]
 mod tests {
-    use crate::stdlib::common::architecture::{assignment::StdLogicValue, object::ObjectType};
+    use std::convert::TryFrom;
+
+    use crate::generator::common::Type;
+    use crate::stdlib::common::architecture::{
+        assignment::{
+            Assign, Assignment, FieldSelection, ObjectAssignment, StdLogicValue, ValueAssignment,
+        },
+        declaration::tests::test_complex_signal,
+        object::ObjectType,
+    };
 
     use super::*;
 
@@ -89,4 +131,108 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_bounded_declarations() -> Result<()> {
+        let data = ObjectType::bit_vector(7, 0)?.with_bounds("WIDTH-1", "0")?;
+        assert_eq!(
+            "signal data : std_logic_vector(WIDTH-1 downto 0);\n",
+            ObjectDeclaration::signal("data", data.clone(), None).declare("", ";\n")?
+        );
+        let mem = ObjectType::array(15, 0, data.clone(), "mem_type")?
+            .with_bounds("DEPTH-1", "0")?
+            .with_bounds("DEPTH", "1")?;
+        assert_eq!(
+            "signal mem : mem_type(DEPTH downto 1);\n",
+            ObjectDeclaration::signal("mem", mem.clone(), None).declare("", ";\n")?
+        );
+        assert_eq!(
+            "signal count : unsigned(WIDTH-1 downto 0);\n",
+            ObjectDeclaration::signal(
+                "count",
+                ObjectType::unsigned(7, 0)?.with_bounds("WIDTH-1", "0")?,
+                None
+            )
+            .declare("", ";\n")?
+        );
+        // The values of the bounds are used to verify assignments
+        let mem_data = mem.get_field(&FieldSelection::index(3))?;
+        assert!(data.can_assign_type(&mem_data).is_ok());
+        assert!(ObjectType::bit_vector(3, 0)?
+            .can_assign_type(&mem_data)
+            .is_err());
+        assert!(ObjectType::Bit.with_bounds("WIDTH-1", "0").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_enum_declarations() -> Result<()> {
+        let typ = Type::enumeration("state", vec!["idle", "busy", "done"])?;
+        assert_eq!(
+            "  type state_type is (idle, busy, done);\n",
+            ArchitectureDeclaration::Type(typ.clone()).declare("  ", ";\n")?
+        );
+        let state = ObjectType::try_from(typ)?;
+        assert_eq!(
+            "signal state : state_type := idle;\n",
+            ObjectDeclaration::signal(
+                "state",
+                state.clone(),
+                Some(ValueAssignment::Enum("idle".to_string()).into())
+            )
+            .declare("", ";\n")?
+        );
+        assert!(state
+            .can_assign(&ValueAssignment::Enum("busy".to_string()).into())
+            .is_ok());
+        assert!(state
+            .can_assign(&ValueAssignment::Enum("stop".to_string()).into())
+            .is_err());
+        assert!(ObjectType::Bit
+            .can_assign(&ValueAssignment::Enum("idle".to_string()).into())
+            .is_err());
+        assert!(Type::enumeration("state", vec!["idle", "idle"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_alias_declarations() -> Result<()> {
+        let signal = test_complex_signal()?;
+        let alias = AliasDeclaration::new(
+            &signal,
+            "a_high",
+            vec![FieldSelection::name("a"), FieldSelection::downto(10, 3)?],
+        )?;
+        assert_eq!(
+            "  alias a_high : std_logic_vector(10 downto 3) is test_signal.a(10 downto 3);\n",
+            ArchitectureDeclaration::from(alias.clone()).declare("  ", ";\n")?
+        );
+
+        // Aliases are assigned to and from by their identifier
+        let other = ObjectDeclaration::signal("other", ObjectType::bit_vector(7, 0)?, None);
+        assert_eq!(
+            "a_high <= other;\n",
+            alias.assign(&other)?.declare("", ";\n")?
+        );
+        assert_eq!(
+            "other <= a_high;\n",
+            other.assign(&alias)?.declare("", ";\n")?
+        );
+        assert_eq!(
+            "other(3 downto 0) <= a_high(6 downto 3);\n",
+            other
+                .assign(
+                    &Assignment::from(
+                        ObjectAssignment::from(alias.clone())
+                            .assign_from(&vec![FieldSelection::downto(6, 3)?])?
+                    )
+                    .to_downto(3, 0)?
+                )?
+                .declare("", ";\n")?
+        );
+        assert!(other
+            .assign(&AliasDeclaration::from_object(&signal, "rec"))
+            .is_err());
+        Ok(())
+    }
 }
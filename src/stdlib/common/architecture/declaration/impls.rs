@@ -1,12 +1,29 @@
 use crate::generator::vhdl::{ListUsings, Usings};
+use crate::Result;
 
-use super::ObjectDeclaration;
+use super::{ArchitectureDeclaration, ObjectDeclaration};
 
 impl ListUsings for ObjectDeclaration {
     fn list_usings(&self) -> crate::Result<crate::generator::vhdl::Usings> {
-        match self.default() {
-            Some(ak) => ak.list_usings(),
-            None => Ok(Usings::new_empty()),
+        let mut usings = self.typ().list_usings()?;
+        if let Some(ak) = self.default() {
+            usings.combine(&ak.list_usings()?);
+        }
+        Ok(usings)
+    }
+}
+
+impl ListUsings for ArchitectureDeclaration {
+    fn list_usings(&self) -> Result<Usings> {
+        match self {
+            ArchitectureDeclaration::Object(object) => object.list_usings(),
+            ArchitectureDeclaration::Alias(alias) => alias.object().list_usings(),
+            ArchitectureDeclaration::Type(_)
+            | ArchitectureDeclaration::SubType(_)
+            | ArchitectureDeclaration::Procedure(_)
+            | ArchitectureDeclaration::Function(_)
+            | ArchitectureDeclaration::Component(_)
+            | ArchitectureDeclaration::Custom(_) => Ok(Usings::new_empty()),
         }
     }
 }
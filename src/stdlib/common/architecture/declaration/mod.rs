@@ -40,7 +40,7 @@ pub mod impls;
 // | PSL_Clock_Declaration
 /// Architecture declaration.
 #[derive(Debug, Clone)]
-pub enum ArchitectureDeclaration<'a> {
+pub enum ArchitectureDeclaration {
     /// Type declarations within the architecture
     Type(Type),
     SubType(String), // TODO: Do we want subtypes, or should these just be (part of) types?
@@ -53,7 +53,7 @@ pub enum ArchitectureDeclaration<'a> {
     /// as such, the ports of the entity implemented are treated as inferred declarations.
     Object(ObjectDeclaration),
     /// Alias for an object declaration, with optional range constraint
-    Alias(AliasDeclaration<'a>),
+    Alias(AliasDeclaration),
     /// Component declarations within the architecture
     Component(Component),
     Custom(String), // TODO: Custom (templates?)
@@ -67,6 +67,8 @@ pub enum ObjectKind {
     Constant,
     /// Represents ports declared on the entity this architecture is describing
     EntityPort,
+    /// Represents generics declared on the entity this architecture is describing
+    EntityGeneric,
     /// Represents ports on components within the architecture
     ComponentPort,
 }
@@ -78,6 +80,7 @@ impl fmt::Display for ObjectKind {
             ObjectKind::Variable => write!(f, "Variable"),
             ObjectKind::Constant => write!(f, "Constant"),
             ObjectKind::EntityPort => write!(f, "EntityPort"),
+            ObjectKind::EntityGeneric => write!(f, "EntityGeneric"),
             ObjectKind::ComponentPort => write!(f, "ComponentPort"),
         }
     }
@@ -89,6 +92,7 @@ impl fmt::Display for ObjectKind {
 // Now consider that there can be multiple signals between these steps, and that these signals can consist of multiple fields (records, arrays), each of which can also be assigned objects...
 // So the challenge will be making sure that assigning something like some_record <= (a => some_other_record.a.b.c.d, b => some_array(4 to 8)) forces all objects to which the fields belong into appropriate modes.
 // Basically, this only works if each object declaration is only ever a single mutable reference, which requires some significant rewrites at this point, and dealing with Rust's lifetimes.
+// Architectures now own their objects in an ObjectStore, which provides such references through handles, and mark signals as assigned when they are.
 /// The state of the object, with respect to the architecture
 ///
 /// (E.g., an "in" port on the entity is "Assigned", but so is an "out" port of a component inside the architecture)
@@ -184,6 +188,18 @@ impl ObjectDeclaration {
         }
     }
 
+    /// Entity Generics represent the generics of the entity the architecture is describing.
+    /// Like constants, they carry a value which can be used by other objects, but cannot be assigned.
+    pub fn entity_generic(identifier: impl Into<String>, typ: ObjectType) -> ObjectDeclaration {
+        ObjectDeclaration {
+            identifier: identifier.into(),
+            typ,
+            mode: ObjectMode::Assigned,
+            default: None,
+            kind: ObjectKind::EntityGeneric,
+        }
+    }
+
     pub fn component_port(
         identifier: impl Into<String>,
         typ: ObjectType,
@@ -201,7 +217,7 @@ impl ObjectDeclaration {
         }
     }
 
-    pub fn set_default(mut self, default: AssignmentKind) -> Result<()> {
+    pub fn set_default(&mut self, default: AssignmentKind) -> Result<()> {
         // TODO: Verify mode as well
         match self.kind() {
             ObjectKind::Signal | ObjectKind::Variable | ObjectKind::ComponentPort => {
@@ -209,7 +225,7 @@ impl ObjectDeclaration {
                 self.default = Some(default);
                 Ok(())
             }
-            ObjectKind::Constant | ObjectKind::EntityPort => Err(Error::InvalidTarget(format!(
+            ObjectKind::Constant | ObjectKind::EntityPort | ObjectKind::EntityGeneric => Err(Error::InvalidTarget(format!(
                 "Default cannot be assigned to {} object",
                 self.kind()
             ))),
@@ -236,6 +252,11 @@ impl ObjectDeclaration {
         &self.mode
     }
 
+    /// Set the mode of the object, e.g. once it has been assigned
+    pub fn set_mode(&mut self, mode: ObjectMode) {
+        self.mode = mode;
+    }
+
     pub fn from_port(port: &Port, is_entity: bool) -> Result<Vec<ObjectDeclaration>> {
         let ent_obj = |p: &Port| -> Result<ObjectDeclaration> {
             Ok(ObjectDeclaration::entity_port(
@@ -276,42 +297,41 @@ impl ObjectDeclaration {
 
 /// Aliases an existing object, with optional field constraint
 #[derive(Debug, Clone)]
-pub struct AliasDeclaration<'a> {
+pub struct AliasDeclaration {
     identifier: String,
-    /// Reference to an existing object declaration
-    object: &'a ObjectDeclaration,
+    /// The existing object declaration being aliased
+    object: ObjectDeclaration,
     /// Optional field selection(s) - when assigning to or from the alias, this is used to determine the fields it represents
     field_selection: Vec<FieldSelection>,
+    /// The object type of the alias (after fields have been selected)
+    typ: ObjectType,
 }
 
-impl<'a> AliasDeclaration<'a> {
+impl AliasDeclaration {
     pub fn new(
-        object: &'a ObjectDeclaration,
+        object: &ObjectDeclaration,
         identifier: impl Into<String>,
         fields: Vec<FieldSelection>,
-    ) -> Result<AliasDeclaration<'a>> {
+    ) -> Result<AliasDeclaration> {
         AliasDeclaration::from_object(object, identifier).with_selection(fields)
     }
 
     pub fn from_object(
-        object: &'a ObjectDeclaration,
+        object: &ObjectDeclaration,
         identifier: impl Into<String>,
-    ) -> AliasDeclaration<'a> {
+    ) -> AliasDeclaration {
         AliasDeclaration {
             identifier: identifier.into(),
-            object,
+            object: object.clone(),
             field_selection: vec![],
+            typ: object.typ().clone(),
         }
     }
 
     /// Apply one or more field selections to the alias
     pub fn with_selection(mut self, fields: Vec<FieldSelection>) -> Result<Self> {
-        let mut object = self.object().typ().clone();
-        for field in self.field_selection() {
-            object = object.get_field(field)?;
-        }
         for field in fields {
-            object = object.get_field(&field)?;
+            self.typ = self.typ.get_field(&field)?;
             self.field_selection.push(field)
         }
 
@@ -319,8 +339,8 @@ impl<'a> AliasDeclaration<'a> {
     }
 
     /// Returns the actual object this is aliasing
-    pub fn object(&self) -> &'a ObjectDeclaration {
-        self.object
+    pub fn object(&self) -> &ObjectDeclaration {
+        &self.object
     }
 
     /// Returns the optional field selection of this alias
@@ -334,26 +354,28 @@ impl<'a> AliasDeclaration<'a> {
     }
 
     /// Returns the object type of the alias (after fields have been selected)
-    pub fn typ(&self) -> Result<ObjectType> {
-        let mut object = self.object().typ().clone();
-        for field in self.field_selection() {
-            object = object.get_field(field)?;
+    pub fn typ(&self) -> &ObjectType {
+        &self.typ
+    }
+
+    /// Returns an object declaration representing the alias, which can be
+    /// assigned to or from by the alias's identifier
+    pub fn to_object(&self) -> ObjectDeclaration {
+        ObjectDeclaration {
+            identifier: self.identifier().to_string(),
+            typ: self.typ().clone(),
+            mode: self.object().mode().clone(),
+            default: None,
+            kind: self.object().kind().clone(),
         }
-        Ok(object)
     }
 }
 
-impl<'a> TryInto<ObjectDeclaration> for AliasDeclaration<'a> {
+impl TryInto<ObjectDeclaration> for AliasDeclaration {
     type Error = Error;
 
     fn try_into(self) -> Result<ObjectDeclaration> {
-        Ok(ObjectDeclaration {
-            identifier: self.identifier().to_string(),
-            typ: self.typ()?,
-            mode: self.object().mode().clone(),
-            default: None,
-            kind: self.object().kind().clone(),
-        })
+        Ok(self.to_object())
     }
 }
 
@@ -1,11 +1,14 @@
 use crate::{
-    generator::vhdl::{Declare, DeclareUsings, ListUsings},
+    generator::{
+        user_region,
+        vhdl::{Declare, DeclareUsings, ListUsings},
+    },
     Document, Result,
 };
 
 use super::*;
 
-impl ListUsings for Architecture<'_> {
+impl ListUsings for Architecture {
     fn list_usings(&self) -> Result<Usings> {
         Ok(self.usings.clone())
     }
@@ -42,7 +45,7 @@ impl ListUsings for Architecture<'_> {
 // Sequential statements
 //
 // Any complex logic should probably just be string templates.
-impl<'a> Declare for Architecture<'a> {
+impl Declare for Architecture {
     fn declare(&self) -> Result<String> {
         let mut result = String::new();
         result.push_str(self.declare_usings()?.as_str());
@@ -64,25 +67,31 @@ impl<'a> Declare for Architecture<'a> {
             )
             .as_str(),
         );
-        for declaration in self.declarations() {
+        for declaration in self.declarations()? {
             result.push_str(&declaration.declare("  ", ";\n")?);
         }
+        if self.has_user_regions() {
+            result.push_str(&user_region("--", "  ", "declarations"));
+        }
         result.push_str("begin\n");
         for statement in self.statements() {
             result.push_str(&statement.declare("  ", ";\n")?);
         }
+        if self.has_user_regions() {
+            result.push_str(&user_region("--", "  ", "statements"));
+        }
         result.push_str(format!("end architecture {};\n", self.identifier()).as_str());
         Ok(result)
     }
 }
 
-impl Identify for Architecture<'_> {
+impl Identify for Architecture {
     fn identifier(&self) -> &str {
         self.identifier.as_ref()
     }
 }
 
-impl Document for Architecture<'_> {
+impl Document for Architecture {
     fn doc(&self) -> Option<String> {
         self.doc.clone()
     }
@@ -1,6 +1,8 @@
+use std::convert::TryInto;
+
 use indexmap::IndexMap;
 
-use crate::Result;
+use crate::{Error, Result};
 use crate::{
     generator::{
         common::Package,
@@ -11,14 +13,17 @@ use crate::{
 
 use super::entity::Entity;
 
-use self::declaration::{ArchitectureDeclaration, ObjectDeclaration};
+use self::assignment::{AssignDeclaration, FieldSelection};
+use self::declaration::{ArchitectureDeclaration, ObjectDeclaration, ObjectKind, ObjectMode};
 use self::statement::Statement;
+use self::store::{ObjectHandle, ObjectStore};
 
 pub mod assignment;
 pub mod declaration;
 pub mod impls;
 pub mod object;
 pub mod statement;
+pub mod store;
 
 // NOTE: One of the main things to consider is probably how to handle multiple element lanes. Probably as a check on the number of lanes,
 // then wrapping in a generate statement. Need to consider indexes at that point.
@@ -26,7 +31,7 @@ pub mod statement;
 
 /// An architecture
 #[derive(Debug, Clone)]
-pub struct Architecture<'a> {
+pub struct Architecture {
     /// Name of the architecture
     identifier: Name,
     /// Entity which this architecture is for
@@ -35,10 +40,35 @@ pub struct Architecture<'a> {
     usings: Usings,
     /// Documentation.
     doc: Option<String>,
+    /// The objects of the architecture, i.e. the ports of its entity and the objects it declares
+    objects: ObjectStore,
     /// The declaration part of the architecture
-    declaration: Vec<ArchitectureDeclaration<'a>>,
+    declaration: Vec<Declared>,
     /// The statement part of the architecture
     statement: Vec<Statement>,
+    /// The objects driven by the statements of the architecture
+    drivers: Vec<Driver>,
+    /// Whether the declaration and statement parts end with user regions
+    user_regions: bool,
+}
+
+/// An object, or fields of an object, driven by a statement of an architecture
+#[derive(Debug, Clone)]
+struct Driver {
+    object: ObjectHandle,
+    /// The (nested) fields of the object being driven, all fields if empty
+    fields: Vec<FieldSelection>,
+    /// Description of the statement driving the object
+    statement: String,
+}
+
+/// A declaration in the declaration part of an architecture
+#[derive(Debug, Clone)]
+enum Declared {
+    /// An object, owned by the object store of the architecture
+    Object(ObjectHandle),
+    /// Any other declaration
+    Other(Box<ArchitectureDeclaration>),
 }
 
 pub trait ArchitectureDeclare {
@@ -46,7 +76,7 @@ pub trait ArchitectureDeclare {
     fn declare(&self, pre: &str, post: &str) -> Result<String>;
 }
 
-impl<'a> Architecture<'a> {
+impl Architecture {
     /// Create the architecture based on a component contained within a package, assuming the library (project) is "work" and the architecture's identifier is "behavioral"
     pub fn new_default(package: &Package, component_id: impl Into<String>) -> Result<Architecture> {
         Architecture::new(
@@ -66,13 +96,29 @@ impl<'a> Architecture<'a> {
     ) -> Result<Architecture> {
         let mut usings = package.list_usings()?;
         usings.add_using(library_id, format!("{}.all", package.identifier));
+        let entity = Entity::from(package.get_component(component_id)?);
+        let mut objects = ObjectStore::new();
+        for parameter in entity.parameters() {
+            objects.insert(ObjectDeclaration::entity_generic(
+                parameter.name.clone(),
+                parameter.typ.clone().try_into()?,
+            ))?;
+        }
+        for port in entity.ports() {
+            for object in ObjectDeclaration::from_port(port, true)? {
+                objects.insert(object)?;
+            }
+        }
         Ok(Architecture {
             identifier,
-            entity: Entity::from(package.get_component(component_id)?),
+            entity,
             usings: usings,
             doc: None,
+            objects,
             declaration: vec![],
             statement: vec![],
+            drivers: vec![],
+            user_regions: false,
         })
     }
 
@@ -92,39 +138,107 @@ impl<'a> Architecture<'a> {
         self.doc = Some(doc.into())
     }
 
+    /// Return this architecture with user regions at the end of its
+    /// declaration and statement parts, whose contents are kept when a file
+    /// with this architecture is regenerated.
+    pub fn with_user_regions(mut self) -> Self {
+        self.user_regions = true;
+        self
+    }
+
+    /// Returns true if the declaration and statement parts of this
+    /// architecture end with user regions.
+    pub fn has_user_regions(&self) -> bool {
+        self.user_regions
+    }
+
+    /// Add a declaration to the declaration part of this architecture.
+    /// Objects are added to the object store of this architecture, see [Architecture::add_object].
     pub fn add_declaration(
         &mut self,
-        declaration: impl Into<ArchitectureDeclaration<'a>>,
+        declaration: impl Into<ArchitectureDeclaration>,
     ) -> Result<()> {
-        let declaration = declaration.into();
-        match &declaration {
-            ArchitectureDeclaration::Object(object) => {
-                self.usings.combine(&object.list_usings()?);
-            }
-            ArchitectureDeclaration::Alias(alias) => {
-                self.usings.combine(&alias.object().list_usings()?);
+        match declaration.into() {
+            ArchitectureDeclaration::Object(object) => self.add_object(object).map(|_| ()),
+            declaration => {
+                self.usings.combine(&declaration.list_usings()?);
+                self.declaration.push(Declared::Other(Box::new(declaration)));
+                Ok(())
             }
-            ArchitectureDeclaration::Type(_)
-            | ArchitectureDeclaration::SubType(_)
-            | ArchitectureDeclaration::Procedure(_)
-            | ArchitectureDeclaration::Function(_)
-            | ArchitectureDeclaration::Component(_)
-            | ArchitectureDeclaration::Custom(_) => (),
         }
-        self.declaration.push(declaration);
-        Ok(())
     }
 
+    /// Declare an object in this architecture, returning the handle through which it can be referred to.
+    ///
+    /// Returns an error if the architecture already has an object (or port) with the same identifier.
+    pub fn add_object(&mut self, object: ObjectDeclaration) -> Result<ObjectHandle> {
+        let usings = object.list_usings()?;
+        let handle = self.objects.insert(object)?;
+        self.usings.combine(&usings);
+        self.declaration.push(Declared::Object(handle));
+        Ok(handle)
+    }
+
+    /// Returns the object referred to by a handle
+    pub fn object(&self, handle: ObjectHandle) -> Result<&ObjectDeclaration> {
+        self.objects.get(handle)
+    }
+
+    /// Returns a mutable reference to the object referred to by a handle
+    pub fn object_mut(&mut self, handle: ObjectHandle) -> Result<&mut ObjectDeclaration> {
+        self.objects.get_mut(handle)
+    }
+
+    /// Returns the handle of the object, entity port or entity generic with the given identifier, if any
+    pub fn find_object(&self, identifier: &str) -> Option<ObjectHandle> {
+        self.objects.find(identifier)
+    }
+
+    /// Returns the objects of this architecture, i.e. the ports and generics of its entity and the objects it declares
+    pub fn objects(&self) -> &ObjectStore {
+        &self.objects
+    }
+
+    /// Add a statement to the statement part of this architecture.
+    /// Signals of this architecture which are driven by the statement are marked as assigned.
+    ///
+    /// Returns an error if the statement drives (fields of) an object which is already driven by another statement.
     pub fn add_statement(&mut self, statement: impl Into<Statement>) -> Result<()> {
         let statement = statement.into();
-        match &statement {
-            Statement::Assignment(assignment) => self.usings.combine(&assignment.list_usings()?),
-            Statement::PortMapping(pm) => {
-                for (_, object) in pm.ports() {
-                    self.usings.combine(&object.list_usings()?);
+        let usings = statement.list_usings()?;
+        let description = statement.describe();
+        let mut drivers: Vec<Driver> = vec![];
+        for (object, fields) in statement.drivers() {
+            let handle = match self.find_object(object.identifier()) {
+                Some(handle) => handle,
+                None => continue,
+            };
+            if let Some(driver) = self.drivers.iter().chain(drivers.iter()).find(|driver| {
+                driver.object == handle && FieldSelection::overlap(&driver.fields, fields)
+            }) {
+                let mut driven = object.identifier().to_string();
+                for field in fields {
+                    driven.push_str(&field.to_string());
                 }
+                return Err(Error::InvalidArgument(format!(
+                    "{} is driven by both the {} and the {}",
+                    driven, driver.statement, description
+                )));
             }
+            drivers.push(Driver {
+                object: handle,
+                fields: fields.to_vec(),
+                statement: description.clone(),
+            });
         }
+        for driver in &drivers {
+            let object = self.objects.get_mut(driver.object)?;
+            if object.kind() == ObjectKind::Signal && *object.mode() == ObjectMode::Undefined {
+                object.set_mode(ObjectMode::Assigned);
+            }
+        }
+        self.usings.combine(&usings);
+        self.drivers.extend(drivers);
         self.statement.push(statement);
         Ok(())
     }
@@ -133,8 +247,15 @@ impl<'a> Architecture<'a> {
         &self.statement
     }
 
-    pub fn declarations(&self) -> &Vec<ArchitectureDeclaration> {
-        &self.declaration
+    /// Returns the declaration part of this architecture, with objects in their current state
+    pub fn declarations(&self) -> Result<Vec<ArchitectureDeclaration>> {
+        self.declaration
+            .iter()
+            .map(|declared| match declared {
+                Declared::Object(handle) => Ok(self.object(*handle)?.clone().into()),
+                Declared::Other(declaration) => Ok(declaration.as_ref().clone()),
+            })
+            .collect()
     }
 
     /// Return the entity this architecture implements.
@@ -142,15 +263,52 @@ impl<'a> Architecture<'a> {
         &self.entity
     }
 
+    /// Returns the assignments of the reset branch of a clocked process,
+    /// which assign each of the registered signals its default value.
+    ///
+    /// Returns an error if a register is not a signal declared in this
+    /// architecture, or if it has no default value.
+    pub fn reset_assignments(
+        &self,
+        registers: &[ObjectDeclaration],
+    ) -> Result<Vec<AssignDeclaration>> {
+        registers
+            .iter()
+            .map(|register| {
+                let declared = self
+                    .find_object(register.identifier())
+                    .and_then(|handle| self.object(handle).ok())
+                    .is_some_and(|object| object.kind() == ObjectKind::Signal);
+                if declared {
+                    register.reset()
+                } else {
+                    Err(Error::InvalidArgument(format!(
+                        "Register {} is not a signal declared in architecture {}",
+                        register.identifier(),
+                        self.identifier
+                    )))
+                }
+            })
+            .collect()
+    }
+
     pub fn entity_ports(&self) -> Result<IndexMap<String, ObjectDeclaration>> {
-        let mut result = IndexMap::new();
-        for port in self.entity.ports() {
-            let objs = ObjectDeclaration::from_port(port, true)?;
-            for obj in objs {
-                result.insert(obj.identifier().to_string(), obj);
-            }
-        }
-        Ok(result)
+        Ok(self
+            .objects
+            .iter()
+            .filter(|(_, object)| object.kind() == ObjectKind::EntityPort)
+            .map(|(_, object)| (object.identifier().to_string(), object.clone()))
+            .collect())
+    }
+
+    /// Returns the generics of the entity, which can be used like constants
+    pub fn entity_generics(&self) -> Result<IndexMap<String, ObjectDeclaration>> {
+        Ok(self
+            .objects
+            .iter()
+            .filter(|(_, object)| object.kind() == ObjectKind::EntityGeneric)
+            .map(|(_, object)| (object.identifier().to_string(), object.clone()))
+            .collect())
     }
 }
 
@@ -158,6 +316,9 @@ impl<'a> Architecture<'a> {
 mod tests {
     use crate::generator::{common::convert::Packify, vhdl::Declare};
 
+    use self::assignment::{bitvec::BitVecValue, Assign, Assignment, StdLogicValue};
+    use self::object::ObjectType;
+
     use super::*;
 
     pub fn test_package() -> Package {
@@ -205,4 +366,166 @@ end behavioral;
             architecture.declare().unwrap()
         );
     }
+
+    #[test]
+    fn test_reset_assignments() -> Result<()> {
+        let package = test_package();
+        let mut architecture = Architecture::new_default(&package, Name::try_new("test")?)?;
+        let count = ObjectDeclaration::signal(
+            "count",
+            ObjectType::bit_vector(3, 0)?,
+            Some(BitVecValue::Others(StdLogicValue::Logic(false)).into()),
+        );
+        let valid = ObjectDeclaration::signal(
+            "valid",
+            ObjectType::Bit,
+            Some(StdLogicValue::Logic(false).into()),
+        );
+        let data = ObjectDeclaration::signal("data", ObjectType::bit_vector(7, 0)?, None);
+        for signal in &[count.clone(), valid.clone(), data.clone()] {
+            architecture.add_declaration(signal.clone())?;
+        }
+
+        let reset = architecture
+            .reset_assignments(&[count.clone(), valid])?
+            .iter()
+            .map(|assignment| assignment.declare("      ", ";\n"))
+            .collect::<Result<String>>()?;
+        assert_eq!(
+            reset,
+            "      count <= (others => '0');\n      valid <= '0';\n"
+        );
+        // Registers must have a default, and be declared in the architecture
+        assert!(architecture.reset_assignments(&[data]).is_err());
+        let other =
+            ObjectDeclaration::signal("other", ObjectType::Bit, Some(StdLogicValue::U.into()));
+        assert!(architecture.reset_assignments(&[count, other]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_store() -> Result<()> {
+        let package = test_package();
+        let mut architecture = Architecture::new_default(&package, Name::try_new("test")?)?;
+        let ready = architecture.find_object("a_up").unwrap();
+        assert_eq!(architecture.object(ready)?.kind(), ObjectKind::EntityPort);
+
+        let valid =
+            architecture.add_object(ObjectDeclaration::signal("valid", ObjectType::Bit, None))?;
+        assert_eq!(architecture.find_object("VALID"), Some(valid));
+        assert_eq!(*architecture.object(valid)?.mode(), ObjectMode::Undefined);
+        // Identifiers are unique, including those of the ports of the entity
+        for duplicate in &["valid", "Valid", "clk"] {
+            let object = ObjectDeclaration::signal(*duplicate, ObjectType::Bit, None);
+            assert!(architecture.add_declaration(object).is_err());
+        }
+
+        // Objects can be modified through their handles
+        let assignment = architecture
+            .object(valid)?
+            .assign(&StdLogicValue::Logic(true))?;
+        architecture.add_statement(assignment)?;
+        assert_eq!(*architecture.object(valid)?.mode(), ObjectMode::Assigned);
+        architecture
+            .object_mut(valid)?
+            .set_default(StdLogicValue::Logic(false).into())?;
+        let declarations = architecture.declarations()?;
+        assert_eq!(
+            "  signal valid : std_logic := '0';\n",
+            declarations[0].declare("  ", ";\n")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_entity_generics() -> Result<()> {
+        use crate::generator::common::{Component, Mode, Parameter, Port, Type};
+
+        let component = Component::new(
+            "generic_comp",
+            vec![Parameter {
+                name: "WIDTH".to_string(),
+                typ: Type::Natural,
+                default: Some(8),
+            }],
+            vec![Port::new("a", Mode::Out, Type::Natural)],
+            None,
+        );
+        let package = Package {
+            identifier: "test".to_string(),
+            doc: None,
+            components: vec![component],
+        };
+        let mut architecture = Architecture::new_default(&package, "generic_comp")?;
+        let width = architecture.entity_generics()?["WIDTH"].clone();
+        assert_eq!(width.kind(), ObjectKind::EntityGeneric);
+        assert!(!architecture.entity_ports()?.contains_key("WIDTH"));
+        // Generics can be used like constants, but not assigned
+        let a = architecture.entity_ports()?["a"].clone();
+        architecture.add_statement(a.assign(&width)?)?;
+        assert!(architecture
+            .add_declaration(ObjectDeclaration::signal("width", ObjectType::Natural, None))
+            .is_err());
+        assert!(architecture
+            .object_mut(architecture.find_object("WIDTH").unwrap())?
+            .set_default(StdLogicValue::Logic(true).into())
+            .is_err());
+
+        assert_eq!(
+            r#"library work;
+use work.test.all;
+
+entity generic_comp is
+  generic(
+    WIDTH : natural := 8
+  );
+  port(
+    a : out natural
+  );
+end entity generic_comp;
+
+architecture behavioral of generic_comp is
+begin
+  a <= WIDTH;
+end architecture behavioral;
+"#,
+            architecture.declare()?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_multiple_drivers() -> Result<()> {
+        let package = test_package();
+        let mut architecture = Architecture::new_default(&package, Name::try_new("test")?)?;
+        let data = ObjectDeclaration::signal("data", ObjectType::bit_vector(7, 0)?, None);
+        let source = ObjectDeclaration::signal("source", ObjectType::bit_vector(3, 0)?, None);
+        architecture.add_declaration(data.clone())?;
+        architecture.add_declaration(source.clone())?;
+
+        // Disjoint ranges of the same object can be driven separately
+        architecture
+            .add_statement(data.assign(&Assignment::from(source.clone()).to_downto(3, 0)?)?)?;
+        architecture
+            .add_statement(data.assign(&Assignment::from(source.clone()).to_downto(7, 4)?)?)?;
+        let overlapping = data.assign(&Assignment::from(source.clone()).to_downto(5, 2)?)?;
+        match architecture.add_statement(overlapping) {
+            Err(Error::InvalidArgument(message)) => assert_eq!(
+                message,
+                "data(5 downto 2) is driven by both the assignment to data(3 downto 0) and the assignment to data(5 downto 2)"
+            ),
+            _ => panic!("Expected an error for multiple drivers"),
+        }
+
+        // Fields of records are tracked separately
+        let a_up = architecture.entity_ports()?["a_up"].clone();
+        let ready = ObjectDeclaration::signal("ready", ObjectType::Bit, None);
+        architecture.add_declaration(ready.clone())?;
+        architecture
+            .add_statement(a_up.assign(&Assignment::from(ready.clone()).to_named("ready"))?)?;
+        assert!(architecture
+            .add_statement(a_up.assign(&Assignment::from(ready).to_named("ready"))?)
+            .is_err());
+        Ok(())
+    }
 }
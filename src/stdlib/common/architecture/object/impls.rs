@@ -0,0 +1,31 @@
+use crate::{
+    generator::vhdl::{ListUsings, Usings},
+    Name, Result,
+};
+
+use super::ObjectType;
+
+impl ListUsings for ObjectType {
+    fn list_usings(&self) -> Result<Usings> {
+        let mut usings = Usings::new_empty();
+        match self {
+            ObjectType::Bit
+            | ObjectType::Natural
+            | ObjectType::Positive
+            | ObjectType::Integer(_)
+            | ObjectType::Enum(_) => (),
+            ObjectType::Signed(_) | ObjectType::Unsigned(_) => {
+                usings.add_using(Name::try_new("ieee")?, "numeric_std.all");
+            }
+            ObjectType::Array(array) => {
+                usings.combine(&array.typ().list_usings()?);
+            }
+            ObjectType::Record(record) => {
+                for (_, typ) in record.fields() {
+                    usings.combine(&typ.list_usings()?);
+                }
+            }
+        }
+        Ok(usings)
+    }
+}
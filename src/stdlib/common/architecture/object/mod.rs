@@ -7,17 +7,22 @@ use indexmap::IndexMap;
 
 use crate::{
     generator::{
-        common::{Array, Record, Type},
+        common::{Array, Enumeration, Record, Type},
         vhdl::{Split, VHDLIdentifier},
     },
     stdlib::common::architecture::assignment::{
-        array_assignment::ArrayAssignment, DirectAssignment, ValueAssignment,
+        array_assignment::ArrayAssignment, bitvec::BitVecValue, DirectAssignment, NumericValue,
+        ValueAssignment,
     },
     Error, Identify, Result
 };
 
-use super::assignment::{Assignment, AssignmentKind, FieldSelection, RangeConstraint};
+use super::assignment::{
+    flatten::FlatLength, Assignment, AssignmentKind, FieldSelection, FlatConversion,
+    RangeConstraint,
+};
 
+pub mod impls;
 pub mod object_from;
 
 /// Types of VHDL objects, possibly referring to fields
@@ -29,6 +34,14 @@ pub enum ObjectType {
     Natural,
     /// A positive object which is an integer from 1 to integer'high
     Positive,
+    /// An integer object constrained to a range
+    Integer(IntegerObject),
+    /// A signed number, a vector of bits interpreted as a two's complement integer
+    Signed(NumericObject),
+    /// An unsigned number, a vector of bits interpreted as a natural number
+    Unsigned(NumericObject),
+    /// An enumeration object, which takes one of a set of literals
+    Enum(EnumObject),
     /// An array of fields, covers both conventional arrays, as well as bit vectors
     Array(ArrayObject),
     /// A record object, consisting of named fields
@@ -41,6 +54,21 @@ impl fmt::Display for ObjectType {
             ObjectType::Bit => write!(f, "Bit"),
             ObjectType::Natural => write!(f, "Natural"),
             ObjectType::Positive => write!(f, "Positive"),
+            ObjectType::Integer(integer) => {
+                write!(f, "Integer ({} to {})", integer.low(), integer.high())
+            }
+            ObjectType::Signed(signed) => {
+                write!(f, "Signed ({} downto {})", signed.high(), signed.low())
+            }
+            ObjectType::Unsigned(unsigned) => {
+                write!(f, "Unsigned ({} downto {})", unsigned.high(), unsigned.low())
+            }
+            ObjectType::Enum(enumeration) => write!(
+                f,
+                "Enum (type name: {}) with literals: ( {} )",
+                enumeration.type_name(),
+                enumeration.literals().join(", ")
+            ),
             ObjectType::Array(array) => write!(
                 f,
                 "Array ({} to {}) containing {}",
@@ -77,10 +105,54 @@ impl ObjectType {
             ObjectType::Positive => Err(Error::InvalidTarget(
                 "Cannot select a field on a Positive".to_string(),
             )),
+            ObjectType::Integer(_) => Err(Error::InvalidTarget(
+                "Cannot select a field on an Integer".to_string(),
+            )),
+            ObjectType::Enum(_) => Err(Error::InvalidTarget(
+                "Cannot select a field on an Enum".to_string(),
+            )),
+            ObjectType::Signed(number) | ObjectType::Unsigned(number) => match field {
+                FieldSelection::Range(range) if range.is_index() => {
+                    let index = range.high();
+                    if index <= number.high() && index >= number.low() {
+                        Ok(ObjectType::Bit)
+                    } else {
+                        Err(Error::InvalidArgument(format!(
+                            "Cannot select index {} on {} with high: {}, low: {}",
+                            index,
+                            self,
+                            number.high(),
+                            number.low()
+                        )))
+                    }
+                }
+                FieldSelection::Range(range) => {
+                    if range.is_between(number.high(), number.low())? {
+                        if let ObjectType::Signed(_) = self {
+                            ObjectType::signed(range.high(), range.low())
+                        } else {
+                            ObjectType::unsigned(range.high(), range.low())
+                        }
+                    } else {
+                        Err(Error::InvalidArgument(format!(
+                            "Cannot select {} on {} with high: {}, low: {}",
+                            range,
+                            self,
+                            number.high(),
+                            number.low()
+                        )))
+                    }
+                }
+                FieldSelection::Name(_) => Err(Error::InvalidTarget(format!(
+                    "Cannot select a named field on {}",
+                    self
+                ))),
+            },
             ObjectType::Array(array) => match field {
                 FieldSelection::Range(range) => {
-                    if let RangeConstraint::Index(index) = range {
-                        if *index <= array.high() && *index >= array.low() {
+                    if range.is_index() {
+                        let index = range.high();
+                        if index <= array.high() && index >= array.low() {
                             Ok(array.typ().clone())
                         } else {
                             Err(Error::InvalidArgument(format!(
@@ -92,6 +164,9 @@ impl ObjectType {
                         }
                     } else {
                         if range.is_between(array.high(), array.low())? {
+                            if array.is_bitvector() {
+                                return ObjectType::bit_vector(range.high(), range.low());
+                            }
                             Ok(ObjectType::array(
                                 range.high(),
                                 range.low(),
@@ -153,6 +228,42 @@ impl ObjectType {
         Ok(ArrayObject::bit_vector(high, low)?.into())
     }
 
+    /// Create an integer object constrained to the range \[low\] to \[high\]
+    pub fn integer(low: i32, high: i32) -> Result<ObjectType> {
+        Ok(ObjectType::Integer(IntegerObject::new(low, high)?))
+    }
+
+    /// Create a signed number object
+    pub fn signed(high: i32, low: i32) -> Result<ObjectType> {
+        Ok(ObjectType::Signed(NumericObject::new("signed", high, low)?))
+    }
+
+    /// Create an unsigned number object
+    pub fn unsigned(high: i32, low: i32) -> Result<ObjectType> {
+        Ok(ObjectType::Unsigned(NumericObject::new("unsigned", high, low)?))
+    }
+
+    /// Returns this array or number with its bounds declared by expressions,
+    /// e.g. in terms of generics, rather than by their values
+    ///
+    /// The values of the bounds are still used to verify assignments, and
+    /// should match the values of the expressions.
+    pub fn with_bounds(
+        self,
+        high: impl Into<String>,
+        low: impl Into<String>,
+    ) -> Result<ObjectType> {
+        match self {
+            ObjectType::Array(array) => Ok(ObjectType::Array(array.with_bounds(high, low))),
+            ObjectType::Signed(number) => Ok(ObjectType::Signed(number.with_bounds(high, low))),
+            ObjectType::Unsigned(number) => Ok(ObjectType::Unsigned(number.with_bounds(high, low))),
+            _ => Err(Error::InvalidArgument(format!(
+                "Cannot declare the bounds of {} by expressions",
+                self
+            ))),
+        }
+    }
+
     pub fn can_assign_type(&self, typ: &ObjectType) -> Result<()> {
         match self {
             ObjectType::Bit => {
@@ -172,6 +283,8 @@ impl ObjectType {
                 // Positive can be assigned to natural but not the other way around
                 else if let ObjectType::Positive = typ {
                     Ok(())
+                } else if matches!(typ, ObjectType::Integer(integer) if integer.low() >= 0) {
+                    Ok(())
                 } else {
                     Err(Error::InvalidTarget(format!(
                         "Cannot assign {} to Natural",
@@ -182,6 +295,8 @@ impl ObjectType {
             ObjectType::Positive => {
                 if let ObjectType::Positive = typ {
                     Ok(())
+                } else if matches!(typ, ObjectType::Integer(integer) if integer.low() >= 1) {
+                    Ok(())
                 } else {
                     // Natural can not be assigned to positive because positive does not include 0
                     Err(Error::InvalidTarget(format!(
@@ -190,6 +305,52 @@ impl ObjectType {
                     )))
                 }
             }
+            ObjectType::Integer(_) => match typ {
+                // Values are checked against the range of the integer during simulation
+                ObjectType::Integer(_) | ObjectType::Natural | ObjectType::Positive => Ok(()),
+                _ => Err(Error::InvalidTarget(format!(
+                    "Cannot assign {} to {}",
+                    typ, self
+                ))),
+            },
+            ObjectType::Signed(to_number) | ObjectType::Unsigned(to_number) => match (self, typ) {
+                (ObjectType::Signed(_), ObjectType::Signed(from_number))
+                | (ObjectType::Unsigned(_), ObjectType::Unsigned(from_number)) => {
+                    if from_number.width() == to_number.width() {
+                        Ok(())
+                    } else {
+                        Err(Error::InvalidTarget(format!(
+                            "Cannot assign {} with width {} to {} with width {}",
+                            typ,
+                            from_number.width(),
+                            self,
+                            to_number.width(),
+                        )))
+                    }
+                }
+                _ => Err(Error::InvalidTarget(format!(
+                    "Cannot assign {} to {}",
+                    typ, self
+                ))),
+            },
+            ObjectType::Enum(to_enum) => {
+                if let ObjectType::Enum(from_enum) = typ {
+                    if from_enum.type_name() == to_enum.type_name() {
+                        Ok(())
+                    } else {
+                        Err(Error::InvalidTarget(format!(
+                            "Cannot assign enum type {} to enum type {}",
+                            from_enum.type_name(),
+                            to_enum.type_name(),
+                        )))
+                    }
+                } else {
+                    Err(Error::InvalidTarget(format!(
+                        "Cannot assign {} to {}",
+                        typ, self
+                    )))
+                }
+            }
             ObjectType::Array(to_array) => {
                 if let ObjectType::Array(from_array) = typ {
                     if from_array.width() == to_array.width() {
@@ -267,31 +428,99 @@ impl ObjectType {
                     to_object
                 ))),
             },
+            AssignmentKind::Conversion(conversion) => {
+                let from_object = conversion.object().typ()?;
+                let (complex, flat) = match conversion {
+                    FlatConversion::ToFlat(_) => (&from_object, &to_object),
+                    FlatConversion::FromFlat(_) => (&to_object, &from_object),
+                };
+                if complex.is_flat() || !complex.has_flat_length() {
+                    Err(Error::InvalidArgument(format!(
+                        "{} has no flattened representation to convert to or from",
+                        complex
+                    )))
+                } else if !matches!(flat, ObjectType::Array(array) if array.is_bitvector()) {
+                    Err(Error::InvalidArgument(format!(
+                        "Cannot convert {} to or from {}, which is not a bit vector",
+                        complex, flat
+                    )))
+                } else if complex.flat_length()? != flat.flat_length()? {
+                    Err(Error::InvalidArgument(format!(
+                        "Cannot convert {} to or from {}, mismatched length ({} and {})",
+                        complex,
+                        flat,
+                        complex.flat_length()?,
+                        flat.flat_length()?
+                    )))
+                } else {
+                    Ok(())
+                }
+            }
             AssignmentKind::Direct(direct) => match direct {
                 DirectAssignment::Value(value) => match value {
                     ValueAssignment::Bit(_) => match to_object {
                         ObjectType::Bit => Ok(()),
-                        ObjectType::Array(_) | ObjectType::Record(_) | ObjectType::Natural | ObjectType::Positive => Err(Error::InvalidTarget(
+                        ObjectType::Array(_) | ObjectType::Record(_) | ObjectType::Natural | ObjectType::Positive
+                        | ObjectType::Integer(_) | ObjectType::Signed(_) | ObjectType::Unsigned(_) | ObjectType::Enum(_) => Err(Error::InvalidTarget(
                             format!("Cannot assign Bit to {}", to_object),
                         )),
                     },
-                    ValueAssignment::Integer(integer) => match to_object {
+                    ValueAssignment::Integer(integer) => match &to_object {
                         //TODO: check if integer is in natural and positive range
                         ObjectType::Natural => Ok(()),
                         ObjectType::Positive => if *integer == 0 { Err(Error::BackEndError("Cannot assign zero to Positive".to_string())) } else { Ok(()) },
-                        ObjectType::Bit | ObjectType::Array(_) | ObjectType::Record(_) => Err(Error::InvalidTarget(
+                        ObjectType::Integer(range) => if range.contains(i64::from(*integer)) { Ok(()) } else {
+                            Err(Error::InvalidArgument(format!("Cannot assign {} to {}", integer, to_object)))
+                        },
+                        ObjectType::Bit | ObjectType::Array(_) | ObjectType::Record(_) | ObjectType::Signed(_) | ObjectType::Unsigned(_) | ObjectType::Enum(_) => Err(Error::InvalidTarget(
                             format!("Cannot assign Integer to {}", to_object),
                         )),
                     }
-                    ValueAssignment::BitVec(bitvec) => match to_object {
+                    ValueAssignment::BitVec(bitvec) => match &to_object {
                         ObjectType::Array(array) if array.is_bitvector() => {
-                            bitvec.validate_width(array.width())
+                            bitvec.validate_width(array.width().into())
                         }
+                        // Literals can be assigned to numbers directly, integers require a numeric value
+                        ObjectType::Signed(number) | ObjectType::Unsigned(number) => match bitvec {
+                            BitVecValue::Others(_) | BitVecValue::Full(_) | BitVecValue::Hex(_) | BitVecValue::Octal(_) => bitvec.validate_width(number.width().into()),
+                            BitVecValue::Unsigned(_) | BitVecValue::Signed(_) => Err(Error::InvalidTarget(format!(
+                                "Cannot assign an integer Bit Vector to {}, use a numeric value instead",
+                                to_object
+                            ))),
+                        },
                         _ => Err(Error::InvalidTarget(format!(
                             "Cannot assign Bit Vector to {}",
                             to_object
                         ))),
                     },
+                    ValueAssignment::Numeric(numeric) => match (numeric, &to_object) {
+                        (NumericValue::Signed(value), ObjectType::Signed(number)) => {
+                            BitVecValue::Signed(*value).validate_width(number.width().into())
+                        }
+                        (NumericValue::Unsigned(value), ObjectType::Unsigned(number)) => {
+                            BitVecValue::Unsigned(*value).validate_width(number.width().into())
+                        }
+                        _ => Err(Error::InvalidTarget(format!(
+                            "Cannot assign {} to {}",
+                            numeric, to_object
+                        ))),
+                    },
+                    ValueAssignment::Enum(literal) => match &to_object {
+                        ObjectType::Enum(enumeration) => {
+                            if enumeration.has_literal(literal) {
+                                Ok(())
+                            } else {
+                                Err(Error::InvalidArgument(format!(
+                                    "Literal {} does not exist on {}",
+                                    literal, to_object
+                                )))
+                            }
+                        }
+                        _ => Err(Error::InvalidTarget(format!(
+                            "Cannot assign literal {} to {}",
+                            literal, to_object
+                        ))),
+                    },
                 },
                 DirectAssignment::FullRecord(record) => {
                     if let ObjectType::Record(to_record) = &to_object {
@@ -311,11 +540,49 @@ impl ObjectType {
                         )))
                     }
                 }
+                DirectAssignment::PartialRecord { direct, others } => {
+                    if let ObjectType::Record(to_record) = &to_object {
+                        for (field, value) in direct {
+                            let to_field = to_object.get_field(&FieldSelection::name(field))?;
+                            to_field.can_assign(&Assignment::from(value.clone()))?;
+                        }
+                        let remaining = to_record
+                            .fields()
+                            .iter()
+                            .filter(|(field, _)| !direct.contains_key(*field))
+                            .map(|(_, typ)| typ)
+                            .collect::<Vec<_>>();
+                        if remaining.is_empty() {
+                            return Err(Error::InvalidArgument("Partial record assignment contains an 'others' field, but already assigns all fields directly.".to_string()));
+                        }
+                        // All fields associated with 'others' must be of the same type
+                        if remaining.iter().any(|typ| typ.type_name() != remaining[0].type_name()) {
+                            return Err(Error::InvalidArgument(format!("Partial record assignment: the fields not assigned directly to {} are not of the same type, and can not be assigned by 'others'", to_object)));
+                        }
+                        // The value can not refer to the field it is assigned to
+                        if let AssignmentKind::Direct(DirectAssignment::Value(
+                            ValueAssignment::BitVec(BitVecValue::Unsigned(_))
+                            | ValueAssignment::BitVec(BitVecValue::Signed(_))
+                            | ValueAssignment::Numeric(_),
+                        )) = others.as_ref()
+                        {
+                            return Err(Error::InvalidArgument(
+                                "Partial record assignment: integer values can not be assigned by 'others', as their width depends on the field".to_string(),
+                            ));
+                        }
+                        remaining[0].can_assign(&Assignment::from(others.as_ref().clone()))
+                    } else {
+                        Err(Error::InvalidTarget(format!(
+                            "Cannot perform partial Record assignment to {}",
+                            to_object
+                        )))
+                    }
+                }
                 DirectAssignment::FullArray(array) => {
                     if let ObjectType::Array(to_array) = &to_object {
                         match array {
                             ArrayAssignment::Direct(direct) => {
-                                if to_array.width() == direct.len().try_into().unwrap() {
+                                if to_array.width() == TryInto::<u32>::try_into(direct.len()).unwrap() {
                                     for value in direct {
                                         to_array
                                             .typ()
@@ -383,6 +650,9 @@ impl ObjectType {
             ObjectType::Bit => "std_logic",
             ObjectType::Natural => "natural",
             ObjectType::Positive => "positive",
+            ObjectType::Integer(integer) => integer.type_name(),
+            ObjectType::Signed(number) | ObjectType::Unsigned(number) => number.type_name(),
+            ObjectType::Enum(enumeration) => enumeration.type_name(),
             ObjectType::Array(array) => array.type_name(),
             ObjectType::Record(record) => record.type_name(),
         }
@@ -397,12 +667,26 @@ impl ObjectType {
         }
     }
 
+    /// Returns true if the object has a fixed flattened representation, i.e.
+    /// it consists only of bits, bit vectors and signed or unsigned numbers
+    pub fn has_flat_length(&self) -> bool {
+        match self {
+            ObjectType::Bit | ObjectType::Signed(_) | ObjectType::Unsigned(_) => true,
+            ObjectType::Array(arr) => arr.typ().has_flat_length(),
+            ObjectType::Record(rec) => rec.fields().values().all(|typ| typ.has_flat_length()),
+            ObjectType::Natural
+            | ObjectType::Positive
+            | ObjectType::Integer(_)
+            | ObjectType::Enum(_) => false,
+        }
+    }
+
     pub fn try_from_splittable(typ: Type) -> Result<(Option<ObjectType>, Option<ObjectType>)> {
         if typ.has_reversed() {
             let (dn, up) = typ.split();
             let dn_obj = if let Some(dn_t) = dn {
                 Some(match dn_t {
-                    Type::Bit | Type::Natural | Type::Positive | Type::BitVec { width: _ } => unreachable!(),
+                    Type::Bit | Type::Natural | Type::Positive | Type::BitVec { width: _ } | Type::Enum(_) => unreachable!(),
                     Type::Record(rec) => Type::Record(rec.append_name_nested("dn")).try_into()?,
                     Type::Union(rec) => Type::Union(rec.append_name_nested("dn")).try_into()?,
                     Type::Array(arr) => Type::Array(arr.append_name_nested("dn")).try_into()?,
@@ -413,7 +697,7 @@ impl ObjectType {
 
             let up_obj = if let Some(up_t) = up {
                 Some(match up_t {
-                    Type::Bit | Type::Natural | Type::Positive | Type::BitVec { width: _ } => unreachable!(),
+                    Type::Bit | Type::Natural | Type::Positive | Type::BitVec { width: _ } | Type::Enum(_) => unreachable!(),
                     Type::Record(rec) => Type::Record(rec.append_name_nested("up")).try_into()?,
                     Type::Union(rec) => Type::Union(rec.append_name_nested("up")).try_into()?,
                     Type::Array(arr) => Type::Array(arr.append_name_nested("up")).try_into()?,
@@ -453,6 +737,7 @@ impl TryFrom<Type> for ObjectType {
                 )))
             }
             Type::Array(array) => Ok(ObjectType::Array(ArrayObject::try_from(array)?)),
+            Type::Enum(enumeration) => Ok(ObjectType::Enum(EnumObject::try_from(enumeration)?)),
         }
     }
 
@@ -536,6 +821,8 @@ pub struct ArrayObject {
     low: i32,
     typ: Box<ObjectType>,
     type_name: String,
+    /// Expressions of the high and low bounds, if they are not declared by their values
+    bounds: Option<(String, String)>,
 }
 
 impl ArrayObject {
@@ -567,6 +854,7 @@ impl ArrayObject {
                 low,
                 typ: Box::new(object),
                 type_name: type_name.into(),
+                bounds: None,
             })
         }
     }
@@ -597,6 +885,36 @@ impl ArrayObject {
     pub fn type_name(&self) -> &str {
         self.type_name.as_str()
     }
+
+    /// Returns this array with its bounds declared by expressions, e.g. in
+    /// terms of generics, rather than by their values
+    ///
+    /// Bit vectors are declared as `std_logic_vector([high] downto [low])`.
+    /// Other arrays are declared as their type constrained by the bounds, so
+    /// their type must be an unconstrained array type.
+    pub fn with_bounds(mut self, high: impl Into<String>, low: impl Into<String>) -> Self {
+        let (high, low) = (high.into(), low.into());
+        let type_name = if self.is_bitvector() {
+            "std_logic_vector".to_string()
+        } else if let Some((prev_high, prev_low)) = &self.bounds {
+            self.type_name
+                .trim_end_matches(&format!("({} downto {})", prev_high, prev_low))
+                .to_string()
+        } else {
+            self.type_name.clone()
+        };
+        self.type_name = format!("{}({} downto {})", type_name, high, low);
+        self.bounds = Some((high, low));
+        self
+    }
+
+    /// Returns the expressions of the high and low bounds, if they are not
+    /// declared by their values
+    pub fn bounds(&self) -> Option<(&str, &str)> {
+        self.bounds
+            .as_ref()
+            .map(|(high, low)| (high.as_str(), low.as_str()))
+    }
 }
 
 impl TryFrom<Array> for ArrayObject {
@@ -611,3 +929,150 @@ impl TryFrom<Array> for ArrayObject {
         )?)
     }
 }
+
+/// An integer object, constrained to a range
+#[derive(Debug, Clone)]
+pub struct IntegerObject {
+    low: i32,
+    high: i32,
+    type_name: String,
+}
+
+impl IntegerObject {
+    /// Create an integer object constrained to the range \[low\] to \[high\]
+    pub fn new(low: i32, high: i32) -> Result<IntegerObject> {
+        if low > high {
+            Err(Error::InvalidArgument(format!(
+                "{} > {}! Low must be lower than high",
+                low, high
+            )))
+        } else {
+            Ok(IntegerObject {
+                low,
+                high,
+                type_name: format!("integer range {} to {}", low, high),
+            })
+        }
+    }
+
+    pub fn low(&self) -> i32 {
+        self.low
+    }
+
+    pub fn high(&self) -> i32 {
+        self.high
+    }
+
+    /// Returns true if the value is within the range of this integer
+    pub fn contains(&self, value: i64) -> bool {
+        value >= i64::from(self.low) && value <= i64::from(self.high)
+    }
+
+    pub fn type_name(&self) -> &str {
+        self.type_name.as_str()
+    }
+}
+
+/// A signed or unsigned number object, a vector of bits interpreted as an integer
+///
+/// Whether the number is signed is determined by the ObjectType containing it.
+#[derive(Debug, Clone)]
+pub struct NumericObject {
+    high: i32,
+    low: i32,
+    type_name: String,
+    /// Expressions of the high and low bounds, if they are not declared by their values
+    bounds: Option<(String, String)>,
+}
+
+impl NumericObject {
+    fn new(numeric_type: &str, high: i32, low: i32) -> Result<NumericObject> {
+        if low > high {
+            Err(Error::InvalidArgument(format!(
+                "{} > {}! Low must be lower than high",
+                low, high
+            )))
+        } else {
+            Ok(NumericObject {
+                high,
+                low,
+                type_name: format!("{}({} downto {})", numeric_type, high, low),
+                bounds: None,
+            })
+        }
+    }
+
+    pub fn high(&self) -> i32 {
+        self.high
+    }
+
+    pub fn low(&self) -> i32 {
+        self.low
+    }
+
+    pub fn width(&self) -> u32 {
+        (1 + self.high - self.low).try_into().unwrap()
+    }
+
+    pub fn type_name(&self) -> &str {
+        self.type_name.as_str()
+    }
+
+    /// Returns this number with its bounds declared by expressions, e.g. in
+    /// terms of generics, rather than by their values
+    pub fn with_bounds(mut self, high: impl Into<String>, low: impl Into<String>) -> Self {
+        let (high, low) = (high.into(), low.into());
+        let numeric_type = self.type_name.split('(').next().unwrap_or_default();
+        self.type_name = format!("{}({} downto {})", numeric_type, high, low);
+        self.bounds = Some((high, low));
+        self
+    }
+
+    /// Returns the expressions of the high and low bounds, if they are not
+    /// declared by their values
+    pub fn bounds(&self) -> Option<(&str, &str)> {
+        self.bounds
+            .as_ref()
+            .map(|(high, low)| (high.as_str(), low.as_str()))
+    }
+}
+
+/// An enumeration object, which takes one of a set of literals
+#[derive(Debug, Clone)]
+pub struct EnumObject {
+    type_name: String,
+    literals: Vec<String>,
+}
+
+impl EnumObject {
+    pub fn new(type_name: impl Into<String>, literals: Vec<String>) -> EnumObject {
+        EnumObject {
+            type_name: type_name.into(),
+            literals,
+        }
+    }
+
+    pub fn type_name(&self) -> &str {
+        self.type_name.as_str()
+    }
+
+    pub fn literals(&self) -> &Vec<String> {
+        &self.literals
+    }
+
+    /// Returns true if the literal is one of the literals of this enumeration
+    pub fn has_literal(&self, literal: &str) -> bool {
+        self.literals.iter().any(|l| l == literal)
+    }
+}
+
+impl TryFrom<Enumeration> for EnumObject {
+    type Error = Error;
+
+    fn try_from(value: Enumeration) -> Result<Self> {
+        Ok(EnumObject::new(
+            value.vhdl_identifier()?,
+            value.literals().to_vec(),
+        ))
+    }
+}
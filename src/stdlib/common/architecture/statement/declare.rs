@@ -1,6 +1,6 @@
 use crate::{stdlib::common::architecture::ArchitectureDeclare, Error, Result};
 
-use super::{PortMapping, Statement};
+use super::{Block, PortMapping, Statement};
 
 impl ArchitectureDeclare for PortMapping {
     fn declare(&self, pre: &str, post: &str) -> Result<String> {
@@ -39,6 +39,8 @@ impl ArchitectureDeclare for PortMapping {
         for (port, _) in self.ports() {
             if let Some(port_assign) = self.mappings().get(port) {
                 port_maps.push(port_assign.declare(&format!("{}    ", pre), "")?);
+            } else if self.open_ports().contains(port) {
+                port_maps.push(format!("{}    {} => open", pre, port));
             } else {
                 return Err(Error::BackEndError(format!(
                     "Error while declaring port mapping, port {} is not assigned",
@@ -53,11 +55,29 @@ impl ArchitectureDeclare for PortMapping {
     }
 }
 
+impl ArchitectureDeclare for Block {
+    fn declare(&self, pre: &str, post: &str) -> Result<String> {
+        let inner = format!("{}  ", pre);
+        let mut result = format!("{}{}: block\n", pre, self.label());
+        for declaration in self.declarations() {
+            result.push_str(&declaration.declare(&inner, ";\n")?);
+        }
+        result.push_str(&format!("{}begin\n", pre));
+        for statement in self.statements() {
+            result.push_str(&statement.declare(&inner, ";\n")?);
+        }
+        result.push_str(&format!("{}end block {}", pre, self.label()));
+        result.push_str(post);
+        Ok(result)
+    }
+}
+
 impl ArchitectureDeclare for Statement {
     fn declare(&self, pre: &str, post: &str) -> Result<String> {
         match self {
             Statement::Assignment(assignment) => assignment.declare(pre, post),
             Statement::PortMapping(portmapping) => portmapping.declare(pre, post),
+            Statement::Block(block) => block.declare(pre, post),
         }
     }
 }
@@ -67,13 +87,19 @@ mod tests {
 
     use super::*;
     use crate::{
-        generator::common::test::{
-            records::{rec_rev, rec_rev_nested},
-            test_comp,
+        generator::common::{
+            test::{
+                records::{rec_rev, rec_rev_nested},
+                test_comp,
+            },
+            Component, Mode, Parameter, Port, Type,
         },
         stdlib::common::architecture::{
-            assignment::AssignmentKind, declaration::ObjectDeclaration, object::ObjectType,
+            assignment::{Assign, AssignmentKind, StdLogicValue},
+            declaration::ObjectDeclaration,
+            object::ObjectType,
         },
+        Natural,
     };
 
     #[test]
@@ -146,4 +172,131 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_generic_portmapping() -> Result<()> {
+        let comp = Component::new(
+            "generic_comp",
+            vec![
+                Parameter {
+                    name: "WIDTH".to_string(),
+                    typ: Type::Natural,
+                    default: None,
+                },
+                Parameter {
+                    name: "DEPTH".to_string(),
+                    typ: Type::Natural,
+                    default: Some(4),
+                },
+            ],
+            vec![Port::new("a", Mode::In, Type::Bit)],
+            None,
+        );
+        let a = ObjectDeclaration::signal("a_sig", ObjectType::Bit, None);
+        let mut pm = PortMapping::from_component(&comp, "some_label")?;
+        pm.map_port("a", &a)?;
+        let err = pm.clone().finish().unwrap_err();
+        assert!(err.to_string().contains("not mapped: WIDTH"));
+
+        let width =
+            ObjectDeclaration::constant("WIDTH_CONST", ObjectType::Natural, Natural::from(8u32));
+        let mapped = pm.map_generic("WIDTH", &width)?.clone().finish()?;
+        assert_eq!(
+            r#"some_label: generic_comp
+  generic map(
+    WIDTH => WIDTH_CONST,
+    DEPTH => 4
+  )
+  port map(
+    a => a_sig
+  );
+"#,
+            mapped.declare("", ";\n")?
+        );
+        assert_eq!(
+            "    DEPTH => 8",
+            pm.map_generic("DEPTH", &Natural::from(8u32))?
+                .generic_mappings()["DEPTH"]
+                .declare("    ", "")?
+        );
+
+        // Generics can only be mapped to constants and values of their type
+        let err = pm.map_generic("WIDTH", &a).unwrap_err();
+        assert_eq!(err.notes(), &["while mapping generic WIDTH".to_string()]);
+        let signal = ObjectDeclaration::signal("width_sig", ObjectType::Natural, None);
+        assert!(pm.map_generic("WIDTH", &signal).is_err());
+        let bit = ObjectDeclaration::constant("BIT_CONST", ObjectType::Bit, StdLogicValue::U);
+        assert!(pm.map_generic("WIDTH", &bit).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_portmapping_validation() -> Result<()> {
+        let comp = Component::new(
+            "comp",
+            vec![],
+            vec![
+                Port::new("a", Mode::In, Type::Bit),
+                Port::new("b", Mode::Out, Type::Bit),
+                Port::new("c", Mode::Out, Type::Bit),
+            ],
+            None,
+        );
+        let mut pm = PortMapping::from_component(&comp, "some_label")?;
+        pm.map_port("a", &StdLogicValue::Logic(true))?;
+        let err = pm.clone().finish().unwrap_err();
+        assert!(err.to_string().contains("Ports of some_label are not mapped: b, c"));
+
+        // Out ports can only drive signals and out ports of the entity
+        let b_in = ObjectDeclaration::entity_port("b_in", ObjectType::Bit, Mode::In);
+        assert!(pm.map_port("b", &b_in).is_err());
+        let b_const = ObjectDeclaration::constant("B_CONST", ObjectType::Bit, StdLogicValue::U);
+        assert!(pm.map_port("b", &b_const).is_err());
+        let err = pm.map_port("b", &StdLogicValue::Logic(false)).unwrap_err();
+        assert_eq!(err.notes(), &["while mapping out port b".to_string()]);
+        let b_out = ObjectDeclaration::entity_port("b_out", ObjectType::Bit, Mode::Out);
+        pm.map_port("b", &b_out)?;
+
+        // Only out ports can be left open
+        assert!(pm.map_open("a").is_err());
+        let mapped = pm.map_open("c")?.clone().finish()?;
+        assert_eq!(
+            r#"some_label: comp
+  port map(
+    a => '1',
+    b => b_out,
+    c => open
+  );
+"#,
+            mapped.declare("", ";\n")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_declare() -> Result<()> {
+        let valid = ObjectDeclaration::signal("a_valid", ObjectType::Bit, None);
+        let ready = ObjectDeclaration::signal("a_ready", ObjectType::Bit, None);
+        let inner = Block::new("a_handshake").with_statement(ready.assign(&valid)?);
+        let block = Block::new("a_stream")
+            .with_declaration(valid.clone())
+            .with_declaration(ready.clone())
+            .with_statement(valid.assign(&StdLogicValue::Logic(true))?)
+            .with_statement(inner);
+        assert_eq!(
+            r#"  a_stream: block
+    signal a_valid : std_logic;
+    signal a_ready : std_logic;
+  begin
+    a_valid <= '1';
+    a_handshake: block
+    begin
+      a_ready <= a_valid;
+    end block a_handshake;
+  end block a_stream;
+"#,
+            Statement::from(block).declare("  ", ";\n")?
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,33 @@
+use crate::generator::vhdl::{ListUsings, Usings};
+use crate::Result;
+
+use super::{Block, Statement};
+
+impl ListUsings for Statement {
+    fn list_usings(&self) -> Result<Usings> {
+        match self {
+            Statement::Assignment(assignment) => assignment.list_usings(),
+            Statement::PortMapping(pm) => {
+                let mut usings = Usings::new_empty();
+                for (_, object) in pm.ports() {
+                    usings.combine(&object.list_usings()?);
+                }
+                Ok(usings)
+            }
+            Statement::Block(block) => block.list_usings(),
+        }
+    }
+}
+
+impl ListUsings for Block {
+    fn list_usings(&self) -> Result<Usings> {
+        let mut usings = Usings::new_empty();
+        for declaration in self.declarations() {
+            usings.combine(&declaration.list_usings()?);
+        }
+        for statement in self.statements() {
+            usings.combine(&statement.list_usings()?);
+        }
+        Ok(usings)
+    }
+}
@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use std::convert::TryInto;
 
 use indexmap::IndexMap;
@@ -6,20 +5,85 @@ use indexmap::IndexMap;
 use crate::{
     generator::common::{Component, Mode},
     stdlib::common::architecture::assignment::Assign, Error,
-    Identify, Result,
+    Identify, Result, ResultExt,
 };
 
 use super::{
-    assignment::{AssignDeclaration, Assignment},
-    declaration::ObjectDeclaration,
+    assignment::{
+        array_assignment::ArrayAssignment, AssignDeclaration, Assignment, AssignmentKind,
+        DirectAssignment, FieldSelection,
+    },
+    declaration::{ArchitectureDeclaration, ObjectDeclaration, ObjectKind, ObjectMode},
 };
 
 pub mod declare;
+pub mod impls;
 
 #[derive(Debug, Clone)]
 pub enum Statement {
     Assignment(AssignDeclaration),
     PortMapping(PortMapping),
+    /// A block, with its own declarations and statements
+    Block(Block),
+}
+
+impl Statement {
+    /// Returns the objects driven by this statement, with the (nested) fields
+    /// of those objects it drives.
+    ///
+    /// Objects declared within a block are not visible outside of it, and are
+    /// therefore not included.
+    pub fn drivers(&self) -> Vec<(&ObjectDeclaration, &[FieldSelection])> {
+        match self {
+            Statement::Assignment(assignment) => vec![(
+                assignment.object(),
+                assignment.assignment().to_field().as_slice(),
+            )],
+            // The objects mapped to the "out" ports of the component
+            Statement::PortMapping(portmapping) => portmapping
+                .mappings()
+                .iter()
+                .filter(|(port, _)| {
+                    portmapping.ports()[port.as_str()].mode() == &ObjectMode::Assigned
+                })
+                .filter_map(|(_, mapping)| match mapping.assignment().kind() {
+                    AssignmentKind::Object(object) => {
+                        Some((object.object(), object.from_field().as_slice()))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            Statement::Block(block) => {
+                let local = |object: &ObjectDeclaration| {
+                    block.declarations().iter().any(|declaration| {
+                        matches!(declaration, ArchitectureDeclaration::Object(local)
+                            if local.identifier().eq_ignore_ascii_case(object.identifier()))
+                    })
+                };
+                block
+                    .statements()
+                    .iter()
+                    .flat_map(|statement| statement.drivers())
+                    .filter(|(object, _)| !local(object))
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns a short description of this statement, e.g. for errors
+    pub fn describe(&self) -> String {
+        match self {
+            Statement::Assignment(assignment) => {
+                format!("assignment to {}", assignment.object_string())
+            }
+            Statement::PortMapping(portmapping) => format!(
+                "port mapping {} of {}",
+                portmapping.label(),
+                portmapping.component_name()
+            ),
+            Statement::Block(block) => format!("block {}", block.label()),
+        }
+    }
 }
 
 impl From<AssignDeclaration> for Statement {
@@ -34,6 +98,69 @@ impl From<PortMapping> for Statement {
     }
 }
 
+impl From<Block> for Statement {
+    fn from(block: Block) -> Self {
+        Statement::Block(block)
+    }
+}
+
+/// A block statement, which partitions the statement part of an architecture
+/// into labeled sections. Declarations within a block are only visible to the
+/// statements of that block.
+#[derive(Debug, Clone)]
+pub struct Block {
+    label: String,
+    /// The declaration part of the block
+    declarations: Vec<ArchitectureDeclaration>,
+    /// The statement part of the block
+    statements: Vec<Statement>,
+}
+
+impl Block {
+    /// Create an empty block
+    pub fn new(label: impl Into<String>) -> Block {
+        Block {
+            label: label.into(),
+            declarations: vec![],
+            statements: vec![],
+        }
+    }
+
+    /// Add a declaration to the declaration part of this block
+    pub fn add_declaration(&mut self, declaration: impl Into<ArchitectureDeclaration>) {
+        self.declarations.push(declaration.into());
+    }
+
+    /// Add a statement to the statement part of this block
+    pub fn add_statement(&mut self, statement: impl Into<Statement>) {
+        self.statements.push(statement.into());
+    }
+
+    /// Return this block with a declaration added
+    pub fn with_declaration(mut self, declaration: impl Into<ArchitectureDeclaration>) -> Self {
+        self.add_declaration(declaration);
+        self
+    }
+
+    /// Return this block with a statement added
+    pub fn with_statement(mut self, statement: impl Into<Statement>) -> Self {
+        self.add_statement(statement);
+        self
+    }
+
+    pub fn label(&self) -> &str {
+        self.label.as_str()
+    }
+
+    pub fn declarations(&self) -> &Vec<ArchitectureDeclaration> {
+        &self.declarations
+    }
+
+    pub fn statements(&self) -> &Vec<Statement> {
+        &self.statements
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PortMapping {
     label: String,
@@ -42,12 +169,14 @@ pub struct PortMapping {
     ports: IndexMap<String, ObjectDeclaration>,
     // Mappings for those ports, will be declared in the order of the original component declaration,
     /// irrespective of the order they're mapped during generation.
-    mappings: HashMap<String, AssignDeclaration>,
+    mappings: IndexMap<String, AssignDeclaration>,
     /// Generics that the component has
     generics: IndexMap<String, ObjectDeclaration>,
     /// Mappings for the generics, will be declared in the order of the original component declaration,
     /// irrespective of the order they're mapped during generation.
-    generic_mappings: HashMap<String, AssignDeclaration>,
+    generic_mappings: IndexMap<String, AssignDeclaration>,
+    /// Ports which are explicitly left unconnected (VHDL `open`)
+    open: Vec<String>,
 }
 
 impl PortMapping {
@@ -71,21 +200,30 @@ impl PortMapping {
                 )
             );
         }
-        Ok(PortMapping {
+        let mut result = PortMapping {
             label: label.into(),
             component_name: component.identifier().to_string(),
             ports,
-            mappings: HashMap::new(),
+            mappings: IndexMap::new(),
             generics,
-            generic_mappings: HashMap::new(),
-        })
+            generic_mappings: IndexMap::new(),
+            open: vec![],
+        };
+        // Map generics with a default value to that value, so they only
+        // need to be mapped when a different value is required.
+        for parameter in component.parameters() {
+            if let Some(default) = parameter.default {
+                result.map_generic(parameter.name.clone(), &default)?;
+            }
+        }
+        Ok(result)
     }
 
     pub fn ports(&self) -> &IndexMap<String, ObjectDeclaration> {
         &self.ports
     }
 
-    pub fn mappings(&self) -> &HashMap<String, AssignDeclaration> {
+    pub fn mappings(&self) -> &IndexMap<String, AssignDeclaration> {
         &self.mappings
     }
 
@@ -93,10 +231,15 @@ impl PortMapping {
         &self.generics
     }
 
-    pub fn generic_mappings(&self) -> &HashMap<String, AssignDeclaration> {
+    pub fn generic_mappings(&self) -> &IndexMap<String, AssignDeclaration> {
         &self.generic_mappings
     }
 
+    /// Returns the ports which are explicitly left unconnected
+    pub fn open_ports(&self) -> &Vec<String> {
+        &self.open
+    }
+
     pub fn map_port(
         &mut self,
         identifier: impl Into<String>,
@@ -111,10 +254,40 @@ impl PortMapping {
                 identifier
             )))?;
         let assigned = port.assign(assignment)?;
+        // The "out" ports of a component drive the objects they're mapped to
+        if port.mode() == &ObjectMode::Assigned {
+            verify_driven(assigned.assignment().kind())
+                .with_context(|| format!("while mapping out port {}", identifier))?;
+        }
+        self.open.retain(|open| open != identifier);
         self.mappings.insert(identifier.to_string(), assigned);
         Ok(self)
     }
 
+    /// Explicitly leave an "out" port of the component unconnected (VHDL `open`)
+    pub fn map_open(&mut self, identifier: impl Into<String>) -> Result<&mut Self> {
+        let identifier: String = identifier.into();
+        let port = self
+            .ports()
+            .get(&identifier)
+            .ok_or(Error::InvalidArgument(format!(
+                "Port {} does not exist on this component",
+                identifier
+            )))?;
+        if port.mode() != &ObjectMode::Assigned {
+            return Err(Error::InvalidArgument(format!(
+                "Port {} is an in port, and cannot be left open",
+                identifier
+            )));
+        }
+        self.mappings.shift_remove(&identifier);
+        if !self.open.contains(&identifier) {
+            self.open.push(identifier);
+        }
+        Ok(self)
+    }
+
+    /// Map a generic to a value or constant, or an assignment from constants
     pub fn map_generic(
         &mut self,
         identifier: impl Into<String>,
@@ -128,20 +301,47 @@ impl PortMapping {
                 "Generic {} does not exist on this component",
                 identifier
             )))?;
+        verify_constant(assignment.clone().into().kind())
+            .with_context(|| format!("while mapping generic {}", identifier))?;
         let assigned = generic.assign(assignment)?;
         self.generic_mappings.insert(identifier.to_string(), assigned);
         Ok(self)
     }
 
+    /// Verifies that all ports and generics are mapped, or that ports are
+    /// explicitly left open
     pub fn finish(self) -> Result<Self> {
-        if self.ports().len() == self.mappings().len() {
+        let unmapped_ports = self
+            .ports()
+            .keys()
+            .filter(|port| !self.mappings().contains_key(*port) && !self.open.contains(*port))
+            .cloned()
+            .collect::<Vec<_>>();
+        let unmapped_generics = self
+            .generics()
+            .keys()
+            .filter(|generic| !self.generic_mappings().contains_key(*generic))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut errors = vec![];
+        if !unmapped_ports.is_empty() {
+            errors.push(format!(
+                "Ports of {} are not mapped: {}",
+                self.label(),
+                unmapped_ports.join(", ")
+            ));
+        }
+        if !unmapped_generics.is_empty() {
+            errors.push(format!(
+                "Generics of {} are not mapped: {}",
+                self.label(),
+                unmapped_generics.join(", ")
+            ));
+        }
+        if errors.is_empty() {
             Ok(self)
         } else {
-            Err(Error::BackEndError(format!(
-                "The number of mappings ({}) does not match the number of ports ({})",
-                self.mappings().len(),
-                self.ports().len()
-            )))
+            Err(Error::BackEndError(errors.join("; ")))
         }
     }
 
@@ -153,3 +353,86 @@ impl PortMapping {
         self.component_name.as_str()
     }
 }
+
+/// Verifies that an assignment only refers to objects which can be driven by
+/// the "out" port of a component: signals and the "out" ports of the entity
+fn verify_driven(kind: &AssignmentKind) -> Result<()> {
+    let verify_object = |object: &ObjectDeclaration| match (object.kind(), object.mode()) {
+        (ObjectKind::Signal, _) | (ObjectKind::EntityPort, ObjectMode::Out) => Ok(()),
+        (ObjectKind::EntityPort, _) => Err(Error::InvalidArgument(format!(
+            "{} is an in port of the entity, and cannot be driven",
+            object.identifier()
+        ))),
+        (kind, _) => Err(Error::InvalidArgument(format!(
+            "{} is a {}, and cannot be driven by a port",
+            object.identifier(),
+            kind
+        ))),
+    };
+    match kind {
+        AssignmentKind::Object(object) => verify_object(object.object()),
+        AssignmentKind::Concatenation(_) => Err(Error::InvalidArgument(
+            "An out port cannot drive a concatenation".to_string(),
+        )),
+        AssignmentKind::Conversion(_) => Err(Error::InvalidArgument(
+            "An out port cannot drive a conversion".to_string(),
+        )),
+        AssignmentKind::Direct(direct) => match direct {
+            DirectAssignment::Value(_) => Err(Error::InvalidArgument(
+                "An out port cannot drive a value".to_string(),
+            )),
+            DirectAssignment::FullRecord(record) => record.values().try_for_each(verify_driven),
+            DirectAssignment::PartialRecord { direct, others } => {
+                direct.values().try_for_each(verify_driven)?;
+                verify_driven(others)
+            }
+            DirectAssignment::FullArray(array) => match array {
+                ArrayAssignment::Direct(direct) => direct.iter().try_for_each(verify_driven),
+                ArrayAssignment::Sliced { direct, others } => {
+                    direct.values().try_for_each(verify_driven)?;
+                    others.iter().try_for_each(|value| verify_driven(value))
+                }
+                ArrayAssignment::Others(value) => verify_driven(value),
+            },
+        },
+    }
+}
+
+/// Verifies that an assignment only refers to constants (or generics of the
+/// entity), as required for mapping generics
+fn verify_constant(kind: &AssignmentKind) -> Result<()> {
+    let verify_object = |object: &ObjectDeclaration| {
+        if object.kind() == ObjectKind::Constant || object.kind() == ObjectKind::EntityGeneric {
+            Ok(())
+        } else {
+            Err(Error::InvalidArgument(format!(
+                "{} is a {}, not a constant",
+                object.identifier(),
+                object.kind()
+            )))
+        }
+    };
+    match kind {
+        AssignmentKind::Object(object) => verify_object(object.object()),
+        AssignmentKind::Concatenation(objects) => objects
+            .iter()
+            .try_for_each(|object| verify_object(object.object())),
+        AssignmentKind::Conversion(conversion) => verify_object(conversion.object().object()),
+        AssignmentKind::Direct(direct) => match direct {
+            DirectAssignment::Value(_) => Ok(()),
+            DirectAssignment::FullRecord(record) => record.values().try_for_each(verify_constant),
+            DirectAssignment::PartialRecord { direct, others } => {
+                direct.values().try_for_each(verify_constant)?;
+                verify_constant(others)
+            }
+            DirectAssignment::FullArray(array) => match array {
+                ArrayAssignment::Direct(direct) => direct.iter().try_for_each(verify_constant),
+                ArrayAssignment::Sliced { direct, others } => {
+                    direct.values().try_for_each(verify_constant)?;
+                    others.iter().try_for_each(|value| verify_constant(value))
+                }
+                ArrayAssignment::Others(value) => verify_constant(value),
+            },
+        },
+    }
+}
@@ -0,0 +1,75 @@
+use indexmap::IndexMap;
+
+use crate::{Error, Result};
+
+use super::declaration::ObjectDeclaration;
+
+/// A handle to an object owned by an [ObjectStore]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectHandle(usize);
+
+/// Owns object declarations, which are referred to by handles, such that
+/// they can be referenced and modified after they were created.
+///
+/// As VHDL identifiers are case-insensitive, the identifiers of the objects
+/// in a store are unique regardless of case.
+#[derive(Debug, Clone, Default)]
+pub struct ObjectStore {
+    /// The objects, by their lowercase identifier
+    objects: IndexMap<String, ObjectDeclaration>,
+}
+
+impl ObjectStore {
+    /// Create an empty object store
+    pub fn new() -> ObjectStore {
+        ObjectStore::default()
+    }
+
+    /// Add an object to the store, returning its handle.
+    ///
+    /// Returns an error if the store already contains an object with the
+    /// same identifier.
+    pub fn insert(&mut self, object: ObjectDeclaration) -> Result<ObjectHandle> {
+        let key = object.identifier().to_lowercase();
+        if self.objects.contains_key(&key) {
+            return Err(Error::InvalidArgument(format!(
+                "An object with identifier {} already exists",
+                object.identifier()
+            )));
+        }
+        let (index, _) = self.objects.insert_full(key, object);
+        Ok(ObjectHandle(index))
+    }
+
+    /// Returns the object referred to by a handle
+    pub fn get(&self, handle: ObjectHandle) -> Result<&ObjectDeclaration> {
+        self.objects
+            .get_index(handle.0)
+            .map(|(_, object)| object)
+            .ok_or_else(|| Error::InvalidArgument(format!("Invalid object handle {:?}", handle)))
+    }
+
+    /// Returns a mutable reference to the object referred to by a handle
+    pub fn get_mut(&mut self, handle: ObjectHandle) -> Result<&mut ObjectDeclaration> {
+        self.objects
+            .get_index_mut(handle.0)
+            .map(|(_, object)| object)
+            .ok_or_else(|| Error::InvalidArgument(format!("Invalid object handle {:?}", handle)))
+    }
+
+    /// Returns the handle of the object with the given identifier, if any
+    pub fn find(&self, identifier: &str) -> Option<ObjectHandle> {
+        self.objects
+            .get_index_of(&identifier.to_lowercase())
+            .map(ObjectHandle)
+    }
+
+    /// Returns the objects in the store with their handles, in the order
+    /// they were added
+    pub fn iter(&self) -> impl Iterator<Item = (ObjectHandle, &ObjectDeclaration)> {
+        self.objects
+            .values()
+            .enumerate()
+            .map(|(index, object)| (ObjectHandle(index), object))
+    }
+}
@@ -12,6 +12,7 @@ impl Declare for Entity {
             result.push('\n');
         }
         result.push_str(format!("entity {} is\n", self.identifier()).as_str());
+        result.push_str(self.parameters().declare()?.as_str());
         result.push_str(self.ports().declare()?.as_str());
         result.push_str(format!("end entity {};\n", self.identifier()).as_str());
         Ok(result)
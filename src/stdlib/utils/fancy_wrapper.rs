@@ -9,21 +9,52 @@ use crate::{
     },
     stdlib::common::{
         architecture::{
-            assignment::{flatten::FlatAssignment, Assign, FieldSelection},
+            assignment::{
+                flatten::{FlatAssignment, FlatLength},
+                Assign, Assignment, FieldSelection, FlatConversion, ObjectAssignment,
+            },
             declaration::{ObjectDeclaration, ObjectMode},
             statement::PortMapping,
             Architecture,
         },
     },
-    Error, Result,
+    Error, NamingPolicy, Result,
 };
 
-pub fn generate_fancy_wrapper<'a>(
-    package: &'a Package,
+/// Generate the wrapper of a fancy component: the architecture of the
+/// canonical component, which instantiates the user component (named after
+/// the streamlet, and declared in the fancy package) with its fancy ports,
+/// and wires those to the canonical ports.
+///
+/// The user component is implemented in a separate file, such that
+/// regenerating the wrapper does not affect the user's implementation. The
+/// wrapper ends its declaration and statement parts with user regions, for
+/// additional logic which is kept when the wrapper is regenerated.
+pub fn generate_fancy_wrapper(
+    package: &Package,
     streamlet_key: &StreamletKey,
-) -> Result<Architecture<'a>> {
+) -> Result<Architecture> {
+    generate_fancy_wrapper_with(package, streamlet_key, &NamingPolicy::default())
+}
+
+/// Generate the wrapper of a fancy component, for a package of which the
+/// ports are named according to a naming policy.
+pub fn generate_fancy_wrapper_with(
+    package: &Package,
+    streamlet_key: &StreamletKey,
+    policy: &NamingPolicy,
+) -> Result<Architecture> {
     let mut architecture =
-        Architecture::new_default(package, cat!(streamlet_key, CANON_SUFFIX.unwrap()))?;
+        Architecture::new_default(package, cat!(streamlet_key, CANON_SUFFIX.unwrap()))?
+            .with_user_regions();
+    // The records of fancy components have the widths that result from the
+    // default values of the generics of the canonical component.
+    if !architecture.entity().parameters().is_empty() {
+        return Err(Error::BackEndError(format!(
+            "Cannot generate the fancy wrapper of {}, because it has generics",
+            streamlet_key
+        )));
+    }
     let mut portmap =
         PortMapping::from_component(&package.get_component(streamlet_key.clone())?, "fancy")?;
     let mut fancy_wires = IndexMap::new();
@@ -48,18 +79,33 @@ pub fn generate_fancy_wrapper<'a>(
                             field_name: &str,
                             to_complex: bool|
      -> Result<()> {
-        fancy_assigns.extend(if to_complex {
-            port.to_complex(signal, &vec![FieldSelection::name(field_name)], &vec![])?
+        let field = vec![FieldSelection::name(field_name)];
+        let typ = signal.typ().get_nested(&field)?;
+        // Records and arrays are converted using the functions generated in the package
+        if !typ.is_flat() && typ.has_flat_length() && typ.flat_length()? > 0 {
+            fancy_assigns.push(if to_complex {
+                let conversion = FlatConversion::FromFlat(ObjectAssignment::from(port.clone()));
+                signal.assign(&Assignment::from(conversion).to_nested(&field))?
+            } else {
+                let field = ObjectAssignment::from(signal.clone()).assign_from(&field)?;
+                let conversion = FlatConversion::ToFlat(field);
+                port.assign(&Assignment::from(conversion))?
+            });
         } else {
-            signal.to_flat(port, &vec![], &vec![FieldSelection::name(field_name)])?
-        });
+            fancy_assigns.extend(if to_complex {
+                port.to_complex(signal, &field, &vec![])?
+            } else {
+                signal.to_flat(port, &vec![], &field)?
+            });
+        }
         Ok(())
     };
     for (port_name, wire) in &fancy_wires {
         let base_name = port_name.replace("_dn", "").replace("_up", "");
         for (canon_name, entity_port) in architecture.entity_ports()? {
             if canon_name.starts_with(&base_name) {
-                let field_name = canon_name.trim_start_matches(&format!("{}_", base_name));
+                let field_name =
+                    canon_name.trim_start_matches(&format!("{}{}", base_name, policy.separator()));
                 match wire.typ().get_field(&FieldSelection::name(field_name)) {
                     Ok(_) => field_assign(
                         wire,
@@ -90,6 +136,7 @@ mod tests {
     use std::convert::TryFrom;
 
     use crate::{
+        design::implementation::composer::tests::sdf_test_proj,
         generator::{common::convert::Packify, vhdl::Declare},
         stdlib::basic::stub::tests::parsed_stub_project,
         Name,
@@ -143,68 +190,23 @@ architecture behavioral of passthrough_stub_com is
    signal in_pass2_up_wire : passthrough_stub_in_pass2_up_type;
    signal out_pass_dn_wire : passthrough_stub_out_pass_dn_type;
    signal out_pass_up_wire : passthrough_stub_out_pass_up_type;
+  -- tydi-user-begin: declarations
+  -- tydi-user-end: declarations
 begin
    clk_wire <= clk;
    rst_wire <= rst;
    in_pass_dn_wire.valid <= in_pass_valid;
-   in_pass_dn_wire.data(0).tag <= in_pass_data(32 downto 32);
-   in_pass_dn_wire.data(0).a <= in_pass_data(31 downto 0);
-   in_pass_dn_wire.data(0).b <= in_pass_data(7 downto 0);
-   in_pass_dn_wire.data(1).tag <= in_pass_data(65 downto 65);
-   in_pass_dn_wire.data(1).a <= in_pass_data(64 downto 33);
-   in_pass_dn_wire.data(1).b <= in_pass_data(40 downto 33);
-   in_pass_dn_wire.data(2).tag <= in_pass_data(98 downto 98);
-   in_pass_dn_wire.data(2).a <= in_pass_data(97 downto 66);
-   in_pass_dn_wire.data(2).b <= in_pass_data(73 downto 66);
-   in_pass_dn_wire.data(3).tag <= in_pass_data(131 downto 131);
-   in_pass_dn_wire.data(3).a <= in_pass_data(130 downto 99);
-   in_pass_dn_wire.data(3).b <= in_pass_data(106 downto 99);
-   in_pass_dn_wire.data(4).tag <= in_pass_data(164 downto 164);
-   in_pass_dn_wire.data(4).a <= in_pass_data(163 downto 132);
-   in_pass_dn_wire.data(4).b <= in_pass_data(139 downto 132);
-   in_pass_dn_wire.data(5).tag <= in_pass_data(197 downto 197);
-   in_pass_dn_wire.data(5).a <= in_pass_data(196 downto 165);
-   in_pass_dn_wire.data(5).b <= in_pass_data(172 downto 165);
-   in_pass_dn_wire.data(6).tag <= in_pass_data(230 downto 230);
-   in_pass_dn_wire.data(6).a <= in_pass_data(229 downto 198);
-   in_pass_dn_wire.data(6).b <= in_pass_data(205 downto 198);
-   in_pass_dn_wire.data(7).tag <= in_pass_data(263 downto 263);
-   in_pass_dn_wire.data(7).a <= in_pass_data(262 downto 231);
-   in_pass_dn_wire.data(7).b <= in_pass_data(238 downto 231);
+   in_pass_dn_wire.data <= from_slv(in_pass_data);
    in_pass_dn_wire.stai <= in_pass_stai;
    in_pass_dn_wire.endi <= in_pass_endi;
    in_pass_dn_wire.strb <= in_pass_strb;
    in_pass_ready <= in_pass_up_wire.ready;
    in_pass2_dn_wire.valid <= in_pass2_valid;
-   in_pass2_dn_wire.data.op1 <= in_pass2_data(63 downto 0);
-   in_pass2_dn_wire.data.op2 <= in_pass2_data(117 downto 64);
+   in_pass2_dn_wire.data <= from_slv(in_pass2_data);
    in_pass2_dn_wire.strb <= in_pass2_strb;
    in_pass2_ready <= in_pass2_up_wire.ready;
    out_pass_valid <= out_pass_dn_wire.valid;
-   out_pass_data(32 downto 32) <= out_pass_dn_wire.data(0).tag;
-   out_pass_data(31 downto 0) <= out_pass_dn_wire.data(0).a;
-   out_pass_data(7 downto 0) <= out_pass_dn_wire.data(0).b;
-   out_pass_data(65 downto 65) <= out_pass_dn_wire.data(1).tag;
-   out_pass_data(64 downto 33) <= out_pass_dn_wire.data(1).a;
-   out_pass_data(40 downto 33) <= out_pass_dn_wire.data(1).b;
-   out_pass_data(98 downto 98) <= out_pass_dn_wire.data(2).tag;
-   out_pass_data(97 downto 66) <= out_pass_dn_wire.data(2).a;
-   out_pass_data(73 downto 66) <= out_pass_dn_wire.data(2).b;
-   out_pass_data(131 downto 131) <= out_pass_dn_wire.data(3).tag;
-   out_pass_data(130 downto 99) <= out_pass_dn_wire.data(3).a;
-   out_pass_data(106 downto 99) <= out_pass_dn_wire.data(3).b;
-   out_pass_data(164 downto 164) <= out_pass_dn_wire.data(4).tag;
-   out_pass_data(163 downto 132) <= out_pass_dn_wire.data(4).a;
-   out_pass_data(139 downto 132) <= out_pass_dn_wire.data(4).b;
-   out_pass_data(197 downto 197) <= out_pass_dn_wire.data(5).tag;
-   out_pass_data(196 downto 165) <= out_pass_dn_wire.data(5).a;
-   out_pass_data(172 downto 165) <= out_pass_dn_wire.data(5).b;
-   out_pass_data(230 downto 230) <= out_pass_dn_wire.data(6).tag;
-   out_pass_data(229 downto 198) <= out_pass_dn_wire.data(6).a;
-   out_pass_data(205 downto 198) <= out_pass_dn_wire.data(6).b;
-   out_pass_data(263 downto 263) <= out_pass_dn_wire.data(7).tag;
-   out_pass_data(262 downto 231) <= out_pass_dn_wire.data(7).a;
-   out_pass_data(238 downto 231) <= out_pass_dn_wire.data(7).b;
+   out_pass_data <= to_slv(out_pass_dn_wire.data);
    out_pass_stai <= out_pass_dn_wire.stai;
    out_pass_endi <= out_pass_dn_wire.endi;
    out_pass_strb <= out_pass_dn_wire.strb;
@@ -219,10 +221,24 @@ begin
      out_pass_dn => out_pass_dn_wire,
      out_pass_up => out_pass_up_wire
    );
+  -- tydi-user-begin: statements
+  -- tydi-user-end: statements
 end behavioral;
 "#,
             arch.declare()?
         );
         Ok(())
     }
+    #[test]
+    fn test_fancy_wrapper_generics() -> Result<()> {
+        let prj = sdf_test_proj(&["Streamlet x <W = 8> (a : in Stream<Bits<W>>)"])?;
+        let pak = prj.get_lib(Name::try_from("lib")?)?.fancy();
+        assert_eq!(
+            generate_fancy_wrapper(&pak, &StreamletKey::try_from("x")?)
+                .unwrap_err()
+                .to_string(),
+            "Back-end error: Cannot generate the fancy wrapper of x, because it has generics"
+        );
+        Ok(())
+    }
 }
@@ -1 +1,2 @@
 pub mod fancy_wrapper;
+pub mod structure;
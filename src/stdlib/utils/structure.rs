@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use crate::{
+    cat,
+    design::{
+        implementation::{composer::GenericComponent, structure::Structure},
+        NodeIFHandle, NodeKey, Project,
+    },
+    generator::common::{
+        convert::{canonical_field, Componentify, CANON_SUFFIX},
+        Mode, Package,
+    },
+    stdlib::common::architecture::{
+        assignment::{bitvec::BitVecValue, Assign, StdLogicValue},
+        declaration::ObjectDeclaration,
+        object::ObjectType,
+        statement::PortMapping,
+        Architecture,
+    },
+    Error, Identify, Name, NamingPolicy, PathName, Result,
+};
+
+/// Generate the architecture of the canonical component of a streamlet with a
+/// structural implementation.
+///
+/// Every node is instantiated through its canonical component, and every
+/// port of a node is connected to a signal named after the node and the port.
+/// Edges are implemented by assigning the signals (or entity ports) of the
+/// source interface to those of the sink interface, in the direction of each
+/// individual signal. Edges between fields of interfaces only assign the
+/// signals of those fields. The signals of fields that are not connected
+/// while other fields of their interface are, are tied off: `ready` signals
+/// are tied to `'1'`, all other signals to `'0'`.
+///
+/// The sink of an edge may have a higher complexity than its source, in
+/// which case it has `stai`, `endi` or `strb` signals that its source does
+/// not have. These are tied to their defaults: `stai` to 0, `endi` to the
+/// index of the last lane, and `strb` to all ones.
+///
+/// Nodes whose streamlets have a structural implementation themselves are
+/// instantiated like any other node; their architectures are generated
+/// separately. Nodes whose streamlets are implemented externally instantiate
+/// the component named after their entity, which is made visible from its
+/// library.
+pub fn generate_structure(
+    package: &Package,
+    project: &Project,
+    structure: &Structure,
+) -> Result<Architecture> {
+    generate_structure_with(package, project, structure, &NamingPolicy::default())
+}
+
+/// Generate the architecture of the canonical component of a streamlet with a
+/// structural implementation, see [generate_structure], for a package of
+/// which the ports are named according to a naming policy.
+pub fn generate_structure_with(
+    package: &Package,
+    project: &Project,
+    structure: &Structure,
+    policy: &NamingPolicy,
+) -> Result<Architecture> {
+    let handle = structure.streamlet_handle();
+    let streamlet = project.get_streamlet(handle.clone())?;
+    let mut architecture =
+        Architecture::new_default(package, cat!(streamlet.key(), CANON_SUFFIX.unwrap()))?;
+    let entity_ports = architecture.entity_ports()?;
+    let entity_port = |name: &str| -> Result<ObjectDeclaration> {
+        entity_ports
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::BackEndError(format!("Entity does not have a {} signal", name)))
+    };
+
+    // Instantiate all nodes, connecting their ports to signals.
+    let mut wires: HashMap<(NodeKey, String), ObjectDeclaration> = HashMap::new();
+    let mut portmaps = vec![];
+    for (node, node_handle) in structure.nodes() {
+        let instance = project.get_streamlet(node_handle.clone())?;
+        if node_handle.lib() != handle.lib() {
+            architecture.add_using(Name::try_new("work")?, format!("{}.all", node_handle.lib()));
+        }
+        if let Some((Some(library), entity)) = instance.external() {
+            architecture.add_using(Name::try_new(library)?, entity);
+        }
+        let mut portmap = PortMapping::from_component(
+            &instance.canonical_with(CANON_SUFFIX, policy),
+            node.to_string(),
+        )?;
+        for (port_name, object) in portmap.clone().ports() {
+            if port_name == "clk" || port_name == "rst" {
+                portmap.map_port(port_name, &entity_port(port_name)?)?;
+            } else {
+                let wire =
+                    ObjectDeclaration::signal(cat!(node, port_name), object.typ().clone(), None);
+                portmap.map_port(port_name, &wire)?;
+                wires.insert((node.clone(), port_name.to_string()), wire.clone());
+                architecture.add_declaration(wire)?;
+            }
+        }
+        portmaps.push(portmap);
+    }
+
+    // Returns the ports of a field of an interface of a node.
+    let endpoint = |handle: &NodeIFHandle, field: &PathName| -> Result<Vec<FieldPort>> {
+        let interface = project
+            .get_streamlet(structure.get_node(&handle.node())?)?
+            .get_interface(handle.iface())?;
+        // The ports of the field are in the order of the signals and streams
+        // of the synthesized interface, see [canonical_field].
+        let synth = interface.synthesize();
+        let relative = |path: &PathName| path.as_ref()[field.len()..].to_vec();
+        let keys = synth
+            .signals()
+            .filter(|(path, _)| path.starts_with(field))
+            .map(|(path, _)| ((relative(path), None), None))
+            .chain(
+                synth
+                    .streams()
+                    .filter(|(path, _)| path.starts_with(field))
+                    .flat_map(|(path, phys)| {
+                        let lanes = phys.element_lanes().get();
+                        phys.signal_list().into_iter().map(move |signal| {
+                            let default = match signal.identifier() {
+                                "stai" => Some(BitVecValue::Others(StdLogicValue::Logic(false))),
+                                "endi" => Some(BitVecValue::Unsigned(lanes - 1)),
+                                "strb" => Some(BitVecValue::Others(StdLogicValue::Logic(true))),
+                                _ => None,
+                            };
+                            (
+                                (relative(path), Some(signal.identifier().to_string())),
+                                default,
+                            )
+                        })
+                    }),
+            );
+        canonical_field(&interface, interface.identifier(), field, policy)
+            .into_iter()
+            .zip(keys)
+            .map(|(port, (key, default))| {
+                let (object, drives) = if handle.node() == NodeKey::this() {
+                    (entity_port(port.identifier())?, port.mode() == Mode::In)
+                } else {
+                    let wire = wires
+                        .get(&(handle.node(), port.identifier().to_string()))
+                        .cloned()
+                        .ok_or_else(|| {
+                            Error::BackEndError(format!(
+                                "Node {} does not have a {} port",
+                                handle.node(),
+                                port.identifier()
+                            ))
+                        })?;
+                    (wire, port.mode() == Mode::Out)
+                };
+                Ok(FieldPort {
+                    key,
+                    object,
+                    drives,
+                    default,
+                })
+            })
+            .collect()
+    };
+
+    // Connect the interfaces of all edges.
+    for edge in structure.edges() {
+        let (source, sink) = (
+            endpoint(&edge.source(), edge.source_field())?,
+            endpoint(&edge.sink(), edge.sink_field())?,
+        );
+        let unmatched = |port: &FieldPort| {
+            Error::BackEndError(format!(
+                "Cannot connect {}.{} to {}.{}, {} has no counterpart",
+                edge.source().node(),
+                edge.source().iface(),
+                edge.sink().node(),
+                edge.sink().iface(),
+                port.object.identifier()
+            ))
+        };
+        if let Some(port) = source
+            .iter()
+            .find(|port| !sink.iter().any(|other| other.key == port.key))
+        {
+            return Err(unmatched(port));
+        }
+        for port in &sink {
+            match source.iter().find(|other| other.key == port.key) {
+                Some(source) if source.drives => {
+                    architecture.add_statement(port.object.assign(&source.object)?)?
+                }
+                Some(source) => architecture.add_statement(source.object.assign(&port.object)?)?,
+                None => match &port.default {
+                    Some(default) if !port.drives => {
+                        architecture.add_statement(port.object.assign(default)?)?
+                    }
+                    _ => return Err(unmatched(port)),
+                },
+            }
+        }
+    }
+
+    // Tie off the fields of partially connected interfaces.
+    let nodes = std::iter::once(NodeKey::this()).chain(structure.nodes().map(|(n, _)| n.clone()));
+    for node in nodes {
+        let interfaces = project
+            .get_streamlet(structure.get_node(&node)?)?
+            .interfaces()
+            .map(|iface| NodeIFHandle::new(node.clone(), iface.key().clone()))
+            .collect::<Vec<_>>();
+        for handle in interfaces {
+            for field in structure.unconnected_fields(project, &handle)? {
+                for FieldPort { object, drives, .. } in endpoint(&handle, &field)? {
+                    if drives {
+                        continue;
+                    }
+                    let ready = object.identifier().ends_with("_ready");
+                    let statement = match object.typ() {
+                        ObjectType::Bit => object.assign(&StdLogicValue::Logic(ready))?,
+                        _ => object.assign(&BitVecValue::Others(StdLogicValue::Logic(false)))?,
+                    };
+                    architecture.add_statement(statement)?;
+                }
+            }
+        }
+    }
+
+    for portmap in portmaps {
+        architecture.add_statement(portmap)?;
+    }
+
+    Ok(architecture)
+}
+
+/// A port of a field of an interface of a node.
+struct FieldPort {
+    /// The path of the port relative to the field, and the name of its signal
+    /// if it is a signal of a physical stream, by which it is matched to the
+    /// port of the other end of an edge.
+    key: (Vec<Name>, Option<String>),
+    /// The signal or entity port the port is connected to.
+    object: ObjectDeclaration,
+    /// Whether the port is driven from within its node.
+    drives: bool,
+    /// The value the port is tied to if the other end of an edge does not
+    /// have it.
+    default: Option<BitVecValue>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::{
+        design::implementation::{
+            composer::tests::{handle, iface, sdf_test_proj},
+            Implementation,
+        },
+        generator::{common::convert::Packify, vhdl::Declare},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_structure() -> Result<()> {
+        let mut prj = sdf_test_proj(&[
+            "Streamlet top (a : in Stream<Bits<4>>, b : out Stream<Bits<4>>)",
+            "Streamlet leaf (a : in Stream<Bits<4>>, b : out Stream<Bits<4>>)",
+        ])?;
+
+        let mut structure = Structure::new(handle("top"));
+        structure.add_node(Name::try_from("x")?, handle("leaf"))?;
+        structure.connect(&prj, iface("this", "a"), iface("x", "a"))?;
+        structure.connect(&prj, iface("x", "b"), iface("this", "b"))?;
+
+        let pak = prj.get_lib(Name::try_from("lib")?)?.canonical();
+        let arch = generate_structure(&pak, &prj, &structure)?;
+        let vhdl = arch.declare()?;
+        assert!(vhdl.contains("architecture behavioral of top_com is"));
+        assert!(vhdl.contains("signal x_a_valid : std_logic;"));
+        assert!(vhdl.contains("x_a_valid <= a_valid;"));
+        assert!(vhdl.contains("a_ready <= x_a_ready;"));
+        assert!(vhdl.contains("x_a_data <= a_data;"));
+        assert!(vhdl.contains("b_valid <= x_b_valid;"));
+        assert!(vhdl.contains("x_b_ready <= b_ready;"));
+        assert!(vhdl.contains("x: leaf_com\n    port map(\n      clk => clk,"));
+
+        prj.add_streamlet_impl(handle("top"), Implementation::Structural(structure))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_structure_fields() -> Result<()> {
+        let pair = |name: &str| {
+            format!(
+                "Streamlet {} (a : in Stream<Bits<4>>, \
+                 b : out Group<x: Stream<Bits<4>>, y: Stream<Bits<4>>>)",
+                name
+            )
+        };
+        let prj = sdf_test_proj(&[&pair("top"), &pair("leaf")])?;
+
+        let mut structure = Structure::new(handle("top"));
+        structure.add_node(Name::try_from("p")?, handle("leaf"))?;
+        structure.connect(&prj, iface("this", "a"), iface("p", "a"))?;
+        structure.connect_fields(
+            &prj,
+            iface("p", "b"),
+            PathName::try_new(vec!["x"])?,
+            iface("this", "b"),
+            PathName::try_new(vec!["x"])?,
+        )?;
+
+        let pak = prj.get_lib(Name::try_from("lib")?)?.canonical();
+        let vhdl = generate_structure(&pak, &prj, &structure)?.declare()?;
+        assert!(vhdl.contains("b_x_valid <= p_b_x_valid;"));
+        assert!(vhdl.contains("p_b_x_ready <= b_x_ready;"));
+        assert!(vhdl.contains("b_x_data <= p_b_x_data;"));
+        assert!(!vhdl.contains("b_y_valid <= p_b_y_valid;"));
+        // Tie-offs of the unconnected fields of the source and sink.
+        assert!(vhdl.contains("p_b_y_ready <= '1';"));
+        assert!(vhdl.contains("b_y_valid <= '0';"));
+        assert!(vhdl.contains("b_y_data <= (others => '0');"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_structure_complexity() -> Result<()> {
+        let prj = sdf_test_proj(&[
+            "Streamlet top (a : in Stream<Bits<4>, t=4, c=1>, b : out Stream<Bits<4>, t=4, c=7>)",
+            "Streamlet leaf (a : in Stream<Bits<4>, t=4, c=7>, b : out Stream<Bits<4>, t=4, c=1>)",
+        ])?;
+        let mut structure = Structure::new(handle("top"));
+        structure.add_node(Name::try_from("x")?, handle("leaf"))?;
+        structure.connect(&prj, iface("this", "a"), iface("x", "a"))?;
+        structure.connect(&prj, iface("x", "b"), iface("this", "b"))?;
+
+        let pak = prj.get_lib(Name::try_from("lib")?)?.canonical();
+        let vhdl = generate_structure(&pak, &prj, &structure)?.declare()?;
+        assert!(vhdl.contains("x_a_data <= a_data;"));
+        // The signals that only the sinks have are tied to their defaults.
+        assert!(vhdl.contains("x_a_stai <= (others => '0');"));
+        assert!(vhdl.contains("x_a_endi <= std_logic_vector(to_unsigned(3, x_a_endi'length));"));
+        assert!(vhdl.contains("x_a_strb <= (others => '1');"));
+        assert!(vhdl.contains("b_stai <= (others => '0');"));
+        assert!(vhdl.contains("b_endi <= std_logic_vector(to_unsigned(3, b_endi'length));"));
+        assert!(vhdl.contains("b_strb <= (others => '1');"));
+
+        // A source cannot have signals that its sink does not have.
+        let mut structure = Structure::new(handle("top"));
+        structure.add_node(Name::try_from("x")?, handle("leaf"))?;
+        assert!(structure
+            .connect(&prj, iface("this", "a"), iface("x", "b"))
+            .is_err());
+        Ok(())
+    }
+}
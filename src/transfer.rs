@@ -0,0 +1,513 @@
+//! Transfer-level encoding and decoding of physical streams.
+//!
+//! This module converts between a sequence of [`Element`]s, i.e. the packed
+//! element bits of every element with the dimension boundaries it closes,
+//! and the per-cycle [`Transfer`]s of a [`PhysicalStream`], i.e. the bit
+//! patterns of its `data`, `last`, `stai`, `endi`, `strb` and `user` signals.
+//!
+//! The packed bits of an element can be obtained from a [`Value`] using
+//! [`Value::to_element_bits`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tydi::physical::PhysicalStream;
+//! use tydi::transfer::{decode, encode, Element};
+//!
+//! // Two element lanes of 2 bits, with one dimension.
+//! let stream = PhysicalStream::try_new(vec![("a", 2)], 2, 1, 4, vec![])?;
+//!
+//! // The sequence [1, 2, 3].
+//! let elements = vec![
+//!     Element::new(vec![true, false]),
+//!     Element::new(vec![false, true]),
+//!     Element::new(vec![true, true]).with_last(vec![true]),
+//! ];
+//!
+//! let transfers = encode(&stream, &elements)?;
+//! assert_eq!(transfers.len(), 2);
+//! assert_eq!(transfers[0].data(), &[true, false, false, true]);
+//! assert_eq!(transfers[1].last(), &[true]);
+//! // Below complexity 8, the inactive lane is indicated by endi only.
+//! assert_eq!(transfers[1].endi(), &[false]);
+//! assert_eq!(transfers[1].strb(), &[true, true]);
+//!
+//! assert_eq!(decode(&stream, &transfers)?, elements);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`Element`]: ./struct.Element.html
+//! [`Transfer`]: ./struct.Transfer.html
+//! [`PhysicalStream`]: ../physical/struct.PhysicalStream.html
+//! [`Value`]: ../value/enum.Value.html
+//! [`Value::to_element_bits`]: ../value/enum.Value.html#method.to_element_bits
+
+use crate::physical::{LaneSlice, PhysicalStream};
+use crate::{Error, Result, WideNonNegative};
+
+/// An element carried by a physical stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    /// The packed element bits, least significant bit first. None for the
+    /// marker of an empty sequence.
+    data: Option<Vec<bool>>,
+    /// The dimension boundaries closed by this element, innermost dimension
+    /// first.
+    last: Vec<bool>,
+}
+
+impl Element {
+    /// Returns a new element with the provided packed element bits, least
+    /// significant bit first, that closes no dimensions.
+    pub fn new(data: Vec<bool>) -> Self {
+        Element {
+            data: Some(data),
+            last: Vec::new(),
+        }
+    }
+
+    /// Returns a marker for the end of an empty sequence, i.e. a transfer
+    /// without any active element lanes that closes the given dimensions.
+    pub fn empty(last: Vec<bool>) -> Self {
+        Element { data: None, last }
+    }
+
+    /// Returns this element, closing the given dimensions, innermost
+    /// dimension first.
+    pub fn with_last(mut self, last: Vec<bool>) -> Self {
+        self.last = last;
+        self
+    }
+
+    /// Returns the packed element bits, or None if this is the marker of an
+    /// empty sequence.
+    pub fn data(&self) -> Option<&[bool]> {
+        self.data.as_deref()
+    }
+
+    /// Returns the dimension boundaries closed by this element, innermost
+    /// dimension first.
+    pub fn last(&self) -> &[bool] {
+        &self.last
+    }
+
+    /// Returns true if this element closes any dimension.
+    fn is_last(&self) -> bool {
+        self.last.iter().any(|&b| b)
+    }
+}
+
+/// The signal values of a single transfer on a physical stream.
+///
+/// All signals are least significant bit first, and have the width of the
+/// corresponding signal of the physical stream. Signals that are absent from
+/// the physical stream are empty.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transfer {
+    data: Vec<bool>,
+    last: Vec<bool>,
+    stai: Vec<bool>,
+    endi: Vec<bool>,
+    strb: Vec<bool>,
+    user: Vec<bool>,
+}
+
+impl Transfer {
+    /// Returns a new transfer with the provided signal values. Returns an
+    /// error when a signal does not have the width of the corresponding
+    /// signal of the physical stream.
+    pub fn try_new(
+        stream: &PhysicalStream,
+        data: Vec<bool>,
+        last: Vec<bool>,
+        stai: Vec<bool>,
+        endi: Vec<bool>,
+        strb: Vec<bool>,
+        user: Vec<bool>,
+    ) -> Result<Self> {
+        let transfer = Transfer {
+            data,
+            last,
+            stai,
+            endi,
+            strb,
+            user,
+        };
+        for (name, bits, width) in [
+            ("data", &transfer.data, stream.data_bit_count()),
+            ("last", &transfer.last, stream.last_bit_count()),
+            ("stai", &transfer.stai, stream.stai_bit_count()),
+            ("endi", &transfer.endi, stream.endi_bit_count()),
+            ("strb", &transfer.strb, stream.strb_bit_count()),
+            ("user", &transfer.user, stream.user_bit_count()),
+        ] {
+            if bits.len() as WideNonNegative != width {
+                return Err(Error::InvalidArgument(format!(
+                    "{} signal has {} bits, expected {}",
+                    name,
+                    bits.len(),
+                    width
+                )));
+            }
+        }
+        Ok(transfer)
+    }
+
+    /// Returns the `data` signal.
+    pub fn data(&self) -> &[bool] {
+        &self.data
+    }
+
+    /// Returns the `last` signal.
+    pub fn last(&self) -> &[bool] {
+        &self.last
+    }
+
+    /// Returns the `stai` signal.
+    pub fn stai(&self) -> &[bool] {
+        &self.stai
+    }
+
+    /// Returns the `endi` signal.
+    pub fn endi(&self) -> &[bool] {
+        &self.endi
+    }
+
+    /// Returns the `strb` signal.
+    pub fn strb(&self) -> &[bool] {
+        &self.strb
+    }
+
+    /// Returns the `user` signal.
+    pub fn user(&self) -> &[bool] {
+        &self.user
+    }
+
+    /// Returns the element lanes of a physical stream that are active in
+    /// this transfer, as indicated by its `stai`, `endi` and `strb` signals.
+    pub fn active_lanes(&self, stream: &PhysicalStream) -> Vec<LaneSlice> {
+        let lanes = stream.signal_list().lanes();
+        let stai = if self.stai.is_empty() {
+            0
+        } else {
+            to_uint(&self.stai)
+        };
+        let endi = if self.endi.is_empty() {
+            lanes.len() - 1
+        } else {
+            to_uint(&self.endi)
+        };
+        lanes
+            .into_iter()
+            .filter(|lane| (stai..=endi).contains(&(lane.index() as usize)))
+            .filter(|lane| lane.strb().is_none_or(|strb| self.strb[strb as usize]))
+            .collect()
+    }
+}
+
+/// Encodes a sequence of elements into transfers on a physical stream.
+///
+/// Elements are packed into the element lanes starting from lane zero. A
+/// transfer ends when all element lanes are used, or after an element that
+/// closes a dimension. This encoding is valid for every complexity level.
+/// The `user` signal is driven low.
+///
+/// Returns an error when an element does not have the element bit count or
+/// dimensionality of the physical stream.
+pub fn encode(stream: &PhysicalStream, elements: &[Element]) -> Result<Vec<Transfer>> {
+    let lanes = stream.element_lanes().get() as usize;
+    let element_bits = element_bit_count(stream);
+    let dimensionality = stream.dimensionality() as usize;
+
+    let mut transfers = Vec::new();
+    let mut active: Vec<&[bool]> = Vec::with_capacity(lanes);
+    for element in elements {
+        // Elements that close no dimensions may omit their last flags.
+        if !element.last.is_empty() && element.last.len() != dimensionality {
+            return Err(Error::InvalidArgument(format!(
+                "element closes {} dimensions, expected {}",
+                element.last.len(),
+                dimensionality
+            )));
+        }
+        match &element.data {
+            Some(data) => {
+                if data.len() != element_bits {
+                    return Err(Error::InvalidArgument(format!(
+                        "element has {} bits, expected {}",
+                        data.len(),
+                        element_bits
+                    )));
+                }
+                active.push(data);
+            }
+            None => {
+                if !active.is_empty() {
+                    return Err(Error::InvalidArgument(
+                        "an empty sequence must start a new transfer".to_string(),
+                    ));
+                }
+                if !element.is_last() {
+                    return Err(Error::InvalidArgument(
+                        "an empty sequence marker must close a dimension".to_string(),
+                    ));
+                }
+            }
+        }
+        if active.len() == lanes || element.is_last() {
+            transfers.push(build_transfer(stream, &active, &element.last)?);
+            active.clear();
+        }
+    }
+    if !active.is_empty() {
+        transfers.push(build_transfer(stream, &active, &[])?);
+    }
+    Ok(transfers)
+}
+
+/// Decodes transfers on a physical stream into a sequence of elements.
+///
+/// Only the active element lanes of a transfer, as indicated by its `stai`,
+/// `endi` and `strb` signals, result in elements. The dimension boundaries of
+/// the `last` signal are attached to the last active element, or result in
+/// an empty sequence marker for transfers without active element lanes.
+/// Transfers without active element lanes that close no dimensions are
+/// skipped.
+///
+/// Returns an error when a transfer does not match the signal widths of the
+/// physical stream, or when its `strb` bits are not all driven to the same
+/// value below complexity 8.
+pub fn decode(stream: &PhysicalStream, transfers: &[Transfer]) -> Result<Vec<Element>> {
+    let mut elements = Vec::new();
+    for transfer in transfers {
+        let transfer = Transfer::try_new(
+            stream,
+            transfer.data.clone(),
+            transfer.last.clone(),
+            transfer.stai.clone(),
+            transfer.endi.clone(),
+            transfer.strb.clone(),
+            transfer.user.clone(),
+        )?;
+        if !stream.complexity().allows_lane_strobe()
+            && transfer.strb.iter().any(|&b| b != transfer.strb[0])
+        {
+            return Err(Error::InvalidArgument(format!(
+                "strb bits must be driven to the same value below complexity 8, found {:?}",
+                transfer.strb
+            )));
+        }
+        let first = elements.len();
+        for lane in transfer.active_lanes(stream) {
+            let data = lane.data();
+            elements.push(Element::new(
+                transfer.data[data.start as usize..data.end as usize].to_vec(),
+            ));
+        }
+        if transfer.last.iter().any(|&b| b) {
+            if elements.len() > first {
+                elements.last_mut().unwrap().last = transfer.last.clone();
+            } else {
+                elements.push(Element::empty(transfer.last.clone()));
+            }
+        }
+    }
+    Ok(elements)
+}
+
+/// Returns the number of bits of a single element lane.
+fn element_bit_count(stream: &PhysicalStream) -> usize {
+    stream
+        .element_fields()
+        .values()
+        .map(|b| b.get() as usize)
+        .sum()
+}
+
+/// Returns the transfer for the provided active element lanes, starting at
+/// lane zero, closing the provided dimensions. Returns an error when the
+/// physical stream cannot indicate that only some element lanes are active.
+///
+/// Below complexity 8, all `strb` bits are driven to the same value, such
+/// that `strb` only marks transfers without active element lanes, and the
+/// active lanes of other transfers are indicated by `endi`.
+fn build_transfer(stream: &PhysicalStream, active: &[&[bool]], last: &[bool]) -> Result<Transfer> {
+    let lanes = stream.element_lanes().get() as usize;
+    let strobe = match active.len() {
+        0 => stream.strb_bit_count() > 0,
+        _ => stream.complexity().allows_lane_strobe() && stream.strb_bit_count() > 0,
+    };
+    if active.len() < lanes && stream.endi_bit_count() == 0 && !strobe {
+        return Err(Error::InvalidArgument(format!(
+            "a transfer with {} of {} element lanes active requires an endi signal, or a strb signal at complexity 8 or higher",
+            active.len(),
+            lanes
+        )));
+    }
+    let element_bits = element_bit_count(stream);
+    let mut data = active.concat();
+    data.resize(lanes * element_bits, false);
+    let mut last = last.to_vec();
+    last.resize(stream.last_bit_count() as usize, false);
+    Ok(Transfer {
+        data,
+        last,
+        stai: from_uint(0, stream.stai_bit_count()),
+        endi: from_uint(active.len().saturating_sub(1), stream.endi_bit_count()),
+        strb: (0..stream.strb_bit_count() as usize)
+            .map(|lane| match stream.complexity().allows_lane_strobe() {
+                true => lane < active.len(),
+                false => !active.is_empty(),
+            })
+            .collect(),
+        user: vec![false; stream.user_bit_count() as usize],
+    })
+}
+
+/// Returns the bits of an unsigned integer, least significant bit first.
+fn from_uint(value: usize, width: WideNonNegative) -> Vec<bool> {
+    (0..width)
+        .map(|idx| idx < 64 && (value >> idx) & 1 == 1)
+        .collect()
+}
+
+/// Returns the unsigned integer of bits, least significant bit first.
+fn to_uint(bits: &[bool]) -> usize {
+    bits.iter()
+        .enumerate()
+        .filter(|(_, &b)| b)
+        .map(|(idx, _)| 1 << idx)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(value: usize, width: WideNonNegative) -> Vec<bool> {
+        from_uint(value, width)
+    }
+
+    #[test]
+    fn encode_decode() -> Result<()> {
+        // Three lanes of 4 bits, two dimensions, complexity 8.
+        let stream = PhysicalStream::try_new(vec![("a", 4)], 3, 2, 8, vec![("u", 1)])?;
+        // [[1, 2, 3, 4], []], [[5]]
+        let elements = vec![
+            Element::new(bits(1, 4)),
+            Element::new(bits(2, 4)),
+            Element::new(bits(3, 4)),
+            Element::new(bits(4, 4)).with_last(vec![true, false]),
+            Element::empty(vec![true, true]),
+            Element::new(bits(5, 4)).with_last(vec![true, true]),
+        ];
+        let transfers = encode(&stream, &elements)?;
+        assert_eq!(transfers.len(), 4);
+        assert_eq!(
+            transfers[0],
+            Transfer::try_new(
+                &stream,
+                [bits(1, 4), bits(2, 4), bits(3, 4)].concat(),
+                vec![false, false],
+                bits(0, 2),
+                bits(2, 2),
+                vec![true, true, true],
+                vec![false],
+            )?
+        );
+        assert_eq!(transfers[1].data(), [bits(4, 4), bits(0, 8)].concat());
+        assert_eq!(transfers[1].last(), &[true, false]);
+        assert_eq!(transfers[1].endi(), bits(0, 2).as_slice());
+        assert_eq!(transfers[1].strb(), &[true, false, false]);
+        assert_eq!(transfers[2].strb(), &[false, false, false]);
+        assert_eq!(transfers[2].last(), &[true, true]);
+        assert_eq!(transfers[3].last(), &[true, true]);
+        assert_eq!(decode(&stream, &transfers)?, elements);
+
+        // Decoding honours stai, endi and strb.
+        let transfer = Transfer::try_new(
+            &stream,
+            [bits(7, 4), bits(8, 4), bits(9, 4)].concat(),
+            vec![true, false],
+            bits(1, 2),
+            bits(2, 2),
+            vec![true, false, true],
+            vec![false],
+        )?;
+        assert_eq!(
+            decode(&stream, &[transfer])?,
+            vec![Element::new(bits(9, 4)).with_last(vec![true, false])]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn uniform_strobe() -> Result<()> {
+        // Three lanes of 4 bits, one dimension, complexity 7.
+        let stream = PhysicalStream::try_new(vec![("a", 4)], 3, 1, 7, vec![])?;
+        // [1, 2], []
+        let elements = vec![
+            Element::new(bits(1, 4)),
+            Element::new(bits(2, 4)).with_last(vec![true]),
+            Element::empty(vec![true]),
+        ];
+        let transfers = encode(&stream, &elements)?;
+        assert_eq!(transfers.len(), 2);
+        // Partial transfers are indicated by endi, not by strb.
+        assert_eq!(transfers[0].endi(), bits(1, 2).as_slice());
+        assert_eq!(transfers[0].strb(), &[true, true, true]);
+        assert_eq!(transfers[1].strb(), &[false, false, false]);
+        assert_eq!(decode(&stream, &transfers)?, elements);
+
+        // Deactivating individual lanes by strb requires complexity 8.
+        let transfer = Transfer::try_new(
+            &stream,
+            [bits(7, 4), bits(8, 4), bits(9, 4)].concat(),
+            vec![true],
+            bits(0, 2),
+            bits(2, 2),
+            vec![true, false, true],
+            vec![],
+        )?;
+        assert!(decode(&stream, &[transfer]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid() -> Result<()> {
+        let stream = PhysicalStream::try_new(vec![("a", 4)], 2, 1, 4, vec![])?;
+        assert!(encode(&stream, &[Element::new(bits(1, 3))]).is_err());
+        assert!(encode(
+            &stream,
+            &[Element::new(bits(1, 4)).with_last(vec![true, true])]
+        )
+        .is_err());
+        assert!(encode(
+            &stream,
+            &[Element::new(bits(1, 4)), Element::empty(vec![true])]
+        )
+        .is_err());
+        assert!(encode(&stream, &[Element::empty(vec![false])]).is_err());
+        let stream = PhysicalStream::try_new(vec![("a", 4)], 2, 0, 4, vec![])?;
+        assert!(encode(&stream, &[Element::new(bits(1, 4))]).is_err());
+        assert_eq!(
+            encode(
+                &stream,
+                &[Element::new(bits(1, 4)), Element::new(bits(2, 4))]
+            )?
+            .len(),
+            1
+        );
+        assert!(Transfer::try_new(
+            &stream,
+            bits(0, 8),
+            vec![],
+            vec![],
+            bits(0, 1),
+            vec![true, true],
+            vec![]
+        )
+        .is_err());
+        Ok(())
+    }
+}
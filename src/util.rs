@@ -1,9 +1,6 @@
 use std::collections::HashSet;
 use std::iter::FromIterator;
 
-use colored::Colorize;
-use log::{Level, Metadata, Record};
-
 use crate::traits::Identify;
 use crate::{Error, Result};
 use crate::{NonNegative, Positive};
@@ -14,6 +11,14 @@ pub(crate) const fn log2_ceil(x: Positive) -> NonNegative {
         - (x.get() - 1).leading_zeros() as NonNegative
 }
 
+/// Returns the 64-bit FNV-1a hash of bytes, which is stable across platforms
+/// and compiler versions.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,34 +150,3 @@ impl<T: Identify> Default for UniquelyNamedBuilder<T> {
         UniquelyNamedBuilder { items: Vec::new() }
     }
 }
-
-/// Simple logger for Tydi.
-pub struct Logger;
-
-impl log::Log for Logger {
-    fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= Level::Debug
-    }
-
-    fn log(&self, record: &Record) {
-        if self.enabled(record.metadata()) {
-            println!(
-                "[{:5}]: {}",
-                {
-                    let lvl = format!("{}", record.level());
-                    let l = lvl.as_str();
-                    match record.level() {
-                        log::Level::Error => l.red(),
-                        log::Level::Warn => l.yellow(),
-                        log::Level::Info => l.white(),
-                        log::Level::Debug => l.green(),
-                        log::Level::Trace => l.bright_black(),
-                    }
-                },
-                record.args()
-            );
-        }
-    }
-
-    fn flush(&self) {}
-}
@@ -0,0 +1,432 @@
+//! Transaction values for logical types.
+//!
+//! A [`Value`] mirrors the structure of a [`LogicalType`], and can be used
+//! to describe the data carried by a logical stream, e.g. for simulation,
+//! stimulus files or software interoperability.
+//!
+//! Element-only parts of a value can be converted to and from the packed
+//! bits of a single element lane, following the physical mapping of the
+//! [`LogicalType`]'s fields: the first field occupies the least significant
+//! bits, and unions place their tag field before their union field.
+//!
+//! [`LogicalType`]: ../logical/enum.LogicalType.html
+//! [`Value`]: ./enum.Value.html
+
+use std::convert::TryInto;
+use std::error;
+
+use indexmap::IndexMap;
+
+use crate::logical::{LogicalType, Union};
+use crate::{Error, Name, NonNegative, PathName, Result, WideNonNegative};
+
+/// A value of a [`LogicalType`].
+///
+/// [`LogicalType`]: ../logical/enum.LogicalType.html
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// The value of a Null type.
+    Null,
+    /// The value of a Bits type, least significant bit first.
+    Bits(Vec<bool>),
+    /// The values of the fields of a Group type.
+    Group(IndexMap<Name, Value>),
+    /// The active variant of a Union type and its value.
+    Union(Name, Box<Value>),
+    /// A sequence of values. The value of a Stream type with dimensionality
+    /// `d` is a sequence nested `d` levels deep, with the element values at
+    /// the innermost level. A Stream with dimensionality zero carries a
+    /// single element.
+    Sequence(Vec<Value>),
+}
+
+impl Value {
+    /// Returns a new Bits value of `width` bits holding the unsigned integer
+    /// `value`. Returns an error if `value` does not fit in `width` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tydi::value::Value;
+    ///
+    /// let value = Value::uint(4, 5)?;
+    /// assert_eq!(value, Value::Bits(vec![true, false, true, false]));
+    /// assert_eq!(value.as_uint(), Some(5));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn uint(width: NonNegative, value: u64) -> Result<Self> {
+        if width < 64 && value >> width != 0 {
+            Err(Error::InvalidArgument(format!(
+                "value {} does not fit in {} bits",
+                value, width
+            )))
+        } else {
+            Ok(Value::Bits(
+                (0..width)
+                    .map(|idx| idx < 64 && (value >> idx) & 1 == 1)
+                    .collect(),
+            ))
+        }
+    }
+
+    /// Returns a new Group value. Returns an error when a name conversion
+    /// fails or when there are duplicate names.
+    pub fn try_new_group(
+        group: impl IntoIterator<
+            Item = (
+                impl TryInto<Name, Error = impl Into<Box<dyn error::Error>>>,
+                Value,
+            ),
+        >,
+    ) -> Result<Self> {
+        let mut map = IndexMap::new();
+        for (name, value) in group {
+            let name = name.try_into().map_err(|e| Error::from(e.into()))?;
+            map.insert(name, value)
+                .map(|_| -> Result<()> { Err(Error::UnexpectedDuplicate) })
+                .transpose()?;
+        }
+        Ok(Value::Group(map))
+    }
+
+    /// Returns a new Union value with the active variant `name`.
+    pub fn try_new_union(
+        name: impl TryInto<Name, Error = impl Into<Box<dyn error::Error>>>,
+        value: Value,
+    ) -> Result<Self> {
+        let name = name.try_into().map_err(|e| Error::from(e.into()))?;
+        Ok(Value::Union(name, Box::new(value)))
+    }
+
+    /// Returns the unsigned integer interpretation of a Bits value, or None
+    /// if this is not a Bits value or it is wider than 64 bits.
+    pub fn as_uint(&self) -> Option<u64> {
+        match self {
+            Value::Bits(bits) if bits.len() <= 64 => Some(
+                bits.iter()
+                    .rev()
+                    .fold(0, |acc, bit| (acc << 1) | u64::from(*bit)),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Verifies that this value is a valid value of the given logical type.
+    pub fn validate(&self, typ: &LogicalType) -> Result<()> {
+        self.validate_at(typ, &PathName::new_empty(), true)
+    }
+
+    /// Validates this value against a logical type. If `streams` is false,
+    /// values for nested streams are not validated.
+    fn validate_at(&self, typ: &LogicalType, path: &PathName, streams: bool) -> Result<()> {
+        let mismatch = |expected: &str| {
+            Err(Error::InvalidArgument(format!(
+                "expected {} value at \"{}\", found {}",
+                expected,
+                path,
+                self.kind()
+            )))
+        };
+        match (typ, self) {
+            (LogicalType::Null, Value::Null) => Ok(()),
+            (LogicalType::Null, _) => mismatch("Null"),
+            (LogicalType::Bits(width), Value::Bits(bits)) => {
+                if bits.len() == width.get() as usize {
+                    Ok(())
+                } else {
+                    Err(Error::InvalidArgument(format!(
+                        "expected {} bits at \"{}\", found {}",
+                        width,
+                        path,
+                        bits.len()
+                    )))
+                }
+            }
+            (LogicalType::Bits(_), _) => mismatch("Bits"),
+            (LogicalType::Group(group), Value::Group(values)) => {
+                if let Some(name) = values
+                    .keys()
+                    .find(|name| group.iter().all(|(field, _)| field != *name))
+                {
+                    return Err(Error::InvalidArgument(format!(
+                        "unexpected field \"{}\" at \"{}\"",
+                        name, path
+                    )));
+                }
+                for (name, field) in group.iter() {
+                    let value = values.get(name).ok_or_else(|| {
+                        Error::InvalidArgument(format!(
+                            "missing field \"{}\" at \"{}\"",
+                            name, path
+                        ))
+                    })?;
+                    value.validate_at(field, &child(path, name), streams)?;
+                }
+                Ok(())
+            }
+            (LogicalType::Group(_), _) => mismatch("Group"),
+            (LogicalType::Union(union), Value::Union(name, value)) => {
+                let (_, variant) = variant(union, name).ok_or_else(|| {
+                    Error::InvalidArgument(format!("unknown variant \"{}\" at \"{}\"", name, path))
+                })?;
+                value.validate_at(variant, &child(path, name), streams)
+            }
+            (LogicalType::Union(_), _) => mismatch("Union"),
+            (LogicalType::Stream(stream), _) if streams => {
+                self.validate_sequence(stream.data(), stream.dimensionality(), path)
+            }
+            (LogicalType::Stream(_), _) => Ok(()),
+        }
+    }
+
+    fn validate_sequence(
+        &self,
+        element: &LogicalType,
+        dimensionality: NonNegative,
+        path: &PathName,
+    ) -> Result<()> {
+        if dimensionality == 0 {
+            self.validate_at(element, path, true)
+        } else {
+            match self {
+                Value::Sequence(values) => values.iter().try_for_each(|value| {
+                    value.validate_sequence(element, dimensionality - 1, path)
+                }),
+                _ => Err(Error::InvalidArgument(format!(
+                    "expected Sequence value at \"{}\", found {}",
+                    path,
+                    self.kind()
+                ))),
+            }
+        }
+    }
+
+    /// Packs the element-only part of this value into the bits of a single
+    /// element lane of the given logical type, least significant bit first.
+    ///
+    /// Nested streams are carried by other physical streams and do not
+    /// contribute any bits. The number of bits returned is equal to the sum
+    /// of the bit counts of the fields of the logical type.
+    pub fn to_element_bits(&self, typ: &LogicalType) -> Result<Vec<bool>> {
+        self.validate_at(typ, &PathName::new_empty(), false)?;
+        let mut bits = Vec::new();
+        self.pack(typ, &mut bits)?;
+        Ok(bits)
+    }
+
+    /// Unpacks the bits of a single element lane into a value of the given
+    /// logical type. This is the inverse of [`to_element_bits`].
+    ///
+    /// Nested streams are carried by other physical streams, and unpack to
+    /// an empty Sequence.
+    ///
+    /// [`to_element_bits`]: #method.to_element_bits
+    pub fn from_element_bits(typ: &LogicalType, bits: &[bool]) -> Result<Self> {
+        let expected = field_bits(typ);
+        if bits.len() != expected as usize {
+            return Err(Error::InvalidArgument(format!(
+                "expected {} element bits, found {}",
+                expected,
+                bits.len()
+            )));
+        }
+        let mut offset = 0;
+        Value::unpack(typ, bits, &mut offset)
+    }
+
+    fn pack(&self, typ: &LogicalType, bits: &mut Vec<bool>) -> Result<()> {
+        match (typ, self) {
+            (LogicalType::Null, _) | (LogicalType::Stream(_), _) => (),
+            (LogicalType::Bits(_), Value::Bits(value)) => bits.extend(value),
+            (LogicalType::Group(group), Value::Group(values)) => {
+                for (name, field) in group.iter() {
+                    values[name].pack(field, bits)?;
+                }
+            }
+            (LogicalType::Union(union), Value::Union(name, value)) => {
+                let (index, variant_type) = variant(union, name).unwrap();
+                if let Some((_, tag_bits)) = union.tag() {
+                    if let Value::Bits(tag) =
+                        Value::uint(tag_bits.get() as NonNegative, index as u64)?
+                    {
+                        bits.extend(tag);
+                    }
+                }
+                let width = union_bits(typ) as usize;
+                let mut variant = Vec::new();
+                value.pack(variant_type, &mut variant)?;
+                if variant.len() > width {
+                    return Err(Error::InvalidArgument(format!(
+                        "variant \"{}\" requires {} bits, but the union field is {} bits",
+                        name,
+                        variant.len(),
+                        width
+                    )));
+                }
+                variant.resize(width, false);
+                bits.extend(variant);
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    fn unpack(typ: &LogicalType, bits: &[bool], offset: &mut usize) -> Result<Self> {
+        Ok(match typ {
+            LogicalType::Null => Value::Null,
+            LogicalType::Stream(_) => Value::Sequence(vec![]),
+            LogicalType::Bits(width) => {
+                let width = width.get() as usize;
+                let value = Value::Bits(bits[*offset..*offset + width].to_vec());
+                *offset += width;
+                value
+            }
+            LogicalType::Group(group) => Value::Group(
+                group
+                    .iter()
+                    .map(|(name, field)| Ok((name.clone(), Value::unpack(field, bits, offset)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            LogicalType::Union(union) => {
+                let index = match union.tag() {
+                    Some((_, tag_bits)) => {
+                        let tag_bits = tag_bits.get() as usize;
+                        let tag = Value::Bits(bits[*offset..*offset + tag_bits].to_vec());
+                        *offset += tag_bits;
+                        tag.as_uint().unwrap() as usize
+                    }
+                    None => 0,
+                };
+                let (name, variant) = union.iter().nth(index).ok_or_else(|| {
+                    Error::InvalidArgument(format!("invalid union tag {}", index))
+                })?;
+                let width = union_bits(typ) as usize;
+                let mut variant_offset = 0;
+                let value = Value::unpack(
+                    variant,
+                    &bits[*offset..*offset + width],
+                    &mut variant_offset,
+                )?;
+                *offset += width;
+                Value::Union(name.clone(), Box::new(value))
+            }
+        })
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Null => "Null",
+            Value::Bits(_) => "Bits",
+            Value::Group(_) => "Group",
+            Value::Union(_, _) => "Union",
+            Value::Sequence(_) => "Sequence",
+        }
+    }
+}
+
+/// Returns the index and type of the variant of a union with the given name.
+fn variant<'a>(union: &'a Union, name: &Name) -> Option<(usize, &'a LogicalType)> {
+    union
+        .iter()
+        .enumerate()
+        .find(|(_, (variant, _))| *variant == name)
+        .map(|(index, (_, typ))| (index, typ))
+}
+
+/// Returns the path name of a child of the given path.
+fn child(path: &PathName, name: &Name) -> PathName {
+    let mut child = path.clone();
+    child.push(name.clone());
+    child
+}
+
+/// Returns the total bit count of the fields of a logical type.
+fn field_bits(typ: &LogicalType) -> WideNonNegative {
+    typ.fields().values().map(|b| b.get()).sum()
+}
+
+/// Returns the bit count of the union field of a Union type.
+fn union_bits(typ: &LogicalType) -> WideNonNegative {
+    typ.fields()
+        .iter()
+        .find(|(path_name, _)| path_name.last().map(|n| n.as_ref()) == Some("union"))
+        .map(|(_, b)| b.get())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logical::tests::{elements, streams};
+
+    use super::*;
+
+    #[test]
+    fn uint() -> Result<()> {
+        assert_eq!(Value::uint(3, 6)?, Value::Bits(vec![false, true, true]));
+        assert_eq!(Value::uint(3, 6)?.as_uint(), Some(6));
+        assert!(Value::uint(2, 4).is_err());
+        assert_eq!(Value::Group(IndexMap::new()).as_uint(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn validate() -> Result<()> {
+        let group = Value::try_new_group(vec![
+            ("c", Value::uint(42, 1)?),
+            ("d", Value::uint(1337, 2)?),
+        ])?;
+        group.validate(&elements::group())?;
+        assert!(Value::uint(8, 1)?.validate(&elements::group()).is_err());
+        assert!(Value::try_new_group(vec![("c", Value::uint(42, 1)?)])?
+            .validate(&elements::group())
+            .is_err());
+
+        let stream = LogicalType::from(crate::logical::Stream::new(
+            elements::prim(8),
+            crate::PositiveReal::new(1.).unwrap(),
+            2,
+            crate::logical::Synchronicity::Sync,
+            1,
+            crate::logical::Direction::Forward,
+            None,
+            false,
+        ));
+        Value::Sequence(vec![
+            Value::Sequence(vec![Value::uint(8, 1)?, Value::uint(8, 2)?]),
+            Value::Sequence(vec![]),
+        ])
+        .validate(&stream)?;
+        assert!(Value::Sequence(vec![Value::uint(8, 1)?])
+            .validate(&stream)
+            .is_err());
+        Value::uint(8, 3)?.validate(&streams::prim(8))?;
+        Ok(())
+    }
+
+    #[test]
+    fn element_bits() -> Result<()> {
+        let typ = LogicalType::try_new_group(vec![
+            ("a", LogicalType::try_new_bits(2)?),
+            (
+                "b",
+                LogicalType::try_new_union(vec![
+                    ("x", LogicalType::try_new_bits(3)?),
+                    ("y", LogicalType::Null),
+                    ("z", LogicalType::try_new_bits(1)?),
+                ])?,
+            ),
+            ("s", streams::prim(4)),
+        ])?;
+        let value = Value::try_new_group(vec![
+            ("a", Value::uint(2, 1)?),
+            ("b", Value::try_new_union("z", Value::uint(1, 1)?)?),
+            ("s", Value::Sequence(vec![])),
+        ])?;
+        let bits = value.to_element_bits(&typ)?;
+        // a = 01, tag = 10 (z), union = 001
+        assert_eq!(bits, vec![true, false, false, true, true, false, false]);
+        assert_eq!(Value::from_element_bits(&typ, &bits)?, value);
+        assert!(Value::from_element_bits(&typ, &bits[1..]).is_err());
+        Ok(())
+    }
+}
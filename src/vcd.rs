@@ -0,0 +1,310 @@
+//! Value Change Dump traces of physical streams.
+//!
+//! A [`Trace`] records the signals of physical streams clock cycle by clock
+//! cycle, and renders them as a Value Change Dump (VCD) file, e.g. to inspect
+//! the transfers of [`encode`] in a waveform viewer such as GTKWave, or to
+//! compare them against the waveforms of an RTL simulation.
+//!
+//! Signals are named like the ports of the canonical representation, i.e.
+//! `<stream>_valid`, `<stream>_data`, etc., and are sampled at the rising
+//! edges of a `clk` signal with a period of 10 time units. Custom sideband
+//! signals are not traced.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tydi::physical::PhysicalStream;
+//! use tydi::transfer::{encode, Element};
+//! use tydi::vcd::Trace;
+//!
+//! let stream = PhysicalStream::try_new(vec![("a", 2)], 1, 1, 4, vec![])?;
+//! let transfers = encode(&stream, &[Element::new(vec![true, false]).with_last(vec![true])])?;
+//!
+//! let mut trace = Trace::new().with_stream("a", &stream)?;
+//! trace.record("a", true, true, transfers.first())?;
+//!
+//! let vcd = trace.to_vcd();
+//! assert!(vcd.contains("$var wire 2 $ a_data [1:0] $end"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`Trace`]: ./struct.Trace.html
+//! [`encode`]: ../transfer/fn.encode.html
+
+use indexmap::IndexMap;
+
+use crate::physical::{PhysicalStream, Width};
+use crate::transfer::Transfer;
+use crate::{Error, Identify, NamingPolicy, Result};
+
+/// The state of a physical stream in a single clock cycle.
+#[derive(Debug, Clone, PartialEq)]
+struct Cycle {
+    valid: bool,
+    ready: bool,
+    /// The signals driven by the source, if any.
+    transfer: Option<Transfer>,
+}
+
+/// A traced physical stream.
+#[derive(Debug, Clone, PartialEq)]
+struct Traced {
+    stream: PhysicalStream,
+    cycles: Vec<Cycle>,
+}
+
+/// A trace of the signals of physical streams.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trace {
+    /// The traced streams, by name.
+    streams: IndexMap<String, Traced>,
+}
+
+impl Trace {
+    /// Returns a new trace without streams.
+    pub fn new() -> Self {
+        Trace::default()
+    }
+
+    /// Returns this trace, also tracing a physical stream with the given
+    /// name. Returns an error if the name is already traced.
+    pub fn with_stream(mut self, name: impl Into<String>, stream: &PhysicalStream) -> Result<Self> {
+        let name = name.into();
+        if self.streams.contains_key(&name) {
+            return Err(Error::UnexpectedDuplicate);
+        }
+        self.streams.insert(
+            name,
+            Traced {
+                stream: stream.clone(),
+                cycles: Vec::new(),
+            },
+        );
+        Ok(self)
+    }
+
+    /// Records the next clock cycle of a traced stream, with the state of
+    /// its handshake and the signals driven by its source. Signals without a
+    /// transfer hold their previous values.
+    ///
+    /// Returns an error if the stream is not traced, or if the transfer does
+    /// not match the signal widths of the physical stream.
+    pub fn record(
+        &mut self,
+        name: &str,
+        valid: bool,
+        ready: bool,
+        transfer: Option<&Transfer>,
+    ) -> Result<()> {
+        let traced = self
+            .streams
+            .get_mut(name)
+            .ok_or_else(|| Error::InvalidArgument(format!("stream {} is not traced", name)))?;
+        let transfer = transfer
+            .map(|t| {
+                Transfer::try_new(
+                    &traced.stream,
+                    t.data().to_vec(),
+                    t.last().to_vec(),
+                    t.stai().to_vec(),
+                    t.endi().to_vec(),
+                    t.strb().to_vec(),
+                    t.user().to_vec(),
+                )
+            })
+            .transpose()?;
+        traced.cycles.push(Cycle {
+            valid,
+            ready,
+            transfer,
+        });
+        Ok(())
+    }
+
+    /// Returns the number of recorded clock cycles, i.e. the number of
+    /// cycles of the longest traced stream.
+    pub fn cycles(&self) -> usize {
+        self.streams
+            .values()
+            .map(|traced| traced.cycles.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Renders this trace as a Value Change Dump.
+    ///
+    /// Streams of which fewer cycles are recorded than of others hold the
+    /// values of their last recorded cycle.
+    pub fn to_vcd(&self) -> String {
+        let naming = NamingPolicy::default();
+        // The name, width and cycle values of every signal.
+        let mut signals: Vec<(String, Width, Vec<Vec<bool>>)> = Vec::new();
+        for (name, traced) in &self.streams {
+            for signal in traced.stream.signal_list().into_iter() {
+                if !SIGNALS.contains(&signal.identifier()) {
+                    continue;
+                }
+                let mut held = vec![false; signal.bit_count() as usize];
+                let values = traced
+                    .cycles
+                    .iter()
+                    .map(|cycle| {
+                        if let Some(value) = value(signal.identifier(), cycle) {
+                            held = value;
+                        }
+                        held.clone()
+                    })
+                    .collect();
+                signals.push((
+                    naming.join(&[name, signal.identifier()]),
+                    signal.width(),
+                    values,
+                ));
+            }
+        }
+
+        let mut result = "$timescale 1ns $end\n$scope module tydi $end\n".to_string();
+        result.push_str(&format!("$var wire 1 {} clk $end\n", code(0)));
+        for (idx, (name, width, _)) in signals.iter().enumerate() {
+            result.push_str(&match width {
+                Width::Scalar => format!("$var wire 1 {} {} $end\n", code(idx + 1), name),
+                Width::Vector(w) => format!(
+                    "$var wire {} {} {} [{}:0] $end\n",
+                    w,
+                    code(idx + 1),
+                    name,
+                    w - 1
+                ),
+            });
+        }
+        result.push_str("$upscope $end\n$enddefinitions $end\n");
+
+        let mut previous: Vec<Option<&Vec<bool>>> = vec![None; signals.len()];
+        for cycle in 0..self.cycles() {
+            result.push_str(&format!("#{}\n0{}\n", cycle * 10, code(0)));
+            for (idx, (_, width, values)) in signals.iter().enumerate() {
+                if let Some(value) = values.get(cycle).or_else(|| values.last()) {
+                    if previous[idx] != Some(value) {
+                        result.push_str(&change(*width, value, &code(idx + 1)));
+                        previous[idx] = Some(value);
+                    }
+                }
+            }
+            result.push_str(&format!("#{}\n1{}\n", cycle * 10 + 5, code(0)));
+        }
+        result.push_str(&format!("#{}\n", self.cycles() * 10));
+        result
+    }
+}
+
+/// The names of the traced signals of a physical stream.
+const SIGNALS: &[&str] = &[
+    "valid", "ready", "data", "last", "stai", "endi", "strb", "user",
+];
+
+/// Returns the value of a signal of a stream in a clock cycle, or None if it
+/// holds its previous value.
+fn value(signal: &str, cycle: &Cycle) -> Option<Vec<bool>> {
+    match (signal, &cycle.transfer) {
+        ("valid", _) => Some(vec![cycle.valid]),
+        ("ready", _) => Some(vec![cycle.ready]),
+        ("data", Some(t)) => Some(t.data().to_vec()),
+        ("last", Some(t)) => Some(t.last().to_vec()),
+        ("stai", Some(t)) => Some(t.stai().to_vec()),
+        ("endi", Some(t)) => Some(t.endi().to_vec()),
+        ("strb", Some(t)) => Some(t.strb().to_vec()),
+        ("user", Some(t)) => Some(t.user().to_vec()),
+        _ => None,
+    }
+}
+
+/// Returns the identifier code of the signal with the given index, using the
+/// printable ASCII characters.
+fn code(mut idx: usize) -> String {
+    let mut result = String::new();
+    loop {
+        result.push((b'!' + (idx % 94) as u8) as char);
+        idx /= 94;
+        if idx == 0 {
+            return result;
+        }
+        idx -= 1;
+    }
+}
+
+/// Returns the value change of a signal, least significant bit first.
+fn change(width: Width, value: &[bool], code: &str) -> String {
+    let bits = value
+        .iter()
+        .rev()
+        .map(|&b| if b { '1' } else { '0' })
+        .collect::<String>();
+    match width {
+        Width::Scalar => format!("{}{}\n", bits, code),
+        Width::Vector(_) => format!("b{} {}\n", bits, code),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer::{encode, Element};
+
+    #[test]
+    fn trace() -> Result<()> {
+        let stream = PhysicalStream::try_new(vec![("a", 2)], 2, 1, 4, vec![])?;
+        let transfers = encode(
+            &stream,
+            &[
+                Element::new(vec![true, false]),
+                Element::new(vec![false, true]),
+                Element::new(vec![true, true]).with_last(vec![true]),
+            ],
+        )?;
+        let mut trace = Trace::new().with_stream("x_a", &stream)?;
+        trace.record("x_a", false, true, None)?;
+        trace.record("x_a", true, true, transfers.first())?;
+        trace.record("x_a", true, false, transfers.get(1))?;
+        trace.record("x_a", true, true, transfers.get(1))?;
+        assert_eq!(trace.cycles(), 4);
+
+        let vcd = trace.to_vcd();
+        for line in &[
+            "$var wire 1 ! clk $end",
+            "$var wire 1 \" x_a_valid $end",
+            "$var wire 1 # x_a_ready $end",
+            "$var wire 4 $ x_a_data [3:0] $end",
+            "$var wire 1 % x_a_last [0:0] $end",
+            "$var wire 1 & x_a_endi [0:0] $end",
+            "$var wire 2 ' x_a_strb [1:0] $end",
+            "$enddefinitions $end",
+            "b1001 $",
+            "b1 &",
+            "b11 '",
+            "b0011 $",
+            "#35",
+            "#40",
+        ] {
+            assert!(vcd.lines().any(|l| l == *line), "missing {}", line);
+        }
+        // The data of the third cycle is unchanged, so it is not dumped again.
+        assert_eq!(vcd.matches("b0011 $").count(), 1);
+        // The partial transfer is indicated by endi, strb stays high.
+        assert_eq!(vcd.matches("b0 &\n").count(), 2);
+        assert_eq!(vcd.matches("b11 '").count(), 1);
+        assert_eq!(vcd.matches("0\"\n").count(), 1);
+
+        assert!(Trace::new()
+            .with_stream("a", &stream)?
+            .with_stream("a", &stream)
+            .is_err());
+        assert!(trace.record("y", true, true, None).is_err());
+        let other = PhysicalStream::try_new(vec![("a", 3)], 1, 0, 1, vec![])?;
+        let transfers = encode(&other, &[Element::new(vec![true; 3])])?;
+        assert!(trace.record("x_a", true, true, transfers.first()).is_err());
+        assert_eq!(code(0), "!");
+        assert_eq!(code(93), "~");
+        assert_eq!(code(94), "!!");
+        Ok(())
+    }
+}
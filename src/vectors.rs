@@ -0,0 +1,669 @@
+//! Textual test vectors for physical streams.
+//!
+//! Test vectors describe the [`Element`]s carried by a physical stream, one
+//! element per row, with a column for every element field and a `last`
+//! column with the dimension boundaries the element closes. Since the same
+//! vectors can be read by every tool that stimulates or checks a stream,
+//! they can be shared between simulators and software models.
+//!
+//! Field values are unsigned integers, written in decimal for fields of up
+//! to 64 bits and in hexadecimal with a `0x` prefix otherwise. Both notations
+//! are accepted when reading. The `last` column holds one character per
+//! dimension, most significant (outermost) dimension first, e.g. `01` closes
+//! only the innermost of two dimensions. It is omitted for streams without
+//! dimensions. The name of the column of a field is its path name, or
+//! `value` for an element that is a single unnamed field.
+//!
+//! Elements without any field values are markers of empty sequences, see
+//! [`Element::empty`]. Streams without element fields therefore have no
+//! empty sequence markers.
+//!
+//! Vectors are written as CSV, and as JSON if the `serde_json` dependency is
+//! enabled, e.g. by the `cli` feature.
+//!
+//! Elements can also be written as transfer vectors, with the signal values of
+//! every transfer that carries them, which are simple enough to be read by
+//! HDL testbenches, see [`write_transfers`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tydi::physical::PhysicalStream;
+//! use tydi::transfer::Element;
+//! use tydi::vectors::{read_csv, write_csv};
+//!
+//! // Elements with fields a and b of 4 bits, with one dimension.
+//! let stream = PhysicalStream::try_new(vec![("a", 4), ("b", 4)], 1, 1, 4, vec![])?;
+//!
+//! let csv = "a,b,last\n1,2,0\n3,15,1\n";
+//! let elements = read_csv(&stream, csv)?;
+//! assert_eq!(elements[0], Element::new(vec![
+//!     true, false, false, false, false, true, false, false
+//! ]).with_last(vec![false]));
+//! assert_eq!(elements[1].last(), &[true]);
+//!
+//! assert_eq!(write_csv(&stream, &elements)?, csv);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`Element`]: ../transfer/struct.Element.html
+//! [`Element::empty`]: ../transfer/struct.Element.html#method.empty
+
+use crate::physical::PhysicalStream;
+use crate::transfer::{Element, Transfer};
+use crate::{Error, Result, ResultExt};
+
+/// The name of the column with the dimension boundaries closed by an
+/// element.
+const LAST: &str = "last";
+
+/// An element field of a physical stream.
+struct Column {
+    /// The name of the column.
+    name: String,
+    /// The offset of the field in the element bits.
+    offset: usize,
+    /// The number of bits of the field.
+    width: usize,
+}
+
+/// Returns the columns of the element fields of a physical stream, in the
+/// order of the element layout.
+fn columns(stream: &PhysicalStream) -> Result<Vec<Column>> {
+    stream
+        .element_fields()
+        .layout()
+        .map(|(path, offset, width)| {
+            let name = if path.is_empty() {
+                "value".to_string()
+            } else {
+                path.to_string()
+            };
+            if name == LAST {
+                Err(Error::InvalidArgument(format!(
+                    "element field \"{}\" conflicts with the column of dimension boundaries",
+                    LAST
+                )))
+            } else {
+                Ok(Column {
+                    name,
+                    offset: offset as usize,
+                    width: width.get() as usize,
+                })
+            }
+        })
+        .collect()
+}
+
+/// Verifies that an element has the element bit count and dimensionality of
+/// a physical stream.
+fn validate(stream: &PhysicalStream, element: &Element) -> Result<()> {
+    if let Some(data) = element.data() {
+        if data.len() as u64 != stream.element_bit_count() {
+            return Err(Error::InvalidArgument(format!(
+                "element has {} bits, expected {}",
+                data.len(),
+                stream.element_bit_count()
+            )));
+        }
+    }
+    if element.last().len() != stream.dimensionality() as usize {
+        return Err(Error::InvalidArgument(format!(
+            "element closes {} dimensions, expected {}",
+            element.last().len(),
+            stream.dimensionality()
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the text of an unsigned value, least significant bit first.
+fn format_value(bits: &[bool]) -> String {
+    if bits.len() <= 64 {
+        bits.iter()
+            .rev()
+            .fold(0u64, |acc, bit| (acc << 1) | u64::from(*bit))
+            .to_string()
+    } else {
+        let digits = bits
+            .chunks(4)
+            .rev()
+            .map(|nibble| {
+                let digit = nibble
+                    .iter()
+                    .rev()
+                    .fold(0u32, |acc, bit| (acc << 1) | u32::from(*bit));
+                std::char::from_digit(digit, 16).unwrap()
+            })
+            .collect::<String>();
+        let digits = digits.trim_start_matches('0');
+        format!("0x{}", if digits.is_empty() { "0" } else { digits })
+    }
+}
+
+/// Parses the text of an unsigned value into a number of bits, least
+/// significant bit first.
+fn parse_value(text: &str, width: usize) -> Result<Vec<bool>> {
+    let invalid = || Error::ParsingError(format!("invalid value \"{}\"", text));
+    let mut bits = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(digits) => {
+            if digits.is_empty() {
+                return Err(invalid());
+            }
+            let mut bits = Vec::with_capacity(digits.len() * 4);
+            for c in digits.chars().rev() {
+                let digit = c.to_digit(16).ok_or_else(invalid)?;
+                bits.extend((0..4).map(|i| (digit >> i) & 1 == 1));
+            }
+            bits
+        }
+        None => {
+            let value = text.parse::<u64>().map_err(|_| invalid())?;
+            (0..64).map(|i| (value >> i) & 1 == 1).collect()
+        }
+    };
+    if bits[width.min(bits.len())..].iter().any(|&bit| bit) {
+        return Err(Error::ParsingError(format!(
+            "value {} does not fit in {} bits",
+            text, width
+        )));
+    }
+    bits.resize(width, false);
+    Ok(bits)
+}
+
+/// Returns the text of the dimension boundaries closed by an element.
+fn format_last(last: &[bool]) -> String {
+    last.iter()
+        .rev()
+        .map(|&b| if b { '1' } else { '0' })
+        .collect()
+}
+
+/// Parses the text of the dimension boundaries closed by an element.
+fn parse_last(text: &str, dimensionality: usize) -> Result<Vec<bool>> {
+    if text.len() != dimensionality || !text.chars().all(|c| c == '0' || c == '1') {
+        return Err(Error::ParsingError(format!(
+            "invalid {} \"{}\", expected {} binary digit(s)",
+            LAST, text, dimensionality
+        )));
+    }
+    Ok(text.chars().rev().map(|c| c == '1').collect())
+}
+
+/// Returns the element with the given field values and dimension boundaries,
+/// where a value of None indicates an absent field.
+fn element(columns: &[Column], values: &[Option<Vec<bool>>], last: Vec<bool>) -> Result<Element> {
+    if !columns.is_empty() && values.iter().all(Option::is_none) {
+        return Ok(Element::empty(last));
+    }
+    let mut data = Vec::new();
+    for (column, value) in columns.iter().zip(values) {
+        match value {
+            Some(bits) => data.extend(bits),
+            None => {
+                return Err(Error::ParsingError(format!(
+                    "missing value of field {}",
+                    column.name
+                )))
+            }
+        }
+    }
+    Ok(Element::new(data).with_last(last))
+}
+
+/// Returns the field values of an element, or None for all fields if it is
+/// the marker of an empty sequence.
+fn values<'a>(
+    columns: &'a [Column],
+    element: &'a Element,
+) -> impl Iterator<Item = (&'a Column, Option<&'a [bool]>)> {
+    columns.iter().map(move |column| {
+        (
+            column,
+            element
+                .data()
+                .map(|data| &data[column.offset..column.offset + column.width]),
+        )
+    })
+}
+
+/// Writes elements of a physical stream as CSV, with a header row.
+///
+/// Returns an error when an element does not have the element bit count or
+/// dimensionality of the physical stream.
+pub fn write_csv(stream: &PhysicalStream, elements: &[Element]) -> Result<String> {
+    let columns = columns(stream)?;
+    let dimensionality = stream.dimensionality() as usize;
+    let mut header = columns.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+    if dimensionality > 0 {
+        header.push(LAST.to_string());
+    }
+    let mut result = format!("{}\n", header.join(","));
+    for element in elements {
+        validate(stream, element)?;
+        let mut row = values(&columns, element)
+            .map(|(_, value)| value.map(format_value).unwrap_or_default())
+            .collect::<Vec<_>>();
+        if dimensionality > 0 {
+            row.push(format_last(element.last()));
+        }
+        result.push_str(&format!("{}\n", row.join(",")));
+    }
+    Ok(result)
+}
+
+/// The header row of CSV vectors.
+struct Header {
+    /// The index of the cell of every element field.
+    fields: Vec<usize>,
+    /// The index of the cell of the dimension boundaries, if any.
+    last: Option<usize>,
+    /// The number of cells of a row.
+    len: usize,
+}
+
+impl Header {
+    /// Parses the header row of CSV vectors of a physical stream.
+    fn parse(columns: &[Column], dimensionality: usize, row: &str) -> Result<Self> {
+        let names = row.split(',').map(str::trim).collect::<Vec<_>>();
+        let position = |name: &str| {
+            names
+                .iter()
+                .position(|&n| n == name)
+                .ok_or_else(|| Error::ParsingError(format!("missing column {}", name)))
+        };
+        let fields = columns
+            .iter()
+            .map(|column| position(&column.name))
+            .collect::<Result<Vec<_>>>()?;
+        let last = if dimensionality > 0 {
+            Some(position(LAST)?)
+        } else {
+            None
+        };
+        if names.len() != fields.len() + last.iter().len() {
+            let unknown = names
+                .iter()
+                .find(|&&n| columns.iter().all(|c| c.name != n) && (n != LAST || last.is_none()))
+                .map_or_else(
+                    || "duplicate column".to_string(),
+                    |n| format!("unknown column {}", n),
+                );
+            return Err(Error::ParsingError(unknown));
+        }
+        Ok(Header {
+            fields,
+            last,
+            len: names.len(),
+        })
+    }
+
+    /// Parses a row of CSV vectors of a physical stream into an element.
+    fn element(&self, columns: &[Column], dimensionality: usize, row: &str) -> Result<Element> {
+        let cells = row.split(',').map(str::trim).collect::<Vec<_>>();
+        if cells.len() != self.len {
+            return Err(Error::ParsingError(format!(
+                "found {} cells, expected {}",
+                cells.len(),
+                self.len
+            )));
+        }
+        let values = columns
+            .iter()
+            .zip(&self.fields)
+            .map(|(column, &idx)| match cells[idx] {
+                "" => Ok(None),
+                text => parse_value(text, column.width).map(Some),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let last = match self.last {
+            Some(idx) => parse_last(cells[idx], dimensionality)?,
+            None => Vec::new(),
+        };
+        element(columns, &values, last)
+    }
+}
+
+/// Reads elements of a physical stream from CSV.
+///
+/// The first row is a header with the names of the columns, which may be in
+/// any order. Empty lines and lines starting with `#` are ignored.
+pub fn read_csv(stream: &PhysicalStream, input: &str) -> Result<Vec<Element>> {
+    let columns = columns(stream)?;
+    let dimensionality = stream.dimensionality() as usize;
+    let note = |line: usize| format!("while reading line {}", line);
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    let (line, row) = lines
+        .next()
+        .ok_or_else(|| Error::ParsingError("missing header row".to_string()))?;
+    let header = Header::parse(&columns, dimensionality, row).with_context(|| note(line))?;
+    lines
+        .map(|(line, row)| {
+            header
+                .element(&columns, dimensionality, row)
+                .with_context(|| note(line))
+        })
+        .collect()
+}
+
+/// Writes transfers of a physical stream as transfer vectors, one transfer
+/// per line.
+///
+/// Every line holds the signals of the stream that are present, in the order
+/// `data`, `last`, `stai`, `endi`, `strb` and `user`, in binary with the most
+/// significant bit first, separated by spaces. The data bits of element
+/// lanes that are not active are written as `-`, as their value does not
+/// matter.
+///
+/// # Examples
+///
+/// ```rust
+/// use tydi::physical::PhysicalStream;
+/// use tydi::transfer::{encode, Element};
+/// use tydi::vectors::{read_transfers, write_transfers};
+///
+/// // Two element lanes of 2 bits, with one dimension.
+/// let stream = PhysicalStream::try_new(vec![("a", 2)], 2, 1, 4, vec![])?;
+/// let elements = vec![Element::new(vec![true, false]).with_last(vec![true])];
+///
+/// let transfers = encode(&stream, &elements)?;
+/// let vectors = write_transfers(&stream, &transfers)?;
+/// // data, last, endi and strb, as there is no stai signal at complexity 4.
+/// assert_eq!(vectors, "--01 1 0 11\n");
+/// assert_eq!(read_transfers(&stream, &vectors)?, transfers);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn write_transfers(stream: &PhysicalStream, transfers: &[Transfer]) -> Result<String> {
+    let binary = |bits: &[bool]| -> String {
+        bits.iter()
+            .rev()
+            .map(|&b| if b { '1' } else { '0' })
+            .collect()
+    };
+    let mut result = String::new();
+    for transfer in transfers {
+        let transfer = Transfer::try_new(
+            stream,
+            transfer.data().to_vec(),
+            transfer.last().to_vec(),
+            transfer.stai().to_vec(),
+            transfer.endi().to_vec(),
+            transfer.strb().to_vec(),
+            transfer.user().to_vec(),
+        )?;
+        let mut data = vec!['-'; transfer.data().len()];
+        for lane in transfer.active_lanes(stream) {
+            for idx in lane.data() {
+                data[idx as usize] = if transfer.data()[idx as usize] {
+                    '1'
+                } else {
+                    '0'
+                };
+            }
+        }
+        let signals = [
+            data.into_iter().rev().collect::<String>(),
+            binary(transfer.last()),
+            binary(transfer.stai()),
+            binary(transfer.endi()),
+            binary(transfer.strb()),
+            binary(transfer.user()),
+        ]
+        .iter()
+        .filter(|signal| !signal.is_empty())
+        .cloned()
+        .collect::<Vec<_>>();
+        result.push_str(&format!("{}\n", signals.join(" ")));
+    }
+    Ok(result)
+}
+
+/// Reads transfers of a physical stream from transfer vectors, see
+/// [`write_transfers`]. Bits written as `-` are read as low bits.
+///
+/// Empty lines and lines starting with `#` are ignored.
+pub fn read_transfers(stream: &PhysicalStream, input: &str) -> Result<Vec<Transfer>> {
+    let widths = [
+        stream.data_bit_count(),
+        stream.last_bit_count(),
+        stream.stai_bit_count(),
+        stream.endi_bit_count(),
+        stream.strb_bit_count(),
+        stream.user_bit_count(),
+    ];
+    let present = widths.iter().filter(|&&w| w > 0).count();
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line, row)| {
+            let words = row.split_whitespace().collect::<Vec<_>>();
+            if words.len() != present {
+                return Err(Error::ParsingError(format!(
+                    "found {} signals, expected {}",
+                    words.len(),
+                    present
+                )));
+            }
+            let mut words = words.into_iter();
+            let mut signals = widths.iter().map(|&width| {
+                if width == 0 {
+                    return Ok(Vec::new());
+                }
+                let word = words.next().unwrap();
+                word.chars()
+                    .rev()
+                    .map(|c| match c {
+                        '1' => Ok(true),
+                        '0' | '-' => Ok(false),
+                        _ => Err(Error::ParsingError(format!("invalid bit {}", c))),
+                    })
+                    .collect::<Result<Vec<_>>>()
+            });
+            let mut next = || signals.next().unwrap();
+            Transfer::try_new(stream, next()?, next()?, next()?, next()?, next()?, next()?)
+                .with_context(|| format!("while reading line {}", line))
+        })
+        .collect()
+}
+
+/// Writes elements of a physical stream as a JSON array with an object per
+/// element, on a line of its own. Field values of up to 64 bits are numbers,
+/// wider values are strings.
+///
+/// Returns an error when an element does not have the element bit count or
+/// dimensionality of the physical stream.
+#[cfg(feature = "serde_json")]
+pub fn write_json(stream: &PhysicalStream, elements: &[Element]) -> Result<String> {
+    use serde_json::{Map, Value};
+
+    let columns = columns(stream)?;
+    let mut objects = Vec::with_capacity(elements.len());
+    for element in elements {
+        validate(stream, element)?;
+        let mut object = Map::new();
+        for (column, value) in values(&columns, element) {
+            if let Some(value) = value {
+                let text = format_value(value);
+                object.insert(
+                    column.name.clone(),
+                    match text.parse::<u64>() {
+                        Ok(number) => Value::from(number),
+                        Err(_) => Value::from(text),
+                    },
+                );
+            }
+        }
+        if stream.dimensionality() > 0 {
+            object.insert(LAST.to_string(), Value::from(format_last(element.last())));
+        }
+        objects.push(format!("  {}", Value::Object(object)));
+    }
+    Ok(if objects.is_empty() {
+        "[]\n".to_string()
+    } else {
+        format!("[\n{}\n]\n", objects.join(",\n"))
+    })
+}
+
+/// Reads elements of a physical stream from a JSON array with an object per
+/// element. Field values are numbers or strings.
+#[cfg(feature = "serde_json")]
+pub fn read_json(stream: &PhysicalStream, input: &str) -> Result<Vec<Element>> {
+    let columns = columns(stream)?;
+    let dimensionality = stream.dimensionality() as usize;
+    match serde_json::from_str(input) {
+        Ok(serde_json::Value::Array(array)) => array
+            .iter()
+            .enumerate()
+            .map(|(idx, object)| {
+                json_element(&columns, dimensionality, object)
+                    .with_context(|| format!("while reading element {}", idx))
+            })
+            .collect(),
+        Ok(_) => Err(Error::ParsingError("expected an array".to_string())),
+        Err(e) => Err(Error::ParsingError(e.to_string())),
+    }
+}
+
+/// Parses a JSON object of a physical stream into an element.
+#[cfg(feature = "serde_json")]
+fn json_element(
+    columns: &[Column],
+    dimensionality: usize,
+    object: &serde_json::Value,
+) -> Result<Element> {
+    use serde_json::Value;
+
+    let object = object
+        .as_object()
+        .ok_or_else(|| Error::ParsingError("expected an object".to_string()))?;
+    if let Some(unknown) = object
+        .keys()
+        .find(|&key| columns.iter().all(|c| &c.name != key) && (key != LAST || dimensionality == 0))
+    {
+        return Err(Error::ParsingError(format!("unknown field {}", unknown)));
+    }
+    let values = columns
+        .iter()
+        .map(|column| match object.get(&column.name) {
+            None => Ok(None),
+            Some(Value::Number(number)) => parse_value(&number.to_string(), column.width).map(Some),
+            Some(Value::String(text)) => parse_value(text, column.width).map(Some),
+            Some(other) => Err(Error::ParsingError(format!("invalid value {}", other))),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let last = match object.get(LAST) {
+        Some(Value::String(text)) => parse_last(text, dimensionality)?,
+        None if dimensionality == 0 => Vec::new(),
+        _ => parse_last("", dimensionality)?,
+    };
+    element(columns, &values, last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical::{BitCount, Fields};
+    use crate::{PathName, Positive};
+
+    fn stream() -> PhysicalStream {
+        PhysicalStream::try_new(vec![("a", 4), ("b", 100)], 2, 2, 4, vec![]).unwrap()
+    }
+
+    fn bits(value: u64, width: usize) -> Vec<bool> {
+        (0..width)
+            .map(|i| i < 64 && (value >> i) & 1 == 1)
+            .collect()
+    }
+
+    #[test]
+    fn csv() -> Result<()> {
+        let stream = stream();
+        let mut wide = bits(1, 100);
+        wide[99] = true;
+        let elements = vec![
+            Element::new([bits(3, 4), bits(255, 100)].concat()).with_last(vec![false, false]),
+            Element::new([bits(15, 4), wide].concat()).with_last(vec![true, false]),
+            Element::empty(vec![true, true]),
+        ];
+        let csv = write_csv(&stream, &elements)?;
+        assert_eq!(
+            csv,
+            "a,b,last\n3,0xff,00\n15,0x8000000000000000000000001,01\n,,11\n"
+        );
+        assert_eq!(read_csv(&stream, &csv)?, elements);
+
+        // Columns in any order, decimal wide values, comments and blank lines.
+        assert_eq!(
+            read_csv(&stream, "# vectors\nlast, b, a\n\n00, 255, 0x3\n")?,
+            elements[..1]
+        );
+
+        assert!(read_csv(&stream, "").is_err());
+        assert!(read_csv(&stream, "a,last\n3,00\n").is_err());
+        assert!(read_csv(&stream, "a,b,c,last\n").is_err());
+        assert!(read_csv(&stream, "a,b,last\n3,1\n").is_err());
+        assert!(read_csv(&stream, "a,b,last\n16,1,00\n").is_err());
+        assert!(read_csv(&stream, "a,b,last\n3,,00\n").is_err());
+        assert!(read_csv(&stream, "a,b,last\n3,1,0\n").is_err());
+        assert!(read_csv(&stream, "a,b,last\n3,x,00\n").is_err());
+        assert!(read_csv(&stream, "a,b,a,last\n").is_err());
+        let err = read_csv(&stream, "a,b,last\n3,1,00\n3,1,2\n").unwrap_err();
+        assert_eq!(err.notes(), &["while reading line 3".to_string()]);
+
+        assert!(write_csv(&stream, &[Element::new(bits(0, 4))]).is_err());
+        assert!(write_csv(&stream, &[Element::empty(vec![true])]).is_err());
+
+        let bits_only = PhysicalStream::new(
+            Fields::new(vec![(PathName::new_empty(), BitCount::new(8).unwrap())])?,
+            Positive::new(1).unwrap(),
+            0,
+            1,
+            Fields::new(vec![])?,
+        );
+        let csv = write_csv(&bits_only, &[Element::new(bits(42, 8))])?;
+        assert_eq!(csv, "value\n42\n");
+        assert_eq!(read_csv(&bits_only, &csv)?, vec![Element::new(bits(42, 8))]);
+
+        let conflict = PhysicalStream::try_new(vec![("last", 1)], 1, 1, 1, vec![])?;
+        assert!(write_csv(&conflict, &[]).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn json() -> Result<()> {
+        let stream = stream();
+        let elements = vec![
+            Element::new([bits(3, 4), bits(1, 100)].concat()).with_last(vec![true, false]),
+            Element::empty(vec![true, true]),
+        ];
+        let json = write_json(&stream, &elements)?;
+        assert_eq!(
+            json,
+            "[\n  {\"a\":3,\"b\":\"0x1\",\"last\":\"01\"},\n  {\"last\":\"11\"}\n]\n"
+        );
+        assert_eq!(read_json(&stream, &json)?, elements);
+        assert_eq!(
+            read_json(&stream, r#"[{"b": 1, "a": "0x3", "last": "01"}]"#)?,
+            elements[..1]
+        );
+        assert_eq!(write_json(&stream, &[])?, "[]\n");
+
+        assert!(read_json(&stream, "{}").is_err());
+        assert!(read_json(&stream, "[1]").is_err());
+        assert!(read_json(&stream, r#"[{"a": 3, "b": 1}]"#).is_err());
+        assert!(read_json(&stream, r#"[{"a": 3, "last": "00"}]"#).is_err());
+        assert!(read_json(&stream, r#"[{"a": 3, "b": 1, "c": 1, "last": "00"}]"#).is_err());
+        assert!(read_json(&stream, r#"[{"a": 3, "b": true, "last": "00"}]"#).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+//! JavaScript interface for WebAssembly builds.
+//!
+//! The interface is enabled by the `wasm` feature flag, and exposes the
+//! parser and the synthesis of logical types to JavaScript, e.g. for a
+//! browser-based explorer of interfaces. It can be built for the
+//! `wasm32-unknown-unknown` target with [wasm-pack]:
+//!
+//! ```bash
+//! wasm-pack build --target web --no-default-features --features wasm
+//! ```
+//!
+//! Functions return their results as JSON strings, and throw an `Error`
+//! with the message of the error if they fail.
+//!
+//! [wasm-pack]: https://rustwasm.github.io/wasm-pack/
+
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::Library;
+use crate::logical::{LogicalType, SynthesisCache};
+use crate::parser::nom::logical_type;
+use crate::{Document, Identify, Name, Result};
+
+/// Returns the JSON representation of the physical streams and signals a
+/// logical type is synthesized into, with a cache.
+fn synthesized(typ: &LogicalType, cache: &SynthesisCache) -> Value {
+    let synthesized = cache.synthesize(typ);
+    json!({
+        "signals": synthesized
+            .signals()
+            .map(|(path, width)| json!({ "path": path.to_string(), "width": width.get() }))
+            .collect::<Vec<_>>(),
+        "streams": synthesized
+            .streams()
+            .map(|(path, stream)| {
+                json!({
+                    "path": path.to_string(),
+                    "elementLanes": stream.element_lanes().get(),
+                    "dimensionality": stream.dimensionality(),
+                    "complexity": stream.complexity().to_string(),
+                    "bitCount": stream.bit_count(),
+                    "data": stream.data_bit_count(),
+                    "last": stream.last_bit_count(),
+                    "stai": stream.stai_bit_count(),
+                    "endi": stream.endi_bit_count(),
+                    "strb": stream.strb_bit_count(),
+                    "user": stream.user_bit_count(),
+                })
+            })
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Returns the JSON representation of a library parsed from the contents of
+/// a Streamlet Definition File, with its named types and streamlets, and
+/// the physical streams of the interfaces of the streamlets.
+pub fn library_json(name: &str, sdf: &str) -> Result<Value> {
+    let library = Library::from_sdf(Name::try_new(name)?, sdf)?;
+    // Interfaces of the same type are only synthesized once.
+    let cache = SynthesisCache::new();
+    Ok(json!({
+        "name": library.identifier(),
+        "doc": library.doc(),
+        "imports": library.imports().map(|l| l.to_string()).collect::<Vec<_>>(),
+        "types": library
+            .types()
+            .map(|(name, typ)| json!({ "name": name.to_string(), "type": typ.to_string() }))
+            .collect::<Vec<_>>(),
+        "streamlets": library
+            .streamlets()
+            .map(|streamlet| {
+                json!({
+                    "name": streamlet.identifier(),
+                    "doc": streamlet.doc(),
+                    "interfaces": streamlet
+                        .interfaces()
+                        .map(|interface| {
+                            json!({
+                                "name": interface.identifier(),
+                                "mode": interface.mode().to_string(),
+                                "doc": interface.doc(),
+                                "type": interface.typ().to_string(),
+                                "physical": synthesized(&interface.typ(), &cache),
+                            })
+                        })
+                        .collect::<Vec<_>>(),
+                })
+            })
+            .collect::<Vec<_>>(),
+    }))
+}
+
+/// Returns the JSON representation of the physical streams and signals a
+/// logical type in Streamlet Definition File syntax is synthesized into.
+pub fn synthesize_json(typ: &str) -> Result<Value> {
+    Ok(synthesized(&logical_type(typ)?, &SynthesisCache::new()))
+}
+
+/// Parse the contents of a Streamlet Definition File as a library with the
+/// given name, and return it as JSON.
+#[wasm_bindgen(js_name = parseLibrary)]
+pub fn parse_library(name: &str, sdf: &str) -> std::result::Result<String, JsError> {
+    library_json(name, sdf)
+        .map(|library| library.to_string())
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Synthesize a logical type in Streamlet Definition File syntax, and return
+/// its physical streams and signals as JSON.
+#[wasm_bindgen]
+pub fn synthesize(typ: &str) -> std::result::Result<String, JsError> {
+    synthesize_json(typ)
+        .map(|synthesized| synthesized.to_string())
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json() -> Result<()> {
+        let library = library_json(
+            "lib",
+            "//! A library.\n\
+             type Byte = Bits<8>;\n\
+             /// A streamlet.\n\
+             Streamlet x (a : in Stream<Byte, t=2>, b : out Group<c: Bits<1>, d: Stream<Byte>>)",
+        )?;
+        assert_eq!(library["name"], "lib");
+        assert_eq!(library["doc"].as_str().map(str::trim), Some("A library."));
+        assert_eq!(library["types"][0]["name"], "Byte");
+        let streamlet = &library["streamlets"][0];
+        assert_eq!(streamlet["name"], "x");
+        assert_eq!(
+            streamlet["doc"].as_str().map(str::trim),
+            Some("A streamlet.")
+        );
+        let a = &streamlet["interfaces"][0];
+        assert_eq!(a["mode"], "in");
+        assert_eq!(a["physical"]["streams"][0]["elementLanes"], 2);
+        assert_eq!(a["physical"]["streams"][0]["data"], 16);
+        let b = &streamlet["interfaces"][1];
+        assert_eq!(
+            b["physical"]["signals"][0],
+            json!({ "path": "c", "width": 1 })
+        );
+        assert_eq!(b["physical"]["streams"][0]["path"], "d");
+        assert!(library_json("lib", "Streamlet x (").is_err());
+
+        let synthesized = synthesize_json("Stream<Bits<4>, d=2, c=7>")?;
+        assert_eq!(synthesized["streams"][0]["dimensionality"], 2);
+        assert_eq!(synthesized["streams"][0]["complexity"], "7");
+        assert!(synthesize_json("Stream<").is_err());
+        Ok(())
+    }
+}